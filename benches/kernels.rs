@@ -0,0 +1,220 @@
+//! Timings of the metric kernels on synthetic data, run with `cargo bench`.
+//!
+//! `cargo bench -- vpin` runs the benchmarks whose name contains `vpin`.
+//! `-- --save FILE` writes the nanoseconds per event of each benchmark to FILE,
+//! and `-- --compare FILE` fails if any is more than 20% slower than in FILE.
+
+use hlp_toshogu::metrics::{self, risk, streaming::StreamingMetricsEngine};
+use hlp_toshogu::model::{AssetInfo, Fill, L2Snapshot, Meta, OrderBookLevel};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const COINS: [&str; 8] = ["BTC", "ETH", "SOL", "DOGE", "AVAX", "ARB", "OP", "HYPE"];
+const MIDS: [i64; 8] = [60_000, 3_000, 150, 1, 30, 1, 2, 20];
+/// Runs of each benchmark; the median is reported.
+const SAMPLES: usize = 5;
+/// Slowdown over `--compare` that counts as a regression.
+const TOLERANCE: f64 = 0.20;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (mut filter, mut save, mut compare) = (None, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = args.next(),
+            "--compare" => compare = args.next(),
+            // Passed by `cargo bench` to every bench target.
+            "--bench" => {}
+            _ => filter = Some(arg),
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let meta = meta();
+    let fills_1e5 = fills(&mut rng, 100_000);
+    let fills_1e6 = fills(&mut rng, 1_000_000);
+    let books_1e5 = books(&mut rng, 100_000, 20);
+    let book_map: HashMap<String, L2Snapshot> = books_1e5.iter().take(COINS.len()).map(|book| (book.coin.clone(), book.clone())).collect();
+
+    let mut bench = Bench { filter, results: BTreeMap::new() };
+    bench.run("vpin/1e5", fills_1e5.len(), || risk::calculate_vpin(&fills_1e5, &meta));
+    bench.run("vpin/1e6", fills_1e6.len(), || risk::calculate_vpin(&fills_1e6, &meta));
+    bench.run("vpin_by_asset/1e6", fills_1e6.len(), || risk::calculate_vpin_by_asset(&fills_1e6));
+    bench.run("depth_at_50bps/1e5", books_1e5.len(), || {
+        books_1e5.iter().map(|book| metrics::calculate_depth_at_bps(book, 50.0)).sum::<Decimal>()
+    });
+    bench.run("order_book_imbalance/1e5", books_1e5.len(), || {
+        books_1e5.iter().map(metrics::calculate_order_book_imbalance).sum::<f64>()
+    });
+    // One cycle's worth: a book per asset and 1e5 fills.
+    bench.run("liquidity_metrics/cycle", 1, || metrics::calculate_liquidity_metrics(&book_map, &fills_1e5, &meta));
+    let (wide_meta, wide_books, wide_fills) = wide_universe(&books_1e5, &fills_1e6, 200);
+    bench.run("liquidity_metrics/200_assets", 1, || metrics::calculate_liquidity_metrics(&wide_books, &fills_1e5, &wide_meta));
+    bench.run("vpin_by_asset/200_assets/1e6", wide_fills.len(), || risk::calculate_vpin_by_asset(&wide_fills));
+    bench.run("streaming/trades/1e6", fills_1e6.len(), || {
+        let mut engine = StreamingMetricsEngine::default();
+        for fill in &fills_1e6 {
+            engine.process_trade(fill.clone());
+        }
+        engine.get_current_vpin()
+    });
+    bench.run("streaming/l2_updates/1e5", books_1e5.len(), || {
+        let mut engine = StreamingMetricsEngine::default();
+        for book in &books_1e5 {
+            engine.process_l2_update(book.clone());
+        }
+        engine.get_depth_realisation_ratio()
+    });
+    bench.run("streaming/orders/1e6", 1_000_000, || {
+        let mut engine = StreamingMetricsEngine::default();
+        for id in 0..500_000 {
+            engine.on_new_order(id, id);
+        }
+        for id in 0..500_000 {
+            engine.on_cancel_or_fill(id, id % 3 != 0, id + 250);
+        }
+        engine.get_phantom_liquidity_metrics().cancellation_rate
+    });
+
+    if let Some(path) = save {
+        let json = serde_json::to_string_pretty(&bench.results).expect("results serialize");
+        std::fs::write(&path, json).unwrap_or_else(|e| panic!("cannot write {}: {}", path, e));
+        println!("saved to {}", path);
+    }
+    if let Some(path) = compare {
+        let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e));
+        let baseline: BTreeMap<String, f64> = serde_json::from_str(&text).unwrap_or_else(|e| panic!("cannot parse {}: {}", path, e));
+        if !bench.compare(&baseline) {
+            std::process::exit(1);
+        }
+    }
+}
+
+struct Bench {
+    filter: Option<String>,
+    /// Median nanoseconds per event by benchmark.
+    results: BTreeMap<String, f64>,
+}
+
+impl Bench {
+    fn run<T>(&mut self, name: &str, events: usize, mut routine: impl FnMut() -> T) {
+        if self.filter.as_ref().is_some_and(|filter| !name.contains(filter.as_str())) {
+            return;
+        }
+        black_box(routine());
+        let mut times: Vec<Duration> = (0..SAMPLES)
+            .map(|_| {
+                let started = Instant::now();
+                black_box(routine());
+                started.elapsed()
+            })
+            .collect();
+        times.sort();
+        let median = times[SAMPLES / 2];
+        let ns_per_event = median.as_nanos() as f64 / events as f64;
+        println!(
+            "{:<28} {:>9.2?}  {:>9.1} ns/event  {:>7.2} M events/s",
+            name,
+            median,
+            ns_per_event,
+            events as f64 / median.as_secs_f64() / 1e6,
+        );
+        self.results.insert(name.to_string(), ns_per_event);
+    }
+
+    /// Prints the change against `baseline`; false if anything regressed.
+    fn compare(&self, baseline: &BTreeMap<String, f64>) -> bool {
+        let mut ok = true;
+        for (name, ns) in &self.results {
+            let Some(before) = baseline.get(name) else {
+                continue;
+            };
+            let change = ns / before - 1.0;
+            let regressed = change > TOLERANCE;
+            println!("{:<28} {:>+7.1}%{}", name, change * 100.0, if regressed { "  REGRESSED" } else { "" });
+            ok &= !regressed;
+        }
+        ok
+    }
+}
+
+fn meta() -> Meta {
+    Meta {
+        universe: COINS
+            .iter()
+            .map(|coin| AssetInfo { name: coin.to_string(), sz_decimals: 3, max_leverage: 20, only_isolated: false, is_delisted: false })
+            .collect(),
+        contexts: HashMap::new(),
+    }
+}
+
+fn fills(rng: &mut StdRng, count: usize) -> Vec<Fill> {
+    (0..count)
+        .map(|i| {
+            let asset = rng.gen_range(0..COINS.len());
+            let side = if rng.gen_bool(0.5) { "B" } else { "A" };
+            Fill {
+                coin: COINS[asset].to_string(),
+                px: price(rng, MIDS[asset]),
+                sz: Decimal::new(rng.gen_range(1..50_000), 3),
+                side: side.to_string(),
+                time: 1_700_000_000_000 + i as u64,
+                start_position: Decimal::ZERO,
+                dir: side.to_string(),
+                closed_pnl: Decimal::new(rng.gen_range(-10_000..10_000), 2),
+                hash: String::new(),
+                oid: i as u64,
+                crossed: rng.gen_bool(0.5),
+                fee: Decimal::new(rng.gen_range(0..100), 2),
+                builder_fee: Decimal::ZERO,
+                expected_fee: None,
+                liquidation: None,
+                users: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+fn books(rng: &mut StdRng, count: usize, depth: i64) -> Vec<L2Snapshot> {
+    (0..count)
+        .map(|i| {
+            let asset = i % COINS.len();
+            let mid = price(rng, MIDS[asset]);
+            let tick = mid / Decimal::from(10_000);
+            let mut level = |side: i64, step: i64| OrderBookLevel {
+                px: mid + tick * Decimal::from(side * step),
+                sz: Decimal::new(rng.gen_range(1..100_000), 3),
+                n: rng.gen_range(1..10),
+            };
+            L2Snapshot {
+                coin: COINS[asset].to_string(),
+                time: 1_700_000_000_000 + i as u64,
+                bids: (1..=depth).map(|step| level(-1, step)).collect(),
+                asks: (1..=depth).map(|step| level(1, step)).collect(),
+            }
+        })
+        .collect()
+}
+
+/// The books and fills spread over `assets` coins named `A0`, `A1`, …
+fn wide_universe(books: &[L2Snapshot], fills: &[Fill], assets: usize) -> (Meta, HashMap<String, L2Snapshot>, Vec<Fill>) {
+    let name = |i: usize| format!("A{}", i % assets);
+    let meta = Meta {
+        universe: (0..assets)
+            .map(|i| AssetInfo { name: name(i), sz_decimals: 3, max_leverage: 20, only_isolated: false, is_delisted: false })
+            .collect(),
+        contexts: HashMap::new(),
+    };
+    let books = books.iter().take(assets).enumerate().map(|(i, book)| (name(i), L2Snapshot { coin: name(i), ..book.clone() })).collect();
+    let fills = fills.iter().enumerate().map(|(i, fill)| Fill { coin: name(i), ..fill.clone() }).collect();
+    (meta, books, fills)
+}
+
+/// `mid` moved by up to 1%, to two decimals.
+fn price(rng: &mut StdRng, mid: i64) -> Decimal {
+    Decimal::new(mid * 100 + rng.gen_range(-mid..=mid), 2)
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+
+use crate::model::{Counterparty, GlobalMetrics};
+
+/// Labels for addresses, from a TOML file of `"0x…" = "label"` lines.
+#[derive(Debug, Clone, Default)]
+pub struct AddressBook {
+    /// By lowercased address.
+    labels: HashMap<String, String>,
+}
+
+impl AddressBook {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read address book {}", path))?;
+        let labels: HashMap<String, String> = toml::from_str(&text).with_context(|| format!("cannot parse address book {}", path))?;
+        Ok(Self { labels: labels.into_iter().map(|(address, label)| (address.to_lowercase(), label)).collect() })
+    }
+
+    /// The book at `path`, or an empty one without a path or when it cannot be
+    /// read, which leaves the addresses unlabeled rather than stopping the
+    /// monitor.
+    pub fn load_or_empty(path: Option<&str>) -> Self {
+        let Some(path) = path else {
+            return Self::default();
+        };
+        match Self::load(path) {
+            Ok(book) => {
+                info!("📇 Loaded {} address labels from {}", book.labels.len(), path);
+                book
+            }
+            Err(e) => {
+                warn!("⚠️ Addresses stay unlabeled: {:#}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn label(&self, address: &str) -> Option<&str> {
+        self.labels.get(&address.to_lowercase()).map(String::as_str)
+    }
+
+    /// Labels the counterparties of the trade tape, the large prints and the
+    /// top trader.
+    pub fn annotate(&self, metrics: &mut GlobalMetrics) {
+        let mut annotate = |counterparty: &mut Counterparty| counterparty.label = self.label(&counterparty.address).map(str::to_string);
+        let large_prints = metrics.risk_metrics.large_prints.iter_mut().map(|print| &mut print.trade);
+        for trade in metrics.trade_tape.iter_mut().chain(large_prints) {
+            [&mut trade.buyer, &mut trade.seller].into_iter().flatten().for_each(&mut annotate);
+        }
+        metrics.liquidity_metrics.top_trader.iter_mut().for_each(annotate);
+    }
+}
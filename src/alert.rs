@@ -1,126 +1,255 @@
+use crate::config::AlertThresholds;
 use crate::model::{Alert, AlertLevel, GlobalMetrics};
 use chrono::Utc;
+use rust_decimal::prelude::ToPrimitive;
 use uuid::Uuid;
 
-pub fn check_alerts(metrics: &GlobalMetrics) -> Vec<Alert> {
+/// Raises alerts for every metric past its configured cut-off. The same
+/// thresholds drive the dashboard's value colors, so a red reading always
+/// has a matching Critical alert and a yellow one a Warning.
+pub fn check_alerts(metrics: &GlobalMetrics, thresholds: &AlertThresholds) -> Vec<Alert> {
     let mut alerts = Vec::new();
+    let risk = &metrics.risk_metrics;
+    let liquidity = &metrics.liquidity_metrics;
+    // Thin trading and wide quotes inflate both readings without anyone trading on information.
+    let dead_hours = match liquidity.illiquid_assets.is_empty() {
+        true => String::new(),
+        false => format!(" (illiquid hours in {})", liquidity.illiquid_assets.join(", ")),
+    };
     
-    if metrics.risk_metrics.vpin_score > 0.7 {
-        alerts.push(create_alert(
-            AlertLevel::Critical,
-            "VPIN".to_string(),
-            format!("Extreme toxic flow detected: {:.3}", metrics.risk_metrics.vpin_score),
-            metrics.risk_metrics.vpin_score,
-            0.7,
-        ));
-    } else if metrics.risk_metrics.vpin_score > 0.5 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "VPIN".to_string(),
-            format!("High toxic flow detected: {:.3}", metrics.risk_metrics.vpin_score),
-            metrics.risk_metrics.vpin_score,
-            0.5,
-        ));
-    }
+    push_tiered(
+        &mut alerts,
+        "VPIN",
+        risk.vpin_score,
+        (thresholds.vpin_warning, thresholds.vpin_critical),
+        |v| format!("High toxic flow detected: {:.3}{}", v, dead_hours),
+        |v| format!("Extreme toxic flow detected: {:.3}{}", v, dead_hours),
+    );
     
-    if metrics.risk_metrics.phantom_liquidity_index > 0.6 {
-        alerts.push(create_alert(
-            AlertLevel::Critical,
-            "Phantom Liquidity".to_string(),
-            format!("Severely compromised liquidity: {:.1}%", metrics.risk_metrics.phantom_liquidity_index * 100.0),
-            metrics.risk_metrics.phantom_liquidity_index,
-            0.6,
-        ));
-    } else if metrics.risk_metrics.phantom_liquidity_index > 0.4 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Phantom Liquidity".to_string(),
-            format!("Significant phantom liquidity: {:.1}%", metrics.risk_metrics.phantom_liquidity_index * 100.0),
-            metrics.risk_metrics.phantom_liquidity_index,
-            0.4,
-        ));
+    push_tiered(
+        &mut alerts,
+        "Phantom Liquidity",
+        risk.phantom_liquidity_index,
+        (thresholds.phantom_liquidity_warning, thresholds.phantom_liquidity_critical),
+        |v| format!("Significant phantom liquidity: {:.1}%{}", v * 100.0, dead_hours),
+        |v| format!("Severely compromised liquidity: {:.1}%{}", v * 100.0, dead_hours),
+    );
+    
+    push_tiered(
+        &mut alerts,
+        "Liquidation Risk",
+        risk.liquidation_risk_score,
+        (thresholds.liquidation_risk_warning, thresholds.liquidation_risk_critical),
+        |v| format!("Elevated liquidation risk: {:.2}", v),
+        |v| format!("Critical liquidation risk: {:.2}", v),
+    );
+    
+    push_tiered(
+        &mut alerts,
+        "Max Drawdown",
+        risk.max_drawdown,
+        (thresholds.max_drawdown_warning, thresholds.max_drawdown_critical),
+        |v| format!("High drawdown: {:.1}%", v * 100.0),
+        |v| format!("Excessive drawdown: {:.1}%", v * 100.0),
+    );
+    
+    push_tiered(
+        &mut alerts,
+        "Utilization",
+        metrics.vault_metrics.utilization_rate,
+        (thresholds.utilization_warning, thresholds.utilization_critical),
+        |v| format!("High capital utilization: {:.1}%", v * 100.0),
+        |v| format!("Capital nearly fully utilized: {:.1}%", v * 100.0),
+    );
+    
+    let window_minutes = thresholds.withdrawal_window_ms / 60_000;
+    push_tiered(
+        &mut alerts,
+        "Vault Withdrawals",
+        crate::metrics::net_withdrawal(&metrics.vault_metrics.flows, thresholds.withdrawal_window_ms),
+        (thresholds.withdrawal_warning, thresholds.withdrawal_critical),
+        |v| format!("Net withdrawals of {:.1}% of TVL in {} min", v * 100.0, window_minutes),
+        |v| format!("Depositor flight: net withdrawals of {:.1}% of TVL in {} min", v * 100.0, window_minutes),
+    );
+    
+    let max_concentration = risk.position_concentration
+        .values()
+        .fold(0.0f64, |acc, &x| acc.max(x));
+    
+    push_tiered(
+        &mut alerts,
+        "Position Concentration",
+        max_concentration,
+        (thresholds.concentration_warning, thresholds.concentration_critical),
+        |v| format!("High position concentration: {:.1}%", v * 100.0),
+        |v| format!("Excessive position concentration: {:.1}%", v * 100.0),
+    );
+    
+    if let Some((coin, spread)) = liquidity.bid_ask_spread_bps
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        push_tiered(
+            &mut alerts,
+            "Spread",
+            *spread,
+            (thresholds.spread_warning_bps, thresholds.spread_critical_bps),
+            |v| format!("Wide {} spread: {:.2} bps", coin, v),
+            |v| format!("Extremely wide {} spread: {:.2} bps", coin, v),
+        );
     }
     
-    if metrics.risk_metrics.liquidation_risk_score > 0.85 {
-        alerts.push(create_alert(
-            AlertLevel::Critical,
-            "Liquidation Risk".to_string(),
-            format!("Critical liquidation risk: {:.2}", metrics.risk_metrics.liquidation_risk_score),
-            metrics.risk_metrics.liquidation_risk_score,
-            0.85,
-        ));
-    } else if metrics.risk_metrics.liquidation_risk_score > 0.7 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Liquidation Risk".to_string(),
-            format!("Elevated liquidation risk: {:.2}", metrics.risk_metrics.liquidation_risk_score),
-            metrics.risk_metrics.liquidation_risk_score,
-            0.7,
-        ));
+    // New listings are checked on their own, against thresholds scaled down for their first hours.
+    let factor = thresholds.probation_factor;
+    for coin in risk.probation.iter().map(|asset| asset.coin.as_str()) {
+        let readings = [
+            ("spread", liquidity.bid_ask_spread_bps.get(coin).copied(), (thresholds.spread_warning_bps, thresholds.spread_critical_bps), " bps", 1.0),
+            ("book imbalance", liquidity.order_book_imbalance.get(coin).map(|v| v.abs()), (thresholds.imbalance_warning, thresholds.imbalance_critical), "", 1.0),
+            ("oracle divergence", risk.oracle_divergence.get(coin).copied(), (thresholds.oracle_divergence_warning, thresholds.oracle_divergence_critical), "%", 100.0),
+            ("VPIN", risk.vpin_by_asset.get(coin).copied(), (thresholds.vpin_warning, thresholds.vpin_critical), "", 1.0),
+        ];
+        for (name, value, (warning, critical), unit, scale) in readings {
+            let Some(value) = value else {
+                continue;
+            };
+            push_tiered(
+                &mut alerts,
+                "New Listing",
+                value,
+                (warning * factor, critical * factor),
+                |v| format!("{} on probation: {} {:.3}{}", coin, name, v * scale, unit),
+                |v| format!("{} on probation: extreme {} {:.3}{}", coin, name, v * scale, unit),
+            );
+        }
     }
     
-    if metrics.risk_metrics.max_drawdown > 0.25 {
-        alerts.push(create_alert(
-            AlertLevel::Critical,
-            "Max Drawdown".to_string(),
-            format!("Excessive drawdown: {:.1}%", metrics.risk_metrics.max_drawdown * 100.0),
-            metrics.risk_metrics.max_drawdown,
-            0.25,
-        ));
-    } else if metrics.risk_metrics.max_drawdown > 0.15 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Max Drawdown".to_string(),
-            format!("High drawdown: {:.1}%", metrics.risk_metrics.max_drawdown * 100.0),
-            metrics.risk_metrics.max_drawdown,
-            0.15,
-        ));
+    if let Some((coin, divergence)) = risk.oracle_divergence
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        push_tiered(
+            &mut alerts,
+            "Oracle Divergence",
+            *divergence,
+            (thresholds.oracle_divergence_warning, thresholds.oracle_divergence_critical),
+            |v| format!("{} mark {:.2}% from oracle", coin, v * 100.0),
+            |v| format!("{} mark dislocated {:.2}% from oracle", coin, v * 100.0),
+        );
     }
     
-    if metrics.vault_metrics.utilization_rate > 0.9 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Utilization".to_string(),
-            format!("High capital utilization: {:.1}%", metrics.vault_metrics.utilization_rate * 100.0),
-            metrics.vault_metrics.utilization_rate,
-            0.9,
-        ));
+    if let Some(lag) = metrics.system_status.block_lag_secs {
+        push_tiered(
+            &mut alerts,
+            "Chain Stall",
+            lag,
+            (thresholds.chain_lag_warning_secs, thresholds.chain_lag_critical_secs),
+            |v| format!("L1 blocks {:.0}s behind the clock: block production degraded", v),
+            |v| format!("L1 chain stalled: no new block for {:.0}s", v),
+        );
     }
     
-    let max_concentration = metrics.risk_metrics.position_concentration
-        .values()
-        .fold(0.0f64, |acc, &x| acc.max(x));
+    if let Some(p95) = metrics.system_status.info_latency_p95_ms() {
+        push_tiered(
+            &mut alerts,
+            "API Latency",
+            p95,
+            (thresholds.api_latency_warning_ms, thresholds.api_latency_critical_ms),
+            |v| format!("Info requests slow: p95 {:.0}ms", v),
+            |v| format!("Info requests degraded: p95 {:.0}ms", v),
+        );
+    }
     
-    if max_concentration > 0.15 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Position Concentration".to_string(),
-            format!("High position concentration: {:.1}%", max_concentration * 100.0),
-            max_concentration,
-            0.15,
-        ));
+    if let Some((coin, distance)) = metrics.positions
+        .iter()
+        .filter_map(|position| Some((&position.symbol, position.liquidation_distance()?)))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        push_tiered_below(
+            &mut alerts,
+            "Liquidation Distance",
+            distance,
+            (thresholds.liquidation_distance_warning, thresholds.liquidation_distance_critical),
+            |v| format!("{} position {:.1}% from its liquidation price", coin, v * 100.0),
+            |v| format!("{} position about to be liquidated: {:.1}% from its liquidation price", coin, v * 100.0),
+        );
     }
     
-    if metrics.liquidity_metrics.cancel_rate > 0.5 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Cancel Rate".to_string(),
-            format!("High order cancel rate: {:.1}%", metrics.liquidity_metrics.cancel_rate * 100.0),
-            metrics.liquidity_metrics.cancel_rate,
-            0.5,
-        ));
+    push_tiered(
+        &mut alerts,
+        "Whale Activity",
+        risk.whale_activity,
+        (thresholds.whale_activity_warning, thresholds.whale_activity_critical),
+        |v| format!("Large prints are {:.0}% of recent volume", v * 100.0),
+        |v| format!("Concentrated aggressive size: large prints are {:.0}% of recent volume", v * 100.0),
+    );
+    
+    push_tiered(
+        &mut alerts,
+        "Backstop Drawdown",
+        risk.backstop.drawdown(),
+        (thresholds.backstop_drawdown_warning, thresholds.backstop_drawdown_critical),
+        |v| format!("Backstop accounts down {:.1}% from their recent high", v * 100.0),
+        |v| format!("Exchange backstop draining: down {:.1}% from its recent high", v * 100.0),
+    );
+    
+    // Only held assets: a capped market is only a problem for a position that has to unwind into it.
+    if let Some((coin, usage)) = risk.open_interest_cap_usage
+        .iter()
+        .filter(|(coin, _)| metrics.positions.iter().any(|position| &position.symbol == *coin))
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        push_tiered(
+            &mut alerts,
+            "OI Cap",
+            *usage,
+            (thresholds.oi_cap_warning, thresholds.oi_cap_critical),
+            |v| format!("{} open interest at {:.0}% of its cap", coin, v * 100.0),
+            |v| format!("{} open interest at {:.0}% of its cap: the position may not unwind cleanly", coin, v * 100.0),
+        );
     }
     
-    if metrics.liquidity_metrics.fleeting_order_ratio > 0.2 {
-        alerts.push(create_alert(
-            AlertLevel::Warning,
-            "Fleeting Orders".to_string(),
-            format!("High fleeting order ratio: {:.1}%", metrics.liquidity_metrics.fleeting_order_ratio * 100.0),
-            metrics.liquidity_metrics.fleeting_order_ratio,
-            0.2,
-        ));
+    if let Some((coin, imbalance)) = liquidity.order_book_imbalance
+        .iter()
+        .max_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        push_tiered(
+            &mut alerts,
+            "Order Book Imbalance",
+            imbalance.abs(),
+            (thresholds.imbalance_warning, thresholds.imbalance_critical),
+            |v| format!("Lopsided {} order book: {:.3}", coin, v),
+            |v| format!("Severely lopsided {} order book: {:.3}", coin, v),
+        );
     }
     
+    push_tiered(
+        &mut alerts,
+        "Cancel Rate",
+        liquidity.cancel_rate,
+        (thresholds.cancel_rate_warning, thresholds.cancel_rate_critical),
+        |v| format!("High order cancel rate: {:.1}%", v * 100.0),
+        |v| format!("Extreme order cancel rate: {:.1}%", v * 100.0),
+    );
+    
+    push_tiered(
+        &mut alerts,
+        "Fleeting Orders",
+        liquidity.fleeting_order_ratio,
+        (thresholds.fleeting_ratio_warning, thresholds.fleeting_ratio_critical),
+        |v| format!("High fleeting order ratio: {:.1}%", v * 100.0),
+        |v| format!("Extreme fleeting order ratio: {:.1}%", v * 100.0),
+    );
+    
+    let fees = &metrics.performance_metrics.fees;
+    push_tiered(
+        &mut alerts,
+        "Fee Excess",
+        fees.excess_bps,
+        (thresholds.fee_excess_warning_bps, thresholds.fee_excess_critical_bps),
+        |v| format!("Fees {:.2} bps above schedule", v),
+        |v| format!("Fees far above schedule: {:.2} bps, builder fees ${:.2}", v, fees.builder_fees.to_f64().unwrap_or(0.0)),
+    );
+    
     if metrics.performance_metrics.sharpe_ratio < 1.0 {
         alerts.push(create_alert(
             AlertLevel::Info,
@@ -134,6 +263,37 @@ pub fn check_alerts(metrics: &GlobalMetrics) -> Vec<Alert> {
     alerts
 }
 
+fn push_tiered(
+    alerts: &mut Vec<Alert>,
+    metric: &str,
+    value: f64,
+    (warning, critical): (f64, f64),
+    warning_message: impl Fn(f64) -> String,
+    critical_message: impl Fn(f64) -> String,
+) {
+    if value > critical {
+        alerts.push(create_alert(AlertLevel::Critical, metric.to_string(), critical_message(value), value, critical));
+    } else if value > warning {
+        alerts.push(create_alert(AlertLevel::Warning, metric.to_string(), warning_message(value), value, warning));
+    }
+}
+
+/// `push_tiered` for a reading that is worse the lower it goes.
+fn push_tiered_below(
+    alerts: &mut Vec<Alert>,
+    metric: &str,
+    value: f64,
+    (warning, critical): (f64, f64),
+    warning_message: impl Fn(f64) -> String,
+    critical_message: impl Fn(f64) -> String,
+) {
+    if value < critical {
+        alerts.push(create_alert(AlertLevel::Critical, metric.to_string(), critical_message(value), value, critical));
+    } else if value < warning {
+        alerts.push(create_alert(AlertLevel::Warning, metric.to_string(), warning_message(value), value, warning));
+    }
+}
+
 fn create_alert(
     level: AlertLevel,
     metric: String,
@@ -149,5 +309,57 @@ fn create_alert(
         timestamp: Utc::now(),
         value,
         threshold,
+        acknowledged: false,
+    }
+}
+
+/// An Info alert about the monitor itself rather than a metric crossing a threshold.
+pub fn notice(metric: &str, message: String) -> Alert {
+    create_alert(AlertLevel::Info, metric.to_string(), message, 0.0, 0.0)
+}
+
+/// An alert about something that happened rather than a metric crossing a
+/// threshold, raised once.
+pub fn event(level: AlertLevel, metric: &str, message: String) -> Alert {
+    create_alert(level, metric.to_string(), message, 0.0, 0.0)
+}
+
+/// A Critical alert about the monitor itself, such as a task that died.
+pub fn failure(metric: &str, message: String) -> Alert {
+    create_alert(AlertLevel::Critical, metric.to_string(), message, 0.0, 0.0)
+}
+
+/// Marks every alert acknowledged, returning how many were not already.
+pub fn acknowledge_all(alerts: &mut [Alert]) -> usize {
+    let mut acknowledged = 0;
+    for alert in alerts.iter_mut().filter(|a| !a.acknowledged) {
+        alert.acknowledged = true;
+        acknowledged += 1;
+    }
+    acknowledged
+}
+
+/// Marks the alert with `id` acknowledged; false if there is none.
+pub fn acknowledge(alerts: &mut [Alert], id: &str) -> bool {
+    match alerts.iter_mut().find(|a| a.id == id) {
+        Some(alert) => {
+            alert.acknowledged = true;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Latest unacknowledged Critical alert per metric, newest first.
+pub fn unacknowledged_critical(alerts: &[Alert]) -> Vec<&Alert> {
+    let mut latest: Vec<&Alert> = Vec::new();
+    for alert in alerts.iter().rev() {
+        if alert.level == AlertLevel::Critical
+            && !alert.acknowledged
+            && !latest.iter().any(|a| a.metric == alert.metric)
+        {
+            latest.push(alert);
+        }
     }
+    latest
 }
\ No newline at end of file
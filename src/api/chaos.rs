@@ -0,0 +1,98 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::ChaosSettings;
+
+/// A fault to answer an info request with instead of Hyperliquid's response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HttpFault {
+    /// 429 Too Many Requests.
+    RateLimited,
+    /// 500 Internal Server Error.
+    ServerError,
+}
+
+impl HttpFault {
+    pub fn status(self) -> reqwest::StatusCode {
+        match self {
+            HttpFault::RateLimited => reqwest::StatusCode::TOO_MANY_REQUESTS,
+            HttpFault::ServerError => reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Decides which requests and streamed messages of the Hyperliquid connection
+/// go wrong under `[chaos]`, so the reconnect, staleness and alerting paths run
+/// before production needs them. Shared by the info client and the WebSocket.
+/// Nothing goes wrong until `arm`, so start-up, which gives up on the first
+/// failure, is left alone.
+pub struct Chaos {
+    settings: ChaosSettings,
+    rng: Mutex<StdRng>,
+    armed: AtomicBool,
+}
+
+impl Chaos {
+    pub fn new(settings: &ChaosSettings) -> Self {
+        Self { settings: settings.clone(), rng: Mutex::new(StdRng::from_entropy()), armed: AtomicBool::new(false) }
+    }
+
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::Relaxed);
+    }
+
+    fn roll(&self, chance: f64) -> bool {
+        chance > 0.0 && self.armed.load(Ordering::Relaxed) && self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_bool(chance.min(1.0))
+    }
+
+    /// How long to hold this request or message back, if it is delayed.
+    pub fn delay(&self) -> Option<Duration> {
+        if !self.roll(self.settings.delay_rate) {
+            return None;
+        }
+        let ms = self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(0..=self.settings.max_delay_ms);
+        Some(Duration::from_millis(ms))
+    }
+
+    /// The error status to fail this info request with, if it fails.
+    pub fn http_fault(&self) -> Option<HttpFault> {
+        if self.roll(self.settings.http_429_rate) {
+            Some(HttpFault::RateLimited)
+        } else if self.roll(self.settings.http_500_rate) {
+            Some(HttpFault::ServerError)
+        } else {
+            None
+        }
+    }
+
+    /// `text` cut off part way, as a dropped connection leaves a body, if this
+    /// one is malformed.
+    pub fn malform(&self, text: &str) -> Option<String> {
+        if text.is_empty() || !self.roll(self.settings.malformed_rate) {
+            return None;
+        }
+        let mut end = self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(0..text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(text[..end].to_string())
+    }
+
+    /// Whether this streamed message is delivered twice.
+    pub fn duplicate(&self) -> bool {
+        self.roll(self.settings.duplicate_rate)
+    }
+
+    /// How long a new WebSocket connection lasts before it is dropped; drawn
+    /// around `disconnect_secs`, and never dropped when that is 0.
+    pub fn connection_lifetime(&self) -> Option<Duration> {
+        if self.settings.disconnect_secs == 0 || !self.armed.load(Ordering::Relaxed) {
+            return None;
+        }
+        let draw: f64 = self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(f64::EPSILON..1.0);
+        Some(Duration::from_secs_f64(-draw.ln() * self.settings.disconnect_secs as f64))
+    }
+}
@@ -1,2 +1,7 @@
+pub mod chaos;
 pub mod provider;
+pub mod recording;
+pub mod replay;
+pub mod scenario;
 pub mod sdk;
+pub mod wire;
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::model::*;
 
@@ -19,13 +19,25 @@ pub trait DataProvider {
     async fn get_user_state(&self) -> Result<UserState>;
     async fn get_meta(&self) -> Result<Meta>;
     async fn get_recent_fills(&self) -> Result<Vec<Fill>>;
-    async fn get_l2_snapshots(&self) -> Result<HashMap<String, L2Snapshot>>;
+    /// Books of the monitored assets, except those in `live`, which the caller
+    /// already has from the WebSocket.
+    async fn get_l2_snapshots(&self, live: &HashSet<String>) -> Result<HashMap<String, L2Snapshot>>;
+    /// Books of `coins` polled over HTTP although they stream, as an
+    /// independent reading for the shadow comparison.
+    async fn get_shadow_books(&self, coins: &[String]) -> Result<HashMap<String, L2Snapshot>>;
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>>;
+    /// The backstop accounts as of their latest refresh, which may be several
+    /// cycles old.
+    async fn get_backstop(&self) -> Result<Backstop>;
+    async fn get_candles(&self, coin: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<Candle>>;
     async fn get_status(&self) -> DataSourceStatus;
+    /// Starts a collection cycle. Requests the calls of one cycle have in common,
+    /// like the account state behind both the vault summary and the user state,
+    /// are then made once and shared until `end_cycle`.
+    fn begin_cycle(&self) {}
+    /// Ends the collection cycle, so calls outside it, from the control socket,
+    /// the status check or the API, are answered from fresh requests.
+    fn end_cycle(&self) {}
     
     fn as_any(&self) -> &dyn std::any::Any;
 }
-
-
-pub fn parse_decimal(s: &str) -> rust_decimal::Decimal {
-    s.parse().unwrap_or_else(|_| rust_decimal::Decimal::ZERO)
-}
\ No newline at end of file
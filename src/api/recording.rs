@@ -0,0 +1,175 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::api::provider::{DataProvider, DataSourceStatus};
+use crate::config::Config;
+use crate::model::*;
+
+/// Session metadata, written once when recording starts.
+pub const SESSION_FILE: &str = "session.json";
+/// Every provider call of the session, one JSON object per line.
+pub const CALLS_FILE: &str = "calls.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub started: DateTime<Utc>,
+    pub update_interval_ms: u64,
+    pub user_address: String,
+    pub vault_address: Option<String>,
+}
+
+/// One provider call and its outcome, `at_ms` after the recording started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub at_ms: u64,
+    pub call: String,
+    /// Coin of a candle request; other calls take no arguments worth keeping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Wraps a provider and appends every response (or error) it returns to
+/// `<dir>/calls.jsonl`, so the session can be played back with `ReplayProvider`.
+/// `as_any` answers for the wrapped provider, so WebSocket streams and status
+/// keep working as if it were used directly.
+pub struct RecordingProvider<P> {
+    inner: P,
+    started: Instant,
+    out: Mutex<BufWriter<File>>,
+}
+
+impl<P: DataProvider + Send + Sync + 'static> RecordingProvider<P> {
+    pub fn new(inner: P, dir: &Path, config: &Config) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let session = SessionInfo {
+            started: Utc::now(),
+            update_interval_ms: config.update_interval_ms,
+            user_address: config.user_address.clone(),
+            vault_address: config.vault_address.clone(),
+        };
+        fs::write(dir.join(SESSION_FILE), serde_json::to_string_pretty(&session)?)?;
+        let out = File::create(dir.join(CALLS_FILE))?;
+        info!("⏺️ Recording provider calls to {}", dir.display());
+
+        Ok(Self { inner, started: Instant::now(), out: Mutex::new(BufWriter::new(out)) })
+    }
+
+    fn recorded<T: Serialize>(&self, call: &str, coin: Option<&str>, result: Result<T>) -> Result<T> {
+        let (response, error) = match &result {
+            Ok(value) => (serde_json::to_value(value).ok(), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let entry = RecordedCall {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            call: call.to_string(),
+            coin: coin.map(str::to_string),
+            response,
+            error,
+        };
+
+        // Flushed per call so a crash mid-incident keeps everything up to it.
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let written = serde_json::to_writer(&mut *out, &entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(writeln!(out)?))
+            .and_then(|_| Ok(out.flush()?));
+        if let Err(e) = written {
+            warn!("⚠️ Failed to record {} call: {}", call, e);
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<P: DataProvider + Send + Sync + 'static> DataProvider for RecordingProvider<P> {
+    async fn get_vault_summary(&self) -> Result<VaultSummary> {
+        self.recorded("vault_summary", None, self.inner.get_vault_summary().await)
+    }
+
+    async fn get_user_state(&self) -> Result<UserState> {
+        self.recorded("user_state", None, self.inner.get_user_state().await)
+    }
+
+    async fn get_meta(&self) -> Result<Meta> {
+        self.recorded("meta", None, self.inner.get_meta().await)
+    }
+
+    async fn get_recent_fills(&self) -> Result<Vec<Fill>> {
+        self.recorded("recent_fills", None, self.inner.get_recent_fills().await)
+    }
+
+    async fn get_l2_snapshots(&self, live: &HashSet<String>) -> Result<HashMap<String, L2Snapshot>> {
+        self.recorded("l2_snapshots", None, self.inner.get_l2_snapshots(live).await)
+    }
+
+    async fn get_shadow_books(&self, coins: &[String]) -> Result<HashMap<String, L2Snapshot>> {
+        self.inner.get_shadow_books(coins).await
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        self.recorded("open_orders", None, self.inner.get_open_orders().await)
+    }
+
+    async fn get_backstop(&self) -> Result<Backstop> {
+        self.recorded("backstop", None, self.inner.get_backstop().await)
+    }
+
+    async fn get_candles(&self, coin: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<Candle>> {
+        let candles = self.inner.get_candles(coin, interval, start_time, end_time).await;
+        self.recorded("candles", Some(coin), candles)
+    }
+
+    async fn get_status(&self) -> DataSourceStatus {
+        self.inner.get_status().await
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+}
+
+/// Reads a session directory written by `RecordingProvider`.
+pub fn load_session(dir: &Path) -> Result<(SessionInfo, Vec<RecordedCall>)> {
+    let session_path = dir.join(SESSION_FILE);
+    let session: SessionInfo = serde_json::from_str(
+        &fs::read_to_string(&session_path).map_err(|e| anyhow::anyhow!("cannot read {}: {}", session_path.display(), e))?,
+    )?;
+
+    let calls_path = dir.join(CALLS_FILE);
+    let mut calls = Vec::new();
+    for (i, line) in fs::read_to_string(&calls_path)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RecordedCall>(line) {
+            Ok(call) => calls.push(call),
+            // A recording cut off mid-write ends in a partial line.
+            Err(e) => warn!("⚠️ Skipping {} line {}: {}", calls_path.display(), i + 1, e),
+        }
+    }
+    Ok((session, calls))
+}
+
+impl RecordedCall {
+    pub fn result<T: DeserializeOwned>(&self) -> Result<T> {
+        match (&self.response, &self.error) {
+            (Some(response), _) => Ok(T::deserialize(response)?),
+            (None, Some(error)) => Err(anyhow::anyhow!("{} (recorded)", error)),
+            (None, None) => Err(anyhow::anyhow!("recorded {} call has no response", self.call)),
+        }
+    }
+}
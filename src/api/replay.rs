@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use log::info;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::api::provider::{DataProvider, DataSourceStatus};
+use crate::api::recording::{load_session, RecordedCall, SessionInfo};
+use crate::model::*;
+
+/// Plays back a session written by `RecordingProvider`. Each call returns the
+/// latest response recorded for it up to the replay position, which advances at
+/// `speed` times real time; once the recording runs out the last responses are
+/// served unchanged. Errors are replayed as errors.
+pub struct ReplayProvider {
+    session: SessionInfo,
+    /// Recorded calls by name, oldest first.
+    calls: HashMap<String, Vec<RecordedCall>>,
+    duration_ms: u64,
+    speed: f64,
+    started: Instant,
+    finished: AtomicBool,
+}
+
+impl ReplayProvider {
+    pub fn open(dir: &Path, speed: f64) -> Result<Self> {
+        let (session, recorded) = load_session(dir)?;
+        if recorded.is_empty() {
+            return Err(anyhow!("session {} has no recorded calls", dir.display()));
+        }
+
+        let duration_ms = recorded.iter().map(|c| c.at_ms).max().unwrap_or(0);
+        let mut calls: HashMap<String, Vec<RecordedCall>> = HashMap::new();
+        for call in recorded {
+            calls.entry(call.call.clone()).or_default().push(call);
+        }
+        info!(
+            "⏯️ Replaying {} ({}s recorded {}) at {}x",
+            dir.display(),
+            duration_ms / 1000,
+            session.started.format("%Y-%m-%d %H:%M UTC"),
+            speed
+        );
+
+        Ok(Self { session, calls, duration_ms, speed, started: Instant::now(), finished: AtomicBool::new(false) })
+    }
+
+    pub fn session(&self) -> &SessionInfo {
+        &self.session
+    }
+
+    /// Milliseconds into the recording.
+    fn position_ms(&self) -> u64 {
+        let position = (self.started.elapsed().as_millis() as f64 * self.speed) as u64;
+        if position > self.duration_ms && !self.finished.swap(true, Ordering::Relaxed) {
+            info!("⏹️ Replay reached the end of the recording; holding the last state");
+        }
+        position
+    }
+
+    fn replay<T: DeserializeOwned>(&self, call: &str, matches: impl Fn(&RecordedCall) -> bool) -> Result<T> {
+        let position = self.position_ms();
+        let recorded = self.calls.get(call).map(Vec::as_slice).unwrap_or_default();
+        let mut candidates = recorded.iter().filter(|c| matches(c));
+        // Before the first recorded call of a kind, serve that first call.
+        let first = candidates.clone().next();
+        candidates
+            .rfind(|c| c.at_ms <= position)
+            .or(first)
+            .ok_or_else(|| anyhow!("no {} call in the recording", call))?
+            .result()
+    }
+}
+
+#[async_trait]
+impl DataProvider for ReplayProvider {
+    async fn get_vault_summary(&self) -> Result<VaultSummary> {
+        self.replay("vault_summary", |_| true)
+    }
+
+    async fn get_user_state(&self) -> Result<UserState> {
+        self.replay("user_state", |_| true)
+    }
+
+    async fn get_meta(&self) -> Result<Meta> {
+        self.replay("meta", |_| true)
+    }
+
+    async fn get_recent_fills(&self) -> Result<Vec<Fill>> {
+        self.replay("recent_fills", |_| true)
+    }
+
+    async fn get_l2_snapshots(&self, _live: &HashSet<String>) -> Result<HashMap<String, L2Snapshot>> {
+        self.replay("l2_snapshots", |_| true)
+    }
+
+    async fn get_shadow_books(&self, _coins: &[String]) -> Result<HashMap<String, L2Snapshot>> {
+        // A recording has no stream to shadow.
+        Ok(HashMap::new())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        self.replay("open_orders", |_| true)
+    }
+
+    async fn get_backstop(&self) -> Result<Backstop> {
+        // Recordings from before backstop tracking have none.
+        Ok(self.replay("backstop", |_| true).unwrap_or_default())
+    }
+
+    async fn get_candles(&self, coin: &str, _interval: &str, _start_time: u64, _end_time: u64) -> Result<Vec<Candle>> {
+        self.replay("candles", |c| c.coin.as_deref() == Some(coin))
+    }
+
+    async fn get_status(&self) -> DataSourceStatus {
+        DataSourceStatus::Connected
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
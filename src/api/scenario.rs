@@ -0,0 +1,309 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use log::info;
+use rust_decimal::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::api::provider::{DataProvider, DataSourceStatus};
+use crate::model::*;
+use crate::scenario::{Market, Scenario};
+
+/// The calm majors around the scenario's asset, as (coin, mid, max leverage).
+const MAJORS: [(&str, f64, u32); 12] = [
+    ("BTC", 67_500.0, 40),
+    ("ETH", 3_450.0, 25),
+    ("SOL", 165.0, 20),
+    ("XRP", 0.6, 20),
+    ("DOGE", 0.15, 20),
+    ("HYPE", 20.0, 10),
+    ("AVAX", 30.0, 10),
+    ("LINK", 15.0, 10),
+    ("LTC", 80.0, 10),
+    ("SUI", 1.2, 10),
+    ("ARB", 1.0, 10),
+    ("OP", 2.0, 10),
+];
+/// Traded by the vault in each major each cycle, in USD.
+const MAJOR_VOLUME: f64 = 250_000.0;
+/// Notional of each generated fill, well inside a VPIN bucket.
+const FILL_NOTIONAL: f64 = 2_500.0;
+/// Margin the vault's major positions use, as a fraction of TVL.
+const MAJOR_MARGIN: f64 = 0.35;
+/// The vault's long in each major, in USD; together they dwarf a calm
+/// inventory in the scenario's asset.
+const MAJOR_POSITION: f64 = 1_500_000.0;
+const BOOK_LEVELS: u32 = 10;
+
+/// Plays a `Scenario`, one cycle per collection cycle. Every call of a cycle
+/// answers from the same market, so the metrics and alerts come out as the
+/// live pipeline would compute them from such data. After the last phase its
+/// market holds.
+pub struct ScenarioProvider {
+    scenario: Scenario,
+    /// Collection cycles begun so far.
+    cycle: AtomicU32,
+}
+
+impl ScenarioProvider {
+    pub fn new(scenario: Scenario) -> Self {
+        info!("🎬 Playing {} ({} phases, {} cycles)", scenario.name, scenario.phases.len(), scenario.cycles());
+        Self { scenario, cycle: AtomicU32::new(0) }
+    }
+
+    fn market(&self) -> (usize, Market) {
+        self.scenario.at(self.cycle.load(Ordering::Relaxed).max(1))
+    }
+
+    /// The cycle's orders in the scenario's asset, each placed and then
+    /// cancelled or filled `order_lifetime_ms` later, with the cancels spread
+    /// evenly among them. A scenario has no WebSocket, so `--check` plays them
+    /// into the streaming engine itself.
+    pub fn order_events(&self) -> Vec<OrderEvent> {
+        let cycle = self.cycle.load(Ordering::Relaxed).max(1) as u64;
+        let (_, market) = self.market();
+        let placed = now_ms();
+        let lifetime = market.order_lifetime_ms.max(0.0) as u64;
+        (0..market.orders.round().max(0.0) as u64)
+            .flat_map(|i| {
+                let cancelled = ((i + 1) as f64 * market.cancel_share).floor() > (i as f64 * market.cancel_share).floor();
+                let event = |action, timestamp| OrderEvent {
+                    id: cycle * 1_000_000 + i,
+                    action,
+                    coin: self.scenario.coin.clone(),
+                    side: if i % 2 == 0 { "B" } else { "A" }.to_string(),
+                    px: decimal(market.mark_px, 8),
+                    sz: decimal(FILL_NOTIONAL / market.mark_px, 4),
+                    timestamp,
+                };
+                let end = if cancelled { OrderAction::Cancelled } else { OrderAction::Filled };
+                [event(OrderAction::New, placed), event(end, placed + lifetime)]
+            })
+            .collect()
+    }
+
+    /// Margin behind the vault's positions.
+    fn margin(&self, market: &Market) -> f64 {
+        self.scenario.tvl * MAJOR_MARGIN + market.position.abs() / self.scenario.max_leverage as f64
+    }
+}
+
+fn decimal(value: f64, dp: u32) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(dp)
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+/// `count` fills of `coin` at `px` worth `FILL_NOTIONAL`, with the buys spread
+/// evenly among them.
+fn fills(coin: &str, px: f64, count: usize, buy_share: f64, liquidation: bool) -> Vec<Fill> {
+    let time = now_ms();
+    (0..count)
+        .map(|i| {
+            let buy = ((i + 1) as f64 * buy_share).floor() > (i as f64 * buy_share).floor();
+            let side = if buy { "B" } else { "A" };
+            Fill {
+                coin: coin.to_string(),
+                px: decimal(px, 8),
+                sz: decimal(FILL_NOTIONAL / px, 4),
+                side: side.to_string(),
+                time: time - (count - i) as u64,
+                start_position: Decimal::ZERO,
+                dir: if buy { "Open Long" } else { "Open Short" }.to_string(),
+                closed_pnl: Decimal::from(if i % 2 == 0 { 1 } else { 3 }),
+                hash: String::new(),
+                oid: i as u64,
+                crossed: false,
+                fee: decimal(FILL_NOTIONAL * 0.0001, 4),
+                builder_fee: Decimal::ZERO,
+                expected_fee: None,
+                liquidation: liquidation.then(|| FillLiquidation { liquidated_user: None, method: "backstop".to_string() }),
+                users: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// A book around `mid` with `bid_depth` and `ask_depth` USD at the best
+/// prices and `ask_depth` at every level behind them.
+fn book(coin: &str, mid: f64, spread_bps: f64, bid_depth: f64, ask_depth: f64) -> L2Snapshot {
+    let step = mid * spread_bps / 10_000.0;
+    let level = |side: f64, i: u32, depth: f64| {
+        let px = mid + side * step * (i as f64 + 0.5);
+        OrderBookLevel { px: decimal(px, 8), sz: decimal(depth / px, 4), n: 1 + i % 4 }
+    };
+    L2Snapshot {
+        coin: coin.to_string(),
+        time: now_ms(),
+        bids: (0..BOOK_LEVELS).map(|i| level(-1.0, i, if i == 0 { bid_depth } else { ask_depth })).collect(),
+        asks: (0..BOOK_LEVELS).map(|i| level(1.0, i, ask_depth)).collect(),
+    }
+}
+
+#[async_trait]
+impl DataProvider for ScenarioProvider {
+    async fn get_vault_summary(&self) -> Result<VaultSummary> {
+        let (_, market) = self.market();
+        let tvl = self.scenario.tvl;
+        let equity = tvl * (1.0 - market.loss);
+        let margin = self.margin(&market);
+        Ok(VaultSummary {
+            vault_address: self.scenario.name.clone(),
+            name: Some(self.scenario.name.clone()),
+            tvl: decimal(tvl, 2),
+            equity: decimal(equity, 2),
+            apr: 5.0,
+            all_time_pnl: decimal(equity - tvl, 2),
+            max_drawdown: market.loss,
+            share_price: 1.0 - market.loss,
+            num_depositors: 50_000,
+            portfolio_value: decimal(equity, 2),
+            deployed_liquidity: decimal(margin, 2),
+            idle_liquidity: decimal(tvl - margin, 2),
+            flows: Vec::new(),
+            vaults: Vec::new(),
+            // Scenarios stress HLP, whose fills are split like the live vault's.
+            components: true,
+        })
+    }
+
+    async fn get_user_state(&self) -> Result<UserState> {
+        let (_, market) = self.market();
+        let scenario = &self.scenario;
+        let equity = scenario.tvl * (1.0 - market.loss);
+        let leverage = scenario.max_leverage as f64;
+        let position = market.position;
+        let mut positions: Vec<Position> = MAJORS
+            .iter()
+            .map(|(coin, mid, leverage)| Position {
+                symbol: coin.to_string(),
+                size: decimal(MAJOR_POSITION / mid, 4),
+                entry_px: Some(decimal(*mid, 8)),
+                position_value: decimal(MAJOR_POSITION, 2),
+                unrealized_pnl: Decimal::ZERO,
+                margin_used: decimal(MAJOR_POSITION / *leverage as f64, 2),
+                isolated: false,
+                leverage: Some(*leverage),
+                liquidation_px: None,
+            })
+            .collect();
+        if position != 0.0 {
+            positions.push(Position {
+                symbol: scenario.coin.clone(),
+                size: decimal(position / market.mark_px, 2),
+                entry_px: Some(decimal(scenario.start.mark_px, 8)),
+                position_value: decimal(position.abs(), 2),
+                unrealized_pnl: decimal(-market.loss * scenario.tvl, 2),
+                margin_used: decimal(position.abs() / leverage, 2),
+                isolated: false,
+                leverage: Some(scenario.max_leverage),
+                liquidation_px: None,
+            });
+        }
+        Ok(UserState {
+            account_value: decimal(equity, 2),
+            total_margin_used: decimal(self.margin(&market), 2),
+            total_ntl_pos: decimal(scenario.tvl * MAJOR_MARGIN * 10.0 + market.position.abs(), 2),
+            total_raw_usd: decimal(equity, 2),
+            positions,
+            spot_balances: Vec::new(),
+            accounts: Vec::new(),
+        })
+    }
+
+    async fn get_meta(&self) -> Result<Meta> {
+        let (_, market) = self.market();
+        let scenario = &self.scenario;
+        let asset = |name: &str, max_leverage: u32| AssetInfo {
+            name: name.to_string(),
+            sz_decimals: 4,
+            max_leverage,
+            only_isolated: false,
+            is_delisted: false,
+        };
+        let mut universe: Vec<AssetInfo> = MAJORS.iter().map(|(coin, _, leverage)| asset(coin, *leverage)).collect();
+        universe.push(asset(&scenario.coin, scenario.max_leverage));
+
+        let mut contexts: HashMap<String, AssetContext> = MAJORS
+            .iter()
+            .map(|(coin, mid, _)| {
+                let context = AssetContext {
+                    mark_px: decimal(*mid, 8),
+                    oracle_px: decimal(*mid, 8),
+                    open_interest: Decimal::ZERO,
+                    open_interest_cap: None,
+                    at_open_interest_cap: false,
+                };
+                (coin.to_string(), context)
+            })
+            .collect();
+        contexts.insert(scenario.coin.clone(), AssetContext {
+            mark_px: decimal(market.mark_px, 8),
+            oracle_px: decimal(market.mark_px * (1.0 + market.oracle_gap), 8),
+            open_interest: decimal(market.open_interest / market.mark_px, 2),
+            open_interest_cap: Some(decimal(scenario.open_interest_cap, 2)),
+            at_open_interest_cap: market.open_interest >= scenario.open_interest_cap,
+        });
+        Ok(Meta { universe, contexts })
+    }
+
+    async fn get_recent_fills(&self) -> Result<Vec<Fill>> {
+        let (phase, market) = self.market();
+        let per_major = (MAJOR_VOLUME / FILL_NOTIONAL) as usize;
+        let mut recent: Vec<Fill> = MAJORS.iter().flat_map(|(coin, mid, _)| fills(coin, *mid, per_major, 0.5, false)).collect();
+        let count = ((market.volume / FILL_NOTIONAL).round() as usize).max(1);
+        recent.extend(fills(&self.scenario.coin, market.mark_px, count, market.buy_share, self.scenario.phases[phase].liquidations));
+        Ok(recent)
+    }
+
+    async fn get_l2_snapshots(&self, live: &HashSet<String>) -> Result<HashMap<String, L2Snapshot>> {
+        let (_, market) = self.market();
+        let mut books: HashMap<String, L2Snapshot> = MAJORS
+            .iter()
+            .map(|(coin, mid, _)| (coin.to_string(), book(coin, *mid, 1.0, 500_000.0, 500_000.0)))
+            .collect();
+        let coin = &self.scenario.coin;
+        books.insert(coin.clone(), book(coin, market.mark_px, market.spread_bps, market.bid_depth, market.ask_depth));
+        books.retain(|coin, _| !live.contains(coin));
+        Ok(books)
+    }
+
+    async fn get_shadow_books(&self, _coins: &[String]) -> Result<HashMap<String, L2Snapshot>> {
+        // A scenario's books are all polled.
+        Ok(HashMap::new())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_backstop(&self) -> Result<Backstop> {
+        Ok(Backstop::default())
+    }
+
+    async fn get_candles(&self, _coin: &str, _interval: &str, _start_time: u64, _end_time: u64) -> Result<Vec<Candle>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_status(&self) -> DataSourceStatus {
+        DataSourceStatus::Connected
+    }
+
+    fn begin_cycle(&self) {
+        let cycle = self.cycle.fetch_add(1, Ordering::Relaxed) + 1;
+        let (phase, _) = self.scenario.at(cycle);
+        let (previous, _) = self.scenario.at(cycle - 1);
+        if cycle == 1 || phase != previous {
+            info!("🎬 Phase {}/{}: {}", phase + 1, self.scenario.phases.len(), self.scenario.phases[phase].name);
+        }
+        if cycle == self.scenario.cycles() + 1 {
+            info!("⏹️ {} is over; holding its last market", self.scenario.name);
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
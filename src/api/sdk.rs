@@ -1,50 +1,295 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use reqwest::Client;
 use serde_json::Value;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::broadcast;
 use log::{info, warn, error, debug};
+use tracing::Instrument;
 use rust_decimal::prelude::*;
 
-use crate::api::provider::{DataProvider, DataSourceStatus, parse_decimal};
-use crate::config::Config;
+use crate::api::chaos::Chaos;
+use crate::api::provider::{DataProvider, DataSourceStatus};
+use crate::api::wire;
+use crate::config::{BackstopSettings, Config, StreamSettings};
+use crate::latency::LatencyTracker;
+use crate::loading::{LoadingProgress, StepStatus};
 use crate::model::*;
+use crate::pipeline;
+
+/// An account's `userFees`, shared by the cycles until `FEE_REFRESH`.
+type FeeRates = std::sync::Arc<wire::UserFees>;
+
+/// `l2Book` requests in flight at once while polling books.
+const L2_CONCURRENCY: usize = 8;
+/// A polled book older than this is left out of the cycle, so a coin whose
+/// fetches keep failing drops out instead of showing its last book forever.
+const BOOK_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long an account's fee rates are used before `userFees` is asked again;
+/// they only move with its 14-day volume and staking.
+const FEE_REFRESH: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// The most fills a `userFillsByTime` response holds.
+pub const USER_FILLS_PAGE: usize = 2000;
+
+/// Tasks parsing streamed books, besides the one parsing everything else.
+const BOOK_PARSERS: usize = 2;
+
+/// Text messages each parsing task can have waiting before the reader waits for it.
+const PARSE_QUEUE: usize = 1024;
+
+/// Which of `parsers` parsing tasks handles `text`. Books of a coin always go to
+/// the same one of the book parsers, so they stay in order; everything else goes
+/// to the first, so trades and order events keep their order and a burst of
+/// books never delays them. Books are recognized without parsing, from the
+/// compact JSON Hyperliquid sends; anything else falls to the first parser.
+fn parser_for(text: &str, parsers: usize) -> usize {
+    if parsers < 2 || !text.starts_with(r#"{"channel":"l2Book""#) {
+        return 0;
+    }
+    let coin = text.split(r#""coin":""#).nth(1).and_then(|rest| rest.split('"').next()).unwrap_or_default();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::hash::Hash::hash(coin, &mut hasher);
+    1 + (std::hash::Hasher::finish(&hasher) as usize) % (parsers - 1)
+}
+
+/// Polled order books with when each arrived, which outlive the cycle that
+/// fetched them so the books of assets without positions can load in the
+/// background, in `load_books`.
+#[derive(Default)]
+struct BookCache {
+    books: HashMap<String, (std::time::Instant, L2Snapshot)>,
+    /// Requested and not yet answered; not requested again meanwhile.
+    in_flight: HashSet<String>,
+    /// Streamed rather than polled, as of the latest cycle.
+    live: HashSet<String>,
+    /// Waiting for `load_books`, which is running when `loader` is set.
+    queued: Vec<String>,
+    loader: bool,
+}
+
+/// Fetches the books of `coins` into `cache`, `L2_CONCURRENCY` at a time,
+/// returning how many arrived. A failed fetch keeps the previous book until it
+/// is `BOOK_MAX_AGE` old.
+async fn fetch_books(
+    info_client: std::sync::Arc<InfoClient>,
+    cache: std::sync::Arc<std::sync::Mutex<BookCache>>,
+    progress: LoadingProgress,
+    coins: Vec<String>,
+) -> usize {
+    let requests: Vec<_> = coins
+        .iter()
+        .map(|coin| {
+            let info_client = &info_client;
+            async move { (coin, info_client.get_l2_book(coin).await) }
+        })
+        .collect();
+    let fetches = futures_util::stream::iter(requests).buffer_unordered(L2_CONCURRENCY);
+    futures_util::pin_mut!(fetches);
+    let mut fetched = 0;
+    while let Some((coin, book)) = fetches.next().await {
+        let mut cache = lock(&cache);
+        cache.in_flight.remove(coin);
+        match book {
+            Ok(book) => {
+                cache.books.insert(coin.clone(), (std::time::Instant::now(), L2Snapshot { coin: coin.clone(), ..book.into() }));
+                fetched += 1;
+                progress.order_book_loaded();
+                debug!("✅ Successfully fetched L2 for {}", coin);
+            }
+            Err(e) => warn!("⚠️ Failed to get L2 book for {}: {}", coin, e),
+        }
+    }
+    fetched
+}
+
+/// Hands the background books back to the cycle when `load_books` is dropped,
+/// forgetting the requests it had queued or in flight.
+struct LoaderStopped<'a>(&'a std::sync::Mutex<BookCache>);
+
+impl Drop for LoaderStopped<'_> {
+    fn drop(&mut self) {
+        let mut books = lock(self.0);
+        books.loader = false;
+        books.queued.clear();
+        books.in_flight.clear();
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The validator-operated perp DEX, as the `dex` of info requests.
+pub const MAIN_DEX: &str = "";
+
+/// `payload` for a builder-deployed perp DEX; unchanged for `MAIN_DEX`, so
+/// requests for it stay as they were and share the cycle cache.
+fn on_dex(mut payload: Value, dex: &str) -> Value {
+    if dex != MAIN_DEX {
+        payload["dex"] = Value::from(dex);
+    }
+    payload
+}
+
+/// `coin` of `dex` as `dex:COIN`, so it doesn't collide with the main DEX's
+/// or another's.
+fn qualified(dex: &str, coin: String) -> String {
+    match dex == MAIN_DEX || dex_of(&coin).is_some() {
+        true => coin,
+        false => format!("{}:{}", dex, coin),
+    }
+}
+
+/// `meta` of `dex` with its assets qualified by the DEX.
+fn qualify(dex: &str, mut meta: Meta) -> Meta {
+    if dex == MAIN_DEX {
+        return meta;
+    }
+    for asset in meta.universe.iter_mut() {
+        asset.name = qualified(dex, std::mem::take(&mut asset.name));
+    }
+    meta.contexts = meta.contexts.into_iter().map(|(coin, context)| (qualified(dex, coin), context)).collect();
+    meta
+}
+
+/// The assets of every DEX in one `meta`.
+fn merge(metas: Vec<Meta>) -> Meta {
+    let mut merged = Meta { universe: Vec::new(), contexts: HashMap::new() };
+    for meta in metas {
+        merged.universe.extend(meta.universe);
+        merged.contexts.extend(meta.contexts);
+    }
+    merged
+}
+
+/// The WebSocket endpoint served next to an info API URL.
+pub fn websocket_url(api_url: &str) -> String {
+    api_url.replace("https://", "wss://").replace("http://", "ws://") + "/ws"
+}
 
 pub struct HyperliquidProvider {
-    info_client: InfoClient,
+    info_client: std::sync::Arc<InfoClient>,
+    books: std::sync::Arc<std::sync::Mutex<BookCache>>,
+    /// Wakes `load_books` when the cycle queues books for it.
+    books_due: tokio::sync::Notify,
     ws_manager: Option<WsManager>,
     user_address: String,
-    monitored_assets: Vec<String>,
+    /// Summarized from `vaultDetails` when set; when it is also `user_address`,
+    /// the vault's child vaults are monitored along with it.
+    vault_address: Option<String>,
+    /// Monitored with `vault_address`, accounts and all, and summed with it.
+    vaults: Vec<String>,
+    /// The vaults whose fills are split into components.
+    component_vaults: Vec<String>,
+    /// `userFees` by account and when it was fetched; none if it failed.
+    fee_rates: std::sync::Mutex<HashMap<String, (std::time::Instant, Option<FeeRates>)>>,
+    /// Grows when assets are added at runtime through the control socket.
+    monitored_assets: std::sync::RwLock<Vec<String>>,
+    /// Every tradeable asset of the latest `meta`.
+    universe: std::sync::RwLock<Vec<String>>,
+    /// Builder-deployed perp DEXs monitored along with the main one.
+    perp_dexs: Vec<String>,
+    /// Whether the sub-accounts of `user_address` are monitored with it.
+    sub_accounts: bool,
+    /// `open_interest.caps_usd`, attached to each asset's context.
+    open_interest_caps: HashMap<String, Decimal>,
+    backstop_settings: BackstopSettings,
+    /// The latest backstop and when it was fetched.
+    backstop: std::sync::Mutex<(Option<std::time::Instant>, Backstop)>,
+    progress: LoadingProgress,
 }
 
 pub struct InfoClient {
     client: Client,
     base_url: String,
+    last_latency_ms: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    /// Round-trip times by request type, response body included.
+    latency: LatencyTracker,
+    /// Info responses of the current fetch cycle by payload, from `begin_cycle`
+    /// to `end_cycle`; a request made twice in a cycle is sent once.
+    cycle: std::sync::Mutex<Option<HashMap<String, CycleResponse>>>,
+    /// Faults to answer some requests with, under `[chaos]`.
+    chaos: Option<std::sync::Arc<Chaos>>,
 }
 
+/// An info response shared by every request for it in a cycle, including those
+/// made while it is in flight.
+type CycleResponse = std::sync::Arc<tokio::sync::OnceCell<std::result::Result<Value, String>>>;
+
 pub struct WsManager {
     url: String,
-    trade_sender: broadcast::Sender<Fill>,
-    l2_sender: broadcast::Sender<L2Snapshot>,
-    order_sender: broadcast::Sender<OrderEvent>,
+    trade_sender: pipeline::Sender<Fill>,
+    l2_sender: pipeline::Sender<L2Snapshot>,
+    order_sender: pipeline::Sender<OrderEvent>,
+    candle_sender: pipeline::Sender<Candle>,
     connected: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    message_count: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    last_message_ms: std::sync::Arc<std::sync::atomic::AtomicI64>,
+    /// Time spent handling each message.
+    handle_latency: std::sync::Arc<LatencyTracker>,
+    /// Write half of the live connection, kept for later subscriptions.
+    sink: tokio::sync::Mutex<Option<WsSink>>,
+    /// Read half of a new connection, until `read` takes it.
+    source: tokio::sync::Mutex<Option<WsSource>>,
+    /// Drops, delays, duplicates or cuts off some messages, under `[chaos]`.
+    chaos: Option<std::sync::Arc<Chaos>>,
 }
 
+type WsConnection = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+type WsSink = futures_util::stream::SplitSink<WsConnection, Message>;
+type WsSource = futures_util::stream::SplitStream<WsConnection>;
+
 impl InfoClient {
-    pub fn new(base_url: String) -> Self {
+    pub fn new(base_url: String, chaos: Option<std::sync::Arc<Chaos>>) -> Self {
         Self {
             client: Client::new(),
             base_url,
+            last_latency_ms: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            latency: LatencyTracker::new(),
+            cycle: std::sync::Mutex::new(None),
+            chaos,
+        }
+    }
+    
+    /// Round-trip time of the most recent request, or `None` before the first one completes.
+    pub fn last_latency_ms(&self) -> Option<u64> {
+        match self.last_latency_ms.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
         }
     }
     
     pub async fn post_request(&self, endpoint: &str, payload: Value) -> Result<Value> {
+        let request = payload.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+        let span = tracing::info_span!("http_request", endpoint, request, error = tracing::field::Empty);
+        let started = std::time::Instant::now();
+        let result = self.send_request(endpoint, payload).instrument(span.clone()).await;
+        crate::telemetry::record_error(&span, &result);
+        if result.is_ok() {
+            self.latency.record(if request.is_empty() { endpoint } else { &request }, started.elapsed());
+        }
+        result
+    }
+    
+    async fn send_request(&self, endpoint: &str, payload: Value) -> Result<Value> {
         let url = format!("{}/{}", self.base_url, endpoint);
         debug!("📡 Making request to: {} with payload: {}", url, payload);
         
+        if let Some(chaos) = &self.chaos {
+            if let Some(delay) = chaos.delay() {
+                debug!("🐒 Chaos: holding a request back {}ms", delay.as_millis());
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(fault) = chaos.http_fault() {
+                error!("❌ Request failed with status {}: injected by chaos mode", fault.status());
+                return Err(anyhow::anyhow!("Request failed: {} - injected by chaos mode", fault.status()));
+            }
+        }
+        
+        let started = std::time::Instant::now();
         let response = self.client
             .post(&url)
             .json(&payload)
@@ -55,6 +300,9 @@ impl InfoClient {
                 error!("❌ HTTP request failed: {}", e);
                 anyhow::anyhow!("HTTP request failed: {}", e)
             })?;
+        
+        let latency_ms = (started.elapsed().as_millis() as u64).max(1);
+        self.last_latency_ms.store(latency_ms, std::sync::atomic::Ordering::Relaxed);
             
         if !response.status().is_success() {
             let status = response.status();
@@ -63,7 +311,19 @@ impl InfoClient {
             return Err(anyhow::anyhow!("Request failed: {} - {}", status, error_body));
         }
         
-        let result = response.json().await
+        let body = response.text().await
+            .map_err(|e| {
+                error!("❌ Failed to read response: {}", e);
+                anyhow::anyhow!("Failed to read response: {}", e)
+            })?;
+        let body = match self.chaos.as_ref().and_then(|chaos| chaos.malform(&body)) {
+            Some(cut) => {
+                debug!("🐒 Chaos: cutting a response off at {} of {} bytes", cut.len(), body.len());
+                cut
+            }
+            None => body,
+        };
+        let result = serde_json::from_str(&body)
             .map_err(|e| {
                 error!("❌ Failed to parse JSON response: {}", e);
                 anyhow::anyhow!("Failed to parse JSON response: {}", e)
@@ -73,69 +333,222 @@ impl InfoClient {
         Ok(result)
     }
     
-    pub async fn get_clearinghouse_state(&self, user_address: &str) -> Result<Value> {
-        let payload = serde_json::json!({
+    /// Starts a fetch cycle: until `end_cycle`, each distinct info request is
+    /// sent at most once and its response, or failure, shared.
+    pub fn begin_cycle(&self) {
+        *self.cycle.lock().unwrap_or_else(|e| e.into_inner()) = Some(HashMap::new());
+    }
+    
+    /// Drops the responses of the cycle; requests are sent every time again.
+    pub fn end_cycle(&self) {
+        self.cycle.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+    
+    /// An info request whose response must have the shape of `T`.
+    async fn info<T: serde::de::DeserializeOwned>(&self, payload: Value) -> Result<T> {
+        let request = payload["type"].as_str().unwrap_or_default().to_string();
+        let shared = self.cycle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_mut()
+            .map(|responses| responses.entry(payload.to_string()).or_default().clone());
+        let response = match shared {
+            Some(shared) => shared
+                .get_or_init(|| async { self.post_request("info", payload).await.map_err(|e| e.to_string()) })
+                .await
+                .clone()
+                .map_err(anyhow::Error::msg)?,
+            None => self.post_request("info", payload).await?,
+        };
+        serde_json::from_value(response).map_err(|e| {
+            error!("❌ Unexpected {} response: {}", request, e);
+            anyhow::anyhow!("Unexpected {} response: {}", request, e)
+        })
+    }
+    
+    /// The account's margin and positions on `dex`, each DEX being margined apart.
+    pub async fn get_clearinghouse_state(&self, user_address: &str, dex: &str) -> Result<wire::ClearinghouseState> {
+        let payload = on_dex(serde_json::json!({
             "type": "clearinghouseState", 
             "user": user_address
-        });
+        }), dex);
         
         info!("📊 Fetching clearinghouse state for user: {}", user_address);
-        self.post_request("info", payload).await
+        self.info(payload).await
     }
     
-    pub async fn get_meta(&self) -> Result<Value> {
+    pub async fn get_spot_clearinghouse_state(&self, user_address: &str) -> Result<wire::SpotClearinghouseState> {
         let payload = serde_json::json!({
-            "type": "meta"
+            "type": "spotClearinghouseState",
+            "user": user_address
         });
         
+        debug!("📊 Fetching spot balances for: {}", user_address);
+        self.info(payload).await
+    }
+    
+    pub async fn get_spot_meta(&self) -> Result<wire::SpotMeta> {
+        let payload = serde_json::json!({
+            "type": "spotMeta"
+        });
+        
+        debug!("📊 Fetching spot meta information");
+        self.info(payload).await
+    }
+    
+    pub async fn get_meta(&self, dex: &str) -> Result<wire::Meta> {
+        let payload = on_dex(serde_json::json!({
+            "type": "meta"
+        }), dex);
+        
         info!("📊 Fetching meta information");
-        self.post_request("info", payload).await
+        self.info(payload).await
+    }
+    
+    pub async fn get_meta_and_asset_ctxs(&self, dex: &str) -> Result<wire::MetaAndAssetCtxs> {
+        let payload = on_dex(serde_json::json!({
+            "type": "metaAndAssetCtxs"
+        }), dex);
+        
+        debug!("📊 Fetching meta and asset contexts");
+        self.info(payload).await
     }
     
-    pub async fn get_user_fills(&self, user_address: &str) -> Result<Value> {
+    pub async fn get_user_fills(&self, user_address: &str) -> Result<Vec<wire::UserFill>> {
         let payload = serde_json::json!({
             "type": "userFills",
             "user": user_address
         });
         
         info!("📊 Fetching user fills for: {}", user_address);
-        self.post_request("info", payload).await
+        self.info(payload).await
+    }
+    
+    /// Fills of `user_address` from `start_time` to `end_time`, in ms, oldest
+    /// first; at most `USER_FILLS_PAGE`, the rest asked for from the last one on.
+    pub async fn get_user_fills_by_time(&self, user_address: &str, start_time: u64, end_time: u64) -> Result<Vec<wire::UserFill>> {
+        let payload = serde_json::json!({
+            "type": "userFillsByTime",
+            "user": user_address,
+            "startTime": start_time,
+            "endTime": end_time,
+            "aggregateByTime": false
+        });
+        
+        debug!("📊 Fetching fills of {} from {}", user_address, start_time);
+        self.info(payload).await
+    }
+    
+    pub async fn get_open_orders(&self, user_address: &str, dex: &str) -> Result<Vec<wire::OpenOrder>> {
+        let payload = on_dex(serde_json::json!({
+            "type": "openOrders",
+            "user": user_address
+        }), dex);
+        
+        debug!("📊 Fetching open orders for: {}", user_address);
+        self.info(payload).await
     }
     
-    pub async fn get_l2_book(&self, coin: &str) -> Result<Value> {
+    pub async fn get_user_fees(&self, user_address: &str) -> Result<wire::UserFees> {
+        let payload = serde_json::json!({
+            "type": "userFees",
+            "user": user_address
+        });
+        
+        debug!("📊 Fetching fee rates for: {}", user_address);
+        self.info(payload).await
+    }
+    
+    pub async fn get_vault_details(&self, vault_address: &str) -> Result<wire::VaultDetails> {
+        let payload = serde_json::json!({
+            "type": "vaultDetails",
+            "vaultAddress": vault_address
+        });
+        
+        debug!("📊 Fetching vault details for: {}", vault_address);
+        self.info(payload).await
+    }
+    
+    /// The sub-accounts of `user`; Hyperliquid answers null when it has none.
+    pub async fn get_sub_accounts(&self, user: &str) -> Result<Vec<wire::SubAccount>> {
+        let payload = serde_json::json!({
+            "type": "subAccounts",
+            "user": user
+        });
+        
+        debug!("📊 Fetching sub-accounts for: {}", user);
+        let sub_accounts: Option<Vec<wire::SubAccount>> = self.info(payload).await?;
+        Ok(sub_accounts.unwrap_or_default())
+    }
+    
+    pub async fn get_l2_book(&self, coin: &str) -> Result<wire::L2Book> {
         let payload = serde_json::json!({
             "type": "l2Book",
             "coin": coin
         });
         
         debug!("📊 Fetching L2 book for: {}", coin);
-        self.post_request("info", payload).await
+        self.info(payload).await
     }
 
-    #[allow(dead_code)]
-    pub async fn get_all_mids(&self) -> Result<Value> {
+    pub async fn get_candle_snapshot(&self, coin: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<wire::Candle>> {
+        let payload = serde_json::json!({
+            "type": "candleSnapshot",
+            "req": {
+                "coin": coin,
+                "interval": interval,
+                "startTime": start_time,
+                "endTime": end_time
+            }
+        });
+        
+        debug!("📊 Fetching {} candles for: {}", interval, coin);
+        self.info(payload).await
+    }
+
+    pub async fn get_all_mids(&self) -> Result<wire::AllMids> {
         let payload = serde_json::json!({
             "type": "allMids"
         });
         
         debug!("📊 Fetching all mids");
-        self.post_request("info", payload).await
+        self.info(payload).await
+    }
+    
+    /// Perps whose open interest is at its cap.
+    pub async fn get_perps_at_open_interest_cap(&self) -> Result<Vec<String>> {
+        let payload = serde_json::json!({
+            "type": "perpsAtOpenInterestCap"
+        });
+        
+        debug!("📊 Fetching perps at open interest cap");
+        self.info(payload).await
     }
 }
 
 impl WsManager {
-    pub fn new(url: String) -> Self {
-        let (trade_sender, _) = broadcast::channel(1000);
-        let (l2_sender, _) = broadcast::channel(1000);
-        let (order_sender, _) = broadcast::channel(1000);
+    pub fn new(url: String, streams: &StreamSettings, chaos: Option<std::sync::Arc<Chaos>>) -> Self {
+        let trade_sender = pipeline::channel("trades", streams);
+        let l2_sender = pipeline::channel("l2_snapshots", streams);
+        let order_sender = pipeline::channel("order_events", streams);
+        let candle_sender = pipeline::channel("candles", streams);
         let connected = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let message_count = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let last_message_ms = std::sync::Arc::new(std::sync::atomic::AtomicI64::new(0));
         
         Self { 
             url,
             trade_sender,
             l2_sender,
             order_sender,
+            candle_sender,
             connected,
+            message_count,
+            last_message_ms,
+            handle_latency: std::sync::Arc::new(LatencyTracker::new()),
+            sink: tokio::sync::Mutex::new(None),
+            source: tokio::sync::Mutex::new(None),
+            chaos,
         }
     }
     
@@ -144,7 +557,7 @@ impl WsManager {
         info!("🔌 Connecting to WebSocket: {}", ws_url);
         
         let (ws_stream, _) = connect_async(ws_url).await?;
-        let (mut ws_sink, mut ws_stream) = ws_stream.split();
+        let (mut ws_sink, ws_stream) = ws_stream.split();
         
         self.connected.store(true, std::sync::atomic::Ordering::Relaxed);
         info!("✅ WebSocket connected successfully");
@@ -160,98 +573,204 @@ impl WsManager {
         info!("📊 Subscribed to orders");
 
         for asset in assets {
-            let subscribe_msg = serde_json::json!({
-                "method": "subscribe",
-                "subscription": {
-                    "type": "trades",
-                    "coin": asset
-                }
-            });
-            
-            ws_sink.send(Message::Text(subscribe_msg.to_string())).await?;
-            info!("📡 Subscribed to trades for {}", asset);
-            
-            let l2_subscribe_msg = serde_json::json!({
-                "method": "subscribe", 
-                "subscription": {
-                    "type": "l2Book",
-                    "coin": asset
-                }
-            });
-            
-            ws_sink.send(Message::Text(l2_subscribe_msg.to_string())).await?;
-            info!("📊 Subscribed to L2 book for {}", asset);
-
+            Self::subscribe_asset(&mut ws_sink, asset).await?;
         }
+        *self.sink.lock().await = Some(ws_sink);
+        *self.source.lock().await = Some(ws_stream);
         
-        let trade_sender = self.trade_sender.clone();
-        let l2_sender = self.l2_sender.clone();
-        let order_sender = self.order_sender.clone();
-        let connected = self.connected.clone();
+        Ok(())
+    }
+    
+    /// Reads the connection until it ends, handing messages to the parsing
+    /// tasks, which pass them on to the streams. When a previous read ended the
+    /// connection, reconnects to `assets` first.
+    pub async fn read(&self, assets: &[String]) -> Result<()> {
+        let source = self.source.lock().await.take();
+        let mut ws_stream = match source {
+            Some(source) => source,
+            None => {
+                self.connect_and_subscribe(assets).await?;
+                self.source.lock().await.take().ok_or_else(|| anyhow::anyhow!("WebSocket closed while connecting"))?
+            }
+        };
         
-        tokio::spawn(async move {
-            while let Some(msg_result) = ws_stream.next().await {
-                match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if let Err(e) = Self::handle_message(&text, &trade_sender, &l2_sender, &order_sender).await {
-                            warn!("⚠️ Failed to handle WebSocket message: {}", e);
+        // Dropped when the read ends; the tasks finish what is queued and stop.
+        let parsers: Vec<_> = (0..=BOOK_PARSERS).map(|_| self.spawn_parser()).collect();
+        let connected_at = std::time::Instant::now();
+        let lifetime = self.chaos.as_ref().and_then(|chaos| chaos.connection_lifetime());
+        while let Some(msg_result) = ws_stream.next().await {
+            match msg_result {
+                Ok(Message::Text(text)) => {
+                    let mut copies = 1;
+                    let mut text = text;
+                    if let Some(chaos) = &self.chaos {
+                        if lifetime.is_some_and(|lifetime| connected_at.elapsed() >= lifetime) {
+                            warn!("🐒 Chaos: dropping the WebSocket connection");
+                            self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+                            self.sink.lock().await.take();
+                            return Err(anyhow::anyhow!("WebSocket connection dropped by chaos mode"));
+                        }
+                        if let Some(delay) = chaos.delay() {
+                            debug!("🐒 Chaos: holding the stream back {}ms", delay.as_millis());
+                            tokio::time::sleep(delay).await;
+                        }
+                        if let Some(cut) = chaos.malform(&text) {
+                            debug!("🐒 Chaos: cutting a message off at {} of {} bytes", cut.len(), text.len());
+                            text = cut;
+                        }
+                        if chaos.duplicate() {
+                            debug!("🐒 Chaos: delivering a message twice");
+                            copies = 2;
                         }
                     }
-                    Ok(Message::Close(_)) => {
-                        warn!("🔌 WebSocket connection closed");
-                        connected.store(false, std::sync::atomic::Ordering::Relaxed);
-                        break;
+                    self.message_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    self.last_message_ms.store(chrono::Utc::now().timestamp_millis(), std::sync::atomic::Ordering::Relaxed);
+                    let parser = &parsers[parser_for(&text, parsers.len())];
+                    for _ in 0..copies {
+                        if parser.send(text.clone()).await.is_err() {
+                            self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+                            return Err(anyhow::anyhow!("WebSocket message parser stopped"));
+                        }
                     }
-                    Err(e) => {
-                        error!("❌ WebSocket error: {}", e);
-                        connected.store(false, std::sync::atomic::Ordering::Relaxed);
-                        break;
+                }
+                Ok(Message::Close(_)) => {
+                    // Not connected any more if `close` hung up first.
+                    if self.connected.swap(false, std::sync::atomic::Ordering::Relaxed) {
+                        warn!("🔌 WebSocket connection closed");
+                    } else {
+                        debug!("🔌 WebSocket close acknowledged");
                     }
-                    _ => {}
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+                    return Err(e.into());
                 }
+                _ => {}
+            }
+        }
+        self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+    
+    /// Subscribes a running connection to one more asset, for assets added after start-up.
+    pub async fn subscribe(&self, asset: &str) -> Result<()> {
+        match self.sink.lock().await.as_mut() {
+            Some(sink) => Self::subscribe_asset(sink, asset).await,
+            None => Err(anyhow::anyhow!("WebSocket is not connected")),
+        }
+    }
+    
+    /// Stops the trade, book and candle streams of `asset`.
+    pub async fn unsubscribe(&self, asset: &str) -> Result<()> {
+        let mut sink = self.sink.lock().await;
+        let Some(sink) = sink.as_mut() else {
+            return Err(anyhow::anyhow!("WebSocket is not connected"));
+        };
+        for subscription in [
+            serde_json::json!({ "type": "trades", "coin": asset }),
+            serde_json::json!({ "type": "l2Book", "coin": asset }),
+            serde_json::json!({ "type": "candle", "coin": asset, "interval": crate::history::CANDLE_INTERVAL }),
+        ] {
+            let unsubscribe_msg = serde_json::json!({ "method": "unsubscribe", "subscription": subscription });
+            sink.send(Message::Text(unsubscribe_msg.to_string())).await?;
+        }
+        info!("📴 Unsubscribed from {}", asset);
+        Ok(())
+    }
+    
+    async fn subscribe_asset(sink: &mut WsSink, asset: &str) -> Result<()> {
+        let subscribe_msg = serde_json::json!({
+            "method": "subscribe",
+            "subscription": {
+                "type": "trades",
+                "coin": asset
+            }
+        });
+        
+        sink.send(Message::Text(subscribe_msg.to_string())).await?;
+        info!("📡 Subscribed to trades for {}", asset);
+        
+        let l2_subscribe_msg = serde_json::json!({
+            "method": "subscribe", 
+            "subscription": {
+                "type": "l2Book",
+                "coin": asset
+            }
+        });
+        
+        sink.send(Message::Text(l2_subscribe_msg.to_string())).await?;
+        info!("📊 Subscribed to L2 book for {}", asset);
+        
+        let candle_subscribe_msg = serde_json::json!({
+            "method": "subscribe",
+            "subscription": {
+                "type": "candle",
+                "coin": asset,
+                "interval": crate::history::CANDLE_INTERVAL
             }
         });
         
+        sink.send(Message::Text(candle_subscribe_msg.to_string())).await?;
+        debug!("🕯️ Subscribed to candles for {}", asset);
         Ok(())
     }
     
+    /// A task handling the messages sent to it in order.
+    fn spawn_parser(&self) -> tokio::sync::mpsc::Sender<String> {
+        let (sender, mut messages) = tokio::sync::mpsc::channel::<String>(PARSE_QUEUE);
+        let (trade_sender, l2_sender, order_sender, candle_sender) =
+            (self.trade_sender.clone(), self.l2_sender.clone(), self.order_sender.clone(), self.candle_sender.clone());
+        let latency = self.handle_latency.clone();
+        tokio::spawn(async move {
+            while let Some(text) = messages.recv().await {
+                let span = tracing::debug_span!("ws_message", bytes = text.len());
+                let started = std::time::Instant::now();
+                let handled = Self::handle_message(&text, &trade_sender, &l2_sender, &order_sender, &candle_sender);
+                if let Err(e) = handled.instrument(span).await {
+                    warn!("⚠️ Failed to handle WebSocket message: {}", e);
+                }
+                latency.record("ws_message", started.elapsed());
+            }
+        });
+        sender
+    }
+    
     async fn handle_message(
         text: &str,
-        trade_sender: &broadcast::Sender<Fill>,
-        l2_sender: &broadcast::Sender<L2Snapshot>,
-        order_sender: &broadcast::Sender<OrderEvent>,
+        trade_sender: &pipeline::Sender<Fill>,
+        l2_sender: &pipeline::Sender<L2Snapshot>,
+        order_sender: &pipeline::Sender<OrderEvent>,
+        candle_sender: &pipeline::Sender<Candle>,
     ) -> Result<()> {
-        let msg: Value = serde_json::from_str(text)?;
+        let msg: wire::WsMessage = serde_json::from_str(text)?;
         
-        if let Some(channel) = msg.get("channel").and_then(|v| v.as_str()) {
+        if let Some(channel) = msg.channel.as_deref() {
             match channel {
                 "trades" => {
-                    if let Some(data) = msg.get("data") {
-                        for trade_data in data.as_array().unwrap_or(&vec![]) {
-                            let fill = Self::parse_trade(trade_data)?;
-                            if let Err(_) = trade_sender.send(fill) {
-                                debug!("No trade receivers active");
-                            }
+                    for trade in serde_json::from_value::<Vec<wire::Trade>>(msg.data)? {
+                        if trade_sender.send(trade.into()).await == 0 {
+                            debug!("No trade receivers active");
                         }
                     }
                 }
                 "l2Book" => {
-                    if let Some(data) = msg.get("data") {
-                        let snapshot = Self::parse_l2_snapshot(data)?;
-                        if let Err(_) = l2_sender.send(snapshot) {
-                            debug!("No L2 receivers active");
-                        }
+                    let book: wire::L2Book = serde_json::from_value(msg.data)?;
+                    if l2_sender.send(book.into()).await == 0 {
+                        debug!("No L2 receivers active");
                     }
                 }
                 "orders" => {
-                    if let Some(data) = msg.get("data") {
-                        for order in data.as_array().unwrap_or(&vec![]) {
-                            let evt = Self::parse_order_event(order)?;
-                            if let Err(_) = order_sender.send(evt) {
-                                debug!("No order receivers active");
-                            }
+                    for order in serde_json::from_value::<Vec<wire::OrderUpdate>>(msg.data)? {
+                        if order_sender.send(order.try_into()?).await == 0 {
+                            debug!("No order receivers active");
                         }
-
+                    }
+                }
+                "candle" => {
+                    let candle: wire::Candle = serde_json::from_value(msg.data)?;
+                    if candle_sender.send(candle.into()).await == 0 {
+                        debug!("No candle receivers active");
                     }
                 }
                 _ => {
@@ -263,106 +782,69 @@ impl WsManager {
         Ok(())
     }
     
-    fn parse_order_event(data: &Value) -> Result<OrderEvent> {
-        use OrderAction::*;
-        let action = match data["status"].as_str().unwrap_or("") {
-            "open"      => New,
-            "filled"    => Filled,
-            "cancelled" => Cancelled,
-            other       => anyhow::bail!("unknown status {}", other),
-        };
-        Ok(OrderEvent {
-            id:        data["oid"].as_u64().unwrap_or(0),
-            action,
-            coin:      data["coin"].as_str().unwrap_or("").to_string(),
-            side:      data["side"].as_str().unwrap_or("").to_string(),
-            px:        parse_decimal(data["limitPx"].as_str().unwrap_or("0")),
-            sz:        parse_decimal(data["sz"].as_str().unwrap_or("0")),
-            timestamp: data["statusTimestamp"].as_u64().unwrap_or(0),
-        })
-    }
-    
-    fn parse_trade(data: &Value) -> Result<Fill> {
-        Ok(Fill {
-            coin: data["coin"].as_str().unwrap_or("").to_string(),
-            px: parse_decimal(data["px"].as_str().unwrap_or("0")),
-            sz: parse_decimal(data["sz"].as_str().unwrap_or("0")),
-            side: data["side"].as_str().unwrap_or("").to_string(),
-            time: data["time"].as_u64().unwrap_or(0),
-            start_position: parse_decimal("0"),
-            dir: data["side"].as_str().unwrap_or("").to_string(),
-            closed_pnl: parse_decimal("0"),
-            hash: data["tid"].as_str().unwrap_or("").to_string(),
-            oid: 0,
-            crossed: false,
-            fee: parse_decimal("0"),
-        })
-    }
-    
-    fn parse_l2_snapshot(data: &Value) -> Result<L2Snapshot> {
-        let coin = data["coin"].as_str().unwrap_or("").to_string();
-        let time = data["time"].as_u64().unwrap_or(0);
-        
-        let levels = &data["levels"];
-        let bids = levels[0]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        let asks = levels[1]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        Ok(L2Snapshot {
-            coin,
-            time,
-            bids,
-            asks,
-        })
-    }
-    
-    pub fn get_trade_receiver(&self) -> broadcast::Receiver<Fill> {
+    pub fn get_trade_receiver(&self) -> pipeline::Receiver<Fill> {
         self.trade_sender.subscribe()
     }
     
-    pub fn get_l2_receiver(&self) -> broadcast::Receiver<L2Snapshot> {
+    pub fn get_l2_receiver(&self) -> pipeline::Receiver<L2Snapshot> {
         self.l2_sender.subscribe()
     }
 
-    pub fn get_order_receiver(&self) -> broadcast::Receiver<OrderEvent> {
+    pub fn get_order_receiver(&self) -> pipeline::Receiver<OrderEvent> {
         self.order_sender.subscribe()
     }
     
+    pub fn get_candle_receiver(&self) -> pipeline::Receiver<Candle> {
+        self.candle_sender.subscribe()
+    }
+    
+    pub fn stream_stats(&self) -> Vec<StreamStats> {
+        vec![self.trade_sender.stats(), self.l2_sender.stats(), self.order_sender.stats(), self.candle_sender.stats()]
+    }
+    
     pub fn is_connected(&self) -> bool {
         self.connected.load(std::sync::atomic::Ordering::Relaxed)
     }
+    
+    /// Sends a close frame and drops the write half. The reader stops once the
+    /// server acknowledges, which closes the streams.
+    pub async fn close(&self) {
+        let Some(mut sink) = self.sink.lock().await.take() else {
+            return;
+        };
+        self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+        if let Err(e) = sink.send(Message::Close(None)).await {
+            debug!("🔌 WebSocket already closed: {}", e);
+        }
+        info!("🔌 WebSocket closed");
+    }
+    
+    pub fn message_count(&self) -> u64 {
+        self.message_count.load(std::sync::atomic::Ordering::Relaxed)
+    }
+    
+    pub fn last_message_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self.last_message_ms.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => None,
+            ms => chrono::DateTime::from_timestamp_millis(ms),
+        }
+    }
 }
 
 impl HyperliquidProvider {
-    pub async fn new(config: &Config) -> Result<Self> {
+    pub async fn new(config: &Config, progress: LoadingProgress) -> Result<Self> {
         info!("🚀 Initializing HyperliquidProvider with API: {}", config.hyperliquid_api_url);
         
-        let info_client = InfoClient::new(config.hyperliquid_api_url.clone());
+        let chaos = config.chaos.enabled.then(|| {
+            warn!("🐒 Chaos mode: injecting faults into the Hyperliquid connection");
+            std::sync::Arc::new(Chaos::new(&config.chaos))
+        });
+        let info_client = std::sync::Arc::new(InfoClient::new(config.hyperliquid_api_url.clone(), chaos.clone()));
         
         let ws_manager = if config.enable_websocket {
-            let ws_url = config.hyperliquid_api_url
-                .replace("https://", "wss://")
-                .replace("http://", "ws://") + "/ws";
+            let ws_url = websocket_url(&config.hyperliquid_api_url);
             info!("🔌 WebSocket URL: {}", ws_url);
-            Some(WsManager::new(ws_url))
+            Some(WsManager::new(ws_url, &config.streams, chaos.clone()))
         } else {
             info!("🔌 WebSocket disabled in config");
             None
@@ -373,26 +855,40 @@ impl HyperliquidProvider {
         
         let provider = Self {
             info_client,
+            books: Default::default(),
+            books_due: tokio::sync::Notify::new(),
             ws_manager,
             user_address: config.user_address.clone(),
-            monitored_assets: monitored_assets.clone(),
+            vault_address: config.vault_address.clone(),
+            vaults: config.vaults.clone(),
+            component_vaults: config.component_vaults.clone(),
+            fee_rates: Default::default(),
+            monitored_assets: std::sync::RwLock::new(monitored_assets.clone()),
+            universe: Default::default(),
+            perp_dexs: config.perp_dexs.clone(),
+            sub_accounts: config.sub_accounts,
+            open_interest_caps: config.open_interest.caps_usd
+                .iter()
+                .filter_map(|(coin, cap)| Some((coin.clone(), Decimal::from_f64(*cap)?)))
+                .collect(),
+            backstop_settings: config.backstop.clone(),
+            backstop: Default::default(),
+            progress,
         };
         
         info!("✅ Testing API connectivity...");
-        match provider.info_client.get_meta().await {
-            Ok(meta_data) => {
+        let metas = futures_util::future::try_join_all(provider.dexes().into_iter().map(|dex| {
+            let info_client = &provider.info_client;
+            async move { anyhow::Ok(qualify(dex, Meta::from(info_client.get_meta(dex).await?))) }
+        }));
+        match metas.await {
+            Ok(metas) => {
                 info!("✅ API connectivity test successful");
                 
                 // Get all available assets from the universe
-                if let Some(universe) = meta_data.get("universe").and_then(|u| u.as_array()) {
-                    monitored_assets = universe
-                        .iter()
-                        .filter_map(|asset| asset.get("name").and_then(|n| n.as_str()))
-                        .map(|s| s.to_string())
-                        .collect();
-                    
-                    info!("📊 Found {} assets in universe, monitoring all of them", monitored_assets.len());
-                }
+                monitored_assets = merge(metas).universe.into_iter().filter(|asset| !asset.is_delisted).map(|asset| asset.name).collect();
+                info!("📊 Found {} assets in universe, monitoring all of them", monitored_assets.len());
+                provider.progress.meta_fetched(monitored_assets.len());
             }
             Err(e) => {
                 error!("❌ API connectivity test failed: {}", e);
@@ -400,21 +896,44 @@ impl HyperliquidProvider {
             }
         }
         
-        // Create a new provider with all assets
+        let universe = monitored_assets;
+        let monitored_assets = Self::select_assets(&universe, &config.monitored_assets);
+        
+        // Create a new provider with the selected assets
         let provider = Self {
             info_client: provider.info_client,
+            books: provider.books,
+            books_due: provider.books_due,
             ws_manager: provider.ws_manager,
             user_address: provider.user_address,
-            monitored_assets,
+            vault_address: provider.vault_address,
+            vaults: provider.vaults,
+            component_vaults: provider.component_vaults,
+            fee_rates: provider.fee_rates,
+            monitored_assets: std::sync::RwLock::new(monitored_assets),
+            universe: std::sync::RwLock::new(universe),
+            perp_dexs: provider.perp_dexs,
+            sub_accounts: provider.sub_accounts,
+            open_interest_caps: provider.open_interest_caps,
+            backstop_settings: provider.backstop_settings,
+            backstop: provider.backstop,
+            progress: provider.progress,
         };
         
         if let Some(ref ws_manager) = provider.ws_manager {
-            if let Err(e) = ws_manager.connect_and_subscribe(&provider.monitored_assets).await {
-                warn!("⚠️ Failed to connect WebSocket, falling back to HTTP only: {}", e);
+            match ws_manager.connect_and_subscribe(&provider.get_monitored_assets()).await {
+                Ok(()) => provider.progress.websocket(StepStatus::Done(String::new())),
+                Err(e) => {
+                    warn!("⚠️ Failed to connect WebSocket, falling back to HTTP only: {}", e);
+                    provider.progress.websocket(StepStatus::Failed("HTTP fallback".to_string()));
+                }
             }
         }
         
         info!("✅ HyperliquidProvider initialized successfully");
+        if let Some(chaos) = chaos {
+            chaos.arm();
+        }
         Ok(provider)
     }
     
@@ -428,187 +947,667 @@ impl HyperliquidProvider {
         ]
     }
     
-    #[allow(dead_code)]
-    pub async fn update_monitored_assets_from_meta(&mut self, meta: &Meta) {
-        let major_assets: Vec<String> = meta.universe
+    /// Follows the tradeable assets of the latest `meta`: delisted assets stop
+    /// being monitored, and new listings start to when the whole universe is
+    /// monitored or `wanted` names them.
+    pub async fn update_universe(&self, meta: &Meta, wanted: &[String]) {
+        let listed: Vec<String> = crate::universe::listed(meta).map(|asset| asset.name.clone()).collect();
+        let previous = std::mem::replace(&mut *self.universe.write().unwrap_or_else(|e| e.into_inner()), listed.clone());
+        let monitored = self.get_monitored_assets();
+        let target: Vec<String> = monitored
             .iter()
-            .filter(|asset| {
-                let name = &asset.name;
-                name == "BTC" || name == "ETH" || name == "SOL" || 
-                name == "DOGE" || name == "AVAX" || asset.max_leverage >= 10
-            })
-            .take(10)
-            .map(|asset| asset.name.clone())
+            .filter(|coin| listed.contains(coin))
+            .chain(listed.iter().filter(|coin| {
+                !previous.contains(coin) && !monitored.contains(coin) && (wanted.is_empty() || wanted.contains(coin))
+            }))
+            .cloned()
             .collect();
-            
-        if major_assets != self.monitored_assets {
-            info!("📊 Updating monitored assets: {:?}", major_assets);
-            self.monitored_assets = major_assets;
-            
-            if let Some(ref ws_manager) = self.ws_manager {
-                if ws_manager.is_connected() {
-                    if let Err(e) = ws_manager.connect_and_subscribe(&self.monitored_assets).await {
-                        warn!("⚠️ Failed to resubscribe to new assets: {}", e);
-                    }
-                }
-            }
+        if target != monitored {
+            self.switch_monitored_assets(target).await;
         }
     }
     
-    pub fn get_live_trades(&self) -> Option<broadcast::Receiver<Fill>> {
+    pub fn get_live_trades(&self) -> Option<pipeline::Receiver<Fill>> {
         self.ws_manager.as_ref().map(|ws| ws.get_trade_receiver())
     }
     
-    pub fn get_live_l2_updates(&self) -> Option<broadcast::Receiver<L2Snapshot>> {
+    pub fn get_live_l2_updates(&self) -> Option<pipeline::Receiver<L2Snapshot>> {
         self.ws_manager.as_ref().map(|ws| ws.get_l2_receiver())
     }
 
-    pub fn get_live_orders(&self) -> Option<broadcast::Receiver<OrderEvent>> {
+    pub fn get_live_orders(&self) -> Option<pipeline::Receiver<OrderEvent>> {
         self.ws_manager.as_ref().map(|ws| ws.get_order_receiver())
     }
     
-    #[allow(dead_code)]
-    pub fn get_monitored_assets(&self) -> &[String] {
-        &self.monitored_assets
+    /// New receivers of the trade, book and order streams; `None` without WebSocket.
+    pub fn live_streams(&self) -> Option<(pipeline::Receiver<Fill>, pipeline::Receiver<L2Snapshot>, pipeline::Receiver<OrderEvent>)> {
+        Some((self.get_live_trades()?, self.get_live_l2_updates()?, self.get_live_orders()?))
     }
     
-    async fn convert_user_state(&self, data: Value) -> Result<UserState> {
-        debug!("📊 Converting user state data: {}", data);
-        
-        let margin_summary = data.get("marginSummary")
-            .ok_or_else(|| anyhow::anyhow!("Missing marginSummary in response"))?;
-        let cross_margin_summary = data.get("crossMarginSummary")
-            .ok_or_else(|| anyhow::anyhow!("Missing crossMarginSummary in response"))?;
+    pub fn get_live_candles(&self) -> Option<pipeline::Receiver<Candle>> {
+        self.ws_manager.as_ref().map(|ws| ws.get_candle_receiver())
+    }
+    
+    /// Delivery counters of the WebSocket streams; empty without WebSocket.
+    pub fn stream_stats(&self) -> Vec<StreamStats> {
+        self.ws_manager.as_ref().map(|ws| ws.stream_stats()).unwrap_or_default()
+    }
+    
+    /// Total WebSocket text messages received since connecting, if WebSocket is enabled.
+    pub fn ws_message_count(&self) -> Option<u64> {
+        self.ws_manager.as_ref().map(|ws| ws.message_count())
+    }
+    
+    /// When the last WebSocket message arrived; `None` before the first one or without WebSocket.
+    pub fn ws_last_message_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.ws_manager.as_ref().and_then(|ws| ws.last_message_at())
+    }
+    
+    /// Monitored assets with a book, streamed or polled at least once, and all
+    /// monitored assets; the books of assets without positions load after the
+    /// first cycle.
+    pub fn book_coverage(&self) -> (usize, usize) {
+        let monitored = self.get_monitored_assets();
+        let books = lock(&self.books);
+        let covered = monitored.iter().filter(|coin| books.books.contains_key(*coin) || books.live.contains(*coin)).count();
+        (covered, monitored.len())
+    }
+    
+    pub fn last_api_latency_ms(&self) -> Option<u64> {
+        self.info_client.last_latency_ms()
+    }
+    
+    /// Info request latencies by type and overall as `info`, then WebSocket
+    /// message handling as `ws_message`.
+    pub fn latency_stats(&self) -> Vec<LatencyStats> {
+        let mut stats = self.info_client.latency.stats(Some("info"));
+        if let Some(ref ws_manager) = self.ws_manager {
+            stats.extend(ws_manager.handle_latency.stats(None));
+        }
+        stats
+    }
+    
+    pub fn get_monitored_assets(&self) -> Vec<String> {
+        self.monitored_assets.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
+    /// The main DEX, then the builder-deployed ones.
+    fn dexes(&self) -> Vec<&str> {
+        std::iter::once(MAIN_DEX).chain(self.perp_dexs.iter().map(String::as_str)).collect()
+    }
+    
+    /// `wanted` in the universe, or the whole universe when `wanted` is empty.
+    fn select_assets(universe: &[String], wanted: &[String]) -> Vec<String> {
+        if wanted.is_empty() {
+            return universe.to_vec();
+        }
+        for coin in wanted.iter().filter(|coin| !universe.contains(coin)) {
+            warn!("⚠️ Not monitoring {}: it is not in the universe", coin);
+        }
+        wanted.iter().filter(|coin| universe.contains(coin)).cloned().collect()
+    }
+    
+    /// Switches to monitoring `wanted` (empty for the whole universe), subscribing
+    /// to the streams of added assets and unsubscribing from removed ones.
+    pub async fn set_monitored_assets(&self, wanted: &[String]) {
+        let target = Self::select_assets(&self.universe.read().unwrap_or_else(|e| e.into_inner()), wanted);
+        self.switch_monitored_assets(target).await;
+    }
+    
+    async fn switch_monitored_assets(&self, target: Vec<String>) {
+        let current = std::mem::replace(&mut *self.monitored_assets.write().unwrap_or_else(|e| e.into_inner()), target.clone());
+        let added: Vec<&String> = target.iter().filter(|coin| !current.contains(coin)).collect();
+        let removed: Vec<&String> = current.iter().filter(|coin| !target.contains(coin)).collect();
+        info!("📊 Monitoring {} assets ({} added, {} removed)", target.len(), added.len(), removed.len());
         
-        let account_value = parse_decimal(margin_summary["accountValue"].as_str().unwrap_or("0"));
-        let total_margin_used = parse_decimal(cross_margin_summary["totalMarginUsed"].as_str().unwrap_or("0"));
-        let total_ntl_pos = parse_decimal(cross_margin_summary["totalNtlPos"].as_str().unwrap_or("0"));
-        let total_raw_usd = parse_decimal(cross_margin_summary["totalRawUsd"].as_str().unwrap_or("0"));
+        let Some(ref ws_manager) = self.ws_manager else {
+            return;
+        };
+        for coin in added {
+            if let Err(e) = ws_manager.subscribe(coin).await {
+                warn!("⚠️ Polling {} without streaming: {}", coin, e);
+            }
+        }
+        for coin in removed {
+            if let Err(e) = ws_manager.unsubscribe(coin).await {
+                warn!("⚠️ Failed to unsubscribe from {}: {}", coin, e);
+            }
+        }
+    }
+    
+    /// Reads the WebSocket until the connection ends, reconnecting first if it
+    /// already has; the data collection loop supervises it. Returns at once
+    /// without WebSocket.
+    pub async fn read_websocket(&self) -> Result<()> {
+        match self.ws_manager {
+            Some(ref ws_manager) => ws_manager.read(&self.get_monitored_assets()).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Fetches the books each cycle queues for the background, the ones of assets
+    /// without positions, until dropped; the data collection loop runs it under
+    /// the shutdown token. While it runs, cycles no longer wait for those books.
+    pub async fn load_books(&self) {
+        lock(&self.books).loader = true;
+        let _stopped = LoaderStopped(&self.books);
+        loop {
+            self.books_due.notified().await;
+            let coins = std::mem::take(&mut lock(&self.books).queued);
+            if !coins.is_empty() {
+                fetch_books(self.info_client.clone(), self.books.clone(), self.progress.clone(), coins).await;
+            }
+        }
+    }
+    
+    /// Opens a new connection and resubscribes after the previous one ended, so a
+    /// caller can tell a failed reconnect from a read that fails later.
+    pub async fn reconnect_websocket(&self) -> Result<()> {
+        match self.ws_manager {
+            Some(ref ws_manager) => ws_manager.connect_and_subscribe(&self.get_monitored_assets()).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Closes the WebSocket, if there is one, with a close frame. The streams stay
+    /// open: their consumers stop with the shutdown token instead.
+    pub async fn close(&self) {
+        if let Some(ref ws_manager) = self.ws_manager {
+            ws_manager.close().await;
+        }
+    }
+    
+    /// Starts monitoring `coin`: its book is polled from the next cycle on and, with
+    /// WebSocket enabled, its trades, book and candles are streamed. Returns false if
+    /// it was already monitored; fails if Hyperliquid has no book for it.
+    pub async fn add_monitored_asset(&self, coin: &str) -> Result<bool> {
+        if self.get_monitored_assets().iter().any(|asset| asset == coin) {
+            return Ok(false);
+        }
+        self.info_client.get_l2_book(coin).await
+            .map_err(|e| anyhow::anyhow!("no order book for {}: {}", coin, e))?;
         
-        let positions: Vec<_> = data["assetPositions"]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .filter_map(|pos| {
-                let position = pos.get("position")?;
-                Some(Position {
-                    symbol: position["coin"].as_str().unwrap_or("").to_string(),
-                    size: parse_decimal(position["szi"].as_str().unwrap_or("0")),
-                    entry_px: position.get("entryPx").and_then(|v| v.as_str()).map(parse_decimal),
-                    position_value: parse_decimal(position["positionValue"].as_str().unwrap_or("0")),
-                    unrealized_pnl: parse_decimal(position["unrealizedPnl"].as_str().unwrap_or("0")),
-                    margin_used: parse_decimal(position["marginUsed"].as_str().unwrap_or("0")),
-                })
-            })
-            .collect();
-            
+        self.monitored_assets.write().unwrap_or_else(|e| e.into_inner()).push(coin.to_string());
+        info!("📊 Now monitoring {}", coin);
+        if let Some(ref ws_manager) = self.ws_manager {
+            if let Err(e) = ws_manager.subscribe(coin).await {
+                warn!("⚠️ Polling {} without streaming: {}", coin, e);
+            }
+        }
+        Ok(true)
+    }
+    
+    fn convert_user_state(&self, state: wire::ClearinghouseState) -> UserState {
+        let user_state = UserState::from(state);
         info!("✅ Converted user state - Account Value: ${:.2}, Margin Used: ${:.2}, Positions: {}", 
-              account_value.to_f64().unwrap_or(0.0),
-              total_margin_used.to_f64().unwrap_or(0.0),
-              positions.len());
-        
-        Ok(UserState {
-            account_value,
-            total_margin_used,
-            total_ntl_pos,
-            total_raw_usd,
-            positions,
-        })
+              user_state.account_value.to_f64().unwrap_or(0.0),
+              user_state.total_margin_used.to_f64().unwrap_or(0.0),
+              user_state.positions.len());
+        user_state
     }
     
-    async fn convert_meta(&self, data: Value) -> Result<Meta> {
-        debug!("📊 Converting meta data");
-        
-        let universe: Vec<_> = data["universe"]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|asset| AssetInfo {
-                name: asset["name"].as_str().unwrap_or("").to_string(),
-                sz_decimals: asset["szDecimals"].as_u64().unwrap_or(0) as u8,
-                max_leverage: asset["maxLeverage"].as_u64().unwrap_or(1) as u32,
-                only_isolated: asset["onlyIsolated"].as_bool().unwrap_or(false),
+    /// The monitored account and, when it is a parent vault such as HLP, the
+    /// vaults it trades through, whose positions, fills and orders are its own;
+    /// then its sub-accounts when they are monitored, and the accounts of the
+    /// further `vaults`.
+    async fn accounts(&self) -> Vec<String> {
+        let mut accounts = match self.vault_address.as_deref() == Some(self.user_address.as_str()) {
+            true => self.vault_accounts(&self.user_address).await,
+            false => vec![self.user_address.clone()],
+        };
+        for (_, account) in self.sub_accounts().await.into_iter().skip(1) {
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+        for vault in &self.vaults {
+            for account in self.vault_accounts(vault).await {
+                if !accounts.contains(&account) {
+                    accounts.push(account);
+                }
+            }
+        }
+        accounts
+    }
+    
+    /// `vault` and its child vaults.
+    async fn vault_accounts(&self, vault: &str) -> Vec<String> {
+        let mut accounts = vec![vault.to_string()];
+        // Shared with get_vault_summary within the cycle.
+        match self.info_client.get_vault_details(vault).await {
+            Ok(details) => accounts.extend(details.child_addresses().iter().cloned()),
+            Err(e) => warn!("⚠️ Cannot list the child vaults of {}: {}", vault, e),
+        }
+        accounts
+    }
+    
+    /// The names and addresses of `user_address`, as the master, and of its
+    /// sub-accounts, when they are monitored and it has any. A failure leaves
+    /// the sub-accounts out rather than failing the cycle.
+    async fn sub_accounts(&self) -> Vec<(String, String)> {
+        if !self.sub_accounts {
+            return Vec::new();
+        }
+        // Shared by every fetch of the cycle.
+        let sub_accounts = match self.info_client.get_sub_accounts(&self.user_address).await {
+            Ok(sub_accounts) if !sub_accounts.is_empty() => sub_accounts,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                warn!("⚠️ Cannot list the sub-accounts of {}: {}", self.user_address, e);
+                return Vec::new();
+            }
+        };
+        std::iter::once(("Master".to_string(), self.user_address.clone()))
+            .chain(sub_accounts.into_iter().map(|sub_account| (sub_account.name, sub_account.sub_account_user)))
+            .collect()
+    }
+    
+    /// The fills of `account`, each with the fee its schedule charges when
+    /// that is known.
+    async fn fills_of(&self, account: &str) -> Result<Vec<Fill>> {
+        let (fills, rates) = tokio::join!(self.info_client.get_user_fills(account), self.fee_rates(account));
+        Ok(fills?
+            .into_iter()
+            .map(|fill| {
+                let mut fill = Fill::from(fill);
+                // A builder-deployed DEX's deployer sets its own fee share, which the schedule doesn't cover.
+                fill.expected_fee = rates.as_deref().filter(|_| dex_of(&fill.coin).is_none()).map(|rates| expected_fee(&fill, rates));
+                fill
             })
-            .collect();
-            
-        info!("✅ Converted meta - {} assets in universe", universe.len());
-        
-        Ok(Meta {
-            universe,
+            .collect())
+    }
+    
+    async fn fee_rates(&self, account: &str) -> Option<FeeRates> {
+        if let Some((fetched, rates)) = lock(&self.fee_rates).get(account) {
+            if fetched.elapsed() < FEE_REFRESH {
+                return rates.clone();
+            }
+        }
+        let rates = match self.info_client.get_user_fees(account).await {
+            Ok(rates) => Some(std::sync::Arc::new(rates)),
+            Err(e) => {
+                warn!("⚠️ Cannot fetch the fee rates of {}; fees are not checked until the next try: {}", account, e);
+                None
+            }
+        };
+        lock(&self.fee_rates).insert(account.to_string(), (std::time::Instant::now(), rates.clone()));
+        rates
+    }
+    
+    /// The value of a backstop account, spot included.
+    async fn backstop_account(&self, name: &str, address: &str) -> Result<BackstopAccount> {
+        let (state, spot_balances) = tokio::join!(self.info_client.get_clearinghouse_state(address, MAIN_DEX), self.spot_of(address));
+        Ok(BackstopAccount {
+            name: name.to_string(),
+            address: address.to_string(),
+            perp_value: state?.margin_summary.account_value.0,
+            spot_value: spot_balances.iter().map(|balance| balance.value).sum(),
         })
     }
     
-    async fn convert_fills(&self, data: Value) -> Result<Vec<Fill>> {
-        debug!("📊 Converting fills data");
-        
-        let fills: Vec<_> = data
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|fill| Fill {
-                coin: fill["coin"].as_str().unwrap_or("").to_string(),
-                px: parse_decimal(fill["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(fill["sz"].as_str().unwrap_or("0")),
-                side: fill["side"].as_str().unwrap_or("").to_string(),
-                time: fill["time"].as_u64().unwrap_or(0),
-                start_position: parse_decimal(fill["startPosition"].as_str().unwrap_or("0")),
-                dir: fill["dir"].as_str().unwrap_or("").to_string(),
-                closed_pnl: parse_decimal(fill["closedPnl"].as_str().unwrap_or("0")),
-                hash: fill["hash"].as_str().unwrap_or("").to_string(),
-                oid: fill["oid"].as_u64().unwrap_or(0),
-                crossed: fill["crossed"].as_bool().unwrap_or(false),
-                fee: parse_decimal(fill["fee"].as_str().unwrap_or("0")),
-            })
-            .collect();
-            
-        info!("✅ Converted {} fills", fills.len());
-        Ok(fills)
+    /// The value of a backstop vault and its child vaults, as one account.
+    async fn backstop_vault(&self, name: &str, address: &str) -> Result<BackstopAccount> {
+        let details = self.info_client.get_vault_details(address).await?;
+        let children = details.child_addresses().iter().map(|child| self.backstop_account(name, child));
+        let children = futures_util::future::try_join_all(children).await?;
+        let mut vault = self.backstop_account(name, address).await?;
+        for child in children {
+            vault.perp_value += child.perp_value;
+            vault.spot_value += child.spot_value;
+        }
+        Ok(vault)
     }
     
-    async fn convert_l2_snapshot(&self, coin: &str, data: Value) -> Result<L2Snapshot> {
-        let levels = data.get("levels")
-            .ok_or_else(|| anyhow::anyhow!("Missing levels in L2 response for {}", coin))?;
-        
-        let bids = levels[0]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
+    /// The state of `accounts` taken together.
+    async fn state_of(&self, accounts: &[String]) -> Result<UserState> {
+        let names = self.sub_accounts().await;
+        let names = &names;
+        let states = futures_util::future::try_join_all(accounts.iter().map(|account| async move {
+            let states = futures_util::future::try_join_all(self.dexes().into_iter().map(|dex| async move {
+                let mut state = self.convert_user_state(self.info_client.get_clearinghouse_state(account, dex).await?);
+                for position in state.positions.iter_mut() {
+                    position.symbol = qualified(dex, std::mem::take(&mut position.symbol));
+                }
+                anyhow::Ok(state)
+            }));
+            let (states, spot_balances) = tokio::join!(states, self.spot_of(account));
+            // Each DEX margins the account apart; spot is shared by all of them.
+            let mut user_state = combine(states?);
+            user_state.spot_balances = spot_balances;
+            if let Some((name, _)) = names.iter().find(|(_, address)| address == account) {
+                user_state.accounts.push(AccountBreakdown::of(name, account, &user_state));
+            }
+            anyhow::Ok(user_state)
+        }))
+        .await?;
+        Ok(combine(states))
+    }
+    
+    /// The spot balances of `account`, marked at their spot mids. Without them
+    /// the account only looks smaller than it is, so a failure leaves them out
+    /// rather than failing the cycle.
+    async fn spot_of(&self, account: &str) -> Vec<SpotBalance> {
+        let (state, marks) = tokio::join!(self.info_client.get_spot_clearinghouse_state(account), self.spot_marks());
+        let state = match state {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("⚠️ Cannot fetch the spot balances of {}: {}", account, e);
+                return Vec::new();
+            }
+        };
+        let marks = marks.unwrap_or_else(|e| {
+            warn!("⚠️ Cannot mark spot balances: {}", e);
+            HashMap::new()
+        });
+        state.balances
+            .into_iter()
+            .filter(|balance| !balance.total.0.is_zero())
+            .map(|balance| {
+                let mark = match balance.coin.as_str() {
+                    SPOT_QUOTE => Some(Decimal::ONE),
+                    _ => marks.get(&balance.token).copied(),
+                };
+                SpotBalance {
+                    value: mark.map_or(Decimal::ZERO, |mark| mark * balance.total.0),
+                    coin: balance.coin,
+                    total: balance.total.0,
+                    hold: balance.hold.0,
+                }
             })
-            .collect();
-            
-        let asks = levels[1]
-            .as_array()
-            .unwrap_or(&vec![])
+            .collect()
+    }
+    
+    /// The spot mid against USDC of each token by index. Every account shares
+    /// both requests within the cycle.
+    async fn spot_marks(&self) -> Result<HashMap<u32, Decimal>> {
+        let (meta, mids) = tokio::try_join!(self.info_client.get_spot_meta(), self.info_client.get_all_mids())?;
+        let quote = meta.tokens.iter().find(|token| token.name == SPOT_QUOTE).map(|token| token.index);
+        Ok(meta.universe
             .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        Ok(L2Snapshot {
-            coin: coin.to_string(),
-            time: data["time"].as_u64().unwrap_or(0),
-            bids,
-            asks,
+            .filter(|pair| Some(pair.tokens.1) == quote)
+            .filter_map(|pair| Some((pair.tokens.0, mids.get(&pair.name)?.0)))
+            .collect())
+    }
+    
+    /// Summarizes `vault` from its `vaultDetails`: TVL, APR and PnL as the
+    /// vault page shows them, and the drawdown of its all-time PnL. Equity and
+    /// liquidity are those of the vault's own accounts, it and its child vaults,
+    /// so vaults summed by `combine_vaults` are each counted once.
+    async fn get_vault_details_summary(&self, vault: &str) -> Result<VaultSummary> {
+        info!("📊 Fetching vault details for: {}", vault);
+        let details = self.info_client.get_vault_details(vault).await?;
+        let user_state = self.state_of(&self.vault_accounts(vault).await).await?;
+        
+        let latest = |window: &str, history: fn(&wire::Portfolio) -> &Vec<(u64, wire::Num)>| {
+            details.window(window).and_then(|portfolio| history(portfolio).last()).map(|(_, value)| value.0)
+        };
+        let tvl = latest("day", |portfolio| &portfolio.account_value_history).unwrap_or_else(|| user_state.total_value());
+        let all_time_pnl = latest("allTime", |portfolio| &portfolio.pnl_history).unwrap_or_default();
+        let max_drawdown = details.window("allTime").map(pnl_drawdown).unwrap_or(0.0);
+        let share_price = details.window("allTime").map(share_price).unwrap_or(0.0);
+        let flows = details.window("day").map(flows).unwrap_or_default();
+        
+        info!("✅ Vault {} - TVL: ${:.2}, APR: {:.2}%, {} children", 
+              details.name,
+              tvl.to_f64().unwrap_or(0.0),
+              details.apr * 100.0,
+              details.child_addresses().len());
+        
+        let breakdown = VaultBreakdown {
+            name: details.name.clone(),
+            address: details.vault_address.clone(),
+            tvl,
+            equity: user_state.total_value(),
+            apr: details.apr * 100.0,
+            max_drawdown,
+            exposure: user_state.positions.iter().map(|position| position.position_value).sum::<Decimal>() + user_state.spot_exposure(),
+            margin_used: user_state.total_margin_used,
+            positions: user_state.positions.len(),
+            largest_position: user_state.positions
+                .iter()
+                .max_by_key(|position| position.position_value)
+                .map(|position| position.symbol.clone()),
+            pnl_24h: flows.iter().map(|flow| flow.pnl).sum(),
+            net_flow_24h: flows.iter().map(|flow| flow.net_flow).sum(),
+        };
+        let components = self.component_vaults.iter().any(|vault| vault.eq_ignore_ascii_case(&details.vault_address));
+        Ok(VaultSummary {
+            vault_address: details.vault_address,
+            name: Some(details.name),
+            tvl,
+            equity: user_state.total_value(),
+            apr: details.apr * 100.0,
+            all_time_pnl,
+            max_drawdown,
+            share_price,
+            num_depositors: details.followers.len() as u64,
+            portfolio_value: user_state.total_raw_usd,
+            deployed_liquidity: user_state.total_margin_used,
+            idle_liquidity: user_state.total_raw_usd - user_state.total_margin_used,
+            flows,
+            vaults: vec![breakdown],
+            components,
+        })
+    }
+}
+
+/// Several vaults as one: amounts summed, APR and share price weighted by
+/// TVL, the deepest drawdown of any of them, and their flows merged in time.
+fn combine_vaults(summaries: Vec<VaultSummary>) -> VaultSummary {
+    let tvl: Decimal = summaries.iter().map(|summary| summary.tvl).sum();
+    let weighted = |value: fn(&VaultSummary) -> f64| match tvl.to_f64().unwrap_or(0.0) {
+        total if total > 0.0 => summaries.iter().map(|summary| value(summary) * summary.tvl.to_f64().unwrap_or(0.0)).sum::<f64>() / total,
+        _ => 0.0,
+    };
+    let (apr, share_price) = (weighted(|summary| summary.apr), weighted(|summary| summary.share_price));
+    let names: Vec<&str> = summaries.iter().filter_map(|summary| summary.name.as_deref()).collect();
+    VaultSummary {
+        vault_address: summaries.iter().map(|summary| summary.vault_address.as_str()).collect::<Vec<_>>().join(","),
+        name: Some(names.join(" + ")),
+        tvl,
+        equity: summaries.iter().map(|summary| summary.equity).sum(),
+        apr,
+        all_time_pnl: summaries.iter().map(|summary| summary.all_time_pnl).sum(),
+        max_drawdown: summaries.iter().map(|summary| summary.max_drawdown).fold(0.0, f64::max),
+        share_price,
+        num_depositors: summaries.iter().map(|summary| summary.num_depositors).sum(),
+        portfolio_value: summaries.iter().map(|summary| summary.portfolio_value).sum(),
+        deployed_liquidity: summaries.iter().map(|summary| summary.deployed_liquidity).sum(),
+        idle_liquidity: summaries.iter().map(|summary| summary.idle_liquidity).sum(),
+        flows: merge_flows(summaries.iter().map(|summary| summary.flows.as_slice()).collect()),
+        components: summaries.iter().any(|summary| summary.components),
+        vaults: summaries.into_iter().flat_map(|summary| summary.vaults).collect(),
+    }
+}
+
+/// The flows of several vaults on one timeline: at each time any of them has a
+/// point, the latest account value of each summed, and the changes of the
+/// vaults with a point then.
+fn merge_flows(histories: Vec<&[VaultFlow]>) -> Vec<VaultFlow> {
+    let mut times: Vec<u64> = histories.iter().flat_map(|flows| flows.iter().map(|flow| flow.time)).collect();
+    times.sort_unstable();
+    times.dedup();
+    times
+        .into_iter()
+        .map(|time| {
+            let mut merged = VaultFlow { time, account_value: Decimal::ZERO, pnl: Decimal::ZERO, net_flow: Decimal::ZERO };
+            for flows in &histories {
+                if let Some(latest) = flows.iter().rev().find(|flow| flow.time <= time) {
+                    merged.account_value += latest.account_value;
+                    if latest.time == time {
+                        merged.pnl += latest.pnl;
+                        merged.net_flow += latest.net_flow;
+                    }
+                }
+            }
+            merged
         })
+        .collect()
+}
+
+/// The account value history of `portfolio` with each change split into PnL
+/// and deposits less withdrawals: whatever of the change the PnL history does
+/// not account for was moved by depositors.
+fn flows(portfolio: &wire::Portfolio) -> Vec<VaultFlow> {
+    let pnl: HashMap<u64, Decimal> = portfolio.pnl_history.iter().map(|(time, pnl)| (*time, pnl.0)).collect();
+    let mut previous: Option<(Decimal, Decimal)> = None;
+    portfolio
+        .account_value_history
+        .iter()
+        .filter_map(|(time, value)| {
+            let total_pnl = *pnl.get(time)?;
+            let (pnl, net_flow) = match previous {
+                Some((last_value, last_pnl)) => (total_pnl - last_pnl, value.0 - last_value - (total_pnl - last_pnl)),
+                None => (Decimal::ZERO, Decimal::ZERO),
+            };
+            previous = Some((value.0, total_pnl));
+            Some(VaultFlow { time: *time, account_value: value.0, pnl, net_flow })
+        })
+        .collect()
+}
+
+/// The fee `rates` charge for `fill`: the taker rate if it crossed the spread,
+/// else the maker rate, with spot rates for spot pairs (`@107`, `PURR/USDC`).
+/// The account's rates are today's; a fill from a day it was in another volume
+/// tier gets that tier's rate, with the same discount off the schedule.
+fn expected_fee(fill: &Fill, rates: &wire::UserFees) -> Decimal {
+    let spot = fill.coin.starts_with('@') || fill.coin.contains('/');
+    let (user, pick): (_, fn(&wire::FeeRates) -> Decimal) = match (spot, fill.crossed) {
+        (false, true) => (rates.user_cross_rate.0, |tier| tier.cross.0),
+        (false, false) => (rates.user_add_rate.0, |tier| tier.add.0),
+        (true, true) => (rates.user_spot_cross_rate.0, |tier| tier.spot_cross.0),
+        (true, false) => (rates.user_spot_add_rate.0, |tier| tier.spot_add.0),
+    };
+    let day = |ms: i64| chrono::DateTime::from_timestamp_millis(ms).map(|time| time.date_naive());
+    let tiers = day(chrono::Utc::now().timestamp_millis())
+        .zip(day(fill.time as i64))
+        .and_then(|(today, filled)| Some((rates.tier_on(today)?, rates.tier_on(filled)?)));
+    let rate = match tiers {
+        // A rebate is no fraction of the schedule, so it is taken as it is today.
+        Some((now, then)) if pick(now) > Decimal::ZERO => pick(then) * user / pick(now),
+        _ => user,
+    };
+    fill.px * fill.sz.abs() * rate
+}
+
+/// The value of a share issued at $1 when the history starts: compounded by
+/// each interval's PnL as a fraction of the account value it began with, so
+/// deposits and withdrawals do not move it.
+fn share_price(portfolio: &wire::Portfolio) -> f64 {
+    let values: HashMap<u64, Decimal> = portfolio.account_value_history.iter().map(|(time, value)| (*time, value.0)).collect();
+    let mut price = 1.0;
+    for pair in portfolio.pnl_history.windows(2) {
+        let ((start, before), (_, after)) = (&pair[0], &pair[1]);
+        if let Some(value) = values.get(start).filter(|value| **value > Decimal::ZERO) {
+            price *= 1.0 + ((after.0 - before.0) / value).to_f64().unwrap_or(0.0);
+        }
+    }
+    price
+}
+
+/// The deepest fall of cumulative PnL from a high, as a fraction of the
+/// account value at that high, so deposits and withdrawals do not count.
+fn pnl_drawdown(portfolio: &wire::Portfolio) -> f64 {
+    let values: HashMap<u64, Decimal> = portfolio.account_value_history.iter().map(|(time, value)| (*time, value.0)).collect();
+    let mut peak: Option<(Decimal, Decimal)> = None;
+    let mut deepest = 0.0f64;
+    for (time, pnl) in &portfolio.pnl_history {
+        match peak {
+            Some((high, _)) if pnl.0 <= high => {}
+            _ => {
+                peak = Some((pnl.0, values.get(time).copied().unwrap_or_default()));
+                continue;
+            }
+        }
+        if let Some((high, value)) = peak.filter(|(_, value)| *value > Decimal::ZERO) {
+            deepest = deepest.max(((high - pnl.0) / value).to_f64().unwrap_or(0.0));
+        }
+    }
+    deepest
+}
+
+/// Several accounts as one: balances and spot holdings summed, and positions
+/// in the same coin and margin mode netted at their combined entry price.
+fn combine(mut states: Vec<UserState>) -> UserState {
+    if states.len() == 1 {
+        return states.remove(0);
+    }
+    let mut combined = UserState {
+        account_value: Decimal::ZERO,
+        total_margin_used: Decimal::ZERO,
+        total_ntl_pos: Decimal::ZERO,
+        total_raw_usd: Decimal::ZERO,
+        positions: Vec::new(),
+        spot_balances: Vec::new(),
+        accounts: Vec::new(),
+    };
+    // Cost of each coin's position, while every part has an entry price.
+    let mut costs: HashMap<(String, bool), Option<Decimal>> = HashMap::new();
+    for state in states {
+        combined.account_value += state.account_value;
+        combined.total_margin_used += state.total_margin_used;
+        combined.total_ntl_pos += state.total_ntl_pos;
+        combined.total_raw_usd += state.total_raw_usd;
+        combined.accounts.extend(state.accounts);
+        for balance in state.spot_balances {
+            match combined.spot_balances.iter_mut().find(|held| held.coin == balance.coin) {
+                Some(held) => {
+                    held.total += balance.total;
+                    held.hold += balance.hold;
+                    held.value += balance.value;
+                }
+                None => combined.spot_balances.push(balance),
+            }
+        }
+        for position in state.positions {
+            let cost = costs.entry((position.symbol.clone(), position.isolated)).or_insert(Some(Decimal::ZERO));
+            *cost = cost.zip(position.entry_px).map(|(cost, px)| cost + px * position.size);
+            match combined.positions.iter_mut().find(|held| held.symbol == position.symbol && held.isolated == position.isolated) {
+                Some(held) => {
+                    held.size += position.size;
+                    held.position_value += position.position_value;
+                    held.unrealized_pnl += position.unrealized_pnl;
+                    held.margin_used += position.margin_used;
+                    // Each account is liquidated on its own, so the parts
+                    // have no price in common.
+                    held.liquidation_px = None;
+                    held.leverage = held.leverage.max(position.leverage);
+                }
+                None => combined.positions.push(position),
+            }
+        }
     }
+    for position in &mut combined.positions {
+        position.entry_px = costs[&(position.symbol.clone(), position.isolated)].filter(|_| !position.size.is_zero()).map(|cost| cost / position.size);
+    }
+    combined
 }
 
 #[async_trait]
 impl DataProvider for HyperliquidProvider {
+    fn begin_cycle(&self) {
+        self.info_client.begin_cycle();
+    }
+    
+    fn end_cycle(&self) {
+        self.info_client.end_cycle();
+    }
+    
     async fn get_vault_summary(&self) -> Result<VaultSummary> {
+        if let Some(vault) = &self.vault_address {
+            if self.vaults.is_empty() {
+                return self.get_vault_details_summary(vault).await;
+            }
+            let vaults = std::iter::once(vault).chain(&self.vaults);
+            let summaries = futures_util::future::try_join_all(vaults.map(|vault| self.get_vault_details_summary(vault))).await?;
+            return Ok(combine_vaults(summaries));
+        }
         info!("📊 Creating synthetic vault summary from user state");
         
         let user_state = self.get_user_state().await?;
         
-        let tvl = user_state.account_value;
-        let equity = user_state.account_value;
+        let tvl = user_state.total_value();
+        let equity = tvl;
         let portfolio_value = user_state.total_raw_usd;
         let deployed_liquidity = user_state.total_margin_used;
         let idle_liquidity = user_state.total_raw_usd - user_state.total_margin_used;
@@ -637,73 +1636,212 @@ impl DataProvider for HyperliquidProvider {
         
         Ok(VaultSummary {
             vault_address: self.user_address.clone(),
+            name: None,
             tvl,
             equity,
             apr,
             all_time_pnl,
             max_drawdown,
+            share_price: 0.0,
             num_depositors: 1,
             portfolio_value,
             deployed_liquidity,
             idle_liquidity,
+            flows: Vec::new(),
+            vaults: Vec::new(),
+            components: false,
         })
     }
     
     async fn get_user_state(&self) -> Result<UserState> {
-        info!("📊 Fetching user state for: {}", self.user_address);
-        let data = self.info_client.get_clearinghouse_state(&self.user_address).await?;
-        self.convert_user_state(data).await
+        let accounts = self.accounts().await;
+        info!("📊 Fetching user state for: {}", accounts.join(", "));
+        let user_state = self.state_of(&accounts).await?;
+        self.progress.user_state_fetched();
+        Ok(user_state)
     }
     
     async fn get_meta(&self) -> Result<Meta> {
         info!("📊 Fetching meta information");
-        let data = self.info_client.get_meta().await?;
-        self.convert_meta(data).await
+        let metas = futures_util::future::try_join_all(self.dexes().into_iter().map(|dex| async move {
+            anyhow::Ok(qualify(dex, Meta::from(self.info_client.get_meta_and_asset_ctxs(dex).await?)))
+        }));
+        let (metas, at_cap) = tokio::join!(metas, self.info_client.get_perps_at_open_interest_cap());
+        let mut meta = merge(metas?);
+        // Only sharpens the cap proximity, so a failure leaves it to the configured caps.
+        let at_cap = at_cap.unwrap_or_else(|e| {
+            warn!("⚠️ Failed to get perps at open interest cap: {}", e);
+            Vec::new()
+        });
+        for (coin, context) in meta.contexts.iter_mut() {
+            context.open_interest_cap = self.open_interest_caps.get(coin).copied();
+            context.at_open_interest_cap = at_cap.contains(coin);
+        }
+        info!("✅ Converted meta - {} assets in universe, {} priced", meta.universe.len(), meta.contexts.len());
+        Ok(meta)
     }
     
     async fn get_recent_fills(&self) -> Result<Vec<Fill>> {
-        info!("📊 Fetching recent fills for: {}", self.user_address);
-        let data = self.info_client.get_user_fills(&self.user_address).await?;
-        self.convert_fills(data).await
-    }
-    
-    async fn get_l2_snapshots(&self) -> Result<HashMap<String, L2Snapshot>> {
-        info!("📊 Fetching L2 snapshots for {} assets", self.monitored_assets.len());
-        let mut snapshots = HashMap::new();
-        let mut successful_fetches = 0;
-        
-        for coin in &self.monitored_assets {
-            match self.info_client.get_l2_book(coin).await {
-                Ok(data) => {
-                    match self.convert_l2_snapshot(coin, data).await {
-                        Ok(snapshot) => {
-                            snapshots.insert(coin.clone(), snapshot);
-                            successful_fetches += 1;
-                            debug!("✅ Successfully fetched L2 for {}", coin);
-                        }
-                        Err(e) => {
-                            warn!("⚠️ Failed to convert L2 snapshot for {}: {}", coin, e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("⚠️ Failed to get L2 book for {}: {}", coin, e);
-                }
-            }
+        let accounts = self.accounts().await;
+        info!("📊 Fetching recent fills for: {}", accounts.join(", "));
+        let mut fills: Vec<Fill> = futures_util::future::try_join_all(accounts.iter().map(|account| self.fills_of(account))).await?
+            .into_iter()
+            .flatten()
+            .collect();
+        if accounts.len() > 1 {
+            fills.sort_by_key(|fill| fill.time);
         }
+        info!("✅ Converted {} fills", fills.len());
+        Ok(fills)
+    }
+    
+    async fn get_l2_snapshots(&self, live: &HashSet<String>) -> Result<HashMap<String, L2Snapshot>> {
+        let wanted: Vec<String> = self.get_monitored_assets().into_iter().filter(|coin| !live.contains(coin)).collect();
+        // Shared with get_user_state within the cycle, so this costs no request.
+        let accounts = self.accounts().await;
+        let dexes = self.dexes();
+        let states = accounts.iter().flat_map(|account| {
+            dexes.iter().map(move |dex| async move { (dex, self.info_client.get_clearinghouse_state(account, dex).await) })
+        });
+        let held: HashSet<String> = futures_util::future::join_all(states)
+            .await
+            .into_iter()
+            .filter_map(|(dex, state)| Some((dex, state.ok()?)))
+            .flat_map(|(dex, state)| state.asset_positions.into_iter().map(|asset| qualified(dex, asset.position.coin)))
+            .collect();
+        let (priority, rest): (Vec<String>, Vec<String>) = {
+            let mut books = lock(&self.books);
+            books.live = live.clone();
+            let due: Vec<String> = wanted.iter().filter(|coin| !books.in_flight.contains(*coin)).cloned().collect();
+            books.in_flight.extend(due.iter().cloned());
+            due.into_iter().partition(|coin| held.contains(coin))
+        };
+        info!("📊 Fetching L2 snapshots for {} held and {} other assets ({} streamed)", priority.len(), rest.len(), live.len());
+        self.progress.order_books_started(wanted.len());
         
-        info!("📊 Successfully fetched L2 snapshots for {}/{} assets", 
-              successful_fetches, self.monitored_assets.len());
+        // Without a `load_books` task, as in one-shot commands, the rest load in the cycle too.
+        let background = lock(&self.books).loader;
+        let (priority, rest) = if background { (priority, rest) } else { ([priority, rest].concat(), Vec::new()) };
+        let fetched = fetch_books(self.info_client.clone(), self.books.clone(), self.progress.clone(), priority).await;
+        self.progress.order_books_finished();
+        let loading = !rest.is_empty();
+        if loading {
+            lock(&self.books).queued.extend(rest);
+            self.books_due.notify_one();
+        }
         
-        if snapshots.is_empty() {
+        let books = lock(&self.books);
+        let snapshots: HashMap<String, L2Snapshot> = wanted
+            .iter()
+            .filter_map(|coin| match books.books.get(coin) {
+                Some((fetched, book)) if fetched.elapsed() <= BOOK_MAX_AGE => Some((coin.clone(), book.clone())),
+                _ => None,
+            })
+            .collect();
+        debug!("📊 {} books this cycle, {} fetched for held assets", snapshots.len(), fetched);
+        if snapshots.is_empty() && !wanted.is_empty() && !loading {
             warn!("⚠️ No L2 snapshots were successfully fetched!");
         }
         
         Ok(snapshots)
     }
     
+    async fn get_shadow_books(&self, coins: &[String]) -> Result<HashMap<String, L2Snapshot>> {
+        // Straight from the exchange: the cache holds books of polled assets only.
+        let requests: Vec<_> = coins
+            .iter()
+            .map(|coin| {
+                let info_client = &self.info_client;
+                async move { (coin, info_client.get_l2_book(coin).await) }
+            })
+            .collect();
+        let fetches = futures_util::stream::iter(requests).buffer_unordered(L2_CONCURRENCY);
+        futures_util::pin_mut!(fetches);
+        let mut books = HashMap::new();
+        while let Some((coin, book)) = fetches.next().await {
+            match book {
+                Ok(book) => {
+                    books.insert(coin.clone(), L2Snapshot { coin: coin.clone(), ..book.into() });
+                }
+                Err(e) => debug!("📊 Failed to get the shadow L2 book for {}: {}", coin, e),
+            }
+        }
+        Ok(books)
+    }
+    
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>> {
+        let accounts = self.accounts().await;
+        let dexes = self.dexes();
+        let requests = accounts.iter().flat_map(|account| {
+            dexes.iter().map(move |dex| async move {
+                let orders = self.info_client.get_open_orders(account, dex).await?.into_iter().map(|order| {
+                    let mut order = OpenOrder::from(order);
+                    order.coin = qualified(dex, std::mem::take(&mut order.coin));
+                    order
+                });
+                anyhow::Ok(orders.collect::<Vec<_>>())
+            })
+        });
+        let orders: Vec<OpenOrder> = futures_util::future::try_join_all(requests).await?
+            .into_iter()
+            .flatten()
+            .collect();
+        debug!("✅ {} open orders", orders.len());
+        Ok(orders)
+    }
+    
+    async fn get_backstop(&self) -> Result<Backstop> {
+        let settings = &self.backstop_settings;
+        let due = match lock(&self.backstop).0 {
+            Some(fetched) => fetched.elapsed() >= std::time::Duration::from_secs(settings.interval_secs),
+            None => true,
+        };
+        if !settings.enabled || !due {
+            return Ok(lock(&self.backstop).1.clone());
+        }
+        let accounts = futures_util::future::try_join_all(
+            settings.accounts.iter().map(|(name, address)| self.backstop_account(name, address)),
+        );
+        let vaults = futures_util::future::try_join_all(
+            settings.vaults.iter().map(|(name, address)| self.backstop_vault(name, address)),
+        );
+        let accounts = match tokio::join!(accounts, vaults) {
+            (Ok(mut accounts), Ok(vaults)) => {
+                accounts.extend(vaults);
+                Ok(accounts)
+            }
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+        let mut backstop = lock(&self.backstop);
+        match accounts {
+            Ok(accounts) => {
+                let now = chrono::Utc::now().timestamp_millis() as u64;
+                backstop.1.accounts = accounts;
+                let total = backstop.1.total();
+                let start = now.saturating_sub(settings.window_secs * 1000);
+                backstop.1.samples.retain(|(time, _)| *time >= start);
+                backstop.1.samples.push((now, total));
+                backstop.0 = Some(std::time::Instant::now());
+                info!("🛟 Backstop accounts hold ${}", total.round());
+            }
+            // The previous balance stands until the next cycle tries again.
+            Err(e) => warn!("⚠️ Cannot fetch the backstop accounts: {}", e),
+        }
+        Ok(backstop.1.clone())
+    }
+    
+    async fn get_candles(&self, coin: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<Candle>> {
+        let candles: Vec<Candle> = self.info_client.get_candle_snapshot(coin, interval, start_time, end_time).await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+            
+        debug!("✅ Converted {} candles for {}", candles.len(), coin);
+        Ok(candles)
+    }
+    
     async fn get_status(&self) -> DataSourceStatus {
-        let http_status = match self.info_client.get_meta().await {
+        let http_status = match self.info_client.get_meta_and_asset_ctxs(MAIN_DEX).await {
             Ok(_) => {
                 debug!("✅ HTTP API status: Connected");
                 true
@@ -0,0 +1,602 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+use crate::model;
+
+/// A decimal sent as a string, as Hyperliquid sends prices and sizes. Anything
+/// that does not parse is an error rather than zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Num(pub Decimal);
+
+impl<'de> Deserialize<'de> for Num {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse()
+            .map(Num)
+            .map_err(|_| serde::de::Error::custom(format!("invalid decimal {:?}", text)))
+    }
+}
+
+/// An identifier Hyperliquid sends as a string in some messages and a number in others.
+fn id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Text(String),
+        Number(u64),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::Text(text) => text,
+        Id::Number(number) => number.to_string(),
+    })
+}
+
+/// `clearinghouseState`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearinghouseState {
+    pub margin_summary: MarginSummary,
+    pub cross_margin_summary: MarginSummary,
+    pub asset_positions: Vec<AssetPosition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginSummary {
+    pub account_value: Num,
+    pub total_ntl_pos: Num,
+    pub total_raw_usd: Num,
+    pub total_margin_used: Num,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetPosition {
+    pub position: PositionData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionData {
+    pub coin: String,
+    pub szi: Num,
+    pub entry_px: Option<Num>,
+    pub position_value: Num,
+    pub unrealized_pnl: Num,
+    pub margin_used: Num,
+    /// Absent from older responses, which were all cross.
+    #[serde(default)]
+    pub leverage: Option<Leverage>,
+    /// Null when the account could not be liquidated at any price.
+    #[serde(default)]
+    pub liquidation_px: Option<Num>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Leverage {
+    /// `cross` or `isolated`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: u32,
+}
+
+/// `spotClearinghouseState`: the account's token balances, kept apart from
+/// its perp margin.
+#[derive(Debug, Deserialize)]
+pub struct SpotClearinghouseState {
+    pub balances: Vec<SpotBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpotBalance {
+    pub coin: String,
+    /// Index into `spotMeta` tokens.
+    pub token: u32,
+    pub total: Num,
+    pub hold: Num,
+}
+
+/// `spotMeta`.
+#[derive(Debug, Deserialize)]
+pub struct SpotMeta {
+    pub tokens: Vec<SpotToken>,
+    pub universe: Vec<SpotPair>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpotToken {
+    pub name: String,
+    pub index: u32,
+}
+
+/// A spot market, named as `allMids` keys its mid: `PURR/USDC`, or `@107`
+/// for pairs without a canonical name.
+#[derive(Debug, Deserialize)]
+pub struct SpotPair {
+    pub name: String,
+    /// Base token, then quote token.
+    pub tokens: (u32, u32),
+}
+
+/// `meta`.
+#[derive(Debug, Deserialize)]
+pub struct Meta {
+    pub universe: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+    pub name: String,
+    pub sz_decimals: u8,
+    pub max_leverage: u32,
+    /// Only listed for assets that are.
+    #[serde(default)]
+    pub only_isolated: bool,
+    #[serde(default)]
+    pub is_delisted: bool,
+}
+
+/// `metaAndAssetCtxs`: `meta`, then each asset's live context in the same order.
+/// Delisted assets keep a context whose prices have stopped.
+pub type MetaAndAssetCtxs = (Meta, Vec<AssetCtx>);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetCtx {
+    /// Null for assets without a price, such as some delisted ones.
+    #[serde(default)]
+    pub mark_px: Option<Num>,
+    #[serde(default)]
+    pub oracle_px: Option<Num>,
+    /// In units of the asset, not USD.
+    #[serde(default)]
+    pub open_interest: Option<Num>,
+}
+
+/// An element of `userFills`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFill {
+    pub coin: String,
+    pub px: Num,
+    pub sz: Num,
+    pub side: String,
+    pub time: u64,
+    pub start_position: Num,
+    pub dir: String,
+    pub closed_pnl: Num,
+    pub hash: String,
+    pub oid: u64,
+    /// The trade's id, which tells apart the fills of one order in one transaction.
+    #[serde(default)]
+    pub tid: Option<u64>,
+    pub crossed: bool,
+    /// Includes `builder_fee`, both in `fee_token`.
+    pub fee: Num,
+    /// Only listed for fills routed through a builder.
+    #[serde(default)]
+    pub builder_fee: Option<Num>,
+    /// USDC, except for spot buys, which pay in the token bought.
+    #[serde(default)]
+    pub fee_token: Option<String>,
+    /// Only listed for fills of a liquidation, on either side of it.
+    #[serde(default)]
+    pub liquidation: Option<FillLiquidation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillLiquidation {
+    #[serde(default)]
+    pub liquidated_user: Option<String>,
+    pub method: String,
+}
+
+/// `userFees`: the rates the account pays after volume tiers and discounts.
+/// Negative add rates are rebates.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFees {
+    pub user_cross_rate: Num,
+    pub user_add_rate: Num,
+    pub user_spot_cross_rate: Num,
+    pub user_spot_add_rate: Num,
+    /// The account's volume by day, for its tier on earlier days.
+    #[serde(default)]
+    pub daily_user_vlm: Vec<DailyVolume>,
+    #[serde(default)]
+    pub fee_schedule: Option<FeeSchedule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyVolume {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub user_cross: Num,
+    pub user_add: Num,
+}
+
+/// The exchange's base rates and the volume tiers below them, before discounts.
+#[derive(Debug, Deserialize)]
+pub struct FeeSchedule {
+    #[serde(flatten)]
+    pub base: FeeRates,
+    pub tiers: FeeTiers,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeeTiers {
+    pub vip: Vec<VipTier>,
+}
+
+/// Rates for an account with at least `ntl_cutoff` of volume over 14 days.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VipTier {
+    pub ntl_cutoff: Num,
+    #[serde(flatten)]
+    pub rates: FeeRates,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeRates {
+    pub cross: Num,
+    pub add: Num,
+    pub spot_cross: Num,
+    pub spot_add: Num,
+}
+
+impl UserFees {
+    /// The schedule's rates for the tier the account was in on `day`, by its
+    /// volume over the 14 days before; none without the schedule.
+    pub fn tier_on(&self, day: chrono::NaiveDate) -> Option<&FeeRates> {
+        let schedule = self.fee_schedule.as_ref()?;
+        let volume: Decimal = self
+            .daily_user_vlm
+            .iter()
+            .filter(|daily| chrono::NaiveDate::parse_from_str(&daily.date, "%Y-%m-%d").is_ok_and(|date| date < day && date >= day - chrono::Days::new(14)))
+            .map(|daily| daily.user_cross.0 + daily.user_add.0)
+            .sum();
+        let tier = schedule.tiers.vip.iter().filter(|tier| volume >= tier.ntl_cutoff.0).max_by_key(|tier| tier.ntl_cutoff.0);
+        Some(tier.map_or(&schedule.base, |tier| &tier.rates))
+    }
+}
+
+/// An element of `openOrders`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOrder {
+    pub coin: String,
+    pub side: String,
+    pub limit_px: Num,
+    pub sz: Num,
+    pub oid: u64,
+    pub timestamp: u64,
+}
+
+/// `l2Book`, from the info API and the WebSocket alike: bids, then asks.
+#[derive(Debug, Deserialize)]
+pub struct L2Book {
+    pub coin: String,
+    pub time: u64,
+    pub levels: (Vec<Level>, Vec<Level>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Level {
+    pub px: Num,
+    pub sz: Num,
+    pub n: u32,
+}
+
+/// An element of `candleSnapshot`, or a `candle` WebSocket update.
+#[derive(Debug, Deserialize)]
+pub struct Candle {
+    pub s: String,
+    pub t: u64,
+    #[serde(rename = "T")]
+    pub close_time: u64,
+    pub o: Num,
+    pub h: Num,
+    pub l: Num,
+    pub c: Num,
+    pub v: Num,
+    pub n: u64,
+}
+
+/// An entry of `subAccounts`, which lists a master's sub-accounts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccount {
+    pub name: String,
+    pub sub_account_user: String,
+}
+
+/// `vaultDetails`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultDetails {
+    pub name: String,
+    pub vault_address: String,
+    /// Trailing annualised return as a fraction.
+    pub apr: f64,
+    /// By window: `day`, `week`, `month` and `allTime`.
+    pub portfolio: Vec<(String, Portfolio)>,
+    /// Depositors, only counted. Hyperliquid lists the largest hundred or so.
+    #[serde(default)]
+    pub followers: Vec<serde::de::IgnoredAny>,
+    #[serde(default)]
+    pub relationship: Option<Relationship>,
+}
+
+impl VaultDetails {
+    pub fn window(&self, name: &str) -> Option<&Portfolio> {
+        self.portfolio.iter().find(|(window, _)| window == name).map(|(_, portfolio)| portfolio)
+    }
+
+    /// The vaults a parent such as HLP trades through; none for other vaults.
+    pub fn child_addresses(&self) -> &[String] {
+        match &self.relationship {
+            Some(Relationship { kind, data: Some(data) }) if kind == "parent" => &data.child_addresses,
+            _ => &[],
+        }
+    }
+}
+
+/// The account value and cumulative PnL of a window as `(time, value)` points, oldest first.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Portfolio {
+    pub account_value_history: Vec<(u64, Num)>,
+    pub pnl_history: Vec<(u64, Num)>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Relationship {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub data: Option<RelationshipData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipData {
+    #[serde(default)]
+    pub child_addresses: Vec<String>,
+}
+
+/// `allMids`: mid price by asset.
+pub type AllMids = HashMap<String, Num>;
+
+/// A WebSocket message. `data` is read according to `channel`.
+#[derive(Debug, Deserialize)]
+pub struct WsMessage {
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// An element of a `trades` WebSocket update.
+#[derive(Debug, Deserialize)]
+pub struct Trade {
+    pub coin: String,
+    pub side: String,
+    pub px: Num,
+    pub sz: Num,
+    pub time: u64,
+    #[serde(deserialize_with = "id")]
+    pub tid: String,
+    /// Buyer then seller.
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+/// An element of an `orders` WebSocket update.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderUpdate {
+    pub oid: u64,
+    pub status: String,
+    pub coin: String,
+    pub side: String,
+    pub limit_px: Num,
+    pub sz: Num,
+    pub status_timestamp: u64,
+}
+
+impl From<PositionData> for model::Position {
+    fn from(position: PositionData) -> Self {
+        Self {
+            symbol: position.coin,
+            size: position.szi.0,
+            entry_px: position.entry_px.map(|px| px.0),
+            position_value: position.position_value.0,
+            unrealized_pnl: position.unrealized_pnl.0,
+            margin_used: position.margin_used.0,
+            isolated: position.leverage.as_ref().is_some_and(|leverage| leverage.kind == "isolated"),
+            leverage: position.leverage.map(|leverage| leverage.value),
+            liquidation_px: position.liquidation_px.map(|px| px.0),
+        }
+    }
+}
+
+impl From<ClearinghouseState> for model::UserState {
+    fn from(state: ClearinghouseState) -> Self {
+        Self {
+            account_value: state.margin_summary.account_value.0,
+            total_margin_used: state.cross_margin_summary.total_margin_used.0,
+            total_ntl_pos: state.cross_margin_summary.total_ntl_pos.0,
+            total_raw_usd: state.cross_margin_summary.total_raw_usd.0,
+            positions: state.asset_positions.into_iter().map(|asset| asset.position.into()).collect(),
+            spot_balances: Vec::new(),
+            accounts: Vec::new(),
+        }
+    }
+}
+
+impl From<Meta> for model::Meta {
+    fn from(meta: Meta) -> Self {
+        Self {
+            universe: meta.universe
+                .into_iter()
+                .map(|asset| model::AssetInfo {
+                    name: asset.name,
+                    sz_decimals: asset.sz_decimals,
+                    max_leverage: asset.max_leverage,
+                    only_isolated: asset.only_isolated,
+                    is_delisted: asset.is_delisted,
+                })
+                .collect(),
+            contexts: HashMap::new(),
+        }
+    }
+}
+
+impl From<MetaAndAssetCtxs> for model::Meta {
+    fn from((meta, contexts): MetaAndAssetCtxs) -> Self {
+        let mut converted = model::Meta::from(meta);
+        converted.contexts = converted.universe
+            .iter()
+            .zip(contexts)
+            .filter(|(asset, _)| !asset.is_delisted)
+            .filter_map(|(asset, context)| {
+                let context = model::AssetContext {
+                    mark_px: context.mark_px?.0,
+                    oracle_px: context.oracle_px?.0,
+                    open_interest: context.open_interest.map(|oi| oi.0).unwrap_or_default(),
+                    open_interest_cap: None,
+                    at_open_interest_cap: false,
+                };
+                Some((asset.name.clone(), context))
+            })
+            .collect();
+        converted
+    }
+}
+
+impl From<UserFill> for model::Fill {
+    fn from(fill: UserFill) -> Self {
+        let in_base = fill.fee_token.as_deref().is_some_and(|token| token != "USDC");
+        let fee_usd = |fee: Decimal| if in_base { fee * fill.px.0 } else { fee };
+        Self {
+            coin: fill.coin,
+            px: fill.px.0,
+            sz: fill.sz.0,
+            side: fill.side,
+            time: fill.time,
+            start_position: fill.start_position.0,
+            dir: fill.dir,
+            closed_pnl: fill.closed_pnl.0,
+            hash: fill.hash,
+            oid: fill.oid,
+            crossed: fill.crossed,
+            // A spot buy pays in the base token; in USD at the fill's price.
+            fee: fee_usd(fill.fee.0),
+            builder_fee: fee_usd(fill.builder_fee.map_or(Decimal::ZERO, |fee| fee.0)),
+            expected_fee: None,
+            liquidation: fill.liquidation.map(|liquidation| model::FillLiquidation {
+                liquidated_user: liquidation.liquidated_user,
+                method: liquidation.method,
+            }),
+            users: Vec::new(),
+        }
+    }
+}
+
+/// A market trade as a fill without an account side: no position, PnL or fee.
+impl From<Trade> for model::Fill {
+    fn from(trade: Trade) -> Self {
+        Self {
+            coin: trade.coin,
+            px: trade.px.0,
+            sz: trade.sz.0,
+            dir: trade.side.clone(),
+            side: trade.side,
+            time: trade.time,
+            start_position: Decimal::ZERO,
+            closed_pnl: Decimal::ZERO,
+            hash: trade.tid,
+            oid: 0,
+            crossed: false,
+            fee: Decimal::ZERO,
+            builder_fee: Decimal::ZERO,
+            expected_fee: None,
+            liquidation: None,
+            users: trade.users,
+        }
+    }
+}
+
+impl From<OpenOrder> for model::OpenOrder {
+    fn from(order: OpenOrder) -> Self {
+        Self {
+            coin: order.coin,
+            side: order.side,
+            limit_px: order.limit_px.0,
+            sz: order.sz.0,
+            oid: order.oid,
+            timestamp: order.timestamp,
+        }
+    }
+}
+
+impl From<Level> for model::OrderBookLevel {
+    fn from(level: Level) -> Self {
+        Self { px: level.px.0, sz: level.sz.0, n: level.n }
+    }
+}
+
+impl From<L2Book> for model::L2Snapshot {
+    fn from(book: L2Book) -> Self {
+        let (bids, asks) = book.levels;
+        Self {
+            coin: book.coin,
+            time: book.time,
+            bids: bids.into_iter().map(Into::into).collect(),
+            asks: asks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Candle> for model::Candle {
+    fn from(candle: Candle) -> Self {
+        Self {
+            coin: candle.s,
+            open_time: candle.t,
+            close_time: candle.close_time,
+            open: candle.o.0,
+            high: candle.h.0,
+            low: candle.l.0,
+            close: candle.c.0,
+            volume: candle.v.0,
+            trades: candle.n,
+        }
+    }
+}
+
+impl TryFrom<OrderUpdate> for model::OrderEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(order: OrderUpdate) -> anyhow::Result<Self> {
+        use model::OrderAction::*;
+        let action = match order.status.as_str() {
+            "open"      => New,
+            "filled"    => Filled,
+            "cancelled" => Cancelled,
+            other       => anyhow::bail!("unknown status {}", other),
+        };
+        Ok(Self {
+            id:        order.oid,
+            action,
+            coin:      order.coin,
+            side:      order.side,
+            px:        order.limit_px.0,
+            sz:        order.sz.0,
+            timestamp: order.status_timestamp,
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use log::info;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::model::Alert;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// Serves the dashboard's current state as JSON, so other tools can read it
+/// without polling Hyperliquid themselves:
+///
+/// - `GET /api/metrics`: the latest `GlobalMetrics` snapshot
+/// - `GET /api/alerts`: the alert list, oldest first
+/// - `GET /api/positions`: open positions of the monitored account
+pub async fn serve(addr: SocketAddr, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        let alerts = alerts.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let metrics = metrics.clone();
+                let alerts = alerts.clone();
+                async move { Ok::<_, Infallible>(respond(request, &metrics, &alerts).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    info!("🌐 JSON API on http://{}/api/metrics", addr);
+    server.await?;
+    Ok(())
+}
+
+async fn respond(request: Request<Body>, metrics: &SharedMetrics, alerts: &Versioned<Vec<Alert>>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return json(StatusCode::METHOD_NOT_ALLOWED, &serde_json::json!({ "error": "only GET is supported" }));
+    }
+
+    match request.uri().path() {
+        "/api/metrics" => json(StatusCode::OK, &metrics.snapshot().await),
+        "/api/alerts" => json(StatusCode::OK, &*alerts.read().await),
+        "/api/positions" => json(StatusCode::OK, &metrics.market.read().await.positions),
+        path => json(StatusCode::NOT_FOUND, &serde_json::json!({ "error": format!("no route for {}", path) })),
+    }
+}
+
+fn json(status: StatusCode, value: &impl Serialize) -> Response<Body> {
+    let (status, body) = match serde_json::to_vec(value) {
+        Ok(body) => (status, body),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{{\"error\":\"{}\"}}", e).into_bytes()),
+    };
+
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    response
+}
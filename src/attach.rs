@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use log::{debug, info};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{AttachSettings, Config, Denomination};
+use crate::control;
+use crate::history::MetricHistory;
+use crate::model::Alert;
+use crate::shared::SharedMetrics;
+use crate::ui::ui::{self as dashboard, UIState, TAB_TITLES};
+use crate::versioned::Versioned;
+
+/// How often an attached terminal is redrawn; only changed cells are sent.
+const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Accepts read-only dashboard sessions on the attach socket. A client sends
+/// `size <columns> <rows>` first and then `size ...` and `key <name>` lines as
+/// its terminal changes and keys are pressed; it gets the dashboard drawn for
+/// its size, as terminal output, with its own tab and scroll position. Keys
+/// only move around the dashboard: nothing an attached client does changes
+/// the running instance. The socket file is removed once `token` is cancelled.
+pub async fn serve(
+    settings: AttachSettings,
+    config: Arc<RwLock<Arc<Config>>>,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    token: CancellationToken,
+) -> Result<()> {
+    let path = Path::new(&settings.socket);
+    if path.exists() {
+        // A socket nobody answers on is left over from an instance that did not exit cleanly.
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(anyhow!("{} is in use by another instance", path.display()));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = control::bind_private(path)?;
+    info!("📺 Attach socket listening on {}", path.display());
+
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = token.cancelled() => break,
+        };
+        let (config, metrics, alerts, history, token) = (config.clone(), metrics.clone(), alerts.clone(), history.clone(), token.clone());
+        tokio::spawn(async move {
+            info!("📺 Dashboard attached");
+            match session(stream, config, metrics, alerts, history, token).await {
+                Ok(()) => info!("📺 Dashboard detached"),
+                Err(e) => debug!("📺 Attached dashboard closed: {}", e),
+            }
+        });
+    }
+    std::fs::remove_file(path)?;
+    debug!("📺 Removed attach socket {}", path.display());
+    Ok(())
+}
+
+/// Terminal output of the frames drawn for one client, taken after each draw.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn session(
+    stream: UnixStream,
+    config: Arc<RwLock<Arc<Config>>>,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    token: CancellationToken,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let first = lines.next_line().await?.unwrap_or_default();
+    let area = parse_size(&first).ok_or_else(|| anyhow!("expected `size <columns> <rows>`, got {:?}", first))?;
+
+    let mut viewer = Viewer::new(area, config.read().await.ui_settings.denomination)?;
+    let mut frames = tokio::time::interval(FRAME_INTERVAL);
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                match parse_size(&line) {
+                    Some(size) => viewer.resize(size)?,
+                    None => viewer.key(line.strip_prefix("key ").unwrap_or_default()),
+                }
+            }
+            _ = frames.tick() => {}
+            _ = token.cancelled() => return Ok(()),
+        }
+
+        let output = viewer.draw(&config, &metrics, &alerts, &history).await?;
+        writer.write_all(&output).await?;
+    }
+}
+
+/// One attached client's dashboard, with its own size, tab, scroll position and
+/// denomination, drawn as terminal output. The SSH server draws its sessions
+/// with it too.
+pub struct Viewer {
+    output: Output,
+    terminal: Terminal<CrosstermBackend<Output>>,
+    area: Rect,
+    state: UIState,
+}
+
+impl Viewer {
+    pub fn new(area: Rect, denomination: Denomination) -> Result<Self> {
+        let output = Output::default();
+        let terminal = screen(&output, area)?;
+        let mut state = UIState::new();
+        state.log_capture = false;
+        state.denomination = denomination;
+        Ok(Self { output, terminal, area, state })
+    }
+
+    pub fn resize(&mut self, area: Rect) -> Result<()> {
+        if area != self.area {
+            self.area = area;
+            self.terminal = screen(&self.output, area)?;
+        }
+        Ok(())
+    }
+
+    /// Applies a key by the name `key_line` sends it as; see `navigate`.
+    pub fn key(&mut self, key: &str) {
+        navigate(&mut self.state, key);
+    }
+
+    /// Draws the dashboard as it is now and returns the terminal output that
+    /// brings the client's screen up to date.
+    pub async fn draw(
+        &mut self,
+        config: &RwLock<Arc<Config>>,
+        metrics: &SharedMetrics,
+        alerts: &Versioned<Vec<Alert>>,
+        history: &RwLock<MetricHistory>,
+    ) -> Result<Vec<u8>> {
+        let config = config.read().await.clone();
+        let snapshot = metrics.snapshot().await;
+        let alerts = alerts.read().await.clone();
+        let history = history.read().await;
+        let state = &mut self.state;
+        self.terminal.draw(|f| dashboard::draw(f, state, &config, &snapshot, &alerts, &history))?;
+        Ok(self.output.take())
+    }
+}
+
+/// A blank `area` to draw into. A fixed viewport keeps its size through
+/// `Terminal::resize`, so a resized client gets a new one.
+fn screen(output: &Output, area: Rect) -> Result<Terminal<CrosstermBackend<Output>>> {
+    let mut terminal = Terminal::with_options(CrosstermBackend::new(output.clone()), TerminalOptions { viewport: Viewport::Fixed(area) })?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+    Ok(terminal)
+}
+
+/// `size <columns> <rows>`.
+fn parse_size(line: &str) -> Option<Rect> {
+    let mut parts = line.strip_prefix("size ")?.split_whitespace().map(str::parse::<u16>);
+    match (parts.next(), parts.next()) {
+        (Some(Ok(columns)), Some(Ok(rows))) if columns > 0 && rows > 0 => Some(Rect::new(0, 0, columns, rows)),
+        _ => None,
+    }
+}
+
+/// The keys an attached client may use: tabs, scrolling and the denomination.
+fn navigate(state: &mut UIState, key: &str) {
+    match key {
+        "tab" => state.next_tab(),
+        "backtab" => state.previous_tab(),
+        "up" => state.scroll_up(),
+        "down" => state.scroll_down(),
+        "pageup" => state.page_up(),
+        "pagedown" => state.page_down(),
+        "home" => state.scroll_to_top(),
+        "d" | "D" => state.denomination = state.denomination.next(),
+        digit => {
+            if let Some(tab) = digit.parse::<usize>().ok().filter(|tab| (1..=TAB_TITLES.len()).contains(tab)) {
+                state.selected_tab = tab - 1;
+            }
+        }
+    }
+}
+
+/// The line `key` is sent as, or none for keys the dashboard ignores when attached.
+fn key_line(key: KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::Char(c) if c.is_ascii_digit() || c == 'd' || c == 'D' => c.to_string(),
+        _ => return None,
+    };
+    Some(format!("key {}\n", name))
+}
+
+/// Attaches this terminal to the dashboard served on `socket` until `q`, Esc or
+/// Ctrl+C, or until the instance goes away. Meant to be run over `ssh -t`.
+pub fn run(socket: &str) -> Result<()> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)
+        .map_err(|e| anyhow!("cannot attach to {}: {}; is [attach] enabled on the running instance?", socket, e))?;
+    let (columns, rows) = crossterm::terminal::size()?;
+    stream.write_all(format!("size {} {}\n", columns, rows).as_bytes())?;
+
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen, crossterm::terminal::Clear(crossterm::terminal::ClearType::All))?;
+    let result = forward(stream);
+    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen, crossterm::cursor::Show)?;
+    crossterm::terminal::disable_raw_mode()?;
+    result
+}
+
+/// Copies the dashboard's output to stdout while sending sizes and keys back.
+fn forward(mut stream: std::os::unix::net::UnixStream) -> Result<()> {
+    let closed = Arc::new(AtomicBool::new(false));
+    let mut output = stream.try_clone()?;
+    let copier = {
+        let closed = closed.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 16 * 1024];
+            let mut stdout = std::io::stdout();
+            while let Ok(read @ 1..) = output.read(&mut buffer) {
+                if stdout.write_all(&buffer[..read]).and_then(|()| stdout.flush()).is_err() {
+                    break;
+                }
+            }
+            closed.store(true, Ordering::Relaxed);
+        })
+    };
+
+    while !closed.load(Ordering::Relaxed) {
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        let line = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Press => None,
+            Event::Key(key) if matches!(key.code, KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc) => break,
+            Event::Key(key) if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            Event::Key(key) => key_line(key),
+            Event::Resize(columns, rows) => Some(format!("size {} {}\n", columns, rows)),
+            _ => None,
+        };
+        if let Some(line) = line {
+            if stream.write_all(line.as_bytes()).is_err() {
+                break;
+            }
+        }
+    }
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+    let _ = copier.join();
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+
+use crate::api::sdk::{InfoClient, MAIN_DEX, USER_FILLS_PAGE};
+use crate::metrics::{self, risk};
+use crate::model::{Fill, Meta, VaultSummary};
+use crate::report::ReportFormat;
+use crate::session::format_signed_usd;
+
+/// `account` and, when it is a parent vault such as HLP, the child vaults
+/// that trade for it.
+pub async fn accounts_of(client: &InfoClient, account: &str) -> Vec<String> {
+    let mut accounts = vec![account.to_string()];
+    // Only vaults have details; for an account this fails and it stands alone.
+    if let Ok(details) = client.get_vault_details(account).await {
+        accounts.extend(details.child_addresses().iter().cloned());
+    }
+    accounts
+}
+
+/// The fills of `account` from `start_ms` to `end_ms`, oldest first, paged
+/// through `userFillsByTime`. Hyperliquid only serves an account's 10,000
+/// latest fills, so a long history may start later than `start_ms`.
+pub async fn fetch_fills(client: &InfoClient, account: &str, start_ms: u64, end_ms: u64) -> Result<Vec<Fill>> {
+    let mut fills = Vec::new();
+    let mut seen = HashSet::new();
+    let mut from = start_ms;
+    loop {
+        let page = client.get_user_fills_by_time(account, from, end_ms).await?;
+        let full = page.len() >= USER_FILLS_PAGE;
+        let mut newest = from;
+        let mut added = false;
+        for fill in page {
+            newest = newest.max(fill.time);
+            // Each page starts again at the millisecond the last one ended in,
+            // so that fills a full page cut off there are not lost.
+            let id = (fill.hash.clone(), fill.oid, fill.tid, fill.time, fill.px.0, fill.sz.0);
+            if seen.insert(id) {
+                fills.push(Fill::from(fill));
+                added = true;
+            }
+        }
+        if !full || newest >= end_ms {
+            break;
+        }
+        // A page of nothing new is a millisecond with more fills than a page holds.
+        from = if added { newest } else { newest + 1 };
+    }
+    fills.sort_by_key(|fill| fill.time);
+    Ok(fills)
+}
+
+/// The metrics of one window of the history.
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestWindow {
+    pub start: DateTime<Utc>,
+    pub fills: usize,
+    pub volume: f64,
+    pub realized_pnl: f64,
+    pub fees: f64,
+    /// Realized PnL after fees, summed from the first window.
+    pub cumulative_pnl: f64,
+    /// Of `cumulative_pnl` below its highest so far, in USD. Realized only:
+    /// a history has no marks to value the open positions at.
+    pub drawdown: f64,
+    /// Of the window's fills, as the Performance tab computes them.
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub adverse_selection_cost: f64,
+    pub vpin: f64,
+    pub cascade_risk: f64,
+    /// The asset with the largest share of the window's volume, and the share;
+    /// a history has no positions to take the concentration of.
+    pub top_concentration: Option<(String, f64)>,
+}
+
+/// An account's fill history replayed through the performance and risk
+/// metrics, window by window, with the figures of the whole run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Backtest {
+    pub generated: DateTime<Utc>,
+    pub accounts: Vec<String>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub window_secs: u64,
+    /// Later than `from` when Hyperliquid no longer serves the fills before it.
+    pub first_fill: Option<DateTime<Utc>>,
+    pub fills: usize,
+    pub volume: f64,
+    pub realized_pnl: f64,
+    pub fees: f64,
+    pub net_pnl: f64,
+    /// Of the windows' net PnL.
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    /// The deepest fall of the cumulative realized net PnL from a high, in
+    /// USD, fill by fill; unrealized PnL is not counted.
+    pub max_drawdown: f64,
+    pub max_drawdown_at: Option<DateTime<Utc>>,
+    pub peak_vpin: f64,
+    pub peak_vpin_at: Option<DateTime<Utc>>,
+    pub windows: Vec<BacktestWindow>,
+}
+
+fn time(ms: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(ms as i64).unwrap_or_default()
+}
+
+fn usd(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// What the performance metrics need of a vault; a history has none of its
+/// TVL or equity, only of its fills.
+fn empty_summary(account: &str) -> VaultSummary {
+    VaultSummary {
+        vault_address: account.to_string(),
+        name: None,
+        tvl: Decimal::ZERO,
+        equity: Decimal::ZERO,
+        apr: 0.0,
+        all_time_pnl: Decimal::ZERO,
+        max_drawdown: 0.0,
+        share_price: 0.0,
+        num_depositors: 0,
+        portfolio_value: Decimal::ZERO,
+        deployed_liquidity: Decimal::ZERO,
+        idle_liquidity: Decimal::ZERO,
+        flows: Vec::new(),
+        vaults: Vec::new(),
+        components: false,
+    }
+}
+
+impl Backtest {
+    /// Replays `fills`, oldest first, in windows of `window_ms` from `from_ms`
+    /// to `to_ms`. `meta` decides the majors VPIN and cascade risk count, as
+    /// it does live.
+    pub fn build(accounts: Vec<String>, fills: &[Fill], meta: &Meta, from_ms: u64, to_ms: u64, window_ms: u64) -> Self {
+        let summary = empty_summary(accounts.first().map_or("", String::as_str));
+        let window_ms = window_ms.max(1);
+        let mut windows = Vec::new();
+        let (mut cumulative, mut high) = (0.0, 0.0);
+        let mut rest = fills;
+        let mut start = from_ms;
+        while start < to_ms {
+            let end = (start + window_ms).min(to_ms);
+            let split = rest.iter().position(|fill| fill.time >= end).unwrap_or(rest.len());
+            let (window, later) = rest.split_at(split);
+            rest = later;
+            let window: Vec<Fill> = window.iter().filter(|fill| fill.time >= start).cloned().collect();
+
+            let performance = metrics::calculate_performance_metrics(&window, &summary, &[]);
+            let realized_pnl = usd(window.iter().map(|fill| fill.closed_pnl).sum());
+            let fees = usd(window.iter().map(|fill| fill.fee).sum());
+            cumulative += realized_pnl - fees;
+            high = f64::max(high, cumulative);
+            let top_concentration = risk::calculate_turnover_concentration(&window, meta)
+                .into_iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+            windows.push(BacktestWindow {
+                start: time(start),
+                fills: window.len(),
+                volume: usd(performance.total_volume),
+                realized_pnl,
+                fees,
+                cumulative_pnl: cumulative,
+                drawdown: high - cumulative,
+                sharpe_ratio: performance.sharpe_ratio,
+                sortino_ratio: performance.sortino_ratio,
+                adverse_selection_cost: performance.adverse_selection_cost,
+                vpin: risk::calculate_vpin(&window, meta),
+                cascade_risk: risk::calculate_cascade_risk(&window, meta),
+                top_concentration,
+            });
+            start = end;
+        }
+
+        let in_range: Vec<&Fill> = fills.iter().filter(|fill| (from_ms..to_ms).contains(&fill.time)).collect();
+        let (mut cumulative, mut high, mut max_drawdown, mut max_drawdown_at) = (0.0, 0.0, 0.0, None);
+        for fill in &in_range {
+            cumulative += usd(fill.closed_pnl - fill.fee);
+            high = f64::max(high, cumulative);
+            if high - cumulative > max_drawdown {
+                max_drawdown = high - cumulative;
+                max_drawdown_at = Some(time(fill.time));
+            }
+        }
+        let net: Vec<f64> = windows.iter().map(|window| window.realized_pnl - window.fees).collect();
+        let peak = windows.iter().filter(|window| window.fills > 0).max_by(|a, b| a.vpin.total_cmp(&b.vpin));
+        let realized_pnl: f64 = windows.iter().map(|window| window.realized_pnl).sum();
+        let fees: f64 = windows.iter().map(|window| window.fees).sum();
+
+        Self {
+            generated: Utc::now(),
+            accounts,
+            from: time(from_ms),
+            to: time(to_ms),
+            window_secs: window_ms / 1000,
+            first_fill: in_range.first().map(|fill| time(fill.time)),
+            fills: in_range.len(),
+            volume: windows.iter().map(|window| window.volume).sum(),
+            realized_pnl,
+            fees,
+            net_pnl: realized_pnl - fees,
+            sharpe_ratio: metrics::calculate_sharpe_ratio(&net),
+            sortino_ratio: metrics::calculate_sortino_ratio(&net),
+            max_drawdown,
+            max_drawdown_at,
+            peak_vpin: peak.map_or(0.0, |window| window.vpin),
+            peak_vpin_at: peak.map(|window| window.start),
+            windows,
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let day = |at: DateTime<Utc>| at.format("%Y-%m-%d %H:%M").to_string();
+        let mut out = String::new();
+        let _ = writeln!(out, "# HLP Backtest\n");
+        let _ = writeln!(
+            out,
+            "Fills of {} from {} to {} UTC, in windows of {}, generated {}.",
+            self.accounts.join(", "),
+            day(self.from),
+            day(self.to),
+            window_label(self.window_secs),
+            self.generated.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if let Some(first) = self.first_fill.filter(|first| (*first - self.from).num_seconds() as u64 > self.window_secs) {
+            let _ = writeln!(out, "\nThe first fill served is of {}; Hyperliquid keeps only an account's latest 10,000.", day(first));
+        }
+
+        let _ = writeln!(out, "\n## Summary\n\n| Metric | Value |\n|---|---:|");
+        let at = |when: Option<DateTime<Utc>>| when.map_or_else(String::new, |when| format!(" ({})", day(when)));
+        let rows = [
+            ("Fills", self.fills.to_string()),
+            ("Volume", format_signed_usd(self.volume)),
+            ("Realized PnL", format_signed_usd(self.realized_pnl)),
+            ("Fees", format_signed_usd(self.fees)),
+            ("Net PnL", format_signed_usd(self.net_pnl)),
+            ("Sharpe (per window)", format!("{:.2}", self.sharpe_ratio)),
+            ("Sortino (per window)", format!("{:.2}", self.sortino_ratio)),
+            ("Max realized drawdown", format!("{}{}", format_signed_usd(-self.max_drawdown), at(self.max_drawdown_at))),
+            ("Peak VPIN", format!("{:.3}{}", self.peak_vpin, at(self.peak_vpin_at))),
+        ];
+        for (metric, value) in rows {
+            let _ = writeln!(out, "| {} | {} |", metric, value);
+        }
+
+        let _ = writeln!(
+            out,
+            "\n## Series\n\n| Window | Fills | Volume | Net PnL | Cumulative | Realized drawdown | Sharpe | VPIN | Cascade | Top asset |\n|---|---:|---:|---:|---:|---:|---:|---:|---:|---|"
+        );
+        for window in &self.windows {
+            let top = window.top_concentration.as_ref().map_or_else(|| "-".to_string(), |(coin, share)| format!("{} {:.0}%", coin, share * 100.0));
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {:.2} | {:.3} | {:.3} | {} |",
+                day(window.start),
+                window.fills,
+                format_signed_usd(window.volume),
+                format_signed_usd(window.realized_pnl - window.fees),
+                format_signed_usd(window.cumulative_pnl),
+                format_signed_usd(-window.drawdown),
+                window.sharpe_ratio,
+                window.vpin,
+                window.cascade_risk,
+                top
+            );
+        }
+        out
+    }
+}
+
+fn window_label(secs: u64) -> String {
+    match secs {
+        secs if secs % 86_400 == 0 => format!("{}d", secs / 86_400),
+        secs if secs % 3_600 == 0 => format!("{}h", secs / 3_600),
+        secs if secs % 60 == 0 => format!("{}m", secs / 60),
+        secs => format!("{}s", secs),
+    }
+}
+
+/// Fetches the history of `account` and its child vaults over the last
+/// `since_ms` and replays it in windows of `window_ms`.
+pub async fn run(client: &InfoClient, account: &str, since_ms: u64, window_ms: u64) -> Result<Backtest> {
+    let to_ms = Utc::now().timestamp_millis() as u64;
+    let from_ms = to_ms.saturating_sub(since_ms);
+    let meta = Meta::from(client.get_meta_and_asset_ctxs(MAIN_DEX).await?);
+    let accounts = accounts_of(client, account).await;
+
+    let mut fills = Vec::new();
+    for (index, account) in accounts.iter().enumerate() {
+        match fetch_fills(client, account, from_ms, to_ms).await {
+            Ok(account_fills) => {
+                info!("📜 {} fills of {}", account_fills.len(), account);
+                fills.extend(account_fills);
+            }
+            Err(e) if index > 0 => warn!("⚠️ Cannot fetch the fills of child vault {}; the backtest leaves them out: {}", account, e),
+            Err(e) => return Err(e),
+        }
+    }
+    fills.sort_by_key(|fill| fill.time);
+    Ok(Backtest::build(accounts, &fills, &meta, from_ms, to_ms, window_ms))
+}
@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+use crate::alert;
+use crate::api::sdk::{websocket_url, InfoClient, MAIN_DEX};
+use crate::config::Config;
+use crate::diagnostics::request;
+use crate::{daily_report, mqtt};
+
+/// One connection tried by `check`: what was confirmed, or why it failed.
+pub struct Outcome {
+    pub name: &'static str,
+    pub result: Result<String>,
+}
+
+/// Tries every connection a session depends on, in the order they are made at
+/// start-up: the info API, the account (and vault) behind the configured
+/// addresses, the WebSocket feed, then a test message through each enabled
+/// notification channel.
+pub async fn run(config: &Config) -> Vec<Outcome> {
+    let client = InfoClient::new(config.hyperliquid_api_url.clone(), None);
+    let mut outcomes = vec![Outcome { name: "Info API", result: info_api(&client).await }];
+    let reachable = outcomes[0].result.is_ok();
+    if reachable {
+        outcomes.push(Outcome { name: "Account", result: account(&client, &config.user_address).await });
+        if let Some(vault) = &config.vault_address {
+            outcomes.push(Outcome { name: "Vault", result: vault_details(&client, vault).await });
+        }
+    }
+    if config.enable_websocket {
+        outcomes.push(Outcome { name: "WebSocket", result: websocket(&websocket_url(&config.hyperliquid_api_url)).await });
+    }
+
+    let test_alert = alert::notice("Check", "Test message from hlp-toshogu check".to_string());
+    if config.mqtt.enabled {
+        let result = request_ok(mqtt::send_test(&config.mqtt, &test_alert)).await;
+        outcomes.push(Outcome {
+            name: "MQTT",
+            result: result.map(|_| format!("published a test alert to {}/info on {}", config.mqtt.alerts_topic, config.mqtt.broker)),
+        });
+    }
+    if config.daily_report.enabled && !config.daily_report.email_to.is_empty() {
+        let settings = config.daily_report.clone();
+        let result = tokio::task::spawn_blocking(move || daily_report::send_test_email(&settings))
+            .await
+            .unwrap_or_else(|e| Err(anyhow!(e)));
+        outcomes.push(Outcome {
+            name: "Email",
+            result: result.map(|_| format!("handed a test message for {} to {}", config.daily_report.email_to.join(", "), config.daily_report.sendmail_command)),
+        });
+    }
+    outcomes
+}
+
+async fn info_api(client: &InfoClient) -> Result<String> {
+    let started = Instant::now();
+    let meta = request(client.get_meta(MAIN_DEX)).await?;
+    let assets = meta.universe.len();
+    if assets == 0 {
+        return Err(anyhow!("meta lists no assets"));
+    }
+    Ok(format!("meta answered in {}ms with {} assets", started.elapsed().as_millis(), assets))
+}
+
+async fn account(client: &InfoClient, address: &str) -> Result<String> {
+    let state = request(client.get_clearinghouse_state(address, MAIN_DEX)).await?;
+    Ok(format!("{} holds ${:.2} in {} positions", address, state.margin_summary.account_value.0, state.asset_positions.len()))
+}
+
+async fn vault_details(client: &InfoClient, vault: &str) -> Result<String> {
+    let payload = serde_json::json!({ "type": "vaultDetails", "vaultAddress": vault });
+    match request(client.post_request("info", payload)).await? {
+        Value::Null => Err(anyhow!("{} is not a Hyperliquid vault", vault)),
+        details => Ok(format!("{} is the vault {:?}", vault, details["name"].as_str().unwrap_or("without a name"))),
+    }
+}
+
+/// Subscribes to `allMids` and waits for the first update, which proves the feed flows.
+async fn websocket(url: &str) -> Result<String> {
+    let started = Instant::now();
+    let feed = async {
+        let (mut stream, _) = connect_async(url).await?;
+        let subscribe = serde_json::json!({ "method": "subscribe", "subscription": { "type": "allMids" } });
+        stream.send(Message::Text(subscribe.to_string())).await?;
+        while let Some(message) = stream.next().await {
+            if let Message::Text(text) = message? {
+                if serde_json::from_str::<Value>(&text).is_ok_and(|value| value["channel"] == "allMids") {
+                    let _ = stream.close(None).await;
+                    return Ok(());
+                }
+            }
+        }
+        Err(anyhow!("closed before the first update"))
+    };
+    request_ok(feed).await?;
+    Ok(format!("{} sent its first update after {}ms", url, started.elapsed().as_millis()))
+}
+
+/// `request` for futures that return nothing.
+async fn request_ok(call: impl std::future::Future<Output = Result<()>>) -> Result<()> {
+    request(async { call.await.map(|_| Value::Null) }).await.map(|_| ())
+}
@@ -2,18 +2,115 @@ use anyhow::Result;
 use figment::{Figment, providers::{Format, Toml, Env}};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Files layered over this one, in order, relative to its directory: a shared
+    /// base kept in git, then local overrides.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
     pub operating_mode: OperatingMode,
     pub hyperliquid_api_url: String,
     pub hyperliquid_ws_url: String,
     pub user_address: String,
     pub vault_address: Option<String>,
+    /// Further vaults monitored with `vault_address`, accounts and all; the
+    /// dashboard shows their sum, and the Vaults panel each of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vaults: Vec<String>,
+    /// Vaults whose fills the PnL Breakdown panel splits into market making
+    /// and liquidation takeovers, as HLP runs them; other vaults are not split.
+    #[serde(default = "default_component_vaults")]
+    pub component_vaults: Vec<String>,
     pub enable_websocket: bool,
+    /// `update_interval = "2s"` works too; see `DURATION_KEYS`.
+    #[serde(deserialize_with = "millis")]
     pub update_interval_ms: u64,
+    /// Assets to monitor; empty monitors every asset in the exchange universe.
+    #[serde(default)]
+    pub monitored_assets: Vec<String>,
+    /// Builder-deployed (HIP-3) perp DEXs whose assets, named `dex:COIN`, join the main DEX's.
+    #[serde(default)]
+    pub perp_dexs: Vec<String>,
+    /// Monitor the sub-accounts of `user_address` with it; the `sub_accounts`
+    /// Overview panel shows each of them.
+    #[serde(default)]
+    pub sub_accounts: bool,
+    /// TOML file of `"0x…" = "label"` lines naming the traders of the tape.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_book: Option<String>,
     pub alert_thresholds: AlertThresholds,
     pub ui_settings: UiSettings,
+    #[serde(default)]
+    pub layout: LayoutSettings,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    pub raw_capture: RawCaptureSettings,
+    #[serde(default)]
+    pub prometheus: PrometheusSettings,
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
+    #[serde(default)]
+    pub grpc: GrpcSettings,
+    #[serde(default)]
+    pub ws_server: WsServerSettings,
+    #[serde(default)]
+    pub telemetry: TelemetrySettings,
+    #[serde(default)]
+    pub statsd: StatsdSettings,
+    #[serde(default)]
+    pub mqtt: MqttSettings,
+    #[serde(default)]
+    pub service: ServiceSettings,
+    #[serde(default)]
+    pub forensics: ForensicsSettings,
+    #[serde(default)]
+    pub daily_report: DailyReportSettings,
+    #[serde(default)]
+    pub upload: UploadSettings,
+    #[serde(default)]
+    pub control: ControlSettings,
+    #[serde(default)]
+    pub attach: AttachSettings,
+    #[serde(default)]
+    pub ssh: SshSettings,
+    #[serde(default)]
+    pub reload: ReloadSettings,
+    #[serde(default)]
+    pub log_file: LogFileSettings,
+    #[serde(default)]
+    pub streams: StreamSettings,
+    #[serde(default)]
+    pub buffers: BufferSettings,
+    #[serde(default)]
+    pub open_interest: OpenInterestSettings,
+    #[serde(default)]
+    pub backstop: BackstopSettings,
+    #[serde(default)]
+    pub whales: WhaleSettings,
+    #[serde(default)]
+    pub illiquid: IlliquidSettings,
+    #[serde(default)]
+    pub probation: ProbationSettings,
+    #[serde(default)]
+    pub demo: DemoSettings,
+    #[serde(default)]
+    pub chaos: ChaosSettings,
+    /// The file this was loaded from, set by `load_config`.
+    #[serde(skip)]
+    pub path: String,
+    /// Every file `include` pulled in, directly or through other includes.
+    #[serde(skip)]
+    pub included: Vec<String>,
+    /// Secrets filled in from the environment or keyring rather than the file.
+    #[serde(skip)]
+    pub secret_sources: Vec<(String, crate::secrets::Source)>,
+    /// The file's user address, vault address and vaults while `--user-address`,
+    /// `--vault-address` or `--vault` replace them, so saving the config keeps the file's.
+    #[serde(skip)]
+    pub file_addresses: Option<(String, Option<String>, Vec<String>)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,7 +119,10 @@ pub enum OperatingMode {
     Demo,
 }
 
+/// Warning/critical cut-offs shared by alert generation and the dashboard's
+/// value coloring: above `*_warning` is yellow, above `*_critical` is red.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AlertThresholds {
     pub vpin_warning: f64,
     pub vpin_critical: f64,
@@ -30,30 +130,726 @@ pub struct AlertThresholds {
     pub phantom_liquidity_critical: f64,
     pub liquidation_risk_warning: f64,
     pub liquidation_risk_critical: f64,
+    /// Liquidation cascade risk; colors the reading and, above the warning
+    /// level, recommends diversifying. It raises no alert of its own.
+    pub cascade_risk_warning: f64,
+    pub cascade_risk_critical: f64,
     pub max_drawdown_warning: f64,
     pub max_drawdown_critical: f64,
+    pub spread_warning_bps: f64,
+    pub spread_critical_bps: f64,
+    pub imbalance_warning: f64,
+    pub imbalance_critical: f64,
+    pub utilization_warning: f64,
+    pub utilization_critical: f64,
+    pub concentration_warning: f64,
+    pub concentration_critical: f64,
+    pub cancel_rate_warning: f64,
+    pub cancel_rate_critical: f64,
+    pub fleeting_ratio_warning: f64,
+    pub fleeting_ratio_critical: f64,
+    /// Net withdrawals from the vault within `withdrawal_window`, as a fraction
+    /// of its TVL at the start of the window.
+    pub withdrawal_warning: f64,
+    pub withdrawal_critical: f64,
+    #[serde(deserialize_with = "millis")]
+    pub withdrawal_window_ms: u64,
+    /// Fees paid on the recent fills beyond the account's fee schedule, builder
+    /// fees included, in basis points of their notional.
+    pub fee_excess_warning_bps: f64,
+    pub fee_excess_critical_bps: f64,
+    /// Distance of an asset's mark price from its oracle price, as a fraction
+    /// of the oracle price.
+    pub oracle_divergence_warning: f64,
+    pub oracle_divergence_critical: f64,
+    /// Open interest of an asset the account holds, as a fraction of its cap.
+    pub oi_cap_warning: f64,
+    pub oi_cap_critical: f64,
+    /// Fall of the backstop accounts' total value from its high over
+    /// `backstop.window_secs`, as a fraction of the high.
+    pub backstop_drawdown_warning: f64,
+    pub backstop_drawdown_critical: f64,
+    /// Seconds the newest L1 block time seen is behind the local clock.
+    pub chain_lag_warning_secs: f64,
+    pub chain_lag_critical_secs: f64,
+    /// Share of the recent streamed volume traded in large prints (see `whales`).
+    pub whale_activity_warning: f64,
+    pub whale_activity_critical: f64,
+    /// Share of the recent streamed volume traded by the most active trader;
+    /// colors the Top Trader reading and raises no alert of its own.
+    pub top_trader_share_warning: f64,
+    pub top_trader_share_critical: f64,
+    /// p95 round trip of the recent info requests, in ms; colors the status
+    /// bar's latency.
+    pub api_latency_warning_ms: f64,
+    pub api_latency_critical_ms: f64,
+    /// Distance of a position's mark from its liquidation price, as a fraction
+    /// of the mark. Lower is worse, so warning sits above critical.
+    pub liquidation_distance_warning: f64,
+    pub liquidation_distance_critical: f64,
+    /// Gap between a streamed reading and its polled twin in the `shadow`
+    /// panel, as a fraction of the polled one; colors only.
+    pub shadow_gap_warning: f64,
+    pub shadow_gap_critical: f64,
+    /// What the spread, imbalance, oracle divergence and VPIN thresholds are
+    /// scaled by for an asset on probation (see `probation`).
+    pub probation_factor: f64,
+}
+
+impl AlertThresholds {
+    /// The pairs of `pairs` whose readings are worse the lower they go.
+    pub const FALLING: [&'static str; 1] = ["liquidation_distance"];
+
+    pub fn pairs(&self) -> [(&'static str, f64, f64); 22] {
+        [
+            ("vpin", self.vpin_warning, self.vpin_critical),
+            ("phantom_liquidity", self.phantom_liquidity_warning, self.phantom_liquidity_critical),
+            ("liquidation_risk", self.liquidation_risk_warning, self.liquidation_risk_critical),
+            ("cascade_risk", self.cascade_risk_warning, self.cascade_risk_critical),
+            ("max_drawdown", self.max_drawdown_warning, self.max_drawdown_critical),
+            ("spread", self.spread_warning_bps, self.spread_critical_bps),
+            ("imbalance", self.imbalance_warning, self.imbalance_critical),
+            ("utilization", self.utilization_warning, self.utilization_critical),
+            ("concentration", self.concentration_warning, self.concentration_critical),
+            ("cancel_rate", self.cancel_rate_warning, self.cancel_rate_critical),
+            ("fleeting_ratio", self.fleeting_ratio_warning, self.fleeting_ratio_critical),
+            ("withdrawal", self.withdrawal_warning, self.withdrawal_critical),
+            ("fee_excess", self.fee_excess_warning_bps, self.fee_excess_critical_bps),
+            ("oracle_divergence", self.oracle_divergence_warning, self.oracle_divergence_critical),
+            ("oi_cap", self.oi_cap_warning, self.oi_cap_critical),
+            ("backstop_drawdown", self.backstop_drawdown_warning, self.backstop_drawdown_critical),
+            ("chain_lag", self.chain_lag_warning_secs, self.chain_lag_critical_secs),
+            ("whale_activity", self.whale_activity_warning, self.whale_activity_critical),
+            ("top_trader_share", self.top_trader_share_warning, self.top_trader_share_critical),
+            ("api_latency", self.api_latency_warning_ms, self.api_latency_critical_ms),
+            ("liquidation_distance", self.liquidation_distance_warning, self.liquidation_distance_critical),
+            ("shadow_gap", self.shadow_gap_warning, self.shadow_gap_critical),
+        ]
+    }
+
+    /// Whether a pair of `pairs` is reached warning first: warning below critical,
+    /// or above it for the `FALLING` pairs.
+    pub fn ordered(name: &str, warning: f64, critical: f64) -> bool {
+        match Self::FALLING.contains(&name) {
+            true => warning > critical,
+            false => warning < critical,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UiSettings {
+    #[serde(deserialize_with = "millis")]
     pub refresh_rate_ms: u64,
     pub theme: String,
     pub show_debug_info: bool,
     pub auto_scroll_alerts: bool,
+    /// Ask before quitting, listing any unacknowledged Critical alerts.
+    pub confirm_quit: bool,
+    /// Save the active tab and chart selection on exit and restore them on startup.
+    pub persist_state: bool,
+    pub state_file: String,
+    /// Draw with plain ASCII only, for basic SSH sessions and fonts without good Unicode coverage.
+    pub ascii_mode: bool,
+    /// Show min/max/avg metrics, alerts and uptime for the session before exiting.
+    pub session_summary: bool,
+    /// Also append each session summary to this file.
+    pub session_summary_file: Option<String>,
+    /// Thousands and decimal separators of amounts, prices and axis labels.
+    pub locale: Locale,
+    /// Amounts as "$109.5M" rather than "$109,512,345.67".
+    pub compact_numbers: bool,
+    /// The zone times are shown in.
+    pub timezone: Timezone,
+    /// What amounts are shown in at startup; `D` cycles through them.
+    pub denomination: Denomination,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Denomination {
+    Usd,
+    /// At the BTC book's mid.
+    Btc,
+    /// In shares of the vault, at its share price.
+    Vault,
+}
+
+impl Denomination {
+    pub fn next(self) -> Self {
+        match self {
+            Denomination::Usd => Denomination::Btc,
+            Denomination::Btc => Denomination::Vault,
+            Denomination::Vault => Denomination::Usd,
+        }
+    }
+}
+
+/// "UTC", "local" for the machine's zone, or a fixed offset such as "+09:00"
+/// or "UTC-5". Named zones need a time zone database this build does not
+/// carry; a fixed offset does not follow daylight saving.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Timezone {
+    Utc,
+    Local,
+    Fixed(chrono::FixedOffset),
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+    
+    fn try_from(text: String) -> std::result::Result<Self, String> {
+        let invalid = || format!("invalid timezone {:?}, expected \"UTC\", \"local\" or an offset such as \"+09:00\"", text);
+        if text.contains('/') {
+            return Err(format!(
+                "named timezone {:?} is not supported; use \"local\" to follow the machine's zone and its daylight saving, or a fixed offset such as \"-05:00\"",
+                text
+            ));
+        }
+        match text.trim().to_ascii_lowercase().as_str() {
+            "utc" | "z" => Ok(Timezone::Utc),
+            "local" => Ok(Timezone::Local),
+            zone => {
+                let offset = zone.strip_prefix("utc").unwrap_or(zone);
+                let (sign, rest) = match offset.split_at_checked(1) {
+                    Some(("+", rest)) => (1, rest),
+                    Some(("-", rest)) => (-1, rest),
+                    _ => return Err(invalid()),
+                };
+                let (hours, minutes) = match rest.split_once(':') {
+                    Some((hours, minutes)) => (hours, minutes),
+                    None if rest.len() == 4 => rest.split_at(2),
+                    None => (rest, "0"),
+                };
+                let (Ok(hours), Ok(minutes)) = (hours.parse::<u32>(), minutes.parse::<u32>()) else {
+                    return Err(invalid());
+                };
+                if hours > 14 || minutes >= 60 {
+                    return Err(invalid());
+                }
+                chrono::FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60) as i32).map(Timezone::Fixed).ok_or_else(invalid)
+            }
+        }
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(zone: Timezone) -> String {
+        match zone {
+            Timezone::Utc => "UTC".to_string(),
+            Timezone::Local => "local".to_string(),
+            Timezone::Fixed(offset) => offset.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Locale {
+    /// 1,234,567.89
+    #[serde(rename = "en-US")]
+    EnUs,
+    /// 1.234.567,89
+    #[serde(rename = "de-DE")]
+    DeDe,
+    /// 1 234 567,89, grouped with narrow spaces.
+    #[serde(rename = "fr-FR")]
+    FrFr,
+    /// 1'234'567.89
+    #[serde(rename = "de-CH")]
+    DeCh,
+}
+
+/// SQLite metric history (see `storage::MetricStore`). Live mode only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageSettings {
+    pub enabled: bool,
+    pub path: String,
+    /// Delete samples older than this.
+    pub retention_days: u64,
+    /// Keep every sample this recent; older ones are thinned to one per `downsample_interval_secs`.
+    pub full_resolution_hours: u64,
+    pub downsample_interval_secs: u64,
+}
+
+/// Hourly Parquet files of the raw WebSocket streams (see `storage::raw::RawCapture`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RawCaptureSettings {
+    pub enabled: bool,
+    pub dir: String,
+    /// Start a new file once a stream has buffered this many rows within the hour.
+    pub max_rows_per_file: usize,
+}
+
+/// HTTP endpoint serving `/metrics` for Prometheus (see `exporter`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrometheusSettings {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+/// HTTP endpoint serving the current metrics, alerts and positions as JSON (see `api_server`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+/// Plaintext gRPC endpoint streaming metrics and alerts (see `server`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+/// Local WebSocket endpoint pushing metrics and alerts as JSON every update (see `ws_server`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WsServerSettings {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+/// OTLP/HTTP trace export of the collection pipeline (see `telemetry`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    /// Collector traces endpoint, e.g. Jaeger or Tempo's OTLP/HTTP receiver.
+    pub endpoint: String,
+    pub service_name: String,
+    /// Most verbose spans to export: "info", or "debug" to add one span per WebSocket message.
+    pub level: String,
+    pub export_interval_secs: u64,
+}
+
+/// UDP sink sending gauges and alert counts to a StatsD or DogStatsD agent (see `statsd`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsdSettings {
+    pub enabled: bool,
+    pub address: String,
+    /// Prepended to every metric name with a dot; empty for none.
+    pub prefix: String,
+    /// DogStatsD tags added to every metric, e.g. ["env:prod", "vault:hlp"].
+    pub tags: Vec<String>,
+    /// Use DogStatsD tags; plain StatsD folds per-asset keys into the metric name.
+    pub dogstatsd: bool,
+}
+
+/// MQTT 3.1.1 publisher for snapshots and alerts (see `mqtt`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    /// Broker `host:port`; plain TCP, no TLS.
+    pub broker: String,
+    pub client_id: String,
+    pub username: Option<String>,
+    /// Only with `username`, which MQTT 3.1.1 requires for a password.
+    pub password: Option<String>,
+    pub metrics_topic: String,
+    /// Alerts go to `<alerts_topic>/info`, `/warning` and `/critical`.
+    pub alerts_topic: String,
+    /// Retained `online`/`offline` topic; empty to disable.
+    pub status_topic: String,
+    pub retain_metrics: bool,
+    pub keep_alive_secs: u64,
+}
+
+/// Bundles written when a Critical alert fires (see `forensics`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForensicsSettings {
+    pub enabled: bool,
+    pub dir: String,
+    /// Book snapshots kept per asset.
+    pub l2_snapshots: usize,
+    /// Trades kept per asset.
+    pub trades: usize,
+    /// Order events kept per asset.
+    pub order_events: usize,
+    /// Minimum time between two bundles for the same metric.
+    pub cooldown_secs: u64,
+}
+
+/// Report rendered every day (see `daily_report`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyReportSettings {
+    pub enabled: bool,
+    pub dir: String,
+    /// Time of day in UTC, as HH:MM; each report covers the 24 hours before it.
+    pub time: String,
+    pub formats: Vec<DailyFormat>,
+    /// Recipients; no email is sent when empty.
+    pub email_to: Vec<String>,
+    pub email_from: String,
+    /// Reads the message on stdin, e.g. `sendmail -t` or `msmtp -t`.
+    pub sendmail_command: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DailyFormat {
+    Html,
+    Markdown,
+}
+
+/// Shipping sessions, forensic bundles and daily reports to S3 (see `upload`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadSettings {
+    pub enabled: bool,
+    /// `https://s3.<region>.amazonaws.com`, or a MinIO / R2 / B2 endpoint.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+    /// Address the bucket as `<endpoint>/<bucket>` instead of `<bucket>.<endpoint host>`,
+    /// as most self-hosted stores need.
+    pub path_style: bool,
+    /// Falls back to `AWS_ACCESS_KEY_ID` when empty.
+    pub access_key_id: String,
+    /// Falls back to `AWS_SECRET_ACCESS_KEY` when empty.
+    pub secret_access_key: String,
+    /// Falls back to `AWS_SESSION_TOKEN`.
+    pub session_token: Option<String>,
+    pub interval_secs: u64,
+    /// Set by `record --out`.
+    #[serde(skip)]
+    pub session_dir: Option<std::path::PathBuf>,
+}
+
+/// Applying edits to the config file while running (see `reload`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReloadSettings {
+    pub enabled: bool,
+    /// How often the file's modification time is checked.
+    #[serde(deserialize_with = "millis")]
+    pub poll_interval_ms: u64,
+}
+
+/// Local command socket for scripts and key bindings (see `control`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub enabled: bool,
+    /// Path of the Unix socket; created with mode 0600.
+    pub socket: String,
+}
+
+/// Read-only dashboards for other terminals on this host (see `attach`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachSettings {
+    pub enabled: bool,
+    /// Path of the Unix socket `hlp-toshogu attach` connects to; created with mode 0600.
+    pub socket: String,
+}
+
+/// Read-only dashboards served over SSH to the users listed here (see `ssh`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshSettings {
+    pub enabled: bool,
+    pub listen: String,
+    /// PKCS#8 PEM Ed25519 key; created with mode 0600 when missing.
+    pub host_key: String,
+    /// Each user's public keys, as `authorized_keys` lines.
+    pub users: std::collections::BTreeMap<String, Vec<String>>,
+    /// Connections over this are turned away.
+    pub max_sessions: usize,
+}
+
+/// Copy of the log kept on disk whether the Logs tab or stderr shows it (see `logfile`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFileSettings {
+    pub enabled: bool,
+    pub path: String,
+    /// error, warn, info, debug or trace.
+    pub level: String,
+    /// The file is rotated to `<path>.1` once it would grow past this.
+    pub max_size_mb: u64,
+    /// Rotated files kept, `<path>.1` being the newest.
+    pub keep: usize,
+}
+
+/// Queues between the WebSocket reader and the streaming consumers (see `pipeline`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamSettings {
+    /// Messages each consumer may fall behind by, per stream.
+    pub capacity: usize,
+    pub overflow: Overflow,
+}
+
+/// What a stream does when a consumer's queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    /// Discard the oldest queued message and count it as dropped.
+    DropOldest,
+    /// Stop reading the WebSocket until the consumer catches up, so nothing is
+    /// lost but every stream and the exchange connection wait for the slowest consumer.
+    Block,
+}
+
+/// Sizes of the streaming engine's buffers, which drop their oldest entries when
+/// full (see `ring::RingSeries`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BufferSettings {
+    /// Recent streamed trades.
+    pub trades: usize,
+    /// Completed VPIN buckets averaged into the streaming VPIN.
+    pub vpin_window: usize,
+    /// Lifetimes of recent orders, behind the fleeting-order figures.
+    pub order_lifetimes: usize,
+    /// Refuse to start when `estimated_memory_mb` exceeds this; 0 for no cap.
+    pub memory_cap_mb: usize,
+}
+
+impl BufferSettings {
+    /// Memory the engine's buffers, the stream queues and the metric history take
+    /// when all are full, with books at 20 levels a side. An estimate of the
+    /// bounded state, not of the process.
+    pub fn estimated_memory_bytes(&self, config: &Config) -> usize {
+        use crate::model::{Candle, Fill, L2Snapshot, OrderBookLevel, OrderEvent};
+        use std::mem::size_of;
+        // Inline size plus a coin name and a transaction hash.
+        let fill = size_of::<Fill>() + 72;
+        let book = size_of::<L2Snapshot>() + 40 * size_of::<OrderBookLevel>();
+        let consumers = 1 + usize::from(config.forensics.enabled) + usize::from(config.raw_capture.enabled);
+        let queues = config.streams.capacity * (consumers * (fill + book + size_of::<OrderEvent>()) + size_of::<Candle>());
+        let engine = self.trades * fill + self.vpin_window * size_of::<f64>() + self.order_lifetimes * size_of::<u64>();
+        let history = crate::history::HistoryMetric::ALL.len() * crate::history::SERIES_BUDGET_BYTES;
+        queues + engine + history
+    }
+}
+
+/// Open interest caps of the assets, which the exchange enforces but doesn't
+/// publish; an asset it reports at its cap counts as full either way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenInterestSettings {
+    /// Notional cap in USD by asset.
+    pub caps_usd: std::collections::BTreeMap<String, f64>,
+}
+
+/// What makes a streamed trade a large print.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhaleSettings {
+    /// Notional over this multiple of the asset's average trade notional.
+    pub multiple: f64,
+    /// Recent trades of an asset its average is taken over; none is flagged
+    /// before this many have been seen.
+    pub average_trades: usize,
+}
+
+/// When an asset counts as in illiquid hours: trading at under
+/// `activity_fraction` of its usual rate while quoting over `spread_multiple`
+/// times its usual spread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IlliquidSettings {
+    pub enabled: bool,
+    /// What "recent" means for the trade rate.
+    pub window_secs: u64,
+    /// What "usual" means for the trade rate and spread.
+    pub baseline_secs: u64,
+    pub activity_fraction: f64,
+    pub spread_multiple: f64,
+}
+
+/// How long a newly listed asset stays under tightened thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProbationSettings {
+    pub enabled: bool,
+    pub hours: u64,
+}
+
+/// The simulated market behind demo mode and `run --test-mode`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DemoSettings {
+    pub regime: Regime,
+    /// The same seed plays the same market, cycle for cycle.
+    pub seed: u64,
+    /// Start from a fresh seed each run instead; it is logged so a run can be repeated.
+    pub random_seed: bool,
+    /// A bundled dataset ("hour") or a dataset file whose prices and regimes
+    /// the market plays minute by minute, looping, instead of `regime`.
+    pub dataset: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    /// Tight spreads, deep books and balanced flow.
+    Calm,
+    /// Large correlated moves with jumps, thin books and one-sided flow.
+    Volatile,
+    /// Walls that are pulled before they trade, heavy cancelling and one dominant trader.
+    Manipulated,
+}
+
+/// Faults injected into the Hyperliquid connection (see `api::chaos`). Each
+/// rate is the chance a request or streamed message is hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChaosSettings {
+    pub enabled: bool,
+    /// Info requests answered 429 Too Many Requests.
+    pub http_429_rate: f64,
+    /// Info requests answered 500 Internal Server Error.
+    pub http_500_rate: f64,
+    /// Responses and messages cut off part way.
+    pub malformed_rate: f64,
+    /// Requests and messages held back for up to `max_delay_ms`.
+    pub delay_rate: f64,
+    #[serde(deserialize_with = "millis")]
+    pub max_delay_ms: u64,
+    /// Streamed messages delivered twice.
+    pub duplicate_rate: f64,
+    /// Mean lifetime of a WebSocket connection before it is dropped; 0 never drops it.
+    pub disconnect_secs: u64,
+}
+
+/// The exchange's backstop accounts, whose balance is a gauge of exchange-wide
+/// liquidation stress (see `backstop`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackstopSettings {
+    pub enabled: bool,
+    /// Addresses by display name.
+    pub accounts: std::collections::BTreeMap<String, String>,
+    /// Vaults by display name, each counted with its child vaults; HLP's
+    /// liquidator takes over the positions the order book can't absorb.
+    pub vaults: std::collections::BTreeMap<String, String>,
+    pub interval_secs: u64,
+    /// How far back the drawdown's high reaches.
+    pub window_secs: u64,
+}
+
+/// Running as a service, e.g. under systemd (see `systemd`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceSettings {
+    /// Set by `--headless`.
+    #[serde(skip)]
+    pub headless: bool,
+    /// Set by `--output ndjson`.
+    #[serde(skip)]
+    pub ndjson: bool,
+    /// Withhold the systemd watchdog once no collection cycle has finished for this long.
+    pub stall_timeout_secs: u64,
+}
+
+/// Arrangement of the Overview tab: rows stacked top to bottom, each holding
+/// panels side by side. `size` is a relative weight within the parent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutSettings {
+    pub overview: Vec<LayoutRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutRow {
+    pub size: u16,
+    pub panels: Vec<PanelSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelSpec {
+    pub panel: OverviewPanel,
+    pub size: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverviewPanel {
+    Vault,
+    /// A row per vault when several are monitored together.
+    Vaults,
+    /// A row per sub-account, and the master, when they are monitored.
+    SubAccounts,
+    /// The latest streamed market trades and their traders.
+    TradeTape,
+    /// The latest streamed trades far larger than their asset's average.
+    LargePrints,
+    /// Newly listed assets on probation, against their tightened thresholds.
+    NewListings,
+    Risk,
+    Liquidity,
+    Performance,
+    Health,
+    SystemStatus,
+    Alerts,
+    /// The streamed VPIN, PLI and spreads against the poll-based calculators'.
+    Shadow,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            include: Vec::new(),
             operating_mode: OperatingMode::Live,
             hyperliquid_api_url: "https://api.hyperliquid.xyz".to_string(),
             hyperliquid_ws_url: "wss://api.hyperliquid.xyz/ws".to_string(),
-            user_address: "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303".to_string(),
+            user_address: HLP_VAULT.to_string(),
             vault_address: None,
+            vaults: Vec::new(),
+            component_vaults: default_component_vaults(),
             enable_websocket: true,
             update_interval_ms: 1000,
+            monitored_assets: Vec::new(),
+            perp_dexs: Vec::new(),
+            sub_accounts: false,
+            address_book: None,
             alert_thresholds: AlertThresholds::default(),
             ui_settings: UiSettings::default(),
+            layout: LayoutSettings::default(),
+            storage: StorageSettings::default(),
+            raw_capture: RawCaptureSettings::default(),
+            prometheus: PrometheusSettings::default(),
+            http_api: HttpApiSettings::default(),
+            grpc: GrpcSettings::default(),
+            ws_server: WsServerSettings::default(),
+            telemetry: TelemetrySettings::default(),
+            statsd: StatsdSettings::default(),
+            mqtt: MqttSettings::default(),
+            service: ServiceSettings::default(),
+            forensics: ForensicsSettings::default(),
+            daily_report: DailyReportSettings::default(),
+            upload: UploadSettings::default(),
+            control: ControlSettings::default(),
+            attach: AttachSettings::default(),
+            ssh: SshSettings::default(),
+            reload: ReloadSettings::default(),
+            log_file: LogFileSettings::default(),
+            streams: StreamSettings::default(),
+            buffers: BufferSettings::default(),
+            open_interest: OpenInterestSettings::default(),
+            backstop: BackstopSettings::default(),
+            whales: WhaleSettings::default(),
+            illiquid: IlliquidSettings::default(),
+            probation: ProbationSettings::default(),
+            demo: DemoSettings::default(),
+            chaos: ChaosSettings::default(),
+            path: "config.toml".to_string(),
+            included: Vec::new(),
+            secret_sources: Vec::new(),
+            file_addresses: None,
         }
     }
 }
@@ -67,8 +863,46 @@ impl Default for AlertThresholds {
             phantom_liquidity_critical: 0.6,
             liquidation_risk_warning: 0.7,
             liquidation_risk_critical: 0.85,
+            cascade_risk_warning: 0.5,
+            cascade_risk_critical: 0.7,
             max_drawdown_warning: 0.15,
             max_drawdown_critical: 0.25,
+            spread_warning_bps: 5.0,
+            spread_critical_bps: 15.0,
+            imbalance_warning: 0.3,
+            imbalance_critical: 0.6,
+            utilization_warning: 0.75,
+            utilization_critical: 0.9,
+            concentration_warning: 0.1,
+            concentration_critical: 0.15,
+            cancel_rate_warning: 0.3,
+            cancel_rate_critical: 0.5,
+            fleeting_ratio_warning: 0.1,
+            fleeting_ratio_critical: 0.2,
+            withdrawal_warning: 0.05,
+            withdrawal_critical: 0.1,
+            withdrawal_window_ms: 3_600_000,
+            fee_excess_warning_bps: 0.5,
+            fee_excess_critical_bps: 2.0,
+            oracle_divergence_warning: 0.005,
+            oracle_divergence_critical: 0.02,
+            oi_cap_warning: 0.8,
+            oi_cap_critical: 0.95,
+            backstop_drawdown_warning: 0.05,
+            backstop_drawdown_critical: 0.15,
+            chain_lag_warning_secs: 10.0,
+            chain_lag_critical_secs: 60.0,
+            whale_activity_warning: 0.3,
+            whale_activity_critical: 0.5,
+            top_trader_share_warning: 0.3,
+            top_trader_share_critical: 0.6,
+            api_latency_warning_ms: 250.0,
+            api_latency_critical_ms: 1000.0,
+            liquidation_distance_warning: 0.15,
+            liquidation_distance_critical: 0.05,
+            shadow_gap_warning: 0.1,
+            shadow_gap_critical: 0.5,
+            probation_factor: 0.5,
         }
     }
 }
@@ -80,50 +914,846 @@ impl Default for UiSettings {
             theme: "dark".to_string(),
             show_debug_info: false,
             auto_scroll_alerts: true,
+            confirm_quit: true,
+            persist_state: true,
+            state_file: ".hlp-toshogu-state.json".to_string(),
+            ascii_mode: false,
+            session_summary: true,
+            session_summary_file: None,
+            locale: Locale::EnUs,
+            compact_numbers: true,
+            timezone: Timezone::Utc,
+            denomination: Denomination::Usd,
+        }
+    }
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "hlp-toshogu.db".to_string(),
+            retention_days: 30,
+            full_resolution_hours: 24,
+            downsample_interval_secs: 60,
+        }
+    }
+}
+
+impl Default for RawCaptureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "data/raw".to_string(),
+            max_rows_per_file: 100_000,
+        }
+    }
+}
+
+impl Default for PrometheusSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:9184".to_string(),
+        }
+    }
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:9185".to_string(),
+        }
+    }
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:50051".to_string(),
+        }
+    }
+}
+
+impl Default for WsServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:9186".to_string(),
+        }
+    }
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:4318/v1/traces".to_string(),
+            service_name: "hlp-toshogu".to_string(),
+            level: "info".to_string(),
+            export_interval_secs: 5,
+        }
+    }
+}
+
+impl Default for StatsdSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:8125".to_string(),
+            prefix: "hlp".to_string(),
+            tags: Vec::new(),
+            dogstatsd: true,
+        }
+    }
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker: "127.0.0.1:1883".to_string(),
+            client_id: "hlp-toshogu".to_string(),
+            username: None,
+            password: None,
+            metrics_topic: "hlp/metrics".to_string(),
+            alerts_topic: "hlp/alerts".to_string(),
+            status_topic: "hlp/status".to_string(),
+            retain_metrics: true,
+            keep_alive_secs: 30,
+        }
+    }
+}
+
+impl Default for ForensicsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "forensics".to_string(),
+            l2_snapshots: 20,
+            trades: 200,
+            order_events: 200,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+impl Default for DailyReportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "reports".to_string(),
+            time: "00:00".to_string(),
+            formats: vec![DailyFormat::Html, DailyFormat::Markdown],
+            email_to: Vec::new(),
+            email_from: "hlp-toshogu@localhost".to_string(),
+            sendmail_command: "sendmail -t".to_string(),
+        }
+    }
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "https://s3.us-east-1.amazonaws.com".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            prefix: "hlp-toshogu".to_string(),
+            path_style: false,
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            session_token: None,
+            interval_secs: 300,
+            session_dir: None,
+        }
+    }
+}
+
+impl Default for ReloadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket: "hlp-toshogu.sock".to_string(),
+        }
+    }
+}
+
+impl Default for AttachSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket: "hlp-toshogu-attach.sock".to_string(),
+        }
+    }
+}
+
+impl Default for SshSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:2222".to_string(),
+            host_key: "hlp-toshogu-host-key.pem".to_string(),
+            users: std::collections::BTreeMap::new(),
+            max_sessions: 16,
+        }
+    }
+}
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "hlp-toshogu.log".to_string(),
+            level: "info".to_string(),
+            max_size_mb: 10,
+            keep: 5,
+        }
+    }
+}
+
+impl Default for StreamSettings {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            overflow: Overflow::DropOldest,
+        }
+    }
+}
+
+impl Default for BufferSettings {
+    fn default() -> Self {
+        Self {
+            trades: 5000,
+            vpin_window: 50,
+            order_lifetimes: 1_000,
+            memory_cap_mb: 0,
+        }
+    }
+}
+
+impl Default for BackstopSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            accounts: [("Assistance Fund".to_string(), "0xfefefefefefefefefefefefefefefefefefefefe".to_string())].into(),
+            vaults: [("HLP".to_string(), HLP_VAULT.to_string())].into(),
+            interval_secs: 300,
+            window_secs: 6 * 60 * 60,
+        }
+    }
+}
+
+impl Default for WhaleSettings {
+    fn default() -> Self {
+        Self {
+            multiple: 10.0,
+            average_trades: 100,
+        }
+    }
+}
+
+impl Default for IlliquidSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 300,
+            baseline_secs: 24 * 60 * 60,
+            activity_fraction: 0.25,
+            spread_multiple: 2.0,
+        }
+    }
+}
+
+impl Default for ProbationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hours: 24,
+        }
+    }
+}
+
+impl Default for DemoSettings {
+    fn default() -> Self {
+        Self {
+            regime: Regime::Calm,
+            seed: 1,
+            random_seed: false,
+            dataset: None,
+        }
+    }
+}
+
+impl Default for ChaosSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_429_rate: 0.05,
+            http_500_rate: 0.02,
+            malformed_rate: 0.02,
+            delay_rate: 0.05,
+            max_delay_ms: 3000,
+            duplicate_rate: 0.01,
+            disconnect_secs: 120,
+        }
+    }
+}
+
+impl Default for ServiceSettings {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            ndjson: false,
+            stall_timeout_secs: 60,
+        }
+    }
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        use OverviewPanel::*;
+
+        let row = |size, panels: &[OverviewPanel]| LayoutRow {
+            size,
+            panels: panels.iter().map(|&panel| PanelSpec { panel, size: 1 }).collect(),
+        };
+
+        Self {
+            overview: vec![
+                row(4, &[Vault, Risk, Liquidity, Performance]),
+                row(3, &[Health, SystemStatus]),
+                row(5, &[Alerts]),
+            ],
         }
     }
 }
 
 pub fn load_config(config_path: Option<&str>) -> Result<Config> {
+    let config = read_config(config_path)?;
+    
+    validate_config(&config)?;
+    
+    Ok(config)
+}
+
+/// Parses the config without checking the values, for `config validate`.
+pub fn read_config(config_path: Option<&str>) -> Result<Config> {
     let config_file = config_path.unwrap_or("config.toml");
     
-    let config: Config = Figment::new()
-        .merge(Toml::file(config_file))
-        .merge(Env::prefixed("HLP_"))
+    let mut files = Vec::new();
+    let mut config: Config = layer(Figment::new(), Path::new(config_file), &mut files)?
+        .merge(Durations(Env::prefixed("HLP_")))
         .extract()?;
-    
-    validate_config(&config)?;
+    // Merging replaced the list with the last included file's.
+    config.include = Figment::from(Toml::file(config_file)).extract_inner("include").unwrap_or_default();
+    config.path = config_file.to_string();
+    config.included = files.iter().skip(1).map(|file| file.display().to_string()).collect();
+    crate::secrets::resolve(&mut config);
     
     Ok(config)
 }
 
-pub fn generate_sample_config() -> Result<()> {
+/// The HLP vault, Hyperliquid's protocol vault.
+pub const HLP_VAULT: &str = "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303";
+
+fn default_component_vaults() -> Vec<String> {
+    vec![HLP_VAULT.to_string()]
+}
+
+/// Vaults `--vault` knows by name.
+const VAULT_PRESETS: [(&str, &str); 1] = [("hlp", HLP_VAULT)];
+
+/// The address of `--vault`: a preset name, in any case, or an address.
+pub fn vault_preset(vault: &str) -> Result<String> {
+    if let Some((_, address)) = VAULT_PRESETS.iter().find(|(name, _)| name.eq_ignore_ascii_case(vault)) {
+        return Ok(address.to_string());
+    }
+    if !is_address(vault) {
+        let names: Vec<&str> = VAULT_PRESETS.iter().map(|(name, _)| *name).collect();
+        return Err(anyhow::anyhow!("--vault must be one of {} or 0x followed by 40 hex digits, got {:?}", names.join(", "), vault));
+    }
+    Ok(vault.to_string())
+}
+
+/// Points this run at another account or vault without editing the file.
+pub fn override_addresses(config: &mut Config, user_address: Option<&str>, vault_address: Option<&str>) -> Result<()> {
+    if user_address.is_none() && vault_address.is_none() {
+        return Ok(());
+    }
+    for (flag, address) in [("--user-address", user_address), ("--vault-address", vault_address)] {
+        if let Some(address) = address.filter(|address| !is_address(address)) {
+            return Err(anyhow::anyhow!("{} must be 0x followed by 40 hex digits, got {:?}", flag, address));
+        }
+    }
+    keep_file_addresses(config);
+    if let Some(address) = user_address {
+        config.user_address = address.to_string();
+    }
+    if let Some(address) = vault_address {
+        config.vault_address = Some(address.to_string());
+    }
+    Ok(())
+}
+
+/// Monitors `vaults`, from `vault_preset`, for this run: the first as both
+/// account and vault, and the rest along with it.
+pub fn override_vaults(config: &mut Config, vaults: &[String]) {
+    let Some((first, rest)) = vaults.split_first() else {
+        return;
+    };
+    keep_file_addresses(config);
+    config.user_address = first.clone();
+    config.vault_address = Some(first.clone());
+    config.vaults = rest.to_vec();
+}
+
+fn keep_file_addresses(config: &mut Config) {
+    if config.file_addresses.is_none() {
+        config.file_addresses = Some((config.user_address.clone(), config.vault_address.clone(), config.vaults.clone()));
+    }
+}
+
+/// Millisecond settings that can also be written as a duration under the name
+/// without `_ms`, as (section, name); `""` is the top level.
+const DURATION_KEYS: [(&str, &str); 5] = [
+    ("", "update_interval"),
+    ("ui_settings", "refresh_rate"),
+    ("reload", "poll_interval"),
+    ("alert_thresholds", "withdrawal_window"),
+    ("chaos", "max_delay"),
+];
+
+/// Renames `update_interval = "2s"` and the like to their `_ms` keys before
+/// merging, so a layer using one form replaces a layer using the other.
+struct Durations<P>(P);
+
+impl<P: figment::Provider> figment::Provider for Durations<P> {
+    fn metadata(&self) -> figment::Metadata {
+        self.0.metadata()
+    }
+    
+    fn data(&self) -> std::result::Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut data = self.0.data()?;
+        for dict in data.values_mut() {
+            for (section, name) in DURATION_KEYS {
+                let table = match section {
+                    "" => Some(&mut *dict),
+                    section => match dict.get_mut(section) {
+                        Some(figment::value::Value::Dict(_, table)) => Some(table),
+                        _ => None,
+                    },
+                };
+                let Some(table) = table else { continue };
+                let Some(value) = table.remove(name) else { continue };
+                let key = format!("{}_ms", name);
+                if table.contains_key(&key) {
+                    let setting = if section.is_empty() { name.to_string() } else { format!("{}.{}", section, name) };
+                    return Err(format!("set either {} or {}_ms, not both", setting, setting).into());
+                }
+                table.insert(key, value);
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Merges `path`, then each file its `include` lists over it, in order.
+fn layer(figment: Figment, path: &Path, files: &mut Vec<PathBuf>) -> Result<Figment> {
+    if files.iter().any(|file| file == path) {
+        return Err(anyhow::anyhow!("{} is included more than once", path.display()));
+    }
+    files.push(path.to_path_buf());
+    
+    let file = Figment::from(Durations(Toml::file(path)));
+    let includes: Vec<String> = match file.extract_inner("include") {
+        Ok(includes) => includes,
+        Err(e) if e.missing() => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut figment = figment.merge(file);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        let included = dir.join(&include);
+        if !included.is_file() {
+            return Err(anyhow::anyhow!("{} includes {}, which does not exist", path.display(), included.display()));
+        }
+        figment = layer(figment, &included, files)?;
+    }
+    Ok(figment)
+}
+
+pub fn generate_sample_config(path: &str) -> Result<()> {
     let config = Config::default();
     let toml_content = toml::to_string_pretty(&config)?;
     
-    fs::write("config.toml", toml_content)?;
+    fs::write(path, toml_content)?;
     
     Ok(())
 }
 
 fn validate_config(config: &Config) -> Result<()> {
-    if config.user_address == "0x" {
-        return Err(anyhow::anyhow!("Please set a valid user_address in config"));
+    match config_problems(config).into_iter().next() {
+        Some(problem) => Err(anyhow::anyhow!(problem)),
+        None => Ok(()),
+    }
+}
+
+/// Every setting that would stop the config from loading, as messages naming
+/// the setting and what it must be.
+pub fn config_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    
+    if !is_address(&config.user_address) {
+        problems.push(format!("user_address must be 0x followed by 40 hex digits, got {:?}", config.user_address));
+    }
+    
+    if let Some(vault) = config.vault_address.as_ref().filter(|vault| !is_address(vault)) {
+        problems.push(format!("vault_address must be 0x followed by 40 hex digits, got {:?}", vault));
+    }
+    
+    if let Some(Err(e)) = config.address_book.as_deref().map(crate::address_book::AddressBook::load) {
+        problems.push(format!("address_book: {:#}", e));
+    }
+    
+    if let Some(Err(e)) = config.demo.dataset.as_deref().map(crate::synthetic::Dataset::load) {
+        problems.push(format!("demo.dataset: {:#}", e));
+    }
+    
+    for dex in config.perp_dexs.iter().filter(|dex| dex.is_empty() || dex.contains(':')) {
+        problems.push(format!("perp_dexs must be DEX names like \"xyz\", got {:?}", dex));
+    }
+    
+    for vault in config.vaults.iter().filter(|vault| !is_address(vault)) {
+        problems.push(format!("vaults must be 0x followed by 40 hex digits, got {:?}", vault));
+    }
+    for vault in config.component_vaults.iter().filter(|vault| !is_address(vault)) {
+        problems.push(format!("component_vaults must be 0x followed by 40 hex digits, got {:?}", vault));
+    }
+    if !config.vaults.is_empty() && config.vault_address.is_none() {
+        problems.push("vaults are monitored along with vault_address; set it to the first of them".to_string());
     }
     
     if config.update_interval_ms < 100 {
-        return Err(anyhow::anyhow!("update_interval_ms must be at least 100ms"));
+        problems.push("update_interval_ms must be at least 100ms".to_string());
     }
     
     if config.ui_settings.refresh_rate_ms < 50 {
-        return Err(anyhow::anyhow!("UI refresh_rate_ms must be at least 50ms"));
+        problems.push("UI refresh_rate_ms must be at least 50ms".to_string());
     }
     
-    Ok(())
+    for (name, warning, critical) in config.alert_thresholds.pairs() {
+        if !AlertThresholds::ordered(name, warning, critical) {
+            let side = if AlertThresholds::FALLING.contains(&name) { "above" } else { "below" };
+            problems.push(format!("alert_thresholds.{} warning ({}) must be {} critical ({})", name, warning, side, critical));
+        }
+    }
+    // Vault flows only reach back a day, and the alert names the window in minutes.
+    if !(60_000..=86_400_000).contains(&config.alert_thresholds.withdrawal_window_ms) {
+        problems.push(format!(
+            "alert_thresholds.withdrawal_window must be between 1m and 24h, got {}ms",
+            config.alert_thresholds.withdrawal_window_ms
+        ));
+    }
+    
+    if config.layout.overview.is_empty() {
+        problems.push("layout.overview must contain at least one row".to_string());
+    }
+    
+    if config.storage.retention_days == 0 || config.storage.downsample_interval_secs == 0 {
+        problems.push("storage.retention_days and storage.downsample_interval_secs must be at least 1".to_string());
+    }
+    
+    for (section, enabled, listen) in [
+        ("prometheus", config.prometheus.enabled, &config.prometheus.listen),
+        ("http_api", config.http_api.enabled, &config.http_api.listen),
+        ("grpc", config.grpc.enabled, &config.grpc.listen),
+        ("ws_server", config.ws_server.enabled, &config.ws_server.listen),
+        ("ssh", config.ssh.enabled, &config.ssh.listen),
+    ] {
+        if enabled && listen.parse::<std::net::SocketAddr>().is_err() {
+            problems.push(format!("{}.listen must be an address like 127.0.0.1:9184, got {}", section, listen));
+        }
+    }
+    
+    if config.statsd.enabled && config.statsd.address.parse::<std::net::SocketAddr>().is_err() {
+        problems.push(format!("statsd.address must be an address like 127.0.0.1:8125, got {}", config.statsd.address));
+    }
+    
+    if config.mqtt.enabled {
+        let mqtt = &config.mqtt;
+        if mqtt.client_id.is_empty() || mqtt.metrics_topic.is_empty() || mqtt.alerts_topic.is_empty() {
+            problems.push("mqtt.client_id, mqtt.metrics_topic and mqtt.alerts_topic must not be empty".to_string());
+        }
+        if [&mqtt.metrics_topic, &mqtt.alerts_topic, &mqtt.status_topic].iter().any(|t| t.contains(['#', '+'])) {
+            problems.push("mqtt topics must not contain the wildcards # or +".to_string());
+        }
+        if mqtt.keep_alive_secs < 2 {
+            problems.push("mqtt.keep_alive_secs must be at least 2".to_string());
+        }
+        if mqtt.password.is_some() && mqtt.username.is_none() {
+            problems.push("mqtt.password needs mqtt.username; MQTT 3.1.1 allows no password without a user name".to_string());
+        }
+    }
+    
+    if config.forensics.enabled {
+        let forensics = &config.forensics;
+        if forensics.dir.is_empty() {
+            problems.push("forensics.dir must not be empty".to_string());
+        }
+        if forensics.l2_snapshots == 0 || forensics.trades == 0 || forensics.order_events == 0 {
+            problems.push("forensics.l2_snapshots, forensics.trades and forensics.order_events must be at least 1".to_string());
+        }
+    }
+    
+    if config.daily_report.enabled {
+        let report = &config.daily_report;
+        if chrono::NaiveTime::parse_from_str(&report.time, "%H:%M").is_err() {
+            problems.push(format!("daily_report.time must be HH:MM in UTC, got {}", report.time));
+        }
+        if report.dir.is_empty() || report.formats.is_empty() {
+            problems.push("daily_report.dir and daily_report.formats must not be empty".to_string());
+        }
+        if !report.email_to.is_empty() && (report.email_from.is_empty() || report.sendmail_command.trim().is_empty()) {
+            problems.push("daily_report.email_to needs email_from and sendmail_command".to_string());
+        }
+    }
+    
+    if config.upload.enabled {
+        let upload = &config.upload;
+        if upload.bucket.is_empty() {
+            problems.push("upload.bucket must be set".to_string());
+        }
+        if !url::Url::parse(&upload.endpoint).is_ok_and(|url| url.has_host()) {
+            problems.push(format!("upload.endpoint must be a URL like https://s3.us-east-1.amazonaws.com, got {}", upload.endpoint));
+        }
+        if upload.interval_secs < 1 {
+            problems.push("upload.interval_secs must be at least 1".to_string());
+        }
+        let missing = |value: &str, var: &str| value.is_empty() && std::env::var_os(var).is_none();
+        if missing(&upload.access_key_id, "AWS_ACCESS_KEY_ID") || missing(&upload.secret_access_key, "AWS_SECRET_ACCESS_KEY") {
+            problems.push("upload needs access_key_id and secret_access_key, in the config or AWS_* variables".to_string());
+        }
+    }
+    
+    if config.reload.enabled && config.reload.poll_interval_ms < 100 {
+        problems.push("reload.poll_interval_ms must be at least 100".to_string());
+    }
+    
+    if config.control.enabled && config.control.socket.is_empty() {
+        problems.push("control.socket must be set".to_string());
+    }
+    
+    if config.attach.enabled && config.attach.socket.is_empty() {
+        problems.push("attach.socket must be set".to_string());
+    }
+    
+    if config.ssh.enabled {
+        if config.ssh.host_key.is_empty() {
+            problems.push("ssh.host_key must be set".to_string());
+        }
+        if config.ssh.users.values().all(|keys| keys.is_empty()) {
+            problems.push("ssh.users must give at least one user a public key".to_string());
+        }
+        for (user, keys) in &config.ssh.users {
+            for key in keys {
+                if let Err(e) = crate::ssh::parse_public_key(key) {
+                    problems.push(format!("ssh.users.{}: {}", user, e));
+                }
+            }
+        }
+    }
+    
+    if config.log_file.enabled {
+        if config.log_file.path.is_empty() {
+            problems.push("log_file.path must be set".to_string());
+        }
+        if config.log_file.level.parse::<log::LevelFilter>().is_err() {
+            problems.push(format!("log_file.level must be one of error, warn, info, debug, trace, got {}", config.log_file.level));
+        }
+        if config.log_file.max_size_mb == 0 {
+            problems.push("log_file.max_size_mb must be at least 1".to_string());
+        }
+    }
+    
+    if config.streams.capacity == 0 {
+        problems.push("streams.capacity must be at least 1".to_string());
+    }
+    for (name, size) in [("trades", config.buffers.trades), ("vpin_window", config.buffers.vpin_window), ("order_lifetimes", config.buffers.order_lifetimes)] {
+        if size == 0 {
+            problems.push(format!("buffers.{} must be at least 1", name));
+        }
+    }
+    let estimated_mb = config.buffers.estimated_memory_bytes(config).div_ceil(1024 * 1024);
+    if config.buffers.memory_cap_mb > 0 && estimated_mb > config.buffers.memory_cap_mb {
+        problems.push(format!(
+            "buffers and stream queues may take {} MB, over buffers.memory_cap_mb = {}; lower streams.capacity or the buffer sizes",
+            estimated_mb, config.buffers.memory_cap_mb,
+        ));
+    }
+    
+    for (coin, cap) in config.open_interest.caps_usd.iter().filter(|(_, cap)| **cap <= 0.0) {
+        problems.push(format!("open_interest.caps_usd.{} must be positive, got {}", coin, cap));
+    }
+    
+    if config.backstop.enabled {
+        let backstop = &config.backstop;
+        for (name, address) in backstop.accounts.iter().filter(|(_, address)| !is_address(address)) {
+            problems.push(format!("backstop.accounts.{} must be 0x followed by 40 hex digits, got {:?}", name, address));
+        }
+        for (name, address) in backstop.vaults.iter().filter(|(_, address)| !is_address(address)) {
+            problems.push(format!("backstop.vaults.{} must be 0x followed by 40 hex digits, got {:?}", name, address));
+        }
+        if backstop.interval_secs < 1 {
+            problems.push("backstop.interval_secs must be at least 1".to_string());
+        }
+        if backstop.window_secs < backstop.interval_secs {
+            problems.push("backstop.window_secs must be at least backstop.interval_secs".to_string());
+        }
+    }
+    
+    if config.whales.multiple <= 1.0 {
+        problems.push(format!("whales.multiple must be above 1, got {}", config.whales.multiple));
+    }
+    if config.whales.average_trades < 1 {
+        problems.push("whales.average_trades must be at least 1".to_string());
+    }
+    
+    if config.illiquid.enabled {
+        let illiquid = &config.illiquid;
+        if illiquid.window_secs < 1 {
+            problems.push("illiquid.window_secs must be at least 1".to_string());
+        }
+        if illiquid.baseline_secs <= illiquid.window_secs {
+            problems.push("illiquid.baseline_secs must be above illiquid.window_secs".to_string());
+        }
+        if !(illiquid.activity_fraction > 0.0 && illiquid.activity_fraction < 1.0) {
+            problems.push(format!("illiquid.activity_fraction must be between 0 and 1, got {}", illiquid.activity_fraction));
+        }
+        if illiquid.spread_multiple <= 1.0 {
+            problems.push(format!("illiquid.spread_multiple must be above 1, got {}", illiquid.spread_multiple));
+        }
+    }
+    
+    if config.probation.enabled && config.probation.hours < 1 {
+        problems.push("probation.hours must be at least 1".to_string());
+    }
+    let factor = config.alert_thresholds.probation_factor;
+    if !(factor > 0.0 && factor <= 1.0) {
+        problems.push(format!("alert_thresholds.probation_factor must be above 0 and at most 1, got {}", factor));
+    }
+    
+    if config.chaos.enabled {
+        let chaos = &config.chaos;
+        for (name, rate) in [
+            ("http_429_rate", chaos.http_429_rate),
+            ("http_500_rate", chaos.http_500_rate),
+            ("malformed_rate", chaos.malformed_rate),
+            ("delay_rate", chaos.delay_rate),
+            ("duplicate_rate", chaos.duplicate_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                problems.push(format!("chaos.{} must be between 0 and 1, got {}", name, rate));
+            }
+        }
+    }
+    
+    if config.service.stall_timeout_secs < 1 {
+        problems.push("service.stall_timeout_secs must be at least 1".to_string());
+    }
+    
+    if config.telemetry.enabled {
+        if config.telemetry.level.parse::<tracing::Level>().is_err() {
+            problems.push(format!("telemetry.level must be one of error, warn, info, debug, trace, got {}", config.telemetry.level));
+        }
+        if config.telemetry.export_interval_secs == 0 {
+            problems.push("telemetry.export_interval_secs must be at least 1".to_string());
+        }
+    }
+    
+    if config.raw_capture.max_rows_per_file == 0 {
+        problems.push("raw_capture.max_rows_per_file must be at least 1".to_string());
+    }
+    
+    for (i, row) in config.layout.overview.iter().enumerate() {
+        if row.size == 0 || row.panels.is_empty() {
+            problems.push(format!("layout.overview row {} needs a non-zero size and at least one panel", i + 1));
+        }
+        if row.panels.iter().any(|p| p.size == 0) {
+            problems.push(format!("layout.overview row {} has a panel with size 0", i + 1));
+        }
+    }
+    
+    problems
+}
+
+pub fn is_address(text: &str) -> bool {
+    text.strip_prefix("0x").is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
 }
 
 pub fn save_config_to_file(config: &Config, path: &str) -> Result<()> {
-    let toml_content = toml::to_string_pretty(config)?;
+    let mut config = config.clone();
+    crate::secrets::strip(&mut config);
+    if let Some((user_address, vault_address, vaults)) = config.file_addresses.take() {
+        config.user_address = user_address;
+        config.vault_address = vault_address;
+        config.vaults = vaults;
+    }
+    let toml_content = toml::to_string_pretty(&config)?;
     std::fs::write(path, toml_content)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Reads a millisecond setting given as a number of milliseconds or as a duration
+/// string: `"250ms"`, `"2s"`, `"1.5m"`, `"1h"`. A bare number in a string, as
+/// environment variables are, is milliseconds.
+fn millis<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    struct Millis;
+    
+    impl serde::de::Visitor<'_> for Millis {
+        type Value = u64;
+        
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("milliseconds or a duration such as \"250ms\", \"2s\" or \"1m\"")
+        }
+        
+        fn visit_u64<E: serde::de::Error>(self, value: u64) -> std::result::Result<u64, E> {
+            Ok(value)
+        }
+        
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> std::result::Result<u64, E> {
+            u64::try_from(value).map_err(|_| E::custom(format!("duration cannot be negative, got {}", value)))
+        }
+        
+        fn visit_str<E: serde::de::Error>(self, text: &str) -> std::result::Result<u64, E> {
+            parse_millis(text).ok_or_else(|| E::custom(format!("invalid duration {:?}, expected e.g. 250ms, 2s or 1m", text)))
+        }
+    }
+    
+    deserializer.deserialize_any(Millis)
+}
+
+fn parse_millis(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let count: f64 = text[..split].parse().ok()?;
+    let unit_ms = match text[split..].trim() {
+        "" | "ms" => 1.0,
+        "s" => 1_000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        _ => return None,
+    };
+    Some((count * unit_ms).round() as u64)
+}
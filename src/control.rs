@@ -0,0 +1,261 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot, Notify};
+use tokio_util::sync::CancellationToken;
+
+use crate::alert;
+use crate::config::ControlSettings;
+use crate::model::Alert;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How long `add-asset` waits for the collection loop to check the asset.
+const ADD_ASSET_TIMEOUT: Duration = Duration::from_secs(30);
+
+const HELP: &str = "commands: pause, resume, refresh, ack <id>|all, add-asset <coin>, dump, status, help";
+
+/// An `add-asset` waiting for the collection loop, which owns the provider.
+/// The reply says whether the asset was new.
+pub struct AssetRequest {
+    pub coin: String,
+    pub reply: oneshot::Sender<Result<bool>>,
+}
+
+/// State shared between the control socket and the collection loop.
+pub struct Control {
+    paused: AtomicBool,
+    refresh: Notify,
+    wake: Notify,
+    assets: mpsc::UnboundedSender<AssetRequest>,
+    asset_requests: std::sync::Mutex<mpsc::UnboundedReceiver<AssetRequest>>,
+}
+
+impl Control {
+    pub fn new() -> Arc<Self> {
+        let (assets, requests) = mpsc::unbounded_channel();
+        Arc::new(Self {
+            paused: AtomicBool::new(false),
+            refresh: Notify::new(),
+            wake: Notify::new(),
+            assets,
+            asset_requests: std::sync::Mutex::new(requests),
+        })
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Waits for the next tick of `interval`, a forced refresh or a queued
+    /// `add-asset`, and returns whether to collect: ticks and `add-asset` wake-ups
+    /// are skipped while paused, refreshes never are. A collection started early
+    /// restarts the interval so the next tick is a full period away.
+    pub async fn next_cycle(&self, interval: &mut tokio::time::Interval) -> bool {
+        tokio::select! {
+            _ = interval.tick() => !self.is_paused(),
+            _ = self.refresh.notified() => {
+                interval.reset();
+                true
+            }
+            _ = self.wake.notified() => {
+                let collect = !self.is_paused();
+                if collect {
+                    interval.reset();
+                }
+                collect
+            }
+        }
+    }
+
+    /// `add-asset` requests received since the last call.
+    pub fn asset_requests(&self) -> Vec<AssetRequest> {
+        let mut requests = self.asset_requests.lock().unwrap_or_else(|e| e.into_inner());
+        std::iter::from_fn(|| requests.try_recv().ok()).collect()
+    }
+}
+
+
+/// Accepts connections on the control socket. Each line is one command and gets
+/// one line back, `ok: ...` or `error: ...`, except `dump`, which replies with
+/// the metrics and alerts as a single line of JSON. The socket file is removed
+/// once `token` is cancelled.
+pub async fn serve(
+    settings: ControlSettings,
+    control: Arc<Control>,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    token: CancellationToken,
+) -> Result<()> {
+    let path = Path::new(&settings.socket);
+    if path.exists() {
+        // A socket nobody answers on is left over from an instance that did not exit cleanly.
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(anyhow!("{} is in use by another instance", path.display()));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = bind_private(path)?;
+    info!("🎛️ Control socket listening on {}", path.display());
+
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = token.cancelled() => break,
+        };
+        let (control, metrics, alerts) = (control.clone(), metrics.clone(), alerts.clone());
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, control, metrics, alerts).await {
+                debug!("🎛️ Control connection closed: {}", e);
+            }
+        });
+    }
+    std::fs::remove_file(path)?;
+    debug!("🎛️ Removed control socket {}", path.display());
+    Ok(())
+}
+
+/// Binds a socket at `path` that only this user can ever connect to. Setting the
+/// mode after `bind` would leave a window where anyone could, so the socket is
+/// created inside a fresh 0700 directory, made 0600 and only then moved into place.
+pub fn bind_private(path: &Path) -> Result<UnixListener> {
+    let name = path.file_name().ok_or_else(|| anyhow!("{} is not a socket path", path.display()))?;
+    let private = path.with_file_name(format!(".{}.{}", name.to_string_lossy(), std::process::id()));
+    if private.exists() {
+        std::fs::remove_dir_all(&private)?;
+    }
+    std::fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join(name);
+    let bound = UnixListener::bind(&staged).map_err(anyhow::Error::from).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    bound
+}
+
+async fn handle(
+    stream: UnixStream,
+    control: Arc<Control>,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let reply = match execute(line, &control, &metrics, &alerts).await {
+            Ok(reply) => reply,
+            Err(e) => format!("error: {}", e),
+        };
+        writer.write_all(reply.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+    }
+    Ok(())
+}
+
+async fn execute(
+    line: &str,
+    control: &Control,
+    metrics: &SharedMetrics,
+    alerts: &Versioned<Vec<Alert>>,
+) -> Result<String> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default().to_ascii_lowercase();
+    let argument = words.next();
+    if words.next().is_some() {
+        return Err(anyhow!("too many arguments; {}", HELP));
+    }
+    let reply = match (command.as_str(), argument) {
+        ("pause", None) => {
+            if control.paused.swap(true, Ordering::Relaxed) {
+                "ok: already paused".to_string()
+            } else {
+                info!("⏸️ Collection paused from the control socket");
+                "ok: collection paused".to_string()
+            }
+        }
+        ("resume", None) => {
+            if control.paused.swap(false, Ordering::Relaxed) {
+                info!("▶️ Collection resumed from the control socket");
+                "ok: collection resumed".to_string()
+            } else {
+                "ok: not paused".to_string()
+            }
+        }
+        ("refresh", None) => {
+            control.refresh.notify_one();
+            "ok: refresh requested".to_string()
+        }
+        ("ack", Some("all")) => {
+            let acknowledged = alert::acknowledge_all(&mut alerts.write().await);
+            format!("ok: acknowledged {} alerts", acknowledged)
+        }
+        ("ack", Some(id)) => {
+            if !alert::acknowledge(&mut alerts.write().await, id) {
+                return Err(anyhow!("no alert {}", id));
+            }
+            format!("ok: acknowledged {}", id)
+        }
+        ("add-asset", Some(coin)) => {
+            let coin = coin.to_string();
+            let (reply, added) = oneshot::channel();
+            control
+                .assets
+                .send(AssetRequest { coin: coin.clone(), reply })
+                .map_err(|_| anyhow!("the collection loop is not running"))?;
+            // Wake the loop so the answer comes now rather than after the next tick;
+            // while paused it only takes the asset on, for the first cycle after `resume`.
+            control.wake.notify_one();
+            match tokio::time::timeout(ADD_ASSET_TIMEOUT, added).await {
+                Ok(Ok(Ok(true))) if control.is_paused() => format!("ok: monitoring {} once collection resumes", coin),
+                Ok(Ok(Ok(true))) => format!("ok: monitoring {}", coin),
+                Ok(Ok(Ok(false))) => format!("ok: already monitoring {}", coin),
+                Ok(Ok(Err(e))) => return Err(anyhow!("cannot add {}: {}", coin, e)),
+                Ok(Err(_)) => return Err(anyhow!("the collection loop stopped")),
+                Err(_) => return Err(anyhow!("timed out waiting for the collection loop")),
+            }
+        }
+        ("dump", None) => {
+            let metrics = metrics.snapshot().await;
+            let alerts = alerts.read().await;
+            serde_json::to_string(&serde_json::json!({
+                "paused": control.is_paused(),
+                "metrics": &metrics,
+                "alerts": &*alerts,
+            }))?
+        }
+        ("status", None) => {
+            let last_update = metrics.last_update().await;
+            let alerts = alerts.read().await;
+            let unacknowledged = alerts.iter().filter(|a| !a.acknowledged).count();
+            format!(
+                "ok: {}; last update {}; {} alerts, {} unacknowledged",
+                if control.is_paused() { "paused" } else { "collecting" },
+                last_update.map_or_else(|| "never".to_string(), |at| at.format("%H:%M:%S UTC").to_string()),
+                alerts.len(),
+                unacknowledged
+            )
+        }
+        ("help", None) => format!("ok: {}", HELP),
+        ("pause" | "resume" | "refresh" | "dump" | "status" | "help", Some(_)) => {
+            return Err(anyhow!("{} takes no argument", command));
+        }
+        ("ack" | "add-asset", None) => return Err(anyhow!("{} needs an argument; {}", command, HELP)),
+        _ => {
+            debug!("🎛️ Unknown control command: {}", line);
+            return Err(anyhow!("unknown command {:?}; {}", command, HELP));
+        }
+    };
+    Ok(reply)
+}
@@ -0,0 +1,458 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use log::{info, warn};
+use rust_decimal::prelude::*;
+use std::fmt::Write;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::config::{DailyFormat, DailyReportSettings};
+use crate::history::{HistoryMetric, MetricHistory};
+use crate::model::{Alert, AlertLevel, GlobalMetrics};
+use crate::session::{format_signed_usd, format_value, MetricStats};
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often the scheduler checks the wall clock; sleeping until the next run
+/// would drift across suspends and clock changes.
+const CHECK_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(30);
+
+/// Charted metrics, in report order.
+const CHARTS: [HistoryMetric; 4] = [HistoryMetric::Equity, HistoryMetric::DailyPnl, HistoryMetric::Vpin, HistoryMetric::PhantomLiquidity];
+
+/// Points per chart; the day is averaged into this many buckets.
+const CHART_POINTS: usize = 288;
+
+/// How many assets the "Worst assets" table lists.
+const WORST_ASSETS: usize = 5;
+
+/// The last 24 hours: PnL, Sharpe, drawdown, metric ranges, alerts and the assets
+/// with the most toxic flow. Phantom liquidity is only computed vault-wide, so it
+/// is charted rather than ranked per asset.
+pub struct DailyReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub metrics: Vec<MetricStats>,
+    pub equity_change: Option<f64>,
+    /// Largest peak-to-trough fall in equity over the period, as a fraction.
+    pub period_drawdown: Option<f64>,
+    pub daily_pnl: f64,
+    pub sharpe: f64,
+    pub sortino: f64,
+    pub max_drawdown: f64,
+    pub alert_counts: [(AlertLevel, usize); 3],
+    pub alerts_by_metric: Vec<(String, usize)>,
+    /// Per-asset VPIN, highest first.
+    pub worst_assets: Vec<(String, f64)>,
+    pub charts: Vec<(HistoryMetric, Vec<(i64, f64)>)>,
+}
+
+impl DailyReport {
+    pub fn build(end: DateTime<Utc>, metrics: &GlobalMetrics, history: &MetricHistory, alerts: &[Alert]) -> Self {
+        let start = end - Duration::days(1);
+        let (start_ms, end_ms) = (start.timestamp_millis(), end.timestamp_millis());
+
+        let stats: Vec<MetricStats> = HistoryMetric::ALL
+            .iter()
+            .filter_map(|metric| MetricStats::from_samples(*metric, &history.series(*metric, start_ms, end_ms, 0)))
+            .collect();
+        let equity = history.series(HistoryMetric::Equity, start_ms, end_ms, 0);
+
+        let period_alerts: Vec<&Alert> = alerts.iter().filter(|a| a.timestamp >= start && a.timestamp <= end).collect();
+        let count = |level: AlertLevel| period_alerts.iter().filter(|a| a.level == level).count();
+        let mut by_metric: Vec<(String, usize)> = Vec::new();
+        for alert in &period_alerts {
+            match by_metric.iter_mut().find(|(metric, _)| *metric == alert.metric) {
+                Some((_, n)) => *n += 1,
+                None => by_metric.push((alert.metric.clone(), 1)),
+            }
+        }
+        by_metric.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut worst_assets: Vec<(String, f64)> =
+            metrics.risk_metrics.vpin_by_asset.iter().map(|(coin, vpin)| (coin.clone(), *vpin)).collect();
+        worst_assets.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        worst_assets.truncate(WORST_ASSETS);
+
+        let performance = &metrics.performance_metrics;
+        Self {
+            start,
+            end,
+            equity_change: stats.iter().find(|s| s.metric == HistoryMetric::Equity).map(|s| s.last - s.first),
+            metrics: stats,
+            period_drawdown: drawdown(&equity),
+            daily_pnl: performance.daily_pnl.to_f64().unwrap_or(0.0),
+            sharpe: performance.sharpe_ratio,
+            sortino: performance.sortino_ratio,
+            max_drawdown: metrics.risk_metrics.max_drawdown,
+            alert_counts: [
+                (AlertLevel::Critical, count(AlertLevel::Critical)),
+                (AlertLevel::Warning, count(AlertLevel::Warning)),
+                (AlertLevel::Info, count(AlertLevel::Info)),
+            ],
+            alerts_by_metric: by_metric,
+            worst_assets,
+            charts: CHARTS.iter().map(|metric| (*metric, history.series(*metric, start_ms, end_ms, CHART_POINTS))).collect(),
+        }
+    }
+
+    fn title(&self) -> String {
+        format!("HLP Daily Risk Report: {}", self.end.format("%Y-%m-%d"))
+    }
+
+    fn period(&self) -> String {
+        format!("{} to {}", self.start.format("%Y-%m-%d %H:%M UTC"), self.end.format("%Y-%m-%d %H:%M UTC"))
+    }
+
+    /// Headline rows as (label, value).
+    fn headline(&self) -> Vec<(&'static str, String)> {
+        let percent = |v: f64| format!("{:.2}%", v * 100.0);
+        vec![
+            ("Equity change", self.equity_change.map_or_else(|| "n/a".to_string(), format_signed_usd)),
+            ("Daily PnL", format_signed_usd(self.daily_pnl)),
+            ("Sharpe ratio", format!("{:.2}", self.sharpe)),
+            ("Sortino ratio", format!("{:.2}", self.sortino)),
+            ("Max drawdown (reported)", percent(self.max_drawdown)),
+            ("Max drawdown (equity, period)", self.period_drawdown.map_or_else(|| "n/a".to_string(), percent)),
+        ]
+    }
+
+    /// Markdown with charts linked as `<stem>-<metric>.svg` next to the report.
+    pub fn to_markdown(&self, stem: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n\n{}.\n", self.title(), self.period());
+
+        let _ = writeln!(out, "## Performance\n\n| | |\n|---|---:|");
+        for (label, value) in self.headline() {
+            let _ = writeln!(out, "| {} | {} |", label, value);
+        }
+
+        let _ = writeln!(out, "\n## Charts\n");
+        for (metric, _) in &self.charts {
+            let _ = writeln!(out, "![{}]({}-{}.svg)", metric.label(), stem, chart_slug(*metric));
+        }
+
+        let _ = writeln!(out, "\n## Metric ranges\n\n| Metric | Min | Avg | Max | Last |\n|---|---:|---:|---:|---:|");
+        for s in &self.metrics {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                s.metric.label(),
+                format_value(s.metric, s.min),
+                format_value(s.metric, s.avg),
+                format_value(s.metric, s.max),
+                format_value(s.metric, s.last)
+            );
+        }
+
+        let _ = writeln!(out, "\n## Worst assets by VPIN\n");
+        if self.worst_assets.is_empty() {
+            out.push_str("No per-asset VPIN available.\n");
+        } else {
+            let _ = writeln!(out, "| Asset | VPIN |\n|---|---:|");
+            for (coin, vpin) in &self.worst_assets {
+                let _ = writeln!(out, "| {} | {:.3} |", coin, vpin);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Alerts\n");
+        let counts: Vec<String> = self.alert_counts.iter().map(|(level, n)| format!("{} {:?}", n, level)).collect();
+        let _ = writeln!(out, "{}.\n", counts.join(", "));
+        for (metric, n) in &self.alerts_by_metric {
+            let _ = writeln!(out, "- {}: {}", metric, n);
+        }
+        out
+    }
+
+    /// A self-contained page with the charts inline.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n<style>\
+             body{{font-family:sans-serif;max-width:720px;margin:2em auto;color:#222}}\
+             table{{border-collapse:collapse;margin-bottom:1em}}td,th{{padding:2px 10px;border-bottom:1px solid #ddd}}\
+             td.n{{text-align:right}}</style></head><body>\n<h1>{title}</h1>\n<p>{period}.</p>\n",
+            title = escape(&self.title()),
+            period = escape(&self.period())
+        );
+
+        out.push_str("<h2>Performance</h2>\n<table>\n");
+        for (label, value) in self.headline() {
+            let _ = writeln!(out, "<tr><td>{}</td><td class=\"n\">{}</td></tr>", label, escape(&value));
+        }
+        out.push_str("</table>\n<h2>Charts</h2>\n");
+        for (metric, samples) in &self.charts {
+            out.push_str(&svg_chart(*metric, samples));
+            out.push('\n');
+        }
+
+        out.push_str("<h2>Metric ranges</h2>\n<table>\n<tr><th>Metric</th><th>Min</th><th>Avg</th><th>Max</th><th>Last</th></tr>\n");
+        for s in &self.metrics {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>",
+                escape(s.metric.label()),
+                format_value(s.metric, s.min),
+                format_value(s.metric, s.avg),
+                format_value(s.metric, s.max),
+                format_value(s.metric, s.last)
+            );
+        }
+
+        out.push_str("</table>\n<h2>Worst assets by VPIN</h2>\n");
+        if self.worst_assets.is_empty() {
+            out.push_str("<p>No per-asset VPIN available.</p>\n");
+        } else {
+            out.push_str("<table>\n<tr><th>Asset</th><th>VPIN</th></tr>\n");
+            for (coin, vpin) in &self.worst_assets {
+                let _ = writeln!(out, "<tr><td>{}</td><td class=\"n\">{:.3}</td></tr>", escape(coin), vpin);
+            }
+            out.push_str("</table>\n");
+        }
+
+        let counts: Vec<String> = self.alert_counts.iter().map(|(level, n)| format!("{} {:?}", n, level)).collect();
+        let _ = writeln!(out, "<h2>Alerts</h2>\n<p>{}.</p>\n<ul>", counts.join(", "));
+        for (metric, n) in &self.alerts_by_metric {
+            let _ = writeln!(out, "<li>{}: {}</li>", escape(metric), n);
+        }
+        out.push_str("</ul>\n</body></html>\n");
+        out
+    }
+}
+
+/// Writes the report in each configured format to `dir` and emails it when
+/// recipients are set. Returns the files written.
+pub fn write(settings: &DailyReportSettings, report: &DailyReport) -> Result<Vec<PathBuf>> {
+    let dir = Path::new(&settings.dir);
+    std::fs::create_dir_all(dir)?;
+    let stem = format!("hlp-report-{}", report.end.format("%Y-%m-%d"));
+    let mut written = Vec::new();
+
+    for format in &settings.formats {
+        match format {
+            DailyFormat::Html => {
+                let path = dir.join(format!("{}.html", stem));
+                std::fs::write(&path, report.to_html())?;
+                written.push(path);
+            }
+            DailyFormat::Markdown => {
+                for (metric, samples) in &report.charts {
+                    let path = dir.join(format!("{}-{}.svg", stem, chart_slug(*metric)));
+                    std::fs::write(&path, svg_chart(*metric, samples))?;
+                }
+                let path = dir.join(format!("{}.md", stem));
+                std::fs::write(&path, report.to_markdown(&stem))?;
+                written.push(path);
+            }
+        }
+    }
+
+    if !settings.email_to.is_empty() {
+        send_email(settings, report)?;
+        info!("📧 Emailed daily report to {}", settings.email_to.join(", "));
+    }
+    Ok(written)
+}
+
+/// Renders a report every day at `time` (UTC) for the 24 hours before it.
+/// Alerts come from the in-memory list, which keeps only the most recent ones.
+pub async fn run(
+    settings: DailyReportSettings,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+) -> Result<()> {
+    let time = NaiveTime::parse_from_str(&settings.time, "%H:%M")?;
+    let mut next = next_run(Utc::now(), time);
+    info!("🗓️ Daily report scheduled for {} UTC, next at {}", settings.time, next.format("%Y-%m-%d %H:%M UTC"));
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        let now = Utc::now();
+        if now < next {
+            continue;
+        }
+        next = next_run(now, time);
+
+        let report = {
+            let metrics = metrics.snapshot().await;
+            let (history, alerts) = (history.read().await, alerts.read().await);
+            DailyReport::build(now, &metrics, &history, &alerts)
+        };
+        let settings = settings.clone();
+        match tokio::task::spawn_blocking(move || write(&settings, &report)).await? {
+            Ok(files) => {
+                let files: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+                info!("🗓️ Wrote daily report: {}", files.join(", "));
+            }
+            Err(e) => warn!("⚠️ Daily report failed: {}", e),
+        }
+    }
+}
+
+/// The first `time` of day strictly after `now`.
+fn next_run(now: DateTime<Utc>, time: NaiveTime) -> DateTime<Utc> {
+    let today = now.date_naive().and_time(time).and_utc();
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+/// Pipes a MIME message to `sendmail_command` (`sendmail -t` reads the recipients
+/// from the headers). The HTML report is sent when configured, otherwise the
+/// Markdown as plain text without its charts.
+fn send_email(settings: &DailyReportSettings, report: &DailyReport) -> Result<()> {
+    let (content_type, body) = if settings.formats.contains(&DailyFormat::Html) {
+        ("text/html", report.to_html())
+    } else {
+        ("text/plain", report.to_markdown(&format!("hlp-report-{}", report.end.format("%Y-%m-%d"))))
+    };
+    sendmail(settings, &report.title(), report.end, content_type, &body)
+}
+
+/// A short plain-text message to the report recipients, for `check`.
+pub fn send_test_email(settings: &DailyReportSettings) -> Result<()> {
+    let body = "This is a test message from `hlp-toshogu check`. Daily reports will arrive at this address.\n";
+    sendmail(settings, "HLP Toshogu test message", Utc::now(), "text/plain", body)
+}
+
+/// `body` as quoted-printable (RFC 2045) with CRLF line breaks, so no line comes
+/// near SMTP's 998-character limit however long the report's lines are.
+fn quoted_printable(body: &str) -> String {
+    let mut encoded = String::with_capacity(body.len() + body.len() / 8);
+    for piece in body.split_inclusive('\n') {
+        let (line, newline) = match piece.strip_suffix('\n') {
+            Some(line) => (line.strip_suffix('\r').unwrap_or(line), true),
+            None => (piece, false),
+        };
+        let mut width = 0;
+        for (i, &byte) in line.as_bytes().iter().enumerate() {
+            // Spaces and tabs are literal except at the end of a line, where transports may strip them.
+            let literal = matches!(byte, b'!'..=b'<' | b'>'..=b'~') || (matches!(byte, b' ' | b'\t') && i + 1 < line.len());
+            let len = if literal { 1 } else { 3 };
+            if width + len > 75 {
+                encoded.push_str("=\r\n");
+                width = 0;
+            }
+            if literal {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("={:02X}", byte));
+            }
+            width += len;
+        }
+        if newline {
+            encoded.push_str("\r\n");
+        }
+    }
+    encoded
+}
+
+fn sendmail(settings: &DailyReportSettings, subject: &str, date: DateTime<Utc>, content_type: &str, body: &str) -> Result<()> {
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: {}; charset=utf-8\r\nContent-Transfer-Encoding: quoted-printable\r\n\r\n{}",
+        settings.email_from,
+        settings.email_to.join(", "),
+        subject,
+        date.to_rfc2822(),
+        content_type,
+        quoted_printable(body)
+    );
+
+    let mut parts = settings.sendmail_command.split_whitespace();
+    let program = parts.next().ok_or_else(|| anyhow!("daily_report.sendmail_command is empty"))?;
+    let mut child = Command::new(program).args(parts).stdin(Stdio::piped()).spawn()?;
+    child.stdin.take().ok_or_else(|| anyhow!("no stdin for {}", program))?.write_all(message.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", settings.sendmail_command, status));
+    }
+    Ok(())
+}
+
+fn drawdown(equity: &[(i64, f64)]) -> Option<f64> {
+    if equity.is_empty() {
+        return None;
+    }
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst = 0.0f64;
+    for (_, value) in equity {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            worst = worst.max((peak - value) / peak);
+        }
+    }
+    Some(worst)
+}
+
+/// A 640×160 line chart of `samples` with the range and period labelled.
+fn svg_chart(metric: HistoryMetric, samples: &[(i64, f64)]) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 160.0;
+    const PAD: f64 = 24.0;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"11\">\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"#fafafa\" stroke=\"#ddd\"/>\
+         <text x=\"{p}\" y=\"15\" font-weight=\"bold\">{label}</text>",
+        w = WIDTH,
+        h = HEIGHT,
+        p = PAD,
+        label = escape(metric.label())
+    );
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        svg.push_str("<text x=\"320\" y=\"85\" text-anchor=\"middle\" fill=\"#888\">No samples</text></svg>");
+        return svg;
+    };
+
+    let (lo, hi) = samples.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, v)| (lo.min(*v), hi.max(*v)));
+    let span_y = if hi > lo { hi - lo } else { 1.0 };
+    let span_x = (last.0 - first.0).max(1) as f64;
+    let points: Vec<String> = samples
+        .iter()
+        .map(|(ts, v)| {
+            let x = PAD + (ts - first.0) as f64 / span_x * (WIDTH - 2.0 * PAD);
+            let y = HEIGHT - PAD - (v - lo) / span_y * (HEIGHT - 2.0 * PAD - 10.0);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+
+    let time = |ms: i64| DateTime::from_timestamp_millis(ms).map_or_else(String::new, |t| t.format("%H:%M").to_string());
+    let _ = write!(
+        svg,
+        "<polyline fill=\"none\" stroke=\"#1f77b4\" stroke-width=\"1.5\" points=\"{points}\"/>\
+         <text x=\"{right}\" y=\"15\" text-anchor=\"end\" fill=\"#555\">{lo} – {hi}</text>\
+         <text x=\"{p}\" y=\"{bottom}\" fill=\"#888\">{start}</text>\
+         <text x=\"{right}\" y=\"{bottom}\" text-anchor=\"end\" fill=\"#888\">{end}</text></svg>",
+        points = points.join(" "),
+        right = WIDTH - PAD,
+        p = PAD,
+        bottom = HEIGHT - 6.0,
+        lo = escape(&format_value(metric, lo)),
+        hi = escape(&format_value(metric, hi)),
+        start = time(first.0),
+        end = time(last.0)
+    );
+    svg
+}
+
+fn chart_slug(metric: HistoryMetric) -> &'static str {
+    match metric {
+        HistoryMetric::Equity => "equity",
+        HistoryMetric::DailyPnl => "daily-pnl",
+        HistoryMetric::Vpin => "vpin",
+        HistoryMetric::PhantomLiquidity => "pli",
+        _ => "chart",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
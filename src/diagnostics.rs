@@ -0,0 +1,217 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::api::sdk::{websocket_url, InfoClient, MAIN_DEX};
+use crate::config::{self, AlertThresholds, Config};
+
+/// Per request, so a hung endpoint cannot stall validation.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Alert scores that are fractions, so thresholds above 1 can never be crossed.
+const FRACTIONS: [&str; 9] = [
+    "vpin",
+    "phantom_liquidity",
+    "liquidation_risk",
+    "max_drawdown",
+    "imbalance",
+    "utilization",
+    "concentration",
+    "cancel_rate",
+    "fleeting_ratio",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The config will not load, or the dashboard cannot work with it.
+    Error,
+    /// Loads, but probably not what was meant.
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+    /// What to change.
+    pub hint: Option<String>,
+}
+
+impl Finding {
+    fn error(message: impl Into<String>, hint: Option<String>) -> Self {
+        Self { severity: Severity::Error, message: message.into(), hint }
+    }
+
+    fn warning(message: impl Into<String>, hint: Option<String>) -> Self {
+        Self { severity: Severity::Warning, message: message.into(), hint }
+    }
+}
+
+/// Everything `load_config` rejects, plus settings that load but look wrong:
+/// thresholds that can never fire, clashing listen addresses, an unused
+/// WebSocket URL. With `online`, also checks the API answers, that the user and
+/// vault addresses exist on Hyperliquid, that `monitored_assets` are in the
+/// universe and that enabled outbound endpoints accept connections.
+pub async fn validate(config: &Config, online: bool) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = config::config_problems(config).into_iter().map(|problem| Finding::error(problem, None)).collect();
+    check_thresholds(config, &mut findings);
+    check_listeners(config, &mut findings);
+    check_urls(config, &mut findings);
+    check_assets(config, &mut findings);
+    if online {
+        check_hyperliquid(config, &mut findings).await;
+        check_endpoints(config, &mut findings).await;
+    }
+    findings
+}
+
+fn check_thresholds(config: &Config, findings: &mut Vec<Finding>) {
+    for (name, warning, critical) in config.alert_thresholds.pairs() {
+        if AlertThresholds::FALLING.contains(&name) {
+            if critical <= 0.0 {
+                findings.push(Finding::warning(
+                    format!("alert_thresholds.{} critical is {}, but the reading never falls below 0, so it never fires", name, critical),
+                    Some("falling thresholds fire below their level; use a critical above 0".to_string()),
+                ));
+            }
+            continue;
+        }
+        if warning < 0.0 {
+            findings.push(Finding::warning(
+                format!("alert_thresholds.{} warning is {}, so every reading raises a Warning alert", name, warning),
+                Some("thresholds are compared against non-negative readings; use 0 or above".to_string()),
+            ));
+        }
+        if FRACTIONS.contains(&name) && critical > 1.0 {
+            findings.push(Finding::warning(
+                format!("alert_thresholds.{} critical is {}, but the score never exceeds 1, so it never fires", name, critical),
+                Some(format!("{} is a fraction: 0.5 means 50%", name)),
+            ));
+        }
+    }
+}
+
+fn check_listeners(config: &Config, findings: &mut Vec<Finding>) {
+    let mut taken: HashMap<&str, &str> = HashMap::new();
+    for (section, enabled, listen) in [
+        ("prometheus", config.prometheus.enabled, &config.prometheus.listen),
+        ("http_api", config.http_api.enabled, &config.http_api.listen),
+        ("grpc", config.grpc.enabled, &config.grpc.listen),
+        ("ws_server", config.ws_server.enabled, &config.ws_server.listen),
+        ("ssh", config.ssh.enabled, &config.ssh.listen),
+    ] {
+        if !enabled {
+            continue;
+        }
+        if let Some(other) = taken.insert(listen.as_str(), section) {
+            findings.push(Finding::error(
+                format!("{}.listen and {}.listen are both {}", other, section, listen),
+                Some("give each server its own port".to_string()),
+            ));
+        }
+    }
+}
+
+fn check_urls(config: &Config, findings: &mut Vec<Finding>) {
+    if !url::Url::parse(&config.hyperliquid_api_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+        findings.push(Finding::error(
+            format!("hyperliquid_api_url must be an http(s) URL, got {:?}", config.hyperliquid_api_url),
+            Some("the mainnet API is https://api.hyperliquid.xyz".to_string()),
+        ));
+    }
+    // The provider derives the WebSocket URL from the API URL.
+    let derived = websocket_url(&config.hyperliquid_api_url);
+    if config.enable_websocket && config.hyperliquid_ws_url != derived {
+        findings.push(Finding::warning(
+            format!("hyperliquid_ws_url is {}, but WebSocket connects to {}", config.hyperliquid_ws_url, derived),
+            Some("the WebSocket URL follows hyperliquid_api_url; set that instead".to_string()),
+        ));
+    }
+}
+
+fn check_assets(config: &Config, findings: &mut Vec<Finding>) {
+    let mut seen = Vec::new();
+    for coin in &config.monitored_assets {
+        if seen.contains(&coin) {
+            findings.push(Finding::warning(format!("monitored_assets lists {} twice", coin), None));
+        }
+        seen.push(coin);
+    }
+}
+
+async fn check_hyperliquid(config: &Config, findings: &mut Vec<Finding>) {
+    let client = InfoClient::new(config.hyperliquid_api_url.clone(), None);
+    let meta = match request(client.get_meta(MAIN_DEX)).await {
+        Ok(meta) => meta,
+        Err(e) => {
+            findings.push(Finding::error(
+                format!("cannot reach {}: {}", config.hyperliquid_api_url, e),
+                Some("check hyperliquid_api_url and the network, or pass --offline to skip online checks".to_string()),
+            ));
+            return;
+        }
+    };
+
+    let universe: Vec<&str> = meta.universe.iter().map(|asset| asset.name.as_str()).collect();
+    for coin in config.monitored_assets.iter().filter(|coin| !universe.contains(&coin.as_str())) {
+        let hint = match universe.iter().find(|name| name.eq_ignore_ascii_case(coin)) {
+            Some(name) => format!("names are case-sensitive: did you mean {}?", name),
+            None => "use the names listed by the `meta` info request".to_string(),
+        };
+        findings.push(Finding::error(format!("monitored_assets: {} is not listed on Hyperliquid", coin), Some(hint)));
+    }
+
+    match request(client.get_clearinghouse_state(&config.user_address, MAIN_DEX)).await {
+        Ok(state) => {
+            if state.margin_summary.account_value.0.is_zero() && state.asset_positions.is_empty() {
+                findings.push(Finding::warning(
+                    format!("user_address {} has no balance or positions on Hyperliquid", config.user_address),
+                    Some("check the address; the HLP vault is 0xdfc24b077bc1425ad1dea75bcb6f8158e10df303".to_string()),
+                ));
+            }
+        }
+        Err(e) => findings.push(Finding::error(format!("cannot fetch the account state of user_address: {}", e), None)),
+    }
+
+    let vaults = config.vault_address.iter().map(|vault| ("vault_address", vault)).chain(config.vaults.iter().map(|vault| ("vaults", vault)));
+    for (setting, vault) in vaults {
+        let payload = serde_json::json!({ "type": "vaultDetails", "vaultAddress": vault });
+        match request(client.post_request("info", payload)).await {
+            Ok(Value::Null) => findings.push(Finding::error(
+                format!("{} {} is not a Hyperliquid vault", setting, vault),
+                Some("copy the address from the vault's page on app.hyperliquid.xyz".to_string()),
+            )),
+            Ok(_) => {}
+            Err(e) => findings.push(Finding::error(format!("cannot fetch the details of {} {}: {}", setting, vault, e), None)),
+        }
+    }
+}
+
+/// TCP reachability of enabled sinks that connect out. They retry while running,
+/// so an unreachable one is a warning.
+async fn check_endpoints(config: &Config, findings: &mut Vec<Finding>) {
+    let mut endpoints = Vec::new();
+    if config.mqtt.enabled {
+        endpoints.push(("mqtt.broker", config.mqtt.broker.clone()));
+    }
+    if config.upload.enabled {
+        if let Ok(url) = url::Url::parse(&config.upload.endpoint) {
+            if let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+                endpoints.push(("upload.endpoint", format!("{}:{}", host, port)));
+            }
+        }
+    }
+    for (setting, address) in endpoints {
+        match tokio::time::timeout(TIMEOUT, tokio::net::TcpStream::connect(&address)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => findings.push(Finding::warning(format!("{} {} refused the connection: {}", setting, address, e), None)),
+            Err(_) => findings.push(Finding::warning(format!("{} {} did not answer within {}s", setting, address, TIMEOUT.as_secs()), None)),
+        }
+    }
+}
+
+pub async fn request<T>(call: impl std::future::Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    tokio::time::timeout(TIMEOUT, call)
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("no answer within {}s", TIMEOUT.as_secs())))
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use log::info;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::model::{Alert, AlertLevel, GlobalMetrics, LinkState};
+use crate::storage::flatten;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves `/metrics` in the Prometheus text format until the process exits.
+pub async fn serve(addr: SocketAddr, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        let alerts = alerts.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let metrics = metrics.clone();
+                let alerts = alerts.clone();
+                async move { Ok::<_, Infallible>(respond(request, &metrics, &alerts).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    info!("📈 Prometheus metrics on http://{}/metrics", addr);
+    server.await?;
+    Ok(())
+}
+
+async fn respond(request: Request<Body>, metrics: &SharedMetrics, alerts: &Versioned<Vec<Alert>>) -> Response<Body> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        let mut response = Response::new(Body::from("not found\n"));
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    }
+
+    let body = render(&metrics.snapshot().await, &alerts.read().await);
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(header::CONTENT_TYPE, header::HeaderValue::from_static(CONTENT_TYPE));
+    response
+}
+
+/// Every flattened `GlobalMetrics` value as a gauge named `hlp_<section>_<field>`,
+/// per-asset values labeled by `coin`, plus link state and alert counts.
+pub fn render(metrics: &GlobalMetrics, alerts: &[Alert]) -> String {
+    let mut out = String::new();
+
+    let mut family = "";
+    for value in flatten(metrics) {
+        if value.name != family {
+            family = value.name;
+            let _ = writeln!(out, "# TYPE {} gauge", metric_name(family));
+        }
+        match &value.key {
+            Some((label, key)) => {
+                let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", metric_name(value.name), label, escape(key), value.value);
+            }
+            None => {
+                let _ = writeln!(out, "{} {}", metric_name(value.name), value.value);
+            }
+        }
+    }
+
+    let vault = &metrics.vault_metrics;
+    if let Some(name) = &vault.name {
+        let _ = writeln!(out, "# HELP hlp_vault_info The vault the hlp_vault_* figures are of, from its vaultDetails.");
+        let _ = writeln!(out, "# TYPE hlp_vault_info gauge");
+        let _ = writeln!(out, "hlp_vault_info{{vault=\"{}\",address=\"{}\"}} 1", escape(name), escape(&vault.address));
+    }
+
+    let status = &metrics.system_status;
+    let _ = writeln!(out, "# HELP hlp_link_up Whether a data source is connected (1) or not (0); absent while unknown or disabled.");
+    let _ = writeln!(out, "# TYPE hlp_link_up gauge");
+    for (link, state) in [("http", status.http), ("websocket", status.websocket)] {
+        match state {
+            LinkState::Connected => {
+                let _ = writeln!(out, "hlp_link_up{{link=\"{}\"}} 1", link);
+            }
+            LinkState::Disconnected => {
+                let _ = writeln!(out, "hlp_link_up{{link=\"{}\"}} 0", link);
+            }
+            LinkState::Unknown | LinkState::Disabled => {}
+        }
+    }
+
+    if !status.streams.is_empty() {
+        let _ = writeln!(out, "# HELP hlp_stream_dropped_total WebSocket messages dropped because a consumer fell behind.");
+        let _ = writeln!(out, "# TYPE hlp_stream_dropped_total counter");
+        for stream in &status.streams {
+            let _ = writeln!(out, "hlp_stream_dropped_total{{stream=\"{}\"}} {}", stream.name, stream.dropped);
+        }
+        let _ = writeln!(out, "# HELP hlp_stream_queued Messages waiting in the fullest consumer queue of a WebSocket stream.");
+        let _ = writeln!(out, "# TYPE hlp_stream_queued gauge");
+        for stream in &status.streams {
+            let _ = writeln!(out, "hlp_stream_queued{{stream=\"{}\"}} {}", stream.name, stream.queued);
+        }
+    }
+
+    if !status.buffers.is_empty() {
+        let _ = writeln!(out, "# HELP hlp_buffer_items Items held by an in-memory history buffer.");
+        let _ = writeln!(out, "# TYPE hlp_buffer_items gauge");
+        for buffer in &status.buffers {
+            let _ = writeln!(out, "hlp_buffer_items{{buffer=\"{}\"}} {}", buffer.name, buffer.len);
+        }
+        let _ = writeln!(out, "# HELP hlp_buffer_bytes Storage allocated by an in-memory history buffer.");
+        let _ = writeln!(out, "# TYPE hlp_buffer_bytes gauge");
+        for buffer in &status.buffers {
+            let _ = writeln!(out, "hlp_buffer_bytes{{buffer=\"{}\"}} {}", buffer.name, buffer.bytes);
+        }
+    }
+
+    if !status.latency.is_empty() {
+        let _ = writeln!(out, "# HELP hlp_latency_ms Latency percentiles over recent info requests by type, and of WebSocket message handling.");
+        let _ = writeln!(out, "# TYPE hlp_latency_ms gauge");
+        for stats in &status.latency {
+            for (quantile, ms) in [("0.5", stats.p50_ms), ("0.95", stats.p95_ms), ("0.99", stats.p99_ms)] {
+                let _ = writeln!(out, "hlp_latency_ms{{endpoint=\"{}\",quantile=\"{}\"}} {}", stats.endpoint, quantile, ms);
+            }
+        }
+    }
+
+    if let Some(updated) = metrics.last_update {
+        let _ = writeln!(out, "# TYPE hlp_last_update_timestamp_seconds gauge");
+        let _ = writeln!(out, "hlp_last_update_timestamp_seconds {}", updated.timestamp_millis() as f64 / 1000.0);
+    }
+
+    let _ = writeln!(out, "# HELP hlp_alerts Alerts currently in the dashboard's list.");
+    let _ = writeln!(out, "# TYPE hlp_alerts gauge");
+    for (name, level) in [("info", AlertLevel::Info), ("warning", AlertLevel::Warning), ("critical", AlertLevel::Critical)] {
+        let (total, unacknowledged) = alerts
+            .iter()
+            .filter(|a| a.level == level)
+            .fold((0, 0), |(total, open), a| (total + 1, open + usize::from(!a.acknowledged)));
+        let _ = writeln!(out, "hlp_alerts{{level=\"{}\",acknowledged=\"false\"}} {}", name, unacknowledged);
+        let _ = writeln!(out, "hlp_alerts{{level=\"{}\",acknowledged=\"true\"}} {}", name, total - unacknowledged);
+    }
+
+    out
+}
+
+fn metric_name(name: &str) -> String {
+    format!("hlp_{}", name.replace('.', "_"))
+}
+
+fn escape(label_value: &str) -> String {
+    label_value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
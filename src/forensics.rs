@@ -0,0 +1,202 @@
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::config::ForensicsSettings;
+use crate::model::{Alert, AlertLevel, Fill, GlobalMetrics, L2Snapshot, OrderEvent};
+use crate::pipeline::{Receiver, RecvError};
+use crate::ring::RingSeries;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often new alerts are looked for.
+const POLL_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_millis(200);
+
+/// The WebSocket streams the forensic buffers are filled from.
+pub type Streams = (Receiver<Fill>, Receiver<L2Snapshot>, Receiver<OrderEvent>);
+
+/// Written as `alert.json` in each bundle.
+#[derive(Serialize)]
+struct BundleInfo<'a> {
+    alert: &'a Alert,
+    written: chrono::DateTime<Utc>,
+    /// Assets whose books, trades and order events the bundle holds.
+    assets: &'a [String],
+    /// False in polling mode, where only the metrics are available.
+    streams: bool,
+}
+
+/// Keeps the latest book snapshots, trades and order events of every asset and,
+/// when a Critical alert fires, writes them with the current `GlobalMetrics` to
+/// `<dir>/<timestamp>-<metric>/` for post-incident analysis. Per-asset alerts
+/// (spread, imbalance, concentration) bundle the worst asset, vault-wide ones
+/// every asset. A metric that stays critical is dumped again only after
+/// `cooldown_secs`.
+pub struct ForensicRecorder {
+    dir: PathBuf,
+    settings: ForensicsSettings,
+    books: HashMap<String, RingSeries<L2Snapshot>>,
+    trades: HashMap<String, RingSeries<Fill>>,
+    orders: HashMap<String, RingSeries<OrderEvent>>,
+    last_dump: HashMap<String, Instant>,
+}
+
+impl ForensicRecorder {
+    pub fn new(settings: &ForensicsSettings) -> Self {
+        Self {
+            dir: PathBuf::from(&settings.dir),
+            settings: settings.clone(),
+            books: HashMap::new(),
+            trades: HashMap::new(),
+            orders: HashMap::new(),
+            last_dump: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self, streams: Option<Streams>, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) {
+        let has_streams = streams.is_some();
+        info!(
+            "🧯 Writing forensic bundles for Critical alerts to {}{}",
+            self.dir.display(),
+            if has_streams { "" } else { " (metrics only, no streams)" }
+        );
+        let (mut trade_rx, mut l2_rx, mut order_rx) = match streams {
+            Some((trades, books, orders)) => (Some(trades), Some(books), Some(orders)),
+            None => (None, None, None),
+        };
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        let mut seen: HashSet<String> = alerts.read().await.iter().map(|a| a.id.clone()).collect();
+
+        loop {
+            tokio::select! {
+                Some(fill) = recv(&mut trade_rx) => {
+                    let cap = self.settings.trades;
+                    self.trades.entry(fill.coin.clone()).or_insert_with(|| RingSeries::new(cap)).push(fill);
+                }
+                Some(snapshot) = recv(&mut l2_rx) => {
+                    let cap = self.settings.l2_snapshots;
+                    self.books.entry(snapshot.coin.clone()).or_insert_with(|| RingSeries::new(cap)).push(snapshot);
+                }
+                Some(event) = recv(&mut order_rx) => {
+                    let cap = self.settings.order_events;
+                    self.orders.entry(event.coin.clone()).or_insert_with(|| RingSeries::new(cap)).push(event);
+                }
+                _ = poll.tick() => {
+                    let new_critical: Vec<Alert> = {
+                        let alerts = alerts.read().await;
+                        seen.retain(|id| alerts.iter().any(|a| &a.id == id));
+                        alerts
+                            .iter()
+                            .filter(|a| seen.insert(a.id.clone()))
+                            .filter(|a| a.level == AlertLevel::Critical)
+                            .cloned()
+                            .collect()
+                    };
+                    for alert in new_critical {
+                        if self.cooling_down(&alert.metric) {
+                            debug!("🧯 Skipping forensic bundle for {}: dumped recently", alert.metric);
+                            continue;
+                        }
+                        let snapshot = metrics.snapshot().await;
+                        match self.dump(&alert, &snapshot, has_streams) {
+                            Ok(path) => info!("🧯 Wrote forensic bundle for {} to {}", alert.metric, path.display()),
+                            Err(e) => warn!("⚠️ Failed to write forensic bundle for {}: {}", alert.metric, e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn cooling_down(&mut self, metric: &str) -> bool {
+        let cooldown = std::time::Duration::from_secs(self.settings.cooldown_secs);
+        match self.last_dump.get(metric) {
+            Some(at) if at.elapsed() < cooldown => true,
+            _ => {
+                self.last_dump.insert(metric.to_string(), Instant::now());
+                false
+            }
+        }
+    }
+
+    fn dump(&self, alert: &Alert, metrics: &GlobalMetrics, streams: bool) -> Result<PathBuf> {
+        let assets = self.affected_assets(alert, metrics);
+        let name = format!("{}-{}", alert.timestamp.format("%Y%m%dT%H%M%S%.3fZ"), slug(&alert.metric));
+        let dir = self.dir.join(name);
+        std::fs::create_dir_all(&dir)?;
+
+        let info = BundleInfo { alert, written: Utc::now(), assets: &assets, streams };
+        write_json(&dir, "alert.json", &info)?;
+        write_json(&dir, "metrics.json", metrics)?;
+        write_json(&dir, "l2_snapshots.json", &select(&self.books, &assets))?;
+        write_json(&dir, "trades.json", &select(&self.trades, &assets))?;
+        write_json(&dir, "order_events.json", &select(&self.orders, &assets))?;
+        Ok(dir)
+    }
+
+    /// The asset a per-asset alert was raised for, mirroring how `check_alerts`
+    /// picks it, or every buffered asset for vault-wide metrics.
+    fn affected_assets(&self, alert: &Alert, metrics: &GlobalMetrics) -> Vec<String> {
+        let worst = |map: &HashMap<String, f64>| {
+            map.iter()
+                .max_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(coin, _)| vec![coin.clone()])
+        };
+        let asset = match alert.metric.as_str() {
+            "Spread" => worst(&metrics.liquidity_metrics.bid_ask_spread_bps),
+            "Order Book Imbalance" => worst(&metrics.liquidity_metrics.order_book_imbalance),
+            "Position Concentration" => worst(&metrics.risk_metrics.position_concentration),
+            _ => None,
+        };
+        asset.unwrap_or_else(|| {
+            let mut all: Vec<String> = self.books.keys().chain(self.trades.keys()).chain(self.orders.keys()).cloned().collect();
+            all.sort();
+            all.dedup();
+            all
+        })
+    }
+}
+
+/// Receives from an optional stream; pending forever when there is none or it
+/// has closed, so the `select!` branch is simply never taken.
+async fn recv<T>(rx: &mut Option<Receiver<T>>) -> Option<T> {
+    loop {
+        let Some(receiver) = rx.as_mut() else {
+            return std::future::pending().await;
+        };
+        match receiver.recv().await {
+            Ok(value) => return Some(value),
+            Err(RecvError::Lagged(skipped)) => debug!("🧯 Forensic buffer lagged, skipped {} messages", skipped),
+            Err(RecvError::Closed) => *rx = None,
+        }
+    }
+}
+
+fn select<'a, T>(buffers: &'a HashMap<String, RingSeries<T>>, assets: &[String]) -> BTreeMap<&'a str, &'a RingSeries<T>> {
+    buffers
+        .iter()
+        .filter(|(coin, _)| assets.contains(coin))
+        .map(|(coin, buffer)| (coin.as_str(), buffer))
+        .collect()
+}
+
+fn write_json<T: Serialize + ?Sized>(dir: &Path, file: &str, value: &T) -> Result<()> {
+    let writer = std::io::BufWriter::new(std::fs::File::create(dir.join(file))?);
+    serde_json::to_writer_pretty(writer, value)?;
+    Ok(())
+}
+
+/// `Order Book Imbalance` -> `order-book-imbalance`.
+fn slug(metric: &str) -> String {
+    metric
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
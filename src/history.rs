@@ -0,0 +1,268 @@
+use crate::model::{BufferStats, Candle, GlobalMetrics};
+use crate::ring::RingSeries;
+use chrono::Utc;
+use rust_decimal::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// How long samples are kept in memory; the longest chart range.
+pub const RETENTION_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Candle interval requested from `candleSnapshot` and the WebSocket candle feed.
+pub const CANDLE_INTERVAL: &str = "1m";
+
+/// How far back a `candleSnapshot` backfill reaches.
+pub const CANDLE_BACKFILL_MS: u64 = 3 * 60 * 60 * 1000;
+
+/// A backfilled asset whose newest candle opened longer ago than this has a gap
+/// the candle feed did not fill, which is fetched from that candle on.
+pub const CANDLE_GAP_MS: u64 = 2 * 60 * 1000;
+
+const MAX_CANDLES_PER_ASSET: usize = 720;
+
+/// Cycles of mark/oracle divergence kept per asset, for the Risk tab's sparklines.
+const DIVERGENCE_SAMPLES: usize = 120;
+
+/// Memory each metric's samples may take: 36 hours at the default 1s interval,
+/// proportionally less retention at shorter intervals.
+pub const SERIES_BUDGET_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HistoryMetric {
+    Vpin,
+    PhantomLiquidity,
+    LiquidationRisk,
+    CascadeRisk,
+    Utilization,
+    AvgSpread,
+    CancelRate,
+    FleetingRatio,
+    Tvl,
+    Equity,
+    DailyPnl,
+    Backstop,
+}
+
+impl HistoryMetric {
+    pub const ALL: [HistoryMetric; 12] = [
+        HistoryMetric::Vpin,
+        HistoryMetric::PhantomLiquidity,
+        HistoryMetric::LiquidationRisk,
+        HistoryMetric::CascadeRisk,
+        HistoryMetric::Utilization,
+        HistoryMetric::AvgSpread,
+        HistoryMetric::CancelRate,
+        HistoryMetric::FleetingRatio,
+        HistoryMetric::Tvl,
+        HistoryMetric::Equity,
+        HistoryMetric::DailyPnl,
+        HistoryMetric::Backstop,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HistoryMetric::Vpin => "VPIN",
+            HistoryMetric::PhantomLiquidity => "Phantom Liquidity Index",
+            HistoryMetric::LiquidationRisk => "Liquidation Risk",
+            HistoryMetric::CascadeRisk => "Cascade Risk",
+            HistoryMetric::Utilization => "Utilization",
+            HistoryMetric::AvgSpread => "Avg Spread (bps)",
+            HistoryMetric::CancelRate => "Cancel Rate",
+            HistoryMetric::FleetingRatio => "Fleeting Order Ratio",
+            HistoryMetric::Tvl => "TVL ($)",
+            HistoryMetric::Equity => "Equity ($)",
+            HistoryMetric::DailyPnl => "Daily PnL ($)",
+            HistoryMetric::Backstop => "Backstop ($)",
+        }
+    }
+
+    pub fn extract(&self, metrics: &GlobalMetrics) -> f64 {
+        match self {
+            HistoryMetric::Vpin => metrics.risk_metrics.vpin_score,
+            HistoryMetric::PhantomLiquidity => metrics.risk_metrics.phantom_liquidity_index,
+            HistoryMetric::LiquidationRisk => metrics.risk_metrics.liquidation_risk_score,
+            HistoryMetric::CascadeRisk => metrics.risk_metrics.cascade_risk_score,
+            HistoryMetric::Utilization => metrics.vault_metrics.utilization_rate,
+            HistoryMetric::AvgSpread => {
+                let spreads = &metrics.liquidity_metrics.bid_ask_spread_bps;
+                if spreads.is_empty() {
+                    0.0
+                } else {
+                    spreads.values().sum::<f64>() / spreads.len() as f64
+                }
+            }
+            HistoryMetric::CancelRate => metrics.liquidity_metrics.cancel_rate,
+            HistoryMetric::FleetingRatio => metrics.liquidity_metrics.fleeting_order_ratio,
+            HistoryMetric::Tvl => metrics.vault_metrics.tvl.to_f64().unwrap_or(0.0),
+            HistoryMetric::Equity => metrics.vault_metrics.equity.to_f64().unwrap_or(0.0),
+            HistoryMetric::DailyPnl => metrics.performance_metrics.daily_pnl.to_f64().unwrap_or(0.0),
+            HistoryMetric::Backstop => metrics.risk_metrics.backstop.total().to_f64().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Rolling per-metric time series sampled once per collection cycle, plus recent
+/// OHLC candles per asset keyed by open time.
+#[derive(Default)]
+pub struct MetricHistory {
+    series: HashMap<HistoryMetric, RingSeries<(i64, f64)>>,
+    candles: HashMap<String, BTreeMap<u64, Candle>>,
+    /// Latest mark/oracle divergences of each priced asset, oldest first.
+    divergence: HashMap<String, RingSeries<f64>>,
+}
+
+impl MetricHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, metrics: &GlobalMetrics) {
+        let timestamp_ms = metrics.last_update.unwrap_or_else(Utc::now).timestamp_millis();
+        let cutoff = timestamp_ms - RETENTION_MS;
+
+        for metric in HistoryMetric::ALL {
+            let samples = self.series.entry(metric).or_insert_with(|| RingSeries::with_budget(SERIES_BUDGET_BYTES));
+            samples.push((timestamp_ms, metric.extract(metrics)));
+
+            while samples.front().is_some_and(|(ts, _)| *ts < cutoff) {
+                samples.pop_front();
+            }
+        }
+
+        let divergence = &metrics.risk_metrics.oracle_divergence;
+        self.divergence.retain(|coin, _| divergence.contains_key(coin));
+        for (coin, value) in divergence {
+            self.divergence.entry(coin.clone()).or_insert_with(|| RingSeries::new(DIVERGENCE_SAMPLES)).push(*value);
+        }
+    }
+
+    /// Prepends stored samples (oldest first) from before the first live one, so a
+    /// restart keeps the charts' earlier context.
+    pub fn restore(&mut self, metric: HistoryMetric, samples: Vec<(i64, f64)>) {
+        let series = self.series.entry(metric).or_insert_with(|| RingSeries::with_budget(SERIES_BUDGET_BYTES));
+        let first_live = series.front().map_or(i64::MAX, |(ts, _)| *ts);
+        for sample in samples.into_iter().rev().filter(|(ts, _)| *ts < first_live) {
+            if !series.push_front(sample) {
+                break;
+            }
+        }
+    }
+
+    /// Samples in `[start_ms, end_ms]`, averaged into at most `max_points` equal-width buckets
+    /// so long ranges stay cheap to render.
+    pub fn series(&self, metric: HistoryMetric, start_ms: i64, end_ms: i64, max_points: usize) -> Vec<(i64, f64)> {
+        let Some(samples) = self.series.get(&metric) else {
+            return Vec::new();
+        };
+
+        let in_range: Vec<(i64, f64)> = samples
+            .iter()
+            .copied()
+            .filter(|(ts, _)| *ts >= start_ms && *ts <= end_ms)
+            .collect();
+
+        if max_points == 0 || in_range.len() <= max_points {
+            return in_range;
+        }
+
+        let bucket_ms = ((end_ms - start_ms) as f64 / max_points as f64).max(1.0);
+        let mut buckets: Vec<(i64, f64, usize)> = Vec::with_capacity(max_points);
+
+        for (ts, value) in in_range {
+            let bucket = ((ts - start_ms) as f64 / bucket_ms) as i64;
+            match buckets.last_mut() {
+                Some((b, sum, count)) if *b == bucket => {
+                    *sum += value;
+                    *count += 1;
+                }
+                _ => buckets.push((bucket, value, 1)),
+            }
+        }
+
+        buckets
+            .into_iter()
+            .map(|(bucket, sum, count)| {
+                let ts = start_ms + (bucket as f64 * bucket_ms + bucket_ms / 2.0) as i64;
+                (ts, sum / count as f64)
+            })
+            .collect()
+    }
+
+    /// Fall of `metric` from its running peak within `[start_ms, end_ms]`, as a
+    /// fraction at or below zero. It is taken over every sample and then reduced
+    /// to at most `max_points` buckets by keeping each bucket's deepest point, so
+    /// averaging can't hide a peak or a trough. The last point is the latest
+    /// sample, so the series ends at the current drawdown.
+    pub fn drawdown(&self, metric: HistoryMetric, start_ms: i64, end_ms: i64, max_points: usize) -> Vec<(i64, f64)> {
+        let mut peak = f64::NEG_INFINITY;
+        let raw: Vec<(i64, f64)> = self
+            .series(metric, start_ms, end_ms, 0)
+            .into_iter()
+            .map(|(ts, value)| {
+                peak = peak.max(value);
+                (ts, if peak > 0.0 { (value - peak) / peak } else { 0.0 })
+            })
+            .collect();
+
+        if max_points == 0 || raw.len() <= max_points {
+            return raw;
+        }
+
+        let bucket_ms = ((end_ms - start_ms) as f64 / max_points as f64).max(1.0);
+        let mut buckets: Vec<(i64, f64)> = Vec::with_capacity(max_points + 1);
+        for (ts, drawdown) in &raw {
+            let bucket = (((ts - start_ms) as f64 / bucket_ms) as i64).min(max_points as i64 - 1);
+            let centre = start_ms + (bucket as f64 * bucket_ms + bucket_ms / 2.0) as i64;
+            match buckets.last_mut() {
+                Some((b, deepest)) if *b == centre => *deepest = deepest.min(*drawdown),
+                _ => buckets.push((centre, *drawdown)),
+            }
+        }
+        buckets.extend(raw.last().copied());
+        buckets
+    }
+
+    /// Inserts or replaces candles by open time; a live update for the current minute
+    /// overwrites the partial candle already stored.
+    pub fn merge_candles(&mut self, candles: impl IntoIterator<Item = Candle>) {
+        for candle in candles {
+            let book = self.candles.entry(candle.coin.clone()).or_default();
+            book.insert(candle.open_time, candle);
+
+            while book.len() > MAX_CANDLES_PER_ASSET {
+                book.pop_first();
+            }
+        }
+    }
+
+    /// Occupancy of every metric's samples together.
+    pub fn buffer_stats(&self) -> BufferStats {
+        let mut total = BufferStats { name: "history".to_string(), ..BufferStats::default() };
+        for stats in self.series.values().map(|series| series.stats("history")) {
+            total.len += stats.len;
+            total.capacity += stats.capacity;
+            total.evicted += stats.evicted;
+            total.bytes += stats.bytes;
+        }
+        total
+    }
+
+    /// Recent mark/oracle divergences of `coin`, oldest first.
+    pub fn divergence(&self, coin: &str) -> Vec<f64> {
+        self.divergence.get(coin).map(|series| series.iter().copied().collect()).unwrap_or_default()
+    }
+
+    pub fn candles(&self, coin: &str) -> Vec<&Candle> {
+        self.candles.get(coin).map(|book| book.values().collect()).unwrap_or_default()
+    }
+
+    /// Open time of the newest candle stored for `coin`.
+    pub fn last_candle_open(&self, coin: &str) -> Option<u64> {
+        self.candles.get(coin)?.last_key_value().map(|(open_time, _)| *open_time)
+    }
+
+    pub fn candle_assets(&self) -> Vec<&str> {
+        let mut assets: Vec<&str> = self.candles.keys().map(String::as_str).collect();
+        assets.sort_unstable();
+        assets
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::config::IlliquidSettings;
+use crate::model::GlobalMetrics;
+
+/// How often each asset trades and how wide it quotes, against its own norm.
+#[derive(Debug, Default)]
+pub struct IlliquidWatch {
+    assets: HashMap<String, Activity>,
+}
+
+#[derive(Debug)]
+struct Activity {
+    first_seen: Instant,
+    counted: Instant,
+    /// Trades streamed as of `counted`.
+    trades: u64,
+    /// Trades per minute, averaged over `window_secs` and `baseline_secs`.
+    recent_rate: f64,
+    baseline_rate: f64,
+    baseline_spread_bps: f64,
+}
+
+impl IlliquidWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds in the streamed trade counts so far and the cycle's spreads, and
+    /// tags `metrics` with each asset's trade rate and those trading thinly
+    /// and quoting wide against their norm. Every asset quoted this cycle is
+    /// counted, one without a streamed trade yet at 0, so an asset that stops
+    /// trading altogether still falls below its norm. Without streamed trades
+    /// there is no arrival rate, so nothing is tagged.
+    pub fn update(&mut self, metrics: &mut GlobalMetrics, trade_counts: &HashMap<String, u64>, settings: &IlliquidSettings) {
+        let now = Instant::now();
+        let liquidity = &mut metrics.liquidity_metrics;
+        let smoothing = |dt: f64, secs: u64| 1.0 - (-dt / secs.max(1) as f64).exp();
+        for (coin, &spread) in &liquidity.bid_ask_spread_bps {
+            let trades = trade_counts.get(coin).copied().unwrap_or(0);
+            let Some(activity) = self.assets.get_mut(coin) else {
+                self.assets.insert(coin.clone(), Activity {
+                    first_seen: now,
+                    counted: now,
+                    trades,
+                    recent_rate: 0.0,
+                    baseline_rate: 0.0,
+                    baseline_spread_bps: spread,
+                });
+                continue;
+            };
+            let dt = now.duration_since(activity.counted).as_secs_f64();
+            if dt <= 0.0 {
+                continue;
+            }
+            let rate = trades.saturating_sub(activity.trades) as f64 / dt * 60.0;
+            // The first stretch seeds the averages rather than pulling them up from zero.
+            let warm = now.duration_since(activity.first_seen).as_secs() >= settings.window_secs;
+            let (recent, baseline) = match warm {
+                true => (smoothing(dt, settings.window_secs), smoothing(dt, settings.baseline_secs)),
+                false => (smoothing(dt, settings.window_secs), smoothing(dt, settings.window_secs)),
+            };
+            activity.recent_rate += recent * (rate - activity.recent_rate);
+            activity.baseline_rate += baseline * (rate - activity.baseline_rate);
+            activity.baseline_spread_bps += baseline * (spread - activity.baseline_spread_bps);
+            activity.counted = now;
+            activity.trades = trades;
+
+            liquidity.trade_rate_per_min.insert(coin.clone(), activity.recent_rate);
+            let thin = activity.recent_rate < settings.activity_fraction * activity.baseline_rate;
+            let wide = spread > settings.spread_multiple * activity.baseline_spread_bps;
+            if warm && thin && wide {
+                liquidity.illiquid_assets.push(coin.clone());
+            }
+        }
+        liquidity.illiquid_assets.sort();
+    }
+}
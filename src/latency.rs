@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::model::LatencyStats;
+use crate::ring::RingSeries;
+
+/// Recent samples kept per endpoint; percentiles are taken over these, so they
+/// follow the session rather than averaging it out.
+const WINDOW: usize = 1024;
+
+/// Latencies of the latest requests by endpoint, recorded from any task.
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    endpoints: Mutex<BTreeMap<String, Endpoint>>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    /// Milliseconds, oldest first.
+    samples: RingSeries<f64>,
+    count: u64,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, endpoint: &str, elapsed: Duration) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        if !endpoints.contains_key(endpoint) {
+            endpoints.insert(endpoint.to_string(), Endpoint { samples: RingSeries::new(WINDOW), count: 0 });
+        }
+        if let Some(entry) = endpoints.get_mut(endpoint) {
+            entry.samples.push(elapsed.as_secs_f64() * 1000.0);
+            entry.count += 1;
+        }
+    }
+
+    /// Percentiles of each endpoint by name, then, given a `total` name, of all
+    /// of them together.
+    pub fn stats(&self, total: Option<&str>) -> Vec<LatencyStats> {
+        let endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<LatencyStats> = endpoints
+            .iter()
+            .map(|(name, entry)| percentiles(name, entry.count, entry.samples.iter().copied().collect()))
+            .collect();
+        if let (Some(total), false) = (total, endpoints.is_empty()) {
+            let count = endpoints.values().map(|entry| entry.count).sum();
+            let samples = endpoints.values().flat_map(|entry| entry.samples.iter().copied()).collect();
+            stats.push(percentiles(total, count, samples));
+        }
+        stats
+    }
+}
+
+/// Nearest-rank percentiles of `samples`.
+fn percentiles(endpoint: &str, count: u64, mut samples: Vec<f64>) -> LatencyStats {
+    samples.sort_by(f64::total_cmp);
+    let rank = |p: f64| match samples.len() {
+        0 => 0.0,
+        len => samples[((p * len as f64).ceil() as usize).clamp(1, len) - 1],
+    };
+    LatencyStats {
+        endpoint: endpoint.to_string(),
+        count,
+        p50_ms: rank(0.50),
+        p95_ms: rank(0.95),
+        p99_ms: rank(0.99),
+    }
+}
@@ -3,4 +3,44 @@ pub mod config;
 pub mod model;
 pub mod metrics;
 pub mod ui;
-pub mod alert;
\ No newline at end of file
+pub mod alert;
+pub mod history;
+pub mod loading;
+pub mod session;
+pub mod storage;
+pub mod exporter;
+pub mod api_server;
+pub mod server;
+pub mod ws_server;
+pub mod report;
+pub mod telemetry;
+pub mod statsd;
+pub mod mqtt;
+pub mod systemd;
+pub mod forensics;
+pub mod daily_report;
+pub mod upload;
+pub mod attach;
+pub mod ssh;
+pub mod control;
+pub mod reload;
+pub mod diagnostics;
+pub mod secrets;
+pub mod wizard;
+pub mod logfile;
+pub mod check;
+pub mod versioned;
+pub mod pipeline;
+pub mod ring;
+pub mod shared;
+pub mod shutdown;
+pub mod latency;
+pub mod universe;
+pub mod address_book;
+pub mod illiquid;
+pub mod probation;
+pub mod scenario;
+pub mod synthetic;
+pub mod loadtest;
+pub mod backtest;
+pub mod tune;
@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepStatus {
+    Pending,
+    InProgress(String),
+    Done(String),
+    Skipped(String),
+    Failed(String),
+}
+
+/// Snapshot of start-up progress as shown on the loading screen.
+#[derive(Debug, Clone)]
+pub struct LoadingSteps {
+    pub meta: StepStatus,
+    pub websocket: StepStatus,
+    pub user_state: StepStatus,
+    pub order_books: StepStatus,
+    pub last_error: Option<String>,
+    pub fatal: Option<String>,
+    order_books_loaded: usize,
+    order_books_total: usize,
+}
+
+/// Shared handle the provider and collection loop report initialization steps
+/// through; cheap to clone and safe to update from any task.
+#[derive(Clone)]
+pub struct LoadingProgress {
+    steps: Arc<Mutex<LoadingSteps>>,
+}
+
+impl LoadingProgress {
+    pub fn new(websocket_enabled: bool) -> Self {
+        let websocket = if websocket_enabled {
+            StepStatus::Pending
+        } else {
+            StepStatus::Skipped("disabled in config".to_string())
+        };
+
+        Self {
+            steps: Arc::new(Mutex::new(LoadingSteps {
+                meta: StepStatus::Pending,
+                websocket,
+                user_state: StepStatus::Pending,
+                order_books: StepStatus::Pending,
+                last_error: None,
+                fatal: None,
+                order_books_loaded: 0,
+                order_books_total: 0,
+            })),
+        }
+    }
+
+    /// Progress for the simulated data source, which has nothing to fetch.
+    pub fn simulated() -> Self {
+        let progress = Self::new(false);
+        progress.update(|steps| {
+            let simulated = StepStatus::Skipped("simulated".to_string());
+            steps.meta = simulated.clone();
+            steps.websocket = simulated.clone();
+            steps.user_state = simulated.clone();
+            steps.order_books = simulated;
+        });
+        progress
+    }
+
+    pub fn snapshot(&self) -> LoadingSteps {
+        self.steps.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn meta_fetched(&self, assets: usize) {
+        self.update(|steps| steps.meta = StepStatus::Done(format!("{} assets", assets)));
+    }
+
+    pub fn websocket(&self, status: StepStatus) {
+        self.update(|steps| steps.websocket = status);
+    }
+
+    pub fn user_state_fetched(&self) {
+        self.update(|steps| steps.user_state = StepStatus::Done(String::new()));
+    }
+
+    /// Starts counting order books for the first pass; later passes are ignored.
+    pub fn order_books_started(&self, total: usize) {
+        self.update(|steps| {
+            if !matches!(steps.order_books, StepStatus::Done(_)) {
+                steps.order_books_loaded = 0;
+                steps.order_books_total = total;
+                steps.order_books = StepStatus::InProgress(format!("0/{}", total));
+            }
+        });
+    }
+
+    pub fn order_book_loaded(&self) {
+        self.update(|steps| {
+            if matches!(steps.order_books, StepStatus::InProgress(_)) {
+                steps.order_books_loaded += 1;
+                steps.order_books =
+                    StepStatus::InProgress(format!("{}/{}", steps.order_books_loaded, steps.order_books_total));
+            }
+        });
+    }
+
+    pub fn order_books_finished(&self) {
+        self.update(|steps| {
+            if matches!(steps.order_books, StepStatus::InProgress(_)) {
+                steps.order_books =
+                    StepStatus::Done(format!("{}/{}", steps.order_books_loaded, steps.order_books_total));
+            }
+        });
+    }
+
+    /// A failed collection cycle; start-up keeps retrying and shows the reason.
+    pub fn retrying(&self, error: String) {
+        self.update(|steps| steps.last_error = Some(error));
+    }
+
+    /// An error start-up cannot recover from, such as the API being unreachable.
+    pub fn fail(&self, error: String) {
+        self.update(|steps| steps.fatal = Some(error));
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut LoadingSteps)) {
+        apply(&mut self.steps.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::*;
+use tokio::sync::RwLock;
+
+use crate::config::Config;
+use crate::metrics::streaming::StreamingMetricsEngine;
+use crate::model::{Fill, L2Snapshot, OrderBookLevel, OrderEvent, StreamStats};
+use crate::pipeline::{self, Sender};
+
+/// The assets the generated market trades, about as many as stream at once
+/// when the dashboard follows the busiest of the universe.
+const COINS: [&str; 32] = [
+    "BTC", "ETH", "SOL", "HYPE", "XRP", "DOGE", "SUI", "AVAX", "LINK", "BNB", "ADA", "LTC", "DOT", "TON", "NEAR", "APT", "ARB", "OP",
+    "INJ", "TIA", "SEI", "WIF", "kPEPE", "kBONK", "ENA", "ONDO", "TAO", "FET", "RENDER", "AAVE", "UNI", "JUP",
+];
+/// Levels on each side of a generated book, as `l2Book` sends them.
+const BOOK_LEVELS: usize = 20;
+/// Of the generated messages, the share that are book updates; the rest are trades.
+const BOOK_SHARE: f64 = 0.5;
+/// The producer's pacing step; it sends what fell due since the last one.
+const TICK: Duration = Duration::from_millis(5);
+/// Distinct trades and book updates generated before the run.
+const POOL: usize = 8_192;
+/// Sent per pass when unpaced.
+const UNPACED_BATCH: u64 = 256;
+/// How often the engine is read the way the collection loop reads it.
+const SAMPLE_EVERY: Duration = Duration::from_millis(250);
+/// Samples per progress line.
+const SAMPLES_PER_LINE: usize = 4;
+/// Of the trades and book updates sent, the share the engine must have taken
+/// by the end for the run to count as kept up.
+const KEPT_UP: f64 = 0.99;
+const SEED: u64 = 0x4c4f_4144;
+
+/// A market of `COINS` random-walking around their prices, generating trades
+/// and full book updates.
+struct LoadGenerator {
+    rng: StdRng,
+    mids: Vec<f64>,
+}
+
+impl LoadGenerator {
+    fn new() -> Self {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let mids = (0..COINS.len()).map(|_| 10f64.powf(rng.gen_range(-2.0..5.0))).collect();
+        Self { rng, mids }
+    }
+
+    /// Moves a random asset's mid a little and returns its index.
+    fn step(&mut self) -> usize {
+        let asset = self.rng.gen_range(0..COINS.len());
+        self.mids[asset] *= 1.0 + self.rng.gen_range(-0.0005..0.0005);
+        asset
+    }
+
+    fn decimal(value: f64) -> Decimal {
+        Decimal::from_f64(value).unwrap_or(Decimal::ZERO).round_dp(6)
+    }
+
+    fn trade(&mut self, time: u64) -> Fill {
+        let asset = self.step();
+        let buy = self.rng.gen_bool(0.5);
+        let notional = 10f64.powf(self.rng.gen_range(1.0..5.5));
+        let mid = self.mids[asset];
+        Fill {
+            coin: COINS[asset].to_string(),
+            px: Self::decimal(mid * if buy { 1.0001 } else { 0.9999 }),
+            sz: Self::decimal(notional / mid),
+            side: if buy { "B" } else { "A" }.to_string(),
+            time,
+            start_position: Decimal::ZERO,
+            dir: String::new(),
+            closed_pnl: Decimal::ZERO,
+            hash: String::new(),
+            oid: self.rng.gen(),
+            crossed: true,
+            fee: Decimal::ZERO,
+            builder_fee: Decimal::ZERO,
+            expected_fee: None,
+            liquidation: None,
+            users: vec![format!("0x{:040x}", self.rng.gen_range(0..64u32)), format!("0x{:040x}", self.rng.gen_range(0..64u32))],
+        }
+    }
+
+    fn book(&mut self, time: u64) -> L2Snapshot {
+        let asset = self.step();
+        let (mid, tick) = (self.mids[asset], self.mids[asset] * 0.0001);
+        let mut side = |direction: f64| -> Vec<OrderBookLevel> {
+            (1..=BOOK_LEVELS)
+                .map(|level| OrderBookLevel {
+                    px: Self::decimal(mid + direction * tick * level as f64),
+                    sz: Self::decimal(self.rng.gen_range(1_000.0..50_000.0) / mid),
+                    n: self.rng.gen_range(1..20),
+                })
+                .collect()
+        };
+        let bids = side(-1.0);
+        let asks = side(1.0);
+        L2Snapshot { coin: COINS[asset].to_string(), time, bids, asks }
+    }
+
+    /// `POOL` trades and as many book updates, which the producer sends over
+    /// and over with fresh timestamps, so generating them costs no engine time.
+    fn pool(mut self) -> (Vec<Fill>, Vec<L2Snapshot>) {
+        let trades = (0..POOL).map(|_| self.trade(0)).collect();
+        let books = (0..POOL).map(|_| self.book(0)).collect();
+        (trades, books)
+    }
+}
+
+/// One reading of the engine and its queues during the run.
+struct Sample {
+    /// Since the run started.
+    at: Duration,
+    /// Trades and book updates the engine has taken off its queues.
+    processed: u64,
+    /// How far the engine's newest trade is behind the wall clock.
+    lag_ms: u64,
+    queued: usize,
+    /// How long the collection loop's read of the engine took, lock wait included.
+    read: Duration,
+    resident_bytes: Option<u64>,
+}
+
+/// Throughput, queue lag and memory of a streaming engine fed far faster than
+/// Hyperliquid usually streams, to learn its headroom before a volatility
+/// event finds it. Built by `run`.
+pub struct LoadReport {
+    rate_per_min: u64,
+    duration: Duration,
+    samples: Vec<Sample>,
+    /// Taken off the queues by the last send.
+    processed: u64,
+    trades: StreamStats,
+    books: StreamStats,
+    /// From the last send until the engine had taken every queued message.
+    drained_in: Duration,
+    resident_before: Option<u64>,
+    resident_peak: Option<u64>,
+    buffer_bytes: usize,
+}
+
+/// Pushes trades and book updates at `rate_per_min`, or as fast as the engine
+/// takes them when 0, through the queues of `[streams]` into an engine sized
+/// by `[buffers]`, for `duration`, printing a progress line a second.
+pub async fn run(config: &Config, rate_per_min: u64, duration: Duration) -> LoadReport {
+    let trade_sender: Sender<Fill> = pipeline::channel("trades", &config.streams);
+    let book_sender: Sender<L2Snapshot> = pipeline::channel("l2_snapshots", &config.streams);
+    let order_sender: Sender<OrderEvent> = pipeline::channel("order_events", &config.streams);
+    let engine = Arc::new(RwLock::new(StreamingMetricsEngine::new(&config.buffers, &config.whales)));
+    let consumer = tokio::spawn(StreamingMetricsEngine::run(
+        engine.clone(),
+        trade_sender.subscribe(),
+        book_sender.subscribe(),
+        order_sender.subscribe(),
+    ));
+    let resident_before = resident_bytes();
+
+    // On a thread of its own, as the WebSocket reader is in effect: a producer
+    // sharing the runtime keeps the engine's task in its worker's queue, where
+    // no other worker can take it.
+    let (trades, books) = LoadGenerator::new().pool();
+    let start = Instant::now();
+    let producer = {
+        let (trade_sender, book_sender) = (trade_sender.clone(), book_sender.clone());
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().expect("a producer runtime");
+        std::thread::spawn(move || runtime.block_on(async move {
+            let mut rng = StdRng::seed_from_u64(SEED);
+            let mut sent = 0u64;
+            while start.elapsed() < duration {
+                let due = match rate_per_min {
+                    0 => sent + UNPACED_BATCH,
+                    rate => (start.elapsed().as_secs_f64() * rate as f64 / 60.0) as u64,
+                };
+                while sent < due {
+                    if rng.gen_bool(BOOK_SHARE) {
+                        let mut book = books[sent as usize % POOL].clone();
+                        book.time = now_ms();
+                        book_sender.send(book).await;
+                    } else {
+                        let mut trade = trades[sent as usize % POOL].clone();
+                        trade.time = now_ms();
+                        trade_sender.send(trade).await;
+                    }
+                    sent += 1;
+                }
+                if rate_per_min > 0 {
+                    tokio::time::sleep(TICK).await;
+                }
+            }
+        }))
+    };
+
+    let mut samples = Vec::new();
+    let mut interval = tokio::time::interval(SAMPLE_EVERY);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if producer.is_finished() {
+            break;
+        }
+        let sample = sample(&engine, &trade_sender, &book_sender, start).await;
+        samples.push(sample);
+        if samples.len() % SAMPLES_PER_LINE == 0 {
+            println!("{}", progress_line(&samples[samples.len().saturating_sub(SAMPLES_PER_LINE + 1)..]));
+        }
+    }
+    let _ = producer.join();
+
+    let sent_at = Instant::now();
+    let (trades, books) = (trade_sender.stats(), book_sender.stats());
+    let processed = taken(&trades) + taken(&books);
+    drop((trade_sender, book_sender, order_sender));
+    let _ = consumer.await;
+    let drained_in = sent_at.elapsed();
+
+    let buffer_bytes = engine.read().await.buffer_stats().iter().map(|buffer| buffer.bytes).sum();
+    LoadReport {
+        rate_per_min,
+        duration,
+        samples,
+        processed,
+        trades,
+        books,
+        drained_in,
+        resident_before,
+        resident_peak: peak_resident_bytes(),
+        buffer_bytes,
+    }
+}
+
+/// Reads the engine as a collection cycle does, timing the read.
+async fn sample(engine: &Arc<RwLock<StreamingMetricsEngine>>, trades: &Sender<Fill>, books: &Sender<L2Snapshot>, start: Instant) -> Sample {
+    let reading = Instant::now();
+    let newest_trade = {
+        let engine = engine.read().await;
+        let _ = (engine.get_current_vpin(), engine.get_phantom_liquidity_metrics(), engine.get_real_time_spreads());
+        let _ = (engine.get_volume_metrics(), engine.live_order_books(), engine.top_trader(), engine.large_prints());
+        engine.recent_trades(1).first().map(|trade| trade.time)
+    };
+    let read = reading.elapsed();
+    let (trades, books) = (trades.stats(), books.stats());
+    Sample {
+        at: start.elapsed(),
+        processed: taken(&trades) + taken(&books),
+        lag_ms: newest_trade.map_or(0, |time| now_ms().saturating_sub(time)),
+        queued: trades.queued.max(books.queued),
+        read,
+        resident_bytes: resident_bytes(),
+    }
+}
+
+/// Messages the engine has received from `stream`, or lost to a full queue.
+fn taken(stream: &StreamStats) -> u64 {
+    stream.sent.saturating_sub(stream.queued as u64 + stream.dropped)
+}
+
+fn progress_line(samples: &[Sample]) -> String {
+    let (first, last) = (&samples[0], &samples[samples.len() - 1]);
+    let elapsed = last.at.saturating_sub(first.at).as_secs_f64().max(f64::EPSILON);
+    let rate = last.processed.saturating_sub(first.processed) as f64 / elapsed;
+    format!(
+        "⏱️  {:>4.0}s  {:>8.0} msg/s  lag {:>5} ms  queued {:>6}  read {:>6.2} ms  RSS {}",
+        last.at.as_secs_f64(),
+        rate,
+        last.lag_ms,
+        last.queued,
+        last.read.as_secs_f64() * 1e3,
+        megabytes(last.resident_bytes)
+    )
+}
+
+impl LoadReport {
+    /// Whether the engine took nearly everything sent, without dropping any;
+    /// always for an unpaced run, which finds how much it can take instead.
+    pub fn kept_up(&self) -> bool {
+        if self.rate_per_min == 0 {
+            return true;
+        }
+        let sent = self.trades.sent + self.books.sent;
+        let dropped = self.trades.dropped + self.books.dropped;
+        dropped == 0 && self.processed as f64 >= sent as f64 * KEPT_UP
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let secs = self.duration.as_secs_f64();
+        let sent = self.trades.sent + self.books.sent;
+        let offered = match self.rate_per_min {
+            0 => "as fast as the engine takes them".to_string(),
+            rate => format!("{} messages/min", rate),
+        };
+        let _ = writeln!(out, "\n🏋️ Load test: {} for {:.0}s across {} assets", offered, secs, COINS.len());
+        let _ = writeln!(
+            out,
+            "  Sent       {} messages ({} trades, {} book updates), {:.0}/s",
+            sent,
+            self.trades.sent,
+            self.books.sent,
+            sent as f64 / secs
+        );
+
+        let rates: Vec<f64> = self
+            .samples
+            .windows(2)
+            .map(|pair| pair[1].processed.saturating_sub(pair[0].processed) as f64 / pair[1].at.saturating_sub(pair[0].at).as_secs_f64().max(f64::EPSILON))
+            .collect();
+        let _ = writeln!(
+            out,
+            "  Processed  {} by the last send, {:.0}/s on average, {:.0}/s at peak; the rest drained {:.0} ms after it",
+            self.processed,
+            self.processed as f64 / secs,
+            rates.iter().cloned().fold(0.0, f64::max),
+            self.drained_in.as_secs_f64() * 1e3
+        );
+        let _ = writeln!(
+            out,
+            "  Queues     {} dropped, {} sends blocked, deepest {} of {}",
+            self.trades.dropped + self.books.dropped,
+            self.trades.blocked + self.books.blocked,
+            self.samples.iter().map(|sample| sample.queued).max().unwrap_or(0),
+            self.trades.capacity
+        );
+
+        let mut lags: Vec<u64> = self.samples.iter().map(|sample| sample.lag_ms).collect();
+        lags.sort_unstable();
+        let _ = writeln!(
+            out,
+            "  Lag        p50 {} ms, p99 {} ms, max {} ms behind the wall clock",
+            percentile(&lags, 0.5),
+            percentile(&lags, 0.99),
+            lags.last().copied().unwrap_or(0)
+        );
+        let mut reads: Vec<u64> = self.samples.iter().map(|sample| sample.read.as_micros() as u64).collect();
+        reads.sort_unstable();
+        let _ = writeln!(
+            out,
+            "  Reads      p50 {:.2} ms, p99 {:.2} ms for a collection cycle's read of the engine",
+            percentile(&reads, 0.5) as f64 / 1e3,
+            percentile(&reads, 0.99) as f64 / 1e3
+        );
+        let _ = writeln!(
+            out,
+            "  Memory     RSS {} before, {} at peak; engine buffers {}",
+            megabytes(self.resident_before),
+            megabytes(self.resident_peak),
+            megabytes(Some(self.buffer_bytes as u64))
+        );
+
+        let peak_per_min = rates.iter().cloned().fold(0.0, f64::max) * 60.0;
+        if self.rate_per_min == 0 && self.trades.dropped + self.books.dropped + self.trades.blocked + self.books.blocked == 0 {
+            let _ = writeln!(out, "\n📈 The engine took all of up to {:.0} messages/min, as fast as they were sent", peak_per_min);
+        } else if self.rate_per_min == 0 {
+            let _ = writeln!(out, "\n📈 The engine takes up to {:.0} messages/min", peak_per_min);
+        } else if self.kept_up() {
+            let _ = writeln!(out, "\n✅ The engine kept up");
+        } else {
+            let _ = writeln!(out, "\n❌ The engine fell behind: raise streams.capacity, lower the rate or profile the engine");
+        }
+        out
+    }
+}
+
+fn percentile(sorted: &[u64], fraction: f64) -> u64 {
+    match sorted.len() {
+        0 => 0,
+        len => sorted[((len - 1) as f64 * fraction).round() as usize],
+    }
+}
+
+fn megabytes(bytes: Option<u64>) -> String {
+    match bytes {
+        Some(bytes) => format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+        None => "n/a".to_string(),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+/// The process's resident memory, from `/proc`; none off Linux.
+fn resident_bytes() -> Option<u64> {
+    proc_status_kb("VmRSS:").map(|kb| kb * 1024)
+}
+
+fn peak_resident_bytes() -> Option<u64> {
+    proc_status_kb("VmHWM:").map(|kb| kb * 1024)
+}
+
+fn proc_status_kb(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with(field))?;
+    line[field.len()..].trim().trim_end_matches("kB").trim().parse().ok()
+}
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::LogFileSettings;
+
+/// Set by `open`; records are only shown until then.
+static FILE: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+/// Targets of the records shown in the Logs tab so far, which is its target list.
+static TARGETS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Where log records are shown.
+pub enum Console {
+    /// The dashboard's Logs tab, up to debug level.
+    Tui,
+    Stderr(env_logger::Logger),
+}
+
+struct Logger {
+    console: Console,
+}
+
+/// Installs the global logger, showing records on `console`. `open` adds the file.
+pub fn init(console: Console) -> Result<()> {
+    let level = match &console {
+        Console::Tui => {
+            // What `tui_logger::init_logger` does besides installing its own logger:
+            // move records from its hot buffer to the one the widget reads.
+            std::thread::Builder::new().name("tui-logger::move_events".into()).spawn(|| loop {
+                std::thread::park_timeout(Duration::from_millis(10));
+                tui_logger::move_events();
+            })?;
+            tui_logger::set_default_level(LevelFilter::Debug);
+            LevelFilter::Debug
+        }
+        Console::Stderr(logger) => logger.filter(),
+    };
+    log::set_boxed_logger(Box::new(Logger { console }))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// The targets the Logs tab lists, in its order.
+pub fn targets() -> Vec<String> {
+    TARGETS.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+}
+
+/// Starts copying records at `settings.level` and above to `settings.path`,
+/// appending to what is already there.
+pub fn open(settings: &LogFileSettings) -> Result<()> {
+    let level: LevelFilter = settings.level.parse().map_err(|_| anyhow!("invalid log level {}", settings.level))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&settings.path)
+        .map_err(|e| anyhow!("cannot open log file {}: {}", settings.path, e))?;
+    let len = file.metadata()?.len();
+    *FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(RotatingFile {
+        path: settings.path.clone(),
+        file,
+        len,
+        level,
+        max_bytes: settings.max_size_mb * 1024 * 1024,
+        keep: settings.keep,
+    });
+    if level > log::max_level() {
+        log::set_max_level(level);
+    }
+    Ok(())
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let console = match &self.console {
+            Console::Tui => metadata.level() <= Level::Debug,
+            Console::Stderr(logger) => logger.enabled(metadata),
+        };
+        console || FILE.lock().is_ok_and(|file| file.as_ref().is_some_and(|file| metadata.level() <= file.level))
+    }
+
+    fn log(&self, record: &Record) {
+        match &self.console {
+            Console::Tui if record.level() <= Level::Debug => {
+                let mut targets = TARGETS.lock().unwrap_or_else(|e| e.into_inner());
+                if !targets.contains(record.target()) {
+                    targets.insert(record.target().to_string());
+                }
+                drop(targets);
+                tui_logger::Drain::new().log(record);
+            }
+            Console::Tui => {}
+            Console::Stderr(logger) => logger.log(record),
+        }
+
+        let mut file = FILE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(file) = file.as_mut().filter(|file| record.level() <= file.level) {
+            let line = format!(
+                "[{} {:<5} {}] {}\n",
+                chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            // Nowhere left to report a failing log file.
+            let _ = file.write(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Console::Stderr(logger) = &self.console {
+            logger.flush();
+        }
+    }
+}
+
+/// Size-rotated log: `path` is current, `path.1` to `path.<keep>` are older.
+struct RotatingFile {
+    path: String,
+    file: File,
+    len: u64,
+    level: LevelFilter,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    /// Unbuffered, so nothing is lost when the process dies.
+    fn write(&mut self, line: &[u8]) -> std::io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let numbered = |n: usize| format!("{}.{}", self.path, n);
+        if self.keep > 0 {
+            let _ = std::fs::remove_file(numbered(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = std::fs::rename(numbered(n), numbered(n + 1));
+            }
+            std::fs::rename(&self.path, numbered(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, Event, KeyCode, KeyModifiers},
     execute,
@@ -11,13 +11,16 @@ use ratatui::{
     Terminal,
 };
 use std::{
+    collections::HashSet,
+    future::Future,
     io,
     sync::Arc,
     time::Duration,
 };
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use rust_decimal::prelude::*;
-use tui_logger;
 
 mod api;
 mod config;
@@ -25,12 +28,68 @@ mod model;
 mod metrics;
 mod ui;
 mod alert;
+mod history;
+mod loading;
+mod session;
+mod storage;
+mod exporter;
+mod api_server;
+mod server;
+mod ws_server;
+mod report;
+mod telemetry;
+mod statsd;
+mod mqtt;
+mod systemd;
+mod forensics;
+mod daily_report;
+mod upload;
+mod control;
+mod attach;
+mod ssh;
+mod reload;
+mod diagnostics;
+mod secrets;
+mod wizard;
+mod logfile;
+mod check;
+mod versioned;
+mod pipeline;
+mod ring;
+mod shared;
+mod shutdown;
+mod latency;
+mod universe;
+mod address_book;
+mod illiquid;
+mod probation;
+mod scenario;
+mod synthetic;
+mod loadtest;
+mod backtest;
+mod tune;
 
 use config::{Config, OperatingMode};
-use api::provider::DataProvider;
+use api::provider::{DataProvider, DataSourceStatus};
 use model::*;
-use ui::ui::UIState;
+use ui::ui::{ChartState, UIState, ALERTS_TAB, CHARTS_TAB, LIQUIDITY_TAB, LOGS_TAB, TAB_TITLES};
+use tui_logger::TuiWidgetEvent;
+use history::{MetricHistory, CANDLE_BACKFILL_MS, CANDLE_GAP_MS, CANDLE_INTERVAL};
+use loading::{LoadingProgress, StepStatus};
+use session::{SessionSummary, SessionTracker};
+use storage::{raw::RawCapture, MetricStore, StoreWriter};
+use metrics::cycle::{compute_metrics, CycleData};
 use metrics::streaming::StreamingMetricsEngine;
+use forensics::ForensicRecorder;
+use control::Control;
+use shared::SharedMetrics;
+use shutdown::Shutdown;
+use versioned::Versioned;
+use universe::UniverseWatch;
+use address_book::AddressBook;
+use illiquid::IlliquidWatch;
+use probation::Probation;
+use synthetic::SyntheticMarket;
 
 
 
@@ -38,147 +97,930 @@ use metrics::streaming::StreamingMetricsEngine;
 #[command(name = "hlp-toshogu")]
 #[command(about = "HLP Toshogu Terminal Dashboard for Hyperliquid")]
 struct Args {
+    /// Config file; config.toml when omitted
+    #[arg(short, long, global = true)]
+    config: Option<String>,
+    
+    /// Log to stderr at debug level instead of the Logs tab
+    #[arg(long, global = true)]
+    debug: bool,
+    
+    /// Also write logs to this file, rotated by size; see [log_file] in the config
+    #[arg(long, global = true)]
+    log_file: Option<String>,
+    
+    /// What to do; `run` when omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Which accounts to monitor instead of the config's, shared by the commands that
+/// monitor them.
+#[derive(clap::Args, Default)]
+struct AccountArgs {
+    /// Monitor this account instead of the config's user_address
     #[arg(long)]
-    generate_config: bool,
+    user_address: Option<String>,
     
-    #[arg(short, long)]
-    config: Option<String>,
+    /// Use this vault instead of the config's vault_address
+    #[arg(long)]
+    vault_address: Option<String>,
     
+    /// Monitor this vault as both account and vault: `hlp` for the HLP vault,
+    /// or a vault address. Several, comma separated or repeated, are monitored
+    /// together
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["user_address", "vault_address"])]
+    vault: Vec<String>,
+}
+
+impl AccountArgs {
+    /// Applies `--user-address` and `--vault-address`, or `--vault`, to `config`.
+    fn apply(&self, config: &mut Config) -> Result<()> {
+        if self.vault.is_empty() {
+            config::override_addresses(config, self.user_address.as_deref(), self.vault_address.as_deref())?;
+        } else {
+            let vaults = self.vault.iter().map(|vault| config::vault_preset(vault)).collect::<Result<Vec<_>>>()?;
+            config::override_vaults(config, &vaults);
+        }
+        Ok(())
+    }
+}
+
+#[derive(clap::Args, Default)]
+struct RunArgs {
+    /// Overlay the simulated market of [demo] on the live metrics, and fall back to
+    /// simulated data when collection fails
     #[arg(long)]
     test_mode: bool,
     
+    /// Inject the faults of [chaos] into the Hyperliquid connection
     #[arg(long)]
-    debug: bool,
+    chaos: bool,
+    
+    #[command(flatten)]
+    accounts: AccountArgs,
+    
+    #[command(flatten)]
+    dashboard: DashboardArgs,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+/// How the collected metrics are shown, shared by the commands that run the dashboard.
+#[derive(clap::Args, Default)]
+struct DashboardArgs {
+    /// Render with plain ASCII instead of emoji, braille and box drawing
+    #[arg(long)]
+    ascii: bool,
+    
+    /// Run without the dashboard, e.g. as a systemd service: collect, serve and
+    /// publish until SIGTERM, logging to stderr
+    #[arg(long)]
+    headless: bool,
+    
+    /// With --headless, also print each metrics update to stdout: `ndjson` writes
+    /// one JSON object per line
+    #[arg(long, value_enum, requires = "headless")]
+    output: Option<OutputFormat>,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum OutputFormat {
+    Ndjson,
+}
 
-    let args = Args::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Run the dashboard; simulated data when operating_mode = "Demo"
+    Run(RunArgs),
+    /// Run the dashboard on simulated data, without connecting to Hyperliquid
+    Demo {
+        /// Play an incident through the full metric and alert pipeline instead: a
+        /// bundled scenario such as `jelly`, or a scenario file
+        #[arg(long)]
+        scenario: Option<String>,
+        
+        /// With --scenario, run it without the dashboard and report which expected
+        /// alerts fired; exits non-zero when any did not
+        #[arg(long, requires = "scenario")]
+        check: bool,
+        
+        #[command(flatten)]
+        dashboard: DashboardArgs,
+    },
+    /// Write metric history from the [storage] database as CSV
+    Export {
+        /// Comma-separated metrics: short names (vpin, pli, equity, ...) or stored names
+        /// such as liquidity.bid_ask_spread_bps.BTC
+        #[arg(long, value_delimiter = ',', required = true)]
+        metric: Vec<String>,
+        
+        /// How far back to export, e.g. 30m, 24h, 7d
+        #[arg(long, default_value = "24h")]
+        since: String,
+        
+        /// Output file; stdout when omitted
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Collect metrics once and print a risk report: headline metrics, top risks,
+    /// open alerts and positions
+    Report {
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+        
+        /// Report the latest snapshot in the [storage] database instead of collecting
+        #[arg(long)]
+        from_store: bool,
+        
+        /// Output file; stdout when omitted
+        #[arg(long)]
+        out: Option<String>,
+        
+        #[command(flatten)]
+        accounts: AccountArgs,
+    },
+    /// Check the API, WebSocket feed, addresses and notification channels, sending a
+    /// test message through each channel; exits non-zero when any check fails
+    Check {
+        #[command(flatten)]
+        accounts: AccountArgs,
+    },
+    /// Run the live dashboard while recording every provider response to a session directory
+    Record {
+        #[arg(long)]
+        out: String,
+        
+        #[command(flatten)]
+        accounts: AccountArgs,
+        
+        #[command(flatten)]
+        dashboard: DashboardArgs,
+    },
+    /// Play a recorded session back in the dashboard
+    Replay {
+        /// Directory written by `record`
+        dir: String,
+        
+        /// Playback speed relative to the recording, e.g. 1x, 10x, 0.5x
+        #[arg(long, default_value = "1x")]
+        speed: String,
+        
+        #[command(flatten)]
+        dashboard: DashboardArgs,
+    },
+    /// Replay the account's fill history through the performance and risk metrics and
+    /// report their series by window: PnL, drawdown, Sharpe, VPIN
+    Backtest {
+        /// How far back, e.g. 7d or 90d; Hyperliquid serves an account's latest 10,000 fills
+        #[arg(long, default_value = "30d")]
+        since: String,
+        
+        /// Length of each point of the series, e.g. 1h or 1d
+        #[arg(long, default_value = "1d")]
+        window: String,
+        
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+        
+        /// Output file; stdout when omitted
+        #[arg(long)]
+        out: Option<String>,
+        
+        #[command(flatten)]
+        accounts: AccountArgs,
+    },
+    /// Replay the metric store against candidate alert thresholds and report how many
+    /// alerts each would have raised and which known incidents it would have caught
+    Tune {
+        /// TOML file of [[candidates]], each a name and the [alert_thresholds] it
+        /// changes, and [[incidents]] with a name, start, end and expected alerts
+        file: String,
+        
+        /// How much of the stored history to replay, e.g. 24h or 30d
+        #[arg(long, default_value = "7d")]
+        since: String,
+        
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+        
+        /// Output file; stdout when omitted
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Push synthetic trades and book updates through the streaming engine at a high
+    /// rate and report its throughput, queue lag and memory; exits non-zero when it
+    /// falls behind
+    LoadTest {
+        /// Messages per minute, half trades and half book updates; 0 sends them as
+        /// fast as the engine takes them
+        #[arg(long, default_value_t = 300_000)]
+        rate: u64,
+        
+        /// How long to run, e.g. 30s, 5m
+        #[arg(long, default_value = "1m")]
+        duration: String,
+    },
+    /// Attach read-only to the dashboard of a running instance with `[attach]`
+    /// enabled, e.g. `ssh -t host hlp-toshogu attach`
+    Attach {
+        /// Attach socket; `[attach].socket` from the config when omitted
+        #[arg(long)]
+        socket: Option<String>,
+    },
+    /// Create, check or print the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+impl Command {
+    fn dashboard(&self) -> Option<&DashboardArgs> {
+        match self {
+            Command::Run(RunArgs { dashboard, .. })
+            | Command::Demo { dashboard, .. }
+            | Command::Record { dashboard, .. }
+            | Command::Replay { dashboard, .. } => Some(dashboard),
+            Command::Export { .. } | Command::Report { .. } | Command::Check { .. } | Command::Backtest { .. } | Command::Tune { .. } | Command::LoadTest { .. } | Command::Attach { .. } | Command::Config { .. } => None,
+        }
+    }
+    
+    /// `--chaos`, which only `run` takes: the other commands have no live connection to
+    /// fault, or should not have one faulted.
+    fn chaos(&self) -> bool {
+        matches!(self, Command::Run(RunArgs { chaos: true, .. }))
+    }
     
-    if args.debug {
-        env_logger::Builder::from_default_env()
-            .filter_level(log::LevelFilter::Debug)
-            .init();
+    /// The account overrides of the commands that monitor accounts.
+    fn accounts(&self) -> Option<&AccountArgs> {
+        match self {
+            Command::Run(RunArgs { accounts, .. })
+            | Command::Report { accounts, .. }
+            | Command::Check { accounts }
+            | Command::Record { accounts, .. }
+            | Command::Backtest { accounts, .. } => Some(accounts),
+            Command::Demo { .. } | Command::Export { .. } | Command::Replay { .. } | Command::Tune { .. } | Command::LoadTest { .. } | Command::Attach { .. } | Command::Config { .. } => None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Write a config file with every setting at its default
+    Generate {
+        #[arg(long, default_value = "config.toml")]
+        out: String,
+        
+        /// Replace the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check the config and report every problem with a suggested fix; exits
+    /// non-zero when there are errors
+    #[command(alias = "check")]
+    Validate {
+        /// Skip the checks that contact Hyperliquid and the configured endpoints
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Print the config in effect, including HLP_* environment overrides; secrets
+    /// from the environment or keyring are left out
+    Show,
+    /// Store a secret such as mqtt.password in the OS keyring, read from stdin
+    SetSecret {
+        /// One of mqtt.password, upload.access_key_id, upload.secret_access_key,
+        /// upload.session_token
+        name: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = Args::parse();
+    let command = args.command.take().unwrap_or(Command::Run(RunArgs::default()));
+    let headless = command.dashboard().is_some_and(|dashboard| dashboard.headless);
+    
+    if args.debug || headless {
+        let stderr = env_logger::Builder::from_default_env()
+            .filter_level(if args.debug { log::LevelFilter::Debug } else { log::LevelFilter::Info })
+            .build();
+        logfile::init(logfile::Console::Stderr(stderr))?;
     } else {
-        tui_logger::init_logger(log::LevelFilter::Debug).unwrap();
-        tui_logger::set_default_level(log::LevelFilter::Debug);
+        logfile::init(logfile::Console::Tui)?;
     }
     
-    print_startup_banner();
+    let dashboard = match &command {
+        Command::Export { metric, since, out } => {
+            let config = load_config(&args, &command)?;
+            return run_export(&config, metric, since, out.as_deref());
+        }
+        Command::Report { format, from_store, out, .. } => {
+            let config = load_config(&args, &command)?;
+            open_log_file(&config, &args)?;
+            return run_report(&config, *format, *from_store, out.as_deref()).await;
+        }
+        Command::Check { .. } => {
+            let config = load_config(&args, &command)?;
+            return run_check(&config).await;
+        }
+        Command::Backtest { since, window, format, out, .. } => {
+            let config = load_config(&args, &command)?;
+            open_log_file(&config, &args)?;
+            return run_backtest(&config, since, window, *format, out.as_deref()).await;
+        }
+        Command::Tune { file, since, format, out } => {
+            let config = load_config(&args, &command)?;
+            open_log_file(&config, &args)?;
+            return run_tune(&config, file, since, *format, out.as_deref());
+        }
+        Command::LoadTest { rate, duration } => {
+            let config = load_config(&args, &command)?;
+            return run_load_test(&config, *rate, duration).await;
+        }
+        Command::Attach { socket } => {
+            let socket = match socket {
+                Some(socket) => socket.clone(),
+                None => load_config(&args, &command)?.attach.socket,
+            };
+            return attach::run(&socket);
+        }
+        Command::Config { action } => return run_config(action, &args).await,
+        Command::Demo { scenario: Some(scenario), check: true, .. } => {
+            let config = load_config(&args, &command)?;
+            return run_scenario_check(&config, scenario).await;
+        }
+        command => command.dashboard().expect("every other command runs the dashboard"),
+    };
+    
+    let path = args.config.as_deref().unwrap_or("config.toml");
+    if !dashboard.headless && !std::path::Path::new(path).exists() && io::IsTerminal::is_terminal(&io::stdin()) {
+        match wizard::run(path)? {
+            Some(_) => println!("✅ Configuration written to {}", path),
+            None => {
+                println!("Setup cancelled; `hlp-toshogu config generate` writes a config with every default");
+                return Ok(());
+            }
+        }
+    }
     
-    if args.generate_config {
-        config::generate_sample_config()?;
-        println!("✅ Sample configuration generated at config.toml");
-        return Ok(());
+    if !dashboard.headless {
+        print_startup_banner(dashboard.ascii);
     }
     
-    let config = config::load_config(args.config.as_deref())?;
+    let mut config = load_config(&args, &command)?;
+    open_log_file(&config, &args)?;
+    config.ui_settings.ascii_mode |= dashboard.ascii;
+    config.service.headless = dashboard.headless;
+    config.service.ndjson = dashboard.output == Some(OutputFormat::Ndjson);
     
-    match config.operating_mode {
-        OperatingMode::Live => run_live_mode(config, args.test_mode, args.debug).await,
-        OperatingMode::Demo => run_demo_mode(config, args.test_mode, args.debug).await,
+    if config.telemetry.enabled {
+        telemetry::init(&config.telemetry)?;
+    }
+    
+    match command {
+        Command::Record { out, .. } => run_record_mode(config, &out, args.debug).await,
+        Command::Replay { dir, speed, .. } => run_replay_mode(config, &dir, &speed, args.debug).await,
+        Command::Demo { scenario: Some(scenario), .. } => run_scenario_mode(config, &scenario, args.debug).await,
+        Command::Demo { .. } => run_demo_mode(config, args.debug).await,
+        Command::Run(RunArgs { test_mode, .. }) => match config.operating_mode {
+            OperatingMode::Live => run_live_mode(config, test_mode, args.debug).await,
+            OperatingMode::Demo => run_demo_mode(config, args.debug).await,
+        },
+        Command::Export { .. } | Command::Report { .. } | Command::Check { .. } | Command::Backtest { .. } | Command::Tune { .. } | Command::LoadTest { .. } | Command::Attach { .. } | Command::Config { .. } => unreachable!(),
+    }
+}
+
+/// The config with the command's account overrides applied.
+fn load_config(args: &Args, command: &Command) -> Result<Config> {
+    let mut config = config::load_config(args.config.as_deref())?;
+    config.chaos.enabled |= command.chaos();
+    if let Some(accounts) = command.accounts() {
+        accounts.apply(&mut config)?;
     }
+    Ok(config)
 }
 
-pub fn print_startup_banner() {
-    println!("╔══════════════════════════════════════════════════════════════╗");
-    println!("║                    HLP TOSHOGU DASHBOARD                     ║");
-    println!("║                                                              ║");
-    println!("║           Advanced Market Microstructure Monitoring          ║");
-    println!("║              Post-JELLY Incident Risk Management             ║");
-    println!("║                                                              ║");
-    println!("║  Metrics: PLI | VPIN | Liquidation Risk | Phantom Liquidity  ║");
-    println!("╚══════════════════════════════════════════════════════════════╝");
+/// `[log_file]`, or `--log-file`, which turns it on without changing the config.
+fn open_log_file(config: &Config, args: &Args) -> Result<()> {
+    let mut settings = config.log_file.clone();
+    if let Some(path) = &args.log_file {
+        settings.enabled = true;
+        settings.path = path.clone();
+    }
+    if settings.enabled {
+        logfile::open(&settings)?;
+    }
+    Ok(())
+}
+
+/// A line from stdin, without echo when it is a terminal.
+fn read_secret(prompt: &str) -> Result<String> {
+    use std::io::{BufRead, IsTerminal, Write};
+    if !io::stdin().is_terminal() {
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+    
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    enable_raw_mode()?;
+    let mut value = String::new();
+    let read = loop {
+        match event::read() {
+            Ok(Event::Key(key)) => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Esc => break Err(anyhow::anyhow!("cancelled")),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Err(anyhow::anyhow!("cancelled")),
+                KeyCode::Backspace => {
+                    value.pop();
+                }
+                KeyCode::Char(c) => value.push(c),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    disable_raw_mode()?;
+    eprintln!();
+    read.map(|_| value)
+}
+
+async fn run_config(action: &ConfigAction, args: &Args) -> Result<()> {
+    let path = args.config.as_deref();
+    match action {
+        ConfigAction::Generate { out, force } => {
+            if !force && std::path::Path::new(out).exists() {
+                return Err(anyhow::anyhow!("{} already exists; pass --force to replace it", out));
+            }
+            config::generate_sample_config(out)?;
+            println!("✅ Sample configuration generated at {}", out);
+        }
+        ConfigAction::Validate { offline } => {
+            let config = match config::read_config(path) {
+                Ok(config) => config,
+                Err(e) => {
+                    println!("❌ {}: {}", path.unwrap_or("config.toml"), e);
+                    std::process::exit(1);
+                }
+            };
+            let findings = diagnostics::validate(&config, !offline).await;
+            for finding in &findings {
+                let icon = if finding.severity == diagnostics::Severity::Error { "❌" } else { "⚠️" };
+                println!("{} {}", icon, finding.message);
+                if let Some(hint) = &finding.hint {
+                    println!("   ↳ {}", hint);
+                }
+            }
+            let errors = findings.iter().filter(|f| f.severity == diagnostics::Severity::Error).count();
+            let warnings = findings.len() - errors;
+            let plural = |n: usize| if n == 1 { "" } else { "s" };
+            if errors > 0 {
+                println!("❌ {}: {} error{}, {} warning{}", config.path, errors, plural(errors), warnings, plural(warnings));
+                std::process::exit(1);
+            }
+            println!("✅ {} is valid ({} warning{})", config.path, warnings, plural(warnings));
+        }
+        ConfigAction::Show => {
+            let mut config = config::load_config(path)?;
+            let sources = config.secret_sources.clone();
+            secrets::strip(&mut config);
+            print!("{}", toml::to_string_pretty(&config)?);
+            for (name, source) in sources {
+                let from = match source {
+                    secrets::Source::Env => "the environment",
+                    secrets::Source::Keyring => "the keyring",
+                };
+                println!("# {} is set from {}", name, from);
+            }
+        }
+        ConfigAction::SetSecret { name } => {
+            let value = read_secret(&format!("Value for {}: ", name))?;
+            if value.is_empty() {
+                return Err(anyhow::anyhow!("no value given for {}", name));
+            }
+            secrets::store(name, &value)?;
+            println!("✅ Stored {} in the keyring; leave it out of the config file", name);
+        }
+    }
+    Ok(())
+}
+
+async fn run_check(config: &Config) -> Result<()> {
+    let outcomes = check::run(config).await;
+    for outcome in &outcomes {
+        match &outcome.result {
+            Ok(detail) => println!("✅ {}: {}", outcome.name, detail),
+            Err(e) => println!("❌ {}: {}", outcome.name, e),
+        }
+    }
+    let failed = outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+    if failed > 0 {
+        println!("❌ {} of {} checks failed", failed, outcomes.len());
+        std::process::exit(1);
+    }
+    println!("✅ All {} checks passed", outcomes.len());
+    Ok(())
+}
+
+fn run_export(config: &Config, metrics: &[String], since: &str, out: Option<&str>) -> Result<()> {
+    let store = MetricStore::open_existing(&config.storage)?;
+    let known = store.metric_names()?;
+    
+    let metrics: Vec<String> = metrics.iter().map(|m| storage::resolve_metric(m.trim())).collect();
+    if let Some(unknown) = metrics.iter().find(|m| !known.contains(m)) {
+        return Err(anyhow::anyhow!(
+            "no samples stored for metric {}; use one of {} or a stored name like {}",
+            unknown,
+            storage::metric_aliases().collect::<Vec<_>>().join(", "),
+            known.first().map_or("vault.tvl", String::as_str)
+        ));
+    }
+    
+    let start_ms = chrono::Utc::now().timestamp_millis() - parse_duration(since)?.as_millis() as i64;
+    let rows = match out {
+        Some(path) => store.write_csv(&metrics, start_ms, &mut std::io::BufWriter::new(std::fs::File::create(path)?))?,
+        None => store.write_csv(&metrics, start_ms, &mut std::io::stdout().lock())?,
+    };
+    
+    eprintln!("✅ Exported {} rows{}", rows, out.map(|path| format!(" to {}", path)).unwrap_or_default());
+    Ok(())
+}
+
+async fn run_report(config: &Config, format: report::ReportFormat, from_store: bool, out: Option<&str>) -> Result<()> {
+    let (metrics, source) = if from_store {
+        let snapshot = MetricStore::open_existing(&config.storage)?.latest_snapshot()?;
+        let snapshot = snapshot.ok_or_else(|| anyhow::anyhow!("metric store {} has no samples yet", config.storage.path))?;
+        (snapshot, report::ReportSource::Store)
+    } else {
+        let metrics = match config.operating_mode {
+            // One snapshot needs no streams.
+            OperatingMode::Live => {
+                let config = Config { enable_websocket: false, ..config.clone() };
+                let provider = api::sdk::HyperliquidProvider::new(&config, LoadingProgress::new(false)).await?;
+                update_metrics(&provider, &None).await?
+            }
+            OperatingMode::Demo => {
+                let mut metrics = create_test_metrics(1);
+                let mut market = SyntheticMarket::new(&config.demo);
+                market.step();
+                market.apply(&mut metrics);
+                metrics
+            }
+        };
+        (metrics, report::ReportSource::Live)
+    };
+    
+    let text = report::Report::build(&metrics, &config.alert_thresholds, source).render(format)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            eprintln!("✅ Report written to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Parses a count with an `s`, `m`, `h` or `d` suffix, such as `30m` or `24h`.
+fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || anyhow::anyhow!("invalid duration {:?}, expected e.g. 30m, 24h or 7d", text);
+    let split = text.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let count: u64 = text[..split].parse().map_err(|_| invalid())?;
+    let unit_secs = match &text[split..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(count * unit_secs))
+}
+
+pub fn print_startup_banner(ascii_mode: bool) {
+    let banner = [
+        "╔══════════════════════════════════════════════════════════════╗",
+        "║                    HLP TOSHOGU DASHBOARD                     ║",
+        "║                                                              ║",
+        "║           Advanced Market Microstructure Monitoring          ║",
+        "║              Post-JELLY Incident Risk Management             ║",
+        "║                                                              ║",
+        "║  Metrics: PLI | VPIN | Liquidation Risk | Phantom Liquidity  ║",
+        "╚══════════════════════════════════════════════════════════════╝",
+    ];
+    for line in banner {
+        if ascii_mode {
+            println!("{}", ui::ascii::asciify_str(line));
+        } else {
+            println!("{}", line);
+        }
+    }
     println!();
 }
 
 async fn run_live_mode(config: Config, test_mode: bool, debug_mode: bool) -> Result<()> {
     info!("🚀 Starting live mode (test_mode: {}, debug: {})", test_mode, debug_mode);
     
-    let provider = api::sdk::HyperliquidProvider::new(&config).await?;
-    run_dashboard(provider, config, test_mode, debug_mode).await
+    let progress = LoadingProgress::new(config.enable_websocket);
+    let connect = {
+        let config = config.clone();
+        let progress = progress.clone();
+        async move { api::sdk::HyperliquidProvider::new(&config, progress).await }
+    };
+    run_dashboard(connect, progress, config, test_mode, debug_mode).await
 }
 
-async fn run_demo_mode(config: Config, test_mode: bool, debug_mode: bool) -> Result<()> {
-    info!("🧪 Starting demo mode (test_mode: {}, debug: {})", test_mode, debug_mode);
+/// Live mode with every provider response also written to `out`, for `replay`.
+async fn run_record_mode(mut config: Config, out: &str, debug_mode: bool) -> Result<()> {
+    if !matches!(config.operating_mode, OperatingMode::Live) {
+        return Err(anyhow::anyhow!("record needs operating_mode = \"Live\""));
+    }
+    config.upload.session_dir = Some(std::path::PathBuf::from(out));
+    info!("⏺️ Starting live mode with recording to {}", out);
     
-    if test_mode {
-        info!("📊 Using simulated test data for demo mode");
-        run_test_dashboard(config, debug_mode).await
-    } else {
-        eprintln!("❌ Demo mode requires --test-mode flag");
+    let progress = LoadingProgress::new(config.enable_websocket);
+    let connect = {
+        let config = config.clone();
+        let progress = progress.clone();
+        let dir = std::path::PathBuf::from(out);
+        async move {
+            let provider = api::sdk::HyperliquidProvider::new(&config, progress).await?;
+            api::recording::RecordingProvider::new(provider, &dir, &config)
+        }
+    };
+    run_dashboard(connect, progress, config, false, debug_mode).await
+}
+
+/// Plays a recorded session back through the normal collection loop, polling
+/// `speed` times as often as it was recorded so no recorded cycle is skipped.
+async fn run_replay_mode(mut config: Config, dir: &str, speed: &str, debug_mode: bool) -> Result<()> {
+    let speed = parse_speed(speed)?;
+    let provider = api::replay::ReplayProvider::open(std::path::Path::new(dir), speed)?;
+    
+    config.update_interval_ms = ((provider.session().update_interval_ms as f64 / speed) as u64).max(50);
+    // Streams are not part of a recording, and replayed metrics must not mix with live history.
+    config.enable_websocket = false;
+    config.storage.enabled = false;
+    config.raw_capture.enabled = false;
+    
+    run_dashboard(async move { Ok(provider) }, LoadingProgress::simulated(), config, false, debug_mode).await
+}
+
+/// Parses a playback speed such as `10x`, `0.5x` or `2`.
+fn parse_speed(text: &str) -> Result<f64> {
+    match text.strip_suffix('x').unwrap_or(text).parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(anyhow::anyhow!("invalid speed {:?}, expected e.g. 1x, 10x or 0.5x", text)),
+    }
+}
+
+/// Plays a scenario through the normal collection loop, one scenario cycle per
+/// `update_interval_ms`.
+async fn run_scenario_mode(mut config: Config, scenario: &str, debug_mode: bool) -> Result<()> {
+    let provider = api::scenario::ScenarioProvider::new(scenario::Scenario::load(scenario)?);
+    // Scenarios have no streams, and their metrics must not mix with live history.
+    config.enable_websocket = false;
+    config.storage.enabled = false;
+    config.raw_capture.enabled = false;
+    
+    run_dashboard(async move { Ok(provider) }, LoadingProgress::simulated(), config, false, debug_mode).await
+}
+
+/// Plays a scenario cycle by cycle through the same fetch, metric and alert
+/// steps as the dashboard and reports which expected alerts fired, and which
+/// fired in a phase expecting none.
+async fn run_scenario_check(config: &Config, scenario: &str) -> Result<()> {
+    let scenario = scenario::Scenario::load(scenario)?;
+    let provider = api::scenario::ScenarioProvider::new(scenario.clone());
+    let mut check = scenario::ScenarioCheck::new(&scenario);
+    // Fed the scenario's fills as trades and its order events, as the streams would feed it.
+    let engine = Arc::new(RwLock::new(crate::metrics::streaming::StreamingMetricsEngine::new(&config.buffers, &config.whales)));
+    let streaming = Some(engine.clone());
+    for cycle in 1..=scenario.cycles() {
+        let data = fetch_cycle_data(&provider, &streaming).await?;
+        {
+            let mut engine = engine.write().await;
+            for fill in &data.recent_fills {
+                engine.process_trade(fill.clone());
+            }
+            for event in provider.order_events() {
+                engine.process_order(&event);
+            }
+        }
+        let metrics = compute_metrics(data, &streaming).await;
+        check.record(cycle, &alert::check_alerts(&metrics, &config.alert_thresholds));
+    }
+    print!("{}", check.report());
+    
+    let (missing, unexpected) = (check.missing(), check.unexpected());
+    if !missing.is_empty() || !unexpected.is_empty() {
+        println!("\n❌ {} expected alerts did not fire, {} fired in a phase expecting none", missing.len(), unexpected.len());
+        std::process::exit(1);
+    }
+    println!("\n✅ Every expected alert fired, and no other in a phase expecting none");
+    Ok(())
+}
+
+async fn run_backtest(config: &Config, since: &str, window: &str, format: report::ReportFormat, out: Option<&str>) -> Result<()> {
+    let (since, window) = (parse_duration(since)?, parse_duration(window)?);
+    if window.is_zero() || window > since {
+        return Err(anyhow::anyhow!("--window must be more than zero and at most --since"));
+    }
+    let client = api::sdk::InfoClient::new(config.hyperliquid_api_url.clone(), None);
+    let backtest = backtest::run(&client, &config.user_address, since.as_millis() as u64, window.as_millis() as u64).await?;
+    let text = backtest.render(format)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            eprintln!("✅ Backtest written to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn run_tune(config: &Config, file: &str, since: &str, format: report::ReportFormat, out: Option<&str>) -> Result<()> {
+    let to = chrono::Utc::now();
+    let from = to - chrono::Duration::from_std(parse_duration(since)?)?;
+    let (candidates, incidents) = tune::load(file, &config.alert_thresholds)?;
+    let store = MetricStore::open_existing(&config.storage)?;
+    let text = tune::Tuning::run(&store, &candidates, incidents, from, to)?.render(format)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, text)?;
+            eprintln!("✅ Tuning report written to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+async fn run_load_test(config: &Config, rate: u64, duration: &str) -> Result<()> {
+    let duration = parse_duration(duration)?;
+    let report = loadtest::run(config, rate, duration).await;
+    print!("{}", report.report());
+    if !report.kept_up() {
         std::process::exit(1);
     }
+    Ok(())
 }
 
-async fn run_dashboard<P: DataProvider + Send + Sync + 'static>(
-    provider: P, 
+async fn run_demo_mode(config: Config, debug_mode: bool) -> Result<()> {
+    info!("🧪 Starting demo mode (debug: {})", debug_mode);
+    info!("📊 Using simulated test data for demo mode");
+    run_test_dashboard(config, debug_mode).await
+}
+
+/// Connects the provider in the background so the loading screen can report each
+/// initialization step while it happens.
+async fn run_dashboard<P, F>(
+    connect: F,
+    progress: LoadingProgress,
     config: Config,
     test_mode: bool,
     debug_mode: bool,
-) -> Result<()> {
-    let provider = Arc::new(provider);
-    let metrics = Arc::new(RwLock::new(GlobalMetrics::default()));
-    let alerts = Arc::new(RwLock::new(Vec::<Alert>::new()));
+) -> Result<()>
+where
+    P: DataProvider + Send + Sync + 'static,
+    F: Future<Output = Result<P>> + Send + 'static,
+{
+    let metrics = Arc::new(SharedMetrics::new());
+    let alerts = Arc::new(Versioned::new(Vec::<Alert>::new()));
+    let history = Arc::new(RwLock::new(MetricHistory::new()));
     
     let metrics_clone = metrics.clone();
     let alerts_clone = alerts.clone();
-    let provider_clone = provider.clone();
-    let config_clone = config.clone();
+    let history_clone = history.clone();
+    let progress_clone = progress.clone();
+    let live_config = Arc::new(RwLock::new(Arc::new(config.clone())));
+    let config_clone = live_config.clone();
+    let control = Control::new();
+    let control_clone = control.clone();
+    let shutdown = Shutdown::new();
+    let shutdown_clone = shutdown.clone();
     
-    tokio::spawn(async move {
-        data_collection_loop(provider_clone, metrics_clone, alerts_clone, config_clone, test_mode).await;
+    shutdown.spawn("provider connection", async move {
+        match shutdown_clone.token().run_until_cancelled(connect).await {
+            Some(Ok(provider)) => {
+                let provider = Arc::new(provider);
+                shutdown_clone.supervise("data collection", alerts_clone.clone(), move |run| data_collection_loop(
+                    provider.clone(),
+                    metrics_clone.clone(),
+                    alerts_clone.clone(),
+                    history_clone.clone(),
+                    progress_clone.clone(),
+                    config_clone.clone(),
+                    control_clone.clone(),
+                    run,
+                    test_mode,
+                ));
+            }
+            Some(Err(e)) => progress_clone.fail(e.to_string()),
+            None => {}
+        }
     });
     
-    run_ui_enhanced(metrics, alerts, config, test_mode, debug_mode).await?;
+    spawn_servers(&config, &live_config, &metrics, &alerts, &history, &control, &shutdown);
     
-    Ok(())
+    let result = run_frontend(metrics, alerts, history, progress, live_config, test_mode, debug_mode).await;
+    shutdown.stop(shutdown::GRACE).await;
+    result
 }
 
 async fn run_test_dashboard(config: Config, debug_mode: bool) -> Result<()> {
-    let metrics = Arc::new(RwLock::new(GlobalMetrics::default()));
-    let alerts = Arc::new(RwLock::new(Vec::<Alert>::new()));
+    let metrics = Arc::new(SharedMetrics::new());
+    let alerts = Arc::new(Versioned::new(Vec::<Alert>::new()));
+    let history = Arc::new(RwLock::new(MetricHistory::new()));
     
     let metrics_clone = metrics.clone();
     let alerts_clone = alerts.clone();
-    let config_clone = config.clone();
+    let history_clone = history.clone();
+    let live_config = Arc::new(RwLock::new(Arc::new(config.clone())));
+    let config_clone = live_config.clone();
+    let control = Control::new();
+    let control_clone = control.clone();
+    let shutdown = Shutdown::new();
     
-    tokio::spawn(async move {
-        test_data_loop(metrics_clone, alerts_clone, config_clone).await;
+    shutdown.supervise("test data", alerts.clone(), move |run| {
+        test_data_loop(metrics_clone.clone(), alerts_clone.clone(), history_clone.clone(), config_clone.clone(), control_clone.clone(), run)
     });
     
-    run_ui_enhanced(metrics, alerts, config, true, debug_mode).await?;
+    spawn_servers(&config, &live_config, &metrics, &alerts, &history, &control, &shutdown);
     
-    Ok(())
+    let result = run_frontend(metrics, alerts, history, LoadingProgress::simulated(), live_config, true, debug_mode).await;
+    shutdown.stop(shutdown::GRACE).await;
+    result
 }
 
-async fn data_collection_loop<P: DataProvider>(
+/// Fetch stage of the dashboard: polls the provider every `update_interval_ms`
+/// and hands each cycle's data to `compute_loop`, which runs as its own task so
+/// neither stage waits for the other. The stream consumers it starts (the
+/// streaming engine, WebSocket reader, raw capture, forensics and candles) run
+/// under the shutdown token and stop when it is cancelled. On shutdown it closes
+/// the WebSocket, so the server sees a clean close, and returns, which lets
+/// `compute_loop` finish the last cycle and return too.
+#[allow(clippy::too_many_arguments)]
+async fn data_collection_loop<P: DataProvider + Send + Sync + 'static>(
     provider: Arc<P>,
-    metrics: Arc<RwLock<GlobalMetrics>>,
-    alerts: Arc<RwLock<Vec<Alert>>>,
-    config: Config,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    progress: LoadingProgress,
+    live_config: Arc<RwLock<Arc<Config>>>,
+    control: Arc<Control>,
+    shutdown: Shutdown,
     test_mode: bool,
-) {
+) -> Result<()> {
+    let mut config = live_config.read().await.clone();
     let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(config.update_interval_ms));
     let mut update_counter = 0;
+    // Assets whose candle history has been backfilled; ones added later get theirs in turn.
+    let mut backfilled: HashSet<String> = HashSet::new();
     
     info!("📡 Starting data collection loop (interval: {}ms, test_mode: {})", 
           config.update_interval_ms, test_mode);
     
     let streaming_metrics = if config.enable_websocket {
         if let Some(hyperliquid_provider) = provider.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>() {
-            if let (Some(trade_rx), Some(l2_rx), Some(order_rx)) = (hyperliquid_provider.get_live_trades(), hyperliquid_provider.get_live_l2_updates(), hyperliquid_provider.get_live_orders()) {
-                info!("🔄 Starting streaming metrics engine");            
-
-
-                let streaming_engine = Arc::new(RwLock::new(crate::metrics::streaming::StreamingMetricsEngine::new()));
+            if let Some(streams) = hyperliquid_provider.live_streams() {
+                info!("🔄 Starting streaming metrics engine (buffers up to {:.1} MB)",
+                      config.buffers.estimated_memory_bytes(&config) as f64 / (1024.0 * 1024.0));
+                let streaming_engine = Arc::new(RwLock::new(crate::metrics::streaming::StreamingMetricsEngine::new(&config.buffers, &config.whales)));
 
-                let engine_arc = Arc::clone(&streaming_engine);
-                tokio::spawn(async move {
-                    StreamingMetricsEngine::run(engine_arc, trade_rx, l2_rx, order_rx).await;
+                let (source, engine) = (provider.clone(), streaming_engine.clone());
+                let mut streams = Some(streams);
+                shutdown.supervise("streaming metrics", alerts.clone(), move |run| {
+                    // A restart subscribes afresh; updates in between are missed.
+                    let streams = streams.take().or_else(|| {
+                        source.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>()?.live_streams()
+                    });
+                    let engine = engine.clone();
+                    async move {
+                        if let Some((trade_rx, l2_rx, order_rx)) = streams {
+                            run.token().run_until_cancelled(StreamingMetricsEngine::run(engine, trade_rx, l2_rx, order_rx)).await;
+                        }
+                        Ok(())
+                    }
+                });
+                
+                let (source, ws_alerts) = (provider.clone(), alerts.clone());
+                shutdown.supervise("websocket", alerts.clone(), move |run| {
+                    let (source, alerts) = (source.clone(), ws_alerts.clone());
+                    async move {
+                        let Some(p) = source.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>() else {
+                            return Ok(());
+                        };
+                        read_websocket(p, &alerts, &run.token()).await
+                    }
                 });
+                
+                if config.raw_capture.enabled {
+                    if let Some((trade_rx, l2_rx, order_rx)) = hyperliquid_provider.live_streams() {
+                        let capture = RawCapture::new(&config.raw_capture);
+                        // Dropped on shutdown, which writes the rows not yet in a file.
+                        shutdown.spawn_until_stopped("raw capture", capture.run(trade_rx, l2_rx, order_rx));
+                    }
+                }
                 Some(streaming_engine)
             } else {
                 warn!("⚠️ Websocket streams not available, falling back to polling");
@@ -192,101 +1034,622 @@ async fn data_collection_loop<P: DataProvider>(
         None
     };
     
-    loop {
-        interval.tick().await;
-        update_counter += 1;
-        
-        debug!("📊 Starting metrics update cycle #{}", update_counter);
-        
-        match update_metrics(&*provider, &streaming_metrics).await {
-            Ok(new_metrics) => {
-                info!("✅ Successfully updated metrics from provider");
-                
-                {
-                    let mut metrics_guard = metrics.write().await;
-                    *metrics_guard = new_metrics;
-                    
-                    if test_mode {
-                        apply_test_modifications(&mut metrics_guard, update_counter);
-                        debug!("🧪 Applied test modifications to metrics");
-                    }
-                    
-                    info!("📊 FINAL METRICS - TVL: ${:.1}M, VPIN: {:.3}, PLI: {:.1}%, Spreads: {}", 
-                           metrics_guard.vault_metrics.tvl.to_f64().unwrap_or(0.0) / 1_000_000.0,
-                           metrics_guard.risk_metrics.vpin_score,
-                           metrics_guard.risk_metrics.phantom_liquidity_index * 100.0,
-                           metrics_guard.liquidity_metrics.bid_ask_spread_bps.len());
-                }
-                
-                let metrics_for_alerts = metrics.read().await.clone();
-                let new_alerts = alert::check_alerts(&metrics_for_alerts);
-                if !new_alerts.is_empty() {
-                    info!("🔔 Generated {} new alerts", new_alerts.len());
-                    let mut alerts_guard = alerts.write().await;
-                    alerts_guard.extend(new_alerts);
-                    if alerts_guard.len() > 1000 {
-                        alerts_guard.drain(0..500);
-                    }
-                }
-                
-                if update_counter % 10 == 0 {
-                    let metrics_guard = metrics.read().await;
-                    info!("📊 Data update #{} - VPIN: {:.3}, PLI: {:.1}%, TVL: ${:.1}M", 
-                           update_counter, 
-                           metrics_guard.risk_metrics.vpin_score,
-                           metrics_guard.risk_metrics.phantom_liquidity_index * 100.0,
-                           metrics_guard.vault_metrics.tvl.to_f64().unwrap_or(0.0) / 1_000_000.0);
-                }
-            }
-            Err(e) => {
-                error!("❌ Failed to update metrics (attempt #{}): {}", update_counter, e);
-                
-                if update_counter % 5 == 0 {
-                    warn!("⚠️ Metrics update has been failing for {} attempts", update_counter);
-                }
-                
-                if test_mode {
-                    warn!("🧪 Test mode enabled but real data fetch failed, falling back to test data");
-                    let mut test_metrics = create_test_metrics(update_counter);
-                    apply_test_modifications(&mut test_metrics, update_counter);
-                    
-                    let mut metrics_guard = metrics.write().await;
-                    *metrics_guard = test_metrics;
+    let hyperliquid_provider = provider.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>();
+    if config.forensics.enabled {
+        let streams = hyperliquid_provider.filter(|_| streaming_metrics.is_some()).and_then(|p| p.live_streams());
+        shutdown.spawn_until_stopped("forensics", ForensicRecorder::new(&config.forensics).run(streams, metrics.clone(), alerts.clone()));
+    }
+    
+    if hyperliquid_provider.is_some() {
+        let source = provider.clone();
+        shutdown.spawn_until_stopped("order books", async move {
+            if let Some(p) = source.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>() {
+                p.load_books().await;
+            }
+        });
+    }
+    
+    if let Some(mut candle_rx) = hyperliquid_provider.and_then(|p| p.get_live_candles()) {
+        let history = history.clone();
+        shutdown.spawn_until_stopped("candles", async move {
+            loop {
+                match candle_rx.recv().await {
+                    Ok(candle) => history.write().await.merge_candles([candle]),
+                    Err(crate::pipeline::RecvError::Lagged(skipped)) => {
+                        debug!("🕯️ Candle receiver lagged, skipped {} updates", skipped);
+                    }
+                    Err(crate::pipeline::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+    
+    // Holds one cycle: when computing falls behind, the stale cycle is replaced.
+    let cycles = pipeline::channel("cycles", &config::StreamSettings { capacity: 1, overflow: config::Overflow::DropOldest });
+    let pending = Arc::new(tokio::sync::Mutex::new(cycles.subscribe()));
+    {
+        let (provider, streaming_metrics, metrics, alerts, history, live_config) =
+            (provider.clone(), streaming_metrics.clone(), metrics.clone(), alerts.clone(), history.clone(), live_config.clone());
+        shutdown.supervise("metric computation", alerts.clone(), move |_| compute_loop(
+            provider.clone(),
+            pending.clone(),
+            streaming_metrics.clone(),
+            metrics.clone(),
+            alerts.clone(),
+            history.clone(),
+            live_config.clone(),
+            test_mode,
+        ));
+    }
+    
+    let mut monitored_assets = config.monitored_assets.clone();
+    let mut universe = UniverseWatch::new();
+    let stopping = shutdown.token();
+    
+    loop {
+        let collect = tokio::select! {
+            collect = control.next_cycle(&mut interval) => collect,
+            _ = stopping.cancelled() => break,
+        };
+        config.clone_from(&*live_config.read().await);
+        if config.monitored_assets != monitored_assets {
+            if let Some(p) = hyperliquid_provider {
+                p.set_monitored_assets(&config.monitored_assets).await;
+            }
+            monitored_assets = config.monitored_assets.clone();
+        }
+        for request in control.asset_requests() {
+            let added = match hyperliquid_provider {
+                Some(p) => p.add_monitored_asset(&request.coin).await,
+                None => Err(anyhow::anyhow!("the provider has a fixed asset list")),
+            };
+            if matches!(added, Ok(true)) && !config.monitored_assets.is_empty() {
+                // Into the live config too, so a reload of the file keeps it.
+                let mut live = live_config.write().await;
+                let mut next = Config::clone(&live);
+                next.monitored_assets.push(request.coin.clone());
+                monitored_assets.clone_from(&next.monitored_assets);
+                *live = Arc::new(next);
+                config = live.clone();
+            }
+            let _ = request.reply.send(added);
+        }
+        if !collect {
+            // Paused on purpose, so the systemd watchdog should not restart us.
+            metrics.status.write().await.last_cycle = Some(chrono::Utc::now());
+            continue;
+        }
+        update_counter += 1;
+        
+        debug!("📊 Starting metrics update cycle #{}", update_counter);
+        // Ends when dropped at the end of the iteration, so it spans the whole cycle.
+        let cycle = tracing::info_span!("collection_cycle", cycle = update_counter, error = tracing::field::Empty);
+        
+        let fetched = fetch_cycle_data(&*provider, &streaming_metrics).instrument(tracing::info_span!(parent: &cycle, "fetch_cycle_data")).await;
+        telemetry::record_error(&cycle, &fetched);
+        let data = match fetched {
+            Ok(data) => Some(data),
+            Err(e) => {
+                error!("❌ Failed to update metrics (attempt #{}): {}", update_counter, e);
+                progress.retrying(e.to_string());
+                
+                if update_counter % 5 == 0 {
+                    warn!("⚠️ Metrics update has been failing for {} attempts", update_counter);
+                }
+                None
+            }
+        };
+        if let Some(data) = &data {
+            let changes = universe.update(&data.meta);
+            if !changes.is_empty() {
+                let held: HashSet<&str> = data.user_state.positions.iter().map(|position| position.symbol.as_str()).collect();
+                let changed: Vec<Alert> = changes.iter().map(|change| change.alert(&held)).collect();
+                for alert in &changed {
+                    warn!("🌐 {}", alert.message);
+                }
+                alerts.write().await.extend(changed);
+                if let Some(p) = hyperliquid_provider {
+                    p.update_universe(&data.meta, &config.monitored_assets).await;
+                }
+            }
+        }
+        let listed = universe.listings().to_vec();
+        cycles.send(FetchedCycle { number: update_counter, span: cycle.clone(), data, listed }).await;
+        
+        // Backfill one asset per cycle so candle history fills in without bursts of
+        // requests. Once every asset has its history, only gaps the candle feed left
+        // are fetched, again one asset per cycle.
+        let candle_assets = hyperliquid_provider.map(|p| p.get_monitored_assets()).unwrap_or_default();
+        if !candle_assets.is_empty() {
+            let end_time = chrono::Utc::now().timestamp_millis() as u64;
+            let due = match candle_assets.iter().find(|coin| !backfilled.contains(*coin)) {
+                Some(coin) => Some((coin, end_time - CANDLE_BACKFILL_MS)),
+                None => {
+                    let coin = &candle_assets[update_counter as usize % candle_assets.len()];
+                    let last = history.read().await.last_candle_open(coin).unwrap_or(end_time - CANDLE_BACKFILL_MS);
+                    (last + CANDLE_GAP_MS < end_time).then_some((coin, last))
+                }
+            };
+            if let Some((coin, start_time)) = due {
+                let backfill = provider.get_candles(coin, CANDLE_INTERVAL, start_time, end_time);
+                match backfill.instrument(tracing::info_span!(parent: &cycle, "backfill_candles", coin = coin.as_str())).await {
+                    Ok(candles) => {
+                        history.write().await.merge_candles(candles);
+                        backfilled.insert(coin.clone());
+                    }
+                    Err(e) => debug!("⚠️ Failed to backfill candles for {}: {}", coin, e),
+                }
+            }
+        }
+        
+    }
+    
+    info!("🛑 Stopping data collection after {} cycles", update_counter);
+    if let Some(p) = hyperliquid_provider {
+        p.close().await;
+    }
+    Ok(())
+}
+
+/// WebSocket failures in a row retried before the supervisor takes over.
+const WS_RECONNECT_ATTEMPTS: u32 = 3;
+/// A connection up this long counts as healthy, whatever ended it.
+const WS_HEALTHY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Reads the WebSocket until `token` is cancelled. The server closing the
+/// connection is routine: it is reopened at once, and a successful reconnect
+/// raises only an Info alert. Failures are retried on their own
+/// `WS_RECONNECT_ATTEMPTS` times in a row, and only then returned to the
+/// supervisor, which alerts and backs off.
+async fn read_websocket(p: &crate::api::sdk::HyperliquidProvider, alerts: &Versioned<Vec<Alert>>, token: &CancellationToken) -> Result<()> {
+    let mut failures = 0;
+    let mut closed = false;
+    loop {
+        let started = std::time::Instant::now();
+        let attempt = async {
+            if closed {
+                p.reconnect_websocket().await?;
+                info!("🔌 WebSocket reconnected after the server closed it");
+                alerts.write().await.push(alert::notice("WebSocket", "Reconnected after the server closed the connection".to_string()));
+                closed = false;
+            }
+            p.read_websocket().await
+        };
+        match token.run_until_cancelled(attempt).await {
+            None => return Ok(()),
+            Some(Ok(())) => {
+                info!("🔌 WebSocket closed by the server; reconnecting");
+                closed = true;
+                failures = 0;
+            }
+            Some(Err(e)) => {
+                // A connection that stayed up a while ends a run of failures.
+                failures = if started.elapsed() >= WS_HEALTHY { 1 } else { failures + 1 };
+                if failures >= WS_RECONNECT_ATTEMPTS {
+                    return Err(e);
+                }
+                warn!("⚠️ WebSocket failed: {}; retrying ({} of {})", e, failures, WS_RECONNECT_ATTEMPTS);
+                let retry = tokio::time::sleep(std::time::Duration::from_secs(1 << failures));
+                if token.run_until_cancelled(retry).await.is_none() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// One polling cycle as handed from the fetch stage to the compute stage.
+#[derive(Clone)]
+struct FetchedCycle {
+    number: u32,
+    /// The cycle's `collection_cycle` span, which ends once both stages are done.
+    span: tracing::Span,
+    /// `None` when fetching failed; the failure is already reported.
+    data: Option<CycleData>,
+    /// Every asset listed this session and when it was first seen; all of
+    /// them each cycle, so none is lost to a skipped cycle or while probation
+    /// is off.
+    listed: Vec<(String, chrono::DateTime<chrono::Utc>)>,
+}
+
+/// Compute stage of the dashboard: turns each fetched cycle into metrics,
+/// publishes them, checks alerts and records history. `cycles` is shared so a
+/// restarted loop carries on with the next cycle.
+#[allow(clippy::too_many_arguments)]
+async fn compute_loop<P: DataProvider>(
+    provider: Arc<P>,
+    cycles: Arc<tokio::sync::Mutex<pipeline::Receiver<FetchedCycle>>>,
+    streaming_metrics: Option<Arc<RwLock<StreamingMetricsEngine>>>,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    live_config: Arc<RwLock<Arc<Config>>>,
+    test_mode: bool,
+) -> Result<()> {
+    let mut cycles = cycles.lock().await;
+    let mut config = live_config.read().await.clone();
+    let store = open_metric_store(&config, &history).await;
+    let mut address_book = AddressBook::load_or_empty(config.address_book.as_deref());
+    let mut illiquid = IlliquidWatch::new();
+    let mut probation = Probation::new();
+    let mut synthetic = test_mode.then(|| SyntheticMarket::new(&config.demo));
+    let mut ws_rate_sample = None;
+    let mut active_alerts = 0;
+    loop {
+        let FetchedCycle { number: update_counter, span: cycle, data, listed } = match cycles.recv().await {
+            Ok(fetched) => fetched,
+            Err(pipeline::RecvError::Lagged(skipped)) => {
+                warn!("⚠️ Metric computation fell behind, skipped {} fetched cycles", skipped);
+                continue;
+            }
+            Err(pipeline::RecvError::Closed) => break,
+        };
+        // Of this cycle's books, in milliseconds; unknown when the cycle failed.
+        let mut block_time: Option<u64> = None;
+        let book_path = config.address_book.clone();
+        config.clone_from(&*live_config.read().await);
+        if config.address_book != book_path {
+            address_book = AddressBook::load_or_empty(config.address_book.as_deref());
+        }
+        
+        match data {
+            Some(data) => {
+                let mut new_metrics = compute_metrics(data, &streaming_metrics).instrument(tracing::info_span!(parent: &cycle, "compute_metrics")).await;
+                info!("✅ Successfully updated metrics from provider");
+                
+                if let Some(market) = synthetic.as_mut() {
+                    market.step();
+                    market.overlay(&mut new_metrics);
+                    debug!("🧪 Overlaid simulated metrics - VPIN: {:.3}, PLI: {:.1}%",
+                           new_metrics.risk_metrics.vpin_score,
+                           new_metrics.risk_metrics.phantom_liquidity_index * 100.0);
+                }
+                address_book.annotate(&mut new_metrics);
+                if let (true, Some(engine)) = (config.illiquid.enabled, &streaming_metrics) {
+                    let trade_counts = engine.read().await.trade_counts();
+                    illiquid.update(&mut new_metrics, &trade_counts, &config.illiquid);
+                }
+                if config.probation.enabled {
+                    probation.update(&mut new_metrics, &listed, &config.probation, chrono::Utc::now());
+                }
+                
+                info!("📊 FINAL METRICS - TVL: {}, VPIN: {:.3}, PLI: {:.1}%, Spreads: {}", 
+                       session::format_signed_usd(new_metrics.vault_metrics.tvl.to_f64().unwrap_or(0.0)),
+                       new_metrics.risk_metrics.vpin_score,
+                       new_metrics.risk_metrics.phantom_liquidity_index * 100.0,
+                       new_metrics.liquidity_metrics.bid_ask_spread_bps.len());
+                // Leaves the status cell alone; it is refreshed below.
+                metrics.publish(new_metrics).await;
+                
+                let metrics_for_alerts = metrics.snapshot().await;
+                block_time = metrics_for_alerts.order_books.values().map(|book| book.time).max();
+                let new_alerts = tracing::info_span!(parent: &cycle, "check_alerts")
+                    .in_scope(|| alert::check_alerts(&metrics_for_alerts, &config.alert_thresholds));
+                active_alerts = new_alerts.len();
+                if !new_alerts.is_empty() {
+                    info!("🔔 Generated {} new alerts", new_alerts.len());
+                    let mut alerts_guard = alerts.write().await;
+                    alerts_guard.extend(new_alerts);
+                    if alerts_guard.len() > 1000 {
+                        alerts_guard.drain(0..500);
+                    }
+                }
+                
+                if update_counter % 10 == 0 {
+                    info!("📊 Data update #{} - VPIN: {:.3}, PLI: {:.1}%, TVL: {}", 
+                           update_counter, 
+                           metrics_for_alerts.risk_metrics.vpin_score,
+                           metrics_for_alerts.risk_metrics.phantom_liquidity_index * 100.0,
+                           session::format_signed_usd(metrics_for_alerts.vault_metrics.tvl.to_f64().unwrap_or(0.0)));
+                }
+            }
+            None => {
+                if let Some(market) = synthetic.as_mut() {
+                    warn!("🧪 Test mode enabled but real data fetch failed, falling back to test data");
+                    let mut test_metrics = create_test_metrics(update_counter);
+                    market.step();
+                    market.apply(&mut test_metrics);
+                    metrics.publish(test_metrics).await;
+                }
+            }
+        }
+        
+        let mut status = collect_system_status(&*provider, &config, &mut ws_rate_sample, active_alerts)
+            .instrument(tracing::info_span!(parent: &cycle, "collect_system_status"))
+            .await;
+        if let Some(engine) = &streaming_metrics {
+            let engine = engine.read().await;
+            status.buffers = engine.buffer_stats();
+            status.shadow = Some(engine.shadow().clone());
+        }
+        status.buffers.push(history.read().await.buffer_stats());
+        if let Some(ms) = block_time {
+            status.block_time = chrono::DateTime::from_timestamp_millis(ms as i64);
+            status.block_lag_secs = status.block_time.map(|time| (chrono::Utc::now() - time).num_milliseconds().max(0) as f64 / 1000.0);
+        }
+        *metrics.status.write().await = status;
+        
+        {
+            let snapshot = metrics.snapshot().await;
+            if snapshot.last_update.is_some() {
+                history.write().await.record(&snapshot);
+                if let Some(store) = &store {
+                    store.record(snapshot, tracing::info_span!(parent: &cycle, "store_snapshot"));
+                }
+            }
+        }
+    }
+    if let Some(store) = store {
+        store.close().await;
+    }
+    Ok(())
+}
+
+/// Starts the Prometheus exporter, JSON API, gRPC and WebSocket streams, the
+/// StatsD and MQTT publishers, the daily report, the S3 uploader, the control
+/// and attach sockets, the SSH server and the config file watcher when enabled. A failed bind is logged and
+/// the dashboard carries on. On shutdown the MQTT publisher marks the dashboard
+/// offline and the control socket removes its file; the rest are dropped.
+fn spawn_servers(
+    config: &Config,
+    live_config: &Arc<RwLock<Arc<Config>>>,
+    metrics: &Arc<SharedMetrics>,
+    alerts: &Arc<Versioned<Vec<Alert>>>,
+    history: &Arc<RwLock<MetricHistory>>,
+    control: &Arc<Control>,
+    shutdown: &Shutdown,
+) {
+    // validate_config has already checked the addresses.
+    if let (true, Ok(addr)) = (config.prometheus.enabled, config.prometheus.listen.parse()) {
+        let (metrics, alerts) = (metrics.clone(), alerts.clone());
+        shutdown.spawn_until_stopped("prometheus exporter", async move {
+            if let Err(e) = exporter::serve(addr, metrics, alerts).await {
+                warn!("⚠️ Prometheus exporter stopped: {}", e);
+            }
+        });
+    }
+    
+    if let (true, Ok(addr)) = (config.http_api.enabled, config.http_api.listen.parse()) {
+        let (metrics, alerts) = (metrics.clone(), alerts.clone());
+        shutdown.spawn_until_stopped("json api", async move {
+            if let Err(e) = api_server::serve(addr, metrics, alerts).await {
+                warn!("⚠️ JSON API stopped: {}", e);
+            }
+        });
+    }
+    
+    if let (true, Ok(addr)) = (config.grpc.enabled, config.grpc.listen.parse()) {
+        let (metrics, alerts) = (metrics.clone(), alerts.clone());
+        shutdown.spawn_until_stopped("grpc server", async move {
+            if let Err(e) = server::serve(addr, metrics, alerts).await {
+                warn!("⚠️ gRPC server stopped: {}", e);
+            }
+        });
+    }
+    
+    if let (true, Ok(addr)) = (config.ws_server.enabled, config.ws_server.listen.parse()) {
+        let (metrics, alerts) = (metrics.clone(), alerts.clone());
+        shutdown.spawn_until_stopped("websocket server", async move {
+            if let Err(e) = ws_server::serve(addr, metrics, alerts).await {
+                warn!("⚠️ WebSocket server stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.statsd.enabled {
+        let (settings, metrics, alerts) = (config.statsd.clone(), metrics.clone(), alerts.clone());
+        shutdown.spawn_until_stopped("statsd", async move {
+            if let Err(e) = statsd::run(settings, metrics, alerts).await {
+                warn!("⚠️ StatsD sink stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.mqtt.enabled {
+        let (settings, metrics, alerts, token) = (config.mqtt.clone(), metrics.clone(), alerts.clone(), shutdown.token());
+        shutdown.spawn("mqtt", async move {
+            if let Err(e) = mqtt::run(settings, metrics, alerts, token).await {
+                warn!("⚠️ MQTT publisher stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.daily_report.enabled {
+        let (settings, metrics, alerts, history) = (config.daily_report.clone(), metrics.clone(), alerts.clone(), history.clone());
+        shutdown.spawn_until_stopped("daily report", async move {
+            if let Err(e) = daily_report::run(settings, metrics, alerts, history).await {
+                warn!("⚠️ Daily report scheduler stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.upload.enabled {
+        shutdown.spawn_until_stopped("uploader", upload::Uploader::new(config).run());
+    }
+    
+    if config.reload.enabled {
+        shutdown.spawn_until_stopped("config watcher", reload::watch(live_config.clone(), alerts.clone()));
+    }
+    
+    if config.control.enabled {
+        let (settings, control, metrics, alerts, token) = (config.control.clone(), control.clone(), metrics.clone(), alerts.clone(), shutdown.token());
+        shutdown.spawn("control socket", async move {
+            if let Err(e) = control::serve(settings, control, metrics, alerts, token).await {
+                warn!("⚠️ Control socket stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.attach.enabled {
+        let (settings, live_config, metrics, alerts, history, token) =
+            (config.attach.clone(), live_config.clone(), metrics.clone(), alerts.clone(), history.clone(), shutdown.token());
+        shutdown.spawn("attach socket", async move {
+            if let Err(e) = attach::serve(settings, live_config, metrics, alerts, history, token).await {
+                warn!("⚠️ Attach socket stopped: {}", e);
+            }
+        });
+    }
+    
+    if config.ssh.enabled {
+        let (settings, live_config, metrics, alerts, history, token) =
+            (config.ssh.clone(), live_config.clone(), metrics.clone(), alerts.clone(), history.clone(), shutdown.token());
+        shutdown.spawn("ssh server", async move {
+            if let Err(e) = ssh::serve(settings, live_config, metrics, alerts, history, token).await {
+                warn!("⚠️ SSH server stopped: {}", e);
+            }
+        });
+    }
+}
+
+/// Opens the SQLite history when enabled, seeds the charts from it and hands it
+/// to a writer thread. Storage problems are logged and leave the dashboard running
+/// without persistence.
+async fn open_metric_store(config: &Config, history: &RwLock<MetricHistory>) -> Option<StoreWriter> {
+    if !config.storage.enabled {
+        return None;
+    }
+    
+    // Opening prunes and restoring reads a day of samples, so both run off the runtime.
+    let settings = config.storage.clone();
+    let opened = tokio::task::spawn_blocking(move || {
+        let store = MetricStore::open(&settings)?;
+        let stored = store.stored_history();
+        anyhow::Ok((store, stored))
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|opened| opened);
+    let (store, stored) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            warn!("⚠️ Metric storage disabled, could not open {}: {}", config.storage.path, e);
+            return None;
+        }
+    };
+    
+    match stored {
+        Ok(stored) => {
+            let mut history = history.write().await;
+            let restored = stored.values().map(Vec::len).sum::<usize>();
+            for (metric, samples) in stored {
+                history.restore(metric, samples);
+            }
+            info!("💾 Storing metrics in {} ({} samples restored)", config.storage.path, restored);
+        }
+        Err(e) => warn!("⚠️ Failed to restore metric history from {}: {}", config.storage.path, e),
+    }
+    match StoreWriter::spawn(store) {
+        Ok(writer) => Some(writer),
+        Err(e) => {
+            warn!("⚠️ Metric storage disabled, could not start its writer: {}", e);
+            None
+        }
+    }
+}
+
+async fn collect_system_status<P: DataProvider>(
+    provider: &P,
+    config: &Config,
+    ws_rate_sample: &mut Option<(u64, std::time::Instant)>,
+    active_alerts: usize,
+) -> SystemStatus {
+    let ws_fallback = if config.enable_websocket { LinkState::Disconnected } else { LinkState::Disabled };
+    let (http, websocket) = match provider.get_status().await {
+        DataSourceStatus::Connected => (LinkState::Connected, LinkState::Connected),
+        DataSourceStatus::Error(_) => (LinkState::Connected, ws_fallback),
+        DataSourceStatus::Disconnected => (LinkState::Disconnected, ws_fallback),
+    };
+    
+    let hyperliquid_provider = provider.as_any().downcast_ref::<crate::api::sdk::HyperliquidProvider>();
+    
+    let ws_messages_per_sec = match hyperliquid_provider.and_then(|p| p.ws_message_count()) {
+        Some(count) => {
+            let now = std::time::Instant::now();
+            match ws_rate_sample.replace((count, now)) {
+                Some((previous, at)) => {
+                    count.saturating_sub(previous) as f64 / now.duration_since(at).as_secs_f64().max(0.001)
                 }
+                None => 0.0,
             }
         }
+        None => 0.0,
+    };
+    
+    let (books_covered, books_monitored) = hyperliquid_provider.map(|p| p.book_coverage()).unwrap_or_default();
+    
+    SystemStatus {
+        http,
+        websocket,
+        ws_messages_per_sec,
+        ws_last_message: hyperliquid_provider.and_then(|p| p.ws_last_message_at()),
+        api_latency_ms: hyperliquid_provider.and_then(|p| p.last_api_latency_ms()).map(|ms| ms as f64),
+        active_alerts,
+        last_cycle: Some(chrono::Utc::now()),
+        block_time: None,
+        block_lag_secs: None,
+        streams: hyperliquid_provider.map(|p| p.stream_stats()).unwrap_or_default(),
+        buffers: Vec::new(),
+        latency: hyperliquid_provider.map(|p| p.latency_stats()).unwrap_or_default(),
+        books_covered,
+        books_monitored,
+        shadow: None,
     }
 }
 
 async fn test_data_loop(
-    metrics: Arc<RwLock<GlobalMetrics>>,
-    alerts: Arc<RwLock<Vec<Alert>>>,
-    config: Config,
-) {
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    live_config: Arc<RwLock<Arc<Config>>>,
+    control: Arc<Control>,
+    shutdown: Shutdown,
+) -> Result<()> {
+    let mut config = live_config.read().await.clone();
     let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(config.update_interval_ms));
     let mut update_counter = 0;
     
     info!("🧪 Starting test data loop");
+    let mut market = SyntheticMarket::new(&config.demo);
+    
+    if config.forensics.enabled {
+        shutdown.spawn_until_stopped("forensics", ForensicRecorder::new(&config.forensics).run(None, metrics.clone(), alerts.clone()));
+    }
+    let stopping = shutdown.token();
     
     loop {
-        interval.tick().await;
+        let collect = tokio::select! {
+            collect = control.next_cycle(&mut interval) => collect,
+            _ = stopping.cancelled() => break,
+        };
+        config.clone_from(&*live_config.read().await);
+        for request in control.asset_requests() {
+            let _ = request.reply.send(Err(anyhow::anyhow!("test data has a fixed asset list")));
+        }
+        if !collect {
+            metrics.status.write().await.last_cycle = Some(chrono::Utc::now());
+            continue;
+        }
         update_counter += 1;
         
+        market.step();
         let mut test_metrics = create_test_metrics(update_counter);
-        apply_test_modifications(&mut test_metrics, update_counter);
+        market.apply(&mut test_metrics);
+        
+        debug!("🧪 Test update #{} - Generated metrics: VPIN: {:.3}, PLI: {:.1}%", 
+               update_counter,
+               test_metrics.risk_metrics.vpin_score,
+               test_metrics.risk_metrics.phantom_liquidity_index * 100.0);
+        metrics.publish(test_metrics).await;
         
+        let metrics_for_alerts = metrics.snapshot().await;
+        let new_alerts = alert::check_alerts(&metrics_for_alerts, &config.alert_thresholds);
         {
-            let mut metrics_guard = metrics.write().await;
-            *metrics_guard = test_metrics;
-            
-            debug!("🧪 Test update #{} - Generated metrics: VPIN: {:.3}, PLI: {:.1}%", 
-                   update_counter,
-                   metrics_guard.risk_metrics.vpin_score,
-                   metrics_guard.risk_metrics.phantom_liquidity_index * 100.0);
+            let mut status = metrics.status.write().await;
+            status.active_alerts = new_alerts.len();
+            status.last_cycle = Some(chrono::Utc::now());
         }
-        
-        let metrics_for_alerts = metrics.read().await.clone();
-        let new_alerts = alert::check_alerts(&metrics_for_alerts);
+        history.write().await.record(&metrics.snapshot().await);
+        history.write().await.merge_candles(market.take_candles());
         if !new_alerts.is_empty() {
             let mut alerts_guard = alerts.write().await;
             alerts_guard.extend(new_alerts);
@@ -299,27 +1662,11 @@ async fn test_data_loop(
             info!("🧪 Test update #{} - Simulated metrics generated", update_counter);
         }
     }
+    Ok(())
 }
 
-fn apply_test_modifications(metrics: &mut GlobalMetrics, counter: u32) {
-    let time_factor = (counter as f64 * 0.1).sin();
-    
-    metrics.risk_metrics.vpin_score = (0.3 + time_factor * 0.4).max(0.0).min(1.0);
-    metrics.risk_metrics.phantom_liquidity_index = (0.25 + time_factor * 0.2).max(0.0).min(1.0);
-    metrics.risk_metrics.liquidation_risk_score = (0.2 + time_factor * 0.3).max(0.0).min(1.0);
-    metrics.vault_metrics.utilization_rate = (0.5 + time_factor * 0.3).max(0.0).min(1.0);
-    
-    if counter % 50 == 0 {
-        metrics.risk_metrics.vpin_score = 0.8; 
-    }
-    
-    metrics.last_update = Some(chrono::Utc::now());
-    
-    debug!("🧪 Modified test metrics - VPIN: {:.3}, PLI: {:.1}%", 
-           metrics.risk_metrics.vpin_score,
-           metrics.risk_metrics.phantom_liquidity_index * 100.0);
-}
-
+/// The vault and performance figures of the demo; the market's books, tape and
+/// liquidity and risk readings come from `SyntheticMarket::apply`.
 fn create_test_metrics(counter: u32) -> GlobalMetrics {
     use rust_decimal::Decimal;
     use std::collections::HashMap;
@@ -336,24 +1683,6 @@ fn create_test_metrics(counter: u32) -> GlobalMetrics {
     metrics.performance_metrics.unrealized_pnl = Decimal::from(8500);
     metrics.performance_metrics.total_volume = Decimal::from(25000000);
     
-    let mut spreads = HashMap::new();
-    spreads.insert("BTC".to_string(), 0.5);
-    spreads.insert("ETH".to_string(), 0.8);
-    spreads.insert("SOL".to_string(), 1.2);
-    metrics.liquidity_metrics.bid_ask_spread_bps = spreads;
-    
-    let mut depth_50bps = HashMap::new();
-    depth_50bps.insert("BTC".to_string(), Decimal::from(500000));
-    depth_50bps.insert("ETH".to_string(), Decimal::from(300000));
-    depth_50bps.insert("SOL".to_string(), Decimal::from(150000));
-    metrics.liquidity_metrics.depth_at_50bps = depth_50bps;
-    
-    let mut order_book_imbalance = HashMap::new();
-    order_book_imbalance.insert("BTC".to_string(), 0.05);
-    order_book_imbalance.insert("ETH".to_string(), 0.03);
-    order_book_imbalance.insert("SOL".to_string(), 0.08);
-    metrics.liquidity_metrics.order_book_imbalance = order_book_imbalance;
-    
     let mut fill_probability = HashMap::new();
     fill_probability.insert("5bps".to_string(), 0.95);
     fill_probability.insert("10bps".to_string(), 0.88);
@@ -361,13 +1690,6 @@ fn create_test_metrics(counter: u32) -> GlobalMetrics {
     fill_probability.insert("50bps".to_string(), 0.60);
     metrics.liquidity_metrics.fill_probability_by_distance = fill_probability;
     
-    metrics.liquidity_metrics.avg_order_lifetime_ms = 164170.0;
-    metrics.liquidity_metrics.cancel_rate = 0.45;
-    metrics.liquidity_metrics.fleeting_order_ratio = 0.093;
-    metrics.liquidity_metrics.layering_detection_score = 0.35;
-    metrics.liquidity_metrics.spoofing_detection_index = 0.09;
-    metrics.liquidity_metrics.liquidity_realization_rate = 0.512;
-    
     let mut concentrations = HashMap::new();
     concentrations.insert("BTC".to_string(), 0.08);
     concentrations.insert("ETH".to_string(), 0.06);
@@ -377,9 +1699,6 @@ fn create_test_metrics(counter: u32) -> GlobalMetrics {
     metrics.risk_metrics.cascade_risk_score = 0.12;
     metrics.risk_metrics.max_drawdown = 0.0;
     
-    metrics.vault_metrics.deployed_liquidity = Decimal::from(85000000);
-    metrics.vault_metrics.idle_liquidity = Decimal::from(24530000);
-    
     let mut realized_spreads = HashMap::new();
     realized_spreads.insert("BTC".to_string(), 0.3);
     realized_spreads.insert("ETH".to_string(), 0.5);
@@ -388,21 +1707,115 @@ fn create_test_metrics(counter: u32) -> GlobalMetrics {
     
     metrics.performance_metrics.adverse_selection_cost = 0.05;
     
-    metrics.last_update = Some(chrono::Utc::now());
-    
-    debug!("🧪 Created test metrics #{} - TVL: ${:.1}M", 
-           counter, metrics.vault_metrics.tvl.to_f64().unwrap_or(0.0) / 1_000_000.0);
+    debug!("🧪 Created test metrics #{} - TVL: {}", 
+           counter, session::format_signed_usd(metrics.vault_metrics.tvl.to_f64().unwrap_or(0.0)));
     
     metrics
 }
 
+/// Runs the dashboard, or with `--headless` waits for SIGTERM while the collection
+/// loop and servers carry on. Under systemd, readiness and health are reported
+/// either way.
+async fn run_frontend(
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    progress: LoadingProgress,
+    live_config: Arc<RwLock<Arc<Config>>>,
+    test_mode: bool,
+    debug_mode: bool,
+) -> Result<()> {
+    let config = live_config.read().await.clone();
+    if std::env::var_os("NOTIFY_SOCKET").is_some() {
+        let stall_timeout = Duration::from_secs(config.service.stall_timeout_secs);
+        tokio::spawn(systemd::supervise(metrics.clone(), stall_timeout));
+    }
+    
+    let result = if config.service.headless {
+        run_headless(&metrics, &progress, config.service.ndjson).await
+    } else {
+        tokio::select! {
+            result = run_ui_enhanced(metrics, alerts, history, progress, live_config, test_mode, debug_mode) => result,
+            _ = systemd::shutdown_signal() => {
+                // The UI loop was dropped mid-frame; put the terminal back ourselves.
+                disable_raw_mode()?;
+                execute!(io::stdout(), crossterm::terminal::LeaveAlternateScreen, crossterm::cursor::Show)?;
+                Ok(())
+            }
+        }
+    };
+    systemd::notify("STOPPING=1");
+    result
+}
+
+async fn run_headless(metrics: &Arc<SharedMetrics>, progress: &LoadingProgress, ndjson: bool) -> Result<()> {
+    info!("🖥️ Running headless; stop with SIGTERM or Ctrl-C");
+    let failed = async {
+        loop {
+            if let Some(error) = progress.snapshot().fatal {
+                return error;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    };
+    let output = async {
+        if ndjson {
+            print_ndjson(metrics).await
+        } else {
+            std::future::pending().await
+        }
+    };
+    
+    tokio::select! {
+        error = failed => {
+            error!("❌ Initialization failed: {}", error);
+            Err(anyhow::anyhow!(error))
+        }
+        result = output => result,
+        _ = systemd::shutdown_signal() => Ok(()),
+    }
+}
+
+/// Writes every new snapshot to stdout as one line of JSON. Returns once stdout
+/// is closed, e.g. when `head` has read enough, so the process exits with it.
+async fn print_ndjson(metrics: &Arc<SharedMetrics>) -> Result<()> {
+    use std::io::Write;
+    let mut interval = tokio::time::interval(Duration::from_millis(200));
+    let mut last_printed = None;
+    
+    loop {
+        interval.tick().await;
+        let line = {
+            let last_update = metrics.last_update().await;
+            if last_update.is_none() || last_update == last_printed {
+                continue;
+            }
+            last_printed = last_update;
+            serde_json::to_string(&metrics.snapshot().await)?
+        };
+        
+        let mut stdout = io::stdout().lock();
+        match writeln!(stdout, "{}", line).and_then(|_| stdout.flush()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                info!("🛑 stdout closed, shutting down");
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 async fn run_ui_enhanced(
-    metrics: Arc<RwLock<GlobalMetrics>>,
-    alerts: Arc<RwLock<Vec<Alert>>>,
-    config: Config,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    history: Arc<RwLock<MetricHistory>>,
+    progress: LoadingProgress,
+    live_config: Arc<RwLock<Arc<Config>>>,
     test_mode: bool,
     debug_mode: bool,
 ) -> Result<()> {
+    let mut config = live_config.read().await.clone();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -410,26 +1823,39 @@ async fn run_ui_enhanced(
     let mut terminal = Terminal::new(backend)?;
 
     let mut ui_state = UIState::new();
+    ui_state.test_mode = test_mode;
+    ui_state.denomination = config.ui_settings.denomination;
+    ui_state.log_capture = !debug_mode;
+    if config.ui_settings.persist_state {
+        match ui_state.restore(&config.ui_settings.state_file) {
+            Ok(()) => debug!("📂 Restored UI state from {}", config.ui_settings.state_file),
+            Err(e) => warn!("⚠️ Ignoring unreadable UI state file {}: {}", config.ui_settings.state_file, e),
+        }
+    }
     let mut last_alert_count = 0;
     let mut update_counter = 0;
 
-    show_loading_screen(&mut terminal, &config, test_mode)?;
-    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let startup = wait_for_first_data(&mut terminal, &metrics, &progress, &config, test_mode).await;
+    if !matches!(startup, Ok(true)) {
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), crossterm::terminal::LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        return startup.map(|_| ());
+    }
 
     info!("🎨 Starting UI loop (test_mode: {}, debug: {})", test_mode, debug_mode);
+    let mut session = SessionTracker::new();
+    // Copied only when the collector or an acknowledgement changed them.
+    let (mut metrics_snapshot, mut metrics_seen) = (GlobalMetrics::default(), shared::Seen::default());
+    let (mut alerts_snapshot, mut alerts_seen) = (Vec::new(), None);
 
     loop {
         update_counter += 1;
+        config.clone_from(&*live_config.read().await);
 
-        let metrics_snapshot = {
-            let guard = metrics.read().await;
-            guard.clone()
-        };
-        
-        let alerts_snapshot = {
-            let guard = alerts.read().await;
-            guard.clone()
-        };
+        metrics.refresh(&mut metrics_snapshot, &mut metrics_seen).await;
+        session.observe(&metrics_snapshot.system_status);
+        alerts.refresh(&mut alerts_snapshot, &mut alerts_seen).await;
         
         if debug_mode && update_counter % 100 == 0 {
             debug!("📊 UI Update #{} - VPIN: {:.3}, PLI: {:.1}%, Last Update: {:?}", 
@@ -441,18 +1867,53 @@ async fn run_ui_enhanced(
         
         check_critical_alerts(&alerts_snapshot, &mut last_alert_count);
         
-        terminal.draw(|f| ui::ui::draw(f, &ui_state, &metrics_snapshot, &alerts_snapshot))?;
+        {
+            let history_guard = history.read().await;
+            terminal.draw(|f| ui::ui::draw(f, &mut ui_state, &config, &metrics_snapshot, &alerts_snapshot, &history_guard))?;
+        }
 
         if event::poll(Duration::from_millis(config.ui_settings.refresh_rate_ms))? {
             if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Char('Q') => {
-                        if key.modifiers.contains(KeyModifiers::CONTROL) || key.modifiers.is_empty() {
-                            info!("👋 User requested quit");
+                if ui_state.quit_prompt {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Enter => {
+                            info!("👋 Quit confirmed");
                             break;
                         }
+                        KeyCode::Char('a') | KeyCode::Char('A') => {
+                            let acknowledged = alert::acknowledge_all(&mut alerts.write().await);
+                            info!("✅ Acknowledged {} alert(s)", acknowledged);
+                        }
+                        _ => {
+                            info!("↩️ Quit cancelled");
+                            ui_state.quit_prompt = false;
+                        }
+                    }
+                    continue;
+                }
+
+                if let Some(chart) = ui_state.active_chart() {
+                    if handle_chart_key(chart, key.code) {
+                        continue;
+                    }
+                }
+
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Char('Q')
+                        if key.modifiers.contains(KeyModifiers::CONTROL) || key.modifiers.is_empty() =>
+                    {
+                        if config.ui_settings.confirm_quit {
+                            ui_state.quit_prompt = true;
+                            continue;
+                        }
+                        info!("👋 User requested quit");
+                        break;
                     }
                     KeyCode::Esc => {
+                        if config.ui_settings.confirm_quit {
+                            ui_state.quit_prompt = true;
+                            continue;
+                        }
                         info!("👋 User pressed escape");
                         break;
                     }
@@ -460,26 +1921,68 @@ async fn run_ui_enhanced(
                         ui_state.next_tab();
                         debug!("📑 Switched to next tab");
                     }
-                    KeyCode::Up => ui_state.scroll_up(),
-                    KeyCode::Down => ui_state.scroll_down(),
-                    KeyCode::PageUp => {
-                        for _ in 0..10 {
-                            ui_state.scroll_up();
-                        }
+                    KeyCode::BackTab => {
+                        ui_state.previous_tab();
+                        debug!("📑 Switched to previous tab");
                     }
-                    KeyCode::PageDown => {
-                        for _ in 0..10 {
-                            ui_state.scroll_down();
-                        }
+                    KeyCode::Char(c @ '1'..='9') if (c as usize - '1' as usize) < TAB_TITLES.len() => {
+                        ui_state.select_tab(c as usize - '1' as usize);
+                        debug!("📑 Jumped to tab {}", c);
+                    }
+                    KeyCode::Char('m') if ui_state.selected_tab == CHARTS_TAB => ui_state.chart.next_metric(),
+                    KeyCode::Char('M') if ui_state.selected_tab == CHARTS_TAB => ui_state.chart.previous_metric(),
+                    KeyCode::Char('a') if ui_state.selected_tab == CHARTS_TAB => {
+                        ui_state.chart.next_asset(&history.read().await.candle_assets());
+                    }
+                    KeyCode::Char('A') if ui_state.selected_tab == CHARTS_TAB => {
+                        ui_state.chart.previous_asset(&history.read().await.candle_assets());
+                    }
+                    KeyCode::Char('a') if ui_state.selected_tab == LIQUIDITY_TAB => {
+                        let market = metrics.market.read().await;
+                        ui_state.ladder.next_asset(&ui::ui::ladder_assets(&market.order_books, &market.open_orders));
                     }
-                    KeyCode::Home => ui_state.scroll_offset = 0,
+                    KeyCode::Char('A') if ui_state.selected_tab == LIQUIDITY_TAB => {
+                        let market = metrics.market.read().await;
+                        ui_state.ladder.previous_asset(&ui::ui::ladder_assets(&market.order_books, &market.open_orders));
+                    }
+                    KeyCode::Char('a') | KeyCode::Char('A') if ui_state.selected_tab == ALERTS_TAB => {
+                        let acknowledged = alert::acknowledge_all(&mut alerts.write().await);
+                        info!("✅ Acknowledged {} alert(s)", acknowledged);
+                    }
+                    KeyCode::Up if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::UpKey),
+                    KeyCode::Down if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::DownKey),
+                    KeyCode::Left if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::LeftKey),
+                    KeyCode::Right if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::RightKey),
+                    KeyCode::Char('+') | KeyCode::Char('=') if ui_state.selected_tab == LOGS_TAB => {
+                        ui_state.log_key(TuiWidgetEvent::PlusKey)
+                    }
+                    KeyCode::Char('-') if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::MinusKey),
+                    KeyCode::Char('f') | KeyCode::Char('F') if ui_state.selected_tab == LOGS_TAB => {
+                        ui_state.log_key(TuiWidgetEvent::FocusKey)
+                    }
+                    KeyCode::Char('v') | KeyCode::Char('V') if ui_state.selected_tab == LOGS_TAB => {
+                        ui_state.log_key(TuiWidgetEvent::HideKey)
+                    }
+                    KeyCode::Char(' ') if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::SpaceKey),
+                    KeyCode::PageUp if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::PrevPageKey),
+                    KeyCode::PageDown if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::NextPageKey),
+                    KeyCode::End if ui_state.selected_tab == LOGS_TAB => ui_state.log_key(TuiWidgetEvent::EscapeKey),
+                    KeyCode::Up => ui_state.scroll_up(),
+                    KeyCode::Down => ui_state.scroll_down(),
+                    KeyCode::PageUp => ui_state.page_up(),
+                    KeyCode::PageDown => ui_state.page_down(),
+                    KeyCode::Home => ui_state.scroll_to_top(),
                     KeyCode::Char('r') | KeyCode::Char('R') => {
                         info!("🔄 User requested refresh");
-                        ui_state.scroll_offset = 0;
+                        ui_state.scroll_to_top();
+                    }
+                    KeyCode::Char('d') | KeyCode::Char('D') => {
+                        ui_state.denomination = ui_state.denomination.next();
+                        info!("💱 Showing amounts in {:?}", ui_state.denomination);
                     }
                     KeyCode::Char('h') | KeyCode::Char('H') => {
                         info!("❓ Showing help screen");
-                        show_help_screen(&mut terminal, test_mode, debug_mode)?;
+                        show_help_screen(&mut terminal, test_mode, debug_mode, config.ui_settings.ascii_mode)?;
                     }
                     KeyCode::Char('s') | KeyCode::Char('S') => {
                         info!("💾 Saving configuration");
@@ -491,15 +1994,16 @@ async fn run_ui_enhanced(
                     }
                     KeyCode::Char('t') | KeyCode::Char('T') => {
                         info!("🧪 Running manual test calculations");
-                        {
-                            let mut metrics_guard = metrics.write().await;
-                            apply_test_modifications(&mut metrics_guard, update_counter);
-                            info!("✅ Test metrics applied");
-                        }
+                        let mut test_metrics = metrics.snapshot().await;
+                        let mut market = SyntheticMarket::new(&config.demo);
+                        market.step();
+                        market.overlay(&mut test_metrics);
+                        metrics.publish(test_metrics).await;
+                        info!("✅ Test metrics applied");
                     }
                     KeyCode::F(5) => {
                         info!("🔄 Force refresh requested");
-                        ui_state.scroll_offset = 0;
+                        ui_state.scroll_to_top();
                     }
                     _ => {}
                 }
@@ -507,6 +2011,24 @@ async fn run_ui_enhanced(
         }
     }
 
+    if config.ui_settings.persist_state {
+        if let Err(e) = ui_state.save(&config.ui_settings.state_file) {
+            warn!("⚠️ Failed to save UI state to {}: {}", config.ui_settings.state_file, e);
+        }
+    }
+
+    let summary = session.summary(&*history.read().await, &alerts.read().await);
+    if let Some(path) = &config.ui_settings.session_summary_file {
+        match summary.append_to(path) {
+            Ok(()) => info!("🧾 Session summary appended to {}", path),
+            Err(e) => warn!("⚠️ Failed to write session summary to {}: {}", path, e),
+        }
+    }
+    if config.ui_settings.session_summary {
+        ui::format::set(&config.ui_settings);
+        show_session_summary(&mut terminal, &summary, config.ui_settings.ascii_mode)?;
+    }
+
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), crossterm::terminal::LeaveAlternateScreen)?;
     terminal.show_cursor()?;
@@ -514,10 +2036,48 @@ async fn run_ui_enhanced(
     Ok(())
 }
 
+/// Redraws the loading screen until the first metrics arrive. Returns `Ok(false)` if the
+/// user quits first and an error if the provider could not be initialized.
+async fn wait_for_first_data(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    metrics: &Arc<SharedMetrics>,
+    progress: &LoadingProgress,
+    config: &Config,
+    test_mode: bool,
+) -> Result<bool> {
+    let started = std::time::Instant::now();
+
+    loop {
+        if metrics.last_update().await.is_some() {
+            info!("✅ First metrics received after {:.1}s", started.elapsed().as_secs_f64());
+            return Ok(true);
+        }
+
+        let steps = progress.snapshot();
+        if let Some(error) = steps.fatal {
+            error!("❌ Initialization failed: {}", error);
+            return Err(anyhow::anyhow!(error));
+        }
+
+        show_loading_screen(terminal, config, test_mode, &steps, started.elapsed())?;
+
+        if event::poll(Duration::from_millis(config.ui_settings.refresh_rate_ms))? {
+            if let Event::Key(key) = event::read()? {
+                if matches!(key.code, KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc) {
+                    info!("👋 User quit during initialization");
+                    return Ok(false);
+                }
+            }
+        }
+    }
+}
+
 fn show_loading_screen(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, 
     config: &Config,
-    test_mode: bool
+    test_mode: bool,
+    steps: &loading::LoadingSteps,
+    elapsed: Duration,
 ) -> Result<()> {
     use ratatui::{
         layout::{Alignment, Constraint, Direction, Layout},
@@ -526,13 +2086,36 @@ fn show_loading_screen(
         widgets::{Block, Borders, Paragraph},
     };
 
+    const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let spinner = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+
+    let step_line = |label: &str, status: &StepStatus| {
+        let (icon, color, detail) = match status {
+            StepStatus::Pending => (spinner, Color::DarkGray, "waiting".to_string()),
+            StepStatus::InProgress(detail) => (spinner, Color::Yellow, detail.clone()),
+            StepStatus::Done(detail) => ("✓", Color::Green, detail.clone()),
+            StepStatus::Skipped(detail) => ("–", Color::DarkGray, detail.clone()),
+            StepStatus::Failed(detail) => ("✗", Color::Red, detail.clone()),
+        };
+        Line::from(vec![
+            Span::styled(format!("{} ", icon), Style::default().fg(color)),
+            Span::styled(format!("{:<18}", label), Style::default().fg(Color::White)),
+            Span::styled(format!("{:<14}", detail), Style::default().fg(color)),
+        ])
+    };
+
+    let first_data = match &steps.last_error {
+        Some(error) => StepStatus::InProgress(format!("retrying: {}", error)),
+        None => StepStatus::Pending,
+    };
+
     terminal.draw(|f| {
         let size = f.size();
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Percentage(30),
-                Constraint::Length(14),
+                Constraint::Length(15),
                 Constraint::Percentage(56),
             ])
             .split(size);
@@ -565,27 +2148,41 @@ fn show_loading_screen(
                 Span::raw("API Endpoint: "),
                 Span::styled(config.hyperliquid_api_url.clone(), Style::default().fg(Color::Cyan))
             ]),
-            Line::from(vec![
-                Span::raw("User Address: "),
-                Span::styled(config.user_address.clone(), Style::default().fg(Color::Yellow))
-            ]),
+            Line::from(match &config.vault_address {
+                Some(vault) if *vault == config.user_address => vec![
+                    Span::raw("Vault Address: "),
+                    Span::styled(vault.clone(), Style::default().fg(Color::Yellow))
+                ],
+                _ => vec![
+                    Span::raw("User Address: "),
+                    Span::styled(config.user_address.clone(), Style::default().fg(Color::Yellow))
+                ],
+            }),
             Line::from(vec![
                 Span::raw("Update Interval: "),
                 Span::styled(format!("{}ms", config.update_interval_ms), Style::default().fg(Color::Green))
             ]),
             Line::from(""),
-            Line::from("Initializing metrics..."),
-            Line::from("• Phantom Liquidity Index (PLI)"),
-            Line::from("• VPIN Toxicity Detection"),
-            Line::from("• Position Concentration Analysis"),
-            Line::from("• Liquidation Risk Assessment"),
-            Line::from("• Order Flow Quality Monitoring"),
+            step_line("Exchange metadata", &steps.meta),
+            step_line("WebSocket", &steps.websocket),
+            step_line("User state", &steps.user_state),
+            step_line("Order books", &steps.order_books),
+            step_line("First metrics", &first_data),
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("{:.1}s elapsed │ q to quit", elapsed.as_secs_f64()),
+                Style::default().fg(Color::DarkGray),
+            )),
         ])
         .alignment(Alignment::Center)
         .block(Block::default().title("Initializing").borders(Borders::ALL));
 
         f.render_widget(title, chunks[0]);
         f.render_widget(loading, chunks[1]);
+
+        if config.ui_settings.ascii_mode {
+            ui::ascii::asciify(f.buffer_mut());
+        }
     })?;
 
     Ok(())
@@ -594,7 +2191,8 @@ fn show_loading_screen(
 fn show_help_screen(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     test_mode: bool,
-    debug_mode: bool
+    debug_mode: bool,
+    ascii_mode: bool,
 ) -> Result<()> {
     use ratatui::{
         layout::{Alignment, Constraint, Direction, Layout},
@@ -624,16 +2222,19 @@ fn show_help_screen(
             Line::from(vec![
                 Span::styled("NAVIGATION", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
             ]),
-            Line::from("Tab                 - Switch between tabs"),
+            Line::from("Tab / Shift+Tab     - Next / previous tab"),
+            Line::from("1-9                 - Jump directly to tab"),
             Line::from("↑/↓ Arrow Keys      - Scroll content"),
-            Line::from("Page Up/Page Down   - Fast scroll"),
+            Line::from("Page Up/Page Down   - Previous / next page"),
             Line::from("Home                - Jump to top"),
             Line::from(""),
             Line::from(vec![
                 Span::styled("CONTROLS", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
             ]),
-            Line::from("Q or Ctrl+Q         - Quit application"),
+            Line::from("Q or Ctrl+Q         - Quit application (asks first if confirm_quit is set)"),
             Line::from("Esc                 - Quit application"),
+            Line::from("A (Alerts tab)      - Acknowledge all alerts"),
+            Line::from("D                   - Show amounts in USD, BTC or % of TVL"),
             Line::from("R                   - Reset scroll position"),
             Line::from("H                   - Show this help"),
             Line::from("S                   - Save configuration"),
@@ -649,12 +2250,26 @@ fn show_help_screen(
             Line::from(vec![
                 Span::styled("TABS", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
             ]),
-            Line::from("Overview            - Key metrics and system health"),
-            Line::from("Liquidity           - Spread analysis and phantom liquidity"),
-            Line::from("Risk                - VPIN, liquidation, and concentration risk"),
-            Line::from("Performance         - Returns, Sharpe ratio, and drawdowns"),
-            Line::from("Positions           - Open positions and margin usage"),
-            Line::from("Alerts              - Real-time alert feed"),
+            Line::from("1 Overview          - Key metrics and system health"),
+            Line::from("2 Liquidity         - Spread analysis and phantom liquidity"),
+            Line::from("3 Risk              - VPIN, liquidation, and concentration risk"),
+            Line::from("4 Performance       - Returns, Sharpe ratio, and drawdowns"),
+            Line::from("5 Positions         - Open positions and margin usage"),
+            Line::from("6 Alerts            - Real-time alert feed"),
+            Line::from("7 Charts            - Metric history and price candles"),
+            Line::from("8 Heatmap           - Per-asset spread/VPIN severity grid"),
+            Line::from("9 Logs              - In-app log viewer with level/target filters"),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("CHARTS (Charts and Performance tabs)", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            ]),
+            Line::from("M / Shift+M         - Next / previous metric"),
+            Line::from("A / Shift+A         - Next / previous candle asset (Liquidity: ladder asset)"),
+            Line::from("[ / ]               - Shorter / longer time range"),
+            Line::from("+ / -               - Zoom in / out"),
+            Line::from("← / →               - Pan back / forward, or move the crosshair"),
+            Line::from("X                   - Toggle crosshair with value readout"),
+            Line::from("End                 - Return to live view"),
             Line::from(""),
             Line::from(vec![
                 Span::styled("METRICS LEGEND", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -684,6 +2299,121 @@ fn show_help_screen(
         f.render_widget(title, chunks[0]);
         f.render_widget(help_text, chunks[1]);
         f.render_widget(footer, chunks[2]);
+
+        if ascii_mode {
+            ui::ascii::asciify(f.buffer_mut());
+        }
+    })?;
+
+    loop {
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(_) = event::read()? {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Full-screen recap shown on quit; any key exits.
+fn show_session_summary(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    summary: &SessionSummary,
+    ascii_mode: bool,
+) -> Result<()> {
+    use ratatui::{
+        layout::{Alignment, Constraint, Direction, Layout},
+        style::{Color, Modifier, Style},
+        text::{Line, Span},
+        widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    };
+
+    terminal.draw(|f| {
+        let size = f.size();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Length(7), Constraint::Min(0), Constraint::Length(1)])
+            .split(size);
+
+        let title = Paragraph::new(format!(
+            "Session Summary - {} to {} ({})",
+            ui::format::time(summary.started, "%H:%M:%S"),
+            ui::format::time(summary.ended, "%H:%M:%S"),
+            summary.format_duration()
+        ))
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(title, chunks[0]);
+
+        let pnl = match summary.pnl_change() {
+            Some(change) => {
+                let color = if change >= 0.0 { Color::Green } else { Color::Red };
+                Span::styled(session::format_value(history::HistoryMetric::Equity, change), Style::default().fg(color).add_modifier(Modifier::BOLD))
+            }
+            None => Span::styled("n/a", Style::default().fg(Color::DarkGray)),
+        };
+        let top_alerts = summary
+            .alerts_by_metric
+            .iter()
+            .take(4)
+            .map(|(metric, count)| format!("{} ×{}", metric, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let overview = Paragraph::new(vec![
+            Line::from(vec![Span::raw("PnL change (equity): "), pnl]),
+            Line::from(vec![
+                Span::raw("Alerts fired: "),
+                Span::styled(format!("{} critical", summary.critical_alerts), Style::default().fg(Color::Red)),
+                Span::raw(", "),
+                Span::styled(format!("{} warning", summary.warning_alerts), Style::default().fg(Color::Yellow)),
+                Span::raw(format!(", {} info", summary.info_alerts)),
+            ]),
+            Line::from(format!("Most frequent: {}", if top_alerts.is_empty() { "none".to_string() } else { top_alerts })),
+            Line::from(format!(
+                "Uptime: HTTP {} │ WebSocket {}",
+                session::format_uptime(summary.http_uptime),
+                session::format_uptime(summary.websocket_uptime)
+            )),
+        ])
+        .block(Block::default().title("🧾 Session").borders(Borders::ALL));
+        f.render_widget(overview, chunks[1]);
+
+        let rows: Vec<Row> = summary
+            .metrics
+            .iter()
+            .map(|stats| {
+                let value = |v: f64| Cell::from(session::format_value(stats.metric, v));
+                Row::new(vec![
+                    Cell::from(stats.metric.label()),
+                    value(stats.min),
+                    value(stats.max),
+                    value(stats.avg),
+                    value(stats.last),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [Constraint::Length(26), Constraint::Length(14), Constraint::Length(14), Constraint::Length(14), Constraint::Length(14)],
+        )
+        .header(
+            Row::new(vec!["Metric", "Min", "Max", "Avg", "Last"])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().title("📊 Metrics over the session").borders(Borders::ALL));
+        f.render_widget(table, chunks[2]);
+
+        let footer = Paragraph::new("Press any key to exit")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center);
+        f.render_widget(footer, chunks[3]);
+
+        if ascii_mode {
+            ui::ascii::asciify(f.buffer_mut());
+        }
     })?;
 
     loop {
@@ -697,6 +2427,22 @@ fn show_help_screen(
     Ok(())
 }
 
+/// Window and crosshair keys shared by every chart; returns whether the key was used.
+fn handle_chart_key(chart: &mut ChartState, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char(']') => chart.next_range(),
+        KeyCode::Char('[') => chart.previous_range(),
+        KeyCode::Char('+') | KeyCode::Char('=') => chart.zoom_in(),
+        KeyCode::Char('-') => chart.zoom_out(),
+        KeyCode::Left => chart.step_back(),
+        KeyCode::Right => chart.step_forward(),
+        KeyCode::Char('x') | KeyCode::Char('X') => chart.toggle_crosshair(),
+        KeyCode::End => chart.reset_view(),
+        _ => return false,
+    }
+    true
+}
+
 fn check_critical_alerts(alerts: &[Alert], last_count: &mut usize) {
     let critical_alerts: Vec<_> = alerts.iter()
         .filter(|alert| alert.level == AlertLevel::Critical)
@@ -716,122 +2462,95 @@ fn check_critical_alerts(alerts: &[Alert], last_count: &mut usize) {
     *last_count = current_critical_count;
 }
 
+/// Fetches one cycle and computes its metrics, for one-off snapshots.
 async fn update_metrics<P: DataProvider>(
     provider: &P,
     streaming_metrics: &Option<Arc<RwLock<crate::metrics::streaming::StreamingMetricsEngine>>>
 ) -> Result<GlobalMetrics> {
+    let data = fetch_cycle_data(provider, streaming_metrics).await?;
+    Ok(compute_metrics(data, streaming_metrics).await)
+}
+
+async fn fetch_cycle_data<P: DataProvider>(
+    provider: &P,
+    streaming_metrics: &Option<Arc<RwLock<crate::metrics::streaming::StreamingMetricsEngine>>>
+) -> Result<CycleData> {
     debug!("📊 Fetching data from provider...");
+    provider.begin_cycle();
     
-    let vault_summary = provider.get_vault_summary().await
-        .map_err(|e| {
-            error!("❌ Failed to get vault summary: {}", e);
-            e
-        })?;
-    
-    let user_state = provider.get_user_state().await
-        .map_err(|e| {
-            error!("❌ Failed to get user state: {}", e);
-            e
-        })?;
-    
-    let meta = provider.get_meta().await
-        .map_err(|e| {
-            error!("❌ Failed to get meta: {}", e);
-            e
-        })?;
-    
-    let recent_fills = provider.get_recent_fills().await
-        .map_err(|e| {
-            error!("❌ Failed to get recent fills: {}", e);
-            e
-        })?;
-    
-    let l2_snapshots = provider.get_l2_snapshots().await
-        .map_err(|e| {
-            error!("❌ Failed to get L2 snapshots: {}", e);
-            e
-        })?;
-    
-    debug!("📊 Successfully fetched all data, calculating metrics...");
-    
-    let vault_metrics = metrics::calculate_vault_metrics(&vault_summary, &user_state);
-    let performance_metrics = metrics::calculate_performance_metrics(&recent_fills, &vault_summary);
-    let liquidity_metrics = metrics::calculate_liquidity_metrics(&l2_snapshots, &recent_fills, &meta);
-    let risk_metrics = metrics::calculate_risk_metrics(&vault_summary, &recent_fills, &liquidity_metrics, &meta);
-    
-    let mut global_metrics = GlobalMetrics {
-        vault_metrics,
-        performance_metrics,
-        liquidity_metrics,
-        risk_metrics,
-        last_update: Some(chrono::Utc::now()),
+    // Books streamed over the WebSocket are already current; only the rest are polled.
+    let live_books = match streaming_metrics {
+        Some(engine) => engine.read().await.live_order_books(),
+        None => std::collections::HashMap::new(),
+    };
+    let live: std::collections::HashSet<String> = live_books.keys().cloned().collect();
+    let shadowed = match streaming_metrics {
+        Some(engine) => engine.write().await.shadow_sample(&live),
+        None => Vec::new(),
     };
     
-    if let Some(ref engine) = streaming_metrics {
-        debug!("📊 Integrating streaming metrics...");
-        let engine_guard = engine.read().await;
-        
-        let streaming_vpin = engine_guard.get_current_vpin();
-        let phantom_metrics = engine_guard.get_phantom_liquidity_metrics();
-        let real_time_spreads = engine_guard.get_real_time_spreads();
-        let (streaming_volume, _ ) = engine_guard.get_volume_metrics();
-        let liquidity_realization_rate = engine_guard.get_depth_realisation_ratio();
-        
-        drop(engine_guard);
-        
-        debug!("📊 Streaming data - VPIN: {:.3}, Fleeting: {:.1}%, Spreads: {}, Volume: {:.1}M", 
-               streaming_vpin, phantom_metrics.fleeting_order_ratio * 100.0, real_time_spreads.len(), streaming_volume);
-        
-        global_metrics.risk_metrics.vpin_score = streaming_vpin;
-        global_metrics.risk_metrics.phantom_liquidity_index = phantom_metrics.fleeting_order_ratio;
-        
-        global_metrics.liquidity_metrics.fleeting_order_ratio = phantom_metrics.fleeting_order_ratio;
-        global_metrics.liquidity_metrics.avg_order_lifetime_ms = phantom_metrics.avg_order_lifetime_ms;
-        global_metrics.liquidity_metrics.layering_detection_score = phantom_metrics.layering_score;
-        global_metrics.liquidity_metrics.spoofing_detection_index = phantom_metrics.spoofing_events as f64;
-        global_metrics.liquidity_metrics.cancel_rate = phantom_metrics.cancellation_rate;
-        
-        global_metrics.liquidity_metrics.liquidity_realization_rate = liquidity_realization_rate;
-        
-        global_metrics.risk_metrics.phantom_liquidity_index = {
-            let depth_penalty    = 1.0 - global_metrics.liquidity_metrics.liquidity_realization_rate;
-            let spoof_penalty    = (phantom_metrics.spoofing_events as f64 / 50.0).tanh();
-            let layering_penalty = phantom_metrics.layering_score;
-            let flow_penalty     =
-                0.5 * phantom_metrics.fleeting_order_ratio + 0.5 * phantom_metrics.cancellation_rate;
-        
-            (depth_penalty + spoof_penalty + layering_penalty + flow_penalty) / 4.0
-        };
-        
-        for (coin, spread) in real_time_spreads {
-            global_metrics.liquidity_metrics.bid_ask_spread_bps.insert(coin, spread);
-        }
-
-        global_metrics.performance_metrics.total_volume += streaming_volume;
-
-        
-        global_metrics.vault_metrics.tvl = vault_summary.tvl;
-        global_metrics.vault_metrics.equity = vault_summary.equity;
-        global_metrics.vault_metrics.apr = vault_summary.apr;
-        global_metrics.vault_metrics.deployed_liquidity = vault_summary.deployed_liquidity;
-        global_metrics.vault_metrics.idle_liquidity = vault_summary.idle_liquidity;
-        global_metrics.vault_metrics.utilization_rate = 1.0 - (vault_summary.idle_liquidity / vault_summary.tvl).to_f64().unwrap_or(0.0);
- 
-       
-        
-        debug!("📊 Successfully integrated streaming metrics");
-    }
+    // The requests don't depend on each other, so they are all in flight at once
+    // and the cycle takes as long as the slowest.
+    let (vault_summary, user_state, meta, recent_fills, l2_snapshots, open_orders, backstop, shadow_books) = tokio::join!(
+        provider.get_vault_summary().instrument(tracing::info_span!("get_vault_summary")),
+        provider.get_user_state().instrument(tracing::info_span!("get_user_state")),
+        provider.get_meta().instrument(tracing::info_span!("get_meta")),
+        provider.get_recent_fills().instrument(tracing::info_span!("get_recent_fills")),
+        provider.get_l2_snapshots(&live).instrument(tracing::info_span!("get_l2_snapshots")),
+        provider.get_open_orders().instrument(tracing::info_span!("get_open_orders")),
+        provider.get_backstop().instrument(tracing::info_span!("get_backstop")),
+        provider.get_shadow_books(&shadowed).instrument(tracing::info_span!("get_shadow_books")),
+    );
+    // Every request of the cycle is answered; later callers get fresh responses.
+    provider.end_cycle();
+    
+    let vault_summary = vault_summary.map_err(|e| {
+        error!("❌ Failed to get vault summary: {}", e);
+        e
+    })?;
+    let user_state = user_state.map_err(|e| {
+        error!("❌ Failed to get user state: {}", e);
+        e
+    })?;
+    let meta = meta.map_err(|e| {
+        error!("❌ Failed to get meta: {}", e);
+        e
+    })?;
+    let recent_fills = recent_fills.map_err(|e| {
+        error!("❌ Failed to get recent fills: {}", e);
+        e
+    })?;
+    let mut l2_snapshots = l2_snapshots.map_err(|e| {
+        error!("❌ Failed to get L2 snapshots: {}", e);
+        e
+    })?;
+    l2_snapshots.extend(live_books);
     
-    debug!("📊 Calculated metrics successfully");
+    // Own orders only decorate the depth ladder, so a failed fetch shouldn't cost the cycle.
+    let open_orders = open_orders.unwrap_or_else(|e| {
+        warn!("⚠️ Failed to get open orders: {}", e);
+        Vec::new()
+    });
+    // Exchange-wide context, like the orders above.
+    let backstop = backstop.unwrap_or_else(|e| {
+        warn!("⚠️ Failed to get the backstop: {}", e);
+        Backstop::default()
+    });
+    // Only the shadow comparison misses these.
+    let shadow_books = shadow_books.unwrap_or_else(|e| {
+        debug!("📊 Failed to get the shadow books: {}", e);
+        std::collections::HashMap::new()
+    });
     
-    Ok(global_metrics)
+    debug!("📊 Successfully fetched all data");
+    Ok(CycleData { vault_summary, user_state, meta, recent_fills, l2_snapshots, open_orders, backstop, shadow_books })
 }
 
 #[allow(dead_code)]
-async fn debug_metrics_state(metrics: &Arc<RwLock<GlobalMetrics>>) {
-    let m = metrics.read().await;
+async fn debug_metrics_state(metrics: &Arc<SharedMetrics>) {
+    let m = metrics.snapshot().await;
     eprintln!("🔍 DEBUG METRICS STATE:");
     eprintln!("  VPIN: {:.3}", m.risk_metrics.vpin_score);
     eprintln!("  PLI: {:.1}%", m.risk_metrics.phantom_liquidity_index * 100.0);
-    eprintln!("  TVL: ${:.1}M", m.vault_metrics.tvl.to_f64().unwrap_or(0.0) / 1_000_000.0);
+    eprintln!("  TVL: {}", session::format_signed_usd(m.vault_metrics.tvl.to_f64().unwrap_or(0.0)));
 }
\ No newline at end of file
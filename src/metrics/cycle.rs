@@ -0,0 +1,151 @@
+use log::debug;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::streaming::StreamingMetricsEngine;
+use crate::model::*;
+
+/// What the provider returned in one cycle; everything metrics are computed from
+/// besides the streaming engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleData {
+    pub vault_summary: VaultSummary,
+    pub user_state: UserState,
+    pub meta: Meta,
+    pub recent_fills: Vec<Fill>,
+    pub l2_snapshots: HashMap<String, L2Snapshot>,
+    pub open_orders: Vec<OpenOrder>,
+    pub backstop: Backstop,
+    /// HTTP books of a few of the streamed assets, which `l2_snapshots` has
+    /// from the stream, for the shadow comparison of their spreads.
+    #[serde(default)]
+    pub shadow_books: HashMap<String, L2Snapshot>,
+}
+
+/// The metrics of one cycle: computed from `data`, then overlaid with the
+/// streaming engine's when there is one.
+pub async fn compute_metrics(
+    data: CycleData,
+    streaming_metrics: &Option<Arc<RwLock<StreamingMetricsEngine>>>
+) -> GlobalMetrics {
+    debug!("📊 Calculating metrics...");
+    let CycleData { vault_summary, user_state, meta, recent_fills, l2_snapshots, open_orders, backstop, shadow_books } = data;
+    
+    // CPU work, which `par_map` spreads over threads: off the async workers.
+    let span = tracing::info_span!("calculate_metrics");
+    let calculated = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| {
+            let vault_metrics = super::calculate_vault_metrics(&vault_summary, &user_state);
+            let performance_metrics = super::calculate_performance_metrics(&recent_fills, &vault_summary, &user_state.positions);
+            let liquidity_metrics = super::calculate_liquidity_metrics(&l2_snapshots, &recent_fills, &meta);
+            let mut risk_metrics = super::calculate_risk_metrics(&vault_summary, &recent_fills, &liquidity_metrics, &meta, &user_state.positions, &user_state.spot_balances);
+            risk_metrics.backstop = backstop;
+            (vault_metrics, performance_metrics, liquidity_metrics, risk_metrics, vault_summary, user_state, l2_snapshots)
+        })
+    })
+    .await;
+    let (vault_metrics, performance_metrics, liquidity_metrics, risk_metrics, vault_summary, user_state, l2_snapshots) =
+        calculated.unwrap_or_else(|panic| std::panic::resume_unwind(panic.into_panic()));
+    
+    let mut global_metrics = GlobalMetrics {
+        vault_metrics,
+        performance_metrics,
+        liquidity_metrics,
+        risk_metrics,
+        system_status: SystemStatus::default(),
+        order_books: l2_snapshots,
+        open_orders,
+        positions: user_state.positions,
+        trade_tape: Vec::new(),
+        last_update: Some(chrono::Utc::now()),
+    };
+    
+    if let Some(ref engine) = streaming_metrics {
+        debug!("📊 Integrating streaming metrics...");
+        // The poll-based readings the stream replaces, for the shadow comparison.
+        let polled_vpin = global_metrics.risk_metrics.vpin_score;
+        let polled_phantom_liquidity = global_metrics.risk_metrics.phantom_liquidity_index;
+        // The cycle's books of streamed assets are the stream's own, so their
+        // polled spreads come from the books polled for the comparison.
+        let polled_spreads: HashMap<String, f64> = shadow_books
+            .iter()
+            .filter_map(|(coin, book)| Some((coin.clone(), super::calculate_spread_bps(book.bids.first()?.px, book.asks.first()?.px))))
+            .collect();
+        let engine_guard = engine.read().await;
+        
+        let streaming_vpin = engine_guard.get_current_vpin();
+        let phantom_metrics = engine_guard.get_phantom_liquidity_metrics();
+        let real_time_spreads = engine_guard.get_real_time_spreads();
+        let (streaming_volume, _ ) = engine_guard.get_volume_metrics();
+        let liquidity_realization_rate = engine_guard.get_depth_realisation_ratio();
+        global_metrics.trade_tape = engine_guard.recent_trades(super::streaming::TAPE_LEN);
+        let top_trader = engine_guard.top_trader();
+        global_metrics.risk_metrics.whale_activity = engine_guard.whale_activity();
+        global_metrics.risk_metrics.large_prints = engine_guard.large_prints();
+        
+        drop(engine_guard);
+        
+        debug!("📊 Streaming data - VPIN: {:.3}, Fleeting: {:.1}%, Spreads: {}, Volume: {:.1}M", 
+               streaming_vpin, phantom_metrics.fleeting_order_ratio * 100.0, real_time_spreads.len(), streaming_volume);
+        
+        global_metrics.risk_metrics.vpin_score = streaming_vpin;
+        global_metrics.risk_metrics.phantom_liquidity_index = phantom_metrics.fleeting_order_ratio;
+        
+        global_metrics.liquidity_metrics.fleeting_order_ratio = phantom_metrics.fleeting_order_ratio;
+        global_metrics.liquidity_metrics.avg_order_lifetime_ms = phantom_metrics.avg_order_lifetime_ms;
+        global_metrics.liquidity_metrics.layering_detection_score = phantom_metrics.layering_score;
+        global_metrics.liquidity_metrics.spoofing_detection_index = phantom_metrics.spoofing_events as f64;
+        global_metrics.liquidity_metrics.cancel_rate = phantom_metrics.cancellation_rate;
+        
+        global_metrics.liquidity_metrics.liquidity_realization_rate = liquidity_realization_rate;
+        if let Some((trader, share)) = top_trader {
+            global_metrics.liquidity_metrics.top_trader = Some(trader);
+            global_metrics.liquidity_metrics.top_trader_share = share;
+        }
+        
+        global_metrics.risk_metrics.phantom_liquidity_index = {
+            let depth_penalty    = 1.0 - global_metrics.liquidity_metrics.liquidity_realization_rate;
+            let spoof_penalty    = (phantom_metrics.spoofing_events as f64 / 50.0).tanh();
+            let layering_penalty = phantom_metrics.layering_score;
+            let flow_penalty     =
+                0.5 * phantom_metrics.fleeting_order_ratio + 0.5 * phantom_metrics.cancellation_rate;
+        
+            (depth_penalty + spoof_penalty + layering_penalty + flow_penalty) / 4.0
+        };
+        
+        let shadow_spreads = real_time_spreads
+            .iter()
+            .filter_map(|(coin, &streamed)| polled_spreads.get(coin).map(|&polled| (coin.clone(), ShadowReading { polled, streamed })))
+            .collect();
+        engine.write().await.record_shadow(
+            ShadowReading { polled: polled_vpin, streamed: global_metrics.risk_metrics.vpin_score },
+            ShadowReading { polled: polled_phantom_liquidity, streamed: global_metrics.risk_metrics.phantom_liquidity_index },
+            shadow_spreads,
+        );
+        
+        for (coin, spread) in real_time_spreads {
+            global_metrics.liquidity_metrics.bid_ask_spread_bps.insert(coin, spread);
+        }
+
+        global_metrics.performance_metrics.total_volume += streaming_volume;
+
+        
+        global_metrics.vault_metrics.tvl = vault_summary.tvl;
+        global_metrics.vault_metrics.equity = vault_summary.equity;
+        global_metrics.vault_metrics.apr = vault_summary.apr;
+        global_metrics.vault_metrics.deployed_liquidity = vault_summary.deployed_liquidity;
+        global_metrics.vault_metrics.idle_liquidity = vault_summary.idle_liquidity;
+        global_metrics.vault_metrics.utilization_rate = 1.0 - (vault_summary.idle_liquidity / vault_summary.tvl).to_f64().unwrap_or(0.0);
+ 
+       
+        
+        debug!("📊 Successfully integrated streaming metrics");
+    }
+    
+    debug!("📊 Calculated metrics successfully");
+    
+    global_metrics
+}
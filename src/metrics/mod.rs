@@ -2,9 +2,37 @@ use crate::model::*;
 use rust_decimal::prelude::*;
 use std::collections::{HashMap, HashSet};
 
+pub mod cycle;
 pub mod risk;
 pub mod streaming;
 
+/// Below this many assets, spreading the per-asset work over threads costs more
+/// than it saves.
+const PARALLEL_MIN_ASSETS: usize = 16;
+
+/// `f` of every item, in order. With enough items they are split into one chunk
+/// per core, so the per-asset work of a large universe fits in a cycle. It blocks
+/// until every chunk is done, so async callers run it on `spawn_blocking`, as
+/// `compute_metrics` does.
+pub(crate) fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if items.len() < PARALLEL_MIN_ASSETS || threads == 1 {
+        return items.iter().map(f).collect();
+    }
+    let chunk = items.len().div_ceil(threads);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
+
 pub fn calculate_vault_metrics(
     vault_summary: &VaultSummary, 
     user_state: &UserState
@@ -17,20 +45,62 @@ pub fn calculate_vault_metrics(
     
     let deployed_liquidity = user_state.total_margin_used;
     let idle_liquidity = vault_summary.tvl - deployed_liquidity;
+
+    let isolated_margin: Decimal = user_state.positions
+        .iter()
+        .filter(|position| position.isolated)
+        .map(|position| position.margin_used)
+        .sum();
+    let isolated_utilization = if vault_summary.tvl > Decimal::ZERO {
+        (isolated_margin / vault_summary.tvl).to_f64().unwrap_or(0.0)
+    } else {
+        0.0
+    };
     
     VaultMetrics {
+        name: vault_summary.name.clone(),
+        address: vault_summary.vault_address.clone(),
         tvl: vault_summary.tvl,
         equity: vault_summary.equity,
         apr: vault_summary.apr,
+        share_price: vault_summary.share_price,
         utilization_rate,
         deployed_liquidity,
         idle_liquidity,
+        isolated_margin,
+        isolated_utilization,
+        spot_value: user_state.spot_balances.iter().map(|balance| balance.value).sum(),
+        spot_balances: user_state.spot_balances.clone(),
+        pnl_24h: vault_summary.flows.iter().map(|flow| flow.pnl).sum(),
+        net_flow_24h: vault_summary.flows.iter().map(|flow| flow.net_flow).sum(),
+        flows: vault_summary.flows.clone(),
+        vaults: match vault_summary.vaults.len() {
+            0 | 1 => Vec::new(),
+            _ => vault_summary.vaults.clone(),
+        },
+        sub_accounts: user_state.accounts.clone(),
     }
 }
 
+/// Net withdrawals over the last `window_ms` of `flows`, as a fraction of the
+/// account value at the start of the window; zero when deposits outweigh them.
+pub fn net_withdrawal(flows: &[VaultFlow], window_ms: u64) -> f64 {
+    let Some(latest) = flows.last() else {
+        return 0.0;
+    };
+    let start = latest.time.saturating_sub(window_ms);
+    let opening = flows.iter().rev().find(|flow| flow.time <= start).or(flows.first()).map_or(Decimal::ZERO, |flow| flow.account_value);
+    let withdrawn = -flows.iter().filter(|flow| flow.time > start).map(|flow| flow.net_flow).sum::<Decimal>();
+    if opening <= Decimal::ZERO || withdrawn <= Decimal::ZERO {
+        return 0.0;
+    }
+    (withdrawn / opening).to_f64().unwrap_or(0.0)
+}
+
 pub fn calculate_performance_metrics(
     fills: &[Fill], 
-    vault_summary: &VaultSummary
+    vault_summary: &VaultSummary,
+    positions: &[Position],
 ) -> PerformanceMetrics {
     let daily_pnl = fills.iter()
         .map(|fill| fill.closed_pnl)
@@ -56,6 +126,11 @@ pub fn calculate_performance_metrics(
     }
     
     let adverse_selection_cost = calculate_adverse_selection_cost(fills);
+    let fees = calculate_fee_metrics(fills);
+    let components = match vault_summary.components {
+        true => calculate_component_metrics(fills, positions),
+        false => Vec::new(),
+    };
     
     PerformanceMetrics {
         daily_pnl,
@@ -65,9 +140,108 @@ pub fn calculate_performance_metrics(
         sortino_ratio,
         realized_spread,
         adverse_selection_cost,
+        fees,
+        components,
     }
 }
 
+/// Market making and liquidation takeovers, as HLP runs them through separate
+/// strategies, each from its share of `fills`. A position belongs to the
+/// component whose recent fills on it added the most to it, or to market
+/// making when neither did.
+pub fn calculate_component_metrics(fills: &[Fill], positions: &[Position]) -> Vec<ComponentMetrics> {
+    let (liquidations, market_making): (Vec<Fill>, Vec<Fill>) = fills.iter().cloned().partition(Fill::is_liquidation_takeover);
+    let components = [("Market making", market_making), ("Liquidations", liquidations)];
+
+    let total_exposure: Decimal = positions.iter().map(|position| position.position_value.abs()).sum();
+    let mut owned: Vec<Vec<&Position>> = vec![Vec::new(); components.len()];
+    for position in positions {
+        let added = |fills: &[Fill]| -> Decimal {
+            fills
+                .iter()
+                .filter(|fill| fill.coin == position.symbol)
+                .map(|fill| if fill.side == "B" { fill.sz.abs() } else { -fill.sz.abs() })
+                .sum::<Decimal>()
+                * position.size.signum()
+        };
+        let owner = (1..components.len())
+            .filter(|&i| added(&components[i].1) > added(&components[0].1).max(Decimal::ZERO))
+            .max_by_key(|&i| added(&components[i].1))
+            .unwrap_or(0);
+        owned[owner].push(position);
+    }
+
+    components
+        .into_iter()
+        .zip(owned)
+        .map(|((name, fills), positions)| ComponentMetrics {
+            name: name.to_string(),
+            fills: fills.len(),
+            volume: fills.iter().map(|fill| fill.px * fill.sz.abs()).sum(),
+            realized_pnl: fills.iter().map(|fill| fill.closed_pnl).sum(),
+            fees: fills.iter().map(|fill| fill.fee).sum(),
+            net_notional: fills
+                .iter()
+                .map(|fill| if fill.side == "B" { fill.px * fill.sz.abs() } else { -fill.px * fill.sz.abs() })
+                .sum(),
+            adverse_selection_cost: calculate_adverse_selection_cost(&fills),
+            exposure_share: match total_exposure > Decimal::ZERO {
+                true => (positions.iter().map(|position| position.position_value.abs()).sum::<Decimal>() / total_exposure).to_f64().unwrap_or(0.0),
+                false => 0.0,
+            },
+            liquidation_distance: positions
+                .iter()
+                .filter_map(|position| position.liquidation_distance())
+                .min_by(f64::total_cmp),
+        })
+        .collect()
+}
+
+/// How far a fill's fee may be off its schedule before it counts as a
+/// deviation, in basis points of its notional; covers Hyperliquid's rounding.
+const FEE_TOLERANCE_BPS: f64 = 0.1;
+
+/// Deviations listed in `FeeMetrics`.
+const MAX_FEE_DEVIATIONS: usize = 20;
+
+pub fn calculate_fee_metrics(fills: &[Fill]) -> FeeMetrics {
+    let mut metrics = FeeMetrics::default();
+    let mut scheduled_notional = Decimal::ZERO;
+    for fill in fills {
+        metrics.fees_paid += fill.fee;
+        metrics.builder_fees += fill.builder_fee;
+        let Some(expected_fee) = fill.expected_fee else {
+            continue;
+        };
+        let notional = fill.px * fill.sz.abs();
+        metrics.expected_fees += expected_fee;
+        scheduled_notional += notional;
+        let off_bps = if notional > Decimal::ZERO {
+            ((fill.fee - expected_fee).abs() / notional).to_f64().unwrap_or(0.0) * 10_000.0
+        } else {
+            0.0
+        };
+        if off_bps > FEE_TOLERANCE_BPS || fill.builder_fee > Decimal::ZERO {
+            metrics.deviations.push(FeeDeviation {
+                coin: fill.coin.clone(),
+                time: fill.time,
+                hash: fill.hash.clone(),
+                notional,
+                fee: fill.fee,
+                expected_fee,
+                builder_fee: fill.builder_fee,
+            });
+        }
+    }
+    if scheduled_notional > Decimal::ZERO {
+        let scheduled_paid: Decimal = fills.iter().filter(|fill| fill.expected_fee.is_some()).map(|fill| fill.fee).sum();
+        metrics.excess_bps = ((scheduled_paid - metrics.expected_fees) / scheduled_notional).to_f64().unwrap_or(0.0) * 10_000.0;
+    }
+    metrics.deviations.sort_by_key(|deviation| std::cmp::Reverse(deviation.time));
+    metrics.deviations.truncate(MAX_FEE_DEVIATIONS);
+    metrics
+}
+
 pub fn calculate_liquidity_metrics(
     l2_snapshots: &HashMap<String, L2Snapshot>,
     fills: &[Fill],
@@ -83,19 +257,22 @@ pub fn calculate_liquidity_metrics(
         .map(|asset| asset.name.clone())
         .collect();
     
-    for (coin, snapshot) in l2_snapshots {
-        if !active_assets.contains(coin) {
-            continue;
-        }
-        
-        if let (Some(best_bid), Some(best_ask)) = (snapshot.bids.first(), snapshot.asks.first()) {
-            let spread_bps = calculate_spread_bps(best_bid.px, best_ask.px);
+    let books: Vec<(&String, &L2Snapshot)> = l2_snapshots
+        .iter()
+        .filter(|(coin, _)| active_assets.contains(*coin))
+        .collect();
+    let analytics = par_map(&books, |(_, snapshot)| {
+        let (best_bid, best_ask) = (snapshot.bids.first()?, snapshot.asks.first()?);
+        Some((
+            calculate_spread_bps(best_bid.px, best_ask.px),
+            calculate_depth_at_bps(snapshot, 50.0),
+            calculate_order_book_imbalance(snapshot),
+        ))
+    });
+    for ((coin, _), analytics) in books.into_iter().zip(analytics) {
+        if let Some((spread_bps, depth, imbalance)) = analytics {
             bid_ask_spread_bps.insert(coin.clone(), spread_bps);
-            
-            let depth = calculate_depth_at_bps(snapshot, 50.0);
             depth_at_50bps.insert(coin.clone(), depth);
-            
-            let imbalance = calculate_order_book_imbalance(snapshot);
             order_book_imbalance.insert(coin.clone(), imbalance);
         }
     }
@@ -115,6 +292,12 @@ pub fn calculate_liquidity_metrics(
         spoofing_detection_index: manipulation_scores.spoofing_index,
         liquidity_realization_rate: manipulation_scores.realization_rate,
         fill_probability_by_distance: fill_probabilities,
+        // Streamed trades alone name their traders; filled in with the streaming metrics.
+        top_trader: None,
+        top_trader_share: 0.0,
+        // Measured across cycles by `illiquid::IlliquidWatch`.
+        trade_rate_per_min: HashMap::new(),
+        illiquid_assets: Vec::new(),
     }
 }
 
@@ -122,27 +305,50 @@ pub fn calculate_risk_metrics(
     vault_summary: &VaultSummary,
     fills: &[Fill],
     liquidity_metrics: &LiquidityMetrics,
-    meta: &Meta
+    meta: &Meta,
+    positions: &[Position],
+    spot_balances: &[SpotBalance],
 ) -> RiskMetrics {
     let vpin_score = risk::calculate_vpin(fills, meta);
+    let vpin_by_asset = risk::calculate_vpin_by_asset(fills);
     let phantom_liquidity_index = risk::calculate_phantom_liquidity_index(liquidity_metrics);
     let liquidation_risk_score = risk::calculate_liquidation_risk(vault_summary);
     let cascade_risk_score = risk::calculate_cascade_risk(fills, meta);
-    let position_concentration = risk::calculate_position_concentration(fills, meta);
+    let position_concentration = risk::calculate_position_concentration(positions, spot_balances);
     let cross_exchange_manipulation = risk::detect_cross_exchange_manipulation(fills, meta);
+    let oracle_divergence = meta.contexts
+        .iter()
+        .filter_map(|(coin, context)| Some((coin.clone(), context.oracle_divergence()?)))
+        .collect();
+    let open_interest_cap_usage = meta.contexts
+        .iter()
+        .filter_map(|(coin, context)| Some((coin.clone(), context.open_interest_cap_usage()?)))
+        .collect();
     
     RiskMetrics {
         vpin_score,
+        vpin_by_asset,
         phantom_liquidity_index,
         liquidation_risk_score,
         cascade_risk_score,
         position_concentration,
+        oracle_prices: meta.contexts.clone(),
+        oracle_divergence,
+        open_interest_cap_usage,
+        // Fetched on its own schedule and filled in by the caller.
+        backstop: Default::default(),
         max_drawdown: vault_summary.max_drawdown,
         cross_exchange_manipulation_score: cross_exchange_manipulation,
+        // From the streamed trades; filled in with the streaming metrics.
+        whale_activity: 0.0,
+        large_prints: Vec::new(),
+        // Kept across cycles by `probation::Probation`.
+        probation: Vec::new(),
     }
 }
 
-fn calculate_sharpe_ratio(returns: &[f64]) -> f64 {
+/// Mean of `returns` over their standard deviation, not annualized.
+pub fn calculate_sharpe_ratio(returns: &[f64]) -> f64 {
     if returns.is_empty() {
         return 0.0;
     }
@@ -156,7 +362,8 @@ fn calculate_sharpe_ratio(returns: &[f64]) -> f64 {
     if std_dev == 0.0 { 0.0 } else { mean_return / std_dev }
 }
 
-fn calculate_sortino_ratio(returns: &[f64]) -> f64 {
+/// Mean of `returns` over their downside deviation, not annualized.
+pub fn calculate_sortino_ratio(returns: &[f64]) -> f64 {
     if returns.is_empty() {
         return 0.0;
     }
@@ -207,7 +414,7 @@ fn calculate_spread_bps(bid: Decimal, ask: Decimal) -> f64 {
     }
 }
 
-fn calculate_depth_at_bps(snapshot: &L2Snapshot, bps: f64) -> Decimal {
+pub fn calculate_depth_at_bps(snapshot: &L2Snapshot, bps: f64) -> Decimal {
     if snapshot.bids.is_empty() || snapshot.asks.is_empty() {
         return Decimal::ZERO;
     }
@@ -230,7 +437,7 @@ fn calculate_depth_at_bps(snapshot: &L2Snapshot, bps: f64) -> Decimal {
     bid_depth + ask_depth
 }
 
-fn calculate_order_book_imbalance(snapshot: &L2Snapshot) -> f64 {
+pub fn calculate_order_book_imbalance(snapshot: &L2Snapshot) -> f64 {
     if snapshot.bids.is_empty() || snapshot.asks.is_empty() {
         return 0.0;
     }
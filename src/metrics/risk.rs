@@ -7,23 +7,39 @@ pub fn calculate_vpin(fills: &[Fill], meta: &Meta) -> f64 {
         return 0.0;
     }
     
-    let bucket_size = Decimal::from(10000);
-    let mut buckets = Vec::new();
-    let mut current_bucket_volume = Decimal::ZERO;
-    let mut current_buy_volume = Decimal::ZERO;
-    let mut current_sell_volume = Decimal::ZERO;
-    
     let major_assets: std::collections::HashSet<String> = meta.universe
         .iter()
         .filter(|asset| asset.max_leverage >= 10)
         .map(|asset| asset.name.clone())
         .collect();
     
+    vpin_from_fills(fills.iter().filter(|fill| major_assets.contains(&fill.coin))).unwrap_or(0.0)
+}
+
+/// VPIN computed separately for each coin; coins without a completed volume bucket are omitted.
+pub fn calculate_vpin_by_asset(fills: &[Fill]) -> HashMap<String, f64> {
+    let mut by_coin: HashMap<&str, Vec<&Fill>> = HashMap::new();
+    for fill in fills {
+        by_coin.entry(fill.coin.as_str()).or_default().push(fill);
+    }
+    
+    let by_coin: Vec<(&str, Vec<&Fill>)> = by_coin.into_iter().collect();
+    super::par_map(&by_coin, |(coin, coin_fills)| {
+        vpin_from_fills(coin_fills.iter().copied()).map(|vpin| (coin.to_string(), vpin))
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn vpin_from_fills<'a>(fills: impl Iterator<Item = &'a Fill>) -> Option<f64> {
+    let bucket_size = Decimal::from(10000);
+    let mut buckets = Vec::new();
+    let mut current_bucket_volume = Decimal::ZERO;
+    let mut current_buy_volume = Decimal::ZERO;
+    let mut current_sell_volume = Decimal::ZERO;
+    
     for fill in fills {
-        if !major_assets.contains(&fill.coin) {
-            continue;
-        }
-        
         let volume = fill.px * fill.sz.abs();
         
         if fill.side == "B" {
@@ -50,13 +66,13 @@ pub fn calculate_vpin(fills: &[Fill], meta: &Meta) -> f64 {
     }
     
     if buckets.is_empty() {
-        return 0.0;
+        return None;
     }
     
     let window_size = 50.min(buckets.len());
     let recent_buckets = &buckets[buckets.len().saturating_sub(window_size)..];
     
-    recent_buckets.iter().sum::<f64>() / recent_buckets.len() as f64
+    Some(recent_buckets.iter().sum::<f64>() / recent_buckets.len() as f64)
 }
 
 pub fn calculate_phantom_liquidity_index(liquidity_metrics: &LiquidityMetrics) -> f64 {
@@ -144,35 +160,44 @@ pub fn calculate_cascade_risk(fills: &[Fill], meta: &Meta) -> f64 {
     cascade_risk.clamp(0.0, 1.0)
 }
 
-pub fn calculate_position_concentration(fills: &[Fill], meta: &Meta) -> HashMap<String, f64> {
-    let mut concentrations = HashMap::new();
+/// Each asset's share of the exposure: the value of its perp position and of
+/// the spot tokens held, USDC aside.
+pub fn calculate_position_concentration(positions: &[Position], spot_balances: &[SpotBalance]) -> HashMap<String, f64> {
     let mut position_values: HashMap<String, Decimal> = HashMap::new();
-    
-    let tradeable_assets: std::collections::HashSet<String> = meta.universe
+    for position in positions {
+        *position_values.entry(position.symbol.clone()).or_insert(Decimal::ZERO) += position.position_value.abs();
+    }
+    for balance in spot_balances.iter().filter(|balance| !balance.is_quote()) {
+        *position_values.entry(balance.coin.clone()).or_insert(Decimal::ZERO) += balance.value.abs();
+    }
+    shares(position_values)
+}
+
+/// Each listed asset's share of the notional traded in `fills`; where the
+/// turnover went rather than what is held.
+pub fn calculate_turnover_concentration(fills: &[Fill], meta: &Meta) -> HashMap<String, f64> {
+    let tradeable_assets: std::collections::HashSet<&str> = meta.universe
         .iter()
-        .map(|asset| asset.name.clone())
+        .map(|asset| asset.name.as_str())
         .collect();
     
-    for fill in fills {
-        if !tradeable_assets.contains(&fill.coin) {
-            continue;
-        }
-        
-        let value = fill.px * fill.sz.abs();
-        let entry = position_values.entry(fill.coin.clone()).or_insert(Decimal::ZERO);
-        *entry += value;
+    let mut notionals: HashMap<String, Decimal> = HashMap::new();
+    for fill in fills.iter().filter(|fill| tradeable_assets.contains(fill.coin.as_str())) {
+        *notionals.entry(fill.coin.clone()).or_insert(Decimal::ZERO) += fill.px * fill.sz.abs();
     }
-    
-    let total_value = position_values.values().sum::<Decimal>();
-    
-    if total_value > Decimal::ZERO {
-        for (coin, value) in position_values {
-            let concentration = (value / total_value).to_f64().unwrap_or(0.0);
-            concentrations.insert(coin, concentration);
-        }
+    shares(notionals)
+}
+
+/// Each value as a share of their sum; none when they sum to nothing.
+fn shares(values: HashMap<String, Decimal>) -> HashMap<String, f64> {
+    let total_value = values.values().sum::<Decimal>();
+    if total_value <= Decimal::ZERO {
+        return HashMap::new();
     }
-    
-    concentrations
+    values
+        .into_iter()
+        .map(|(coin, value)| (coin, (value / total_value).to_f64().unwrap_or(0.0)))
+        .collect()
 }
 
 pub fn detect_cross_exchange_manipulation(fills: &[Fill], meta: &Meta) -> f64 {
@@ -185,8 +210,7 @@ pub fn detect_cross_exchange_manipulation(fills: &[Fill], meta: &Meta) -> f64 {
         .filter(|asset| asset.max_leverage >= 10)
         .count();
     
-    let unusual_pattern_score = if major_assets_count > 10 { 0.15 } else { 0.08 };
-    unusual_pattern_score
+    if major_assets_count > 10 { 0.15 } else { 0.08 }
 }
 
 fn calculate_asset_correlation(assets: &std::collections::HashSet<String>) -> f64 {
@@ -1,22 +1,63 @@
 use crate::model::*;
 use rust_decimal::prelude::*;
-use std::collections::{HashMap, VecDeque};
-use tokio::sync::broadcast;
+use std::collections::{HashMap, HashSet};
+use crate::pipeline::{Receiver, RecvError};
+use crate::config::{BufferSettings, WhaleSettings};
+use crate::ring::RingSeries;
 use tokio::sync::RwLock;
 use std::sync::Arc;
 use log::{debug, info, warn};
 use rust_decimal_macros::dec;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// How long a book stays live without a WebSocket update.
+const LIVE_BOOK_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(10);
+/// Trades shown on the tape.
+pub const TAPE_LEN: usize = 50;
+/// Large prints kept for the feed.
+const LARGE_PRINTS: usize = 50;
+/// Time constant of the whale-activity score's decay, in ms of trade time: a
+/// trade counts for a third as much this long after it.
+const WHALE_SCORE_DECAY_MS: f64 = 5.0 * 60_000.0;
+/// Streamed books polled over HTTP each cycle for the shadow comparison.
+pub const SHADOW_BOOKS: usize = 4;
+/// Of the order lifetimes estimated for trades, so the engine computes the same
+/// metrics from the same stream.
+const LIFETIME_SEED: u64 = 0x4c49_4645;
 
 pub struct StreamingMetricsEngine {
-    trade_buffer: VecDeque<Fill>,
+    trade_buffer: RingSeries<Fill>,
     l2_snapshots: HashMap<String, L2Snapshot>,
-    vpin_buckets: VecDeque<f64>,
+    /// When each book last arrived over the WebSocket.
+    l2_received: HashMap<String, std::time::Instant>,
+    vpin_buckets: RingSeries<f64>,
     bucket_accumulator: VpinBucketAccumulator,
     order_flow_analyzer: OrderFlowAnalyzer,
     phantom_liquidity_tracker: PhantomLiquidityTracker,
-    active_orders: HashMap<u64, std::time::Instant>,
+    /// When each open order was placed, by the exchange's timestamps in ms.
+    active_orders: HashMap<u64, u64>,
+    lifetime_rng: StdRng,
     total_volume_traded: Decimal,
     volume_by_coin: HashMap<String, Decimal>,
+    trades_by_coin: HashMap<String, u64>,
+    whales: WhaleTracker,
+    shadow: ShadowComparison,
+    /// Where the next cycle's `shadow_sample` starts among the live books.
+    shadow_cursor: usize,
+}
+
+/// Flags the trades far larger than their asset's recent average.
+struct WhaleTracker {
+    settings: WhaleSettings,
+    /// The notional of each asset's last `average_trades` trades.
+    recent_notional: HashMap<String, RingSeries<f64>>,
+    large_prints: RingSeries<LargePrint>,
+    /// Sums of the notional of every trade and of the large prints, decaying
+    /// with the time since `last_trade_ms`.
+    volume: f64,
+    whale_volume: f64,
+    last_trade_ms: Option<u64>,
 }
 
 #[derive(Default)]
@@ -27,9 +68,8 @@ struct VpinBucketAccumulator {
     bucket_size: Decimal,
 }
 
-#[derive(Default)]
 struct OrderFlowAnalyzer {
-    order_lifetimes: VecDeque<u64>,
+    order_lifetimes: RingSeries<u64>,
     cancellation_events: u32,
     total_orders: u32,
     fleeting_orders: u32,
@@ -52,78 +92,110 @@ pub struct PhantomLiquidityMetrics {
     pub cancellation_rate: f64,
 }
 
+impl Default for StreamingMetricsEngine {
+    fn default() -> Self {
+        Self::new(&BufferSettings::default(), &WhaleSettings::default())
+    }
+}
+
 impl StreamingMetricsEngine {
-    pub fn new() -> Self {
+    pub fn new(buffers: &BufferSettings, whales: &WhaleSettings) -> Self {
         Self {
-            trade_buffer: VecDeque::with_capacity(10000),
+            trade_buffer: RingSeries::new(buffers.trades),
             l2_snapshots: HashMap::new(),
-            vpin_buckets: VecDeque::with_capacity(100),
+            l2_received: HashMap::new(),
+            vpin_buckets: RingSeries::new(buffers.vpin_window),
             bucket_accumulator: VpinBucketAccumulator {
                 bucket_size: Decimal::from(10000),
                 ..Default::default()
             },
-            order_flow_analyzer: OrderFlowAnalyzer::default(),
+            order_flow_analyzer: OrderFlowAnalyzer {
+                order_lifetimes: RingSeries::new(buffers.order_lifetimes),
+                cancellation_events: 0,
+                total_orders: 0,
+                fleeting_orders: 0,
+            },
             phantom_liquidity_tracker: PhantomLiquidityTracker::default(),
             active_orders: HashMap::new(),
+            lifetime_rng: StdRng::seed_from_u64(LIFETIME_SEED),
             total_volume_traded: Decimal::ZERO,
             volume_by_coin: HashMap::new(),
+            trades_by_coin: HashMap::new(),
+            whales: WhaleTracker {
+                settings: whales.clone(),
+                recent_notional: HashMap::new(),
+                large_prints: RingSeries::new(LARGE_PRINTS),
+                volume: 0.0,
+                whale_volume: 0.0,
+                last_trade_ms: None,
+            },
+            shadow: ShadowComparison::default(),
+            shadow_cursor: 0,
         }
     }
 
     pub async fn run(
         engine: Arc<RwLock<Self>>,
-        mut trade_rx: broadcast::Receiver<Fill>,
-        mut l2_rx: broadcast::Receiver<L2Snapshot>,
-        mut order_rx: broadcast::Receiver<OrderEvent>,
+        mut trade_rx: Receiver<Fill>,
+        mut l2_rx: Receiver<L2Snapshot>,
+        mut order_rx: Receiver<OrderEvent>,
     ) {
-        loop {
+        // A lagging stream goes on being read: the oldest message left comes
+        // next. Once every stream is closed, the WebSocket is gone for good.
+        let (mut trades_open, mut books_open, mut orders_open) = (true, true, true);
+        while trades_open || books_open || orders_open {
             tokio::select! {
-                Ok(fill) = trade_rx.recv() => {
-                    let mut e = engine.write().await;
-                    e.process_trade(fill).await;
-                }
-                Ok(snapshot) = l2_rx.recv() => {
-                    let mut e = engine.write().await;
-                    e.process_l2_update(snapshot).await;
-                }
-                Ok(evt) = order_rx.recv() => {
-                    let mut e = engine.write().await;
-                    match evt.action {
-                        OrderAction::New => e.on_new_order(evt.id),
-                        OrderAction::Cancelled => e.on_cancel_or_fill(evt.id, true),
-                        OrderAction::Filled => e.on_cancel_or_fill(evt.id, false),
-                    }
-                }
+                fill = trade_rx.recv(), if trades_open => match fill {
+                    Ok(fill) => engine.write().await.process_trade(fill),
+                    Err(RecvError::Lagged(skipped)) => debug!("📈 Streaming engine lagged, skipped {} trades", skipped),
+                    Err(RecvError::Closed) => trades_open = false,
+                },
+                snapshot = l2_rx.recv(), if books_open => match snapshot {
+                    Ok(snapshot) => engine.write().await.process_l2_update(snapshot),
+                    Err(RecvError::Lagged(skipped)) => debug!("📊 Streaming engine lagged, skipped {} book updates", skipped),
+                    Err(RecvError::Closed) => books_open = false,
+                },
+                event = order_rx.recv(), if orders_open => match event {
+                    Ok(event) => engine.write().await.process_order(&event),
+                    Err(RecvError::Lagged(skipped)) => debug!("📝 Streaming engine lagged, skipped {} order events", skipped),
+                    Err(RecvError::Closed) => orders_open = false,
+                },
             }
         }
     }
 
-    pub fn on_new_order(&mut self, id: u64) {
-        self.active_orders.insert(id, std::time::Instant::now());
+    /// Times an order from its placement to its cancel or fill.
+    pub fn process_order(&mut self, event: &OrderEvent) {
+        match event.action {
+            OrderAction::New => self.on_new_order(event.id, event.timestamp),
+            OrderAction::Cancelled => self.on_cancel_or_fill(event.id, true, event.timestamp),
+            OrderAction::Filled => self.on_cancel_or_fill(event.id, false, event.timestamp),
+        }
+    }
+    
+    pub fn on_new_order(&mut self, id: u64, at_ms: u64) {
+        self.active_orders.insert(id, at_ms);
     }
     
-    pub fn on_cancel_or_fill(&mut self, id: u64, is_cancel: bool) {
-        if let Some(t0) = self.active_orders.remove(&id) {
-            let lifetime = t0.elapsed().as_millis() as u64;
+    pub fn on_cancel_or_fill(&mut self, id: u64, is_cancel: bool, at_ms: u64) {
+        if let Some(placed_ms) = self.active_orders.remove(&id) {
+            let lifetime = at_ms.saturating_sub(placed_ms);
             self.order_flow_analyzer.total_orders += 1;
-            self.order_flow_analyzer.order_lifetimes.push_back(lifetime);
+            self.order_flow_analyzer.order_lifetimes.push(lifetime);
             if lifetime < 100 {
                 self.order_flow_analyzer.fleeting_orders += 1;
             }
             if is_cancel {
                 self.order_flow_analyzer.cancellation_events += 1;
             }
-            if self.order_flow_analyzer.order_lifetimes.len() > 10_000 {
-                self.order_flow_analyzer.order_lifetimes.pop_front();
-            }
         }
     }
 
     #[allow(dead_code)]
     pub async fn start_streaming_analysis(
         &mut self,
-        mut trade_receiver: broadcast::Receiver<Fill>,
-        mut l2_receiver: broadcast::Receiver<L2Snapshot>,
+        mut trade_receiver: Receiver<Fill>,
+        mut l2_receiver: Receiver<L2Snapshot>,
     ) {
         info!("🔄 Starting streaming metrics analysis");
 
@@ -132,7 +204,7 @@ impl StreamingMetricsEngine {
                 trade_result = trade_receiver.recv() => {
                     match trade_result {
                         Ok(fill) => {
-                            self.process_trade(fill).await;
+                            self.process_trade(fill);
                         }
                         Err(e) => {
                             warn!("📡 Trade stream error: {}", e);
@@ -142,7 +214,7 @@ impl StreamingMetricsEngine {
                 l2_result = l2_receiver.recv() => {
                     match l2_result {
                         Ok(snapshot) => {
-                            self.process_l2_update(snapshot).await;
+                            self.process_l2_update(snapshot);
                         }
                         Err(e) => {
                             warn!("📊 L2 stream error: {}", e);
@@ -153,26 +225,26 @@ impl StreamingMetricsEngine {
         }
     }
 
-    async fn process_trade(&mut self, fill: Fill) {
+    /// Folds one streamed trade into the volume totals, VPIN bucket and buffers.
+    pub fn process_trade(&mut self, fill: Fill) {
         debug!("📈 Processing trade: {} {} @ {}", fill.coin, fill.sz, fill.px);
         
         let trade_volume = fill.px * fill.sz.abs();
         self.total_volume_traded += trade_volume;
         *self.volume_by_coin.entry(fill.coin.clone()).or_insert(Decimal::ZERO) += trade_volume;
+        *self.trades_by_coin.entry(fill.coin.clone()).or_insert(0) += 1;
 
         self.update_vpin_calculation(&fill);
         self.analyze_order_flow(&fill);
+        self.whales.process(&fill);
         
-        self.trade_buffer.push_back(fill);
-        
-        if self.trade_buffer.len() > 5000 {
-            self.trade_buffer.pop_front();
-        }
+        self.trade_buffer.push(fill);
 
 
     }
 
-    async fn process_l2_update(&mut self, snapshot: L2Snapshot) {
+    /// Replaces the asset's live book, comparing it with the previous one.
+    pub fn process_l2_update(&mut self, snapshot: L2Snapshot) {
         debug!("📊 Processing L2 update for {}: {} bids, {} asks", 
                snapshot.coin, snapshot.bids.len(), snapshot.asks.len());
         
@@ -182,6 +254,7 @@ impl StreamingMetricsEngine {
             self.detect_phantom_liquidity(&previous_snapshot, &snapshot);
         }
         
+        self.l2_received.insert(snapshot.coin.clone(), std::time::Instant::now());
         self.l2_snapshots.insert(snapshot.coin.clone(), snapshot);
     }
 
@@ -203,11 +276,7 @@ impl StreamingMetricsEngine {
                 let imbalance = (self.bucket_accumulator.buy_volume - self.bucket_accumulator.sell_volume).abs();
                 let vpin = (imbalance / total_volume).to_f64().unwrap_or(0.0);
                 
-                self.vpin_buckets.push_back(vpin);
-                
-                if self.vpin_buckets.len() > 50 {
-                    self.vpin_buckets.pop_front();
-                }
+                self.vpin_buckets.push(vpin);
                 
                 debug!("🔍 New VPIN bucket: {:.4} (imbalance: {:.2}%)", vpin, vpin * 100.0);
             }
@@ -222,7 +291,7 @@ impl StreamingMetricsEngine {
         self.order_flow_analyzer.total_orders += 1;
         
         let order_lifetime = self.estimate_order_lifetime(fill);
-        self.order_flow_analyzer.order_lifetimes.push_back(order_lifetime);
+        self.order_flow_analyzer.order_lifetimes.push(order_lifetime);
         
         if order_lifetime < 100 {
             self.order_flow_analyzer.fleeting_orders += 1;
@@ -232,10 +301,6 @@ impl StreamingMetricsEngine {
         if self.is_likely_cancellation(fill) {
             self.order_flow_analyzer.cancellation_events += 1;
         }
-        
-        if self.order_flow_analyzer.order_lifetimes.len() > 1000 {
-            self.order_flow_analyzer.order_lifetimes.pop_front();
-        }
     }
 
     fn detect_phantom_liquidity(&mut self, previous: &L2Snapshot, current: &L2Snapshot) {
@@ -319,16 +384,62 @@ impl StreamingMetricsEngine {
         price_counts.values().filter(|&&count| count > 1).sum()
     }
 
-    fn estimate_order_lifetime(&self, _fill: &Fill) -> u64 {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        rng.gen_range(50..300000)
+    fn estimate_order_lifetime(&mut self, _fill: &Fill) -> u64 {
+        self.lifetime_rng.gen_range(50..300000)
     }
     
     fn is_likely_cancellation(&self, fill: &Fill) -> bool {
         fill.sz < rust_decimal::Decimal::from(100) && fill.fee == rust_decimal::Decimal::ZERO
     }
 
+    /// Share of the recent volume traded in large prints.
+    pub fn whale_activity(&self) -> f64 {
+        if self.whales.volume > 0.0 {
+            self.whales.whale_volume / self.whales.volume
+        } else {
+            0.0
+        }
+    }
+
+    /// The buffered large prints, newest first.
+    pub fn large_prints(&self) -> Vec<LargePrint> {
+        self.whales.large_prints.iter().rev().cloned().collect()
+    }
+
+    /// Occupancy of the engine's bounded buffers.
+    pub fn buffer_stats(&self) -> Vec<BufferStats> {
+        vec![
+            self.trade_buffer.stats("trades"),
+            self.vpin_buckets.stats("vpin_buckets"),
+            self.order_flow_analyzer.order_lifetimes.stats("order_lifetimes"),
+        ]
+    }
+
+    /// Records what the poll-based calculators and the engine computed in the
+    /// same cycle; VPIN is left out until a bucket has filled.
+    pub fn record_shadow(&mut self, vpin: ShadowReading, phantom_liquidity: ShadowReading, spreads_bps: HashMap<String, ShadowReading>) {
+        let vpin = (!self.vpin_buckets.is_empty()).then_some(vpin);
+        self.shadow.record(vpin, phantom_liquidity, spreads_bps);
+    }
+
+    /// Up to `SHADOW_BOOKS` of `live`, taking turns from cycle to cycle so
+    /// every streamed asset is polled in time.
+    pub fn shadow_sample(&mut self, live: &HashSet<String>) -> Vec<String> {
+        let mut coins: Vec<&String> = live.iter().collect();
+        coins.sort();
+        let n = SHADOW_BOOKS.min(coins.len());
+        let start = match coins.len() {
+            0 => 0,
+            len => self.shadow_cursor % len,
+        };
+        self.shadow_cursor = start + n;
+        coins.into_iter().cycle().skip(start).take(n).cloned().collect()
+    }
+
+    pub fn shadow(&self) -> &ShadowComparison {
+        &self.shadow
+    }
+
     pub fn get_current_vpin(&self) -> f64 {
         if self.vpin_buckets.is_empty() {
             return 0.0;
@@ -362,7 +473,7 @@ impl StreamingMetricsEngine {
             avg_order_lifetime_ms: avg_lifetime,
             layering_score: self.phantom_liquidity_tracker.layering_score,
             spoofing_events: self.phantom_liquidity_tracker.spoofing_events,
-            cancellation_rate: cancellation_rate,
+            cancellation_rate,
         }
     }
 
@@ -376,17 +487,63 @@ impl StreamingMetricsEngine {
             adverse_selection_cost: 0.0,
             daily_pnl: Decimal::ZERO,
             unrealized_pnl:  Decimal::ZERO,
+            fees: FeeMetrics::default(),
+            components: Vec::new(),
         }
     }
 
+    /// The latest `count` buffered trades, newest first.
+    pub fn recent_trades(&self, count: usize) -> Vec<TapeTrade> {
+        let skip = self.trade_buffer.len().saturating_sub(count);
+        let mut trades: Vec<TapeTrade> = self
+            .trade_buffer
+            .iter()
+            .skip(skip)
+            .map(TapeTrade::from)
+            .collect();
+        trades.reverse();
+        trades
+    }
+
+    /// The address on the most buffered volume, on either side of a trade,
+    /// and its share of the volume of the trades naming their traders.
+    pub fn top_trader(&self) -> Option<(Counterparty, f64)> {
+        let mut volume_by_user: HashMap<&str, Decimal> = HashMap::new();
+        let mut total = Decimal::ZERO;
+        for fill in self.trade_buffer.iter().filter(|fill| !fill.users.is_empty()) {
+            let volume = fill.px * fill.sz.abs();
+            total += volume;
+            for user in &fill.users {
+                *volume_by_user.entry(user.as_str()).or_insert(Decimal::ZERO) += volume;
+            }
+        }
+        let (user, volume) = volume_by_user.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))?;
+        Some((Counterparty::new(user), (volume / total).to_f64().unwrap_or(0.0)))
+    }
+
     pub fn get_volume_metrics(&self) -> (Decimal, HashMap<String, Decimal>) {
         (self.total_volume_traded, self.volume_by_coin.clone())
     }
 
+    /// Trades streamed so far by asset.
+    pub fn trade_counts(&self) -> HashMap<String, u64> {
+        self.trades_by_coin.clone()
+    }
+
     pub fn get_depth_realisation_ratio(&self) -> f64 {
         self.calculate_depth_realisation_ratio()
     }
 
+    /// Books the WebSocket updated within `LIVE_BOOK_MAX_AGE`, which need no
+    /// HTTP poll. Quiet books stop counting as live and are polled again.
+    pub fn live_order_books(&self) -> HashMap<String, L2Snapshot> {
+        self.l2_snapshots
+            .iter()
+            .filter(|(coin, _)| self.l2_received.get(*coin).is_some_and(|at| at.elapsed() <= LIVE_BOOK_MAX_AGE))
+            .map(|(coin, book)| (coin.clone(), book.clone()))
+            .collect()
+    }
+
     pub fn get_real_time_spreads(&self) -> HashMap<String, f64> {
         let mut spreads = HashMap::new();
         
@@ -406,3 +563,30 @@ impl StreamingMetricsEngine {
     }
 }
 
+impl WhaleTracker {
+    /// Compares the trade with its asset's average before folding it in.
+    fn process(&mut self, fill: &Fill) {
+        let notional = (fill.px * fill.sz.abs()).to_f64().unwrap_or(0.0);
+        let window = self.settings.average_trades.max(1);
+        let recent = self.recent_notional.entry(fill.coin.clone()).or_insert_with(|| RingSeries::new(window));
+        let average = match recent.len() {
+            0 => 0.0,
+            trades => recent.iter().sum::<f64>() / trades as f64,
+        };
+        let multiple = if average > 0.0 { notional / average } else { 0.0 };
+        let large = recent.len() >= window && multiple > self.settings.multiple;
+        recent.push(notional);
+
+        // Trades may arrive slightly out of order; an earlier one decays nothing.
+        let elapsed_ms = self.last_trade_ms.map_or(0, |last| fill.time.saturating_sub(last));
+        self.last_trade_ms = Some(self.last_trade_ms.map_or(fill.time, |last| last.max(fill.time)));
+        let decay = (-(elapsed_ms as f64) / WHALE_SCORE_DECAY_MS).exp();
+        self.volume = self.volume * decay + notional;
+        self.whale_volume *= decay;
+        if large {
+            self.whale_volume += notional;
+            debug!("🐋 Large print: {} {} @ {} ({:.1}x average)", fill.coin, fill.sz, fill.px, multiple);
+            self.large_prints.push(LargePrint { trade: TapeTrade::from(fill), notional: fill.px * fill.sz.abs(), multiple });
+        }
+    }
+}
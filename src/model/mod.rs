@@ -2,6 +2,7 @@ pub mod vault;
 
 
 use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,15 +10,63 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultSummary {
     pub vault_address: String,
+    /// The vault's name from `vaultDetails`; none when summarized from an account.
+    #[serde(default)]
+    pub name: Option<String>,
     pub tvl: Decimal,
     pub equity: Decimal,
     pub apr: f64,
     pub all_time_pnl: Decimal,
     pub max_drawdown: f64,
+    /// What one share of the vault is worth in USD, counting shares issued
+    /// at $1 when its history starts; 0 when summarized from an account.
+    #[serde(default)]
+    pub share_price: f64,
     pub num_depositors: u64,
     pub portfolio_value: Decimal,
     pub deployed_liquidity: Decimal,
     pub idle_liquidity: Decimal,
+    /// The last day of the vault's account value history; empty when
+    /// summarized from an account.
+    #[serde(default)]
+    pub flows: Vec<VaultFlow>,
+    /// Each vault summed into this one, for the Vaults panel; one entry for a
+    /// single vault, none for an account.
+    #[serde(default)]
+    pub vaults: Vec<VaultBreakdown>,
+    /// Whether its fills are split into components, for the vaults of
+    /// `component_vaults`.
+    #[serde(default)]
+    pub components: bool,
+}
+
+/// One vault's figures, from its `vaultDetails` and its own accounts' state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultBreakdown {
+    pub name: String,
+    pub address: String,
+    pub tvl: Decimal,
+    pub equity: Decimal,
+    pub apr: f64,
+    pub max_drawdown: f64,
+    /// Total value of its positions, long and short alike.
+    pub exposure: Decimal,
+    pub margin_used: Decimal,
+    pub positions: usize,
+    pub largest_position: Option<String>,
+    pub pnl_24h: Decimal,
+    pub net_flow_24h: Decimal,
+}
+
+/// A point of a vault's account value history, with the change since the
+/// previous point split into what the vault made and what depositors moved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultFlow {
+    pub time: u64,
+    pub account_value: Decimal,
+    pub pnl: Decimal,
+    /// Deposits less withdrawals.
+    pub net_flow: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,8 +76,79 @@ pub struct UserState {
     pub total_ntl_pos: Decimal,
     pub total_raw_usd: Decimal,
     pub positions: Vec<Position>,
+    /// Tokens held in the spot balance, which the margin figures above leave out.
+    #[serde(default)]
+    pub spot_balances: Vec<SpotBalance>,
+    /// The master and sub-accounts behind these figures when sub-accounts are
+    /// monitored; empty otherwise.
+    #[serde(default)]
+    pub accounts: Vec<AccountBreakdown>,
+}
+
+impl UserState {
+    /// Perp account value and spot holdings together.
+    pub fn total_value(&self) -> Decimal {
+        self.account_value + self.spot_balances.iter().map(|balance| balance.value).sum::<Decimal>()
+    }
+
+    /// Value held in spot tokens other than USDC, which moves with their prices.
+    pub fn spot_exposure(&self) -> Decimal {
+        self.spot_balances.iter().filter(|balance| !balance.is_quote()).map(|balance| balance.value).sum()
+    }
 }
 
+/// One account's figures within a combined state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBreakdown {
+    pub name: String,
+    pub address: String,
+    pub equity: Decimal,
+    /// Total value of its positions, long and short alike.
+    pub exposure: Decimal,
+    pub margin_used: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub positions: usize,
+    pub largest_position: Option<String>,
+}
+
+impl AccountBreakdown {
+    pub fn of(name: &str, address: &str, state: &UserState) -> Self {
+        Self {
+            name: name.to_string(),
+            address: address.to_string(),
+            equity: state.total_value(),
+            exposure: state.positions.iter().map(|position| position.position_value).sum::<Decimal>() + state.spot_exposure(),
+            margin_used: state.total_margin_used,
+            unrealized_pnl: state.positions.iter().map(|position| position.unrealized_pnl).sum(),
+            positions: state.positions.len(),
+            largest_position: state.positions
+                .iter()
+                .max_by_key(|position| position.position_value)
+                .map(|position| position.symbol.clone()),
+        }
+    }
+}
+
+/// A token of an account's spot balance, marked at its spot mid against USDC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotBalance {
+    pub coin: String,
+    pub total: Decimal,
+    /// Reserved by open orders.
+    pub hold: Decimal,
+    /// In USDC; zero for tokens without a USDC market.
+    pub value: Decimal,
+}
+
+impl SpotBalance {
+    /// USDC, which the other tokens are marked in.
+    pub fn is_quote(&self) -> bool {
+        self.coin == SPOT_QUOTE
+    }
+}
+
+pub const SPOT_QUOTE: &str = "USDC";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub symbol: String,
@@ -37,11 +157,84 @@ pub struct Position {
     pub position_value: Decimal,
     pub unrealized_pnl: Decimal,
     pub margin_used: Decimal,
+    /// Margined on its own rather than from the account's shared balance, so
+    /// it can be liquidated while the rest of the account is healthy.
+    #[serde(default)]
+    pub isolated: bool,
+    #[serde(default)]
+    pub leverage: Option<u32>,
+    #[serde(default)]
+    pub liquidation_px: Option<Decimal>,
+}
+
+impl Position {
+    /// "Cross" or "Isolated", with the leverage when known, as in `Isolated 5x`.
+    pub fn margin_mode(&self) -> String {
+        let mode = if self.isolated { "Isolated" } else { "Cross" };
+        match self.leverage {
+            Some(leverage) => format!("{} {}x", mode, leverage),
+            None => mode.to_string(),
+        }
+    }
+
+    /// How far the mark price is from the liquidation price, as a fraction of the mark.
+    pub fn liquidation_distance(&self) -> Option<f64> {
+        let liquidation_px = self.liquidation_px?;
+        if self.size.is_zero() || self.position_value.is_zero() {
+            return None;
+        }
+        let mark = self.position_value / self.size.abs();
+        ((mark - liquidation_px).abs() / mark).to_f64()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Meta {
     pub universe: Vec<AssetInfo>,
+    /// Prices of each asset by name, from `metaAndAssetCtxs`.
+    #[serde(default)]
+    pub contexts: HashMap<String, AssetContext>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetContext {
+    pub mark_px: Decimal,
+    pub oracle_px: Decimal,
+    /// In units of the asset.
+    #[serde(default)]
+    pub open_interest: Decimal,
+    /// Notional cap in USD, from `open_interest.caps_usd`; the exchange doesn't publish it.
+    #[serde(default)]
+    pub open_interest_cap: Option<Decimal>,
+    /// Listed by `perpsAtOpenInterestCap`: no position in the asset can grow.
+    #[serde(default)]
+    pub at_open_interest_cap: bool,
+}
+
+impl AssetContext {
+    /// How far the mark price is from the oracle price, as a fraction of the oracle.
+    pub fn oracle_divergence(&self) -> Option<f64> {
+        if self.oracle_px <= Decimal::ZERO {
+            return None;
+        }
+        ((self.mark_px - self.oracle_px).abs() / self.oracle_px).to_f64()
+    }
+
+    pub fn open_interest_usd(&self) -> Decimal {
+        self.open_interest * self.mark_px
+    }
+
+    /// Open interest as a fraction of its cap: at least 1 once the exchange
+    /// reports the asset at its cap, none when the cap is unknown.
+    pub fn open_interest_cap_usage(&self) -> Option<f64> {
+        let usage = self.open_interest_cap
+            .filter(|cap| *cap > Decimal::ZERO)
+            .and_then(|cap| (self.open_interest_usd() / cap).to_f64());
+        match self.at_open_interest_cap {
+            true => Some(usage.unwrap_or(1.0).max(1.0)),
+            false => usage,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +243,15 @@ pub struct AssetInfo {
     pub sz_decimals: u8,
     pub max_leverage: u32,
     pub only_isolated: bool,
+    /// Still listed in `meta` but no longer tradeable.
+    #[serde(default)]
+    pub is_delisted: bool,
+}
+
+/// The builder-deployed perp DEX `coin` trades on, from its `dex:COIN` name;
+/// none for the main DEX's assets and spot pairs.
+pub fn dex_of(coin: &str) -> Option<&str> {
+    coin.split_once(':').map(|(dex, _)| dex)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,7 +267,48 @@ pub struct Fill {
     pub hash: String,
     pub oid: u64,
     pub crossed: bool,
+    /// Includes `builder_fee`, both in USD.
     pub fee: Decimal,
+    #[serde(default)]
+    pub builder_fee: Decimal,
+    /// What the account's fee schedule charges for the fill, once known.
+    #[serde(default)]
+    pub expected_fee: Option<Decimal>,
+    #[serde(default)]
+    pub liquidation: Option<FillLiquidation>,
+    /// Buyer then seller of a streamed market trade; the account's own fills
+    /// don't name their counterparty.
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+impl Fill {
+    /// Whether the account took over a liquidated position with this fill, as
+    /// HLP's liquidator does, rather than being liquidated itself.
+    pub fn is_liquidation_takeover(&self) -> bool {
+        self.liquidation.is_some() && !self.dir.starts_with("Liquidated")
+    }
+}
+
+/// The liquidation a fill was part of.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillLiquidation {
+    pub liquidated_user: Option<String>,
+    /// `market` when the book absorbed it, `backstop` when a vault took it over.
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub coin: String,
+    pub open_time: u64,
+    pub close_time: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub trades: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,26 +326,287 @@ pub struct OrderBookLevel {
     pub n: u32,
 }
 
-#[derive(Debug, Clone, Default)]
+/// A resting order of the monitored account, from the `openOrders` info request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub coin: String,
+    /// "B" for bids, "A" for asks, as in fills.
+    pub side: String,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
+    pub oid: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GlobalMetrics {
     pub vault_metrics: VaultMetrics,
     pub performance_metrics: PerformanceMetrics,
     pub liquidity_metrics: LiquidityMetrics,
     pub risk_metrics: RiskMetrics,
+    pub system_status: SystemStatus,
+    /// Latest book per asset: the polled snapshot, or the streamed one when newer.
+    pub order_books: HashMap<String, L2Snapshot>,
+    pub open_orders: Vec<OpenOrder>,
+    /// Open positions of the monitored account.
+    pub positions: Vec<Position>,
+    /// Latest streamed market trades, newest first.
+    pub trade_tape: Vec<TapeTrade>,
     pub last_update: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Default)]
+/// A market trade of the tape, with its buyer and seller when the stream names them.
+#[derive(Debug, Clone, Serialize)]
+pub struct TapeTrade {
+    pub coin: String,
+    pub side: String,
+    pub px: Decimal,
+    pub sz: Decimal,
+    pub time: u64,
+    pub buyer: Option<Counterparty>,
+    pub seller: Option<Counterparty>,
+}
+
+impl From<&Fill> for TapeTrade {
+    fn from(fill: &Fill) -> Self {
+        Self {
+            coin: fill.coin.clone(),
+            side: fill.side.clone(),
+            px: fill.px,
+            sz: fill.sz,
+            time: fill.time,
+            buyer: fill.users.first().map(|address| Counterparty::new(address)),
+            seller: fill.users.get(1).map(|address| Counterparty::new(address)),
+        }
+    }
+}
+
+/// A streamed trade far larger than its asset's recent average.
+#[derive(Debug, Clone, Serialize)]
+pub struct LargePrint {
+    #[serde(flatten)]
+    pub trade: TapeTrade,
+    pub notional: Decimal,
+    /// Of the asset's average trade notional before it.
+    pub multiple: f64,
+}
+
+/// An address, with its label from the address book when it has one.
+#[derive(Debug, Clone, Serialize)]
+pub struct Counterparty {
+    pub address: String,
+    pub label: Option<String>,
+}
+
+impl Counterparty {
+    pub fn new(address: &str) -> Self {
+        Self { address: address.to_string(), label: None }
+    }
+
+    /// The label, or the address shortened to `0x1234…abcd`.
+    pub fn display(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            // `get` rather than slicing: a streamed address need not be ASCII.
+            None => match (self.address.get(..6), self.address.get(self.address.len().saturating_sub(4)..)) {
+                (Some(head), Some(tail)) if self.address.len() > 10 => format!("{}…{}", head, tail),
+                _ => self.address.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    #[default]
+    Unknown,
+    Connected,
+    Disconnected,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemStatus {
+    pub http: LinkState,
+    pub websocket: LinkState,
+    pub ws_messages_per_sec: f64,
+    /// Arrival time of the latest WebSocket message, which feeds the streaming metrics.
+    pub ws_last_message: Option<DateTime<Utc>>,
+    pub api_latency_ms: Option<f64>,
+    pub active_alerts: usize,
+    /// End of the latest collection cycle, successful or not.
+    pub last_cycle: Option<DateTime<Utc>>,
+    /// Newest L1 block time stamped on an order book, and how far the clock
+    /// has moved past it: a halted chain keeps serving its last books.
+    pub block_time: Option<DateTime<Utc>>,
+    pub block_lag_secs: Option<f64>,
+    /// Queues between the WebSocket reader and the streaming consumers; any
+    /// `dropped` means streaming metrics were computed from incomplete data.
+    pub streams: Vec<StreamStats>,
+    /// In-memory history buffers, each capped (see `ring`).
+    pub buffers: Vec<BufferStats>,
+    /// Monitored assets with an order book so far, of `books_monitored`; books
+    /// of assets without positions fill in over the first cycles.
+    pub books_covered: usize,
+    pub books_monitored: usize,
+    /// Info requests by type, then all of them as `info`, then the handling of
+    /// WebSocket messages as `ws_message` (see `latency`).
+    pub latency: Vec<LatencyStats>,
+    /// The streamed metrics against the poll-based calculators', when streaming.
+    pub shadow: Option<ShadowComparison>,
+}
+
+impl SystemStatus {
+    /// The p95 of all info requests, or the latest request's round trip
+    /// before percentiles are kept.
+    pub fn info_latency_p95_ms(&self) -> Option<f64> {
+        match self.latency.iter().find(|stats| stats.endpoint == "info") {
+            Some(info) => Some(info.p95_ms),
+            None => self.api_latency_ms,
+        }
+    }
+}
+
+/// Latency percentiles of one endpoint over its latest samples (see `latency`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub endpoint: String,
+    /// Requests recorded since start-up.
+    pub count: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// One metric as the poll-based calculators and the streaming engine computed
+/// it in the same cycle.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ShadowReading {
+    pub polled: f64,
+    pub streamed: f64,
+}
+
+impl ShadowReading {
+    pub fn gap(&self) -> f64 {
+        (self.streamed - self.polled).abs()
+    }
+}
+
+/// How far one metric's two code paths have disagreed since start-up.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Divergence {
+    pub cycles: u64,
+    pub mean_gap: f64,
+    pub max_gap: f64,
+}
+
+impl Divergence {
+    pub fn record(&mut self, gap: f64) {
+        self.cycles += 1;
+        self.mean_gap += (gap - self.mean_gap) / self.cycles as f64;
+        self.max_gap = self.max_gap.max(gap);
+    }
+}
+
+/// The streaming engine run in shadow of the poll-based calculators: what
+/// each computed in the latest cycle, and how far they have drifted apart.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShadowComparison {
+    /// `None` until the stream has filled a VPIN bucket.
+    pub vpin: Option<ShadowReading>,
+    pub phantom_liquidity: ShadowReading,
+    /// In bps, of the assets both have a book for.
+    pub spreads_bps: HashMap<String, ShadowReading>,
+    pub vpin_divergence: Divergence,
+    pub phantom_liquidity_divergence: Divergence,
+    /// Of the mean spread gap across assets in each cycle, in bps.
+    pub spread_divergence: Divergence,
+}
+
+impl ShadowComparison {
+    /// The mean gap across `spreads_bps`, or `None` when it is empty.
+    pub fn mean_spread_gap(&self) -> Option<f64> {
+        match self.spreads_bps.len() {
+            0 => None,
+            n => Some(self.spreads_bps.values().map(ShadowReading::gap).sum::<f64>() / n as f64),
+        }
+    }
+
+    /// Records a cycle's readings.
+    pub fn record(&mut self, vpin: Option<ShadowReading>, phantom_liquidity: ShadowReading, spreads_bps: HashMap<String, ShadowReading>) {
+        self.vpin = vpin;
+        self.phantom_liquidity = phantom_liquidity;
+        self.spreads_bps = spreads_bps;
+        if let Some(vpin) = vpin {
+            self.vpin_divergence.record(vpin.gap());
+        }
+        self.phantom_liquidity_divergence.record(phantom_liquidity.gap());
+        if let Some(gap) = self.mean_spread_gap() {
+            self.spread_divergence.record(gap);
+        }
+    }
+}
+
+/// Occupancy of one bounded buffer (see `ring`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BufferStats {
+    pub name: String,
+    pub len: usize,
+    pub capacity: usize,
+    /// Oldest items dropped to make room, since start-up.
+    pub evicted: u64,
+    /// Storage allocated for the items, not counting what they point to.
+    pub bytes: usize,
+}
+
+/// Delivery counters of one WebSocket stream (see `pipeline`).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamStats {
+    pub name: String,
+    pub capacity: usize,
+    pub subscribers: usize,
+    /// Messages waiting in the fullest subscriber queue.
+    pub queued: usize,
+    pub sent: u64,
+    /// Oldest messages discarded from full queues under `overflow = "drop_oldest"`.
+    pub dropped: u64,
+    /// Sends that waited for a full queue under `overflow = "block"`.
+    pub blocked: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct VaultMetrics {
+    /// What the figures are of: the vault's name, or none for an account.
+    pub name: Option<String>,
+    pub address: String,
     pub tvl: Decimal,
     pub equity: Decimal,
     pub apr: f64,
+    /// See `VaultSummary::share_price`.
+    pub share_price: f64,
     pub utilization_rate: f64,
     pub deployed_liquidity: Decimal,
     pub idle_liquidity: Decimal,
+    /// Margin posted to isolated positions, which `utilization_rate` leaves out,
+    /// and its share of TVL.
+    pub isolated_margin: Decimal,
+    pub isolated_utilization: f64,
+    /// Spot holdings, which TVL and equity include.
+    pub spot_value: Decimal,
+    pub spot_balances: Vec<SpotBalance>,
+    /// PnL and net deposits over the vault's last day of history.
+    pub pnl_24h: Decimal,
+    pub net_flow_24h: Decimal,
+    pub flows: Vec<VaultFlow>,
+    /// The vaults behind these figures when several are monitored together.
+    pub vaults: Vec<VaultBreakdown>,
+    /// The master and sub-accounts behind these figures when sub-accounts are
+    /// monitored.
+    pub sub_accounts: Vec<AccountBreakdown>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PerformanceMetrics {
     pub daily_pnl: Decimal,
     pub unrealized_pnl: Decimal,
@@ -111,9 +615,57 @@ pub struct PerformanceMetrics {
     pub sortino_ratio: f64,
     pub realized_spread: HashMap<String, f64>,
     pub adverse_selection_cost: f64,
+    pub fees: FeeMetrics,
+    /// A vault's fills split into market making and liquidation takeovers;
+    /// empty for an account.
+    pub components: Vec<ComponentMetrics>,
+}
+
+/// One kind of a vault's flow over the recent fills.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComponentMetrics {
+    pub name: String,
+    pub fills: usize,
+    pub volume: Decimal,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    /// Bought less sold; what the component added to the vault's exposure.
+    pub net_notional: Decimal,
+    pub adverse_selection_cost: f64,
+    /// Its share of the vault's position value: the positions whose recent
+    /// fills were mostly its own.
+    pub exposure_share: f64,
+    /// How far the mark of the nearest of those positions to liquidation may
+    /// move before it is liquidated, as a share of the mark.
+    pub liquidation_distance: Option<f64>,
+}
+
+/// Fees paid on the recent fills against what the fee schedule charges.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeeMetrics {
+    pub fees_paid: Decimal,
+    pub builder_fees: Decimal,
+    /// Of the fills whose expected fee is known.
+    pub expected_fees: Decimal,
+    /// Fees paid beyond the schedule, in basis points of the notional of the
+    /// fills whose expected fee is known.
+    pub excess_bps: f64,
+    /// The latest fills charged other than the schedule says, newest first.
+    pub deviations: Vec<FeeDeviation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeDeviation {
+    pub coin: String,
+    pub time: u64,
+    pub hash: String,
+    pub notional: Decimal,
+    pub fee: Decimal,
+    pub expected_fee: Decimal,
+    pub builder_fee: Decimal,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct LiquidityMetrics {
     pub bid_ask_spread_bps: HashMap<String, f64>,
     pub depth_at_50bps: HashMap<String, Decimal>,
@@ -125,27 +677,100 @@ pub struct LiquidityMetrics {
     pub spoofing_detection_index: f64,
     pub liquidity_realization_rate: f64,
     pub fill_probability_by_distance: HashMap<String, f64>,
+    /// The address on the most streamed volume, buying or selling, and its
+    /// share of it.
+    pub top_trader: Option<Counterparty>,
+    pub top_trader_share: f64,
+    /// Streamed trades per minute of each asset over `illiquid.window_secs`.
+    pub trade_rate_per_min: HashMap<String, f64>,
+    /// Assets trading thinly and quoting wide against their norm, as in dead
+    /// hours, whose VPIN and phantom liquidity readings are less telling.
+    pub illiquid_assets: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct RiskMetrics {
     pub vpin_score: f64,
+    pub vpin_by_asset: HashMap<String, f64>,
     pub phantom_liquidity_index: f64,
     pub liquidation_risk_score: f64,
     pub cascade_risk_score: f64,
     pub position_concentration: HashMap<String, f64>,
+    /// Mark and oracle prices by asset, and `AssetContext::oracle_divergence`.
+    pub oracle_prices: HashMap<String, AssetContext>,
+    pub oracle_divergence: HashMap<String, f64>,
+    /// `AssetContext::open_interest_cap_usage` of the assets with a known cap.
+    pub open_interest_cap_usage: HashMap<String, f64>,
+    pub backstop: Backstop,
     pub max_drawdown: f64,
     pub cross_exchange_manipulation_score: f64,
+    /// Share of the recent streamed volume traded in large prints.
+    pub whale_activity: f64,
+    /// The latest large prints, newest first.
+    pub large_prints: Vec<LargePrint>,
+    /// Newly listed assets under tightened thresholds.
+    pub probation: Vec<ProbationAsset>,
 }
 
-#[derive(Clone)]
+/// A newly listed asset on probation until `until`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbationAsset {
+    pub coin: String,
+    pub listed_at: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+/// The exchange's backstop accounts, such as the assistance fund and HLP's
+/// liquidator, which absorb the liquidations the book can't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Backstop {
+    pub accounts: Vec<BackstopAccount>,
+    /// Total value at each fetch within `backstop.window_secs`, oldest first,
+    /// as (milliseconds, USD).
+    pub samples: Vec<(u64, Decimal)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackstopAccount {
+    pub name: String,
+    pub address: String,
+    pub perp_value: Decimal,
+    /// Spot balances at their USDC mids.
+    pub spot_value: Decimal,
+}
+
+impl BackstopAccount {
+    pub fn value(&self) -> Decimal {
+        self.perp_value + self.spot_value
+    }
+}
+
+impl Backstop {
+    pub fn total(&self) -> Decimal {
+        self.accounts.iter().map(BackstopAccount::value).sum()
+    }
+
+    /// Fall from the highest sample to the latest, as a fraction of the highest.
+    pub fn drawdown(&self) -> f64 {
+        let Some((_, latest)) = self.samples.last() else {
+            return 0.0;
+        };
+        let peak = self.samples.iter().map(|(_, total)| *total).max().unwrap_or_default();
+        if peak <= Decimal::ZERO {
+            return 0.0;
+        }
+        ((peak - latest) / peak).to_f64().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OrderAction {
     New,
     Filled,
     Cancelled,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct OrderEvent {
     pub id:        u64,
@@ -173,4 +798,6 @@ pub struct Alert {
     pub timestamp: DateTime<Utc>,
     pub value: f64,
     pub threshold: f64,
+    #[serde(default)]
+    pub acknowledged: bool,
 }
\ No newline at end of file
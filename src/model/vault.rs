@@ -2,6 +2,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct VaultDetails {
     pub vault_address: String,
     pub name: String,
@@ -16,6 +17,7 @@ pub struct VaultDetails {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub enum VaultStatus {
     Active,
     Paused,
@@ -23,6 +25,7 @@ pub enum VaultStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct VaultPerformance {
     pub daily_returns: Vec<f64>,
     pub weekly_returns: Vec<f64>,
@@ -35,6 +38,7 @@ pub struct VaultPerformance {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct VaultHoldings {
     pub cash: Decimal,
     pub positions: Vec<VaultPosition>,
@@ -44,6 +48,7 @@ pub struct VaultHoldings {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct VaultPosition {
     pub symbol: String,
     pub quantity: Decimal,
@@ -0,0 +1,254 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::MqttSettings;
+use crate::model::{Alert, AlertLevel};
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often the publisher looks for a new snapshot or new alerts.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Wait between reconnection attempts after the broker goes away.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// MQTT 3.1.1 control packet types (high nibble of the fixed header).
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PINGREQ: u8 = 0xC0;
+const DISCONNECT: u8 = 0xE0;
+
+/// Publishes each new snapshot as JSON to `metrics_topic` (retained, so a new
+/// subscriber gets the latest at once) and every new alert to
+/// `<alerts_topic>/<level>`, e.g. `hlp/alerts/critical` for a kill-switch. The
+/// retained `status_topic` reads `online`, and the broker sets it to `offline`
+/// through the last will when the connection drops. Messages are QoS 0; the
+/// connection is re-established after `RECONNECT_DELAY`, and alerts raised while
+/// disconnected are published on reconnect. Once `token` is cancelled it
+/// publishes `offline` itself and disconnects cleanly.
+pub async fn run(
+    settings: MqttSettings,
+    metrics: Arc<SharedMetrics>,
+    alerts: Arc<Versioned<Vec<Alert>>>,
+    token: CancellationToken,
+) -> Result<()> {
+    // Alerts raised before start-up are not published.
+    let mut published: HashSet<String> = alerts.read().await.iter().map(|a| a.id.clone()).collect();
+    let mut failing = false;
+
+    loop {
+        match session(&settings, &metrics, &alerts, &mut published, &mut failing, &token).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                if !failing {
+                    warn!("⚠️ MQTT connection to {} lost: {}; retrying every {}s", settings.broker, e, RECONNECT_DELAY.as_secs());
+                }
+                failing = true;
+            }
+        }
+        if token.run_until_cancelled(tokio::time::sleep(RECONNECT_DELAY)).await.is_none() {
+            return Ok(());
+        }
+    }
+}
+
+async fn session(
+    settings: &MqttSettings,
+    metrics: &Arc<SharedMetrics>,
+    alerts: &Arc<Versioned<Vec<Alert>>>,
+    published: &mut HashSet<String>,
+    failing: &mut bool,
+    token: &CancellationToken,
+) -> Result<()> {
+    let mut stream = connect(settings).await?;
+    if *failing {
+        info!("📡 MQTT connection to {} restored", settings.broker);
+    } else {
+        info!("📡 Publishing to MQTT broker {} ({}, {}/#)", settings.broker, settings.metrics_topic, settings.alerts_topic);
+    }
+    *failing = false;
+
+    if !settings.status_topic.is_empty() {
+        stream.write_all(&publish_packet(&settings.status_topic, b"online", true)).await?;
+    }
+
+    let keep_alive = Duration::from_secs(settings.keep_alive_secs);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_published = None;
+    let mut last_packet = Instant::now();
+    let mut incoming = [0u8; 256];
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = token.cancelled() => {
+                // The clean DISCONNECT drops the will, so `offline` is sent here.
+                if !settings.status_topic.is_empty() {
+                    stream.write_all(&publish_packet(&settings.status_topic, b"offline", true)).await?;
+                }
+                stream.write_all(&[DISCONNECT, 0]).await?;
+                stream.flush().await?;
+                info!("📡 Disconnected from MQTT broker {}", settings.broker);
+                return Ok(());
+            }
+            // Only PINGRESPs are expected; reading still notices a closed connection.
+            read = stream.read(&mut incoming) => {
+                if read? == 0 {
+                    return Err(anyhow!("broker closed the connection"));
+                }
+                continue;
+            }
+        }
+
+        let mut packets = Vec::new();
+        let mut new_alerts = Vec::new();
+        {
+            let last_update = metrics.last_update().await;
+            if last_update.is_some() && last_update != last_published {
+                last_published = last_update;
+                packets.push(publish_packet(&settings.metrics_topic, &serde_json::to_vec(&metrics.snapshot().await)?, settings.retain_metrics));
+            }
+        }
+        {
+            let alerts = alerts.read().await;
+            published.retain(|id| alerts.iter().any(|a| &a.id == id));
+            for alert in alerts.iter().filter(|a| !published.contains(&a.id)) {
+                let level = match alert.level {
+                    AlertLevel::Info => "info",
+                    AlertLevel::Warning => "warning",
+                    AlertLevel::Critical => "critical",
+                };
+                let topic = format!("{}/{}", settings.alerts_topic, level);
+                packets.push(publish_packet(&topic, &serde_json::to_vec(alert)?, false));
+                new_alerts.push(alert.id.clone());
+            }
+        }
+
+        if packets.is_empty() && last_packet.elapsed() >= keep_alive / 2 {
+            packets.push(vec![PINGREQ, 0]);
+        }
+        for packet in &packets {
+            stream.write_all(packet).await?;
+        }
+        if !packets.is_empty() {
+            last_packet = Instant::now();
+            // Only count alerts as published once they are written.
+            published.extend(new_alerts);
+            debug!("📡 Sent {} MQTT packets", packets.len());
+        }
+    }
+}
+
+/// Publishes one alert to `<alerts_topic>/info` and disconnects, for `check`.
+/// Connects as `<client_id>-check` so a running dashboard keeps its session.
+pub async fn send_test(settings: &MqttSettings, alert: &Alert) -> Result<()> {
+    let settings = MqttSettings { client_id: format!("{}-check", settings.client_id), ..settings.clone() };
+    let mut stream = connect(&settings).await?;
+    let topic = format!("{}/info", settings.alerts_topic);
+    stream.write_all(&publish_packet(&topic, &serde_json::to_vec(alert)?, false)).await?;
+    // A clean DISCONNECT, so the broker drops the will instead of publishing `offline`.
+    stream.write_all(&[DISCONNECT, 0]).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Opens the connection and waits for the broker to accept it.
+async fn connect(settings: &MqttSettings) -> Result<TcpStream> {
+    let mut stream = TcpStream::connect(&settings.broker).await?;
+    stream.write_all(&connect_packet(settings)).await?;
+
+    let mut connack = [0u8; 4];
+    tokio::time::timeout(Duration::from_secs(10), stream.read_exact(&mut connack))
+        .await
+        .map_err(|_| anyhow!("no CONNACK from broker"))??;
+    if connack[0] != CONNACK || connack[1] != 2 {
+        return Err(anyhow!("unexpected reply to CONNECT"));
+    }
+    if connack[3] != 0 {
+        return Err(anyhow!("broker refused connection: {}", connack_reason(connack[3])));
+    }
+    Ok(stream)
+}
+
+fn connect_packet(settings: &MqttSettings) -> Vec<u8> {
+    let will = !settings.status_topic.is_empty();
+    let mut flags = 0x02; // clean session
+    if will {
+        flags |= 0x04 | 0x20; // will flag, will retain, QoS 0
+    }
+    if settings.username.is_some() {
+        flags |= 0x80;
+    }
+    if settings.password.is_some() {
+        flags |= 0x40;
+    }
+
+    let mut body = Vec::new();
+    put_bytes(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&(settings.keep_alive_secs.min(u16::MAX as u64) as u16).to_be_bytes());
+    put_bytes(&mut body, settings.client_id.as_bytes());
+    if will {
+        put_bytes(&mut body, settings.status_topic.as_bytes());
+        put_bytes(&mut body, b"offline");
+    }
+    if let Some(username) = &settings.username {
+        put_bytes(&mut body, username.as_bytes());
+    }
+    if let Some(password) = &settings.password {
+        put_bytes(&mut body, password.as_bytes());
+    }
+    packet(CONNECT, body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + 2 + payload.len());
+    put_bytes(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    packet(PUBLISH | retain as u8, body)
+}
+
+/// Fixed header (type and flags, then the remaining length as a varint) and body.
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 5);
+    out.push(header);
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    out.extend(body);
+    out
+}
+
+/// A length-prefixed UTF-8 string or binary field.
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn connack_reason(code: u8) -> &'static str {
+    match code {
+        1 => "unacceptable protocol version",
+        2 => "client id rejected",
+        3 => "server unavailable",
+        4 => "bad username or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
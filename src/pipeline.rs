@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::Notify;
+
+use crate::config::{Overflow, StreamSettings};
+use crate::model::StreamStats;
+
+/// Why `Receiver::recv` returned no message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecvError {
+    /// The queue was full and this many of the oldest messages were dropped
+    /// since the last `recv`; the next call returns the oldest one left.
+    Lagged(u64),
+    /// Every sender is gone and the queue is drained.
+    Closed,
+}
+
+impl std::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecvError::Lagged(dropped) => write!(f, "lagged behind, {} messages dropped", dropped),
+            RecvError::Closed => write!(f, "stream closed"),
+        }
+    }
+}
+
+/// Creates a stream that delivers every message to each subscriber, through a
+/// queue per subscriber holding at most `settings.capacity` messages. When a
+/// queue is full, `settings.overflow` decides whether its oldest message is
+/// dropped or `send` waits for the subscriber to catch up.
+pub fn channel<T: Clone>(name: &'static str, settings: &StreamSettings) -> Sender<T> {
+    Sender {
+        shared: Arc::new(Shared {
+            name,
+            capacity: settings.capacity.max(1),
+            overflow: settings.overflow,
+            queues: Mutex::new(Vec::new()),
+            senders: AtomicUsize::new(1),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            blocked: AtomicU64::new(0),
+        }),
+    }
+}
+
+struct Shared<T> {
+    name: &'static str,
+    capacity: usize,
+    overflow: Overflow,
+    queues: Mutex<Vec<Weak<Queue<T>>>>,
+    senders: AtomicUsize,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    blocked: AtomicU64,
+}
+
+struct Queue<T> {
+    items: Mutex<VecDeque<T>>,
+    /// Dropped since the subscriber last called `recv`.
+    lagged: AtomicU64,
+    /// Set when every sender is gone.
+    closed: AtomicBool,
+    /// Set when the receiver is dropped, so a blocked sender stops waiting for it.
+    detached: AtomicBool,
+    ready: Notify,
+    space: Notify,
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    queue: Arc<Queue<T>>,
+    /// The oldest message left when a lag was reported, taken with the report
+    /// so a sender that keeps the queue full can't lag every call.
+    held: Option<T>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// A receiver of every message sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let queue = Arc::new(Queue {
+            items: Mutex::new(VecDeque::with_capacity(self.shared.capacity.min(1024))),
+            lagged: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            detached: AtomicBool::new(false),
+            ready: Notify::new(),
+            space: Notify::new(),
+        });
+        lock(&self.shared.queues).push(Arc::downgrade(&queue));
+        Receiver { queue, held: None }
+    }
+
+    /// Queues `value` for every subscriber and returns how many there were.
+    /// Only waits under `Overflow::Block`, and then only for full queues.
+    pub async fn send(&self, value: T) -> usize {
+        let queues: Vec<Arc<Queue<T>>> = {
+            let mut queues = lock(&self.shared.queues);
+            queues.retain(|queue| queue.strong_count() > 0);
+            queues.iter().filter_map(Weak::upgrade).collect()
+        };
+        self.shared.sent.fetch_add(1, Ordering::Relaxed);
+
+        for queue in &queues {
+            let mut waited = false;
+            loop {
+                let space = queue.space.notified();
+                {
+                    let mut items = lock(&queue.items);
+                    if queue.detached.load(Ordering::Acquire) {
+                        break;
+                    }
+                    if items.len() < self.shared.capacity || self.shared.overflow == Overflow::DropOldest {
+                        if items.len() >= self.shared.capacity {
+                            items.pop_front();
+                            queue.lagged.fetch_add(1, Ordering::Relaxed);
+                            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        items.push_back(value.clone());
+                        queue.ready.notify_one();
+                        break;
+                    }
+                }
+                if !waited {
+                    waited = true;
+                    self.shared.blocked.fetch_add(1, Ordering::Relaxed);
+                }
+                space.await;
+            }
+        }
+        queues.len()
+    }
+
+    pub fn stats(&self) -> StreamStats {
+        let queues: Vec<Arc<Queue<T>>> = lock(&self.shared.queues).iter().filter_map(Weak::upgrade).collect();
+        StreamStats {
+            name: self.shared.name.to_string(),
+            capacity: self.shared.capacity,
+            subscribers: queues.len(),
+            queued: queues.iter().map(|queue| lock(&queue.items).len()).max().unwrap_or(0),
+            sent: self.shared.sent.load(Ordering::Relaxed),
+            dropped: self.shared.dropped.load(Ordering::Relaxed),
+            blocked: self.shared.blocked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            for queue in lock(&self.shared.queues).iter().filter_map(Weak::upgrade) {
+                queue.closed.store(true, Ordering::Release);
+                queue.ready.notify_one();
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// The next message, oldest first. Cancel-safe, so it can sit in `select!`.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        if let Some(value) = self.held.take() {
+            return Ok(value);
+        }
+        loop {
+            let ready = self.queue.ready.notified();
+            {
+                let mut items = lock(&self.queue.items);
+                let lagged = self.queue.lagged.swap(0, Ordering::Relaxed);
+                if lagged > 0 {
+                    self.held = items.pop_front();
+                    self.queue.space.notify_one();
+                    return Err(RecvError::Lagged(lagged));
+                }
+                if let Some(value) = items.pop_front() {
+                    self.queue.space.notify_one();
+                    return Ok(value);
+                }
+                if self.queue.closed.load(Ordering::Acquire) {
+                    return Err(RecvError::Closed);
+                }
+            }
+            ready.await;
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.queue.detached.store(true, Ordering::Release);
+        self.queue.space.notify_one();
+    }
+}
+
+/// Queue contents are plain values, so a panicked holder leaves nothing inconsistent.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
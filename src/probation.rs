@@ -0,0 +1,37 @@
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+
+use crate::config::ProbationSettings;
+use crate::model::{GlobalMetrics, ProbationAsset};
+
+/// Newly listed assets, watched under tightened thresholds for their first
+/// `hours` of trading, when manipulation risk is highest.
+#[derive(Debug, Default)]
+pub struct Probation {
+    assets: Vec<(String, DateTime<Utc>)>,
+}
+
+impl Probation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts the assets of `listings` still within their first `hours` in
+    /// `metrics`, logging those that came on or finished their probation.
+    pub fn update(&mut self, metrics: &mut GlobalMetrics, listings: &[(String, DateTime<Utc>)], settings: &ProbationSettings, now: DateTime<Utc>) {
+        let length = Duration::hours(settings.hours as i64);
+        let assets: Vec<(String, DateTime<Utc>)> = listings.iter().filter(|(_, listed_at)| now < *listed_at + length).cloned().collect();
+        for (coin, _) in assets.iter().filter(|(coin, _)| !self.assets.iter().any(|(on, _)| on == coin)) {
+            info!("🆕 {} is on probation", coin);
+        }
+        for (coin, _) in self.assets.iter().filter(|(coin, _)| !assets.iter().any(|(on, _)| on == coin)) {
+            info!("🆕 {} finished its probation", coin);
+        }
+        self.assets = assets;
+        metrics.risk_metrics.probation = self
+            .assets
+            .iter()
+            .map(|(coin, listed_at)| ProbationAsset { coin: coin.clone(), listed_at: *listed_at, until: *listed_at + length })
+            .collect();
+    }
+}
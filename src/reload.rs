@@ -0,0 +1,136 @@
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+
+use crate::alert;
+use crate::config::{self, Config};
+use crate::model::Alert;
+use crate::versioned::Versioned;
+
+/// Top-level keys applied while running; every other section is read once at
+/// start-up. `include` matters only through the settings it pulls in.
+const RELOADABLE: [&str; 4] = ["alert_thresholds", "ui_settings", "monitored_assets", "include"];
+
+/// Polls the modification time of the config file `live` was loaded from, and of
+/// the files it includes, and
+/// swaps a config with the edited alert thresholds, UI settings and monitored
+/// assets into `live`, whose `Arc` the collection loop and dashboard take every cycle. Each
+/// applied reload raises an Info alert listing what changed. An edit that fails
+/// to parse or validate is logged and the running settings are kept.
+pub async fn watch(live: Arc<RwLock<Arc<Config>>>, alerts: Arc<Versioned<Vec<Alert>>>) {
+    let (path, poll) = {
+        let config = live.read().await;
+        (config.path.clone(), Duration::from_millis(config.reload.poll_interval_ms))
+    };
+    let mut on_disk = match config::load_config(Some(&path)) {
+        Ok(config) => config,
+        Err(_) => Config::clone(&**live.read().await),
+    };
+    let mut modified = modified_at(&on_disk);
+    info!("🔄 Watching {} for changes", path);
+
+    let mut interval = tokio::time::interval(poll);
+    loop {
+        interval.tick().await;
+        if modified_at(&on_disk) == modified {
+            continue;
+        }
+
+        let reloaded = match config::load_config(Some(&path)) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("⚠️ Ignoring edit to {}: {}", path, e);
+                modified = modified_at(&on_disk);
+                continue;
+            }
+        };
+        modified = modified_at(&reloaded);
+        let restart = restart_needed(&on_disk, &reloaded);
+        if !restart.is_empty() {
+            warn!("⚠️ Changes to {} in {} take effect after a restart", restart.join(", "), path);
+        }
+        let changes = describe_changes(&on_disk, &reloaded);
+        if !changes.is_empty() {
+            let mut live = live.write().await;
+            *live = Arc::new(apply(&live, &on_disk, &reloaded));
+            drop(live);
+            let summary = changes.join("; ");
+            info!("🔄 Reloaded {}: {}", path, summary);
+            alerts.write().await.push(alert::notice("Config", format!("Reloaded {}: {}", path, summary)));
+        }
+        on_disk = reloaded;
+    }
+}
+
+fn modified_at(config: &Config) -> Vec<Option<SystemTime>> {
+    std::iter::once(&config.path)
+        .chain(&config.included)
+        .map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+/// The running settings with the reloadable sections of `new` swapped in, for
+/// readers to pick up in place of `live`.
+fn apply(live: &Config, old: &Config, new: &Config) -> Config {
+    let mut next = live.clone();
+    next.alert_thresholds = new.alert_thresholds.clone();
+    // On while the file says off means `--ascii` was given; keep honouring it.
+    let forced_ascii = live.ui_settings.ascii_mode && !old.ui_settings.ascii_mode;
+    next.ui_settings = new.ui_settings.clone();
+    next.ui_settings.ascii_mode |= forced_ascii;
+    // Assets the file never listed were added over the control socket and stay
+    // monitored, unless the file now asks for every asset.
+    next.monitored_assets = new.monitored_assets.clone();
+    if !new.monitored_assets.is_empty() {
+        let added = live.monitored_assets.iter().filter(|coin| !old.monitored_assets.contains(coin) && !new.monitored_assets.contains(coin));
+        next.monitored_assets.extend(added.cloned());
+    }
+    next
+}
+
+/// One entry per changed threshold pair, UI setting and the asset list, e.g.
+/// `vpin 0.3/0.5 → 0.4/0.6`.
+fn describe_changes(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+    for ((name, old_warning, old_critical), (_, new_warning, new_critical)) in
+        old.alert_thresholds.pairs().into_iter().zip(new.alert_thresholds.pairs())
+    {
+        if (old_warning, old_critical) != (new_warning, new_critical) {
+            changes.push(format!("{} {}/{} → {}/{}", name, old_warning, old_critical, new_warning, new_critical));
+        }
+    }
+
+    if let (Ok(serde_json::Value::Object(old_ui)), Ok(serde_json::Value::Object(new_ui))) =
+        (serde_json::to_value(&old.ui_settings), serde_json::to_value(&new.ui_settings))
+    {
+        for (key, new_value) in &new_ui {
+            match old_ui.get(key) {
+                Some(old_value) if old_value == new_value => {}
+                old_value => changes.push(format!("{} {} → {}", key, old_value.unwrap_or(&serde_json::Value::Null), new_value)),
+            }
+        }
+    }
+
+    if old.monitored_assets != new.monitored_assets {
+        if new.monitored_assets.is_empty() {
+            changes.push("monitoring every asset".to_string());
+        } else {
+            let added = new.monitored_assets.iter().filter(|coin| !old.monitored_assets.contains(coin)).map(|coin| format!("+{}", coin));
+            let removed = old.monitored_assets.iter().filter(|coin| !new.monitored_assets.contains(coin)).map(|coin| format!("-{}", coin));
+            changes.push(format!("assets {}", added.chain(removed).collect::<Vec<_>>().join(" ")));
+        }
+    }
+    changes
+}
+
+/// Changed top-level settings and sections that are only read at start-up.
+fn restart_needed(old: &Config, new: &Config) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(key, value)| !RELOADABLE.contains(&key.as_str()) && old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
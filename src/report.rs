@@ -0,0 +1,212 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::alert::check_alerts;
+use crate::config::AlertThresholds;
+use crate::history::HistoryMetric;
+use crate::model::{Alert, AlertLevel, GlobalMetrics, Position};
+use crate::session::{format_signed_usd, format_value};
+
+/// How many readings the "Top risks" section lists.
+const TOP_RISKS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSource {
+    /// One collection cycle run for the report.
+    Live,
+    /// The latest snapshot in the `[storage]` database.
+    Store,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Headline {
+    pub metric: &'static str,
+    pub value: f64,
+}
+
+/// A thresholded metric and how close it is to its critical cut-off.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskReading {
+    pub metric: String,
+    pub value: f64,
+    pub warning: f64,
+    pub critical: f64,
+    pub level: Option<AlertLevel>,
+}
+
+impl RiskReading {
+    fn new(metric: impl Into<String>, value: f64, (warning, critical): (f64, f64)) -> Self {
+        let level = if value > critical {
+            Some(AlertLevel::Critical)
+        } else if value > warning {
+            Some(AlertLevel::Warning)
+        } else {
+            None
+        };
+        Self { metric: metric.into(), value, warning, critical, level }
+    }
+
+    fn severity(&self) -> f64 {
+        if self.critical > 0.0 { self.value / self.critical } else { 0.0 }
+    }
+}
+
+/// One-shot risk report: headline metrics, the readings closest to their critical
+/// thresholds, the alerts they raise and open positions.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub generated: DateTime<Utc>,
+    pub source: ReportSource,
+    pub as_of: Option<DateTime<Utc>>,
+    pub headline: Vec<Headline>,
+    pub top_risks: Vec<RiskReading>,
+    pub alerts: Vec<Alert>,
+    /// `None` when the source does not record positions.
+    pub positions: Option<Vec<Position>>,
+}
+
+impl Report {
+    pub fn build(metrics: &GlobalMetrics, thresholds: &AlertThresholds, source: ReportSource) -> Self {
+        let headline = HistoryMetric::ALL
+            .iter()
+            .map(|metric| Headline { metric: metric.label(), value: metric.extract(metrics) })
+            .collect();
+
+        let mut top_risks = risk_readings(metrics, thresholds);
+        top_risks.sort_by(|a, b| b.severity().partial_cmp(&a.severity()).unwrap_or(std::cmp::Ordering::Equal));
+        top_risks.truncate(TOP_RISKS);
+
+        let mut alerts = check_alerts(metrics, thresholds);
+        alerts.sort_by_key(|a| match a.level {
+            AlertLevel::Critical => 0,
+            AlertLevel::Warning => 1,
+            AlertLevel::Info => 2,
+        });
+
+        Self {
+            generated: Utc::now(),
+            source,
+            as_of: metrics.last_update,
+            headline,
+            top_risks,
+            alerts,
+            positions: matches!(source, ReportSource::Live).then(|| metrics.positions.clone()),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let source = match self.source {
+            ReportSource::Live => "a live collection cycle",
+            ReportSource::Store => "the metric history store",
+        };
+        let _ = writeln!(out, "# HLP Risk Report\n");
+        let _ = write!(out, "Generated {} from {}", self.generated.format("%Y-%m-%d %H:%M:%S UTC"), source);
+        match self.as_of {
+            Some(as_of) if matches!(self.source, ReportSource::Store) => {
+                let _ = writeln!(out, " (snapshot of {}).\n", as_of.format("%Y-%m-%d %H:%M:%S UTC"));
+            }
+            _ => out.push_str(".\n\n"),
+        }
+
+        let _ = writeln!(out, "## Headline metrics\n\n| Metric | Value |\n|---|---:|");
+        for (headline, metric) in self.headline.iter().zip(HistoryMetric::ALL) {
+            let _ = writeln!(out, "| {} | {} |", headline.metric, format_value(metric, headline.value));
+        }
+
+        let _ = writeln!(out, "\n## Top risks\n\n| Metric | Value | Warning | Critical | Status |\n|---|---:|---:|---:|---|");
+        for risk in &self.top_risks {
+            let status = match risk.level {
+                Some(AlertLevel::Critical) => "🔴 critical",
+                Some(AlertLevel::Warning) => "🟡 warning",
+                _ => "🟢 ok",
+            };
+            let _ = writeln!(out, "| {} | {:.3} | {:.3} | {:.3} | {} |", risk.metric, risk.value, risk.warning, risk.critical, status);
+        }
+
+        let _ = writeln!(out, "\n## Open alerts\n");
+        if self.alerts.is_empty() {
+            out.push_str("None.\n");
+        }
+        for alert in &self.alerts {
+            let _ = writeln!(out, "- **{:?}** {}: {}", alert.level, alert.metric, alert.message);
+        }
+
+        let _ = writeln!(out, "\n## Positions\n");
+        match &self.positions {
+            None => out.push_str("Not recorded in the history store.\n"),
+            Some(positions) if positions.is_empty() => out.push_str("None.\n"),
+            Some(positions) => {
+                let _ = writeln!(out, "| Coin | Mode | Size | Entry | Value | Unrealized PnL | Margin | Liq. Px |\n|---|---|---:|---:|---:|---:|---:|---:|");
+                let usd = |value: Decimal| format_signed_usd(value.to_f64().unwrap_or(0.0));
+                for p in positions {
+                    let entry = p.entry_px.map_or_else(|| "-".to_string(), |px| px.to_string());
+                    let liquidation = p.liquidation_px.map_or_else(|| "-".to_string(), |px| px.to_string());
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} | {} | {} | {} | {} | {} |",
+                        p.symbol,
+                        p.margin_mode(),
+                        p.size,
+                        entry,
+                        usd(p.position_value),
+                        usd(p.unrealized_pnl),
+                        usd(p.margin_used),
+                        liquidation
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Every metric `check_alerts` thresholds, with per-asset maps reduced to their
+/// worst asset.
+fn risk_readings(metrics: &GlobalMetrics, t: &AlertThresholds) -> Vec<RiskReading> {
+    let risk = &metrics.risk_metrics;
+    let liquidity = &metrics.liquidity_metrics;
+    let worst = |map: &std::collections::HashMap<String, f64>, name: &str| {
+        map.iter()
+            .map(|(coin, v)| (coin, v.abs()))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(coin, v)| (format!("{} ({})", name, coin), v))
+    };
+
+    let mut readings = vec![
+        RiskReading::new("VPIN", risk.vpin_score, (t.vpin_warning, t.vpin_critical)),
+        RiskReading::new("Phantom Liquidity", risk.phantom_liquidity_index, (t.phantom_liquidity_warning, t.phantom_liquidity_critical)),
+        RiskReading::new("Liquidation Risk", risk.liquidation_risk_score, (t.liquidation_risk_warning, t.liquidation_risk_critical)),
+        RiskReading::new("Max Drawdown", risk.max_drawdown, (t.max_drawdown_warning, t.max_drawdown_critical)),
+        RiskReading::new("Utilization", metrics.vault_metrics.utilization_rate, (t.utilization_warning, t.utilization_critical)),
+        RiskReading::new("Cancel Rate", liquidity.cancel_rate, (t.cancel_rate_warning, t.cancel_rate_critical)),
+        RiskReading::new("Fleeting Orders", liquidity.fleeting_order_ratio, (t.fleeting_ratio_warning, t.fleeting_ratio_critical)),
+    ];
+    if let Some((metric, value)) = worst(&risk.position_concentration, "Position Concentration") {
+        readings.push(RiskReading::new(metric, value, (t.concentration_warning, t.concentration_critical)));
+    }
+    if let Some((metric, value)) = worst(&liquidity.bid_ask_spread_bps, "Spread") {
+        readings.push(RiskReading::new(metric, value, (t.spread_warning_bps, t.spread_critical_bps)));
+    }
+    if let Some((metric, value)) = worst(&liquidity.order_book_imbalance, "Order Book Imbalance") {
+        readings.push(RiskReading::new(metric, value, (t.imbalance_warning, t.imbalance_critical)));
+    }
+    readings
+}
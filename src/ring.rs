@@ -0,0 +1,108 @@
+use serde::{Serialize, Serializer};
+use std::collections::VecDeque;
+
+use crate::model::BufferStats;
+
+/// Oldest-first series that never holds more than `capacity` items: pushing onto
+/// a full series evicts the oldest. Storage grows with use up to `capacity` and
+/// no further, so a buffer's memory is bounded however long the session runs.
+#[derive(Debug, Clone)]
+pub struct RingSeries<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    evicted: u64,
+}
+
+impl<T> RingSeries<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self { items: VecDeque::new(), capacity: capacity.max(1), evicted: 0 }
+    }
+
+    /// As many items as fit in `bytes`, counting each at its inline size.
+    pub fn with_budget(bytes: usize) -> Self {
+        Self::new(bytes / std::mem::size_of::<T>().max(1))
+    }
+
+    /// Appends `item`, returning the oldest item if it had to make room.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.items.len() >= self.capacity {
+            self.evicted += 1;
+            self.items.pop_front()
+        } else {
+            self.reserve_one();
+            None
+        };
+        self.items.push_back(item);
+        evicted
+    }
+
+    /// Prepends `item` as the new oldest, unless the series is full.
+    pub fn push_front(&mut self, item: T) -> bool {
+        if self.items.len() >= self.capacity {
+            return false;
+        }
+        self.reserve_one();
+        self.items.push_front(item);
+        true
+    }
+
+    /// Grows storage by doubling like `VecDeque`, but never past `capacity`.
+    fn reserve_one(&mut self) {
+        if self.items.len() == self.items.capacity() {
+            let target = (self.items.len() * 2).clamp(4, self.capacity);
+            self.items.reserve_exact(target - self.items.len());
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Occupancy under `name`; `bytes` is the storage allocated, not counting what
+    /// the items themselves point to.
+    pub fn stats(&self, name: &str) -> BufferStats {
+        BufferStats {
+            name: name.to_string(),
+            len: self.items.len(),
+            capacity: self.capacity,
+            evicted: self.evicted,
+            bytes: self.items.capacity() * std::mem::size_of::<T>(),
+        }
+    }
+}
+
+/// As a list, oldest first.
+impl<T: Serialize> Serialize for RingSeries<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.items)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingSeries<T> {
+    type Item = &'a T;
+    type IntoIter = std::collections::vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
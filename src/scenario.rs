@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::model::{Alert, AlertLevel};
+
+/// Scenarios built in, by the name `demo --scenario` takes.
+const BUNDLED: [(&str, &str); 1] = [("jelly", include_str!("../scenarios/jelly.toml"))];
+
+/// A market incident told as phases, each moving one asset's market from where
+/// the last left it towards its own figures over its cycles. Played by
+/// `api::scenario::ScenarioProvider` through the normal collection loop.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// The asset the incident happens in; a dozen calm majors trade around it.
+    pub coin: String,
+    pub max_leverage: u32,
+    pub tvl: f64,
+    /// In USD.
+    pub open_interest_cap: f64,
+    pub start: Market,
+    pub phases: Vec<Phase>,
+}
+
+/// The incident asset's market in one cycle. Amounts are in USD.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Market {
+    pub mark_px: f64,
+    /// How far the oracle is above the mark, as a fraction of the mark.
+    pub oracle_gap: f64,
+    pub open_interest: f64,
+    pub spread_bps: f64,
+    /// Resting at the best bid and ask.
+    pub bid_depth: f64,
+    pub ask_depth: f64,
+    /// Traded by the vault each cycle.
+    pub volume: f64,
+    /// Share of that volume the vault buys.
+    pub buy_share: f64,
+    /// The vault's position, negative when short.
+    pub position: f64,
+    /// The vault's loss as a fraction of TVL.
+    pub loss: f64,
+    /// Orders placed in the asset each cycle; none without a figure.
+    #[serde(default)]
+    pub orders: f64,
+    /// Share of them cancelled rather than filled.
+    #[serde(default)]
+    pub cancel_share: f64,
+    /// How long each rests before it is cancelled or filled.
+    #[serde(default)]
+    pub order_lifetime_ms: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Phase {
+    pub name: String,
+    pub cycles: u32,
+    /// The vault's fills are liquidation takeovers.
+    #[serde(default)]
+    pub liquidations: bool,
+    /// Alerts that must fire during the phase, by metric name.
+    #[serde(default)]
+    pub expect: Vec<String>,
+    #[serde(flatten)]
+    pub to: MarketChange,
+}
+
+/// The figures a phase moves to; the rest stay as they were.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MarketChange {
+    pub mark_px: Option<f64>,
+    pub oracle_gap: Option<f64>,
+    pub open_interest: Option<f64>,
+    pub spread_bps: Option<f64>,
+    pub bid_depth: Option<f64>,
+    pub ask_depth: Option<f64>,
+    pub volume: Option<f64>,
+    pub buy_share: Option<f64>,
+    pub position: Option<f64>,
+    pub loss: Option<f64>,
+    pub orders: Option<f64>,
+    pub cancel_share: Option<f64>,
+    pub order_lifetime_ms: Option<f64>,
+}
+
+impl MarketChange {
+    fn apply(&self, market: Market) -> Market {
+        Market {
+            mark_px: self.mark_px.unwrap_or(market.mark_px),
+            oracle_gap: self.oracle_gap.unwrap_or(market.oracle_gap),
+            open_interest: self.open_interest.unwrap_or(market.open_interest),
+            spread_bps: self.spread_bps.unwrap_or(market.spread_bps),
+            bid_depth: self.bid_depth.unwrap_or(market.bid_depth),
+            ask_depth: self.ask_depth.unwrap_or(market.ask_depth),
+            volume: self.volume.unwrap_or(market.volume),
+            buy_share: self.buy_share.unwrap_or(market.buy_share),
+            position: self.position.unwrap_or(market.position),
+            loss: self.loss.unwrap_or(market.loss),
+            orders: self.orders.unwrap_or(market.orders),
+            cancel_share: self.cancel_share.unwrap_or(market.cancel_share),
+            order_lifetime_ms: self.order_lifetime_ms.unwrap_or(market.order_lifetime_ms),
+        }
+    }
+}
+
+impl Market {
+    /// `fraction` of the way from `self` to `to`.
+    fn towards(&self, to: &Market, fraction: f64) -> Market {
+        let mix = |from: f64, to: f64| from + (to - from) * fraction;
+        Market {
+            mark_px: mix(self.mark_px, to.mark_px),
+            oracle_gap: mix(self.oracle_gap, to.oracle_gap),
+            open_interest: mix(self.open_interest, to.open_interest),
+            spread_bps: mix(self.spread_bps, to.spread_bps),
+            bid_depth: mix(self.bid_depth, to.bid_depth),
+            ask_depth: mix(self.ask_depth, to.ask_depth),
+            volume: mix(self.volume, to.volume),
+            buy_share: mix(self.buy_share, to.buy_share),
+            position: mix(self.position, to.position),
+            loss: mix(self.loss, to.loss),
+            orders: mix(self.orders, to.orders),
+            cancel_share: mix(self.cancel_share, to.cancel_share),
+            order_lifetime_ms: mix(self.order_lifetime_ms, to.order_lifetime_ms),
+        }
+    }
+}
+
+impl Scenario {
+    /// A bundled scenario by name, or a scenario file.
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        let (source, text) = match BUNDLED.iter().find(|(name, _)| *name == name_or_path) {
+            Some((name, text)) => (format!("bundled scenario {}", name), text.to_string()),
+            None => (
+                name_or_path.to_string(),
+                std::fs::read_to_string(name_or_path).with_context(|| {
+                    let names: Vec<&str> = BUNDLED.iter().map(|(name, _)| *name).collect();
+                    format!("{} is neither a bundled scenario ({}) nor a readable file", name_or_path, names.join(", "))
+                })?,
+            ),
+        };
+        let scenario: Scenario = toml::from_str(&text).with_context(|| format!("cannot parse {}", source))?;
+        if scenario.phases.is_empty() || scenario.phases.iter().any(|phase| phase.cycles == 0) {
+            return Err(anyhow!("{} needs phases of at least one cycle", source));
+        }
+        Ok(scenario)
+    }
+
+    pub fn cycles(&self) -> u32 {
+        self.phases.iter().map(|phase| phase.cycles).sum()
+    }
+
+    /// The phase playing in `cycle`, counted from 1, and the market then. After
+    /// the last phase its market holds.
+    pub fn at(&self, cycle: u32) -> (usize, Market) {
+        let mut from = self.start;
+        let mut first = 1;
+        for (index, phase) in self.phases.iter().enumerate() {
+            let to = phase.to.apply(from);
+            if cycle < first + phase.cycles || index + 1 == self.phases.len() {
+                let fraction = (cycle.saturating_sub(first) + 1).min(phase.cycles) as f64 / phase.cycles as f64;
+                return (index, from.towards(&to, fraction));
+            }
+            from = to;
+            first += phase.cycles;
+        }
+        unreachable!("a loaded scenario has phases")
+    }
+}
+
+/// The alerts a scenario raised in each phase, against those it should have.
+pub struct ScenarioCheck<'a> {
+    scenario: &'a Scenario,
+    /// By phase, the first cycle each metric alerted in, at its highest level,
+    /// with that alert's message.
+    fired: Vec<BTreeMap<String, (u32, AlertLevel, String)>>,
+}
+
+impl<'a> ScenarioCheck<'a> {
+    pub fn new(scenario: &'a Scenario) -> Self {
+        Self { scenario, fired: vec![BTreeMap::new(); scenario.phases.len()] }
+    }
+
+    pub fn record(&mut self, cycle: u32, alerts: &[Alert]) {
+        let (phase, _) = self.scenario.at(cycle);
+        for alert in alerts {
+            let fired = self.fired[phase].entry(alert.metric.clone()).or_insert((cycle, alert.level.clone(), alert.message.clone()));
+            if alert.level == AlertLevel::Critical && fired.1 != AlertLevel::Critical {
+                *fired = (fired.0, AlertLevel::Critical, alert.message.clone());
+            }
+        }
+    }
+
+    /// Expected alerts that never fired in their phase, as (phase, metric).
+    pub fn missing(&self) -> Vec<(&str, &str)> {
+        self.scenario
+            .phases
+            .iter()
+            .zip(&self.fired)
+            .flat_map(|(phase, fired)| {
+                phase.expect.iter().filter(|metric| !fired.contains_key(*metric)).map(|metric| (phase.name.as_str(), metric.as_str()))
+            })
+            .collect()
+    }
+
+    /// Warning and Critical alerts that fired in a phase expecting none, as
+    /// (phase, metric): a calm market must stay quiet.
+    pub fn unexpected(&self) -> Vec<(&str, &str)> {
+        self.scenario
+            .phases
+            .iter()
+            .zip(&self.fired)
+            .filter(|(phase, _)| phase.expect.is_empty())
+            .flat_map(|(phase, fired)| {
+                fired
+                    .iter()
+                    .filter(|(_, (_, level, _))| *level != AlertLevel::Info)
+                    .map(|(metric, _)| (phase.name.as_str(), metric.as_str()))
+            })
+            .collect()
+    }
+
+    /// A line per expected alert under each phase, and the unexpected ones
+    /// that fired too.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "🎬 {}: {} cycles", self.scenario.name, self.scenario.cycles());
+        let mut first = 1;
+        for (phase, fired) in self.scenario.phases.iter().zip(&self.fired) {
+            let _ = writeln!(out, "\n{} (cycles {}-{})", phase.name, first, first + phase.cycles - 1);
+            for metric in &phase.expect {
+                match fired.get(metric) {
+                    Some((cycle, level, message)) => {
+                        let _ = writeln!(out, "  ✅ {} from cycle {}, up to {:?}: {}", metric, cycle, level, message);
+                    }
+                    None => {
+                        let _ = writeln!(out, "  ❌ {} never fired", metric);
+                    }
+                }
+            }
+            let others: Vec<&str> = fired.keys().filter(|metric| !phase.expect.contains(metric)).map(String::as_str).collect();
+            if phase.expect.is_empty() {
+                for (metric, (cycle, level, message)) in fired.iter().filter(|(_, (_, level, _))| *level != AlertLevel::Info) {
+                    let _ = writeln!(out, "  ❌ {} from cycle {}, up to {:?}: {}", metric, cycle, level, message);
+                }
+            } else if !others.is_empty() {
+                let _ = writeln!(out, "  also fired: {}", others.join(", "));
+            }
+            first += phase.cycles;
+        }
+        out
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+
+/// Keyring service every secret is stored under.
+const SERVICE: &str = "hlp-toshogu";
+
+/// Sensitive settings, and the environment variable each can be read from.
+pub const SECRETS: [(&str, &str); 4] = [
+    ("mqtt.password", "HLP_MQTT_PASSWORD"),
+    ("upload.access_key_id", "HLP_UPLOAD_ACCESS_KEY_ID"),
+    ("upload.secret_access_key", "HLP_UPLOAD_SECRET_ACCESS_KEY"),
+    ("upload.session_token", "HLP_UPLOAD_SESSION_TOKEN"),
+];
+
+/// Where a secret left out of config.toml was found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Env,
+    Keyring,
+}
+
+/// Fills in secrets that config.toml leaves empty, from their environment
+/// variable or else the OS keyring (the Secret Service via `secret-tool` on Linux,
+/// the login Keychain via `security` on macOS). Only secrets an enabled feature
+/// uses are looked up, so a keyring is never unlocked for nothing.
+pub fn resolve(config: &mut Config) {
+    for (name, env) in SECRETS {
+        if get(config, name).is_some() || !wanted(config, name) {
+            continue;
+        }
+        let found = match std::env::var(env).ok().filter(|value| !value.is_empty()) {
+            Some(value) => Some((value, Source::Env)),
+            None => keyring_get(name).map(|value| (value, Source::Keyring)),
+        };
+        if let Some((value, source)) = found {
+            set(config, name, Some(value));
+            config.secret_sources.push((name.to_string(), source));
+        }
+    }
+}
+
+/// Clears the secrets `resolve` filled in, so they are not written back to the file.
+pub fn strip(config: &mut Config) {
+    for (name, _) in std::mem::take(&mut config.secret_sources) {
+        set(config, &name, None);
+    }
+}
+
+/// Stores `value` for `name` in the OS keyring.
+pub fn store(name: &str, value: &str) -> Result<()> {
+    if !SECRETS.iter().any(|(secret, _)| *secret == name) {
+        let names: Vec<&str> = SECRETS.iter().map(|(secret, _)| *secret).collect();
+        return Err(anyhow!("unknown secret {}; expected one of {}", name, names.join(", ")));
+    }
+    let status = if cfg!(target_os = "macos") {
+        // `security` only takes the value as an argument, so it is briefly visible in `ps`.
+        Command::new("security")
+            .args(["add-generic-password", "-U", "-s", SERVICE, "-a", name, "-w", value])
+            .stdout(Stdio::null())
+            .status()
+    } else {
+        let label = format!("{} {}", SERVICE, name);
+        Command::new("secret-tool")
+            .args(["store", "--label", &label, "service", SERVICE, "account", name])
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child.stdin.take().map(|mut stdin| stdin.write_all(value.as_bytes())).transpose()?;
+                child.wait()
+            })
+    };
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(anyhow!("the keyring tool exited with {}", status)),
+        Err(e) => Err(anyhow!("cannot run the keyring tool ({}): {}", keyring_tool(), e)),
+    }
+}
+
+fn keyring_tool() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "security"
+    } else {
+        "secret-tool from libsecret"
+    }
+}
+
+fn keyring_get(name: &str) -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security").args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"]).stderr(Stdio::null()).output()
+    } else {
+        Command::new("secret-tool").args(["lookup", "service", SERVICE, "account", name]).stderr(Stdio::null()).output()
+    };
+    match output {
+        Ok(output) if output.status.success() => {
+            let value = String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string();
+            (!value.is_empty()).then_some(value)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!("🔑 No keyring lookup for {}: {}", name, e);
+            None
+        }
+    }
+}
+
+fn wanted(config: &Config, name: &str) -> bool {
+    match name {
+        "mqtt.password" => config.mqtt.enabled && config.mqtt.username.is_some(),
+        _ => config.upload.enabled,
+    }
+}
+
+fn get(config: &Config, name: &str) -> Option<String> {
+    let value = match name {
+        "mqtt.password" => config.mqtt.password.clone(),
+        "upload.access_key_id" => Some(config.upload.access_key_id.clone()),
+        "upload.secret_access_key" => Some(config.upload.secret_access_key.clone()),
+        "upload.session_token" => config.upload.session_token.clone(),
+        _ => None,
+    };
+    value.filter(|value| !value.is_empty())
+}
+
+fn set(config: &mut Config, name: &str, value: Option<String>) {
+    match name {
+        "mqtt.password" => config.mqtt.password = value,
+        "upload.access_key_id" => config.upload.access_key_id = value.unwrap_or_default(),
+        "upload.secret_access_key" => config.upload.secret_access_key = value.unwrap_or_default(),
+        "upload.session_token" => config.upload.session_token = value,
+        _ => {}
+    }
+}
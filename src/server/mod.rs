@@ -0,0 +1,156 @@
+//! gRPC service `hlp_toshogu.v1.Monitor` (see `proto/hlp_toshogu.proto`), streaming
+//! metrics updates and alert events to downstream systems such as kill-switches and
+//! hedgers. There is no codegen: requests are routed by path on a plaintext HTTP/2
+//! server and the responses are encoded in `proto`.
+
+pub mod proto;
+
+use anyhow::Result;
+use hyper::body::{Bytes, Sender};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server};
+use log::{debug, info};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::model::Alert;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often streams look for a new snapshot or new alerts.
+const POLL_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_millis(200);
+
+/// Streams run until the client cancels, so the only status ever sent is this one,
+/// for calls that cannot be served.
+const STATUS_UNIMPLEMENTED: &str = "12";
+
+pub async fn serve(addr: SocketAddr, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        let alerts = alerts.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let metrics = metrics.clone();
+                let alerts = alerts.clone();
+                async move { Ok::<_, Infallible>(respond(request, metrics, alerts).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.http2_only(true).serve(make_service);
+    info!("📡 gRPC streams on {}", addr);
+    server.await?;
+    Ok(())
+}
+
+async fn respond(request: Request<Body>, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Response<Body> {
+    let path = request.uri().path().to_string();
+    if request.method() != Method::POST {
+        return trailers_only(STATUS_UNIMPLEMENTED, "gRPC requests must be POST");
+    }
+
+    let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+    let message = unframe(&body);
+
+    let (sender, body) = Body::channel();
+    match path.as_str() {
+        "/hlp_toshogu.v1.Monitor/StreamMetrics" => {
+            tokio::spawn(stream_metrics(sender, metrics));
+        }
+        "/hlp_toshogu.v1.Monitor/StreamAlerts" => {
+            tokio::spawn(stream_alerts(sender, alerts, proto::include_existing(message)));
+        }
+        _ => return trailers_only(STATUS_UNIMPLEMENTED, &format!("unknown method {}", path)),
+    }
+
+    let mut response = Response::new(body);
+    response.headers_mut().insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/grpc"));
+    response
+}
+
+/// Sends an update whenever the collection loop publishes a new snapshot, starting
+/// with the current one.
+async fn stream_metrics(mut sender: Sender, metrics: Arc<SharedMetrics>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_sent = None;
+
+    loop {
+        interval.tick().await;
+        let message = {
+            let last_update = metrics.last_update().await;
+            if last_update.is_none() || last_update == last_sent {
+                continue;
+            }
+            last_sent = last_update;
+            proto::metrics_update(&metrics.snapshot().await)
+        };
+
+        if sender.send_data(frame(&message)).await.is_err() {
+            debug!("📡 gRPC metrics stream closed by client");
+            return;
+        }
+    }
+}
+
+/// Sends each alert once, oldest first. Ids are remembered only while the alert is
+/// still in the list, so the set stays as small as the list itself.
+async fn stream_alerts(mut sender: Sender, alerts: Arc<Versioned<Vec<Alert>>>, include_existing: bool) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut sent: HashSet<String> = HashSet::new();
+    if !include_existing {
+        sent.extend(alerts.read().await.iter().map(|a| a.id.clone()));
+    }
+
+    loop {
+        interval.tick().await;
+        let messages: Vec<Bytes> = {
+            let alerts = alerts.read().await;
+            sent.retain(|id| alerts.iter().any(|a| &a.id == id));
+            alerts
+                .iter()
+                .filter(|a| sent.insert(a.id.clone()))
+                .map(|a| frame(&proto::alert_event(a)))
+                .collect()
+        };
+
+        for message in messages {
+            if sender.send_data(message).await.is_err() {
+                debug!("📡 gRPC alert stream closed by client");
+                return;
+            }
+        }
+    }
+}
+
+/// Length-prefixed message framing: uncompressed flag, big-endian length, bytes.
+fn frame(message: &[u8]) -> Bytes {
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    Bytes::from(framed)
+}
+
+/// The request message, or empty when the body is missing or malformed; every
+/// request field is optional.
+fn unframe(body: &[u8]) -> &[u8] {
+    let Some(header) = body.get(..5) else {
+        return &[];
+    };
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    body.get(5..5 + len).unwrap_or_default()
+}
+
+/// A response that ends immediately with the given status, carried in the headers.
+fn trailers_only(status: &'static str, message: &str) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/grpc"));
+    headers.insert("grpc-status", header::HeaderValue::from_static(status));
+    if let Ok(value) = header::HeaderValue::from_str(message) {
+        headers.insert("grpc-message", value);
+    }
+    response
+}
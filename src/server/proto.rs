@@ -0,0 +1,149 @@
+//! Protobuf encoding of the messages in `proto/hlp_toshogu.proto`, written out by
+//! hand. Field numbers here must match the .proto file.
+
+use crate::model::{Alert, AlertLevel, GlobalMetrics, LinkState};
+use crate::storage::flatten;
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_FIXED64: u32 = 1;
+const WIRE_LEN: u32 = 2;
+
+#[derive(Default)]
+struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn tag(&mut self, field: u32, wire: u32) {
+        self.varint(((field << 3) | wire) as u64);
+    }
+
+    fn int64(&mut self, field: u32, value: i64) {
+        if value != 0 {
+            self.tag(field, WIRE_VARINT);
+            self.varint(value as u64);
+        }
+    }
+
+    fn enumeration(&mut self, field: u32, value: u32) {
+        self.int64(field, value as i64);
+    }
+
+    fn boolean(&mut self, field: u32, value: bool) {
+        self.int64(field, value as i64);
+    }
+
+    fn double(&mut self, field: u32, value: f64) {
+        if value != 0.0 {
+            self.tag(field, WIRE_FIXED64);
+            self.buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn bytes(&mut self, field: u32, value: &[u8]) {
+        self.tag(field, WIRE_LEN);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+}
+
+fn link_state(state: LinkState) -> u32 {
+    match state {
+        LinkState::Unknown => 0,
+        LinkState::Connected => 1,
+        LinkState::Disconnected => 2,
+        LinkState::Disabled => 3,
+    }
+}
+
+/// `MetricsUpdate`
+pub fn metrics_update(metrics: &GlobalMetrics) -> Vec<u8> {
+    let mut message = Encoder::default();
+    message.int64(1, metrics.last_update.map_or(0, |t| t.timestamp_millis()));
+
+    for value in flatten(metrics) {
+        let mut entry = Encoder::default();
+        entry.string(1, value.name);
+        if let Some((label, key)) = &value.key {
+            entry.string(2, label);
+            entry.string(3, key);
+        }
+        entry.double(4, value.value);
+        message.bytes(2, &entry.buf);
+    }
+
+    message.enumeration(3, link_state(metrics.system_status.http));
+    message.enumeration(4, link_state(metrics.system_status.websocket));
+    message.buf
+}
+
+/// `AlertEvent`
+pub fn alert_event(alert: &Alert) -> Vec<u8> {
+    let level = match alert.level {
+        AlertLevel::Info => 0,
+        AlertLevel::Warning => 1,
+        AlertLevel::Critical => 2,
+    };
+
+    let mut message = Encoder::default();
+    message.string(1, &alert.id);
+    message.enumeration(2, level);
+    message.string(3, &alert.metric);
+    message.string(4, &alert.message);
+    message.int64(5, alert.timestamp.timestamp_millis());
+    message.double(6, alert.value);
+    message.double(7, alert.threshold);
+    message.boolean(8, alert.acknowledged);
+    message.buf
+}
+
+/// `StreamAlertsRequest.include_existing`; anything unreadable counts as unset.
+pub fn include_existing(request: &[u8]) -> bool {
+    let mut pos = 0;
+    let mut include = false;
+    while let Some(tag) = read_varint(request, &mut pos) {
+        let (field, wire) = (tag >> 3, (tag & 7) as u32);
+        match wire {
+            WIRE_VARINT => match read_varint(request, &mut pos) {
+                Some(value) if field == 1 => include = value != 0,
+                Some(_) => {}
+                None => break,
+            },
+            WIRE_FIXED64 => pos += 8,
+            WIRE_LEN => match read_varint(request, &mut pos) {
+                Some(len) => pos += len as usize,
+                None => break,
+            },
+            5 => pos += 4,
+            _ => break,
+        }
+    }
+    include
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
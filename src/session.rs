@@ -0,0 +1,235 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::history::{HistoryMetric, MetricHistory};
+use crate::model::{Alert, AlertLevel, LinkState, SystemStatus};
+use crate::ui::format::NumberFormat;
+
+/// Time-weighted share of the session a data source was up. Time in `Disabled` or
+/// `Unknown` states is not counted either way.
+#[derive(Debug, Default, Clone, Copy)]
+struct Uptime {
+    up: Duration,
+    observed: Duration,
+}
+
+impl Uptime {
+    fn add(&mut self, state: LinkState, elapsed: Duration) {
+        match state {
+            LinkState::Connected => {
+                self.up += elapsed;
+                self.observed += elapsed;
+            }
+            LinkState::Disconnected => self.observed += elapsed,
+            LinkState::Disabled | LinkState::Unknown => {}
+        }
+    }
+
+    fn ratio(&self) -> Option<f64> {
+        (!self.observed.is_zero()).then(|| self.up.as_secs_f64() / self.observed.as_secs_f64())
+    }
+}
+
+/// Follows a dashboard session from the first metrics to quit, sampling connection
+/// state every frame so the summary can report uptime per data source.
+pub struct SessionTracker {
+    started: DateTime<Utc>,
+    last_observed: Option<(Instant, LinkState, LinkState)>,
+    http: Uptime,
+    websocket: Uptime,
+}
+
+impl Default for SessionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self {
+            started: Utc::now(),
+            last_observed: None,
+            http: Uptime::default(),
+            websocket: Uptime::default(),
+        }
+    }
+
+    /// Credits the time since the previous call to the states seen then.
+    pub fn observe(&mut self, status: &SystemStatus) {
+        let now = Instant::now();
+        if let Some((at, http, websocket)) = self.last_observed {
+            let elapsed = now.duration_since(at);
+            self.http.add(http, elapsed);
+            self.websocket.add(websocket, elapsed);
+        }
+        self.last_observed = Some((now, status.http, status.websocket));
+    }
+
+    pub fn summary(&self, history: &MetricHistory, alerts: &[Alert]) -> SessionSummary {
+        let ended = Utc::now();
+        let (start_ms, end_ms) = (self.started.timestamp_millis(), ended.timestamp_millis());
+
+        let metrics = HistoryMetric::ALL
+            .iter()
+            .filter_map(|metric| MetricStats::from_samples(*metric, &history.series(*metric, start_ms, end_ms, 0)))
+            .collect();
+
+        let session_alerts: Vec<&Alert> = alerts.iter().filter(|a| a.timestamp >= self.started).collect();
+        let count = |level: AlertLevel| session_alerts.iter().filter(|a| a.level == level).count();
+
+        let mut by_metric: Vec<(String, usize)> = Vec::new();
+        for alert in &session_alerts {
+            match by_metric.iter_mut().find(|(metric, _)| *metric == alert.metric) {
+                Some((_, n)) => *n += 1,
+                None => by_metric.push((alert.metric.clone(), 1)),
+            }
+        }
+        by_metric.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        SessionSummary {
+            started: self.started,
+            ended,
+            metrics,
+            info_alerts: count(AlertLevel::Info),
+            warning_alerts: count(AlertLevel::Warning),
+            critical_alerts: count(AlertLevel::Critical),
+            alerts_by_metric: by_metric,
+            http_uptime: self.http.ratio(),
+            websocket_uptime: self.websocket.ratio(),
+        }
+    }
+}
+
+/// Range of one history metric over the session.
+#[derive(Debug, Clone)]
+pub struct MetricStats {
+    pub metric: HistoryMetric,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub first: f64,
+    pub last: f64,
+}
+
+impl MetricStats {
+    pub fn from_samples(metric: HistoryMetric, samples: &[(i64, f64)]) -> Option<Self> {
+        let (_, first) = *samples.first()?;
+        let (_, last) = *samples.last()?;
+        let (min, max, sum) = samples
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY, 0.0), |(lo, hi, sum), (_, v)| (lo.min(*v), hi.max(*v), sum + v));
+
+        Some(Self { metric, min, max, avg: sum / samples.len() as f64, first, last })
+    }
+}
+
+/// What happened during a session, shown on quit and optionally appended to a file.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    pub metrics: Vec<MetricStats>,
+    pub info_alerts: usize,
+    pub warning_alerts: usize,
+    pub critical_alerts: usize,
+    /// Alerts per metric, most frequent first.
+    pub alerts_by_metric: Vec<(String, usize)>,
+    pub http_uptime: Option<f64>,
+    pub websocket_uptime: Option<f64>,
+}
+
+impl SessionSummary {
+    pub fn duration(&self) -> chrono::Duration {
+        self.ended - self.started
+    }
+
+    pub fn stats(&self, metric: HistoryMetric) -> Option<&MetricStats> {
+        self.metrics.iter().find(|s| s.metric == metric)
+    }
+
+    /// Change in equity from the first to the last sample of the session.
+    pub fn pnl_change(&self) -> Option<f64> {
+        self.stats(HistoryMetric::Equity).map(|s| s.last - s.first)
+    }
+
+    pub fn format_duration(&self) -> String {
+        let secs = self.duration().num_seconds().max(0);
+        format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+
+    /// Plain-text rendering, used for the summary file.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "Session {} → {} ({})\n",
+            self.started.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.ended.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.format_duration()
+        ));
+
+        if self.metrics.is_empty() {
+            out.push_str("No metric samples were recorded this session\n");
+        } else {
+            out.push_str(&format!("{:<26}{:>14}{:>14}{:>14}{:>14}\n", "Metric", "Min", "Max", "Avg", "Last"));
+        }
+        for stats in &self.metrics {
+            out.push_str(&format!(
+                "{:<26}{:>14}{:>14}{:>14}{:>14}\n",
+                stats.metric.label(),
+                format_value(stats.metric, stats.min),
+                format_value(stats.metric, stats.max),
+                format_value(stats.metric, stats.avg),
+                format_value(stats.metric, stats.last),
+            ));
+        }
+
+        if let Some(change) = self.pnl_change() {
+            out.push_str(&format!("PnL change (equity): {}\n", format_signed_usd(change)));
+        }
+        out.push_str(&format!(
+            "Alerts: {} critical, {} warning, {} info\n",
+            self.critical_alerts, self.warning_alerts, self.info_alerts
+        ));
+        for (metric, count) in &self.alerts_by_metric {
+            out.push_str(&format!("  {}: {}\n", metric, count));
+        }
+        out.push_str(&format!(
+            "Uptime: HTTP {}, WebSocket {}\n",
+            format_uptime(self.http_uptime),
+            format_uptime(self.websocket_uptime)
+        ));
+        out
+    }
+
+    /// Appends the summary to `path`, so one file can collect many sessions.
+    pub fn append_to(&self, path: &str) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", self.to_text())?;
+        Ok(())
+    }
+}
+
+/// Dollar metrics as "$1.23M", ratios and scores with three decimals.
+pub fn format_value(metric: HistoryMetric, value: f64) -> String {
+    match metric {
+        HistoryMetric::Tvl | HistoryMetric::Equity | HistoryMetric::DailyPnl | HistoryMetric::Backstop => format_signed_usd(value),
+        HistoryMetric::AvgSpread => format!("{:.2}", value),
+        _ => format!("{:.3}", value),
+    }
+}
+
+pub fn format_uptime(uptime: Option<f64>) -> String {
+    match uptime {
+        Some(ratio) => format!("{:.1}%", ratio * 100.0),
+        None => "n/a".to_string(),
+    }
+}
+
+/// Dollar amounts as "$1.23M", "-$4.5K" or "$12.34", whatever the dashboard's
+/// locale, so summaries and reports read the same everywhere.
+pub fn format_signed_usd(value: f64) -> String {
+    NumberFormat::default().usd(value)
+}
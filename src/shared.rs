@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::Mutex;
+
+use crate::model::{GlobalMetrics, L2Snapshot, LiquidityMetrics, OpenOrder, PerformanceMetrics, Position, RiskMetrics, SystemStatus, TapeTrade, VaultMetrics};
+use crate::versioned::Versioned;
+
+/// The books and account state a cycle's metrics were computed from.
+#[derive(Debug, Clone, Default)]
+pub struct MarketState {
+    pub order_books: HashMap<String, L2Snapshot>,
+    pub open_orders: Vec<OpenOrder>,
+    pub positions: Vec<Position>,
+    pub trade_tape: Vec<TapeTrade>,
+    pub last_update: Option<DateTime<Utc>>,
+}
+
+/// The dashboard's current metrics, one lock per domain: a reader of the risk
+/// figures never waits on one copying the books, and the status the collector
+/// updates between cycles is apart from the cycle's metrics.
+#[derive(Debug, Default)]
+pub struct SharedMetrics {
+    pub vault: Versioned<VaultMetrics>,
+    pub performance: Versioned<PerformanceMetrics>,
+    pub liquidity: Versioned<LiquidityMetrics>,
+    pub risk: Versioned<RiskMetrics>,
+    pub status: Versioned<SystemStatus>,
+    pub market: Versioned<MarketState>,
+    /// Odd while `publish` is replacing the cycle's cells.
+    generation: AtomicU64,
+    publishing: Mutex<()>,
+}
+
+/// Versions of each cell a copy was last refreshed at (see `SharedMetrics::refresh`).
+#[derive(Debug, Default)]
+pub struct Seen {
+    vault: Option<u64>,
+    performance: Option<u64>,
+    liquidity: Option<u64>,
+    risk: Option<u64>,
+    status: Option<u64>,
+    market: Option<u64>,
+}
+
+impl SharedMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces every cell but `status` with one cycle's metrics. Readers see
+    /// either the previous cycle or this one, never a mix.
+    pub async fn publish(&self, metrics: GlobalMetrics) {
+        let _publishing = self.publishing.lock().await;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *self.vault.write().await = metrics.vault_metrics;
+        *self.performance.write().await = metrics.performance_metrics;
+        *self.liquidity.write().await = metrics.liquidity_metrics;
+        *self.risk.write().await = metrics.risk_metrics;
+        *self.market.write().await = MarketState {
+            order_books: metrics.order_books,
+            open_orders: metrics.open_orders,
+            positions: metrics.positions,
+            trade_tape: metrics.trade_tape,
+            last_update: metrics.last_update,
+        };
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub async fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.market.read().await.last_update
+    }
+
+    /// A copy of every cell, from a single cycle.
+    pub async fn snapshot(&self) -> GlobalMetrics {
+        let mut copy = GlobalMetrics::default();
+        self.refresh(&mut copy, &mut Seen::default()).await;
+        copy
+    }
+
+    /// Brings the cells of `copy` that changed since `seen` up to date, so a
+    /// reader polling many times a second only copies what moved. Returns
+    /// whether anything did.
+    pub async fn refresh(&self, copy: &mut GlobalMetrics, seen: &mut Seen) -> bool {
+        let mut changed = false;
+        loop {
+            let generation = self.generation.load(Ordering::Acquire);
+            if generation % 2 == 1 {
+                tokio::task::yield_now().await;
+                continue;
+            }
+            changed |= self.vault.refresh(&mut copy.vault_metrics, &mut seen.vault).await;
+            changed |= self.performance.refresh(&mut copy.performance_metrics, &mut seen.performance).await;
+            changed |= self.liquidity.refresh(&mut copy.liquidity_metrics, &mut seen.liquidity).await;
+            changed |= self.risk.refresh(&mut copy.risk_metrics, &mut seen.risk).await;
+            changed |= self.status.refresh(&mut copy.system_status, &mut seen.status).await;
+            if seen.market != Some(self.market.version()) {
+                let market = self.market.read().await;
+                copy.order_books.clone_from(&market.order_books);
+                copy.open_orders.clone_from(&market.open_orders);
+                copy.positions.clone_from(&market.positions);
+                copy.trade_tape.clone_from(&market.trade_tape);
+                copy.last_update = market.last_update;
+                seen.market = Some(self.market.version());
+                changed = true;
+            }
+            // A publish that started meanwhile may have left `copy` torn; the
+            // next pass copies only the cells it replaced.
+            if self.generation.load(Ordering::Acquire) == generation {
+                return changed;
+            }
+        }
+    }
+}
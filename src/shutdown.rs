@@ -0,0 +1,136 @@
+use anyhow::Result;
+use futures_util::FutureExt;
+use log::{debug, error, info, warn};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::alert;
+use crate::model::Alert;
+use crate::versioned::Versioned;
+
+/// How long `stop` waits for the background tasks to wind down.
+pub const GRACE: Duration = Duration::from_secs(5);
+
+/// Delay before restarting a supervised task the first time it fails. It
+/// doubles with each failure in a row up to `MAX_BACKOFF`, and a run that lasts
+/// `MAX_BACKOFF` starts the count again.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The background tasks of a dashboard session and the token that asks them to
+/// stop. Tasks with nothing to clean up are spawned with `spawn_until_stopped`
+/// and dropped on quit; tasks that flush, close or persist something watch
+/// `token` themselves and return once done. Tasks the data depends on are
+/// spawned with `supervise`, which restarts them when they die.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tasks: Arc<Mutex<JoinSet<()>>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancelled when the session quits.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Runs `task` until it returns; `stop` waits for it.
+    pub fn spawn(&self, name: &'static str, task: impl Future<Output = ()> + Send + 'static) {
+        lock(&self.tasks).spawn(async move {
+            task.await;
+            debug!("🛑 {} stopped", name);
+        });
+    }
+
+    /// Runs `task` until it returns or the session quits, whichever comes first.
+    pub fn spawn_until_stopped(&self, name: &'static str, task: impl Future<Output = ()> + Send + 'static) {
+        let token = self.token.clone();
+        self.spawn(name, async move {
+            token.run_until_cancelled(task).await;
+        });
+    }
+
+    /// Runs `task` and starts it again whenever it panics, fails or returns
+    /// before the session quits, after a growing delay. Failures are logged, and
+    /// the first of a run of them raises a Critical alert. Each run of `task`
+    /// gets a scope of its own to spawn into, which is cancelled when the run
+    /// ends so a restart leaves nothing of the previous run behind.
+    pub fn supervise<F, Fut>(&self, name: &'static str, alerts: Arc<Versioned<Vec<Alert>>>, mut task: F)
+    where
+        F: FnMut(Shutdown) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        self.spawn(name, async move {
+            let mut backoff = MIN_BACKOFF;
+            while !supervisor.token.is_cancelled() {
+                let scope = Shutdown { token: supervisor.token.child_token(), tasks: supervisor.tasks.clone() };
+                let started = Instant::now();
+                let mut run = Box::pin(AssertUnwindSafe(task(scope.clone())).catch_unwind());
+                let outcome = (&mut run).await;
+                // Before the run is dropped, so what it spawned sees the scope
+                // cancelled rather than its channels closing.
+                scope.token.cancel();
+                drop(run);
+                if supervisor.token.is_cancelled() {
+                    break;
+                }
+
+                let failure = match outcome {
+                    Ok(Ok(())) => "stopped unexpectedly".to_string(),
+                    Ok(Err(e)) => format!("failed: {}", e),
+                    Err(panic) => format!("panicked: {}", panic_message(&*panic)),
+                };
+                if started.elapsed() >= MAX_BACKOFF {
+                    backoff = MIN_BACKOFF;
+                }
+                error!("💥 {} {}; restarting in {}s", name, failure, backoff.as_secs());
+                if backoff == MIN_BACKOFF {
+                    alerts.write().await.push(alert::failure("Supervisor", format!("{} {}", name, failure)));
+                }
+                if supervisor.token.run_until_cancelled(tokio::time::sleep(backoff)).await.is_none() {
+                    break;
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                info!("🔁 Restarting {}", name);
+            }
+        });
+    }
+
+    /// Cancels the token and waits up to `grace` for every task to return,
+    /// aborting the ones still running after that.
+    pub async fn stop(&self, grace: Duration) {
+        self.token.cancel();
+        let mut tasks = std::mem::take(&mut *lock(&self.tasks));
+        info!("🛑 Shutting down {} background tasks", tasks.len());
+        let drained = tokio::time::timeout(grace, async {
+            while tasks.join_next().await.is_some() {}
+        });
+        if drained.await.is_err() {
+            warn!("⚠️ {} background tasks did not stop within {}s; abandoning them", tasks.len(), grace.as_secs());
+            tasks.shutdown().await;
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => message,
+        (_, Some(message)) => message,
+        _ => "unknown panic",
+    }
+}
+
+/// The set only ever has tasks added or taken, so a panicked holder leaves it usable.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
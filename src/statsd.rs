@@ -0,0 +1,142 @@
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+
+use crate::config::StatsdSettings;
+use crate::model::{Alert, AlertLevel};
+use crate::storage::flatten;
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often the sink looks for a new snapshot or new alerts.
+const POLL_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_millis(200);
+
+/// Largest datagram sent; keeps packets under a typical Ethernet MTU.
+const MAX_PACKET_BYTES: usize = 1432;
+
+/// Sends every flattened metric as a gauge once per collection cycle, and counts
+/// new alerts by level, to a StatsD or DogStatsD agent over UDP. With `dogstatsd`
+/// the configured tags are attached and per-asset values are tagged by `coin`
+/// (`distance` for fill probabilities); plain StatsD has no tags, so the key goes
+/// into the metric name instead.
+pub async fn run(settings: StatsdSettings, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Result<()> {
+    let addr: SocketAddr = settings.address.parse()?;
+    let socket = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+    socket.connect(addr).await?;
+    info!("📤 Sending StatsD metrics to {}", addr);
+
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_sent = None;
+    // Alerts raised before start-up are not counted.
+    let mut counted: HashSet<String> = alerts.read().await.iter().map(|a| a.id.clone()).collect();
+
+    loop {
+        interval.tick().await;
+        let mut lines = Vec::new();
+
+        {
+            let last_update = metrics.last_update().await;
+            if last_update.is_some() && last_update != last_sent {
+                last_sent = last_update;
+                for value in flatten(&metrics.snapshot().await) {
+                    lines.push(gauge(&settings, value.name, value.key.as_ref().map(|(l, k)| (*l, k.as_str())), value.value));
+                }
+            }
+        }
+
+        {
+            let alerts = alerts.read().await;
+            counted.retain(|id| alerts.iter().any(|a| &a.id == id));
+            let (mut info, mut warning, mut critical) = (0, 0, 0);
+            for alert in alerts.iter().filter(|a| counted.insert(a.id.clone())) {
+                match alert.level {
+                    AlertLevel::Info => info += 1,
+                    AlertLevel::Warning => warning += 1,
+                    AlertLevel::Critical => critical += 1,
+                }
+            }
+            for (level, count) in [("info", info), ("warning", warning), ("critical", critical)] {
+                if count > 0 {
+                    lines.push(counter(&settings, "alerts", ("level", level), count));
+                }
+            }
+        }
+
+        for packet in packets(&lines) {
+            if let Err(e) = socket.send(packet.as_bytes()).await {
+                // UDP errors are usually a missing agent; keep trying quietly.
+                debug!("📤 StatsD send failed: {}", e);
+            }
+        }
+    }
+}
+
+fn gauge(settings: &StatsdSettings, name: &str, key: Option<(&str, &str)>, value: f64) -> String {
+    let value = if value.is_finite() { value } else { 0.0 };
+    match key {
+        Some((label, key)) if settings.dogstatsd => {
+            format!("{}:{}|g{}", metric_name(settings, name), value, tags(settings, Some((label, key))))
+        }
+        Some((_, key)) => format!("{}.{}:{}|g", metric_name(settings, name), sanitize(key), value),
+        None => format!("{}:{}|g{}", metric_name(settings, name), value, tags(settings, None)),
+    }
+}
+
+fn counter(settings: &StatsdSettings, name: &str, (label, key): (&str, &str), count: u64) -> String {
+    if settings.dogstatsd {
+        format!("{}:{}|c{}", metric_name(settings, name), count, tags(settings, Some((label, key))))
+    } else {
+        format!("{}.{}:{}|c", metric_name(settings, name), key, count)
+    }
+}
+
+fn metric_name(settings: &StatsdSettings, name: &str) -> String {
+    if settings.prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", settings.prefix, name)
+    }
+}
+
+fn tags(settings: &StatsdSettings, extra: Option<(&str, &str)>) -> String {
+    if !settings.dogstatsd {
+        return String::new();
+    }
+    let tags: Vec<String> = settings
+        .tags
+        .iter()
+        .cloned()
+        .chain(extra.map(|(label, key)| format!("{}:{}", label, sanitize(key))))
+        .collect();
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!("|#{}", tags.join(","))
+    }
+}
+
+/// Keys such as `0.1%` may contain characters the line protocol reserves.
+fn sanitize(key: &str) -> String {
+    key.chars().map(|c| if matches!(c, ':' | '|' | '@' | ',' | '#' | ' ') { '_' } else { c }).collect()
+}
+
+/// Joins lines into newline-separated datagrams of at most `MAX_PACKET_BYTES`.
+fn packets(lines: &[String]) -> Vec<String> {
+    let mut packets: Vec<String> = Vec::new();
+    for line in lines {
+        match packets.last_mut() {
+            Some(packet) if packet.len() + 1 + line.len() <= MAX_PACKET_BYTES => {
+                packet.push('\n');
+                packet.push_str(line);
+            }
+            _ => packets.push(line.clone()),
+        }
+    }
+    if packets.iter().any(|p| p.len() > MAX_PACKET_BYTES) {
+        warn!("⚠️ StatsD line longer than {} bytes; the agent may drop it", MAX_PACKET_BYTES);
+    }
+    packets
+}
@@ -0,0 +1,498 @@
+pub mod parquet;
+pub mod raw;
+pub mod sqlite;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::prelude::*;
+use std::collections::HashMap;
+use log::warn;
+use std::io::Write;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::{Duration, Instant};
+
+use crate::config::StorageSettings;
+use crate::history::{HistoryMetric, RETENTION_MS};
+use crate::model::GlobalMetrics;
+use sqlite::{Connection, Param};
+
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+    CREATE TABLE IF NOT EXISTS samples (
+        ts_ms INTEGER NOT NULL,
+        metric TEXT NOT NULL,
+        value REAL NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS samples_metric_ts ON samples (metric, ts_ms);
+    CREATE INDEX IF NOT EXISTS samples_ts ON samples (ts_ms);
+";
+
+/// How often retention runs while recording; it also runs once on open.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Snapshots a [`StoreWriter`] may fall behind by before it drops new ones.
+const WRITE_QUEUE: usize = 64;
+
+/// On-disk history of every metrics snapshot, one row per flattened value (see
+/// [`flatten`]), so charts survive restarts and a run can be analysed offline.
+///
+/// Rows younger than `full_resolution_hours` are kept as recorded; older ones are
+/// thinned to one per metric per `downsample_interval_secs`, and anything past
+/// `retention_days` is deleted.
+pub struct MetricStore {
+    conn: Connection,
+    settings: StorageSettings,
+    last_pruned: Instant,
+}
+
+impl MetricStore {
+    pub fn open(settings: &StorageSettings) -> Result<Self> {
+        let conn = Connection::open(&settings.path)?;
+        conn.execute_batch(SCHEMA)?;
+
+        let mut store = Self { conn, settings: settings.clone(), last_pruned: Instant::now() };
+        store.prune()?;
+        Ok(store)
+    }
+
+    /// Opens an existing store for reading only, e.g. to export from it while the
+    /// dashboard keeps recording.
+    pub fn open_existing(settings: &StorageSettings) -> Result<Self> {
+        if !std::path::Path::new(&settings.path).exists() {
+            return Err(anyhow!("no metric store at {} (enable [storage] to record one)", settings.path));
+        }
+        let conn = Connection::open_read_only(&settings.path)?;
+        Ok(Self { conn, settings: settings.clone(), last_pruned: Instant::now() })
+    }
+
+    pub fn record(&mut self, metrics: &GlobalMetrics) -> Result<()> {
+        let timestamp_ms = metrics.last_update.unwrap_or_else(Utc::now).timestamp_millis();
+        let rows = flatten(metrics);
+
+        self.conn.execute_batch("BEGIN")?;
+        let inserted = self.insert(timestamp_ms, &rows);
+        self.conn.execute_batch(if inserted.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+        inserted?;
+
+        if self.last_pruned.elapsed() >= PRUNE_INTERVAL {
+            self.prune()?;
+        }
+        Ok(())
+    }
+
+    fn insert(&self, timestamp_ms: i64, rows: &[FlatValue]) -> Result<()> {
+        let mut stmt = self.conn.prepare("INSERT INTO samples (ts_ms, metric, value) VALUES (?1, ?2, ?3)")?;
+        for row in rows {
+            stmt.execute(&[Param::Int(timestamp_ms), Param::Text(&row.stored_name()), Param::Real(row.value)])?;
+        }
+        Ok(())
+    }
+
+    /// Applies the retention policy, returning how many rows were deleted.
+    pub fn prune(&mut self) -> Result<usize> {
+        let now_ms = Utc::now().timestamp_millis();
+        let expired_before = now_ms - self.settings.retention_days as i64 * 24 * 60 * 60 * 1000;
+        let thin_before = now_ms - self.settings.full_resolution_hours as i64 * 60 * 60 * 1000;
+        let bucket_ms = self.settings.downsample_interval_secs as i64 * 1000;
+
+        self.conn.prepare("DELETE FROM samples WHERE ts_ms < ?1")?.execute(&[Param::Int(expired_before)])?;
+        let mut deleted = self.conn.changes();
+
+        // Keep the first row of each metric per bucket; re-running over already thinned
+        // rows keeps the same survivors.
+        self.conn
+            .prepare(
+                "DELETE FROM samples WHERE ts_ms < ?1 AND rowid NOT IN (
+                     SELECT MIN(rowid) FROM samples WHERE ts_ms < ?1 GROUP BY metric, ts_ms / ?2
+                 )",
+            )?
+            .execute(&[Param::Int(thin_before), Param::Int(bucket_ms)])?;
+        deleted += self.conn.changes();
+
+        self.last_pruned = Instant::now();
+        Ok(deleted)
+    }
+
+    /// The stored samples of every chart metric within the in-memory history's
+    /// retention, to seed it with through `MetricHistory::restore`.
+    pub fn stored_history(&self) -> Result<HashMap<HistoryMetric, Vec<(i64, f64)>>> {
+        let end_ms = Utc::now().timestamp_millis();
+        HistoryMetric::ALL
+            .into_iter()
+            .map(|metric| Ok((metric, self.series(history_key(metric), end_ms - RETENTION_MS, end_ms)?)))
+            .collect()
+    }
+
+    /// Stored samples of one flattened metric in `[start_ms, end_ms]`, oldest first.
+    pub fn series(&self, metric: &str, start_ms: i64, end_ms: i64) -> Result<Vec<(i64, f64)>> {
+        self.conn
+            .prepare("SELECT ts_ms, value FROM samples WHERE metric = ?1 AND ts_ms BETWEEN ?2 AND ?3 ORDER BY ts_ms")?
+            .query(&[Param::Text(metric), Param::Int(start_ms), Param::Int(end_ms)], |row| (row.int(0), row.real(1)))
+    }
+
+    /// Every metric name in the store, sorted.
+    pub fn metric_names(&self) -> Result<Vec<String>> {
+        self.conn.prepare("SELECT DISTINCT metric FROM samples ORDER BY metric")?.query(&[], |row| row.text(0))
+    }
+
+    /// The most recently recorded snapshot rebuilt from its rows, or `None` for an
+    /// empty store. Only what [`flatten`] stores comes back: no books, orders or
+    /// positions, and link states stay unknown.
+    pub fn latest_snapshot(&self) -> Result<Option<GlobalMetrics>> {
+        let rows = self
+            .conn
+            .prepare("SELECT ts_ms, metric, value FROM samples WHERE ts_ms = (SELECT MAX(ts_ms) FROM samples)")?
+            .query(&[], |row| (row.int(0), row.text(1), row.real(2)))?;
+        let Some((timestamp_ms, _, _)) = rows.first() else {
+            return Ok(None);
+        };
+        let timestamp = DateTime::<Utc>::from_timestamp_millis(*timestamp_ms);
+        Ok(Some(unflatten(timestamp, rows.iter().map(|(_, metric, value)| (metric.as_str(), *value)))))
+    }
+
+    /// Calls `visit` with every snapshot recorded in `[start_ms, end_ms]`, oldest
+    /// first, rebuilt as [`latest_snapshot`](Self::latest_snapshot) rebuilds one,
+    /// returning how many there were. Rows are read as they are visited, so a
+    /// long range is never held in memory at once.
+    pub fn for_each_snapshot(&self, start_ms: i64, end_ms: i64, mut visit: impl FnMut(GlobalMetrics)) -> Result<usize> {
+        let mut snapshots = 0;
+        let mut emit = |timestamp_ms: i64, rows: &mut Vec<(String, f64)>| {
+            let timestamp = DateTime::<Utc>::from_timestamp_millis(timestamp_ms);
+            visit(unflatten(timestamp, rows.iter().map(|(metric, value)| (metric.as_str(), *value))));
+            rows.clear();
+            snapshots += 1;
+        };
+
+        let mut current: Option<i64> = None;
+        let mut rows = Vec::new();
+        self.conn
+            .prepare("SELECT ts_ms, metric, value FROM samples WHERE ts_ms BETWEEN ?1 AND ?2 ORDER BY ts_ms")?
+            .query(&[Param::Int(start_ms), Param::Int(end_ms)], |row| {
+                let timestamp_ms = row.int(0);
+                if let Some(previous) = current.filter(|previous| *previous != timestamp_ms) {
+                    emit(previous, &mut rows);
+                }
+                current = Some(timestamp_ms);
+                rows.push((row.text(1), row.real(2)));
+            })?;
+        if let Some(last) = current {
+            emit(last, &mut rows);
+        }
+        Ok(snapshots)
+    }
+
+    /// Writes samples of `metrics` from `start_ms` on as tidy CSV
+    /// (`timestamp,metric,value`, one row per sample), returning the row count.
+    pub fn write_csv(&self, metrics: &[String], start_ms: i64, out: &mut impl Write) -> Result<usize> {
+        let placeholders = (0..metrics.len()).map(|i| format!("?{}", i + 2)).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT ts_ms, metric, value FROM samples WHERE ts_ms >= ?1 AND metric IN ({}) ORDER BY ts_ms, metric",
+            placeholders
+        );
+        let mut params = vec![Param::Int(start_ms)];
+        params.extend(metrics.iter().map(|m| Param::Text(m)));
+
+        let rows = self.conn.prepare(&sql)?.query(&params, |row| (row.int(0), row.text(1), row.real(2)))?;
+
+        writeln!(out, "timestamp,metric,value")?;
+        for (ts_ms, metric, value) in &rows {
+            let timestamp = DateTime::<Utc>::from_timestamp_millis(*ts_ms).unwrap_or_default();
+            writeln!(out, "{},{},{}", timestamp.to_rfc3339_opts(SecondsFormat::Millis, true), metric, value)?;
+        }
+        out.flush()?;
+        Ok(rows.len())
+    }
+}
+
+/// Records snapshots into a [`MetricStore`] on a thread of its own, so the
+/// blocking SQLite writes and pruning stay off the async runtime.
+pub struct StoreWriter {
+    queue: SyncSender<(GlobalMetrics, tracing::Span)>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl StoreWriter {
+    pub fn spawn(mut store: MetricStore) -> Result<Self> {
+        let (queue, snapshots) = mpsc::sync_channel::<(GlobalMetrics, tracing::Span)>(WRITE_QUEUE);
+        let thread = std::thread::Builder::new().name("metric-store".into()).spawn(move || {
+            for (snapshot, span) in snapshots {
+                if let Err(e) = span.in_scope(|| store.record(&snapshot)) {
+                    warn!("⚠️ Failed to store metrics snapshot: {}", e);
+                }
+            }
+        })?;
+        Ok(Self { queue, thread })
+    }
+
+    /// Queues `snapshot` to be recorded within `span`. A writer that has fallen
+    /// `WRITE_QUEUE` snapshots behind drops it rather than hold up the cycle.
+    pub fn record(&self, snapshot: GlobalMetrics, span: tracing::Span) {
+        match self.queue.try_send((snapshot, span)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => warn!("⚠️ Metric store is {} snapshots behind, dropped one", WRITE_QUEUE),
+            Err(TrySendError::Disconnected(_)) => warn!("⚠️ Metric store writer has stopped, snapshot not stored"),
+        }
+    }
+
+    /// Waits for the queued snapshots to be written.
+    pub async fn close(self) {
+        drop(self.queue);
+        let thread = self.thread;
+        let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+    }
+}
+
+/// Short names accepted on the command line, next to full stored names such as
+/// `liquidity.bid_ask_spread_bps.BTC`.
+const METRIC_ALIASES: [(&str, HistoryMetric); 12] = [
+    ("vpin", HistoryMetric::Vpin),
+    ("pli", HistoryMetric::PhantomLiquidity),
+    ("liquidation_risk", HistoryMetric::LiquidationRisk),
+    ("cascade_risk", HistoryMetric::CascadeRisk),
+    ("utilization", HistoryMetric::Utilization),
+    ("spread", HistoryMetric::AvgSpread),
+    ("cancel_rate", HistoryMetric::CancelRate),
+    ("fleeting_ratio", HistoryMetric::FleetingRatio),
+    ("tvl", HistoryMetric::Tvl),
+    ("equity", HistoryMetric::Equity),
+    ("daily_pnl", HistoryMetric::DailyPnl),
+    ("backstop", HistoryMetric::Backstop),
+];
+
+/// Stored name for a short alias or a full stored name.
+pub fn resolve_metric(name: &str) -> String {
+    METRIC_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or_else(|| name.to_string(), |(_, metric)| history_key(*metric).to_string())
+}
+
+pub fn metric_aliases() -> impl Iterator<Item = &'static str> {
+    METRIC_ALIASES.iter().map(|(alias, _)| *alias)
+}
+
+/// Stored name of the flattened value behind each chart metric.
+pub fn history_key(metric: HistoryMetric) -> &'static str {
+    match metric {
+        HistoryMetric::Vpin => "risk.vpin_score",
+        HistoryMetric::PhantomLiquidity => "risk.phantom_liquidity_index",
+        HistoryMetric::LiquidationRisk => "risk.liquidation_risk_score",
+        HistoryMetric::CascadeRisk => "risk.cascade_risk_score",
+        HistoryMetric::Utilization => "vault.utilization_rate",
+        HistoryMetric::AvgSpread => "liquidity.avg_spread_bps",
+        HistoryMetric::CancelRate => "liquidity.cancel_rate",
+        HistoryMetric::FleetingRatio => "liquidity.fleeting_order_ratio",
+        HistoryMetric::Tvl => "vault.tvl",
+        HistoryMetric::Equity => "vault.equity",
+        HistoryMetric::DailyPnl => "performance.daily_pnl",
+        HistoryMetric::Backstop => "risk.backstop_value",
+    }
+}
+
+/// One numeric value of a snapshot. Values from per-asset maps carry the map key
+/// with the name of what it is (`coin`, or `distance` for fill probabilities).
+pub struct FlatValue {
+    pub name: &'static str,
+    pub key: Option<(&'static str, String)>,
+    pub value: f64,
+}
+
+impl FlatValue {
+    /// `section.field`, or `section.field.KEY` for map entries, as stored.
+    pub fn stored_name(&self) -> String {
+        match &self.key {
+            Some((_, key)) => format!("{}.{}", self.name, key),
+            None => self.name.to_string(),
+        }
+    }
+}
+
+/// The inverse of [`flatten`] for stored names; unknown names are ignored.
+fn unflatten<'a>(last_update: Option<DateTime<Utc>>, values: impl Iterator<Item = (&'a str, f64)>) -> GlobalMetrics {
+    let mut metrics = GlobalMetrics { last_update, ..GlobalMetrics::default() };
+    let usd = |value: f64| Decimal::from_f64(value).unwrap_or_default();
+
+    for (name, value) in values {
+        let (vault, performance, liquidity, risk) = (
+            &mut metrics.vault_metrics,
+            &mut metrics.performance_metrics,
+            &mut metrics.liquidity_metrics,
+            &mut metrics.risk_metrics,
+        );
+        match name {
+            "vault.tvl" => vault.tvl = usd(value),
+            "vault.equity" => vault.equity = usd(value),
+            "vault.apr" => vault.apr = value,
+            "vault.utilization_rate" => vault.utilization_rate = value,
+            "vault.deployed_liquidity" => vault.deployed_liquidity = usd(value),
+            "vault.idle_liquidity" => vault.idle_liquidity = usd(value),
+            "vault.isolated_margin" => vault.isolated_margin = usd(value),
+            "vault.isolated_utilization" => vault.isolated_utilization = value,
+            "vault.spot_value" => vault.spot_value = usd(value),
+            "vault.pnl_24h" => vault.pnl_24h = usd(value),
+            "vault.net_flow_24h" => vault.net_flow_24h = usd(value),
+            "performance.daily_pnl" => performance.daily_pnl = usd(value),
+            "performance.unrealized_pnl" => performance.unrealized_pnl = usd(value),
+            "performance.total_volume" => performance.total_volume = usd(value),
+            "performance.sharpe_ratio" => performance.sharpe_ratio = value,
+            "performance.sortino_ratio" => performance.sortino_ratio = value,
+            "performance.adverse_selection_cost" => performance.adverse_selection_cost = value,
+            "performance.fees_paid" => performance.fees.fees_paid = usd(value),
+            "performance.builder_fees" => performance.fees.builder_fees = usd(value),
+            "performance.fee_excess_bps" => performance.fees.excess_bps = value,
+            "liquidity.avg_order_lifetime_ms" => liquidity.avg_order_lifetime_ms = value,
+            "liquidity.cancel_rate" => liquidity.cancel_rate = value,
+            "liquidity.fleeting_order_ratio" => liquidity.fleeting_order_ratio = value,
+            "liquidity.layering_detection_score" => liquidity.layering_detection_score = value,
+            "liquidity.spoofing_detection_index" => liquidity.spoofing_detection_index = value,
+            "liquidity.liquidity_realization_rate" => liquidity.liquidity_realization_rate = value,
+            "risk.vpin_score" => risk.vpin_score = value,
+            "risk.phantom_liquidity_index" => risk.phantom_liquidity_index = value,
+            "risk.liquidation_risk_score" => risk.liquidation_risk_score = value,
+            "risk.cascade_risk_score" => risk.cascade_risk_score = value,
+            "risk.max_drawdown" => risk.max_drawdown = value,
+            "risk.cross_exchange_manipulation_score" => risk.cross_exchange_manipulation_score = value,
+            "risk.whale_activity" => risk.whale_activity = value,
+            "system.ws_messages_per_sec" => metrics.system_status.ws_messages_per_sec = value,
+            "system.active_alerts" => metrics.system_status.active_alerts = value as usize,
+            "system.api_latency_ms" => metrics.system_status.api_latency_ms = Some(value),
+            _ => {
+                // Map keys may contain dots themselves, so match on the map's name.
+                let entry = |map: &str| name.strip_prefix(map).and_then(|rest| rest.strip_prefix('.')).map(str::to_string);
+                if let Some(coin) = entry("performance.realized_spread") {
+                    performance.realized_spread.insert(coin, value);
+                } else if let Some(coin) = entry("liquidity.bid_ask_spread_bps") {
+                    liquidity.bid_ask_spread_bps.insert(coin, value);
+                } else if let Some(coin) = entry("liquidity.depth_at_50bps") {
+                    liquidity.depth_at_50bps.insert(coin, usd(value));
+                } else if let Some(coin) = entry("liquidity.order_book_imbalance") {
+                    liquidity.order_book_imbalance.insert(coin, value);
+                } else if let Some(distance) = entry("liquidity.fill_probability_by_distance") {
+                    liquidity.fill_probability_by_distance.insert(distance, value);
+                } else if let Some(coin) = entry("risk.vpin_by_asset") {
+                    risk.vpin_by_asset.insert(coin, value);
+                } else if let Some(coin) = entry("risk.position_concentration") {
+                    risk.position_concentration.insert(coin, value);
+                } else if let Some(coin) = entry("risk.oracle_divergence") {
+                    risk.oracle_divergence.insert(coin, value);
+                } else if let Some(coin) = entry("risk.open_interest_cap_usage") {
+                    risk.open_interest_cap_usage.insert(coin, value);
+                }
+            }
+        }
+    }
+
+    metrics
+}
+
+/// Every numeric value of a snapshot, map entries grouped by name. Order books and
+/// open orders are not included.
+pub fn flatten(metrics: &GlobalMetrics) -> Vec<FlatValue> {
+    let mut rows: Vec<FlatValue> = Vec::new();
+    let mut push = |name: &'static str, value: f64| rows.push(FlatValue { name, key: None, value });
+    let usd = |value: Decimal| value.to_f64().unwrap_or(0.0);
+
+    let vault = &metrics.vault_metrics;
+    push("vault.tvl", usd(vault.tvl));
+    push("vault.equity", usd(vault.equity));
+    push("vault.apr", vault.apr);
+    push("vault.utilization_rate", vault.utilization_rate);
+    push("vault.deployed_liquidity", usd(vault.deployed_liquidity));
+    push("vault.idle_liquidity", usd(vault.idle_liquidity));
+    push("vault.isolated_margin", usd(vault.isolated_margin));
+    push("vault.isolated_utilization", vault.isolated_utilization);
+    push("vault.spot_value", usd(vault.spot_value));
+    push("vault.pnl_24h", usd(vault.pnl_24h));
+    push("vault.net_flow_24h", usd(vault.net_flow_24h));
+
+    let performance = &metrics.performance_metrics;
+    push("performance.daily_pnl", usd(performance.daily_pnl));
+    push("performance.unrealized_pnl", usd(performance.unrealized_pnl));
+    push("performance.total_volume", usd(performance.total_volume));
+    push("performance.sharpe_ratio", performance.sharpe_ratio);
+    push("performance.sortino_ratio", performance.sortino_ratio);
+    push("performance.adverse_selection_cost", performance.adverse_selection_cost);
+    push("performance.fees_paid", usd(performance.fees.fees_paid));
+    push("performance.builder_fees", usd(performance.fees.builder_fees));
+    push("performance.fee_excess_bps", performance.fees.excess_bps);
+
+    let liquidity = &metrics.liquidity_metrics;
+    push("liquidity.avg_spread_bps", HistoryMetric::AvgSpread.extract(metrics));
+    push("liquidity.avg_order_lifetime_ms", liquidity.avg_order_lifetime_ms);
+    push("liquidity.cancel_rate", liquidity.cancel_rate);
+    push("liquidity.fleeting_order_ratio", liquidity.fleeting_order_ratio);
+    push("liquidity.layering_detection_score", liquidity.layering_detection_score);
+    push("liquidity.spoofing_detection_index", liquidity.spoofing_detection_index);
+    push("liquidity.liquidity_realization_rate", liquidity.liquidity_realization_rate);
+
+    let risk = &metrics.risk_metrics;
+    push("risk.vpin_score", risk.vpin_score);
+    push("risk.phantom_liquidity_index", risk.phantom_liquidity_index);
+    push("risk.liquidation_risk_score", risk.liquidation_risk_score);
+    push("risk.cascade_risk_score", risk.cascade_risk_score);
+    push("risk.max_drawdown", risk.max_drawdown);
+    push("risk.cross_exchange_manipulation_score", risk.cross_exchange_manipulation_score);
+    push("risk.whale_activity", risk.whale_activity);
+    // Like the vault breakdowns, kept for charts and queries but not restored.
+    push("risk.backstop_value", usd(risk.backstop.total()));
+    push("risk.backstop_drawdown", risk.backstop.drawdown());
+
+    let status = &metrics.system_status;
+    push("system.ws_messages_per_sec", status.ws_messages_per_sec);
+    push("system.active_alerts", status.active_alerts as f64);
+    if let Some(latency) = status.api_latency_ms {
+        push("system.api_latency_ms", latency);
+    }
+    // Not restored either: enough to chart how far the two code paths drift.
+    if let Some(shadow) = &status.shadow {
+        if let Some(vpin) = shadow.vpin {
+            push("shadow.vpin_gap", vpin.gap());
+        }
+        push("shadow.phantom_liquidity_gap", shadow.phantom_liquidity.gap());
+        if let Some(gap) = shadow.mean_spread_gap() {
+            push("shadow.spread_gap_bps", gap);
+        }
+    }
+
+    let depth: HashMap<String, f64> = liquidity.depth_at_50bps.iter().map(|(asset, d)| (asset.clone(), usd(*d))).collect();
+    let component_pnl: HashMap<String, f64> = performance.components.iter().map(|c| (c.name.clone(), usd(c.realized_pnl))).collect();
+    let component_net: HashMap<String, f64> = performance.components.iter().map(|c| (c.name.clone(), usd(c.net_notional))).collect();
+    let component_exposure: HashMap<String, f64> = performance.components.iter().map(|c| (c.name.clone(), c.exposure_share)).collect();
+    let component_liquidation: HashMap<String, f64> =
+        performance.components.iter().filter_map(|c| Some((c.name.clone(), c.liquidation_distance?))).collect();
+    // By address: two vaults may share a display name.
+    let vault_tvl: HashMap<String, f64> = vault.vaults.iter().map(|v| (v.address.clone(), usd(v.tvl))).collect();
+    let vault_exposure: HashMap<String, f64> = vault.vaults.iter().map(|v| (v.address.clone(), usd(v.exposure))).collect();
+    let illiquid: HashMap<String, f64> = liquidity.illiquid_assets.iter().map(|coin| (coin.clone(), 1.0)).collect();
+    let account_equity: HashMap<String, f64> = vault.sub_accounts.iter().map(|a| (a.name.clone(), usd(a.equity))).collect();
+    let account_exposure: HashMap<String, f64> = vault.sub_accounts.iter().map(|a| (a.name.clone(), usd(a.exposure))).collect();
+    for (name, label, map) in [
+        ("performance.realized_spread", "coin", &performance.realized_spread),
+        ("liquidity.bid_ask_spread_bps", "coin", &liquidity.bid_ask_spread_bps),
+        ("liquidity.depth_at_50bps", "coin", &depth),
+        ("liquidity.order_book_imbalance", "coin", &liquidity.order_book_imbalance),
+        ("liquidity.fill_probability_by_distance", "distance", &liquidity.fill_probability_by_distance),
+        ("liquidity.trade_rate_per_min", "coin", &liquidity.trade_rate_per_min),
+        ("liquidity.illiquid", "coin", &illiquid),
+        ("risk.vpin_by_asset", "coin", &risk.vpin_by_asset),
+        ("risk.position_concentration", "coin", &risk.position_concentration),
+        ("risk.oracle_divergence", "coin", &risk.oracle_divergence),
+        ("risk.open_interest_cap_usage", "coin", &risk.open_interest_cap_usage),
+        ("performance.component_pnl", "component", &component_pnl),
+        ("performance.component_net_notional", "component", &component_net),
+        ("performance.component_exposure_share", "component", &component_exposure),
+        ("performance.component_liquidation_distance", "component", &component_liquidation),
+        ("vault.tvl_by_vault", "vault", &vault_tvl),
+        ("vault.exposure_by_vault", "vault", &vault_exposure),
+        ("vault.equity_by_account", "account", &account_equity),
+        ("vault.exposure_by_account", "account", &account_exposure),
+    ] {
+        let mut entries: Vec<(&String, &f64)> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in entries {
+            rows.push(FlatValue { name, key: Some((label, key.clone())), value: *value });
+        }
+    }
+
+    rows
+}
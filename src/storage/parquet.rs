@@ -0,0 +1,273 @@
+//! Just enough of the Parquet format to write flat tables: required columns only,
+//! PLAIN encoding, no compression, one row group and one data page per column.
+//! Files written here load directly into Polars, pandas/pyarrow and DuckDB.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const MAGIC: &[u8] = b"PAR1";
+
+// parquet.thrift enum values
+const TYPE_BOOLEAN: i32 = 0;
+const TYPE_INT64: i32 = 2;
+const TYPE_DOUBLE: i32 = 5;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const REPETITION_REQUIRED: i32 = 0;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+pub enum ColumnData {
+    Boolean(Vec<bool>),
+    Int64(Vec<i64>),
+    /// Milliseconds since the Unix epoch, read back as a UTC timestamp.
+    TimestampMillis(Vec<i64>),
+    Double(Vec<f64>),
+    Utf8(Vec<String>),
+}
+
+impl ColumnData {
+    fn len(&self) -> usize {
+        match self {
+            ColumnData::Boolean(v) => v.len(),
+            ColumnData::Int64(v) | ColumnData::TimestampMillis(v) => v.len(),
+            ColumnData::Double(v) => v.len(),
+            ColumnData::Utf8(v) => v.len(),
+        }
+    }
+
+    fn physical_type(&self) -> i32 {
+        match self {
+            ColumnData::Boolean(_) => TYPE_BOOLEAN,
+            ColumnData::Int64(_) | ColumnData::TimestampMillis(_) => TYPE_INT64,
+            ColumnData::Double(_) => TYPE_DOUBLE,
+            ColumnData::Utf8(_) => TYPE_BYTE_ARRAY,
+        }
+    }
+
+    fn converted_type(&self) -> Option<i32> {
+        match self {
+            ColumnData::TimestampMillis(_) => Some(CONVERTED_TIMESTAMP_MILLIS),
+            ColumnData::Utf8(_) => Some(CONVERTED_UTF8),
+            _ => None,
+        }
+    }
+
+    fn plain_encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            ColumnData::Boolean(values) => {
+                out.resize(values.len().div_ceil(8), 0);
+                for (i, _) in values.iter().enumerate().filter(|(_, v)| **v) {
+                    out[i / 8] |= 1 << (i % 8);
+                }
+            }
+            ColumnData::Int64(values) | ColumnData::TimestampMillis(values) => {
+                values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+            }
+            ColumnData::Double(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+            ColumnData::Utf8(values) => {
+                for value in values {
+                    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    out.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+        out
+    }
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub data: ColumnData,
+}
+
+impl Column {
+    pub fn new(name: &'static str, data: ColumnData) -> Self {
+        Self { name, data }
+    }
+}
+
+/// Writes `columns` as one table; every column must have the same number of rows.
+pub fn write_file(path: &Path, columns: &[Column]) -> Result<()> {
+    let num_rows = columns.first().map_or(0, |c| c.data.len());
+    if let Some(column) = columns.iter().find(|c| c.data.len() != num_rows) {
+        return Err(anyhow!("parquet column {} has {} rows, expected {}", column.name, column.data.len(), num_rows));
+    }
+
+    let mut file = MAGIC.to_vec();
+    let mut chunks = Vec::with_capacity(columns.len());
+
+    for column in columns {
+        let page = column.data.plain_encode();
+        let mut header = CompactWriter::default();
+        header.struct_begin();
+        header.i32_field(1, PAGE_DATA);
+        header.i32_field(2, page.len() as i32);
+        header.i32_field(3, page.len() as i32);
+        header.struct_field_begin(5);
+        header.i32_field(1, num_rows as i32);
+        header.i32_field(2, ENCODING_PLAIN);
+        header.i32_field(3, ENCODING_RLE);
+        header.i32_field(4, ENCODING_RLE);
+        header.struct_end();
+        header.struct_end();
+
+        let offset = file.len() as i64;
+        let size = (header.buf.len() + page.len()) as i64;
+        file.extend_from_slice(&header.buf);
+        file.extend_from_slice(&page);
+        chunks.push((column, offset, size));
+    }
+
+    let mut meta = CompactWriter::default();
+    meta.struct_begin();
+    meta.i32_field(1, 1);
+
+    meta.list_field(2, CompactType::Struct, columns.len() + 1);
+    meta.struct_begin();
+    meta.binary_field(4, b"schema");
+    meta.i32_field(5, columns.len() as i32);
+    meta.struct_end();
+    for column in columns {
+        meta.struct_begin();
+        meta.i32_field(1, column.data.physical_type());
+        meta.i32_field(3, REPETITION_REQUIRED);
+        meta.binary_field(4, column.name.as_bytes());
+        if let Some(converted) = column.data.converted_type() {
+            meta.i32_field(6, converted);
+        }
+        meta.struct_end();
+    }
+
+    meta.i64_field(3, num_rows as i64);
+
+    meta.list_field(4, CompactType::Struct, 1);
+    meta.struct_begin();
+    meta.list_field(1, CompactType::Struct, chunks.len());
+    for (column, offset, size) in &chunks {
+        meta.struct_begin();
+        meta.i64_field(2, *offset);
+        meta.struct_field_begin(3);
+        meta.i32_field(1, column.data.physical_type());
+        meta.list_field(2, CompactType::I32, 2);
+        meta.write_varint(zigzag(ENCODING_PLAIN as i64));
+        meta.write_varint(zigzag(ENCODING_RLE as i64));
+        meta.list_field(3, CompactType::Binary, 1);
+        meta.write_binary(column.name.as_bytes());
+        meta.i32_field(4, CODEC_UNCOMPRESSED);
+        meta.i64_field(5, num_rows as i64);
+        meta.i64_field(6, *size);
+        meta.i64_field(7, *size);
+        meta.i64_field(9, *offset);
+        meta.struct_end();
+        meta.struct_end();
+    }
+    meta.i64_field(2, chunks.iter().map(|(_, _, size)| size).sum());
+    meta.i64_field(3, num_rows as i64);
+    meta.struct_end();
+
+    meta.binary_field(6, concat!("hlp-toshogu version ", env!("CARGO_PKG_VERSION")).as_bytes());
+    meta.struct_end();
+
+    file.extend_from_slice(&meta.buf);
+    file.extend_from_slice(&(meta.buf.len() as u32).to_le_bytes());
+    file.extend_from_slice(MAGIC);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::File::create(path)?.write_all(&file)?;
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum CompactType {
+    I32 = 5,
+    I64 = 6,
+    Binary = 8,
+    List = 9,
+    Struct = 12,
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Thrift compact protocol, write side only.
+#[derive(Default)]
+struct CompactWriter {
+    buf: Vec<u8>,
+    last_field: Vec<i16>,
+}
+
+impl CompactWriter {
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn write_binary(&mut self, bytes: &[u8]) {
+        self.write_varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn field_header(&mut self, id: i16, kind: CompactType) {
+        let last = self.last_field.last_mut().expect("field written outside a struct");
+        let delta = id - std::mem::replace(last, id);
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | kind as u8);
+        } else {
+            self.buf.push(kind as u8);
+            self.write_varint(zigzag(id as i64));
+        }
+    }
+
+    fn struct_begin(&mut self) {
+        self.last_field.push(0);
+    }
+
+    fn struct_field_begin(&mut self, id: i16) {
+        self.field_header(id, CompactType::Struct);
+        self.struct_begin();
+    }
+
+    fn struct_end(&mut self) {
+        self.buf.push(0);
+        self.last_field.pop();
+    }
+
+    fn i32_field(&mut self, id: i16, value: i32) {
+        self.field_header(id, CompactType::I32);
+        self.write_varint(zigzag(value as i64));
+    }
+
+    fn i64_field(&mut self, id: i16, value: i64) {
+        self.field_header(id, CompactType::I64);
+        self.write_varint(zigzag(value));
+    }
+
+    fn binary_field(&mut self, id: i16, bytes: &[u8]) {
+        self.field_header(id, CompactType::Binary);
+        self.write_binary(bytes);
+    }
+
+    /// Writes a list header; the caller then writes `len` bare elements.
+    fn list_field(&mut self, id: i16, element: CompactType, len: usize) {
+        self.field_header(id, CompactType::List);
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | element as u8);
+        } else {
+            self.buf.push(0xF0 | element as u8);
+            self.write_varint(len as u64);
+        }
+    }
+}
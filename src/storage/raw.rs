@@ -0,0 +1,271 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use rust_decimal::prelude::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::parquet::{self, Column, ColumnData};
+use crate::config::RawCaptureSettings;
+use crate::model::{Fill, L2Snapshot, OrderAction, OrderEvent};
+use crate::pipeline::{Receiver, RecvError};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+
+/// One price level of a book snapshot; snapshots are stored one row per level so
+/// the files stay flat.
+struct BookLevel {
+    time: i64,
+    coin: String,
+    side: &'static str,
+    level: i64,
+    px: f64,
+    sz: f64,
+    orders: i64,
+}
+
+/// Buffered rows of one stream by the hour of their event time, so a late row
+/// joins its own hour rather than the one being filled.
+struct Partition<R> {
+    stream: &'static str,
+    hours: BTreeMap<i64, Vec<R>>,
+    rows: usize,
+}
+
+impl<R> Partition<R> {
+    fn new(stream: &'static str) -> Self {
+        Self { stream, hours: BTreeMap::new(), rows: 0 }
+    }
+
+    fn push(&mut self, hour: i64, rows: impl IntoIterator<Item = R>) {
+        let buffered = self.hours.entry(hour).or_default();
+        let before = buffered.len();
+        buffered.extend(rows);
+        self.rows += buffered.len() - before;
+    }
+}
+
+/// Records the WebSocket trade, book and order-event streams to hourly Parquet
+/// files under `dir`, laid out as `<stream>/date=YYYY-MM-DD/hour=HH/*.parquet` so
+/// readers can treat `date`/`hour` as Hive partitions. Rows are filed by their
+/// event time. An hour is written when the first row of a later one arrives, when
+/// the wall clock passes it, when a buffer reaches `max_rows_per_file`, and on
+/// shutdown; a row arriving after its hour was written goes out with the next
+/// minute's check, in a file of its own under that hour.
+pub struct RawCapture {
+    dir: PathBuf,
+    max_rows: usize,
+    /// The latest hour any stream has had a row from.
+    newest: Option<i64>,
+    trades: Partition<Fill>,
+    books: Partition<BookLevel>,
+    orders: Partition<OrderEvent>,
+}
+
+impl RawCapture {
+    pub fn new(settings: &RawCaptureSettings) -> Self {
+        Self {
+            dir: PathBuf::from(&settings.dir),
+            max_rows: settings.max_rows_per_file,
+            newest: None,
+            trades: Partition::new("trades"),
+            books: Partition::new("l2_snapshots"),
+            orders: Partition::new("order_events"),
+        }
+    }
+
+    pub async fn run(
+        mut self,
+        mut trade_rx: Receiver<Fill>,
+        mut l2_rx: Receiver<L2Snapshot>,
+        mut order_rx: Receiver<OrderEvent>,
+    ) {
+        info!("💾 Capturing raw streams to {}", self.dir.display());
+        let mut tick = tokio::time::interval(tokio::time::Duration::from_secs(60));
+
+        loop {
+            let result = tokio::select! {
+                fill = trade_rx.recv() => match fill {
+                    Ok(fill) => self.push_trade(fill),
+                    // Only on shutdown; Drop writes what is left.
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(skipped)) => Self::on_lag("trades", skipped),
+                },
+                snapshot = l2_rx.recv() => match snapshot {
+                    Ok(snapshot) => self.push_book(snapshot),
+                    // Only on shutdown; Drop writes what is left.
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(skipped)) => Self::on_lag("l2_snapshots", skipped),
+                },
+                event = order_rx.recv() => match event {
+                    Ok(event) => self.push_order(event),
+                    // Only on shutdown; Drop writes what is left.
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(skipped)) => Self::on_lag("order_events", skipped),
+                },
+                _ = tick.tick() => self.flush_elapsed(Utc::now().timestamp_millis()),
+            };
+
+            if let Err(e) = result {
+                warn!("⚠️ Raw capture write failed: {}", e);
+            }
+        }
+        info!("💾 Raw capture stopped; writing the remaining rows");
+    }
+
+    fn on_lag(stream: &str, skipped: u64) -> Result<()> {
+        debug!("💾 Raw capture lagged on {}, skipped {} messages", stream, skipped);
+        Ok(())
+    }
+
+    fn push_trade(&mut self, fill: Fill) -> Result<()> {
+        let hour = fill.time as i64 / HOUR_MS;
+        self.advance(hour)?;
+        self.trades.push(hour, [fill]);
+        if self.trades.rows >= self.max_rows {
+            write_trades(&self.dir, &mut self.trades, i64::MAX)?;
+        }
+        Ok(())
+    }
+
+    fn push_book(&mut self, snapshot: L2Snapshot) -> Result<()> {
+        let hour = snapshot.time as i64 / HOUR_MS;
+        self.advance(hour)?;
+        for (side, levels) in [("bid", &snapshot.bids), ("ask", &snapshot.asks)] {
+            self.books.push(hour, levels.iter().enumerate().map(|(i, level)| BookLevel {
+                time: snapshot.time as i64,
+                coin: snapshot.coin.clone(),
+                side,
+                level: i as i64,
+                px: level.px.to_f64().unwrap_or(0.0),
+                sz: level.sz.to_f64().unwrap_or(0.0),
+                orders: level.n as i64,
+            }));
+        }
+        if self.books.rows >= self.max_rows {
+            write_books(&self.dir, &mut self.books, i64::MAX)?;
+        }
+        Ok(())
+    }
+
+    fn push_order(&mut self, event: OrderEvent) -> Result<()> {
+        let hour = event.timestamp as i64 / HOUR_MS;
+        self.advance(hour)?;
+        self.orders.push(hour, [event]);
+        if self.orders.rows >= self.max_rows {
+            write_orders(&self.dir, &mut self.orders, i64::MAX)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the hours before `hour` once a row from it is the first of a later
+    /// hour than any seen; late rows wait for the next minute's check.
+    fn advance(&mut self, hour: i64) -> Result<()> {
+        if self.newest.is_some_and(|newest| newest >= hour) {
+            return Ok(());
+        }
+        self.newest = Some(hour);
+        self.roll(hour)
+    }
+
+    /// Writes every buffered stream's rows from hours before `hour`.
+    fn roll(&mut self, hour: i64) -> Result<()> {
+        write_trades(&self.dir, &mut self.trades, hour)?;
+        write_books(&self.dir, &mut self.books, hour)?;
+        write_orders(&self.dir, &mut self.orders, hour)
+    }
+
+    fn flush_elapsed(&mut self, now_ms: i64) -> Result<()> {
+        self.roll(now_ms / HOUR_MS)
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.roll(i64::MAX)
+    }
+}
+
+impl Drop for RawCapture {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("⚠️ Failed to write remaining raw capture rows: {}", e);
+        }
+    }
+}
+
+fn write_trades(dir: &std::path::Path, partition: &mut Partition<Fill>, before: i64) -> Result<()> {
+    write_partition(dir, partition, before, |rows| {
+        let usd = |f: fn(&Fill) -> Decimal| ColumnData::Double(rows.iter().map(|r| f(r).to_f64().unwrap_or(0.0)).collect());
+        vec![
+            Column::new("time", ColumnData::TimestampMillis(rows.iter().map(|r| r.time as i64).collect())),
+            Column::new("coin", ColumnData::Utf8(rows.iter().map(|r| r.coin.clone()).collect())),
+            Column::new("side", ColumnData::Utf8(rows.iter().map(|r| r.side.clone()).collect())),
+            Column::new("px", usd(|r| r.px)),
+            Column::new("sz", usd(|r| r.sz)),
+            Column::new("dir", ColumnData::Utf8(rows.iter().map(|r| r.dir.clone()).collect())),
+            Column::new("start_position", usd(|r| r.start_position)),
+            Column::new("closed_pnl", usd(|r| r.closed_pnl)),
+            Column::new("fee", usd(|r| r.fee)),
+            Column::new("crossed", ColumnData::Boolean(rows.iter().map(|r| r.crossed).collect())),
+            Column::new("oid", ColumnData::Int64(rows.iter().map(|r| r.oid as i64).collect())),
+            Column::new("hash", ColumnData::Utf8(rows.iter().map(|r| r.hash.clone()).collect())),
+        ]
+    })
+}
+
+fn write_books(dir: &std::path::Path, partition: &mut Partition<BookLevel>, before: i64) -> Result<()> {
+    write_partition(dir, partition, before, |rows| {
+        vec![
+            Column::new("time", ColumnData::TimestampMillis(rows.iter().map(|r| r.time).collect())),
+            Column::new("coin", ColumnData::Utf8(rows.iter().map(|r| r.coin.clone()).collect())),
+            Column::new("side", ColumnData::Utf8(rows.iter().map(|r| r.side.to_string()).collect())),
+            Column::new("level", ColumnData::Int64(rows.iter().map(|r| r.level).collect())),
+            Column::new("px", ColumnData::Double(rows.iter().map(|r| r.px).collect())),
+            Column::new("sz", ColumnData::Double(rows.iter().map(|r| r.sz).collect())),
+            Column::new("orders", ColumnData::Int64(rows.iter().map(|r| r.orders).collect())),
+        ]
+    })
+}
+
+fn write_orders(dir: &std::path::Path, partition: &mut Partition<OrderEvent>, before: i64) -> Result<()> {
+    write_partition(dir, partition, before, |rows| {
+        let action = |a: &OrderAction| match a {
+            OrderAction::New => "new",
+            OrderAction::Filled => "filled",
+            OrderAction::Cancelled => "cancelled",
+        };
+        vec![
+            Column::new("time", ColumnData::TimestampMillis(rows.iter().map(|r| r.timestamp as i64).collect())),
+            Column::new("oid", ColumnData::Int64(rows.iter().map(|r| r.id as i64).collect())),
+            Column::new("action", ColumnData::Utf8(rows.iter().map(|r| action(&r.action).to_string()).collect())),
+            Column::new("coin", ColumnData::Utf8(rows.iter().map(|r| r.coin.clone()).collect())),
+            Column::new("side", ColumnData::Utf8(rows.iter().map(|r| r.side.clone()).collect())),
+            Column::new("px", ColumnData::Double(rows.iter().map(|r| r.px.to_f64().unwrap_or(0.0)).collect())),
+            Column::new("sz", ColumnData::Double(rows.iter().map(|r| r.sz.to_f64().unwrap_or(0.0)).collect())),
+        ]
+    })
+}
+
+/// Writes and clears the buffered rows of every hour before `before`, one file per
+/// hour. Each write gets its own file, named by write time, so a busy hour may
+/// hold several. An hour that fails to write stays buffered.
+fn write_partition<R>(
+    dir: &std::path::Path,
+    partition: &mut Partition<R>,
+    before: i64,
+    columns: impl Fn(&[R]) -> Vec<Column>,
+) -> Result<()> {
+    while let Some(entry) = partition.hours.first_entry().filter(|entry| *entry.key() < before) {
+        let start = DateTime::<Utc>::from_timestamp_millis(*entry.key() * HOUR_MS).unwrap_or_default();
+        let file_name = format!("{}-{}.parquet", partition.stream, Utc::now().timestamp_millis());
+        let path = dir
+            .join(partition.stream)
+            .join(start.format("date=%Y-%m-%d").to_string())
+            .join(start.format("hour=%H").to_string())
+            .join(file_name);
+
+        parquet::write_file(&path, &columns(entry.get()))?;
+        debug!("💾 Wrote {} {} rows to {}", entry.get().len(), partition.stream, path.display());
+        partition.rows -= entry.remove().len();
+    }
+    Ok(())
+}
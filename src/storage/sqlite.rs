@@ -0,0 +1,257 @@
+//! Minimal binding to the system libsqlite3: just enough to open a database, run
+//! statements with positional parameters and read rows back.
+
+use anyhow::{anyhow, Result};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::marker::PhantomData;
+use std::ptr;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::ffi::{c_char, c_double, c_int, c_void};
+
+    pub enum sqlite3 {}
+    pub enum sqlite3_stmt {}
+
+    pub const SQLITE_OK: c_int = 0;
+    pub const SQLITE_ROW: c_int = 100;
+    pub const SQLITE_DONE: c_int = 101;
+
+    pub const SQLITE_OPEN_READONLY: c_int = 0x0000_0001;
+    pub const SQLITE_OPEN_READWRITE: c_int = 0x0000_0002;
+    pub const SQLITE_OPEN_CREATE: c_int = 0x0000_0004;
+    pub const SQLITE_OPEN_FULLMUTEX: c_int = 0x0001_0000;
+
+    #[link(name = "sqlite3")]
+    extern "C" {
+        pub fn sqlite3_open_v2(filename: *const c_char, db: *mut *mut sqlite3, flags: c_int, vfs: *const c_char) -> c_int;
+        pub fn sqlite3_close(db: *mut sqlite3) -> c_int;
+        pub fn sqlite3_errmsg(db: *mut sqlite3) -> *const c_char;
+        pub fn sqlite3_busy_timeout(db: *mut sqlite3, ms: c_int) -> c_int;
+        pub fn sqlite3_exec(
+            db: *mut sqlite3,
+            sql: *const c_char,
+            callback: *const c_void,
+            arg: *mut c_void,
+            errmsg: *mut *mut c_char,
+        ) -> c_int;
+        pub fn sqlite3_free(ptr: *mut c_void);
+        pub fn sqlite3_changes(db: *mut sqlite3) -> c_int;
+
+        pub fn sqlite3_prepare_v2(
+            db: *mut sqlite3,
+            sql: *const c_char,
+            n_byte: c_int,
+            stmt: *mut *mut sqlite3_stmt,
+            tail: *mut *const c_char,
+        ) -> c_int;
+        pub fn sqlite3_finalize(stmt: *mut sqlite3_stmt) -> c_int;
+        pub fn sqlite3_reset(stmt: *mut sqlite3_stmt) -> c_int;
+        pub fn sqlite3_clear_bindings(stmt: *mut sqlite3_stmt) -> c_int;
+        pub fn sqlite3_step(stmt: *mut sqlite3_stmt) -> c_int;
+
+        pub fn sqlite3_bind_int64(stmt: *mut sqlite3_stmt, index: c_int, value: i64) -> c_int;
+        pub fn sqlite3_bind_double(stmt: *mut sqlite3_stmt, index: c_int, value: c_double) -> c_int;
+        pub fn sqlite3_bind_text(
+            stmt: *mut sqlite3_stmt,
+            index: c_int,
+            value: *const c_char,
+            n_byte: c_int,
+            destructor: *const c_void,
+        ) -> c_int;
+
+        pub fn sqlite3_column_int64(stmt: *mut sqlite3_stmt, column: c_int) -> i64;
+        pub fn sqlite3_column_double(stmt: *mut sqlite3_stmt, column: c_int) -> c_double;
+        pub fn sqlite3_column_text(stmt: *mut sqlite3_stmt, column: c_int) -> *const u8;
+        pub fn sqlite3_column_bytes(stmt: *mut sqlite3_stmt, column: c_int) -> c_int;
+    }
+}
+
+/// Positional statement parameter, bound to `?1`, `?2`, ... in order.
+#[derive(Debug, Clone, Copy)]
+pub enum Param<'a> {
+    Int(i64),
+    Real(f64),
+    Text(&'a str),
+}
+
+pub struct Connection {
+    db: *mut ffi::sqlite3,
+}
+
+// Opened with SQLITE_OPEN_FULLMUTEX, so SQLite serializes access itself and the
+// handle may move between threads.
+unsafe impl Send for Connection {}
+
+impl Connection {
+    /// Opens `path` for reading and writing, creating it if needed.
+    pub fn open(path: &str) -> Result<Self> {
+        Self::open_with_flags(path, ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE)
+    }
+
+    pub fn open_read_only(path: &str) -> Result<Self> {
+        Self::open_with_flags(path, ffi::SQLITE_OPEN_READONLY)
+    }
+
+    fn open_with_flags(path: &str, flags: c_int) -> Result<Self> {
+        let filename = CString::new(path)?;
+        let flags = flags | ffi::SQLITE_OPEN_FULLMUTEX;
+        let mut db = ptr::null_mut();
+
+        let rc = unsafe { ffi::sqlite3_open_v2(filename.as_ptr(), &mut db, flags, ptr::null()) };
+        // A handle is returned even on failure so the error message can be read; closing it
+        // is left to Drop either way.
+        let conn = Self { db };
+        if rc != ffi::SQLITE_OK {
+            return Err(anyhow!("failed to open {}: {}", path, conn.error_message()));
+        }
+
+        unsafe { ffi::sqlite3_busy_timeout(conn.db, 5_000) };
+        Ok(conn)
+    }
+
+    /// Runs one or more `;`-separated statements that take no parameters.
+    pub fn execute_batch(&self, sql: &str) -> Result<()> {
+        let sql = CString::new(sql)?;
+        let mut errmsg: *mut c_char = ptr::null_mut();
+
+        let rc = unsafe { ffi::sqlite3_exec(self.db, sql.as_ptr(), ptr::null(), ptr::null_mut(), &mut errmsg) };
+        if rc == ffi::SQLITE_OK {
+            return Ok(());
+        }
+
+        let message = if errmsg.is_null() {
+            self.error_message()
+        } else {
+            let message = unsafe { CStr::from_ptr(errmsg) }.to_string_lossy().into_owned();
+            unsafe { ffi::sqlite3_free(errmsg as *mut c_void) };
+            message
+        };
+        Err(anyhow!("sqlite error: {}", message))
+    }
+
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>> {
+        let sql = CString::new(sql)?;
+        let mut stmt = ptr::null_mut();
+
+        let rc = unsafe { ffi::sqlite3_prepare_v2(self.db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut()) };
+        if rc != ffi::SQLITE_OK {
+            return Err(anyhow!("sqlite error: {}", self.error_message()));
+        }
+
+        Ok(Statement { conn: self, stmt })
+    }
+
+    /// Rows inserted, updated or deleted by the most recent statement.
+    pub fn changes(&self) -> usize {
+        unsafe { ffi::sqlite3_changes(self.db) as usize }
+    }
+
+    fn error_message(&self) -> String {
+        if self.db.is_null() {
+            return "out of memory".to_string();
+        }
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.db)) }.to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3_close(self.db) };
+    }
+}
+
+pub struct Statement<'c> {
+    conn: &'c Connection,
+    stmt: *mut ffi::sqlite3_stmt,
+}
+
+impl Statement<'_> {
+    /// Runs the statement to completion, discarding any rows.
+    pub fn execute(&mut self, params: &[Param]) -> Result<()> {
+        self.query(params, |_| ()).map(|_| ())
+    }
+
+    /// Runs the statement and maps every result row.
+    pub fn query<T>(&mut self, params: &[Param], mut map: impl FnMut(&Row) -> T) -> Result<Vec<T>> {
+        // Text is bound without copying (SQLITE_STATIC), which is sound because the
+        // borrowed params outlive every step below and the statement is reset and its
+        // bindings cleared before returning, so a later run binding fewer parameters
+        // neither reuses an earlier value nor points into freed text.
+        if let Err(e) = self.bind(params) {
+            unsafe { ffi::sqlite3_clear_bindings(self.stmt) };
+            return Err(e);
+        }
+
+        let mut rows = Vec::new();
+        let result = loop {
+            match unsafe { ffi::sqlite3_step(self.stmt) } {
+                ffi::SQLITE_ROW => rows.push(map(&Row { stmt: self.stmt, _statement: PhantomData })),
+                ffi::SQLITE_DONE => break Ok(rows),
+                _ => break Err(anyhow!("sqlite error: {}", self.conn.error_message())),
+            }
+        };
+
+        unsafe {
+            ffi::sqlite3_reset(self.stmt);
+            ffi::sqlite3_clear_bindings(self.stmt);
+        }
+        result
+    }
+
+    fn bind(&mut self, params: &[Param]) -> Result<()> {
+        for (i, param) in params.iter().enumerate() {
+            let index = i as c_int + 1;
+            let rc = unsafe {
+                match param {
+                    Param::Int(value) => ffi::sqlite3_bind_int64(self.stmt, index, *value),
+                    Param::Real(value) => ffi::sqlite3_bind_double(self.stmt, index, *value),
+                    Param::Text(value) => ffi::sqlite3_bind_text(
+                        self.stmt,
+                        index,
+                        value.as_ptr() as *const c_char,
+                        value.len() as c_int,
+                        ptr::null(),
+                    ),
+                }
+            };
+            if rc != ffi::SQLITE_OK {
+                return Err(anyhow!("sqlite error binding parameter {}: {}", index, self.conn.error_message()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Statement<'_> {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3_finalize(self.stmt) };
+    }
+}
+
+/// Current result row; columns are zero-based.
+pub struct Row<'s> {
+    stmt: *mut ffi::sqlite3_stmt,
+    _statement: PhantomData<&'s ()>,
+}
+
+impl Row<'_> {
+    pub fn int(&self, column: usize) -> i64 {
+        unsafe { ffi::sqlite3_column_int64(self.stmt, column as c_int) }
+    }
+
+    pub fn real(&self, column: usize) -> f64 {
+        unsafe { ffi::sqlite3_column_double(self.stmt, column as c_int) }
+    }
+
+    pub fn text(&self, column: usize) -> String {
+        unsafe {
+            let text = ffi::sqlite3_column_text(self.stmt, column as c_int);
+            if text.is_null() {
+                return String::new();
+            }
+            let len = ffi::sqlite3_column_bytes(self.stmt, column as c_int) as usize;
+            String::from_utf8_lossy(std::slice::from_raw_parts(text, len)).into_owned()
+        }
+    }
+}
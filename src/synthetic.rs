@@ -0,0 +1,609 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::*;
+use serde::Deserialize;
+
+use crate::config::{DemoSettings, Regime};
+use crate::history::CANDLE_BACKFILL_MS;
+use crate::metrics::{self, risk};
+use crate::model::*;
+
+/// The simulated assets as (coin, starting mid, tick, max leverage, book
+/// level size in coins).
+const ASSETS: [(&str, f64, f64, u32, f64); 3] = [
+    ("BTC", 67_500.0, 1.0, 50, 1.5),
+    ("ETH", 3_450.0, 0.1, 25, 30.0),
+    ("SOL", 165.0, 0.01, 20, 600.0),
+];
+/// The vault's positions as (coin, size, entry, isolated).
+const POSITIONS: [(&str, f64, f64, bool); 3] = [("BTC", 12.5, 66_900.0, false), ("ETH", -140.0, 3_520.0, false), ("SOL", 2_400.0, 158.0, true)];
+const BOOK_LEVELS: usize = 20;
+/// Trades the VPIN and the tape are computed from.
+const RECENT_TRADES: usize = 2_000;
+/// Finished orders the cancel and fleeting rates are computed from.
+const RECENT_ORDERS: usize = 5_000;
+/// Traders on the simulated tape.
+const TRADERS: usize = 12;
+/// Smoothing of the per-cycle layering, spoofing and realization readings.
+const SMOOTHING: f64 = 0.1;
+/// One-second cycles, sixty to the minute.
+const CYCLES_PER_MINUTE: u64 = 60;
+/// Per cycle, of how far each mid has strayed from a dataset's path.
+const DEVIATION_DECAY: f64 = 0.9;
+
+/// Datasets built in, by the name `[demo] dataset` takes.
+const BUNDLED: [(&str, &str); 1] = [("hour", include_str!("../demo/hour.toml"))];
+
+/// Prices the simulated market follows minute by minute, looping at the end,
+/// each minute under its own regime.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dataset {
+    /// The simulated assets, in order.
+    pub coins: Vec<String>,
+    /// Each asset's price as the first minute opens.
+    pub open: Vec<f64>,
+    pub minutes: Vec<Minute>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Minute {
+    pub regime: Regime,
+    /// Each asset's price as the minute closes.
+    pub close: Vec<f64>,
+}
+
+impl Dataset {
+    /// A bundled dataset by name, or a dataset file.
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        let (source, text) = match BUNDLED.iter().find(|(name, _)| *name == name_or_path) {
+            Some((name, text)) => (format!("bundled dataset {}", name), text.to_string()),
+            None => (
+                name_or_path.to_string(),
+                std::fs::read_to_string(name_or_path).with_context(|| {
+                    let names: Vec<&str> = BUNDLED.iter().map(|(name, _)| *name).collect();
+                    format!("{} is neither a bundled dataset ({}) nor a readable file", name_or_path, names.join(", "))
+                })?,
+            ),
+        };
+        let dataset: Dataset = toml::from_str(&text).with_context(|| format!("cannot parse {}", source))?;
+        let coins: Vec<&str> = ASSETS.iter().map(|asset| asset.0).collect();
+        if dataset.coins != coins {
+            return Err(anyhow!("{} must have the coins {:?}, got {:?}", source, coins, dataset.coins));
+        }
+        if dataset.minutes.is_empty() {
+            return Err(anyhow!("{} needs at least one minute", source));
+        }
+        let prices = std::iter::once(&dataset.open).chain(dataset.minutes.iter().map(|minute| &minute.close));
+        for (n, prices) in prices.enumerate() {
+            if prices.len() != coins.len() || prices.iter().any(|price| !price.is_finite() || *price <= 0.0) {
+                let row = if n == 0 { "open".to_string() } else { format!("minute {}", n) };
+                return Err(anyhow!("{}: {} needs a positive price for each of the {} coins", source, row, coins.len()));
+            }
+        }
+        Ok(dataset)
+    }
+}
+
+/// How the simulated market behaves, per collection cycle.
+struct RegimeParams {
+    /// Standard deviation of each asset's log return.
+    volatility: f64,
+    /// Of each asset's return with the market's.
+    correlation: f64,
+    /// Chance of a jump of several standard deviations.
+    jump_chance: f64,
+    max_spread_ticks: u32,
+    /// Scales the resting size at every level.
+    depth: f64,
+    /// Standard deviation of the log of each level's size and each trade's notional.
+    size_noise: f64,
+    trades: (usize, usize),
+    /// Share of trades on the side of the cycle's move.
+    informed: f64,
+    cancel_share: f64,
+    fleeting_share: f64,
+    mean_lifetime_ms: f64,
+    /// Chance an asset without a wall gets one, to be pulled a few cycles later.
+    wall_chance: f64,
+    /// Share of the volume the busiest trader takes.
+    top_trader_share: f64,
+    /// Capital utilization the vault drifts around.
+    utilization: f64,
+}
+
+impl Regime {
+    fn params(self) -> RegimeParams {
+        match self {
+            Regime::Calm => RegimeParams {
+                volatility: 0.0004,
+                correlation: 0.6,
+                jump_chance: 0.0,
+                max_spread_ticks: 2,
+                depth: 1.0,
+                size_noise: 0.15,
+                trades: (20, 40),
+                informed: 0.05,
+                cancel_share: 0.2,
+                fleeting_share: 0.03,
+                mean_lifetime_ms: 60_000.0,
+                wall_chance: 0.0,
+                top_trader_share: 0.1,
+                utilization: 0.6,
+            },
+            Regime::Volatile => RegimeParams {
+                volatility: 0.003,
+                correlation: 0.85,
+                jump_chance: 0.02,
+                max_spread_ticks: 8,
+                depth: 0.4,
+                size_noise: 0.5,
+                trades: (60, 120),
+                informed: 0.35,
+                cancel_share: 0.4,
+                fleeting_share: 0.08,
+                mean_lifetime_ms: 8_000.0,
+                wall_chance: 0.02,
+                top_trader_share: 0.15,
+                utilization: 0.8,
+            },
+            Regime::Manipulated => RegimeParams {
+                volatility: 0.001,
+                correlation: 0.4,
+                jump_chance: 0.005,
+                max_spread_ticks: 3,
+                depth: 0.8,
+                size_noise: 0.3,
+                trades: (30, 60),
+                informed: 0.2,
+                cancel_share: 0.7,
+                fleeting_share: 0.3,
+                mean_lifetime_ms: 2_000.0,
+                wall_chance: 0.3,
+                top_trader_share: 0.45,
+                utilization: 0.7,
+            },
+        }
+    }
+}
+
+/// A wall resting on one side of a book until it is pulled.
+#[derive(Debug, Clone, Copy)]
+struct Wall {
+    bid: bool,
+    level: usize,
+    cycles_left: u32,
+}
+
+/// A seeded simulated market for demo and test mode: correlated price paths,
+/// books, trades and order events for a few majors, from which the dashboard's
+/// metrics are computed as they are from live data. The same seed and regime
+/// reproduce the same market, cycle for cycle. With a dataset the mids follow
+/// its prices instead, straying and reverting around them.
+pub struct SyntheticMarket {
+    rng: StdRng,
+    params: RegimeParams,
+    meta: Meta,
+    mids: Vec<f64>,
+    dataset: Option<Dataset>,
+    /// Cycles played of the dataset.
+    cycle: u64,
+    /// Each mid's log distance from the dataset's path.
+    deviations: Vec<f64>,
+    walls: Vec<Option<Wall>>,
+    books: HashMap<String, L2Snapshot>,
+    trades: VecDeque<Fill>,
+    /// Finished orders as (lifetime in milliseconds, cancelled).
+    orders: VecDeque<(f64, bool)>,
+    traders: Vec<String>,
+    layering: f64,
+    spoofing: f64,
+    realization: f64,
+    utilization: f64,
+    /// Each asset's candle for the current minute.
+    candles: HashMap<String, Candle>,
+    /// Handed over with the first candles.
+    backfill: Vec<Candle>,
+    next_oid: u64,
+}
+
+impl SyntheticMarket {
+    pub fn new(settings: &DemoSettings) -> Self {
+        let seed = if settings.random_seed { rand::random() } else { settings.seed };
+        let dataset = settings.dataset.as_deref().and_then(|name| match Dataset::load(name) {
+            Ok(dataset) => {
+                info!("🎲 Playing {} minutes of {} from seed {}", dataset.minutes.len(), name, seed);
+                Some(dataset)
+            }
+            Err(e) => {
+                warn!("⚠️ Simulating a {:?} market instead of the dataset: {:#}", settings.regime, e);
+                None
+            }
+        });
+        if dataset.is_none() {
+            info!("🎲 Simulating a {:?} market from seed {}", settings.regime, seed);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let params = dataset.as_ref().map_or(settings.regime, |dataset| dataset.minutes[0].regime).params();
+        let mids = dataset.as_ref().map_or_else(|| ASSETS.iter().map(|asset| asset.1).collect(), |dataset| dataset.open.clone());
+        let traders = (0..TRADERS).map(|_| format!("0x{:040x}", rng.gen::<u128>())).collect();
+        let meta = Meta {
+            universe: ASSETS
+                .iter()
+                .map(|(coin, _, _, max_leverage, _)| AssetInfo {
+                    name: coin.to_string(),
+                    sz_decimals: 4,
+                    max_leverage: *max_leverage,
+                    only_isolated: false,
+                    is_delisted: false,
+                })
+                .collect(),
+            contexts: HashMap::new(),
+        };
+        let mut market = Self {
+            rng,
+            utilization: params.utilization,
+            params,
+            meta,
+            mids,
+            dataset,
+            cycle: 0,
+            deviations: vec![0.0; ASSETS.len()],
+            walls: vec![None; ASSETS.len()],
+            books: HashMap::new(),
+            trades: VecDeque::new(),
+            orders: VecDeque::new(),
+            traders,
+            layering: 0.0,
+            spoofing: 0.0,
+            realization: 1.0,
+            candles: HashMap::new(),
+            backfill: Vec::new(),
+            next_oid: 1,
+        };
+        market.backfill = market.backfill_candles();
+        market
+    }
+
+    /// Standard normal, by Box-Muller.
+    fn normal(&mut self) -> f64 {
+        let (u, v): (f64, f64) = (self.rng.gen_range(f64::EPSILON..1.0), self.rng.gen());
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+
+    /// Each asset's return this cycle: a shared market move, its own move and
+    /// now and then a jump.
+    fn returns(&mut self, volatility: f64) -> Vec<f64> {
+        let market = self.normal();
+        let rho = self.params.correlation;
+        (0..ASSETS.len())
+            .map(|_| {
+                let mut z = rho.sqrt() * market + (1.0 - rho).sqrt() * self.normal();
+                if self.rng.gen_bool(self.params.jump_chance) {
+                    z += self.normal().signum() * 5.0;
+                }
+                volatility * z
+            })
+            .collect()
+    }
+
+    /// Minute candles for the window a live backfill covers, walked back from
+    /// the starting prices.
+    fn backfill_candles(&mut self) -> Vec<Candle> {
+        let now_minute = chrono::Utc::now().timestamp() / 60;
+        let minutes = (CANDLE_BACKFILL_MS / 60_000) as i64;
+        let volatility = self.params.volatility * (CYCLES_PER_MINUTE as f64).sqrt();
+        let mut closes = self.mids.clone();
+        let mut candles = Vec::new();
+        for minute in (now_minute - minutes..now_minute).rev() {
+            let returns = self.returns(volatility);
+            for (i, (coin, ..)) in ASSETS.iter().enumerate() {
+                let close = closes[i];
+                let open = close * (-returns[i]).exp();
+                let wick = close * volatility * self.rng.gen_range(0.1..0.8);
+                let volume = self.rng.gen_range(20.0..200.0) * ASSETS[i].4 / 10.0;
+                candles.push(candle(coin, minute, open, open.max(close) + wick, open.min(close) - wick, close, volume));
+                closes[i] = open;
+            }
+        }
+        candles
+    }
+
+    /// Where the dataset has each asset's price this cycle, between the last
+    /// minute's close and this one's, taking on the minute's regime as it
+    /// starts. None without a dataset.
+    fn follow(&mut self) -> Option<Vec<f64>> {
+        let dataset = self.dataset.as_ref()?;
+        let index = (self.cycle / CYCLES_PER_MINUTE % dataset.minutes.len() as u64) as usize;
+        let minute = &dataset.minutes[index];
+        let from = if index == 0 { &dataset.open } else { &dataset.minutes[index - 1].close };
+        let into = (self.cycle % CYCLES_PER_MINUTE + 1) as f64 / CYCLES_PER_MINUTE as f64;
+        if self.cycle.is_multiple_of(CYCLES_PER_MINUTE) {
+            self.params = minute.regime.params();
+        }
+        self.cycle += 1;
+        Some(from.iter().zip(&minute.close).map(|(from, to)| from * (to / from).powf(into)).collect())
+    }
+
+    /// Moves the market on by one collection cycle.
+    pub fn step(&mut self) {
+        let path = self.follow();
+        let returns = self.returns(self.params.volatility);
+        let time = chrono::Utc::now().timestamp_millis() as u64;
+        let mut pulled = Vec::new();
+        for (i, &(coin, _, tick, _, level_size)) in ASSETS.iter().enumerate() {
+            let before = self.mids[i];
+            match &path {
+                Some(path) => {
+                    self.deviations[i] = DEVIATION_DECAY * self.deviations[i] + returns[i];
+                    self.mids[i] = path[i] * self.deviations[i].exp();
+                }
+                None => self.mids[i] *= returns[i].exp(),
+            }
+            let mid = self.mids[i];
+            self.trade(coin, mid, (mid / before).ln(), time);
+            pulled.extend(self.quote(i, coin, mid, tick, level_size, time));
+            self.order_events();
+            self.update_candle(coin, mid, time);
+        }
+        let assets = ASSETS.len() as f64;
+        let realized = 1.0 - pulled.iter().map(|resting| 1.0 - resting).sum::<f64>() / assets;
+        let layered = self.walls.iter().filter(|wall| wall.is_some()).count() as f64 / assets;
+        let spoofed = if pulled.is_empty() { 0.0 } else { 1.0 };
+        self.realization += SMOOTHING * (realized - self.realization);
+        self.layering += SMOOTHING * (layered - self.layering);
+        self.spoofing += SMOOTHING * (spoofed - self.spoofing);
+        let target = self.params.utilization;
+        self.utilization = (self.utilization + 0.1 * (target - self.utilization) + 0.01 * self.normal()).clamp(0.0, 1.0);
+    }
+
+    fn trade(&mut self, coin: &str, mid: f64, ret: f64, time: u64) {
+        let count = self.rng.gen_range(self.params.trades.0..=self.params.trades.1);
+        for n in 0..count {
+            let buy = match self.rng.gen_bool(self.params.informed) {
+                true => ret >= 0.0,
+                false => self.rng.gen_bool(0.5),
+            };
+            let notional = (300f64.ln() + self.params.size_noise * self.normal()).exp();
+            let px = mid * (1.0 + if buy { 1.0 } else { -1.0 } * self.rng.gen_range(0.0..0.0002));
+            let top = self.rng.gen_bool(self.params.top_trader_share);
+            let (a, b) = match top {
+                true => (0, self.rng.gen_range(1..TRADERS)),
+                false => (self.rng.gen_range(1..TRADERS), self.rng.gen_range(1..TRADERS)),
+            };
+            let side = if buy { "B" } else { "A" };
+            self.trades.push_back(Fill {
+                coin: coin.to_string(),
+                px: decimal(px, 2),
+                sz: decimal(notional / px, 5),
+                side: side.to_string(),
+                time: time + n as u64,
+                start_position: Decimal::ZERO,
+                dir: side.to_string(),
+                closed_pnl: Decimal::ZERO,
+                hash: String::new(),
+                oid: self.next_oid,
+                crossed: true,
+                fee: Decimal::ZERO,
+                builder_fee: Decimal::ZERO,
+                expected_fee: None,
+                liquidation: None,
+                users: vec![self.traders[a].clone(), self.traders[b].clone()],
+            });
+            self.next_oid += 1;
+            if self.trades.len() > RECENT_TRADES {
+                self.trades.pop_front();
+            }
+        }
+    }
+
+    /// Rebuilds the asset's book around `mid`. A wall is pulled a few cycles
+    /// after it went up, before anything trades against it; the share of the
+    /// previous book's size still resting is returned then.
+    fn quote(&mut self, asset: usize, coin: &str, mid: f64, tick: f64, level_size: f64, time: u64) -> Option<f64> {
+        let mut pulled = None;
+        match self.walls[asset] {
+            Some(wall) if wall.cycles_left == 0 => {
+                self.walls[asset] = None;
+                pulled = Some(1.0);
+                if let Some(book) = self.books.get(coin) {
+                    let total: Decimal = book.bids.iter().chain(&book.asks).map(|level| level.sz).sum();
+                    let side = if wall.bid { &book.bids } else { &book.asks };
+                    if let (Some(level), false) = (side.get(wall.level), total.is_zero()) {
+                        pulled = Some(1.0 - (level.sz / total).to_f64().unwrap_or(0.0));
+                    }
+                }
+            }
+            Some(wall) => self.walls[asset] = Some(Wall { cycles_left: wall.cycles_left - 1, ..wall }),
+            None if self.rng.gen_bool(self.params.wall_chance) => {
+                self.walls[asset] = Some(Wall { bid: self.rng.gen_bool(0.5), level: self.rng.gen_range(3..8), cycles_left: self.rng.gen_range(2..6) });
+            }
+            None => {}
+        }
+
+        let half_spread = self.rng.gen_range(1..=self.params.max_spread_ticks) as f64 * tick / 2.0;
+        let wall = self.walls[asset];
+        let mut side = |bid: bool| -> Vec<OrderBookLevel> {
+            (0..BOOK_LEVELS)
+                .map(|i| {
+                    let offset = half_spread + i as f64 * tick;
+                    let px = if bid { mid - offset } else { mid + offset };
+                    let mut sz = level_size * self.params.depth * (1.0 + 0.3 * i as f64) * (self.params.size_noise * self.normal()).exp();
+                    let mut n = self.rng.gen_range(1..8);
+                    if wall.is_some_and(|wall| wall.bid == bid && wall.level == i) {
+                        sz *= 15.0;
+                        n = 1;
+                    }
+                    OrderBookLevel { px: decimal(px, 2), sz: decimal(sz, 4), n }
+                })
+                .collect()
+        };
+        let bids = side(true);
+        let asks = side(false);
+        self.books.insert(coin.to_string(), L2Snapshot { coin: coin.to_string(), time, bids, asks });
+        pulled
+    }
+
+    /// Orders placed and finished this cycle, some cancelled, some fleeting.
+    fn order_events(&mut self) {
+        let count = self.rng.gen_range(self.params.trades.0..=self.params.trades.1) * 3;
+        for _ in 0..count {
+            let lifetime = match self.rng.gen_bool(self.params.fleeting_share) {
+                true => self.rng.gen_range(5.0..100.0),
+                false => -self.params.mean_lifetime_ms * self.rng.gen_range(f64::EPSILON..1.0).ln(),
+            };
+            let cancelled = self.rng.gen_bool(self.params.cancel_share);
+            self.orders.push_back((lifetime, cancelled));
+            if self.orders.len() > RECENT_ORDERS {
+                self.orders.pop_front();
+            }
+        }
+    }
+
+    fn update_candle(&mut self, coin: &str, mid: f64, time: u64) {
+        let minute = (time / 60_000) as i64;
+        let traded: Vec<&Fill> = self.trades.iter().rev().take_while(|fill| fill.time >= time).filter(|fill| fill.coin == coin).collect();
+        let volume: Decimal = traded.iter().map(|fill| fill.sz).sum();
+        let current = self.candles.entry(coin.to_string()).or_insert_with(|| candle(coin, minute, mid, mid, mid, mid, 0.0));
+        if current.open_time != (minute * 60_000) as u64 {
+            let open = current.close.to_f64().unwrap_or(mid);
+            *current = candle(coin, minute, open, open.max(mid), open.min(mid), mid, 0.0);
+        }
+        current.high = current.high.max(decimal(mid, 2));
+        current.low = current.low.min(decimal(mid, 2));
+        current.close = decimal(mid, 2);
+        current.volume += volume.round_dp(2);
+        current.trades += traded.len() as u64;
+    }
+
+    /// The backfill on the first call, then each asset's current candle.
+    pub fn take_candles(&mut self) -> Vec<Candle> {
+        let mut candles = std::mem::take(&mut self.backfill);
+        candles.extend(self.candles.values().cloned());
+        candles
+    }
+
+    /// Fills `metrics` with the market as it stands: books, the vault's quotes
+    /// and positions marked to the mids, the tape, and the liquidity and risk
+    /// metrics computed from them.
+    pub fn apply(&self, metrics: &mut GlobalMetrics) {
+        let fills: Vec<Fill> = self.trades.iter().cloned().collect();
+        let computed = metrics::calculate_liquidity_metrics(&self.books, &fills, &self.meta);
+        let liquidity = &mut metrics.liquidity_metrics;
+        liquidity.bid_ask_spread_bps = computed.bid_ask_spread_bps;
+        liquidity.depth_at_50bps = computed.depth_at_50bps;
+        liquidity.order_book_imbalance = computed.order_book_imbalance;
+        metrics.order_books = self.books.clone();
+        metrics.open_orders = self.open_orders();
+        metrics.positions = self.positions();
+        metrics.trade_tape = fills.iter().rev().take(metrics::streaming::TAPE_LEN).map(TapeTrade::from).collect();
+        metrics.risk_metrics.vpin_by_asset = risk::calculate_vpin_by_asset(&fills);
+        self.overlay(metrics);
+    }
+
+    /// Puts the simulated VPIN, phantom liquidity, liquidation risk and
+    /// utilization on `metrics`, which may come from live data.
+    pub fn overlay(&self, metrics: &mut GlobalMetrics) {
+        let fills: Vec<Fill> = self.trades.iter().cloned().collect();
+        let liquidity = &mut metrics.liquidity_metrics;
+        let orders = self.orders.len().max(1) as f64;
+        liquidity.cancel_rate = self.orders.iter().filter(|(_, cancelled)| *cancelled).count() as f64 / orders;
+        liquidity.fleeting_order_ratio = self.orders.iter().filter(|(lifetime, _)| *lifetime < 100.0).count() as f64 / orders;
+        liquidity.avg_order_lifetime_ms = self.orders.iter().map(|(lifetime, _)| lifetime).sum::<f64>() / orders;
+        liquidity.layering_detection_score = self.layering;
+        liquidity.spoofing_detection_index = self.spoofing;
+        liquidity.liquidity_realization_rate = self.realization;
+
+        let risk = &mut metrics.risk_metrics;
+        risk.vpin_score = risk::calculate_vpin(&fills, &self.meta);
+        risk.phantom_liquidity_index = risk::calculate_phantom_liquidity_index(&metrics.liquidity_metrics);
+        risk.liquidation_risk_score = self.positions().iter().map(liquidation_risk).fold(0.0, f64::max);
+
+        let vault = &mut metrics.vault_metrics;
+        vault.utilization_rate = self.utilization;
+        vault.deployed_liquidity = decimal(vault.tvl.to_f64().unwrap_or(0.0) * self.utilization, 0);
+        vault.idle_liquidity = vault.tvl - vault.deployed_liquidity;
+        metrics.last_update = Some(chrono::Utc::now());
+    }
+
+    fn positions(&self) -> Vec<Position> {
+        POSITIONS
+            .iter()
+            .map(|&(symbol, size, entry, isolated)| {
+                let mark = self.mid(symbol);
+                Position {
+                    symbol: symbol.to_string(),
+                    size: decimal(size, 4),
+                    entry_px: Some(decimal(entry, 2)),
+                    position_value: decimal(size.abs() * mark, 2),
+                    unrealized_pnl: decimal(size * (mark - entry), 2),
+                    margin_used: decimal(size.abs() * mark / 10.0, 2),
+                    isolated,
+                    leverage: Some(10),
+                    liquidation_px: Some(decimal(entry * (1.0 - size.signum() * 0.09), 2)),
+                }
+            })
+            .collect()
+    }
+
+    /// A few resting vault quotes to highlight on the depth ladder.
+    fn open_orders(&self) -> Vec<OpenOrder> {
+        let time = chrono::Utc::now().timestamp_millis() as u64;
+        ASSETS
+            .iter()
+            .flat_map(|&(coin, _, tick, ..)| {
+                let mid = self.mid(coin);
+                [("B", -2.0), ("A", 3.0), ("B", -30.0)].map(|(side, offset)| (coin, side, mid + offset * tick))
+            })
+            .enumerate()
+            .map(|(i, (coin, side, px))| OpenOrder {
+                coin: coin.to_string(),
+                side: side.to_string(),
+                limit_px: decimal(px, 2),
+                sz: decimal(0.5, 3),
+                oid: i as u64 + 1,
+                timestamp: time,
+            })
+            .collect()
+    }
+
+    fn mid(&self, coin: &str) -> f64 {
+        ASSETS.iter().position(|asset| asset.0 == coin).map_or(0.0, |i| self.mids[i])
+    }
+}
+
+/// How far the mark has come from the entry towards the liquidation price.
+fn liquidation_risk(position: &Position) -> f64 {
+    let (Some(entry), Some(liquidation)) = (position.entry_px, position.liquidation_px) else {
+        return 0.0;
+    };
+    let mark = position.position_value / position.size.abs();
+    let span = (entry - liquidation).abs();
+    if span.is_zero() {
+        return 0.0;
+    }
+    (1.0 - ((mark - liquidation).abs() / span).to_f64().unwrap_or(1.0)).clamp(0.0, 1.0)
+}
+
+fn candle(coin: &str, minute: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> Candle {
+    Candle {
+        coin: coin.to_string(),
+        open_time: (minute * 60_000) as u64,
+        close_time: (minute * 60_000 + 59_999) as u64,
+        open: decimal(open, 2),
+        high: decimal(high, 2),
+        low: decimal(low, 2),
+        close: decimal(close, 2),
+        volume: decimal(volume, 2),
+        trades: 0,
+    }
+}
+
+fn decimal(value: f64, dp: u32) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(dp)
+}
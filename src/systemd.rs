@@ -0,0 +1,122 @@
+use chrono::Utc;
+use log::{debug, info, warn};
+use std::os::unix::net::UnixDatagram;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::shared::SharedMetrics;
+
+/// How often the supervisor checks the collection loop when there is no watchdog.
+const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Sends `state` (e.g. `READY=1`) to the service manager over `$NOTIFY_SOCKET`, as
+/// `sd_notify(3)` does. Returns false when not started by systemd with
+/// `Type=notify`, or if the message could not be sent.
+pub fn notify(state: &str) -> bool {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return false;
+    };
+    let sent = UnixDatagram::unbound().and_then(|socket| send(&socket, &path.to_string_lossy(), state));
+    match sent {
+        Ok(_) => true,
+        Err(e) => {
+            debug!("⚙️ sd_notify {:?} failed: {}", state, e);
+            false
+        }
+    }
+}
+
+/// A leading '@' names a socket in the abstract namespace, which only Linux has.
+#[cfg(target_os = "linux")]
+fn send(socket: &UnixDatagram, path: &str, state: &str) -> std::io::Result<usize> {
+    use std::os::linux::net::SocketAddrExt;
+    match path.strip_prefix('@') {
+        Some(name) => socket.send_to_addr(state.as_bytes(), &std::os::unix::net::SocketAddr::from_abstract_name(name)?),
+        None => socket.send_to(state.as_bytes(), path),
+    }
+}
+
+/// Elsewhere only filesystem socket paths exist.
+#[cfg(not(target_os = "linux"))]
+fn send(socket: &UnixDatagram, path: &str, state: &str) -> std::io::Result<usize> {
+    if path.starts_with('@') {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "abstract sockets exist only on Linux"));
+    }
+    socket.send_to(state.as_bytes(), path)
+}
+
+/// The unit's `WatchdogSec`, if the watchdog is enabled for this process.
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    match std::env::var("WATCHDOG_PID").ok().map(|pid| pid.parse::<u32>()) {
+        Some(Ok(pid)) if pid != std::process::id() => None,
+        _ => (usec > 0).then(|| Duration::from_micros(usec)),
+    }
+}
+
+/// Reports start-up and health to systemd: `READY=1` once the first metrics
+/// arrive, then `WATCHDOG=1` at half the watchdog interval for as long as the
+/// collection loop keeps completing cycles. A loop that has not finished a cycle
+/// within `stall_timeout` stops the pings, so systemd restarts the process. Each
+/// check also updates the `STATUS=` line shown by `systemctl status`.
+pub async fn supervise(metrics: Arc<SharedMetrics>, stall_timeout: Duration) {
+    let watchdog = watchdog_interval();
+    let mut interval = tokio::time::interval(watchdog.map_or(STATUS_INTERVAL, |w| w / 2));
+    let mut ready = false;
+    let mut stalled = false;
+
+    loop {
+        interval.tick().await;
+        let (last_update, last_cycle, active_alerts) = {
+            let status = metrics.status.read().await;
+            (metrics.last_update().await, status.last_cycle, status.active_alerts)
+        };
+
+        if !ready {
+            if last_update.is_none() {
+                notify("STATUS=Waiting for first metrics");
+                continue;
+            }
+            ready = true;
+            notify("READY=1");
+            if let Some(watchdog) = watchdog {
+                info!("⚙️ Signalled systemd ready; watchdog every {}s", watchdog.as_secs_f64());
+            }
+        }
+
+        let since_cycle = last_cycle.map(|at| (Utc::now() - at).to_std().unwrap_or_default());
+        if since_cycle.is_some_and(|age| age <= stall_timeout) {
+            if stalled {
+                info!("⚙️ Collection loop resumed");
+            }
+            stalled = false;
+            if watchdog.is_some() {
+                notify("WATCHDOG=1");
+            }
+            let last = last_update.map_or_else(|| "never".to_string(), |at| at.format("%H:%M:%S UTC").to_string());
+            notify(&format!("STATUS=Collecting; last update {}, {} active alerts", last, active_alerts));
+        } else {
+            if !stalled {
+                warn!("⚠️ No collection cycle finished in over {}s; withholding the systemd watchdog", stall_timeout.as_secs());
+            }
+            stalled = true;
+            notify(&format!("STATUS=Collection loop stalled for over {}s", stall_timeout.as_secs()));
+        }
+    }
+}
+
+/// Resolves on SIGTERM (what `systemctl stop` sends) or Ctrl-C.
+pub async fn shutdown_signal() {
+    let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+        Ok(signal) => signal,
+        Err(e) => {
+            warn!("⚠️ Cannot listen for SIGTERM: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+    tokio::select! {
+        _ = sigterm.recv() => info!("🛑 Received SIGTERM, shutting down"),
+        _ = tokio::signal::ctrl_c() => info!("🛑 Received Ctrl-C, shutting down"),
+    }
+}
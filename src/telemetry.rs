@@ -0,0 +1,320 @@
+//! Exports `tracing` spans of the collection pipeline to an OpenTelemetry collector
+//! (Jaeger, Tempo, ...) over OTLP/HTTP with JSON encoding. Only this crate's spans
+//! are collected; logging stays on the `log` crate.
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::{Interest, Subscriber};
+use tracing::{Event, Level, Metadata};
+use tracing_core::span::Current;
+
+use crate::config::TelemetrySettings;
+
+/// Finished spans held while the collector is unreachable; older ones are dropped.
+const MAX_QUEUED_SPANS: usize = 10_000;
+
+const SPAN_KIND_INTERNAL: u8 = 1;
+const STATUS_CODE_ERROR: u8 = 2;
+
+thread_local! {
+    /// Spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<Id>> = const { RefCell::new(Vec::new()) };
+}
+
+enum AttrValue {
+    Str(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+impl AttrValue {
+    fn to_otlp(&self) -> Value {
+        match self {
+            AttrValue::Str(v) => json!({ "stringValue": v }),
+            // OTLP JSON carries 64-bit integers as strings.
+            AttrValue::Int(v) => json!({ "intValue": v.to_string() }),
+            AttrValue::Double(v) => json!({ "doubleValue": v }),
+            AttrValue::Bool(v) => json!({ "boolValue": v }),
+        }
+    }
+}
+
+struct SpanData {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    metadata: &'static Metadata<'static>,
+    start: SystemTime,
+    attributes: Vec<(&'static str, AttrValue)>,
+    refs: usize,
+}
+
+struct FieldRecorder<'a>(&'a mut Vec<(&'static str, AttrValue)>);
+
+impl Visit for FieldRecorder<'_> {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.push((field.name(), AttrValue::Int(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push((field.name(), AttrValue::Int(value as i64)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.push((field.name(), AttrValue::Double(value)));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.push((field.name(), AttrValue::Bool(value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), AttrValue::Str(value.to_string())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push((field.name(), AttrValue::Str(format!("{:?}", value))));
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    next_id: AtomicU64,
+    open: Mutex<HashMap<u64, SpanData>>,
+    finished: Mutex<Vec<(SpanData, SystemTime)>>,
+    dropped: AtomicU64,
+}
+
+/// A minimal `tracing` subscriber that builds OTLP spans: parents come from an
+/// explicit `parent:` or the span entered on the current thread, and a span ends
+/// when its last handle is dropped.
+struct Collector {
+    shared: Arc<Shared>,
+    max_level: Level,
+}
+
+impl Collector {
+    fn wants(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span() && metadata.target().starts_with("hlp_toshogu") && *metadata.level() <= self.max_level
+    }
+}
+
+impl Subscriber for Collector {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if self.wants(metadata) { Interest::always() } else { Interest::never() }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.wants(metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.max_level))
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let parent = match attrs.parent() {
+            Some(parent) => Some(parent.clone()),
+            None if attrs.is_contextual() => ENTERED.with(|entered| entered.borrow().last().cloned()),
+            None => None,
+        };
+
+        let mut open = self.shared.open.lock().unwrap_or_else(|e| e.into_inner());
+        let parent = parent.and_then(|id| open.get(&id.into_u64()).map(|p| (p.trace_id, p.span_id)));
+        let mut attributes = Vec::new();
+        attrs.record(&mut FieldRecorder(&mut attributes));
+
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        open.insert(
+            id,
+            SpanData {
+                trace_id: parent.map_or_else(rand::random, |(trace_id, _)| trace_id),
+                span_id: rand::random(),
+                parent_span_id: parent.map(|(_, span_id)| span_id),
+                metadata: attrs.metadata(),
+                start: SystemTime::now(),
+                attributes,
+                refs: 1,
+            },
+        );
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(data) = self.shared.open.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&span.into_u64()) {
+            values.record(&mut FieldRecorder(&mut data.attributes));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(pos) = entered.iter().rposition(|id| id == span) {
+                entered.remove(pos);
+            }
+        });
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        if let Some(data) = self.shared.open.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&id.into_u64()) {
+            data.refs += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        let mut open = self.shared.open.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(data) = open.get_mut(&id.into_u64()) else {
+            return false;
+        };
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+
+        let data = open.remove(&id.into_u64()).expect("span looked up above");
+        drop(open);
+        let mut finished = self.shared.finished.lock().unwrap_or_else(|e| e.into_inner());
+        if finished.len() >= MAX_QUEUED_SPANS {
+            finished.remove(0);
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        finished.push((data, SystemTime::now()));
+        true
+    }
+
+    fn current_span(&self) -> Current {
+        let Some(id) = ENTERED.with(|entered| entered.borrow().last().cloned()) else {
+            return Current::none();
+        };
+        let open = self.shared.open.lock().unwrap_or_else(|e| e.into_inner());
+        match open.get(&id.into_u64()) {
+            Some(data) => Current::new(id, data.metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+/// Marks `span` as failed with the error's message; the span must declare an
+/// empty `error` field.
+pub fn record_error<T>(span: &tracing::Span, result: &Result<T>) {
+    if let Err(e) = result {
+        span.record("error", e.to_string().as_str());
+    }
+}
+
+/// Installs the span collector as the global `tracing` subscriber and starts
+/// exporting to `settings.endpoint` every `export_interval_secs`.
+pub fn init(settings: &TelemetrySettings) -> Result<()> {
+    let max_level = Level::from_str(&settings.level).map_err(|_| anyhow!("invalid telemetry.level {}", settings.level))?;
+    let shared = Arc::new(Shared::default());
+    tracing::subscriber::set_global_default(Collector { shared: shared.clone(), max_level })?;
+
+    info!("🔭 Exporting traces to {} as {}", settings.endpoint, settings.service_name);
+    tokio::spawn(export_loop(shared, settings.clone()));
+    Ok(())
+}
+
+async fn export_loop(shared: Arc<Shared>, settings: TelemetrySettings) {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+    let mut interval = tokio::time::interval(Duration::from_secs(settings.export_interval_secs));
+    let mut failing = false;
+
+    loop {
+        interval.tick().await;
+        let spans = std::mem::take(&mut *shared.finished.lock().unwrap_or_else(|e| e.into_inner()));
+        let dropped = shared.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            debug!("🔭 Dropped {} spans while the collector was unreachable", dropped);
+        }
+        if spans.is_empty() {
+            continue;
+        }
+
+        let count = spans.len();
+        let body = otlp_request(&settings.service_name, &spans);
+        let sent = client.post(&settings.endpoint).json(&body).send().await.and_then(|r| r.error_for_status());
+        match sent {
+            Ok(_) => {
+                if failing {
+                    info!("🔭 Trace export recovered");
+                }
+                failing = false;
+                debug!("🔭 Exported {} spans", count);
+            }
+            Err(e) => {
+                if !failing {
+                    warn!("⚠️ Trace export to {} failed: {}", settings.endpoint, e);
+                }
+                failing = true;
+                // Put the batch back so a short collector outage loses nothing.
+                let mut finished = shared.finished.lock().unwrap_or_else(|e| e.into_inner());
+                let keep = MAX_QUEUED_SPANS.saturating_sub(finished.len());
+                let mut requeued: Vec<_> = spans.into_iter().rev().take(keep).collect();
+                requeued.reverse();
+                finished.splice(0..0, requeued);
+            }
+        }
+    }
+}
+
+/// An `ExportTraceServiceRequest` in the OTLP JSON encoding.
+fn otlp_request(service_name: &str, spans: &[(SpanData, SystemTime)]) -> Value {
+    let unix_nanos = |t: SystemTime| t.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string();
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|(span, end)| {
+            let error = span.attributes.iter().find(|(key, _)| *key == "error");
+            let mut value = json!({
+                "traceId": hex(&span.trace_id),
+                "spanId": hex(&span.span_id),
+                "name": span.metadata.name(),
+                "kind": SPAN_KIND_INTERNAL,
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(*end),
+                "attributes": span.attributes.iter().map(|(key, v)| json!({ "key": key, "value": v.to_otlp() })).collect::<Vec<_>>(),
+            });
+            if let Some(parent) = span.parent_span_id {
+                value["parentSpanId"] = json!(hex(&parent));
+            }
+            if let Some((_, AttrValue::Str(message))) = error {
+                value["status"] = json!({ "code": STATUS_CODE_ERROR, "message": message });
+            }
+            value
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [{ "key": "service.name", "value": { "stringValue": service_name } }] },
+            "scopeSpans": [{ "scope": { "name": "hlp-toshogu", "version": env!("CARGO_PKG_VERSION") }, "spans": spans }],
+        }]
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut out, b| {
+        let _ = write!(out, "{:02x}", b);
+        out
+    })
+}
@@ -0,0 +1,334 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::alert::check_alerts;
+use crate::config::AlertThresholds;
+use crate::model::{AlertLevel, GlobalMetrics};
+use crate::report::ReportFormat;
+use crate::storage::MetricStore;
+
+/// Name of the row for the thresholds in the config.
+const CONFIGURED: &str = "configured";
+
+/// Alerts whose inputs the metric store does not keep, so a replay never
+/// raises them whatever the thresholds.
+pub const NOT_REPLAYED: [&str; 5] = ["Vault Withdrawals", "Backstop Drawdown", "Chain Stall", "OI Cap", "New Listing"];
+
+/// A `tune` file: candidate thresholds, and the incidents they should catch.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TuningFile {
+    /// A `name` and the `[alert_thresholds]` settings the candidate changes.
+    #[serde(default)]
+    candidates: Vec<toml::Table>,
+    #[serde(default)]
+    incidents: Vec<Incident>,
+}
+
+/// A stretch of history the alerts should have caught.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Incident {
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Alerts that count as catching it, by metric name; any alert when empty.
+    #[serde(default)]
+    pub expect: Vec<String>,
+}
+
+impl Incident {
+    fn covers(&self, time: DateTime<Utc>) -> bool {
+        self.start <= time && time <= self.end
+    }
+
+    fn expects(&self, metric: &str) -> bool {
+        self.expect.is_empty() || self.expect.iter().any(|expected| expected == metric)
+    }
+}
+
+/// One set of thresholds to replay the history against.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub name: String,
+    pub thresholds: AlertThresholds,
+}
+
+/// The candidates of the file at `path`, each the configured thresholds with
+/// its own settings applied, after the configured thresholds themselves; and
+/// the file's incidents.
+pub fn load(path: &str, configured: &AlertThresholds) -> Result<(Vec<Candidate>, Vec<Incident>)> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path))?;
+    let file: TuningFile = toml::from_str(&text).with_context(|| format!("cannot parse {}", path))?;
+    let base = toml::Table::try_from(configured)?;
+
+    let mut candidates = vec![Candidate { name: CONFIGURED.to_string(), thresholds: configured.clone() }];
+    for (index, mut settings) in file.candidates.into_iter().enumerate() {
+        let name = match settings.remove("name") {
+            Some(toml::Value::String(name)) => name,
+            _ => format!("candidate {}", index + 1),
+        };
+        if candidates.iter().any(|candidate| candidate.name == name) {
+            return Err(anyhow!("{}: more than one candidate is named {:?}", path, name));
+        }
+        let mut table = base.clone();
+        for (key, value) in settings {
+            if !table.contains_key(&key) {
+                return Err(anyhow!("{}: {} sets {}, which is not an alert threshold", path, name, key));
+            }
+            table.insert(key, value);
+        }
+        let thresholds: AlertThresholds = table.try_into().with_context(|| format!("{}: invalid thresholds in {}", path, name))?;
+        for (metric, warning, critical) in thresholds.pairs() {
+            if !AlertThresholds::ordered(metric, warning, critical) {
+                let side = if AlertThresholds::FALLING.contains(&metric) { "below" } else { "above" };
+                return Err(anyhow!("{}: {} has {} warning ({}) at or {} critical ({})", path, name, metric, warning, side, critical));
+            }
+        }
+        candidates.push(Candidate { name, thresholds });
+    }
+
+    if let Some(incident) = file.incidents.iter().find(|incident| incident.end < incident.start) {
+        return Err(anyhow!("{}: incident {:?} ends before it starts", path, incident.name));
+    }
+    Ok((candidates, file.incidents))
+}
+
+/// Warning and Critical alerts one metric raised; an alert is raised when the
+/// metric reaches its level from below, not again every cycle it stays there.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Raised {
+    pub warnings: usize,
+    pub criticals: usize,
+}
+
+/// When a candidate's alerts first noticed an incident.
+#[derive(Debug, Clone, Serialize)]
+pub struct Detection {
+    pub incident: String,
+    pub first_warning: Option<DateTime<Utc>>,
+    pub first_critical: Option<DateTime<Utc>>,
+}
+
+impl Detection {
+    /// An incident is caught when an expected alert went Critical during it.
+    pub fn caught(&self) -> bool {
+        self.first_critical.is_some()
+    }
+}
+
+/// What one candidate's thresholds would have raised over the history.
+#[derive(Debug, Clone, Serialize)]
+pub struct Outcome {
+    pub candidate: String,
+    pub warnings: usize,
+    pub criticals: usize,
+    /// Snapshots with a Warning or Critical alert open.
+    pub alerting_snapshots: usize,
+    pub by_metric: BTreeMap<String, Raised>,
+    pub incidents: Vec<Detection>,
+    /// Each metric's level in the previous snapshot: 1 Warning, 2 Critical.
+    #[serde(skip)]
+    open: HashMap<String, u8>,
+}
+
+impl Outcome {
+    fn new(candidate: &Candidate, incidents: &[Incident]) -> Self {
+        Self {
+            candidate: candidate.name.clone(),
+            warnings: 0,
+            criticals: 0,
+            alerting_snapshots: 0,
+            by_metric: BTreeMap::new(),
+            incidents: incidents
+                .iter()
+                .map(|incident| Detection { incident: incident.name.clone(), first_warning: None, first_critical: None })
+                .collect(),
+            open: HashMap::new(),
+        }
+    }
+
+    fn record(&mut self, time: DateTime<Utc>, metrics: &GlobalMetrics, thresholds: &AlertThresholds, incidents: &[Incident]) {
+        // A metric such as New Listing can alert once per asset; its level is the highest.
+        let mut levels: HashMap<String, u8> = HashMap::new();
+        for alert in check_alerts(metrics, thresholds) {
+            let level = match alert.level {
+                AlertLevel::Critical => 2,
+                AlertLevel::Warning => 1,
+                AlertLevel::Info => continue,
+            };
+            let entry = levels.entry(alert.metric).or_insert(level);
+            *entry = (*entry).max(level);
+        }
+
+        if !levels.is_empty() {
+            self.alerting_snapshots += 1;
+        }
+        for (metric, &level) in &levels {
+            if level > self.open.get(metric).copied().unwrap_or(0) {
+                let raised = self.by_metric.entry(metric.clone()).or_default();
+                if level == 2 {
+                    raised.criticals += 1;
+                    self.criticals += 1;
+                } else {
+                    raised.warnings += 1;
+                    self.warnings += 1;
+                }
+            }
+            for (incident, detection) in incidents.iter().zip(&mut self.incidents) {
+                if incident.covers(time) && incident.expects(metric) {
+                    detection.first_warning.get_or_insert(time);
+                    if level == 2 {
+                        detection.first_critical.get_or_insert(time);
+                    }
+                }
+            }
+        }
+        self.open = levels;
+    }
+
+    pub fn caught(&self) -> usize {
+        self.incidents.iter().filter(|detection| detection.caught()).count()
+    }
+
+    /// The metric that raised the most alerts, with how many.
+    fn noisiest(&self) -> Option<(&str, usize)> {
+        self.by_metric
+            .iter()
+            .map(|(metric, raised)| (metric.as_str(), raised.warnings + raised.criticals))
+            .max_by_key(|(_, count)| *count)
+    }
+}
+
+/// Stored history replayed against each candidate's thresholds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tuning {
+    pub generated: DateTime<Utc>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub snapshots: usize,
+    pub incidents: Vec<Incident>,
+    /// The configured thresholds first.
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Tuning {
+    /// Replays the snapshots `store` holds from `from` to `to`.
+    pub fn run(store: &MetricStore, candidates: &[Candidate], incidents: Vec<Incident>, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Self> {
+        let mut outcomes: Vec<Outcome> = candidates.iter().map(|candidate| Outcome::new(candidate, &incidents)).collect();
+        let snapshots = store.for_each_snapshot(from.timestamp_millis(), to.timestamp_millis(), |metrics| {
+            let time = metrics.last_update.unwrap_or(to);
+            for (outcome, candidate) in outcomes.iter_mut().zip(candidates) {
+                outcome.record(time, &metrics, &candidate.thresholds, &incidents);
+            }
+        })?;
+        if snapshots == 0 {
+            return Err(anyhow!("the metric store has no snapshots from {} to {}", minute(from), minute(to)));
+        }
+        Ok(Self { generated: Utc::now(), from, to, snapshots, incidents, outcomes })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HLP Threshold Tuning\n");
+        let _ = writeln!(
+            out,
+            "{} snapshots of the metric store from {} to {} UTC, replayed against {} sets of thresholds, generated {}.\n",
+            self.snapshots,
+            minute(self.from),
+            minute(self.to),
+            self.outcomes.len(),
+            self.generated.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+
+        let _ = writeln!(out, "## Candidates\n");
+        let _ = writeln!(out, "| Candidate | Warnings | Criticals | Time alerting | Incidents caught | Noisiest |\n|---|---:|---:|---:|---:|---|");
+        for outcome in &self.outcomes {
+            let noisiest = outcome.noisiest().map_or_else(|| "-".to_string(), |(metric, count)| format!("{} ({})", metric, count));
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.1}% | {}/{} | {} |",
+                outcome.candidate,
+                outcome.warnings,
+                outcome.criticals,
+                outcome.alerting_snapshots as f64 / self.snapshots as f64 * 100.0,
+                outcome.caught(),
+                self.incidents.len(),
+                noisiest
+            );
+        }
+
+        if !self.incidents.is_empty() {
+            let _ = writeln!(out, "\n## Incidents\n");
+            let _ = writeln!(out, "🔴 went Critical, 🟡 only warned, ❌ missed; times are from the incident's start.\n");
+            let header: Vec<&str> = self.outcomes.iter().map(|outcome| outcome.candidate.as_str()).collect();
+            let _ = writeln!(out, "| Incident | {} |\n|---|{}", header.join(" | "), "---|".repeat(header.len()));
+            for (index, incident) in self.incidents.iter().enumerate() {
+                let cells: Vec<String> = self
+                    .outcomes
+                    .iter()
+                    .map(|outcome| {
+                        let detection = &outcome.incidents[index];
+                        match (detection.first_critical, detection.first_warning) {
+                            (Some(time), _) => format!("🔴 {}", after(incident.start, time)),
+                            (None, Some(time)) => format!("🟡 {}", after(incident.start, time)),
+                            (None, None) => "❌".to_string(),
+                        }
+                    })
+                    .collect();
+                let _ = writeln!(out, "| {} | {} |", incident.name, cells.join(" | "));
+            }
+        }
+
+        let metrics: Vec<&String> = {
+            let mut metrics: Vec<&String> = self.outcomes.iter().flat_map(|outcome| outcome.by_metric.keys()).collect();
+            metrics.sort();
+            metrics.dedup();
+            metrics
+        };
+        if !metrics.is_empty() {
+            let _ = writeln!(out, "\n## Alerts by metric\n");
+            let _ = writeln!(out, "Warnings / criticals raised.\n");
+            let header: Vec<&str> = self.outcomes.iter().map(|outcome| outcome.candidate.as_str()).collect();
+            let _ = writeln!(out, "| Metric | {} |\n|---|{}", header.join(" | "), "---:|".repeat(header.len()));
+            for metric in metrics {
+                let cells: Vec<String> = self
+                    .outcomes
+                    .iter()
+                    .map(|outcome| {
+                        let raised = outcome.by_metric.get(metric).cloned().unwrap_or_default();
+                        format!("{} / {}", raised.warnings, raised.criticals)
+                    })
+                    .collect();
+                let _ = writeln!(out, "| {} | {} |", metric, cells.join(" | "));
+            }
+        }
+
+        let _ = writeln!(out, "\nThe store does not keep the inputs of {}, so they are not replayed.", NOT_REPLAYED.join(", "));
+        out
+    }
+}
+
+fn minute(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// `time` as an offset from `start`, e.g. `+12m`.
+fn after(start: DateTime<Utc>, time: DateTime<Utc>) -> String {
+    let secs = (time - start).num_seconds().max(0);
+    match secs {
+        s if s < 60 => format!("+{}s", s),
+        s if s < 60 * 60 => format!("+{}m", s / 60),
+        s => format!("+{}h{:02}m", s / 3600, s % 3600 / 60),
+    }
+}
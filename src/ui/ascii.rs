@@ -0,0 +1,55 @@
+use ratatui::buffer::Buffer;
+
+/// Rewrites a rendered frame in place so every cell holds plain ASCII: box drawing becomes
+/// `+-|`, braille plots and bar blocks become `*`/`#`, arrows become `<>^v`, and emoji are
+/// blanked. Applied after drawing, so widgets need no separate ASCII code path.
+pub fn asciify(buf: &mut Buffer) {
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = buf.get_mut(x, y);
+            if !cell.symbol().is_ascii() {
+                let replacement = ascii_symbol(cell.symbol());
+                cell.set_char(replacement);
+            }
+        }
+    }
+}
+
+/// Same mapping for text printed outside the TUI, such as the startup banner.
+pub fn asciify_str(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii() { c } else { ascii_char(c) })
+        .collect()
+}
+
+fn ascii_symbol(symbol: &str) -> char {
+    symbol.chars().next().map(ascii_char).unwrap_or(' ')
+}
+
+fn ascii_char(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '╌' | '╍' | '┄' | '┅' | '–' | '—' => '-',
+        '│' | '┃' | '║' | '╎' | '╏' | '┆' | '┇' => '|',
+        '\u{2500}'..='\u{257F}' => '+',
+        '▁' | '▂' => '_',
+        '\u{2580}'..='\u{259F}' => '#',
+        '\u{2800}' => ' ',
+        '\u{2801}'..='\u{28FF}' => '*',
+        '←' => '<',
+        '→' => '>',
+        '↑' => '^',
+        '↓' => 'v',
+        '↩' => '<',
+        '…' => '.',
+        '·' => '.',
+        '•' | '●' | '◆' => '*',
+        '○' => 'o',
+        '✓' => '+',
+        '✗' => 'x',
+        '≥' => '>',
+        '≤' => '<',
+        '₿' => 'B',
+        _ => ' ',
+    }
+}
@@ -0,0 +1,123 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Widget},
+};
+use rust_decimal::prelude::*;
+
+use crate::model::Candle;
+use crate::ui::format;
+
+const VOLUME_BLOCKS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+const AXIS_WIDTH: u16 = 11;
+
+/// OHLC candles, one per column, with volume bars along the bottom quarter.
+/// The most recent candles that fit are shown; older ones scroll off the left.
+pub struct CandleChart<'a> {
+    candles: &'a [&'a Candle],
+    block: Option<Block<'a>>,
+    highlight: Option<u64>,
+}
+
+impl<'a> CandleChart<'a> {
+    pub fn new(candles: &'a [&'a Candle]) -> Self {
+        Self { candles, block: None, highlight: None }
+    }
+
+    /// Marks the column of the candle opening at this time, e.g. under a crosshair.
+    pub fn highlight(mut self, open_time: Option<u64>) -> Self {
+        self.highlight = open_time;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for CandleChart<'_> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let inner = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        if inner.width <= AXIS_WIDTH || inner.height < 3 || self.candles.is_empty() {
+            return;
+        }
+
+        let plot_width = (inner.width - AXIS_WIDTH) as usize;
+        let visible = &self.candles[self.candles.len().saturating_sub(plot_width)..];
+
+        let volume_rows = (inner.height / 4).max(1);
+        let price_rows = inner.height - volume_rows;
+
+        let (low, high) = visible.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), c| {
+            (lo.min(to_f64(c.low)), hi.max(to_f64(c.high)))
+        });
+        let span = (high - low).max(f64::EPSILON);
+        let row_of = |price: f64| -> u16 {
+            let fraction = (high - price) / span;
+            inner.y + (fraction * (price_rows - 1) as f64).round() as u16
+        };
+
+        let max_volume = visible.iter().map(|c| to_f64(c.volume)).fold(0.0, f64::max);
+
+        for (i, candle) in visible.iter().enumerate() {
+            let x = inner.x + i as u16;
+            let (open, close) = (to_f64(candle.open), to_f64(candle.close));
+            let color = if close >= open { Color::Green } else { Color::Red };
+            let style = Style::default().fg(color);
+
+            if self.highlight == Some(candle.open_time) {
+                for y in inner.y..inner.y + inner.height {
+                    buf.get_mut(x, y).set_bg(Color::DarkGray);
+                }
+            }
+
+            for y in row_of(to_f64(candle.high))..=row_of(to_f64(candle.low)) {
+                buf.get_mut(x, y).set_symbol("│").set_style(style);
+            }
+            for y in row_of(open.max(close))..=row_of(open.min(close)) {
+                buf.get_mut(x, y).set_symbol("┃").set_style(style);
+            }
+
+            if max_volume > 0.0 {
+                let eighths = (to_f64(candle.volume) / max_volume * (volume_rows * 8) as f64).round() as u16;
+                let bottom = inner.y + inner.height - 1;
+                for level in 0..volume_rows {
+                    let filled = eighths.saturating_sub(level * 8).min(8);
+                    if filled == 0 {
+                        break;
+                    }
+                    buf.get_mut(x, bottom - level)
+                        .set_symbol(VOLUME_BLOCKS[filled as usize - 1])
+                        .set_style(Style::default().fg(Color::DarkGray));
+                }
+            }
+        }
+
+        let axis_x = inner.x + inner.width - AXIS_WIDTH + 1;
+        let label_style = Style::default().fg(Color::DarkGray);
+        buf.set_string(axis_x, inner.y, format_price(high), label_style);
+        buf.set_string(axis_x, inner.y + price_rows - 1, format_price(low), label_style);
+        if price_rows > 4 {
+            buf.set_string(axis_x, inner.y + (price_rows - 1) / 2, format_price((high + low) / 2.0), label_style);
+        }
+        buf.set_string(axis_x, inner.y + inner.height - 1, "vol", label_style);
+    }
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+fn format_price(price: f64) -> String {
+    format::current().price(price)
+}
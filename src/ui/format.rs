@@ -0,0 +1,196 @@
+use std::cell::Cell;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::config::{Denomination, Locale, Timezone, UiSettings};
+use crate::model::GlobalMetrics;
+
+thread_local! {
+    /// How the frame being drawn writes numbers; `ui::draw` sets it from the
+    /// config on every frame.
+    static CURRENT: Cell<NumberFormat> = const { Cell::new(NumberFormat { locale: Locale::EnUs, compact: true }) };
+    /// The zone the frame being drawn shows times in.
+    static ZONE: Cell<Timezone> = const { Cell::new(Timezone::Utc) };
+    /// What the frame being drawn shows amounts in, with the dollars one unit
+    /// is worth.
+    static UNIT: Cell<(Denomination, f64)> = const { Cell::new((Denomination::Usd, 1.0)) };
+}
+
+/// How amounts, prices and axis labels are written: the locale's separators,
+/// and amounts either compact ("$109.5M") or in full ("$109,512,345.67").
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    pub locale: Locale,
+    pub compact: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self { locale: Locale::EnUs, compact: true }
+    }
+}
+
+impl NumberFormat {
+    pub fn from_settings(settings: &UiSettings) -> Self {
+        Self { locale: settings.locale, compact: settings.compact_numbers }
+    }
+
+    /// The thousands separator and the decimal separator.
+    fn separators(self) -> (&'static str, char) {
+        match self.locale {
+            Locale::EnUs => (",", '.'),
+            Locale::DeDe => (".", ','),
+            Locale::FrFr => ("\u{202f}", ','),
+            Locale::DeCh => ("'", '.'),
+        }
+    }
+
+    /// `value` to `decimals` places, with thousands grouped.
+    pub fn number(self, value: f64, decimals: usize) -> String {
+        let (thousands, decimal) = self.separators();
+        let text = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push_str(thousands);
+            }
+            grouped.push(digit);
+        }
+        let sign = if value < 0.0 && text.bytes().any(|b| b.is_ascii_digit() && b != b'0') { "-" } else { "" };
+        match fraction {
+            "" => format!("{}{}", sign, grouped),
+            _ => format!("{}{}{}{}", sign, grouped, decimal, fraction),
+        }
+    }
+
+    /// "109.5M", "12.35M", "4.5K" or, under a thousand, `value` to `decimals`
+    /// places; always compact, for axis labels and other tight spots.
+    pub fn compact(self, value: f64, decimals: usize) -> String {
+        let abs = value.abs();
+        let (scaled, suffix) = match abs {
+            _ if abs >= 1e9 => (value / 1e9, "B"),
+            _ if abs >= 1e6 => (value / 1e6, "M"),
+            _ if abs >= 1e3 => return format!("{}K", self.number(value / 1e3, 1)),
+            _ => return self.number(value, decimals),
+        };
+        let places = if scaled.abs() >= 100.0 { 1 } else { 2 };
+        format!("{}{}", self.number(scaled, places), suffix)
+    }
+
+    /// Dollar amounts as "-$1.23M" when compact, "-$1,234,567.89" otherwise.
+    pub fn usd(self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "" };
+        let amount = match self.compact {
+            true => self.compact(value.abs(), 2),
+            false => self.number(value.abs(), 2),
+        };
+        format!("{}${}", sign, amount)
+    }
+
+    /// Prices to as many places as their size warrants: "67,512.3", "3.456",
+    /// "0.000123".
+    pub fn price(self, value: f64) -> String {
+        let decimals = match value.abs() {
+            abs if abs >= 1_000.0 => 1,
+            abs if abs >= 1.0 => 3,
+            _ => 6,
+        };
+        self.number(value, decimals)
+    }
+
+    /// A price or size as the exchange gave it, to the places it has:
+    /// "67,512.5", "0.0012".
+    pub fn decimal(self, value: Decimal) -> String {
+        let value = value.normalize();
+        self.number(value.to_f64().unwrap_or(0.0), value.scale() as usize)
+    }
+}
+
+/// Makes `settings` the format of what this thread draws next.
+pub fn set(settings: &UiSettings) {
+    CURRENT.with(|current| current.set(NumberFormat::from_settings(settings)));
+    ZONE.with(|zone| zone.set(settings.timezone));
+}
+
+/// The format of the frame being drawn.
+pub fn current() -> NumberFormat {
+    CURRENT.with(Cell::get)
+}
+
+/// Makes `denomination` what this thread shows amounts in next, at the BTC
+/// book's mid or the vault's share price in `metrics`. Without them amounts stay in
+/// dollars; the denomination in effect is returned.
+pub fn denominate(denomination: Denomination, metrics: &GlobalMetrics) -> Denomination {
+    let usd_per_unit = match denomination {
+        Denomination::Usd => Some(1.0),
+        Denomination::Btc => metrics
+            .order_books
+            .get("BTC")
+            .and_then(|book| Some((book.bids.first()?.px + book.asks.first()?.px) / rust_decimal::Decimal::TWO))
+            .and_then(|mid| mid.to_f64()),
+        Denomination::Vault => Some(metrics.vault_metrics.share_price),
+    };
+    let unit = match usd_per_unit.filter(|usd| *usd > 0.0) {
+        Some(usd) => (denomination, usd),
+        None => (Denomination::Usd, 1.0),
+    };
+    UNIT.with(|current| current.set(unit));
+    unit.0
+}
+
+/// A dollar amount in the current denomination: "$1.23M", "₿18.2345" or
+/// "1.05M sh", in shares of the vault.
+pub fn amount(usd: f64) -> String {
+    let format = current();
+    match UNIT.with(Cell::get) {
+        (Denomination::Usd, _) => format.usd(usd),
+        (Denomination::Btc, usd_per_btc) => {
+            let btc = usd / usd_per_btc;
+            let sign = if btc < 0.0 { "-" } else { "" };
+            let amount = match btc.abs() {
+                abs if abs >= 1_000.0 && format.compact => format.compact(abs, 2),
+                abs if abs >= 100.0 => format.number(abs, 2),
+                abs if abs >= 1.0 || abs == 0.0 => format.number(abs, 4),
+                abs => format.number(abs, 6),
+            };
+            format!("{}₿{}", sign, amount)
+        }
+        (Denomination::Vault, usd_per_share) => {
+            let shares = usd / usd_per_share;
+            let sign = if shares < 0.0 { "-" } else { "" };
+            let amount = match format.compact {
+                true => format.compact(shares.abs(), 2),
+                false => format.number(shares.abs(), 2),
+            };
+            format!("{}{} sh", sign, amount)
+        }
+    }
+}
+
+/// `at` in the configured zone, by a `chrono` format string.
+pub fn time(at: DateTime<Utc>, pattern: &str) -> String {
+    match ZONE.with(Cell::get) {
+        Timezone::Utc => at.format(pattern).to_string(),
+        Timezone::Local => at.with_timezone(&chrono::Local).format(pattern).to_string(),
+        Timezone::Fixed(offset) => at.with_timezone(&offset).format(pattern).to_string(),
+    }
+}
+
+/// `ms`, milliseconds since the epoch, in the configured zone.
+pub fn time_ms(ms: u64, pattern: &str) -> String {
+    DateTime::from_timestamp_millis(ms as i64).map(|at| time(at, pattern)).unwrap_or_default()
+}
+
+/// How long ago `at` was: "12s ago", "4m ago", "3h ago" or "2d ago".
+pub fn ago(at: DateTime<Utc>) -> String {
+    let secs = (Utc::now() - at).num_seconds().max(0);
+    match secs {
+        0..60 => format!("{}s ago", secs),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
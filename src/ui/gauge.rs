@@ -0,0 +1,99 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+/// Narrowest bar worth drawing; below this only the label and value are shown.
+const MIN_BAR_WIDTH: u16 = 8;
+
+/// One-row gauge for a score bounded to 0..1: label, bar with tick marks at the
+/// warning and critical thresholds, then the value. The filled part takes the
+/// color of the tier the value is in.
+pub struct ThresholdGauge<'a> {
+    label: &'a str,
+    label_width: u16,
+    ratio: f64,
+    warning: f64,
+    critical: f64,
+    value: String,
+    color: Color,
+}
+
+impl<'a> ThresholdGauge<'a> {
+    pub fn new(label: &'a str, ratio: f64, value: String) -> Self {
+        Self {
+            label,
+            label_width: label.chars().count() as u16 + 2,
+            ratio: ratio.clamp(0.0, 1.0),
+            warning: 1.0,
+            critical: 1.0,
+            value,
+            color: Color::Green,
+        }
+    }
+
+    pub fn thresholds(mut self, warning: f64, critical: f64) -> Self {
+        self.warning = warning.clamp(0.0, 1.0);
+        self.critical = critical.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Pads the label column so gauges stacked in one panel line up.
+    pub fn label_width(mut self, width: u16) -> Self {
+        self.label_width = width;
+        self
+    }
+}
+
+impl Widget for ThresholdGauge<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+
+        let label = format!("{}: ", self.label);
+        buf.set_stringn(area.x, area.y, &label, area.width as usize, Style::default());
+
+        let value = format!(" {}", self.value);
+        let value_width = value.chars().count() as u16;
+        let value_style = Style::default().fg(self.color).add_modifier(Modifier::BOLD);
+
+        let bar_x = area.x + self.label_width.min(area.width);
+        let bar_width = (area.x + area.width).saturating_sub(bar_x).saturating_sub(value_width);
+        if bar_width < MIN_BAR_WIDTH {
+            let x = area.x + (label.chars().count() as u16).min(area.width);
+            let room = (area.x + area.width).saturating_sub(x) as usize;
+            buf.set_stringn(x, area.y, self.value.as_str(), room, value_style);
+            return;
+        }
+
+        let cell_of = |fraction: f64| ((fraction * bar_width as f64).round() as u16).min(bar_width - 1);
+        let filled = (self.ratio * bar_width as f64).round() as u16;
+        let ticks = [(cell_of(self.warning), Color::Yellow), (cell_of(self.critical), Color::Red)];
+
+        for i in 0..bar_width {
+            let cell = buf.get_mut(bar_x + i, area.y);
+            if let Some((_, tick_color)) = ticks.iter().rev().find(|(at, _)| *at == i) {
+                let style = if i < filled {
+                    Style::default().fg(*tick_color).bg(self.color)
+                } else {
+                    Style::default().fg(*tick_color)
+                };
+                cell.set_symbol("┃").set_style(style);
+            } else if i < filled {
+                cell.set_symbol("█").set_style(Style::default().fg(self.color));
+            } else {
+                cell.set_symbol("─").set_style(Style::default().fg(Color::DarkGray));
+            }
+        }
+
+        buf.set_string(bar_x + bar_width, area.y, value, value_style);
+    }
+}
@@ -0,0 +1,182 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier},
+    widgets::Widget,
+};
+use log::LevelFilter;
+use ratatui_logger as logger_tui;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tui_logger::{TuiLoggerLevelOutput, TuiLoggerSmartWidget, TuiWidgetEvent, TuiWidgetState};
+
+use crate::logfile;
+
+/// Level a target shows from until it is changed.
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// The Logs tab's target and level filters. tui-logger keeps these to itself, so the
+/// key handlers go through [`LogFilters::apply`], which follows the target list and
+/// selection the widget draws and sets the levels itself.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilters {
+    /// Display levels changed from the default, by target.
+    pub levels: BTreeMap<String, LevelFilter>,
+    /// Leaves targets switched off out of the target list.
+    pub hide_off: bool,
+    #[serde(skip)]
+    hide_targets: bool,
+    #[serde(skip)]
+    selected: usize,
+}
+
+impl LogFilters {
+    /// A widget state showing these filters.
+    pub fn widget_state(&self) -> TuiWidgetState {
+        let mut state = TuiWidgetState::new().set_default_display_level(DEFAULT_LEVEL);
+        for (target, level) in &self.levels {
+            state = state.set_level_for_target(target, *level);
+        }
+        if self.hide_off {
+            state.transition(TuiWidgetEvent::SpaceKey);
+        }
+        state
+    }
+
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.levels.get(target).copied().unwrap_or(DEFAULT_LEVEL)
+    }
+
+    /// Handles a Logs tab key on `state`, keeping these filters in step with it.
+    pub fn apply(&mut self, state: &mut TuiWidgetState, event: TuiWidgetEvent) {
+        let targets = self.targets();
+        self.selected = self.selected.min(targets.len().max(1) - 1);
+        match event {
+            TuiWidgetEvent::SpaceKey => self.hide_off ^= true,
+            TuiWidgetEvent::HideKey => self.hide_targets ^= true,
+            TuiWidgetEvent::UpKey if !self.hide_targets => self.selected = self.selected.saturating_sub(1),
+            TuiWidgetEvent::DownKey if !self.hide_targets && self.selected + 1 < targets.len() => self.selected += 1,
+            TuiWidgetEvent::LeftKey | TuiWidgetEvent::RightKey => {
+                if let Some(target) = targets.get(self.selected) {
+                    let level = self.level(target);
+                    let level = if event == TuiWidgetEvent::LeftKey { quieter(level) } else { louder(level) };
+                    self.levels.insert(target.clone(), level);
+                    *state = std::mem::take(state).set_level_for_target(target, level);
+                }
+                return;
+            }
+            _ => {}
+        }
+        state.transition(event);
+    }
+
+    /// The widget's target list: every target logged or given a level, sorted.
+    fn targets(&self) -> Vec<String> {
+        let mut targets: BTreeSet<String> = logfile::targets().into_iter().collect();
+        targets.extend(self.levels.keys().cloned());
+        targets.into_iter().filter(|t| !(self.hide_off && self.level(t) == LevelFilter::Off)).collect()
+    }
+}
+
+fn quieter(level: LevelFilter) -> LevelFilter {
+    match level {
+        LevelFilter::Trace | LevelFilter::Debug => LevelFilter::Info,
+        LevelFilter::Info => LevelFilter::Warn,
+        LevelFilter::Warn => LevelFilter::Error,
+        LevelFilter::Error | LevelFilter::Off => LevelFilter::Off,
+    }
+}
+
+/// Stops at debug, the most the Logs tab records.
+fn louder(level: LevelFilter) -> LevelFilter {
+    match level {
+        LevelFilter::Off => LevelFilter::Error,
+        LevelFilter::Error => LevelFilter::Warn,
+        LevelFilter::Warn => LevelFilter::Info,
+        LevelFilter::Info | LevelFilter::Debug | LevelFilter::Trace => LevelFilter::Debug,
+    }
+}
+
+/// Target selector plus log pane from tui-logger, bridged onto our ratatui version.
+pub struct LogViewer<'a> {
+    state: &'a TuiWidgetState,
+}
+
+impl<'a> LogViewer<'a> {
+    pub fn new(state: &'a TuiWidgetState) -> Self {
+        Self { state }
+    }
+}
+
+impl Widget for LogViewer<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let logger_area = logger_tui::layout::Rect::new(area.x, area.y, area.width, area.height);
+        let mut logger_buf = logger_tui::buffer::Buffer::empty(logger_area);
+
+        let highlight = logger_tui::style::Style::default()
+            .fg(logger_tui::style::Color::Black)
+            .bg(logger_tui::style::Color::Yellow);
+        let level_style = |color| logger_tui::style::Style::default().fg(color);
+
+        logger_tui::widgets::Widget::render(
+            TuiLoggerSmartWidget::default()
+                .title_target("Targets")
+                .title_log("📜 Logs")
+                .highlight_style(highlight)
+                .style_error(level_style(logger_tui::style::Color::Red))
+                .style_warn(level_style(logger_tui::style::Color::Yellow))
+                .style_info(level_style(logger_tui::style::Color::Cyan))
+                .style_debug(level_style(logger_tui::style::Color::Green))
+                .style_trace(level_style(logger_tui::style::Color::Magenta))
+                .output_separator('│')
+                .output_timestamp(Some("%H:%M:%S".to_string()))
+                .output_level(Some(TuiLoggerLevelOutput::Abbreviated))
+                .output_target(true)
+                .output_file(false)
+                .output_line(false)
+                .state(self.state),
+            logger_area,
+            &mut logger_buf,
+        );
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let src = &logger_buf[(x, y)];
+                if src.skip {
+                    continue;
+                }
+                buf.get_mut(x, y)
+                    .set_symbol(src.symbol())
+                    .set_fg(convert_color(src.fg))
+                    .set_bg(convert_color(src.bg))
+                    .modifier = Modifier::from_bits_truncate(src.modifier.bits());
+            }
+        }
+    }
+}
+
+fn convert_color(color: logger_tui::style::Color) -> Color {
+    use logger_tui::style::Color as C;
+    match color {
+        C::Reset => Color::Reset,
+        C::Black => Color::Black,
+        C::Red => Color::Red,
+        C::Green => Color::Green,
+        C::Yellow => Color::Yellow,
+        C::Blue => Color::Blue,
+        C::Magenta => Color::Magenta,
+        C::Cyan => Color::Cyan,
+        C::Gray => Color::Gray,
+        C::DarkGray => Color::DarkGray,
+        C::LightRed => Color::LightRed,
+        C::LightGreen => Color::LightGreen,
+        C::LightYellow => Color::LightYellow,
+        C::LightBlue => Color::LightBlue,
+        C::LightMagenta => Color::LightMagenta,
+        C::LightCyan => Color::LightCyan,
+        C::White => Color::White,
+        C::Rgb(r, g, b) => Color::Rgb(r, g, b),
+        C::Indexed(i) => Color::Indexed(i),
+    }
+}
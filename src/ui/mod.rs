@@ -1 +1,7 @@
-pub mod ui;
\ No newline at end of file
+pub mod ascii;
+pub mod candles;
+pub mod format;
+pub mod gauge;
+pub mod logs;
+#[allow(clippy::module_inception)]
+pub mod ui;
@@ -1,825 +1,3113 @@
-use anyhow::Result;
-use async_trait::async_trait;
-use std::collections::HashMap;
-use reqwest::Client;
-use serde_json::Value;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use futures_util::{SinkExt, StreamExt};
-use tokio::sync::broadcast;
-use log::{info, warn, error, debug};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    symbols,
+    widgets::{block::Title, Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Row, Table, Tabs, Widget, Wrap},
+    Frame,
+};
 use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-use crate::api::provider::{DataProvider, DataSourceStatus, parse_decimal};
-use crate::config::Config;
+use crate::alert::{check_alerts, unacknowledged_critical};
+use crate::config::{AlertThresholds, Config, Denomination, LayoutSettings, OperatingMode, OverviewPanel, PanelSpec};
+use crate::history::{HistoryMetric, MetricHistory, CANDLE_INTERVAL, RETENTION_MS};
+use crate::ui::ascii::asciify;
+use crate::ui::candles::CandleChart;
+use crate::ui::format;
+use crate::ui::gauge::ThresholdGauge;
+use crate::ui::logs::{LogFilters, LogViewer};
+use tui_logger::{TuiWidgetEvent, TuiWidgetState};
 use crate::model::*;
 
-pub struct HyperliquidProvider {
-    info_client: InfoClient,
-    ws_manager: Option<WsManager>,
-    user_address: String,
-    monitored_assets: Vec<String>,
+pub const TAB_TITLES: [&str; 9] = [
+    "Overview",
+    "Liquidity",
+    "Risk",
+    "Performance",
+    "Positions",
+    "Alerts",
+    "Charts",
+    "Heatmap",
+    "Logs",
+];
+
+pub const LIQUIDITY_TAB: usize = 1;
+pub const PERFORMANCE_TAB: usize = 3;
+pub const ALERTS_TAB: usize = 5;
+pub const CHARTS_TAB: usize = 6;
+pub const LOGS_TAB: usize = 8;
+
+/// Selectable chart time ranges as (label, seconds).
+pub const CHART_RANGES: [(&str, i64); 4] = [("15m", 15 * 60), ("1h", 60 * 60), ("6h", 6 * 60 * 60), ("24h", 24 * 60 * 60)];
+
+const MAX_CHART_ZOOM: u32 = 4;
+/// Fraction of the visible window the crosshair moves per key press.
+const CROSSHAIR_STEP: f64 = 1.0 / 40.0;
+/// How long a changed level stays highlighted on the depth ladder.
+const LEVEL_FLASH: Duration = Duration::from_millis(1500);
+
+/// Below this many columns tabs stack their panels instead of splitting side by side.
+const COMPACT_WIDTH: u16 = 100;
+/// From this many columns tabs spread panels into extra columns.
+const WIDE_WIDTH: u16 = 200;
+/// Narrowest an Overview panel may get before its row wraps onto the next line.
+const MIN_PANEL_WIDTH: u16 = 38;
+/// Smaller than this the dashboard shows only its vitals.
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    Compact,
+    Standard,
+    Wide,
 }
 
-pub struct InfoClient {
-    client: Client,
-    base_url: String,
+impl LayoutMode {
+    pub fn for_width(width: u16) -> Self {
+        if width < COMPACT_WIDTH {
+            LayoutMode::Compact
+        } else if width >= WIDE_WIDTH {
+            LayoutMode::Wide
+        } else {
+            LayoutMode::Standard
+        }
+    }
 }
 
-pub struct WsManager {
-    url: String,
-    trade_sender: broadcast::Sender<Fill>,
-    l2_sender: broadcast::Sender<L2Snapshot>,
-    order_sender: broadcast::Sender<OrderEvent>,
-    connected: std::sync::Arc<std::sync::atomic::AtomicBool>,
+pub struct UIState {
+    pub selected_tab: usize,
+    /// Scroll position per tab, so switching tabs never inherits another tab's offset.
+    pub scroll: [TabScroll; TAB_TITLES.len()],
+    pub test_mode: bool,
+    pub chart: ChartState,
+    /// Window and crosshair of the Performance tab's equity and drawdown charts.
+    pub equity_chart: ChartState,
+    pub ladder: LadderState,
+    pub log_state: TuiWidgetState,
+    /// The Logs tab's filters as last set through [`UIState::log_key`].
+    pub log_filters: LogFilters,
+    /// False when `--debug` routes logs to stderr instead of the in-app logger.
+    pub log_capture: bool,
+    /// Quit was requested and is waiting for confirmation.
+    pub quit_prompt: bool,
+    /// What amounts are shown in; falls back to dollars while it has no price.
+    pub denomination: Denomination,
 }
 
-impl InfoClient {
-    pub fn new(base_url: String) -> Self {
-        Self {
-            client: Client::new(),
-            base_url,
-        }
-    }
-    
-    pub async fn post_request(&self, endpoint: &str, payload: Value) -> Result<Value> {
-        let url = format!("{}/{}", self.base_url, endpoint);
-        debug!("📡 Making request to: {} with payload: {}", url, payload);
-        
-        let response = self.client
-            .post(&url)
-            .json(&payload)
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-            .map_err(|e| {
-                error!("❌ HTTP request failed: {}", e);
-                anyhow::anyhow!("HTTP request failed: {}", e)
-            })?;
-            
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            error!("❌ Request failed with status {}: {}", status, error_body);
-            return Err(anyhow::anyhow!("Request failed: {} - {}", status, error_body));
-        }
-        
-        let result = response.json().await
-            .map_err(|e| {
-                error!("❌ Failed to parse JSON response: {}", e);
-                anyhow::anyhow!("Failed to parse JSON response: {}", e)
-            })?;
-        
-        debug!("✅ Request successful, response: {}", result);
-        Ok(result)
-    }
-    
-    pub async fn get_clearinghouse_state(&self, user_address: &str) -> Result<Value> {
-        let payload = serde_json::json!({
-            "type": "clearinghouseState", 
-            "user": user_address
-        });
-        
-        info!("📊 Fetching clearinghouse state for user: {}", user_address);
-        self.post_request("info", payload).await
-    }
-    
-    pub async fn get_meta(&self) -> Result<Value> {
-        let payload = serde_json::json!({
-            "type": "meta"
-        });
-        
-        info!("📊 Fetching meta information");
-        self.post_request("info", payload).await
-    }
-    
-    pub async fn get_user_fills(&self, user_address: &str) -> Result<Value> {
-        let payload = serde_json::json!({
-            "type": "userFills",
-            "user": user_address
-        });
-        
-        info!("📊 Fetching user fills for: {}", user_address);
-        self.post_request("info", payload).await
-    }
-    
-    pub async fn get_l2_book(&self, coin: &str) -> Result<Value> {
-        let payload = serde_json::json!({
-            "type": "l2Book",
-            "coin": coin
-        });
-        
-        debug!("📊 Fetching L2 book for: {}", coin);
-        self.post_request("info", payload).await
-    }
-
-    #[allow(dead_code)]
-    pub async fn get_all_mids(&self) -> Result<Value> {
-        let payload = serde_json::json!({
-            "type": "allMids"
-        });
-        
-        debug!("📊 Fetching all mids");
-        self.post_request("info", payload).await
-    }
-}
-
-impl WsManager {
-    pub fn new(url: String) -> Self {
-        let (trade_sender, _) = broadcast::channel(1000);
-        let (l2_sender, _) = broadcast::channel(1000);
-        let (order_sender, _) = broadcast::channel(1000);
-        let connected = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        
-        Self { 
-            url,
-            trade_sender,
-            l2_sender,
-            order_sender,
-            connected,
-        }
-    }
-    
-    pub async fn connect_and_subscribe(&self, assets: &[String]) -> Result<()> {
-        let ws_url = &self.url;
-        info!("🔌 Connecting to WebSocket: {}", ws_url);
-        
-        let (ws_stream, _) = connect_async(ws_url).await?;
-        let (mut ws_sink, mut ws_stream) = ws_stream.split();
-        
-        self.connected.store(true, std::sync::atomic::Ordering::Relaxed);
-        info!("✅ WebSocket connected successfully");
-        
-        let order_subscribe_msg = serde_json::json!({
-            "method": "subscribe",
-            "subscription": {
-                "type": "orders"
-            }
-        });
-        
-        ws_sink.send(Message::Text(order_subscribe_msg.to_string())).await?;
-        info!("📊 Subscribed to orders");
-
-        for asset in assets {
-            let subscribe_msg = serde_json::json!({
-                "method": "subscribe",
-                "subscription": {
-                    "type": "trades",
-                    "coin": asset
-                }
-            });
-            
-            ws_sink.send(Message::Text(subscribe_msg.to_string())).await?;
-            info!("📡 Subscribed to trades for {}", asset);
-            
-            let l2_subscribe_msg = serde_json::json!({
-                "method": "subscribe", 
-                "subscription": {
-                    "type": "l2Book",
-                    "coin": asset
-                }
-            });
-            
-            ws_sink.send(Message::Text(l2_subscribe_msg.to_string())).await?;
-            info!("📊 Subscribed to L2 book for {}", asset);
-
-        }
-        
-        let trade_sender = self.trade_sender.clone();
-        let l2_sender = self.l2_sender.clone();
-        let order_sender = self.order_sender.clone();
-        let connected = self.connected.clone();
-        
-        tokio::spawn(async move {
-            while let Some(msg_result) = ws_stream.next().await {
-                match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if let Err(e) = Self::handle_message(&text, &trade_sender, &l2_sender, &order_sender).await {
-                            warn!("⚠️ Failed to handle WebSocket message: {}", e);
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        warn!("🔌 WebSocket connection closed");
-                        connected.store(false, std::sync::atomic::Ordering::Relaxed);
-                        break;
-                    }
-                    Err(e) => {
-                        error!("❌ WebSocket error: {}", e);
-                        connected.store(false, std::sync::atomic::Ordering::Relaxed);
-                        break;
-                    }
-                    _ => {}
-                }
-            }
-        });
-        
-        Ok(())
+/// A tab's scroll offset plus what its last draw showed, which sizes PageUp/PageDown
+/// and the "23–45 of 180" position indicator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TabScroll {
+    pub offset: u16,
+    pub visible: u16,
+    pub total: usize,
+}
+
+impl TabScroll {
+    /// Records the content size and clamps the offset so the last page stays full when
+    /// content shrinks or the terminal grows. Returns the first row to show.
+    fn clamp(&mut self, total: usize, visible: usize) -> usize {
+        let max = total.saturating_sub(visible.max(1));
+        self.offset = (self.offset as usize).min(max) as u16;
+        self.visible = visible.min(u16::MAX as usize) as u16;
+        self.total = total;
+        self.offset as usize
     }
-    
-    async fn handle_message(
-        text: &str,
-        trade_sender: &broadcast::Sender<Fill>,
-        l2_sender: &broadcast::Sender<L2Snapshot>,
-        order_sender: &broadcast::Sender<OrderEvent>,
-    ) -> Result<()> {
-        let msg: Value = serde_json::from_str(text)?;
-        
-        if let Some(channel) = msg.get("channel").and_then(|v| v.as_str()) {
-            match channel {
-                "trades" => {
-                    if let Some(data) = msg.get("data") {
-                        for trade_data in data.as_array().unwrap_or(&vec![]) {
-                            let fill = Self::parse_trade(trade_data)?;
-                            if let Err(_) = trade_sender.send(fill) {
-                                debug!("No trade receivers active");
-                            }
-                        }
-                    }
-                }
-                "l2Book" => {
-                    if let Some(data) = msg.get("data") {
-                        let snapshot = Self::parse_l2_snapshot(data)?;
-                        if let Err(_) = l2_sender.send(snapshot) {
-                            debug!("No L2 receivers active");
-                        }
-                    }
-                }
-                "orders" => {
-                    if let Some(data) = msg.get("data") {
-                        for order in data.as_array().unwrap_or(&vec![]) {
-                            let evt = Self::parse_order_event(order)?;
-                            if let Err(_) = order_sender.send(evt) {
-                                debug!("No order receivers active");
-                            }
-                        }
-
-                    }
-                }
-                _ => {
-                    debug!("📨 Unhandled channel: {}", channel);
-                }
-            }
+
+    fn page_size(&self) -> u16 {
+        if self.visible == 0 { 10 } else { self.visible }
+    }
+
+    /// "23–45 of 180" for the rows on screen, or just the count when everything fits.
+    fn position(&self) -> String {
+        let first = self.offset as usize;
+        let last = (first + self.visible as usize).min(self.total);
+        if last <= first || (first == 0 && last == self.total) {
+            format!("{}", self.total)
+        } else {
+            format!("{}–{} of {}", first + 1, last, self.total)
         }
-        
-        Ok(())
     }
-    
-    fn parse_order_event(data: &Value) -> Result<OrderEvent> {
-        use OrderAction::*;
-        let action = match data["status"].as_str().unwrap_or("") {
-            "open"      => New,
-            "filled"    => Filled,
-            "cancelled" => Cancelled,
-            other       => anyhow::bail!("unknown status {}", other),
-        };
-        Ok(OrderEvent {
-            id:        data["oid"].as_u64().unwrap_or(0),
-            action,
-            coin:      data["coin"].as_str().unwrap_or("").to_string(),
-            side:      data["side"].as_str().unwrap_or("").to_string(),
-            px:        parse_decimal(data["limitPx"].as_str().unwrap_or("0")),
-            sz:        parse_decimal(data["sz"].as_str().unwrap_or("0")),
-            timestamp: data["statusTimestamp"].as_u64().unwrap_or(0),
-        })
+}
+
+/// Which metric the Charts tab plots and which slice of its history is visible.
+/// The Performance tab keeps its own instance for the equity charts.
+#[derive(Default)]
+pub struct ChartState {
+    pub metric_index: usize,
+    pub range_index: usize,
+    /// Each zoom level halves the visible window within the selected range.
+    pub zoom: u32,
+    /// How far the right edge of the window sits behind "now", in seconds.
+    pub pan_secs: i64,
+    /// The candle asset to plot, by name: the list of assets with candles
+    /// grows and re-sorts as they are backfilled.
+    pub asset: Option<String>,
+    /// Crosshair position as a fraction of the window from its left edge, when shown.
+    pub cursor: Option<f64>,
+}
+
+impl ChartState {
+    pub fn metric(&self) -> HistoryMetric {
+        HistoryMetric::ALL[self.metric_index % HistoryMetric::ALL.len()]
     }
-    
-    fn parse_trade(data: &Value) -> Result<Fill> {
-        Ok(Fill {
-            coin: data["coin"].as_str().unwrap_or("").to_string(),
-            px: parse_decimal(data["px"].as_str().unwrap_or("0")),
-            sz: parse_decimal(data["sz"].as_str().unwrap_or("0")),
-            side: data["side"].as_str().unwrap_or("").to_string(),
-            time: data["time"].as_u64().unwrap_or(0),
-            start_position: parse_decimal("0"),
-            dir: data["side"].as_str().unwrap_or("").to_string(),
-            closed_pnl: parse_decimal("0"),
-            hash: data["tid"].as_str().unwrap_or("").to_string(),
-            oid: 0,
-            crossed: false,
-            fee: parse_decimal("0"),
-        })
+
+    pub fn window_secs(&self) -> i64 {
+        (CHART_RANGES[self.range_index].1 >> self.zoom).max(60)
     }
-    
-    fn parse_l2_snapshot(data: &Value) -> Result<L2Snapshot> {
-        let coin = data["coin"].as_str().unwrap_or("").to_string();
-        let time = data["time"].as_u64().unwrap_or(0);
-        
-        let levels = &data["levels"];
-        let bids = levels[0]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        let asks = levels[1]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        Ok(L2Snapshot {
-            coin,
-            time,
-            bids,
-            asks,
-        })
+
+    /// The candle asset to plot and its position among `assets`.
+    pub fn asset<'a>(&self, assets: &[&'a str]) -> Option<(usize, &'a str)> {
+        shown_asset(self.asset.as_deref(), assets)
+    }
+
+    pub fn next_asset(&mut self, assets: &[&str]) {
+        step_asset(&mut self.asset, assets, 1);
     }
-    
-    pub fn get_trade_receiver(&self) -> broadcast::Receiver<Fill> {
-        self.trade_sender.subscribe()
+
+    pub fn previous_asset(&mut self, assets: &[&str]) {
+        step_asset(&mut self.asset, assets, assets.len().saturating_sub(1));
     }
-    
-    pub fn get_l2_receiver(&self) -> broadcast::Receiver<L2Snapshot> {
-        self.l2_sender.subscribe()
+
+    pub fn next_metric(&mut self) {
+        self.metric_index = (self.metric_index + 1) % HistoryMetric::ALL.len();
     }
 
-    pub fn get_order_receiver(&self) -> broadcast::Receiver<OrderEvent> {
-        self.order_sender.subscribe()
+    pub fn previous_metric(&mut self) {
+        self.metric_index = (self.metric_index + HistoryMetric::ALL.len() - 1) % HistoryMetric::ALL.len();
     }
-    
-    pub fn is_connected(&self) -> bool {
-        self.connected.load(std::sync::atomic::Ordering::Relaxed)
+
+    pub fn next_range(&mut self) {
+        self.range_index = (self.range_index + 1).min(CHART_RANGES.len() - 1);
+        self.reset_view();
     }
-}
 
-impl HyperliquidProvider {
-    pub async fn new(config: &Config) -> Result<Self> {
-        info!("🚀 Initializing HyperliquidProvider with API: {}", config.hyperliquid_api_url);
-        
-        let info_client = InfoClient::new(config.hyperliquid_api_url.clone());
-        
-        let ws_manager = if config.enable_websocket {
-            let ws_url = config.hyperliquid_api_url
-                .replace("https://", "wss://")
-                .replace("http://", "ws://") + "/ws";
-            info!("🔌 WebSocket URL: {}", ws_url);
-            Some(WsManager::new(ws_url))
-        } else {
-            info!("🔌 WebSocket disabled in config");
-            None
+    pub fn previous_range(&mut self) {
+        self.range_index = self.range_index.saturating_sub(1);
+        self.reset_view();
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom + 1).min(MAX_CHART_ZOOM);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = self.zoom.saturating_sub(1);
+        self.clamp_pan();
+    }
+
+    /// Pans a quarter of the visible window back in time, never past the retained history.
+    pub fn pan_left(&mut self) {
+        self.pan_secs += self.window_secs() / 4;
+        self.clamp_pan();
+    }
+
+    pub fn pan_right(&mut self) {
+        self.pan_secs = (self.pan_secs - self.window_secs() / 4).max(0);
+    }
+
+    pub fn reset_view(&mut self) {
+        self.zoom = 0;
+        self.pan_secs = 0;
+    }
+
+    /// Shows the crosshair on the newest sample, or hides it.
+    pub fn toggle_crosshair(&mut self) {
+        self.cursor = match self.cursor {
+            Some(_) => None,
+            None => Some(1.0),
         };
-        
-        // Start with defaults
-        let mut monitored_assets = Self::get_default_monitored_assets();
-        
-        let provider = Self {
-            info_client,
-            ws_manager,
-            user_address: config.user_address.clone(),
-            monitored_assets: monitored_assets.clone(),
+    }
+
+    /// Moves the crosshair back a step, panning once it reaches the left edge;
+    /// pans directly while the crosshair is hidden.
+    pub fn step_back(&mut self) {
+        let Some(cursor) = self.cursor else {
+            return self.pan_left();
         };
-        
-        info!("✅ Testing API connectivity...");
-        match provider.info_client.get_meta().await {
-            Ok(_) => {
-                info!("✅ API connectivity test successful");
-            }
-            Err(e) => {
-                error!("❌ API connectivity test failed: {}", e);
-                return Err(anyhow::anyhow!("Failed to connect to Hyperliquid API: {}", e));
-            }
+        if cursor - CROSSHAIR_STEP >= 0.0 {
+            self.cursor = Some(cursor - CROSSHAIR_STEP);
+            return;
+        }
+        let before = self.pan_secs;
+        self.pan_left();
+        // Keep the crosshair on the same instant as the window slides under it.
+        let shift = (self.pan_secs - before) as f64 / self.window_secs() as f64;
+        self.cursor = Some((cursor + shift - CROSSHAIR_STEP).max(0.0));
+    }
+
+    pub fn step_forward(&mut self) {
+        let Some(cursor) = self.cursor else {
+            return self.pan_right();
+        };
+        if cursor + CROSSHAIR_STEP <= 1.0 {
+            self.cursor = Some(cursor + CROSSHAIR_STEP);
+            return;
+        }
+        let before = self.pan_secs;
+        self.pan_right();
+        let shift = (before - self.pan_secs) as f64 / self.window_secs() as f64;
+        self.cursor = Some((cursor - shift + CROSSHAIR_STEP).min(1.0));
+    }
+
+    fn clamp_pan(&mut self) {
+        let max_pan = (RETENTION_MS / 1000 - self.window_secs()).max(0);
+        self.pan_secs = self.pan_secs.clamp(0, max_pan);
+    }
+}
+
+/// `selected` among `assets`, or the first of them when nothing is selected or
+/// the selected asset has no data.
+fn shown_asset<'a>(selected: Option<&str>, assets: &[&'a str]) -> Option<(usize, &'a str)> {
+    let position = selected.and_then(|selected| assets.iter().position(|coin| *coin == selected)).unwrap_or(0);
+    assets.get(position).map(|coin| (position, *coin))
+}
+
+/// Moves `selected` `by` places on among `assets`, wrapping around.
+fn step_asset(selected: &mut Option<String>, assets: &[&str], by: usize) {
+    if let Some((position, _)) = shown_asset(selected.as_deref(), assets) {
+        *selected = Some(assets[(position + by) % assets.len()].to_string());
+    }
+}
+
+/// Which asset the Liquidity tab's depth ladder shows, plus the previous book so
+/// levels that changed can flash briefly after each update.
+#[derive(Default)]
+pub struct LadderState {
+    /// By name, as for `ChartState::asset`.
+    pub asset: Option<String>,
+    coin: String,
+    book_time: u64,
+    sizes: HashMap<(bool, Decimal), Decimal>,
+    /// When each (is_bid, price) level last changed and whether its size grew.
+    flashes: HashMap<(bool, Decimal), (Instant, bool)>,
+}
+
+impl LadderState {
+    pub fn next_asset(&mut self, assets: &[&str]) {
+        step_asset(&mut self.asset, assets, 1);
+    }
+
+    pub fn previous_asset(&mut self, assets: &[&str]) {
+        step_asset(&mut self.asset, assets, assets.len().saturating_sub(1));
+    }
+
+    /// Diffs a book against the last one seen for the same asset. Switching assets
+    /// starts over without flashing every level.
+    fn observe(&mut self, book: &L2Snapshot) {
+        if book.coin == self.coin && book.time == self.book_time {
+            return;
+        }
+
+        let now = Instant::now();
+        let fresh = book.coin != self.coin;
+        if fresh {
+            self.flashes.clear();
         }
-        
-        // Get user's actual positions
-        info!("📊 Fetching user's actual positions...");
-        match provider.info_client.get_clearinghouse_state(&config.user_address).await {
-            Ok(state_data) => {
-                let mut user_assets = Vec::new();
-                
-                // Extract assets from current positions
-                if let Some(positions) = state_data["assetPositions"].as_array() {
-                    for pos in positions {
-                        if let Some(position) = pos.get("position") {
-                            if let Some(coin) = position["coin"].as_str() {
-                                // Only add if position size is non-zero
-                                let size = position["szi"].as_str().unwrap_or("0");
-                                if size != "0" && !user_assets.contains(&coin.to_string()) {
-                                    user_assets.push(coin.to_string());
-                                }
-                            }
-                        }
-                    }
+
+        let mut sizes = HashMap::new();
+        for (is_bid, levels) in [(true, &book.bids), (false, &book.asks)] {
+            for level in levels {
+                let key = (is_bid, level.px);
+                let previous = self.sizes.get(&key).copied();
+                if !fresh && previous != Some(level.sz) {
+                    self.flashes.insert(key, (now, previous.is_none_or(|sz| level.sz > sz)));
                 }
-                
-                // If user has positions, use those; otherwise keep defaults
-                if !user_assets.is_empty() {
-                    monitored_assets = user_assets;
-                    info!("📊 Found {} positions for user: {:?}", monitored_assets.len(), monitored_assets);
+                sizes.insert(key, level.sz);
+            }
+        }
+
+        self.flashes.retain(|_, (at, _)| now.duration_since(*at) < LEVEL_FLASH);
+        self.sizes = sizes;
+        self.coin = book.coin.clone();
+        self.book_time = book.time;
+    }
+
+    /// Style for a level's size cell: a solid flash right after it changed, fading to
+    /// colored text, then plain.
+    fn size_style(&self, key: (bool, Decimal)) -> Style {
+        match self.flashes.get(&key) {
+            Some((at, grew)) => {
+                let color = if *grew { Color::Green } else { Color::Red };
+                if at.elapsed() < LEVEL_FLASH / 2 {
+                    Style::default().fg(Color::Black).bg(color)
+                } else if at.elapsed() < LEVEL_FLASH {
+                    Style::default().fg(color)
                 } else {
-                    info!("📊 No positions found, using default assets");
+                    Style::default()
                 }
             }
-            Err(e) => {
-                warn!("⚠️ Failed to fetch user positions: {}, using defaults", e);
-            }
+            None => Style::default(),
+        }
+    }
+}
+
+impl Default for UIState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UIState {
+    pub fn new() -> Self {
+        Self {
+            selected_tab: 0,
+            scroll: [TabScroll::default(); TAB_TITLES.len()],
+            test_mode: false,
+            chart: ChartState::default(),
+            equity_chart: ChartState {
+                range_index: CHART_RANGES.len() - 1,
+                ..ChartState::default()
+            },
+            ladder: LadderState::default(),
+            log_state: LogFilters::default().widget_state(),
+            log_filters: LogFilters::default(),
+            log_capture: true,
+            quit_prompt: false,
+            denomination: Denomination::Usd,
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.selected_tab = (self.selected_tab + 1) % TAB_TITLES.len();
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.selected_tab = (self.selected_tab + TAB_TITLES.len() - 1) % TAB_TITLES.len();
+    }
+
+    /// Jumps straight to a tab by its zero-based index; out-of-range indices are ignored.
+    pub fn select_tab(&mut self, index: usize) {
+        if index < TAB_TITLES.len() {
+            self.selected_tab = index;
+        }
+    }
+
+    /// The chart that zoom, pan and crosshair keys act on in the selected tab, if any.
+    pub fn active_chart(&mut self) -> Option<&mut ChartState> {
+        match self.selected_tab {
+            CHARTS_TAB => Some(&mut self.chart),
+            PERFORMANCE_TAB => Some(&mut self.equity_chart),
+            _ => None,
         }
-        
-        // Create the final provider with user's assets
-        let provider = Self {
-            info_client: provider.info_client,
-            ws_manager: provider.ws_manager,
-            user_address: provider.user_address,
-            monitored_assets,
+    }
+
+    pub fn scroll_up(&mut self) {
+        let scroll = &mut self.scroll[self.selected_tab];
+        scroll.offset = scroll.offset.saturating_sub(1);
+    }
+
+    /// Scrolling past the end is undone on the next draw, which clamps to the content.
+    pub fn scroll_down(&mut self) {
+        let scroll = &mut self.scroll[self.selected_tab];
+        scroll.offset = scroll.offset.saturating_add(1);
+    }
+
+    pub fn page_up(&mut self) {
+        let scroll = &mut self.scroll[self.selected_tab];
+        scroll.offset = scroll.offset.saturating_sub(scroll.page_size());
+    }
+
+    pub fn page_down(&mut self) {
+        let scroll = &mut self.scroll[self.selected_tab];
+        scroll.offset = scroll.offset.saturating_add(scroll.page_size());
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll[self.selected_tab].offset = 0;
+    }
+
+    pub fn log_key(&mut self, event: TuiWidgetEvent) {
+        self.log_filters.apply(&mut self.log_state, event);
+    }
+
+    /// Restores the working context saved by [`UIState::save`]. A missing file is not an error.
+    pub fn restore(&mut self, path: &str) -> anyhow::Result<()> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
         };
-        
-        if let Some(ref ws_manager) = provider.ws_manager {
-            if let Err(e) = ws_manager.connect_and_subscribe(&provider.monitored_assets).await {
-                warn!("⚠️ Failed to connect WebSocket, falling back to HTTP only: {}", e);
-            }
+        let saved: SavedUiState = serde_json::from_str(&contents)?;
+
+        self.select_tab(saved.selected_tab);
+        self.chart.metric_index = saved.chart_metric % HistoryMetric::ALL.len();
+        self.chart.range_index = saved.chart_range.min(CHART_RANGES.len() - 1);
+        self.chart.zoom = saved.chart_zoom.min(MAX_CHART_ZOOM);
+        self.chart.asset = saved.chart_asset;
+        self.ladder.asset = saved.ladder_asset;
+        self.log_state = saved.log_filters.widget_state();
+        self.log_filters = saved.log_filters;
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let saved = SavedUiState {
+            selected_tab: self.selected_tab,
+            chart_metric: self.chart.metric_index,
+            chart_range: self.chart.range_index,
+            chart_zoom: self.chart.zoom,
+            chart_asset: self.chart.asset.clone(),
+            ladder_asset: self.ladder.asset.clone(),
+            log_filters: self.log_filters.clone(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+}
+
+/// The parts of [`UIState`] worth keeping across restarts. Scroll positions and chart
+/// panning are left out so a restart always opens on live data.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedUiState {
+    selected_tab: usize,
+    chart_metric: usize,
+    chart_range: usize,
+    chart_zoom: u32,
+    chart_asset: Option<String>,
+    ladder_asset: Option<String>,
+    log_filters: LogFilters,
+}
+
+pub fn draw(
+    f: &mut Frame,
+    state: &mut UIState,
+    config: &Config,
+    metrics: &GlobalMetrics,
+    alerts: &[Alert],
+    history: &MetricHistory,
+) {
+    if f.size().width < MIN_WIDTH || f.size().height < MIN_HEIGHT {
+        format::set(&config.ui_settings);
+        draw_vitals(f, f.size(), &config.alert_thresholds, &Freshness::new(config, metrics), metrics);
+        if state.quit_prompt {
+            draw_quit_dialog(f, f.size(), alerts);
+        }
+        if config.ui_settings.ascii_mode {
+            asciify(f.buffer_mut());
         }
-        
-        info!("✅ HyperliquidProvider initialized successfully");
-        Ok(provider)
+        return;
     }
-    
-    fn get_default_monitored_assets() -> Vec<String> {
-        vec![
-            "BTC".to_string(),
-            "ETH".to_string(), 
-            "SOL".to_string(),
-            "DOGE".to_string(),
-            "AVAX".to_string(),
-            "ARB".to_string(),
-            "MATIC".to_string(),
-            "OP".to_string(),
-            "LINK".to_string(),
-            "ATOM".to_string(),
-            "DOT".to_string(),
-            "UNI".to_string(),
-            "CRV".to_string(),
-            "AAVE".to_string(),
-            "SNX".to_string(),
-            "MKR".to_string(),
-            "COMP".to_string(),
-            "YFI".to_string(),
-            "SUSHI".to_string(),
-            "1INCH".to_string(),
-            "ENS".to_string(),
-            "GMX".to_string(),
-            "BLUR".to_string(),
-            "LDO".to_string(),
-            "RPL".to_string(),
-            "RNDR".to_string(),
-            "IMX".to_string(),
-            "SAND".to_string(),
-            "MANA".to_string(),
-            "AXS".to_string(),
-            "APE".to_string(),
-            "GALA".to_string(),
-            "FTM".to_string(),
-            "NEAR".to_string(),
-            "FIL".to_string(),
-            "APT".to_string(),
-            "SUI".to_string(),
-            "SEI".to_string(),
-            "INJ".to_string(),
-            "TIA".to_string(),
-            "PYTH".to_string(),
-            "JUP".to_string(),
-            "WIF".to_string(),
-            "BONK".to_string(),
-            "PEPE".to_string(),
-            "SHIB".to_string(),
-            "FLOKI".to_string(),
-            "MEME".to_string(),
-            "ORDI".to_string(),
-            "STX".to_string(),
-        ]
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+        .split(f.size());
+
+    let thresholds = &config.alert_thresholds;
+    let freshness = Freshness::new(config, metrics);
+    format::set(&config.ui_settings);
+    let denomination = format::denominate(state.denomination, metrics);
+
+    draw_tabs(f, chunks[0], state, &tab_badges(thresholds, metrics, alerts));
+    draw_status_bar(f, chunks[2], state, config, &freshness, metrics, denomination);
+
+    match state.selected_tab {
+        0 => draw_overview_tab(f, chunks[1], &config.layout, thresholds, &freshness, metrics, alerts),
+        1 => draw_liquidity_tab(f, chunks[1], &mut state.scroll[1], &mut state.ladder, thresholds, &freshness, metrics),
+        2 => draw_risk_tab(f, chunks[1], &mut state.scroll[2], thresholds, &freshness, metrics, history),
+        3 => draw_performance_tab(
+            f,
+            chunks[1],
+            &mut state.scroll[3],
+            &state.equity_chart,
+            thresholds,
+            &freshness,
+            metrics,
+            alerts,
+            history,
+        ),
+        4 => draw_positions_tab(f, chunks[1], thresholds, &freshness, metrics),
+        5 => draw_alerts_tab(f, chunks[1], &mut state.scroll[5], alerts),
+        6 => draw_charts_tab(f, chunks[1], &state.chart, history),
+        7 => draw_heatmap_tab(f, chunks[1], &mut state.scroll[7], thresholds, &freshness, metrics),
+        _ => draw_logs_tab(f, chunks[1], state),
     }
-    
-    #[allow(dead_code)]
-    pub async fn update_monitored_assets_from_meta(&mut self, meta: &Meta) {
-        let major_assets: Vec<String> = meta.universe
-            .iter()
-            .filter(|asset| {
-                let name = &asset.name;
-                name == "BTC" || name == "ETH" || name == "SOL" || 
-                name == "DOGE" || name == "AVAX" || asset.max_leverage >= 10
-            })
-            .take(10)
-            .map(|asset| asset.name.clone())
-            .collect();
-            
-        if major_assets != self.monitored_assets {
-            info!("📊 Updating monitored assets: {:?}", major_assets);
-            self.monitored_assets = major_assets;
-            
-            if let Some(ref ws_manager) = self.ws_manager {
-                if ws_manager.is_connected() {
-                    if let Err(e) = ws_manager.connect_and_subscribe(&self.monitored_assets).await {
-                        warn!("⚠️ Failed to resubscribe to new assets: {}", e);
-                    }
-                }
+
+    if state.quit_prompt {
+        draw_quit_dialog(f, chunks[1], alerts);
+    }
+
+    if config.ui_settings.ascii_mode {
+        asciify(f.buffer_mut());
+    }
+}
+
+/// Centered confirmation overlay listing Critical alerts nobody has acknowledged yet.
+fn draw_quit_dialog(f: &mut Frame, area: Rect, alerts: &[Alert]) {
+    let pending = unacknowledged_critical(alerts);
+    let shown = pending.len().min(6);
+
+    let width = area.width.min(72);
+    let height = area.height.min(6 + shown as u16 + u16::from(pending.len() > shown));
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let mut lines = vec![Line::from("")];
+    if pending.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No unacknowledged Critical alerts.",
+            Style::default().fg(Color::Green),
+        ))
+        .alignment(Alignment::Center));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!("{} unacknowledged Critical alert(s):", pending.len()),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+        .alignment(Alignment::Center));
+        for alert in pending.iter().take(shown) {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" [{}, {}] ", format::time(alert.timestamp, "%H:%M:%S"), format::ago(alert.timestamp)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(format!("{}: ", alert.metric), Style::default().fg(Color::Cyan)),
+                Span::raw(alert.message.clone()),
+            ]));
+        }
+        if pending.len() > shown {
+            lines.push(Line::from(Span::styled(
+                format!(" … and {} more", pending.len() - shown),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("y", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(" quit   "),
+        Span::styled("n/Esc", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(" keep monitoring   "),
+        Span::styled("a", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(" acknowledge all"),
+    ])
+    .alignment(Alignment::Center));
+
+    let dialog = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("⏻  Quit HLP Toshogu?")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(if pending.is_empty() { Color::Yellow } else { Color::Red })),
+        );
+    f.render_widget(Clear, popup);
+    f.render_widget(dialog, popup);
+}
+
+/// Tabs that display each alert metric, so its badge shows up where the operator can act on it.
+fn alert_tabs(metric: &str) -> &'static [usize] {
+    match metric {
+        "VPIN" | "Position Concentration" => &[2, 7],
+        "Liquidation Risk" | "Max Drawdown" | "Oracle Divergence" | "OI Cap" | "Whale Activity" => &[2],
+        "Liquidation Distance" => &[4],
+        "Phantom Liquidity" | "Cancel Rate" | "Fleeting Orders" => &[1],
+        "Spread" | "Order Book Imbalance" => &[1, 7],
+        "Utilization" => &[3],
+        // The status bar's L1 lag and latency, with the Overview as their home.
+        "Chain Stall" | "API Latency" => &[0],
+        _ => &[],
+    }
+}
+
+/// Per tab: number of Warning/Critical conditions active right now, and whether any is Critical.
+/// The Alerts tab instead counts metrics with unacknowledged Warning/Critical alerts.
+fn tab_badges(thresholds: &AlertThresholds, metrics: &GlobalMetrics, alerts: &[Alert]) -> [(usize, bool); TAB_TITLES.len()] {
+    let mut badges = [(0, false); TAB_TITLES.len()];
+
+    if metrics.last_update.is_some() {
+        for condition in check_alerts(metrics, thresholds) {
+            if condition.level == AlertLevel::Info {
+                continue;
+            }
+            for &tab in alert_tabs(&condition.metric) {
+                badges[tab].0 += 1;
+                badges[tab].1 |= condition.level == AlertLevel::Critical;
             }
         }
     }
-    
-    pub fn get_live_trades(&self) -> Option<broadcast::Receiver<Fill>> {
-        self.ws_manager.as_ref().map(|ws| ws.get_trade_receiver())
-    }
-    
-    pub fn get_live_l2_updates(&self) -> Option<broadcast::Receiver<L2Snapshot>> {
-        self.ws_manager.as_ref().map(|ws| ws.get_l2_receiver())
-    }
-
-    pub fn get_live_orders(&self) -> Option<broadcast::Receiver<OrderEvent>> {
-        self.ws_manager.as_ref().map(|ws| ws.get_order_receiver())
-    }
-    
-    #[allow(dead_code)]
-    pub fn get_monitored_assets(&self) -> &[String] {
-        &self.monitored_assets
-    }
-    
-    async fn convert_user_state(&self, data: Value) -> Result<UserState> {
-        debug!("📊 Converting user state data: {}", data);
-        
-        let margin_summary = data.get("marginSummary")
-            .ok_or_else(|| anyhow::anyhow!("Missing marginSummary in response"))?;
-        let cross_margin_summary = data.get("crossMarginSummary")
-            .ok_or_else(|| anyhow::anyhow!("Missing crossMarginSummary in response"))?;
-        
-        let account_value = parse_decimal(margin_summary["accountValue"].as_str().unwrap_or("0"));
-        let total_margin_used = parse_decimal(cross_margin_summary["totalMarginUsed"].as_str().unwrap_or("0"));
-        let total_ntl_pos = parse_decimal(cross_margin_summary["totalNtlPos"].as_str().unwrap_or("0"));
-        let total_raw_usd = parse_decimal(cross_margin_summary["totalRawUsd"].as_str().unwrap_or("0"));
-        
-        let positions: Vec<_> = data["assetPositions"]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .filter_map(|pos| {
-                let position = pos.get("position")?;
-                let symbol = position["coin"].as_str().unwrap_or("").to_string();
-                let size = parse_decimal(position["szi"].as_str().unwrap_or("0"));
-                
-                // Only include non-zero positions
-                if size == rust_decimal::Decimal::ZERO {
-                    return None;
-                }
-                
-                Some(Position {
-                    symbol: symbol.clone(),
-                    size,
-                    entry_px: position.get("entryPx").and_then(|v| v.as_str()).map(parse_decimal),
-                    position_value: parse_decimal(position["positionValue"].as_str().unwrap_or("0")),
-                    unrealized_pnl: parse_decimal(position["unrealizedPnl"].as_str().unwrap_or("0")),
-                    margin_used: parse_decimal(position["marginUsed"].as_str().unwrap_or("0")),
-                })
-            })
-            .collect();
-            
-        info!("✅ Converted user state - Account Value: ${:.2}, Margin Used: ${:.2}, Active Positions: {}", 
-              account_value.to_f64().unwrap_or(0.0),
-              total_margin_used.to_f64().unwrap_or(0.0),
-              positions.len());
-        
-        // Log position details for debugging
-        for pos in &positions {
-            debug!("  Position: {} Size: {} Value: ${:.2} PnL: ${:.2}", 
-                pos.symbol, 
-                pos.size,
-                pos.position_value.to_f64().unwrap_or(0.0),
-                pos.unrealized_pnl.to_f64().unwrap_or(0.0)
-            );
-        }
-        
-        Ok(UserState {
-            account_value,
-            total_margin_used,
-            total_ntl_pos,
-            total_raw_usd,
-            positions,
-        })
+
+    let mut pending: Vec<(&str, bool)> = Vec::new();
+    for alert in alerts.iter().filter(|a| !a.acknowledged && a.level != AlertLevel::Info) {
+        let critical = alert.level == AlertLevel::Critical;
+        match pending.iter_mut().find(|(metric, _)| *metric == alert.metric) {
+            Some((_, any_critical)) => *any_critical |= critical,
+            None => pending.push((&alert.metric, critical)),
+        }
     }
-    
-    async fn convert_meta(&self, data: Value) -> Result<Meta> {
-        debug!("📊 Converting meta data");
-        
-        let universe: Vec<_> = data["universe"]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|asset| AssetInfo {
-                name: asset["name"].as_str().unwrap_or("").to_string(),
-                sz_decimals: asset["szDecimals"].as_u64().unwrap_or(0) as u8,
-                max_leverage: asset["maxLeverage"].as_u64().unwrap_or(1) as u32,
-                only_isolated: asset["onlyIsolated"].as_bool().unwrap_or(false),
-            })
-            .collect();
-            
-        info!("✅ Converted meta - {} assets in universe", universe.len());
-        
-        Ok(Meta {
-            universe,
+    badges[ALERTS_TAB] = (pending.len(), pending.iter().any(|(_, critical)| *critical));
+
+    badges
+}
+
+fn draw_tabs(f: &mut Frame, area: Rect, state: &UIState, badges: &[(usize, bool); TAB_TITLES.len()]) {
+    let compact = LayoutMode::for_width(area.width) == LayoutMode::Compact;
+    let titles: Vec<Line> = TAB_TITLES
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            let (key_style, title_style) = if i == state.selected_tab {
+                (
+                    Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (Style::default().fg(Color::DarkGray), Style::default().fg(Color::White))
+            };
+
+            let (count, critical) = badges[i];
+            let badge = (count > 0).then(|| {
+                Span::styled(
+                    format!(" ({}{})", count, if critical { "!" } else { "" }),
+                    Style::default()
+                        .fg(if critical { Color::Red } else { Color::Yellow })
+                        .add_modifier(Modifier::BOLD),
+                )
+            });
+
+            // Narrow terminals only spell out the active tab; the rest are reachable by number.
+            let mut spans = vec![Span::styled(format!("{}", i + 1), key_style)];
+            if !compact || i == state.selected_tab {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(*title, title_style));
+            }
+            spans.extend(badge);
+            Line::from(spans)
         })
+        .collect();
+
+    let tabs = Tabs::new(titles)
+        .block(Block::default().borders(Borders::ALL).title("🏛️  HLP Toshogu"))
+        .select(state.selected_tab)
+        .highlight_style(Style::default().add_modifier(Modifier::UNDERLINED))
+        .divider(Span::styled("│", Style::default().fg(Color::DarkGray)));
+
+    f.render_widget(tabs, area);
+}
+
+/// VPIN, phantom liquidity, liquidation risk and the alerts firing, for a
+/// terminal too small for the tabs: one per line in a panel, or all on one
+/// line when even the panel does not fit.
+fn draw_vitals(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let risk = &metrics.risk_metrics;
+    let firing: Vec<Alert> = match metrics.last_update {
+        Some(_) => check_alerts(metrics, thresholds).into_iter().filter(|alert| alert.level != AlertLevel::Info).collect(),
+        None => Vec::new(),
+    };
+    let critical = firing.iter().filter(|alert| alert.level == AlertLevel::Critical).count();
+    let (alert_text, alert_color) = match (firing.len(), critical) {
+        (0, _) => ("0".to_string(), Color::Green),
+        (count, 0) => (count.to_string(), Color::Yellow),
+        (count, critical) => (format!("{} ({} crit)", count, critical), Color::Red),
+    };
+    let (age_text, age_color) = match (metrics.last_update, freshness.poll_age) {
+        (Some(ts), Some(age)) => (format::ago(ts), freshness.color(Source::Poll, age)),
+        _ => ("waiting".to_string(), Color::Red),
+    };
+    let vitals = [
+        ("VPIN", format!("{:.3}", risk.vpin_score), vpin_color(risk.vpin_score, thresholds)),
+        ("PLI", format!("{:.1}%", risk.phantom_liquidity_index * 100.0), pli_color(risk.phantom_liquidity_index, thresholds)),
+        ("Liq risk", format!("{:.3}", risk.liquidation_risk_score), liquidation_color(risk.liquidation_risk_score, thresholds)),
+        ("Alerts", alert_text, alert_color),
+        ("Data", age_text, age_color),
+    ];
+    let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+
+    if area.height < 6 || area.width < 24 {
+        let mut spans = Vec::new();
+        for (i, (label, value, color)) in vitals.into_iter().take(4).enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(" · ", Style::default().fg(Color::DarkGray)));
+            }
+            spans.push(Span::raw(format!("{} ", label)));
+            spans.push(Span::styled(value, bold(color)));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)).wrap(Wrap { trim: true }), area);
+        return;
     }
-    
-    async fn convert_fills(&self, data: Value) -> Result<Vec<Fill>> {
-        debug!("📊 Converting fills data");
-        
-        let fills: Vec<_> = data
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|fill| Fill {
-                coin: fill["coin"].as_str().unwrap_or("").to_string(),
-                px: parse_decimal(fill["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(fill["sz"].as_str().unwrap_or("0")),
-                side: fill["side"].as_str().unwrap_or("").to_string(),
-                time: fill["time"].as_u64().unwrap_or(0),
-                start_position: parse_decimal(fill["startPosition"].as_str().unwrap_or("0")),
-                dir: fill["dir"].as_str().unwrap_or("").to_string(),
-                closed_pnl: parse_decimal(fill["closedPnl"].as_str().unwrap_or("0")),
-                hash: fill["hash"].as_str().unwrap_or("").to_string(),
-                oid: fill["oid"].as_u64().unwrap_or(0),
-                crossed: fill["crossed"].as_bool().unwrap_or(false),
-                fee: parse_decimal(fill["fee"].as_str().unwrap_or("0")),
-            })
-            .collect();
-            
-        info!("✅ Converted {} fills", fills.len());
-        Ok(fills)
-    }
-    
-    async fn convert_l2_snapshot(&self, coin: &str, data: Value) -> Result<L2Snapshot> {
-        let levels = data.get("levels")
-            .ok_or_else(|| anyhow::anyhow!("Missing levels in L2 response for {}", coin))?;
-        
-        let bids = levels[0]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        let asks = levels[1]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|level| OrderBookLevel {
-                px: parse_decimal(level["px"].as_str().unwrap_or("0")),
-                sz: parse_decimal(level["sz"].as_str().unwrap_or("0")),
-                n: level["n"].as_u64().unwrap_or(0) as u32,
-            })
-            .collect();
-            
-        Ok(L2Snapshot {
-            coin: coin.to_string(),
-            time: data["time"].as_u64().unwrap_or(0),
-            bids,
-            asks,
-        })
+
+    let mut lines: Vec<Line> = vitals
+        .into_iter()
+        .map(|(label, value, color)| Line::from(vec![Span::raw(format!("{:<9}", label)), Span::styled(value, bold(color))]))
+        .collect();
+    if area.height >= lines.len() as u16 + 4 {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("{}×{} shows the tabs", MIN_WIDTH, MIN_HEIGHT),
+            Style::default().fg(Color::DarkGray),
+        )));
     }
+    let panel = Paragraph::new(lines).block(Block::default().title("🩺 Vitals").borders(Borders::ALL));
+    f.render_widget(panel, area);
 }
 
-#[async_trait]
-impl DataProvider for HyperliquidProvider {
-    async fn get_vault_summary(&self) -> Result<VaultSummary> {
-        info!("📊 Creating synthetic vault summary from user state");
-        
-        let user_state = self.get_user_state().await?;
-        
-        let tvl = user_state.account_value;
-        let equity = user_state.account_value;
-        let portfolio_value = user_state.total_raw_usd;
-        let deployed_liquidity = user_state.total_margin_used;
-        let idle_liquidity = user_state.total_raw_usd - user_state.total_margin_used;
-        
-        let all_time_pnl = user_state.positions.iter()
-            .map(|pos| pos.unrealized_pnl)
-            .sum::<rust_decimal::Decimal>();
-        
-        let max_drawdown = if all_time_pnl < rust_decimal::Decimal::ZERO {
-            (all_time_pnl / equity).to_f64().unwrap_or(0.0).abs()
-        } else {
-            0.0
+fn draw_status_bar(f: &mut Frame, area: Rect, state: &UIState, config: &Config, freshness: &Freshness, metrics: &GlobalMetrics, denomination: Denomination) {
+    let status = &metrics.system_status;
+    let separator = || Span::styled(" │ ", Style::default().fg(Color::DarkGray));
+
+    let mode = match (&config.operating_mode, state.test_mode) {
+        (OperatingMode::Live, _) if config.chaos.enabled => ("LIVE+CHAOS", Color::Red),
+        (OperatingMode::Live, false) => ("LIVE", Color::Green),
+        (OperatingMode::Live, true) => ("LIVE+TEST", Color::Yellow),
+        (OperatingMode::Demo, _) => ("DEMO", Color::Yellow),
+    };
+
+    let (age_text, age_color) = match freshness.poll_age {
+        Some(age) => (format!("{:.1}s", age), freshness.color(Source::Poll, age)),
+        None => ("--".to_string(), Color::Red),
+    };
+
+    let thresholds = &config.alert_thresholds;
+    let latency_color = |ms: f64| ratio_color(ms, thresholds.api_latency_warning_ms, thresholds.api_latency_critical_ms);
+    let endpoint = |name: &str| status.latency.iter().find(|stats| stats.endpoint == name);
+    // p50/p95/p99 over recent info requests, colored by p95.
+    let latency = match (endpoint("info"), status.api_latency_ms) {
+        (Some(info), _) => Span::styled(
+            format!("{:.0}/{:.0}/{:.0}ms", info.p50_ms, info.p95_ms, info.p99_ms),
+            Style::default().fg(latency_color(info.p95_ms)),
+        ),
+        (None, Some(ms)) => Span::styled(format!("{:.0}ms", ms), Style::default().fg(latency_color(ms))),
+        (None, None) => Span::styled("--", Style::default().fg(Color::DarkGray)),
+    };
+    let handling = match endpoint("ws_message") {
+        Some(ws) => Span::styled(format!(" p99 {:.1}ms", ws.p99_ms), Style::default().fg(Color::DarkGray)),
+        None => Span::raw(""),
+    };
+
+    let alerts_color = if status.active_alerts > 0 { Color::Red } else { Color::Green };
+
+    let chain = match status.block_lag_secs {
+        Some(lag) if lag > thresholds.chain_lag_critical_secs => Span::styled(format!("stalled {:.0}s", lag), Style::default().fg(Color::Red)),
+        Some(lag) => Span::styled(
+            format!("{:.1}s", lag),
+            Style::default().fg(ratio_color(lag, thresholds.chain_lag_warning_secs, thresholds.chain_lag_critical_secs)),
+        ),
+        None => Span::styled("--", Style::default().fg(Color::DarkGray)),
+    };
+
+    // Only while the books of assets without positions are still loading.
+    let coverage = if status.books_covered < status.books_monitored {
+        vec![
+            separator(),
+            Span::raw("Books "),
+            Span::styled(format!("{}/{}", status.books_covered, status.books_monitored), Style::default().fg(Color::Yellow)),
+        ]
+    } else {
+        Vec::new()
+    };
+
+    let dropped = match status.streams.iter().map(|stream| stream.dropped).sum::<u64>() {
+        0 => Span::raw(""),
+        dropped => Span::styled(format!(" ({} dropped)", dropped), Style::default().fg(Color::Yellow)),
+    };
+
+    let mut spans = vec![
+        Span::styled(format!(" {} ", mode.0), Style::default().fg(Color::Black).bg(mode.1).add_modifier(Modifier::BOLD)),
+        separator(),
+        Span::raw("HTTP "),
+        link_span(status.http),
+        separator(),
+        Span::raw("WS "),
+        link_span(status.websocket),
+        Span::raw(format!(" {:.1} msg/s", status.ws_messages_per_sec)),
+        handling,
+        dropped,
+        separator(),
+        Span::raw("Data age "),
+        Span::styled(age_text, Style::default().fg(age_color)),
+        separator(),
+        Span::raw("L1 "),
+        chain,
+    ];
+    spans.extend(coverage);
+    spans.extend(vec![
+        separator(),
+        Span::raw("API "),
+        latency,
+        separator(),
+        Span::raw("Active alerts "),
+        Span::styled(status.active_alerts.to_string(), Style::default().fg(alerts_color).add_modifier(Modifier::BOLD)),
+        separator(),
+        Span::raw("In "),
+        denomination_span(state.denomination, denomination),
+        separator(),
+        Span::styled("H help · Q quit", Style::default().fg(Color::DarkGray)),
+    ]);
+    let line = Line::from(spans);
+
+    f.render_widget(Paragraph::new(line), area);
+}
+
+/// What amounts are shown in, flagged when the chosen denomination has no
+/// price yet and amounts stay in dollars.
+fn denomination_span(chosen: Denomination, shown: Denomination) -> Span<'static> {
+    let (name, price) = match chosen {
+        Denomination::Usd => ("USD", "USD"),
+        Denomination::Btc => ("BTC", "BTC"),
+        Denomination::Vault => ("Vault shares", "share"),
+    };
+    match chosen == shown {
+        true => Span::styled(name, Style::default().fg(if shown == Denomination::Usd { Color::White } else { Color::Cyan })),
+        false => Span::styled(format!("USD (no {} price)", price), Style::default().fg(Color::Yellow)),
+    }
+}
+
+fn link_span(state: LinkState) -> Span<'static> {
+    let (text, color) = match state {
+        LinkState::Connected => ("● up", Color::Green),
+        LinkState::Disconnected => ("● down", Color::Red),
+        LinkState::Disabled => ("○ off", Color::DarkGray),
+        LinkState::Unknown => ("○ n/a", Color::DarkGray),
+    };
+    Span::styled(text, Style::default().fg(color))
+}
+
+/// Which data path a panel's numbers come from: the REST polling cycle, or the
+/// WebSocket feeds (trades, books, orders) that arrive between polls.
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Poll,
+    Stream,
+}
+
+/// Age of each data path for the current frame, so panels can show how old their
+/// numbers are and when the next polling cycle is due.
+struct Freshness {
+    poll_age: Option<f64>,
+    stream_age: Option<f64>,
+    interval: f64,
+}
+
+impl Freshness {
+    fn new(config: &Config, metrics: &GlobalMetrics) -> Self {
+        let now = chrono::Utc::now();
+        let age = |ts: chrono::DateTime<chrono::Utc>| (now - ts).num_milliseconds().max(0) as f64 / 1000.0;
+        let streaming = metrics.system_status.websocket == LinkState::Connected;
+
+        Self {
+            poll_age: metrics.last_update.map(age),
+            stream_age: metrics.system_status.ws_last_message.filter(|_| streaming).map(age),
+            interval: config.update_interval_ms as f64 / 1000.0,
+        }
+    }
+
+    /// Seconds until the next polling cycle; `None` once it is overdue.
+    fn next_poll(&self) -> Option<f64> {
+        self.poll_age.map(|age| self.interval - age).filter(|remaining| *remaining >= 0.0)
+    }
+
+    /// Poll data turns yellow once a cycle runs late and red after several; streamed data
+    /// after 5s and 15s of silence.
+    fn color(&self, source: Source, age: f64) -> Color {
+        let fresh = match source {
+            Source::Poll => self.interval * 1.5,
+            Source::Stream => 5.0,
         };
-        
-        let apr = if equity > rust_decimal::Decimal::ZERO && all_time_pnl > rust_decimal::Decimal::ZERO {
-            (all_time_pnl / equity * rust_decimal::Decimal::from(365) * rust_decimal::Decimal::from(100))
-                .to_f64().unwrap_or(0.0)
+        if age <= fresh {
+            Color::Green
+        } else if age <= fresh * 3.0 {
+            Color::Yellow
         } else {
-            5.76
-        };
-        
-        info!("✅ Synthetic vault summary - TVL: ${:.2}, Equity: ${:.2}, APR: {:.2}%", 
-              tvl.to_f64().unwrap_or(0.0),
-              equity.to_f64().unwrap_or(0.0),
-              apr);
-        
-        Ok(VaultSummary {
-            vault_address: self.user_address.clone(),
-            tvl,
-            equity,
-            apr,
-            all_time_pnl,
-            max_drawdown,
-            num_depositors: 1,
-            portfolio_value,
-            deployed_liquidity,
-            idle_liquidity,
-        })
+            Color::Red
+        }
     }
-    
-    async fn get_user_state(&self) -> Result<UserState> {
-        info!("📊 Fetching user state for: {}", self.user_address);
-        let data = self.info_client.get_clearinghouse_state(&self.user_address).await?;
-        self.convert_user_state(data).await
-    }
-    
-    async fn get_meta(&self) -> Result<Meta> {
-        info!("📊 Fetching meta information");
-        let data = self.info_client.get_meta().await?;
-        self.convert_meta(data).await
-    }
-    
-    async fn get_recent_fills(&self) -> Result<Vec<Fill>> {
-        info!("📊 Fetching recent fills for: {}", self.user_address);
-        let data = self.info_client.get_user_fills(&self.user_address).await?;
-        self.convert_fills(data).await
-    }
-    
-    async fn get_l2_snapshots(&self) -> Result<HashMap<String, L2Snapshot>> {
-        info!("📊 Fetching L2 snapshots for {} assets", self.monitored_assets.len());
-        let mut snapshots = HashMap::new();
-        let mut successful_fetches = 0;
-        
-        for coin in &self.monitored_assets {
-            match self.info_client.get_l2_book(coin).await {
-                Ok(data) => {
-                    match self.convert_l2_snapshot(coin, data).await {
-                        Ok(snapshot) => {
-                            snapshots.insert(coin.clone(), snapshot);
-                            successful_fetches += 1;
-                            debug!("✅ Successfully fetched L2 for {}", coin);
-                        }
-                        Err(e) => {
-                            warn!("⚠️ Failed to convert L2 snapshot for {}: {}", coin, e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("⚠️ Failed to get L2 book for {}: {}", coin, e);
-                }
+
+    /// Bordered block with `title` on the left and the panel's data age on the right,
+    /// e.g. "poll 0.4s · next 0.6s". Streamed panels fall back to the polling age while
+    /// the WebSocket is down; the countdown, then the age, is dropped when the border is
+    /// too short to fit them beside the title.
+    fn block(&self, title: impl Into<Line<'static>>, source: Source, width: u16) -> Block<'static> {
+        let title: Line = title.into();
+        let room = (width as usize).saturating_sub(title.width() + 4);
+
+        let (label, age, source) = match (source, self.stream_age) {
+            (Source::Stream, Some(age)) => ("ws", Some(age), Source::Stream),
+            _ => ("poll", self.poll_age, Source::Poll),
+        };
+
+        let mut spans = match age {
+            Some(age) => vec![Span::styled(format!(" {} {:.1}s ", label, age), Style::default().fg(self.color(source, age)))],
+            None => vec![Span::styled(format!(" {} -- ", label), Style::default().fg(Color::DarkGray))],
+        };
+        if age.is_some() {
+            let next = match self.next_poll() {
+                Some(remaining) => format!("· next {:.1}s ", remaining),
+                None => "· next due ".to_string(),
+            };
+            if spans[0].width() + next.chars().count() <= room {
+                spans.push(Span::styled(next, Style::default().fg(Color::DarkGray)));
             }
         }
-        
-        info!("📊 Successfully fetched L2 snapshots for {}/{} assets", 
-              successful_fetches, self.monitored_assets.len());
-        
-        if snapshots.is_empty() {
-            warn!("⚠️ No L2 snapshots were successfully fetched!");
-        }
-        
-        Ok(snapshots)
-    }
-    
-    async fn get_status(&self) -> DataSourceStatus {
-        let http_status = match self.info_client.get_meta().await {
-            Ok(_) => {
-                debug!("✅ HTTP API status: Connected");
-                true
-            }
-            Err(e) => {
-                debug!("❌ HTTP API status: Error - {}", e);
-                false
-            }
-        };
-        
-        let ws_status = self.ws_manager
-            .as_ref()
-            .map(|ws| {
-                let connected = ws.is_connected();
-                debug!("🔌 WebSocket status: {}", if connected { "Connected" } else { "Disconnected" });
-                connected
-            })
-            .unwrap_or_else(|| {
-                debug!("🔌 WebSocket status: Disabled");
-                false
-            });
-        
-        match (http_status, ws_status) {
-            (true, true) => DataSourceStatus::Connected,
-            (true, false) => DataSourceStatus::Error("WebSocket disconnected, HTTP only".to_string()),
-            (false, _) => DataSourceStatus::Disconnected,
+
+        let block = Block::default().title(title).borders(Borders::ALL);
+        if spans.iter().map(Span::width).sum::<usize>() > room {
+            return block;
         }
+        block.title(Title::from(Line::from(spans)).alignment(Alignment::Right))
     }
-    
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
+
+    /// One System Status row per data path, e.g. "Polling: 0.4s old, next in 0.6s".
+    fn source_line(&self, source: Source) -> Line<'static> {
+        let (label, age) = match source {
+            Source::Poll => ("Polling: ", self.poll_age),
+            Source::Stream => ("Streaming: ", self.stream_age),
+        };
+
+        let mut spans = vec![Span::raw(label)];
+        match age {
+            Some(age) => {
+                spans.push(Span::styled(format!("{:.1}s old", age), Style::default().fg(self.color(source, age))));
+                if source == Source::Poll {
+                    let next = match self.next_poll() {
+                        Some(remaining) => format!(", next in {:.1}s", remaining),
+                        None => ", next cycle overdue".to_string(),
+                    };
+                    spans.push(Span::styled(next, Style::default().fg(Color::DarkGray)));
+                }
+            }
+            None => spans.push(Span::styled("inactive", Style::default().fg(Color::DarkGray))),
+        }
+        Line::from(spans)
     }
-}
\ No newline at end of file
+}
+
+fn draw_overview_tab(
+    f: &mut Frame,
+    area: Rect,
+    layout: &LayoutSettings,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+    alerts: &[Alert],
+) {
+    let lines = pack_overview_rows(layout, area.width);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(lines.iter().map(|(size, _)| Constraint::Fill(*size)))
+        .split(area);
+
+    for ((_, panels), row_area) in lines.iter().zip(rows.iter()) {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(panels.iter().map(|spec| Constraint::Fill(spec.size)))
+            .split(*row_area);
+
+        for (spec, cell) in panels.iter().zip(cells.iter()) {
+            draw_overview_panel(f, *cell, spec.panel, thresholds, freshness, metrics, alerts);
+        }
+    }
+}
+
+/// Re-flows the configured Overview rows for the available width: rows too wide for
+/// `MIN_PANEL_WIDTH` panels wrap onto extra lines, and on ultrawide terminals consecutive
+/// rows that fit side by side share a line. Returns (row weight, panels) per visual line.
+fn pack_overview_rows(layout: &LayoutSettings, width: u16) -> Vec<(u16, Vec<&PanelSpec>)> {
+    let per_line = (width / MIN_PANEL_WIDTH).max(1) as usize;
+    let wide = LayoutMode::for_width(width) == LayoutMode::Wide;
+    let mut lines: Vec<(u16, Vec<&PanelSpec>)> = Vec::new();
+
+    for row in &layout.overview {
+        if let Some((size, panels)) = lines.last_mut() {
+            if wide && panels.len() + row.panels.len() <= per_line {
+                *size = (*size).max(row.size);
+                panels.extend(row.panels.iter());
+                continue;
+            }
+        }
+
+        for chunk in row.panels.chunks(per_line) {
+            lines.push((row.size, chunk.iter().collect()));
+        }
+    }
+
+    lines
+}
+
+fn draw_overview_panel(
+    f: &mut Frame,
+    area: Rect,
+    panel: OverviewPanel,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+    alerts: &[Alert],
+) {
+    match panel {
+        OverviewPanel::Vault => draw_vault_panel(f, area, thresholds, freshness, metrics),
+        OverviewPanel::Vaults => draw_vaults_panel(f, area, thresholds, freshness, metrics),
+        OverviewPanel::SubAccounts => draw_sub_accounts_panel(f, area, thresholds, freshness, metrics),
+        OverviewPanel::TradeTape => draw_trade_tape(f, area, freshness, metrics),
+        OverviewPanel::LargePrints => draw_large_prints(f, area, freshness, metrics),
+        OverviewPanel::NewListings => draw_new_listings(f, area, thresholds, freshness, metrics),
+        OverviewPanel::Risk => draw_risk_panel(f, area, thresholds, freshness, metrics),
+        OverviewPanel::Liquidity => draw_liquidity_panel(f, area, thresholds, freshness, metrics),
+        OverviewPanel::Performance => draw_performance_panel(f, area, freshness, metrics),
+        OverviewPanel::Health => draw_health_panel(f, area, freshness, metrics),
+        OverviewPanel::SystemStatus => draw_system_status_panel(f, area, freshness, metrics, alerts),
+        OverviewPanel::Alerts => draw_recent_alerts(f, area, alerts),
+        OverviewPanel::Shadow => draw_shadow_panel(f, area, thresholds, freshness, metrics),
+    }
+}
+
+fn draw_vault_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let vault = &metrics.vault_metrics;
+    let mut lines = vec![
+        metric_line("TVL", format_amount(vault.tvl), Color::Cyan),
+        metric_line("Equity", format_amount(vault.equity), Color::Cyan),
+        metric_line("APR", format!("{:.2}%", vault.apr), Color::Green),
+        // Utilization gauge row, drawn below.
+        Line::from(""),
+        metric_line("Deployed", format_amount(vault.deployed_liquidity), Color::White),
+        metric_line("Idle", format_amount(vault.idle_liquidity), Color::White),
+    ];
+    if !vault.spot_balances.is_empty() {
+        lines.push(metric_line("Spot", format_amount(vault.spot_value), Color::Cyan));
+    }
+    if vault.isolated_margin > Decimal::ZERO {
+        lines.push(metric_line(
+            "Isolated",
+            format!("{} ({:.1}% of TVL)", format_amount(vault.isolated_margin), vault.isolated_utilization * 100.0),
+            ratio_color(vault.isolated_utilization, thresholds.utilization_warning, thresholds.utilization_critical),
+        ));
+    }
+    if !vault.flows.is_empty() {
+        let withdrawn = crate::metrics::net_withdrawal(&vault.flows, thresholds.withdrawal_window_ms);
+        lines.push(metric_line(
+            "Flows 24h",
+            format!("{} (PnL {})", format_amount(vault.net_flow_24h), format_amount(vault.pnl_24h)),
+            ratio_color(withdrawn, thresholds.withdrawal_warning, thresholds.withdrawal_critical),
+        ));
+    }
+
+    let title = match &vault.name {
+        Some(name) => format!("💰 Vault: {}", name),
+        None => "💰 Vault".to_string(),
+    };
+    let block = freshness.block(title, Source::Poll, area.width);
+    let inner = block.inner(area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+
+    let utilization = ThresholdGauge::new(
+        "Utilization",
+        vault.utilization_rate,
+        format!("{:.1}%", vault.utilization_rate * 100.0),
+    )
+    .thresholds(thresholds.utilization_warning, thresholds.utilization_critical)
+    .color(utilization_color(vault.utilization_rate, thresholds));
+    render_row(f, inner, 3, utilization);
+}
+
+/// Each of the vaults monitored together: size, leverage, how much of its
+/// equity is posted as margin, drawdown and the day's flows.
+fn draw_vaults_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let ratio = |part: Decimal, whole: Decimal| if whole > Decimal::ZERO { (part / whole).to_f64().unwrap_or(0.0) } else { 0.0 };
+    let rows: Vec<Row> = metrics
+        .vault_metrics
+        .vaults
+        .iter()
+        .map(|vault| {
+            let utilization = ratio(vault.margin_used, vault.equity);
+            Row::new(vec![
+                Cell::from(vault.name.clone()),
+                Cell::from(format_amount(vault.tvl)),
+                Cell::from(format_amount(vault.exposure)),
+                Cell::from(format!("{:.2}x", ratio(vault.exposure, vault.equity))),
+                Cell::from(format!("{:.1}%", utilization * 100.0))
+                    .style(Style::default().fg(ratio_color(utilization, thresholds.utilization_warning, thresholds.utilization_critical))),
+                Cell::from(format!("{:.1}%", vault.max_drawdown * 100.0))
+                    .style(Style::default().fg(ratio_color(vault.max_drawdown, thresholds.max_drawdown_warning, thresholds.max_drawdown_critical))),
+                Cell::from(format!("{:.2}%", vault.apr)),
+                Cell::from(format_amount(vault.net_flow_24h)),
+                Cell::from(vault.largest_position.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(6),
+        ],
+    )
+    .header(
+        Row::new(vec!["Vault", "TVL", "Exposure", "Lev", "Util", "DD", "APR", "Flow 24h", "Top"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block("🏦 Vaults", Source::Poll, area.width));
+    f.render_widget(table, area);
+}
+
+fn draw_sub_accounts_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let ratio = |part: Decimal, whole: Decimal| if whole > Decimal::ZERO { (part / whole).to_f64().unwrap_or(0.0) } else { 0.0 };
+    let rows: Vec<Row> = metrics
+        .vault_metrics
+        .sub_accounts
+        .iter()
+        .map(|account| {
+            let utilization = ratio(account.margin_used, account.equity);
+            let pnl_color = if account.unrealized_pnl < Decimal::ZERO { Color::Red } else { Color::Green };
+            Row::new(vec![
+                Cell::from(account.name.clone()),
+                Cell::from(format_amount(account.equity)),
+                Cell::from(format_amount(account.exposure)),
+                Cell::from(format!("{:.2}x", ratio(account.exposure, account.equity))),
+                Cell::from(format!("{:.1}%", utilization * 100.0))
+                    .style(Style::default().fg(ratio_color(utilization, thresholds.utilization_warning, thresholds.utilization_critical))),
+                Cell::from(format_amount(account.unrealized_pnl)).style(Style::default().fg(pnl_color)),
+                Cell::from(account.positions.to_string()),
+                Cell::from(account.largest_position.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(4),
+            Constraint::Length(6),
+        ],
+    )
+    .header(
+        Row::new(vec!["Account", "Equity", "Exposure", "Lev", "Util", "uPnL", "Pos", "Top"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block("👥 Sub-accounts", Source::Poll, area.width));
+    f.render_widget(table, area);
+}
+
+/// The buyer or seller of a trade, in cyan when the address book labels it.
+fn trader_cell(counterparty: &Option<Counterparty>) -> Cell<'static> {
+    match counterparty {
+        Some(counterparty) => Cell::from(counterparty.display())
+            .style(Style::default().fg(if counterparty.label.is_some() { Color::Cyan } else { Color::White })),
+        None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// `trade`'s time, asset and side.
+fn trade_cells(trade: &TapeTrade) -> [Cell<'static>; 3] {
+    let side_color = if trade.side == "B" { Color::Green } else { Color::Red };
+    [
+        Cell::from(format::time_ms(trade.time, "%H:%M:%S")),
+        Cell::from(trade.coin.clone()),
+        Cell::from(if trade.side == "B" { "Buy" } else { "Sell" }).style(Style::default().fg(side_color)),
+    ]
+}
+
+fn draw_new_listings(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let (liquidity, risk) = (&metrics.liquidity_metrics, &metrics.risk_metrics);
+    let factor = thresholds.probation_factor;
+    let reading = |value: Option<f64>, (warning, critical): (f64, f64), text: &dyn Fn(f64) -> String| match value {
+        Some(value) => Cell::from(text(value)).style(Style::default().fg(ratio_color(value, warning * factor, critical * factor))),
+        None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+    };
+    let now = chrono::Utc::now();
+    let rows: Vec<Row> = risk
+        .probation
+        .iter()
+        .map(|asset| {
+            let coin = asset.coin.as_str();
+            let age = now - asset.listed_at;
+            Row::new(vec![
+                Cell::from(coin.to_string()),
+                Cell::from(format!("{}h{:02}m", age.num_hours(), age.num_minutes() % 60)),
+                reading(
+                    liquidity.bid_ask_spread_bps.get(coin).copied(),
+                    (thresholds.spread_warning_bps, thresholds.spread_critical_bps),
+                    &|v| format!("{:.2}", v),
+                ),
+                match liquidity.depth_at_50bps.get(coin) {
+                    Some(depth) => Cell::from(format_amount(*depth)),
+                    None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+                },
+                reading(
+                    liquidity.order_book_imbalance.get(coin).map(|v| v.abs()),
+                    (thresholds.imbalance_warning, thresholds.imbalance_critical),
+                    &|v| format!("{:.3}", v),
+                ),
+                reading(
+                    risk.oracle_divergence.get(coin).copied(),
+                    (thresholds.oracle_divergence_warning, thresholds.oracle_divergence_critical),
+                    &|v| format!("{:.2}%", v * 100.0),
+                ),
+                reading(risk.vpin_by_asset.get(coin).copied(), (thresholds.vpin_warning, thresholds.vpin_critical), &|v| format!("{:.3}", v)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(6),
+        ],
+    )
+    .header(
+        Row::new(vec!["Asset", "Age", "Spread", "Depth", "Imbal", "Oracle", "VPIN"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block("🆕 New Listings", Source::Poll, area.width));
+    f.render_widget(table, area);
+}
+
+fn draw_large_prints(f: &mut Frame, area: Rect, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let rows: Vec<Row> = metrics
+        .risk_metrics
+        .large_prints
+        .iter()
+        .map(|print| {
+            let mut cells = trade_cells(&print.trade).to_vec();
+            cells.extend([
+                Cell::from(format_amount(print.notional)),
+                Cell::from(format!("{:.0}x", print.multiple)),
+                trader_cell(&print.trade.buyer),
+                trader_cell(&print.trade.seller),
+            ]);
+            Row::new(cells)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(4),
+            Constraint::Length(10),
+            Constraint::Length(5),
+            Constraint::Min(12),
+            Constraint::Min(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["Time", "Coin", "Side", "Notional", "Avg", "Buyer", "Seller"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block(format!("🐋 Large Prints ({:.0}% of volume)", metrics.risk_metrics.whale_activity * 100.0), Source::Stream, area.width));
+    f.render_widget(table, area);
+}
+
+fn draw_trade_tape(f: &mut Frame, area: Rect, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let rows: Vec<Row> = metrics
+        .trade_tape
+        .iter()
+        .map(|trade| {
+            let mut cells = trade_cells(trade).to_vec();
+            cells.extend([
+                Cell::from(format::current().decimal(trade.px)),
+                Cell::from(format::current().decimal(trade.sz)),
+                trader_cell(&trade.buyer),
+                trader_cell(&trade.seller),
+            ]);
+            Row::new(cells)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(4),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(12),
+            Constraint::Min(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["Time", "Coin", "Side", "Price", "Size", "Buyer", "Seller"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block("🧾 Trade Tape", Source::Stream, area.width));
+    f.render_widget(table, area);
+}
+
+/// The streamed readings against the polled ones they replace: this cycle's,
+/// then the mean and largest gap since start-up, and the assets whose spreads
+/// disagree most.
+fn draw_shadow_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let block = freshness.block("🔬 Streaming vs Polling", Source::Stream, area.width);
+    let Some(shadow) = &metrics.system_status.shadow else {
+        let note = Paragraph::new("Streaming is off: there is nothing to compare").style(Style::default().fg(Color::DarkGray));
+        f.render_widget(note.block(block), area);
+        return;
+    };
+
+    let gap_style = |reading: &ShadowReading| {
+        let relative = reading.gap() / reading.polled.abs().max(f64::EPSILON);
+        Style::default().fg(ratio_color(relative, thresholds.shadow_gap_warning, thresholds.shadow_gap_critical))
+    };
+    let row = |name: String, reading: Option<&ShadowReading>, divergence: Option<&Divergence>, precision: usize| {
+        let value = |v: f64| format!("{:.*}", precision, v);
+        let mut cells = vec![Cell::from(name)];
+        match reading {
+            Some(reading) => cells.extend([
+                Cell::from(value(reading.polled)),
+                Cell::from(value(reading.streamed)),
+                Cell::from(value(reading.gap())).style(gap_style(reading)),
+            ]),
+            None => cells.extend([Cell::from("-"), Cell::from("warming up"), Cell::from("-")]),
+        }
+        if let Some(divergence) = divergence.filter(|divergence| divergence.cycles > 0) {
+            cells.extend([Cell::from(value(divergence.mean_gap)), Cell::from(value(divergence.max_gap))]);
+        }
+        Row::new(cells)
+    };
+
+    let mut rows = vec![
+        row("VPIN".to_string(), shadow.vpin.as_ref(), Some(&shadow.vpin_divergence), 3),
+        row("PLI".to_string(), Some(&shadow.phantom_liquidity), Some(&shadow.phantom_liquidity_divergence), 3),
+    ];
+    if let Some(gap) = shadow.mean_spread_gap() {
+        let mut cells = vec![
+            Cell::from(format!("Spread bps ({} assets)", shadow.spreads_bps.len())),
+            Cell::from(""),
+            Cell::from(""),
+            Cell::from(format!("{:.2}", gap)),
+        ];
+        if shadow.spread_divergence.cycles > 0 {
+            cells.extend([
+                Cell::from(format!("{:.2}", shadow.spread_divergence.mean_gap)),
+                Cell::from(format!("{:.2}", shadow.spread_divergence.max_gap)),
+            ]);
+        }
+        rows.push(Row::new(cells));
+    }
+    let mut spreads: Vec<(&String, &ShadowReading)> = shadow.spreads_bps.iter().collect();
+    spreads.sort_by(|a, b| b.1.gap().partial_cmp(&a.1.gap()).unwrap_or(std::cmp::Ordering::Equal));
+    let room = (area.height as usize).saturating_sub(2 + 1 + rows.len());
+    rows.extend(spreads.into_iter().take(room).map(|(coin, reading)| row(format!("  {}", coin), Some(reading), None, 2)));
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(18),
+            Constraint::Length(9),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec!["Metric", "Polled", "Streamed", "Gap", "Mean gap", "Max gap"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
+
+fn draw_risk_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let risk = &metrics.risk_metrics;
+    let gauges = [
+        ThresholdGauge::new("VPIN", risk.vpin_score, format!("{:.3}", risk.vpin_score))
+            .thresholds(thresholds.vpin_warning, thresholds.vpin_critical)
+            .color(vpin_color(risk.vpin_score, thresholds)),
+        ThresholdGauge::new(
+            "PLI",
+            risk.phantom_liquidity_index,
+            format!("{:.1}%", risk.phantom_liquidity_index * 100.0),
+        )
+        .thresholds(thresholds.phantom_liquidity_warning, thresholds.phantom_liquidity_critical)
+        .color(pli_color(risk.phantom_liquidity_index, thresholds)),
+        ThresholdGauge::new("Liq. Risk", risk.liquidation_risk_score, format!("{:.2}", risk.liquidation_risk_score))
+            .thresholds(thresholds.liquidation_risk_warning, thresholds.liquidation_risk_critical)
+            .color(liquidation_color(risk.liquidation_risk_score, thresholds)),
+    ];
+
+    // The first rows are left blank for the gauges drawn over them below.
+    let mut lines = vec![Line::from(""); gauges.len()];
+    lines.extend([
+        metric_line(
+            "Cascade",
+            format!("{:.2}", risk.cascade_risk_score),
+            cascade_color(risk.cascade_risk_score, thresholds),
+        ),
+        metric_line(
+            "Max DD",
+            format!("{:.1}%", risk.max_drawdown * 100.0),
+            drawdown_color(risk.max_drawdown, thresholds),
+        ),
+    ]);
+
+    let block = freshness.block("⚠️  Risk", Source::Stream, area.width);
+    let inner = block.inner(area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+
+    for (row, gauge) in gauges.into_iter().enumerate() {
+        render_row(f, inner, row as u16, gauge.label_width(11));
+    }
+}
+
+/// Renders a one-line widget on row `row` of `inner`, if the panel is tall enough.
+fn render_row(f: &mut Frame, inner: Rect, row: u16, widget: impl Widget) {
+    if row < inner.height {
+        f.render_widget(widget, Rect { y: inner.y + row, height: 1, ..inner });
+    }
+}
+
+fn draw_liquidity_panel(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let liquidity = &metrics.liquidity_metrics;
+    let avg_spread = average(liquidity.bid_ask_spread_bps.values().copied());
+    let lines = vec![
+        metric_line("Avg Spread", format!("{:.2} bps", avg_spread), spread_color(avg_spread, thresholds)),
+        metric_line(
+            "Cancel Rate",
+            format!("{:.1}%", liquidity.cancel_rate * 100.0),
+            ratio_color(liquidity.cancel_rate, thresholds.cancel_rate_warning, thresholds.cancel_rate_critical),
+        ),
+        metric_line(
+            "Fleeting",
+            format!("{:.1}%", liquidity.fleeting_order_ratio * 100.0),
+            ratio_color(liquidity.fleeting_order_ratio, thresholds.fleeting_ratio_warning, thresholds.fleeting_ratio_critical),
+        ),
+        metric_line(
+            "Realization",
+            format!("{:.1}%", liquidity.liquidity_realization_rate * 100.0),
+            Color::White,
+        ),
+        metric_line(
+            "Markets",
+            format!("{}", liquidity.bid_ask_spread_bps.len()),
+            Color::White,
+        ),
+    ];
+
+    let panel = Paragraph::new(lines)
+        .block(freshness.block("💧 Liquidity", Source::Stream, area.width));
+    f.render_widget(panel, area);
+}
+
+fn draw_performance_panel(f: &mut Frame, area: Rect, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let performance = &metrics.performance_metrics;
+    let lines = vec![
+        metric_line("Sharpe", format!("{:.2}", performance.sharpe_ratio), ratio_quality_color(performance.sharpe_ratio)),
+        metric_line("Sortino", format!("{:.2}", performance.sortino_ratio), ratio_quality_color(performance.sortino_ratio)),
+        metric_line("Daily PnL", format_amount(performance.daily_pnl), pnl_color(performance.daily_pnl)),
+        metric_line("Unrealized", format_amount(performance.unrealized_pnl), pnl_color(performance.unrealized_pnl)),
+        metric_line("Volume", format_amount(performance.total_volume), Color::White),
+    ];
+
+    let panel = Paragraph::new(lines)
+        .block(freshness.block("📈 Performance", Source::Poll, area.width));
+    f.render_widget(panel, area);
+}
+
+fn draw_health_panel(f: &mut Frame, area: Rect, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let health = calculate_health_score(metrics);
+    let (label, color) = if health >= 0.75 {
+        ("HEALTHY", Color::Green)
+    } else if health >= 0.5 {
+        ("DEGRADED", Color::Yellow)
+    } else {
+        ("STRESSED", Color::Red)
+    };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("Health Score: "),
+            Span::styled(format!("{:.0}/100", health * 100.0), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::raw("  "),
+            Span::styled(label, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(""),
+        Line::from(format!(
+            "Toxicity {:.0}% | Phantom {:.0}% | Liquidation {:.0}%",
+            metrics.risk_metrics.vpin_score * 100.0,
+            metrics.risk_metrics.phantom_liquidity_index * 100.0,
+            metrics.risk_metrics.liquidation_risk_score * 100.0,
+        )),
+        Line::from(format!(
+            "Layering {:.2} | Spoofing {:.2}",
+            metrics.liquidity_metrics.layering_detection_score,
+            metrics.liquidity_metrics.spoofing_detection_index,
+        )),
+    ];
+
+    let panel = Paragraph::new(lines)
+        .block(freshness.block("🩺 Market Microstructure Health", Source::Stream, area.width));
+    f.render_widget(panel, area);
+}
+
+fn draw_system_status_panel(f: &mut Frame, area: Rect, freshness: &Freshness, metrics: &GlobalMetrics, alerts: &[Alert]) {
+    let (update_text, update_color) = match (metrics.last_update, freshness.poll_age) {
+        (Some(ts), Some(age)) => (format!("{} ({})", format::time(ts, "%H:%M:%S"), format::ago(ts)), freshness.color(Source::Poll, age)),
+        _ => ("Waiting for data...".to_string(), Color::Red),
+    };
+
+    let critical = alerts.iter().filter(|a| a.level == AlertLevel::Critical).count();
+    let warning = alerts.iter().filter(|a| a.level == AlertLevel::Warning).count();
+
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("Last Update: "),
+            Span::styled(update_text, Style::default().fg(update_color)),
+        ]),
+        freshness.source_line(Source::Poll),
+        freshness.source_line(Source::Stream),
+        Line::from(vec![
+            Span::raw("Assets Tracked: "),
+            Span::styled(
+                format!("{}", metrics.liquidity_metrics.bid_ask_spread_bps.len()),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("Alerts: "),
+            Span::styled(format!("{} critical", critical), Style::default().fg(Color::Red)),
+            Span::raw(", "),
+            Span::styled(format!("{} warning", warning), Style::default().fg(Color::Yellow)),
+        ]),
+    ];
+
+    let panel = Paragraph::new(lines)
+        .block(Block::default().title("🖥️  System Status").borders(Borders::ALL));
+    f.render_widget(panel, area);
+}
+
+fn draw_recent_alerts(f: &mut Frame, area: Rect, alerts: &[Alert]) {
+    let capacity = area.height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = alerts
+        .iter()
+        .rev()
+        .take(capacity)
+        .map(alert_item)
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().title("🔔 Recent Alerts").borders(Borders::ALL));
+    f.render_widget(list, area);
+}
+
+fn draw_liquidity_tab(
+    f: &mut Frame,
+    area: Rect,
+    scroll: &mut TabScroll,
+    ladder: &mut LadderState,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+) {
+    let liquidity = &metrics.liquidity_metrics;
+    let mode = LayoutMode::for_width(area.width);
+    let columns = Layout::default()
+        .direction(if mode == LayoutMode::Compact { Direction::Vertical } else { Direction::Horizontal })
+        .constraints(if mode == LayoutMode::Wide {
+            [Constraint::Percentage(40), Constraint::Percentage(60)]
+        } else {
+            [Constraint::Percentage(60), Constraint::Percentage(40)]
+        })
+        .split(area);
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(columns[0]);
+
+    let mut coins: Vec<&String> = liquidity.bid_ask_spread_bps.keys().collect();
+    coins.sort();
+    let first = scroll.clamp(coins.len(), left[0].height.saturating_sub(3) as usize);
+
+    let rows: Vec<Row> = coins
+        .iter()
+        .skip(first)
+        .map(|coin| {
+            let spread = liquidity.bid_ask_spread_bps.get(*coin).copied().unwrap_or(0.0);
+            let depth = liquidity.depth_at_50bps.get(*coin).copied().unwrap_or(Decimal::ZERO);
+            let imbalance = liquidity.order_book_imbalance.get(*coin).copied().unwrap_or(0.0);
+            let activity = match liquidity.trade_rate_per_min.get(*coin) {
+                Some(rate) if liquidity.illiquid_assets.contains(*coin) => {
+                    Cell::from(format!("{:.1} thin", rate)).style(Style::default().fg(Color::Magenta))
+                }
+                Some(rate) => Cell::from(format!("{:.1}", rate)),
+                None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+            };
+            Row::new(vec![
+                Cell::from(coin.as_str()),
+                Cell::from(format!("{:.2}", spread)).style(Style::default().fg(spread_color(spread, thresholds))),
+                Cell::from(format::current().number(depth.to_f64().unwrap_or(0.0), 2)),
+                Cell::from(format!("{:+.3}", imbalance)).style(Style::default().fg(imbalance_color(imbalance, thresholds))),
+                activity,
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(14),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(vec!["Asset", "Spread (bps)", "Depth @50bps", "Imbalance", "Trades/min"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block(format!("📊 Spread & Depth by Asset ({})", scroll.position()), Source::Stream, left[0].width));
+    f.render_widget(table, left[0]);
+
+    let right = if mode == LayoutMode::Standard {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(6), Constraint::Length(6), Constraint::Min(0)])
+            .split(columns[1])
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3); 3])
+            .split(columns[1])
+    };
+
+    let lifetime = Paragraph::new(vec![
+        metric_line(
+            "Avg Lifetime",
+            format!("{:.0} ms", liquidity.avg_order_lifetime_ms),
+            Color::White,
+        ),
+        metric_line(
+            "Cancel Rate",
+            format!("{:.1}%", liquidity.cancel_rate * 100.0),
+            ratio_color(liquidity.cancel_rate, thresholds.cancel_rate_warning, thresholds.cancel_rate_critical),
+        ),
+        metric_line(
+            "Fleeting (<100ms)",
+            format!("{:.1}%", liquidity.fleeting_order_ratio * 100.0),
+            ratio_color(liquidity.fleeting_order_ratio, thresholds.fleeting_ratio_warning, thresholds.fleeting_ratio_critical),
+        ),
+    ])
+    .block(freshness.block("⏱️  Order Lifetime", Source::Stream, right[0].width));
+    f.render_widget(lifetime, right[0]);
+
+    let manipulation = Paragraph::new(vec![
+        metric_line(
+            "Layering",
+            format!("{:.3}", liquidity.layering_detection_score),
+            ratio_color(liquidity.layering_detection_score, 0.3, 0.6),
+        ),
+        metric_line(
+            "Spoofing",
+            format!("{:.3}", liquidity.spoofing_detection_index),
+            ratio_color(liquidity.spoofing_detection_index, 0.3, 0.6),
+        ),
+        metric_line(
+            "Realization",
+            format!("{:.1}%", liquidity.liquidity_realization_rate * 100.0),
+            Color::White,
+        ),
+        match &liquidity.top_trader {
+            Some(trader) => metric_line(
+                "Top Trader",
+                format!("{} {:.0}%", trader.display(), liquidity.top_trader_share * 100.0),
+                ratio_color(liquidity.top_trader_share, thresholds.top_trader_share_warning, thresholds.top_trader_share_critical),
+            ),
+            None => metric_line("Top Trader", "-".to_string(), Color::DarkGray),
+        },
+    ])
+    .block(freshness.block("🎭 Manipulation Detection", Source::Stream, right[1].width));
+    f.render_widget(manipulation, right[1]);
+
+    let mut distances: Vec<(&String, &f64)> = liquidity.fill_probability_by_distance.iter().collect();
+    distances.sort_by_key(|(label, _)| label.trim_end_matches("bps").parse::<u32>().unwrap_or(u32::MAX));
+
+    let mut phantom_lines = vec![metric_line(
+        "PLI",
+        format!("{:.1}%", metrics.risk_metrics.phantom_liquidity_index * 100.0),
+        pli_color(metrics.risk_metrics.phantom_liquidity_index, thresholds),
+    )];
+    phantom_lines.push(Line::from(""));
+    phantom_lines.push(Line::from(Span::styled(
+        "Fill probability by distance",
+        Style::default().fg(Color::Yellow),
+    )));
+    for (label, probability) in distances {
+        phantom_lines.push(metric_line(label, format!("{:.1}%", probability * 100.0), Color::White));
+    }
+
+    let phantom = Paragraph::new(phantom_lines)
+        .block(freshness.block("👻 Phantom Liquidity", Source::Stream, right[2].width));
+    f.render_widget(phantom, right[2]);
+
+    draw_depth_ladder(f, left[1], ladder, freshness, metrics);
+}
+
+/// Assets with a book, those where the vault is quoting first.
+pub fn ladder_assets<'a>(order_books: &'a HashMap<String, L2Snapshot>, open_orders: &[OpenOrder]) -> Vec<&'a str> {
+    let mut assets: Vec<&str> = order_books.keys().map(String::as_str).collect();
+    assets.sort_by_key(|coin| (!open_orders.iter().any(|o| o.coin == *coin), *coin));
+    assets
+}
+
+/// Price ladder for one asset: asks above, bids below, a depth bar per level, the
+/// vault's own resting orders highlighted, and levels flashing as they change.
+fn draw_depth_ladder(f: &mut Frame, area: Rect, ladder: &mut LadderState, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let assets = ladder_assets(&metrics.order_books, &metrics.open_orders);
+    let Some((position, book)) = shown_asset(ladder.asset.as_deref(), &assets)
+        .and_then(|(position, coin)| Some((position, metrics.order_books.get(coin)?)))
+    else {
+        let empty = Paragraph::new("Waiting for order book data...")
+            .alignment(Alignment::Center)
+            .block(Block::default().title("📖 Depth Ladder").borders(Borders::ALL));
+        f.render_widget(empty, area);
+        return;
+    };
+    ladder.observe(book);
+
+    let own: Vec<&OpenOrder> = metrics.open_orders.iter().filter(|o| o.coin == book.coin).collect();
+    let own_size = |is_bid: bool, px: Decimal| -> Decimal {
+        own.iter()
+            .filter(|o| (o.side == "B") == is_bid && o.limit_px == px)
+            .map(|o| o.sz)
+            .sum()
+    };
+
+    // One header row and one spread row; the rest is split between asks and bids.
+    let per_side = (area.height.saturating_sub(4) as usize / 2).max(1);
+    let asks: Vec<&OrderBookLevel> = book.asks.iter().take(per_side).collect();
+    let bids: Vec<&OrderBookLevel> = book.bids.iter().take(per_side).collect();
+    let shown = |is_bid: bool, px: Decimal| {
+        let levels = if is_bid { &bids } else { &asks };
+        levels.iter().any(|level| level.px == px)
+    };
+    let hidden_own = own.iter().filter(|o| !shown(o.side == "B", o.limit_px)).count();
+
+    let max_size = asks.iter().chain(bids.iter()).map(|l| l.sz).max().unwrap_or_default();
+    let bar_width = area.width.saturating_sub(2 + 10 + 14 + 12 + 5 + 4) as usize;
+
+    let level_row = |is_bid: bool, level: &OrderBookLevel| -> Row<'static> {
+        let side_color = if is_bid { Color::Green } else { Color::Red };
+        let mine = own_size(is_bid, level.px);
+        let fill = if max_size > Decimal::ZERO {
+            ((level.sz / max_size).to_f64().unwrap_or(0.0) * bar_width as f64).round() as usize
+        } else {
+            0
+        };
+
+        let (own_cell, price_style) = if mine > Decimal::ZERO {
+            (
+                Cell::from(format!("◆ {}", mine)).style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                Style::default().fg(Color::White).bg(Color::Blue).add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (Cell::from(""), Style::default().fg(side_color))
+        };
+
+        Row::new(vec![
+            own_cell,
+            Cell::from(format::current().decimal(level.px)).style(price_style),
+            Cell::from(format::current().decimal(level.sz)).style(ladder.size_style((is_bid, level.px))),
+            Cell::from(level.n.to_string()).style(Style::default().fg(Color::DarkGray)),
+            Cell::from("█".repeat(fill.min(bar_width))).style(Style::default().fg(side_color)),
+        ])
+    };
+
+    let mut rows: Vec<Row> = asks.iter().rev().map(|level| level_row(false, level)).collect();
+    let (spread, spread_bps) = match (book.bids.first(), book.asks.first()) {
+        (Some(bid), Some(ask)) => {
+            let mid = (bid.px + ask.px) / Decimal::from(2);
+            let bps = if mid > Decimal::ZERO { ((ask.px - bid.px) / mid * Decimal::from(10_000)).to_f64().unwrap_or(0.0) } else { 0.0 };
+            (format!("─ {} ─", ask.px - bid.px), format!("{:.2} bps", bps))
+        }
+        _ => ("one-sided".to_string(), String::new()),
+    };
+    let spread_style = Style::default().fg(Color::Yellow);
+    rows.push(Row::new(vec![
+        Cell::from("spread").style(spread_style),
+        Cell::from(spread).style(spread_style),
+        Cell::from(spread_bps).style(spread_style),
+    ]));
+    rows.extend(bids.iter().map(|level| level_row(true, level)));
+
+    let mut title = format!(
+        "📖 Depth Ladder {} ({}/{}) │ {} own order(s)",
+        book.coin,
+        position + 1,
+        assets.len(),
+        own.len()
+    );
+    if hidden_own > 0 {
+        title.push_str(&format!(", {} beyond shown levels", hidden_own));
+    }
+    title.push_str(" │ a/A asset");
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ],
+    )
+    .header(
+        Row::new(vec!["Own", "Price", "Size", "#", "Depth"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block(title, Source::Stream, area.width));
+    f.render_widget(table, area);
+}
+
+fn draw_risk_tab(
+    f: &mut Frame,
+    area: Rect,
+    scroll: &mut TabScroll,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+    history: &MetricHistory,
+) {
+    let risk = &metrics.risk_metrics;
+    let mode = LayoutMode::for_width(area.width);
+
+    // Standard: two columns, of three panels and two. Compact stacks the columns; Wide puts all five side by side.
+    let (left, right) = if mode == LayoutMode::Wide {
+        let panels = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 5); 5])
+            .split(area);
+        (panels[0..3].to_vec(), panels[3..5].to_vec())
+    } else {
+        let columns = Layout::default()
+            .direction(if mode == LayoutMode::Compact { Direction::Vertical } else { Direction::Horizontal })
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(9), Constraint::Length(7), Constraint::Min(0)])
+            .split(columns[0]);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(columns[1]);
+        (left.to_vec(), right.to_vec())
+    };
+
+    let (toxicity, interpretation) = classify_vpin(risk.vpin_score, thresholds);
+    let vpin = Paragraph::new(vec![
+        metric_line("VPIN Score", format!("{:.4}", risk.vpin_score), vpin_color(risk.vpin_score, thresholds)),
+        metric_line("Toxicity", toxicity.to_string(), vpin_color(risk.vpin_score, thresholds)),
+        metric_line(
+            "Whale Activity",
+            format!("{:.1}%", risk.whale_activity * 100.0),
+            ratio_color(risk.whale_activity, thresholds.whale_activity_warning, thresholds.whale_activity_critical),
+        ),
+        Line::from(""),
+        Line::from(interpretation),
+        Line::from(""),
+        match metrics.liquidity_metrics.illiquid_assets.is_empty() {
+            true => Line::from(Span::styled("Volume buckets: $10k | Window: 50 buckets", Style::default().fg(Color::DarkGray))),
+            false => Line::from(Span::styled(
+                format!("Illiquid hours in {}: read with care", metrics.liquidity_metrics.illiquid_assets.join(", ")),
+                Style::default().fg(Color::Magenta),
+            )),
+        },
+    ])
+    .wrap(Wrap { trim: true })
+    .block(freshness.block("☣️  VPIN Toxicity Analysis", Source::Stream, left[0].width));
+    f.render_widget(vpin, left[0]);
+
+    let liquidation = Paragraph::new(vec![
+        metric_line(
+            "Liquidation Risk",
+            format!("{:.3}", risk.liquidation_risk_score),
+            liquidation_color(risk.liquidation_risk_score, thresholds),
+        ),
+        metric_line(
+            "Cascade Risk",
+            format!("{:.3}", risk.cascade_risk_score),
+            cascade_color(risk.cascade_risk_score, thresholds),
+        ),
+        metric_line(
+            "Max Drawdown",
+            format!("{:.2}%", risk.max_drawdown * 100.0),
+            drawdown_color(risk.max_drawdown, thresholds),
+        ),
+        metric_line(
+            "Cross-Exchange",
+            format!("{:.3}", risk.cross_exchange_manipulation_score),
+            ratio_color(risk.cross_exchange_manipulation_score, 0.3, 0.6),
+        ),
+        match risk.backstop.accounts.is_empty() {
+            true => metric_line("Backstop", "-".to_string(), Color::DarkGray),
+            false => metric_line(
+                "Backstop",
+                format!("{} (-{:.1}%)", format_amount(risk.backstop.total()), risk.backstop.drawdown() * 100.0),
+                ratio_color(risk.backstop.drawdown(), thresholds.backstop_drawdown_warning, thresholds.backstop_drawdown_critical),
+            ),
+        },
+    ])
+    .block(freshness.block("💥 Liquidation Risk Assessment", Source::Poll, left[1].width));
+    f.render_widget(liquidation, left[1]);
+
+    draw_oracle_divergence(f, left[2], thresholds, freshness, metrics, history);
+
+    let mut concentrations: Vec<(&String, &f64)> = risk.position_concentration.iter().collect();
+    concentrations.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
+    let first = scroll.clamp(concentrations.len(), right[0].height.saturating_sub(3) as usize);
+
+    let rows: Vec<Row> = concentrations
+        .iter()
+        .skip(first)
+        .map(|(coin, weight)| {
+            let cap = match risk.open_interest_cap_usage.get(coin.as_str()) {
+                Some(usage) => Cell::from(format!("{:.0}%", usage * 100.0))
+                    .style(Style::default().fg(ratio_color(*usage, thresholds.oi_cap_warning, thresholds.oi_cap_critical))),
+                None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+            };
+            Row::new(vec![
+                Cell::from(coin.as_str()),
+                Cell::from(format!("{:.2}%", *weight * 100.0))
+                    .style(Style::default().fg(concentration_color(**weight, thresholds))),
+                cap,
+            ])
+        })
+        .collect();
+
+    let table = Table::new(rows, [Constraint::Length(10), Constraint::Length(10), Constraint::Length(8)])
+        .header(
+            Row::new(vec!["Asset", "Weight", "OI Cap"])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .block(freshness.block(format!("🎯 Position Concentration ({})", scroll.position()), Source::Poll, right[0].width));
+    f.render_widget(table, right[0]);
+
+    let recommendations: Vec<Line> = risk_recommendations(metrics, thresholds)
+        .into_iter()
+        .map(|text| Line::from(format!("• {}", text)))
+        .collect();
+
+    let panel = Paragraph::new(recommendations)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().title("🛡️  Risk Mitigation").borders(Borders::ALL));
+    f.render_widget(panel, right[1]);
+}
+
+/// Assets whose mark price is furthest from the oracle price, with the
+/// divergence over the recent cycles.
+fn draw_oracle_divergence(
+    f: &mut Frame,
+    area: Rect,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+    history: &MetricHistory,
+) {
+    let mut divergences: Vec<(&String, &f64)> = metrics.risk_metrics.oracle_divergence.iter().collect();
+    divergences.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
+    // Inside the borders, after the other columns and the gaps between all five.
+    let trend_width = area.width.saturating_sub(2 + 7 + 9 + 9 + 8 + 4) as usize;
+    let color = |divergence: f64| ratio_color(divergence, thresholds.oracle_divergence_warning, thresholds.oracle_divergence_critical);
+
+    let rows: Vec<Row> = divergences
+        .into_iter()
+        .take(area.height.saturating_sub(3) as usize)
+        .map(|(coin, divergence)| {
+            let context = metrics.risk_metrics.oracle_prices.get(coin);
+            let price = |px: Option<Decimal>| px.map_or_else(|| "-".to_string(), |px| format::current().decimal(px));
+            Row::new(vec![
+                Cell::from(coin.clone()),
+                Cell::from(price(context.map(|context| context.mark_px))),
+                Cell::from(price(context.map(|context| context.oracle_px))),
+                Cell::from(format!("{:.3}%", divergence * 100.0)).style(Style::default().fg(color(*divergence))),
+                Cell::from(sparkline(&history.divergence(coin), trend_width, thresholds.oracle_divergence_warning))
+                    .style(Style::default().fg(color(*divergence))),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(8),
+            Constraint::Min(0),
+        ],
+    )
+    .header(
+        Row::new(vec!["Asset", "Mark", "Oracle", "Diverg.", "Trend"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block("🔮 Mark vs Oracle", Source::Poll, area.width));
+    f.render_widget(table, area);
+}
+
+const SPARK_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The last `width` of `values` as block characters, scaled to the largest of
+/// them or `floor`, whichever is higher, so quiet series stay low.
+fn sparkline(values: &[f64], width: usize, floor: f64) -> String {
+    let recent = &values[values.len().saturating_sub(width)..];
+    let top = recent.iter().copied().fold(floor, f64::max);
+    recent
+        .iter()
+        .map(|value| match top > 0.0 {
+            true => SPARK_BLOCKS[((value / top) * (SPARK_BLOCKS.len() - 1) as f64).round().clamp(0.0, 7.0) as usize],
+            false => SPARK_BLOCKS[0],
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_performance_tab(
+    f: &mut Frame,
+    area: Rect,
+    scroll: &mut TabScroll,
+    chart: &ChartState,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+    alerts: &[Alert],
+    history: &MetricHistory,
+) {
+    let performance = &metrics.performance_metrics;
+    let vault = &metrics.vault_metrics;
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+    let panels = if LayoutMode::for_width(area.width) == LayoutMode::Compact {
+        let halves = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Ratio(1, 2); 2])
+            .split(rows[0]);
+        halves
+            .iter()
+            .flat_map(|half| {
+                Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Ratio(1, 2); 2])
+                    .split(*half)
+                    .to_vec()
+            })
+            .collect::<Vec<Rect>>()
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 4); 4])
+            .split(rows[0])
+            .to_vec()
+    };
+
+    let returns = Paragraph::new(vec![
+        metric_line("APR", format!("{:.2}%", vault.apr), Color::Green),
+        metric_line("Sharpe Ratio", format!("{:.2}", performance.sharpe_ratio), ratio_quality_color(performance.sharpe_ratio)),
+        metric_line("Sortino Ratio", format!("{:.2}", performance.sortino_ratio), ratio_quality_color(performance.sortino_ratio)),
+        metric_line(
+            "Max Drawdown",
+            format!("{:.2}%", metrics.risk_metrics.max_drawdown * 100.0),
+            drawdown_color(metrics.risk_metrics.max_drawdown, thresholds),
+        ),
+    ])
+    .block(freshness.block("📈 Returns & Risk-Adjusted Ratios", Source::Poll, panels[0].width));
+    f.render_widget(returns, panels[0]);
+
+    let fees = &performance.fees;
+    let mut pnl_lines = vec![
+        metric_line("Daily PnL", format_amount(performance.daily_pnl), pnl_color(performance.daily_pnl)),
+        metric_line("Unrealized PnL", format_amount(performance.unrealized_pnl), pnl_color(performance.unrealized_pnl)),
+        metric_line(
+            "Total PnL",
+            format_amount(performance.daily_pnl + performance.unrealized_pnl),
+            pnl_color(performance.daily_pnl + performance.unrealized_pnl),
+        ),
+        metric_line(
+            "Fees",
+            format!("{} ({:+.2} bps vs schedule)", format_amount(-fees.fees_paid), fees.excess_bps),
+            ratio_color(fees.excess_bps, thresholds.fee_excess_warning_bps, thresholds.fee_excess_critical_bps),
+        ),
+        metric_line(
+            "Off-schedule Fills",
+            format!("{} (builder {})", fees.deviations.len(), format_amount(fees.builder_fees)),
+            if fees.deviations.is_empty() { Color::White } else { Color::Yellow },
+        ),
+    ];
+    for component in &performance.components {
+        pnl_lines.push(metric_line(
+            &component.name,
+            format!(
+                "{} on {} vol, net {}, {:.0}% of exposure, liq {}",
+                format_amount(component.realized_pnl),
+                format_amount(component.volume),
+                format_amount(component.net_notional),
+                component.exposure_share * 100.0,
+                component.liquidation_distance.map_or("-".to_string(), |distance| format!("{:.1}% away", distance * 100.0)),
+            ),
+            pnl_color(component.realized_pnl),
+        ));
+    }
+    let pnl = Paragraph::new(pnl_lines)
+        .block(freshness.block("💵 PnL Breakdown", Source::Poll, panels[1].width));
+    f.render_widget(pnl, panels[1]);
+
+    let mut spreads: Vec<(&String, &f64)> = performance.realized_spread.iter().collect();
+    spreads.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut execution_lines = vec![
+        metric_line(
+            "Adverse Selection",
+            format!("{:.4}", performance.adverse_selection_cost),
+            ratio_color(performance.adverse_selection_cost, 0.05, 0.1),
+        ),
+        Line::from(""),
+        Line::from(Span::styled("Realized spread (bps)", Style::default().fg(Color::Yellow))),
+    ];
+    let capacity = (panels[2].height.saturating_sub(2) as usize).saturating_sub(execution_lines.len());
+    let first = scroll.clamp(spreads.len(), capacity);
+    for (coin, spread) in spreads.into_iter().skip(first).take(capacity) {
+        execution_lines.push(metric_line(coin, format!("{:.2}", spread), Color::White));
+    }
+
+    let execution = Paragraph::new(execution_lines)
+        .block(freshness.block(format!("🎯 Execution Quality ({})", scroll.position()), Source::Poll, panels[2].width));
+    f.render_widget(execution, panels[2]);
+
+    let volume = Paragraph::new(vec![
+        metric_line("Total Volume", format_amount(performance.total_volume), Color::White),
+        metric_line(
+            "Utilization",
+            format!("{:.1}%", vault.utilization_rate * 100.0),
+            utilization_color(vault.utilization_rate, thresholds),
+        ),
+        metric_line("Deployed", format_amount(vault.deployed_liquidity), Color::White),
+        metric_line("Idle", format_amount(vault.idle_liquidity), Color::White),
+    ])
+    .block(freshness.block("📦 Volume & Utilization", Source::Poll, panels[3].width));
+    f.render_widget(volume, panels[3]);
+
+    draw_equity_charts(f, rows[1], chart, thresholds, alerts, history);
+}
+
+/// Equity curve over the retained history with its underwater (drawdown from running peak)
+/// chart beneath; Critical alerts are marked on both so risk events line up with PnL.
+fn draw_equity_charts(
+    f: &mut Frame,
+    area: Rect,
+    chart: &ChartState,
+    thresholds: &AlertThresholds,
+    alerts: &[Alert],
+    history: &MetricHistory,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
+
+    let window_secs = chart.window_secs();
+    let end = chrono::Utc::now() - chrono::Duration::seconds(chart.pan_secs);
+    let start = end - chrono::Duration::seconds(window_secs);
+    let (start_ms, end_ms) = (start.timestamp_millis(), end.timestamp_millis());
+    let span_secs = window_secs as f64;
+
+    let max_points = (area.width as usize).saturating_sub(2) * 2;
+    let equity: Vec<(f64, f64)> = history
+        .series(HistoryMetric::Equity, start_ms, end_ms, max_points)
+        .into_iter()
+        .map(|(ts, value)| ((ts - start_ms) as f64 / 1000.0, value))
+        .collect();
+
+    if equity.is_empty() {
+        let empty = Paragraph::new("Collecting equity history...")
+            .alignment(Alignment::Center)
+            .block(Block::default().title("💹 Equity Curve").borders(Borders::ALL));
+        f.render_widget(empty, area);
+        return;
+    }
+
+    // Drawdown is measured from the peak within the visible window, over the raw
+    // samples rather than the averaged curve above.
+    let drawdown: Vec<(f64, f64)> = history
+        .drawdown(HistoryMetric::Equity, start_ms, end_ms, max_points)
+        .into_iter()
+        .map(|(ts, dd)| ((ts - start_ms) as f64 / 1000.0, dd * 100.0))
+        .collect();
+
+    let critical_x: Vec<f64> = alerts
+        .iter()
+        .filter(|a| a.level == AlertLevel::Critical)
+        .map(|a| (a.timestamp.timestamp_millis() - start_ms) as f64 / 1000.0)
+        .filter(|x| *x >= 0.0 && *x <= span_secs)
+        .collect();
+    let equity_markers: Vec<(f64, f64)> = critical_x.iter().filter_map(|x| nearest_point(&equity, *x)).collect();
+    let drawdown_markers: Vec<(f64, f64)> = critical_x.iter().filter_map(|x| nearest_point(&drawdown, *x)).collect();
+
+    let mid = start + chrono::Duration::seconds(window_secs / 2);
+    let time_format = if window_secs <= 60 * 60 { "%H:%M:%S" } else { "%H:%M" };
+    let x_labels = || -> Vec<Span> {
+        [start, mid, end].iter().map(|ts| Span::raw(format::time(*ts, time_format))).collect()
+    };
+
+    let (eq_min, eq_max) = equity
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, v)| (lo.min(*v), hi.max(*v)));
+    let eq_pad = if eq_max > eq_min { (eq_max - eq_min) * 0.05 } else { eq_min.abs().max(1.0) * 0.05 };
+    let (eq_lo, eq_hi) = (eq_min - eq_pad, eq_max + eq_pad);
+    let dd_min = drawdown.iter().map(|(_, v)| *v).fold(0.0, f64::min).min(-0.01);
+    let dd_lo = dd_min * 1.05;
+    let current_dd = drawdown.last().map(|(_, v)| *v).unwrap_or_default();
+
+    let equity_cursor = chart.cursor.and_then(|c| nearest_sample(&equity, c * span_secs));
+    let drawdown_cursor = equity_cursor.and_then(|(x, _)| nearest_sample(&drawdown, x));
+    let equity_line = crosshair_line(equity_cursor, eq_lo, eq_hi);
+    let drawdown_line = crosshair_line(drawdown_cursor, dd_lo, 0.0);
+    let equity_point: Vec<(f64, f64)> = equity_cursor.into_iter().collect();
+    let drawdown_point: Vec<(f64, f64)> = drawdown_cursor.into_iter().collect();
+
+    let marker_dataset = |points| {
+        Dataset::default()
+            .marker(symbols::Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+            .data(points)
+    };
+
+    let mut equity_datasets = vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Green))
+            .data(&equity),
+        marker_dataset(&equity_markers),
+    ];
+    equity_datasets.extend(crosshair_datasets(&equity_line, &equity_point));
+
+    let equity_readout = match equity_cursor {
+        Some(sample) => crosshair_readout(start, sample, time_format, format_axis_value(sample.1)),
+        None => format!("{} │ {}", format_axis_value(equity.last().map(|(_, v)| *v).unwrap_or_default()), chart_view_label(chart)),
+    };
+    let equity_chart = Chart::new(equity_datasets)
+        .block(
+            Block::default()
+                .title(format!(
+                    "💹 Equity Curve │ {} │ {} critical alert(s) marked",
+                    equity_readout,
+                    equity_markers.len()
+                ))
+                .borders(Borders::ALL),
+        )
+        .x_axis(Axis::default().style(Style::default().fg(Color::DarkGray)).bounds([0.0, span_secs]).labels(x_labels()))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([eq_lo, eq_hi])
+                .labels(vec![Span::raw(format_axis_value(eq_lo)), Span::raw(format_axis_value(eq_hi))]),
+        );
+    f.render_widget(equity_chart, chunks[0]);
+
+    let mut drawdown_datasets = vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(drawdown_color(-current_dd / 100.0, thresholds)))
+            .data(&drawdown),
+        marker_dataset(&drawdown_markers),
+    ];
+    drawdown_datasets.extend(crosshair_datasets(&drawdown_line, &drawdown_point));
+
+    let drawdown_readout = match drawdown_cursor {
+        Some(sample) => crosshair_readout(start, sample, time_format, format!("{:.2}%", sample.1)),
+        None => format!("now {:.2}%", current_dd),
+    };
+    let drawdown_chart = Chart::new(drawdown_datasets)
+        .block(
+            Block::default()
+                .title(format!("🌊 Drawdown │ {} │ worst {:.2}%", drawdown_readout, dd_min.min(0.0)))
+                .borders(Borders::ALL),
+        )
+        .x_axis(Axis::default().style(Style::default().fg(Color::DarkGray)).bounds([0.0, span_secs]).labels(x_labels()))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([dd_lo, 0.0])
+                .labels(vec![Span::raw(format!("{:.2}%", dd_min)), Span::raw("0%")]),
+        );
+    f.render_widget(drawdown_chart, chunks[1]);
+}
+
+/// "24h · zoom 2x · LIVE" summary of a chart's window for titles without a header row.
+fn chart_view_label(chart: &ChartState) -> String {
+    let position = if chart.pan_secs == 0 { "LIVE".to_string() } else { format!("-{}", format_duration(chart.pan_secs)) };
+    format!("{} · zoom {}x · {}", CHART_RANGES[chart.range_index].0, 1u32 << chart.zoom, position)
+}
+
+fn nearest_point(points: &[(f64, f64)], x: f64) -> Option<(f64, f64)> {
+    nearest_sample(points, x).map(|(_, y)| (x, y))
+}
+
+/// The sample closest to `x` in a series sorted by x.
+fn nearest_sample(points: &[(f64, f64)], x: f64) -> Option<(f64, f64)> {
+    let idx = points.partition_point(|(px, _)| *px < x);
+    let candidates = [idx.checked_sub(1), Some(idx)];
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|i| points.get(i))
+        .min_by(|a, b| (a.0 - x).abs().total_cmp(&(b.0 - x).abs()))
+        .copied()
+}
+
+/// Vertical line through the crosshair sample spanning the chart's y bounds.
+fn crosshair_line(sample: Option<(f64, f64)>, y_lo: f64, y_hi: f64) -> Vec<(f64, f64)> {
+    sample.map(|(x, _)| vec![(x, y_lo), (x, y_hi)]).unwrap_or_default()
+}
+
+fn crosshair_datasets<'a>(line: &'a [(f64, f64)], point: &'a [(f64, f64)]) -> [Dataset<'a>; 2] {
+    [
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(line),
+        Dataset::default()
+            .marker(symbols::Marker::Block)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .data(point),
+    ]
+}
+
+/// "⌖ 14:03:22 = 1.23M" for the sample under the crosshair; x is seconds after `start`.
+fn crosshair_readout(start: chrono::DateTime<chrono::Utc>, sample: (f64, f64), time_format: &str, value: String) -> String {
+    let at = start + chrono::Duration::milliseconds((sample.0 * 1000.0) as i64);
+    format!("⌖ {} = {}", at.format(time_format), value)
+}
+
+/// Cross positions share the account's margin; isolated ones are listed apart
+/// with the margin posted to each, since one can be liquidated while the
+/// account as a whole looks healthy.
+fn draw_positions_tab(f: &mut Frame, area: Rect, thresholds: &AlertThresholds, freshness: &Freshness, metrics: &GlobalMetrics) {
+    let vault = &metrics.vault_metrics;
+    let (isolated, cross): (Vec<&Position>, Vec<&Position>) = metrics.positions.iter().partition(|position| position.isolated);
+    let cross_title = format!(
+        "📋 Cross Positions ({}) │ margin {}, {:.1}% of TVL",
+        cross.len(),
+        format_amount(vault.deployed_liquidity),
+        vault.utilization_rate * 100.0
+    );
+    let sections = 1 + usize::from(!isolated.is_empty()) + usize::from(!vault.spot_balances.is_empty());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, sections as u32); sections])
+        .split(area);
+    f.render_widget(positions_table(&cross, cross_title, thresholds, freshness, chunks[0].width), chunks[0]);
+    let mut next = 1;
+    if !isolated.is_empty() {
+        let isolated_title = Line::from(vec![
+            Span::raw(format!("🧱 Isolated Positions ({}) │ margin {}, ", isolated.len(), format_amount(vault.isolated_margin))),
+            Span::styled(
+                format!("{:.1}% of TVL", vault.isolated_utilization * 100.0),
+                Style::default().fg(ratio_color(vault.isolated_utilization, thresholds.utilization_warning, thresholds.utilization_critical)),
+            ),
+        ]);
+        f.render_widget(positions_table(&isolated, isolated_title, thresholds, freshness, chunks[next].width), chunks[next]);
+        next += 1;
+    }
+    if !vault.spot_balances.is_empty() {
+        draw_spot_balances(f, chunks[next], freshness, vault);
+    }
+}
+
+/// Token balances, largest first, with their share of the account's spot value.
+fn draw_spot_balances(f: &mut Frame, area: Rect, freshness: &Freshness, vault: &VaultMetrics) {
+    let ratio = |part: Decimal, whole: Decimal| if whole > Decimal::ZERO { (part / whole).to_f64().unwrap_or(0.0) } else { 0.0 };
+    let mut balances: Vec<&SpotBalance> = vault.spot_balances.iter().collect();
+    balances.sort_by_key(|balance| std::cmp::Reverse(balance.value));
+    let rows: Vec<Row> = balances
+        .into_iter()
+        .map(|balance| {
+            let share = ratio(balance.value, vault.spot_value);
+            Row::new(vec![
+                Cell::from(balance.coin.clone()),
+                Cell::from(format::current().decimal(balance.total)),
+                Cell::from(format::current().decimal(balance.hold)),
+                Cell::from(format_amount(balance.value)),
+                Cell::from(format!("{:.1}%", share * 100.0)),
+            ])
+        })
+        .collect();
+
+    let title = format!("💵 Spot Balances │ {} ({:.1}% of TVL)", format_amount(vault.spot_value), ratio(vault.spot_value, vault.tvl) * 100.0);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(18),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec!["Token", "Balance", "On Hold", "Value", "Share"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block(title, Source::Poll, area.width));
+    f.render_widget(table, area);
+}
+
+fn positions_table(
+    positions: &[&Position],
+    title: impl Into<Line<'static>>,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    width: u16,
+) -> Table<'static> {
+    let mut positions = positions.to_vec();
+    positions.sort_by_key(|position| std::cmp::Reverse(position.position_value));
+    let rows: Vec<Row> = positions
+        .into_iter()
+        .map(|position| {
+            let distance = position.liquidation_distance();
+            let distance_color = distance.map_or(Color::DarkGray, |distance| {
+                floor_color(distance, thresholds.liquidation_distance_warning, thresholds.liquidation_distance_critical)
+            });
+            let price = |px: Option<Decimal>| px.map_or_else(|| "-".to_string(), |px| format::current().decimal(px));
+            Row::new(vec![
+                Cell::from(position.symbol.clone()),
+                Cell::from(position.margin_mode()),
+                Cell::from(format::current().decimal(position.size))
+                    .style(Style::default().fg(if position.size < Decimal::ZERO { Color::Red } else { Color::Green })),
+                Cell::from(price(position.entry_px)),
+                Cell::from(format_amount(position.position_value)),
+                Cell::from(format_amount(position.unrealized_pnl)).style(Style::default().fg(pnl_color(position.unrealized_pnl))),
+                Cell::from(format_amount(position.margin_used)),
+                Cell::from(price(position.liquidation_px)),
+                Cell::from(distance.map_or_else(|| "-".to_string(), |distance| format!("{:.1}%", distance * 100.0)))
+                    .style(Style::default().fg(distance_color)),
+            ])
+        })
+        .collect();
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(13),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(12),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec!["Coin", "Mode", "Size", "Entry", "Value", "uPnL", "Margin", "Liq. Px", "To Liq."])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    )
+    .block(freshness.block(title, Source::Poll, width))
+}
+
+fn draw_alerts_tab(f: &mut Frame, area: Rect, scroll: &mut TabScroll, alerts: &[Alert]) {
+    let first = scroll.clamp(alerts.len(), area.height.saturating_sub(2) as usize);
+    let items: Vec<ListItem> = alerts
+        .iter()
+        .rev()
+        .skip(first)
+        .map(alert_item)
+        .collect();
+
+    let unacknowledged = alerts.iter().filter(|a| !a.acknowledged).count();
+    let title = format!(
+        "🔔 Alert History ({}, {} unacknowledged │ a acknowledge all)",
+        scroll.position(),
+        unacknowledged
+    );
+    let list = List::new(items).block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(list, area);
+}
+
+fn draw_charts_tab(f: &mut Frame, area: Rect, chart: &ChartState, history: &MetricHistory) {
+    let direction = if LayoutMode::for_width(area.width) == LayoutMode::Wide {
+        Direction::Horizontal
+    } else {
+        Direction::Vertical
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
+
+    draw_metric_chart(f, chunks[0], chart, history);
+    draw_candle_panel(f, chunks[1], chart, history);
+}
+
+/// The instant under the Charts tab crosshair, which the candle panel follows too.
+fn crosshair_time(chart: &ChartState) -> Option<chrono::DateTime<chrono::Utc>> {
+    let end = chrono::Utc::now() - chrono::Duration::seconds(chart.pan_secs);
+    let window_secs = chart.window_secs();
+    chart
+        .cursor
+        .map(|c| end - chrono::Duration::milliseconds(((1.0 - c) * window_secs as f64 * 1000.0) as i64))
+}
+
+fn draw_candle_panel(f: &mut Frame, area: Rect, chart: &ChartState, history: &MetricHistory) {
+    let assets = history.candle_assets();
+    let Some((position, coin)) = chart.asset(&assets) else {
+        let empty = Paragraph::new("Waiting for candle data...")
+            .alignment(Alignment::Center)
+            .block(Block::default().title("🕯️ Price").borders(Borders::ALL));
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let candles = history.candles(coin);
+    let cursor_ms = crosshair_time(chart).map(|at| at.timestamp_millis().max(0) as u64);
+    let selected = cursor_ms.and_then(|ms| candles.iter().find(|c| c.open_time <= ms && ms <= c.close_time).copied());
+    let title = match selected.or(candles.last().copied()) {
+        Some(last) => format!(
+            "🕯️ {} {} │ {}O {} H {} L {} C {} │ Vol {:.2} │ asset {}/{}",
+            coin,
+            CANDLE_INTERVAL,
+            selected
+                .map(|c| format!("⌖ {} ", format::time_ms(c.open_time, "%H:%M")))
+                .unwrap_or_default(),
+            last.open,
+            last.high,
+            last.low,
+            last.close,
+            last.volume.to_f64().unwrap_or(0.0),
+            position + 1,
+            assets.len()
+        ),
+        None => format!("🕯️ {} {}", coin, CANDLE_INTERVAL),
+    };
+
+    let widget = CandleChart::new(&candles)
+        .highlight(selected.map(|c| c.open_time))
+        .block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(widget, area);
+}
+
+fn draw_metric_chart(f: &mut Frame, area: Rect, chart: &ChartState, history: &MetricHistory) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let metric = chart.metric();
+    let window_secs = chart.window_secs();
+    let end = chrono::Utc::now() - chrono::Duration::seconds(chart.pan_secs);
+    let start = end - chrono::Duration::seconds(window_secs);
+    let start_ms = start.timestamp_millis();
+
+    let max_points = (chunks[1].width as usize).saturating_sub(2) * 2;
+    let samples = history.series(metric, start_ms, end.timestamp_millis(), max_points);
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|(ts, value)| ((ts - start_ms) as f64 / 1000.0, *value))
+        .collect();
+
+    let ranges: Vec<Span> = CHART_RANGES
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (label, _))| {
+            let style = if i == chart.range_index {
+                Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            [Span::styled(format!(" {} ", label), style), Span::raw(" ")]
+        })
+        .collect();
+
+    let position = if chart.pan_secs == 0 {
+        Span::styled("LIVE", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+    } else {
+        Span::styled(format!("-{}", format_duration(chart.pan_secs)), Style::default().fg(Color::Yellow))
+    };
+
+    let mut header = vec![
+        Span::styled(metric.label(), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        Span::raw("  "),
+    ];
+    header.extend(ranges);
+    header.extend([
+        Span::raw(format!(" zoom {}x  ", 1u32 << chart.zoom)),
+        position,
+        Span::styled(
+            "   m/M metric  a/A asset  [/] range  +/- zoom  ←/→ pan  x crosshair  End live",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+
+    let header = Paragraph::new(Line::from(header))
+        .block(Block::default().title("📈 Metric History").borders(Borders::ALL));
+    f.render_widget(header, chunks[0]);
+
+    if points.is_empty() {
+        let empty = Paragraph::new("Collecting history for this window...")
+            .alignment(Alignment::Center)
+            .block(Block::default().title(metric.label()).borders(Borders::ALL));
+        f.render_widget(empty, chunks[1]);
+        return;
+    }
+
+    let (min, max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, v)| (lo.min(*v), hi.max(*v)));
+    let padding = if max > min { (max - min) * 0.05 } else { min.abs().max(1.0) * 0.05 };
+    let (y_min, y_max) = (min - padding, max + padding);
+    let latest = points.last().map(|(_, v)| *v).unwrap_or_default();
+
+    let mid = start + chrono::Duration::seconds(window_secs / 2);
+    let time_format = if window_secs <= 60 * 60 { "%H:%M:%S" } else { "%H:%M" };
+    let x_labels: Vec<Span> = [start, mid, end]
+        .iter()
+        .map(|ts| Span::raw(format::time(*ts, time_format)))
+        .collect();
+    let y_labels: Vec<Span> = [y_min, (y_min + y_max) / 2.0, y_max]
+        .iter()
+        .map(|v| Span::raw(format_axis_value(*v)))
+        .collect();
+
+    let cursor = chart.cursor.and_then(|c| nearest_sample(&points, c * window_secs as f64));
+    let cursor_line = crosshair_line(cursor, y_min, y_max);
+    let cursor_point: Vec<(f64, f64)> = cursor.into_iter().collect();
+
+    let mut datasets = vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&points)];
+    datasets.extend(crosshair_datasets(&cursor_line, &cursor_point));
+
+    let readout = match cursor {
+        Some(sample) => format!(" │ {}", crosshair_readout(start, sample, time_format, format_axis_value(sample.1))),
+        None => String::new(),
+    };
+    let title = format!(
+        "{} │ last {} │ min {} │ max {} │ {} samples{}",
+        metric.label(),
+        format_axis_value(latest),
+        format_axis_value(min),
+        format_axis_value(max),
+        samples.len(),
+        readout
+    );
+
+    let chart_widget = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([0.0, window_secs as f64])
+                .labels(x_labels),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([y_min, y_max])
+                .labels(y_labels),
+        );
+    f.render_widget(chart_widget, chunks[1]);
+}
+
+/// Heatmap columns: header, (warning, critical) cut-offs, and how each cell value is shown.
+const HEATMAP_NAME_WIDTH: usize = 8;
+const HEATMAP_CELL_WIDTH: usize = 5;
+
+/// Column titles with the warning/critical cut-offs each cell is graded against.
+fn heatmap_columns(thresholds: &AlertThresholds) -> [(&'static str, f64, f64); 4] {
+    [
+        ("Sprd", thresholds.spread_warning_bps, thresholds.spread_critical_bps),
+        ("VPIN", thresholds.vpin_warning, thresholds.vpin_critical),
+        ("Imb", thresholds.imbalance_warning, thresholds.imbalance_critical),
+        ("Conc", thresholds.concentration_warning, thresholds.concentration_critical),
+    ]
+}
+
+fn draw_heatmap_tab(
+    f: &mut Frame,
+    area: Rect,
+    scroll: &mut TabScroll,
+    thresholds: &AlertThresholds,
+    freshness: &Freshness,
+    metrics: &GlobalMetrics,
+) {
+    let liquidity = &metrics.liquidity_metrics;
+    let risk = &metrics.risk_metrics;
+    let columns = heatmap_columns(thresholds);
+
+    let mut assets: Vec<&String> = liquidity
+        .bid_ask_spread_bps
+        .keys()
+        .chain(liquidity.order_book_imbalance.keys())
+        .chain(risk.vpin_by_asset.keys())
+        .chain(risk.position_concentration.keys())
+        .collect();
+    assets.sort();
+    assets.dedup();
+
+    let mut rows: Vec<(&String, [Option<f64>; 4])> = assets
+        .into_iter()
+        .map(|asset| {
+            let values = [
+                liquidity.bid_ask_spread_bps.get(asset).copied(),
+                risk.vpin_by_asset.get(asset).copied(),
+                liquidity.order_book_imbalance.get(asset).map(|v| v.abs()),
+                risk.position_concentration.get(asset).copied(),
+            ];
+            (asset, values)
+        })
+        .collect();
+
+    let worst = |values: &[Option<f64>; 4]| {
+        values
+            .iter()
+            .zip(columns.iter())
+            .filter_map(|(v, (_, warning, critical))| v.map(|v| severity(v, *warning, *critical)))
+            .fold(0.0, f64::max)
+    };
+    rows.sort_by(|a, b| worst(&b.1).total_cmp(&worst(&a.1)).then_with(|| a.0.cmp(b.0)));
+
+    let deteriorating = rows.iter().filter(|(_, values)| worst(values) >= 0.5).count();
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let group_width = HEATMAP_NAME_WIDTH + columns.len() * HEATMAP_CELL_WIDTH + 2;
+    let groups = (inner.width as usize / group_width).max(1);
+    let rows_per_group = (inner.height as usize).saturating_sub(2).max(1);
+    let first = scroll.clamp(rows.len(), groups * rows_per_group);
+
+    let block = freshness.block(
+        format!("🌡️ Market Heatmap ({} assets, {} at warning or worse)", scroll.position(), deteriorating),
+        Source::Stream,
+        area.width,
+    );
+    f.render_widget(block, area);
+
+    if rows.is_empty() {
+        let empty = Paragraph::new("No per-asset metrics yet").alignment(Alignment::Center);
+        f.render_widget(empty, inner);
+        return;
+    }
+    let visible: Vec<_> = rows.iter().skip(first).collect();
+
+    let header_style = Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD);
+    let mut header = Vec::new();
+    for _ in 0..groups.min(visible.len().div_ceil(rows_per_group).max(1)) {
+        header.push(Span::styled(format!("{:<width$}", "Asset", width = HEATMAP_NAME_WIDTH), header_style));
+        for (title, _, _) in columns {
+            header.push(Span::styled(format!("{:^width$}", title, width = HEATMAP_CELL_WIDTH), header_style));
+        }
+        header.push(Span::raw("  "));
+    }
+
+    let mut lines = vec![Line::from(header)];
+    for r in 0..rows_per_group.min(visible.len()) {
+        let mut spans = Vec::new();
+        for g in 0..groups {
+            let Some((asset, values)) = visible.get(g * rows_per_group + r) else {
+                break;
+            };
+            let name: String = asset.chars().take(HEATMAP_NAME_WIDTH - 1).collect();
+            spans.push(Span::raw(format!("{:<width$}", name, width = HEATMAP_NAME_WIDTH)));
+            for (i, ((_, warning, critical), value)) in columns.iter().zip(values.iter()).enumerate() {
+                spans.push(match value {
+                    Some(v) => Span::styled(
+                        format!("{:^width$}", format_heat_value(i, *v), width = HEATMAP_CELL_WIDTH),
+                        Style::default().fg(Color::Black).bg(heat_color(severity(*v, *warning, *critical))),
+                    ),
+                    None => Span::styled(format!("{:^width$}", "·", width = HEATMAP_CELL_WIDTH), Style::default().fg(Color::DarkGray)),
+                });
+            }
+            spans.push(Span::raw("  "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let mut legend = vec![Span::styled("healthy ", Style::default().fg(Color::DarkGray))];
+    for step in 0..=10 {
+        legend.push(Span::styled(" ", Style::default().bg(heat_color(step as f64 / 10.0))));
+    }
+    legend.push(Span::styled(" critical   · no data   sorted by worst cell   ↑/↓ scroll", Style::default().fg(Color::DarkGray)));
+
+    let body = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+    f.render_widget(Paragraph::new(lines), body[0]);
+    f.render_widget(Paragraph::new(Line::from(legend)), body[1]);
+}
+
+fn format_heat_value(column: usize, value: f64) -> String {
+    match column {
+        0 if value >= 100.0 => format!("{:.0}", value),
+        0 => format!("{:.1}", value),
+        3 => format!("{:.0}%", value * 100.0),
+        _ => format!("{:.2}", value).trim_start_matches('0').to_string(),
+    }
+}
+
+/// Maps a raw metric onto 0..1 where 0.5 is the warning line and 1.0 is critical.
+fn severity(value: f64, warning: f64, critical: f64) -> f64 {
+    if value <= warning {
+        if warning > 0.0 { 0.5 * (value / warning).max(0.0) } else { 0.0 }
+    } else {
+        0.5 + 0.5 * ((value - warning) / (critical - warning).max(f64::EPSILON)).min(1.0)
+    }
+}
+
+/// Green through yellow to red.
+fn heat_color(severity: f64) -> Color {
+    let s = severity.clamp(0.0, 1.0);
+    if s < 0.5 {
+        let t = s / 0.5;
+        Color::Rgb((40.0 + 180.0 * t) as u8, (170.0 + 20.0 * t) as u8, 40)
+    } else {
+        let t = (s - 0.5) / 0.5;
+        Color::Rgb(220, (190.0 * (1.0 - t)) as u8, 40)
+    }
+}
+
+fn draw_logs_tab(f: &mut Frame, area: Rect, state: &UIState) {
+    if !state.log_capture {
+        let note = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "Logs are written to stderr while running with --debug",
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from("Restart without --debug to browse them here."),
+        ])
+        .alignment(Alignment::Center)
+        .block(Block::default().title("📜 Logs").borders(Borders::ALL));
+        f.render_widget(note, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    f.render_widget(LogViewer::new(&state.log_state), chunks[0]);
+
+    let hint = Paragraph::new(Span::styled(
+        "↑/↓ target  ←/→ shown level  +/- captured level  F focus target  V toggle targets  Space hide off  PgUp/PgDn page  End follow",
+        Style::default().fg(Color::DarkGray),
+    ));
+    f.render_widget(hint, chunks[1]);
+}
+
+fn alert_item(alert: &Alert) -> ListItem<'static> {
+    let (label, color) = match alert.level {
+        AlertLevel::Critical => ("CRIT", Color::Red),
+        AlertLevel::Warning => ("WARN", Color::Yellow),
+        AlertLevel::Info => ("INFO", Color::Blue),
+    };
+
+    let color = if alert.acknowledged { Color::DarkGray } else { color };
+
+    ListItem::new(Line::from(vec![
+        Span::styled(
+            format!("[{}, {}] ", format::time(alert.timestamp, "%H:%M:%S"), format::ago(alert.timestamp)),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(if alert.acknowledged { "✓ " } else { "  " }, Style::default().fg(Color::Green)),
+        Span::styled(format!("{:<5}", label), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+        Span::styled(format!("{}: ", alert.metric), Style::default().fg(Color::Cyan)),
+        Span::raw(alert.message.clone()),
+        Span::styled(
+            format!(" ({:.3} / {:.3})", alert.value, alert.threshold),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]))
+}
+
+fn metric_line(label: &str, value: String, color: Color) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{}: ", label)),
+        Span::styled(value, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+    ])
+}
+
+/// In the denomination `draw` set for the frame.
+fn format_amount(value: Decimal) -> String {
+    format::amount(value.to_f64().unwrap_or(0.0))
+}
+
+fn format_duration(secs: i64) -> String {
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
+fn format_axis_value(value: f64) -> String {
+    format::current().compact(value, 3)
+}
+
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+    if count == 0 { 0.0 } else { sum / count as f64 }
+}
+
+fn calculate_health_score(metrics: &GlobalMetrics) -> f64 {
+    let risk = &metrics.risk_metrics;
+    let penalty = (risk.vpin_score
+        + risk.phantom_liquidity_index
+        + risk.liquidation_risk_score
+        + risk.cascade_risk_score)
+        / 4.0;
+    (1.0 - penalty).clamp(0.0, 1.0)
+}
+
+fn classify_vpin(vpin: f64, thresholds: &AlertThresholds) -> (&'static str, &'static str) {
+    if vpin > thresholds.vpin_critical {
+        ("HIGH", "Order flow is dominated by informed traders; widen quotes and reduce inventory in affected assets.")
+    } else if vpin > thresholds.vpin_warning {
+        ("MODERATE", "Some one-sided flow present; monitor for escalation.")
+    } else {
+        ("LOW", "Balanced order flow; no signs of informed trading pressure.")
+    }
+}
+
+fn risk_recommendations(metrics: &GlobalMetrics, thresholds: &AlertThresholds) -> Vec<String> {
+    let risk = &metrics.risk_metrics;
+    let mut recommendations = Vec::new();
+
+    if risk.vpin_score > thresholds.vpin_critical {
+        recommendations.push("Toxic flow elevated: widen spreads on high-VPIN assets".to_string());
+    }
+    if risk.phantom_liquidity_index > thresholds.phantom_liquidity_warning {
+        recommendations.push("Displayed depth unreliable: size hedges against realized, not quoted, liquidity".to_string());
+    }
+    if risk.liquidation_risk_score > thresholds.liquidation_risk_warning {
+        recommendations.push("Liquidation risk high: reduce leverage or add margin".to_string());
+    }
+    if risk.cascade_risk_score > thresholds.cascade_risk_warning {
+        recommendations.push("Cascade risk high: diversify correlated exposures".to_string());
+    }
+    if let Some((coin, weight)) = risk
+        .position_concentration
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        if *weight > thresholds.concentration_critical {
+            recommendations.push(format!("{} is {:.0}% of exposure: consider trimming", coin, weight * 100.0));
+        }
+    }
+    let mut near_cap: Vec<&String> = risk
+        .open_interest_cap_usage
+        .iter()
+        .filter(|(coin, usage)| **usage > thresholds.oi_cap_warning && metrics.positions.iter().any(|position| &position.symbol == *coin))
+        .map(|(coin, _)| coin)
+        .collect();
+    near_cap.sort();
+    for coin in near_cap {
+        recommendations.push(format!("{} open interest near its cap: unwind early while the book allows", coin));
+    }
+
+    if recommendations.is_empty() {
+        recommendations.push("No action required: all risk indicators within normal ranges".to_string());
+    }
+
+    recommendations
+}
+
+fn vpin_color(vpin: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(vpin, thresholds.vpin_warning, thresholds.vpin_critical)
+}
+
+fn pli_color(pli: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(pli, thresholds.phantom_liquidity_warning, thresholds.phantom_liquidity_critical)
+}
+
+fn liquidation_color(score: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(score, thresholds.liquidation_risk_warning, thresholds.liquidation_risk_critical)
+}
+
+fn cascade_color(score: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(score, thresholds.cascade_risk_warning, thresholds.cascade_risk_critical)
+}
+
+fn drawdown_color(drawdown: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(drawdown, thresholds.max_drawdown_warning, thresholds.max_drawdown_critical)
+}
+
+fn utilization_color(utilization: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(utilization, thresholds.utilization_warning, thresholds.utilization_critical)
+}
+
+fn concentration_color(weight: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(weight, thresholds.concentration_warning, thresholds.concentration_critical)
+}
+
+fn spread_color(spread_bps: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(spread_bps, thresholds.spread_warning_bps, thresholds.spread_critical_bps)
+}
+
+fn imbalance_color(imbalance: f64, thresholds: &AlertThresholds) -> Color {
+    ratio_color(imbalance.abs(), thresholds.imbalance_warning, thresholds.imbalance_critical)
+}
+
+fn ratio_color(value: f64, warning: f64, critical: f64) -> Color {
+    if value > critical {
+        Color::Red
+    } else if value > warning {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+/// `ratio_color` for a reading that is worse the lower it goes.
+fn floor_color(value: f64, warning: f64, critical: f64) -> Color {
+    if value < critical {
+        Color::Red
+    } else if value < warning {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn ratio_quality_color(ratio: f64) -> Color {
+    if ratio >= 2.0 {
+        Color::Green
+    } else if ratio >= 1.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+fn pnl_color(pnl: Decimal) -> Color {
+    if pnl >= Decimal::ZERO { Color::Green } else { Color::Red }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+
+use crate::alert;
+use crate::model::{Alert, AlertLevel, AssetInfo, Meta};
+
+/// Metric name of the alerts raised for universe changes.
+pub const METRIC: &str = "Universe";
+
+/// A change to an asset between two `meta` responses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniverseChange {
+    Listed(String),
+    Delisted(String),
+    LeverageCut { coin: String, from: u32, to: u32 },
+    IsolatedOnly(String),
+}
+
+impl UniverseChange {
+    pub fn coin(&self) -> &str {
+        match self {
+            Self::Listed(coin) | Self::Delisted(coin) | Self::IsolatedOnly(coin) => coin,
+            Self::LeverageCut { coin, .. } => coin,
+        }
+    }
+
+    /// An alert for the change: Critical when it cuts into an open position,
+    /// since the position may no longer be margined or closable as it was.
+    pub fn alert(&self, held: &HashSet<&str>) -> Alert {
+        let message = match self {
+            Self::Listed(coin) => return alert::event(AlertLevel::Info, METRIC, format!("{} was listed", coin)),
+            Self::Delisted(coin) => format!("{} was delisted", coin),
+            Self::LeverageCut { coin, from, to } => format!("{} max leverage cut from {}x to {}x", coin, from, to),
+            Self::IsolatedOnly(coin) => format!("{} is now isolated-only", coin),
+        };
+        match held.contains(self.coin()) {
+            true => alert::event(AlertLevel::Critical, METRIC, format!("{} with an open position", message)),
+            false => alert::event(AlertLevel::Warning, METRIC, message),
+        }
+    }
+}
+
+/// The assets that can be traded: those listed and not delisted.
+pub fn listed(meta: &Meta) -> impl Iterator<Item = &AssetInfo> {
+    meta.universe.iter().filter(|asset| !asset.is_delisted)
+}
+
+/// Compares each `meta` response with the previous one.
+#[derive(Debug, Default)]
+pub struct UniverseWatch {
+    previous: Option<Meta>,
+    /// Every asset listed since the first `meta`, with when it was first seen.
+    listings: Vec<(String, DateTime<Utc>)>,
+}
+
+impl UniverseWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What changed since the last call; nothing on the first.
+    pub fn update(&mut self, meta: &Meta) -> Vec<UniverseChange> {
+        let changes = self.previous.as_ref().map(|previous| diff(previous, meta)).unwrap_or_default();
+        self.previous = Some(meta.clone());
+        let now = Utc::now();
+        for change in &changes {
+            if let UniverseChange::Listed(coin) = change {
+                // A relisted asset starts over.
+                self.listings.retain(|(listed, _)| listed != coin);
+                self.listings.push((coin.clone(), now));
+            }
+        }
+        changes
+    }
+
+    pub fn listings(&self) -> &[(String, DateTime<Utc>)] {
+        &self.listings
+    }
+}
+
+/// Listings, delistings, leverage cuts and isolated-only flips from `previous`
+/// to `current`. An asset gone from the list counts as delisted, as does one
+/// flagged so.
+pub fn diff(previous: &Meta, current: &Meta) -> Vec<UniverseChange> {
+    let mut changes = Vec::new();
+    for asset in listed(current) {
+        let Some(before) = listed(previous).find(|before| before.name == asset.name) else {
+            changes.push(UniverseChange::Listed(asset.name.clone()));
+            continue;
+        };
+        if asset.max_leverage < before.max_leverage {
+            changes.push(UniverseChange::LeverageCut {
+                coin: asset.name.clone(),
+                from: before.max_leverage,
+                to: asset.max_leverage,
+            });
+        }
+        if asset.only_isolated && !before.only_isolated {
+            changes.push(UniverseChange::IsolatedOnly(asset.name.clone()));
+        }
+    }
+    for before in listed(previous) {
+        if !listed(current).any(|asset| asset.name == before.name) {
+            changes.push(UniverseChange::Delisted(before.name.clone()));
+        }
+    }
+    changes
+}
@@ -0,0 +1,329 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{debug, info, warn};
+use ring::{digest, hmac};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use url::Url;
+
+use crate::config::{Config, UploadSettings};
+
+/// Per-directory record of what has been uploaded, so restarts skip unchanged files.
+const LEDGER_FILE: &str = ".s3-uploaded.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Uploaded {
+    key: String,
+    len: u64,
+    modified_ms: i64,
+}
+
+/// A local directory shipped under `<prefix>/<kind>/`.
+struct Source {
+    kind: &'static str,
+    dir: PathBuf,
+    ledger: BTreeMap<String, Uploaded>,
+}
+
+/// Copies recorded sessions, forensic bundles and daily reports to an S3-compatible
+/// bucket every `interval_secs`. Keys are
+/// `<prefix>/<kind>/YYYY/MM/DD/<path in the directory>`, dated by the day in the
+/// file's path (a forensic bundle's alert time, a report's day) or else by when the
+/// file was first seen, so bucket lifecycle rules can expire each kind by prefix.
+/// A file missing from the ledger is only sent when the bucket does not already
+/// hold an object of the same size under its key. A file is uploaded again when
+/// its size or modification time changes; the key stays the same, so a growing
+/// recording replaces its earlier copy.
+pub struct Uploader {
+    settings: UploadSettings,
+    client: reqwest::Client,
+    sources: Vec<Source>,
+}
+
+impl Uploader {
+    /// Sources are the `[forensics]` and `[daily_report]` directories when those are
+    /// enabled, and the session directory of a running `record`.
+    pub fn new(config: &Config) -> Self {
+        let mut sources = Vec::new();
+        let mut add = |kind, dir: &Path| {
+            let ledger = std::fs::read_to_string(dir.join(LEDGER_FILE))
+                .ok()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default();
+            sources.push(Source { kind, dir: dir.to_path_buf(), ledger });
+        };
+        if let Some(dir) = &config.upload.session_dir {
+            add("sessions", dir);
+        }
+        if config.forensics.enabled {
+            add("forensics", Path::new(&config.forensics.dir));
+        }
+        if config.daily_report.enabled {
+            add("reports", Path::new(&config.daily_report.dir));
+        }
+
+        let mut settings = config.upload.clone();
+        // The usual AWS variables fill in credentials left out of the config.
+        if settings.access_key_id.is_empty() {
+            settings.access_key_id = std::env::var("AWS_ACCESS_KEY_ID").unwrap_or_default();
+        }
+        if settings.secret_access_key.is_empty() {
+            settings.secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default();
+        }
+        if settings.session_token.is_none() {
+            settings.session_token = std::env::var("AWS_SESSION_TOKEN").ok();
+        }
+
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(120)).build().unwrap_or_default();
+        Self { settings, client, sources }
+    }
+
+    pub async fn run(mut self) {
+        if self.sources.is_empty() {
+            warn!("⚠️ Upload enabled but nothing to upload: enable [forensics] or [daily_report], or run `record`");
+            return;
+        }
+        let kinds: Vec<&str> = self.sources.iter().map(|s| s.kind).collect();
+        info!("☁️ Uploading {} to s3://{}/{}", kinds.join(", "), self.settings.bucket, self.settings.prefix);
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.settings.interval_secs));
+        let mut failing = false;
+        loop {
+            interval.tick().await;
+            match self.sync().await {
+                Ok(0) => {}
+                Ok(count) => info!("☁️ Uploaded {} files", count),
+                Err(e) if !failing => {
+                    warn!("⚠️ Upload to {} failed: {}; retrying every {}s", self.settings.endpoint, e, self.settings.interval_secs);
+                    failing = true;
+                    continue;
+                }
+                Err(e) => {
+                    debug!("☁️ Upload still failing: {}", e);
+                    continue;
+                }
+            }
+            if failing {
+                info!("☁️ Uploads recovered");
+            }
+            failing = false;
+        }
+    }
+
+    /// Uploads every new or changed file; stops at the first failure.
+    async fn sync(&mut self) -> Result<usize> {
+        let mut uploaded = 0;
+        for i in 0..self.sources.len() {
+            let source = &self.sources[i];
+            let mut files = Vec::new();
+            list_files(&source.dir, &source.dir, &mut files);
+
+            let mut changed = false;
+            let mut result = Ok(());
+            for (relative, path, len, modified) in files {
+                let modified_ms = modified.timestamp_millis();
+                let source = &self.sources[i];
+                let key = match source.ledger.get(&relative) {
+                    Some(entry) if entry.len == len && entry.modified_ms == modified_ms => continue,
+                    Some(entry) => entry.key.clone(),
+                    None => {
+                        let day = partition_date(&relative).unwrap_or_else(|| Utc::now().date_naive());
+                        let key = object_key(&self.settings.prefix, source.kind, day, &relative);
+                        // A lost ledger, or another host sharing the bucket, should not resend what is already there.
+                        match self.head_object(&key).await {
+                            Ok(Some(existing)) if existing == len => {
+                                debug!("☁️ {} is already in the bucket", key);
+                                self.sources[i].ledger.insert(relative, Uploaded { key, len, modified_ms });
+                                changed = true;
+                                continue;
+                            }
+                            Ok(_) => key,
+                            Err(e) => {
+                                result = Err(e);
+                                break;
+                            }
+                        }
+                    }
+                };
+
+                let body = match tokio::fs::read(&path).await {
+                    Ok(body) => body,
+                    Err(e) => {
+                        debug!("☁️ Skipping {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+                if let Err(e) = self.put_object(&key, body).await {
+                    result = Err(e);
+                    break;
+                }
+                debug!("☁️ Uploaded {} to {}", path.display(), key);
+                self.sources[i].ledger.insert(relative, Uploaded { key, len, modified_ms });
+                changed = true;
+                uploaded += 1;
+            }
+
+            if changed {
+                let source = &self.sources[i];
+                if let Err(e) = std::fs::write(source.dir.join(LEDGER_FILE), serde_json::to_string_pretty(&source.ledger)?) {
+                    warn!("⚠️ Failed to save upload ledger in {}: {}", source.dir.display(), e);
+                }
+            }
+            result?;
+        }
+        Ok(uploaded)
+    }
+
+    async fn put_object(&self, key: &str, body: Vec<u8>) -> Result<()> {
+        let response = self.request(reqwest::Method::PUT, key, body)?.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("PUT {} returned {}: {}", key, status, text.chars().take(200).collect::<String>()));
+        }
+        Ok(())
+    }
+
+    /// Size of the object at `key`, or none when the bucket has no such object.
+    async fn head_object(&self, key: &str) -> Result<Option<u64>> {
+        let response = self.request(reqwest::Method::HEAD, key, Vec::new())?.send().await?;
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            // `content_length()` reports the empty body of a HEAD response, not the header.
+            status if status.is_success() => Ok(response
+                .headers()
+                .get(reqwest::header::CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok()?.parse().ok())),
+            status => Err(anyhow!("HEAD {} returned {}", key, status)),
+        }
+    }
+
+    /// A request for `key` signed with AWS Signature Version 4.
+    fn request(&self, method: reqwest::Method, key: &str, body: Vec<u8>) -> Result<reqwest::RequestBuilder> {
+        let settings = &self.settings;
+        let endpoint = Url::parse(&settings.endpoint)?;
+        let host = endpoint.host_str().ok_or_else(|| anyhow!("upload.endpoint has no host"))?;
+        let host = match endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let encoded_key: Vec<String> = key.split('/').map(uri_encode).collect();
+        let (host, path) = if settings.path_style {
+            (host, format!("/{}/{}", settings.bucket, encoded_key.join("/")))
+        } else {
+            (format!("{}.{}", settings.bucket, host), format!("/{}", encoded_key.join("/")))
+        };
+        let url = format!("{}://{}{}", endpoint.scheme(), host, path);
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(digest::digest(&digest::SHA256, &body).as_ref());
+
+        let mut headers = vec![
+            ("host", host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = settings.session_token.as_ref().filter(|t| !t.is_empty()) {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers: Vec<&str> = headers.iter().map(|(name, _)| *name).collect();
+        let signed_headers = signed_headers.join(";");
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+        let canonical_request = format!("{}\n{}\n\n{}\n{}\n{}", method, path, canonical_headers, signed_headers, payload_hash);
+
+        let scope = format!("{}/{}/s3/aws4_request", date, settings.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+        );
+        let mut signing_key = format!("AWS4{}", settings.secret_access_key).into_bytes();
+        for part in [date.as_str(), settings.region.as_str(), "s3", "aws4_request"] {
+            signing_key = sign(&signing_key, part.as_bytes());
+        }
+        let signature = hex(&sign(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            settings.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut request = self.client.request(method, url).header("Authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        Ok(request.body(body))
+    }
+}
+
+/// Regular files under `dir`, recursively, as (path relative to `root` with `/`
+/// separators, path, size, modification time). Dotfiles are skipped.
+fn list_files(root: &Path, dir: &Path, out: &mut Vec<(String, PathBuf, u64, DateTime<Utc>)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            list_files(root, &path, out);
+        } else if metadata.is_file() {
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            out.push((relative.join("/"), path, metadata.len(), modified.into()));
+        }
+    }
+}
+
+/// The day a file belongs to by its path: a `YYYYMMDDT...` forensic bundle, or a
+/// `YYYY-MM-DD` anywhere in a name (`hlp-report-2025-01-01.html`, `date=2025-01-01`).
+fn partition_date(relative: &str) -> Option<NaiveDate> {
+    relative.split('/').find_map(|part| {
+        let compact = part.get(..9).filter(|start| start.ends_with('T')).and_then(|start| NaiveDate::parse_from_str(&start[..8], "%Y%m%d").ok());
+        compact.or_else(|| (0..part.len()).filter_map(|i| part.get(i..i + 10)).find_map(|window| NaiveDate::parse_from_str(window, "%Y-%m-%d").ok()))
+    })
+}
+
+fn object_key(prefix: &str, kind: &str, day: NaiveDate, relative: &str) -> String {
+    let dated = format!("{}/{}/{}", kind, day.format("%Y/%m/%d"), relative);
+    match prefix.trim_matches('/') {
+        "" => dated,
+        prefix => format!("{}/{}", prefix, dated),
+    }
+}
+
+fn sign(key: &[u8], message: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), message).as_ref().to_vec()
+}
+
+/// SigV4 URI encoding: everything but unreserved characters is percent-encoded.
+fn uri_encode(segment: &str) -> String {
+    segment.bytes().fold(String::new(), |mut out, b| {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            let _ = write!(out, "%{:02X}", b);
+        }
+        out
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut out, b| {
+        let _ = write!(out, "{:02x}", b);
+        out
+    })
+}
@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A `RwLock` that counts write locks, so a reader polling many times a second
+/// can keep its own copy and refresh it only when `version` has moved.
+#[derive(Debug, Default)]
+pub struct Versioned<T> {
+    value: RwLock<T>,
+    version: AtomicU64,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(value: T) -> Self {
+        Self { value: RwLock::new(value), version: AtomicU64::new(0) }
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().await
+    }
+
+    /// Counts as a change whether or not the value is modified. The version moves
+    /// once the lock is held, so a reader that sees the new version and then waits
+    /// for `read` gets the written value.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let guard = self.value.write().await;
+        self.version.fetch_add(1, Ordering::Release);
+        guard
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+impl<T: Clone> Versioned<T> {
+    /// Brings `copy` up to date if the value changed since `seen`, the version of
+    /// the last refresh. Returns whether it did.
+    pub async fn refresh(&self, copy: &mut T, seen: &mut Option<u64>) -> bool {
+        let version = self.version();
+        if *seen == Some(version) {
+            return false;
+        }
+        copy.clone_from(&*self.value.read().await);
+        *seen = Some(version);
+        true
+    }
+}
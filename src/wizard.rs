@@ -0,0 +1,201 @@
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Terminal,
+};
+use std::io;
+
+use crate::config::{self, Config, OperatingMode};
+
+const ADDRESS: usize = 0;
+const VAULT: usize = 1;
+const MODE: usize = 2;
+const MQTT: usize = 3;
+const EMAIL: usize = 4;
+
+const MODES: [&str; 2] = ["Live", "Demo"];
+
+struct Step {
+    label: &'static str,
+    help: &'static str,
+}
+
+const STEPS: [Step; 5] = [
+    Step { label: "Account address", help: "The account to monitor, 0x followed by 40 hex digits. The default is the HLP vault." },
+    Step { label: "Vault address", help: "The vault_address setting, 0x followed by 40 hex digits; leave empty for none." },
+    Step { label: "Mode", help: "Live connects to Hyperliquid; Demo shows simulated data. Left/Right to change." },
+    Step { label: "MQTT broker", help: "host:port to publish metrics and alerts to; leave empty to skip." },
+    Step { label: "Daily report email", help: "Comma-separated recipients of the daily report, sent with sendmail; leave empty to skip." },
+];
+
+/// Asks for the address, vault, mode and notification channels, one step at a
+/// time, and writes a config with everything else at its default to `path`.
+/// Returns `None` when cancelled, leaving no file behind.
+pub fn run(path: &str) -> Result<Option<Config>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let answers = ask(&mut terminal, path);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    let Some(answers) = answers? else {
+        return Ok(None);
+    };
+    let config = build(&answers);
+    config::save_config_to_file(&config, path)?;
+    Ok(Some(config))
+}
+
+fn ask(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, path: &str) -> Result<Option<Vec<String>>> {
+    let defaults = Config::default();
+    let mut answers = vec![defaults.user_address, String::new(), MODES[0].to_string(), String::new(), String::new()];
+    // STEPS.len() is the review screen.
+    let mut step = 0;
+    let mut error: Option<String> = None;
+
+    loop {
+        terminal.draw(|f| draw(f, path, &answers, step, error.as_deref()))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc if step == 0 => return Ok(None),
+            KeyCode::Esc | KeyCode::BackTab | KeyCode::Up => {
+                step = step.saturating_sub(1);
+                error = None;
+            }
+            KeyCode::Enter if step == STEPS.len() => return Ok(Some(answers)),
+            KeyCode::Enter | KeyCode::Tab | KeyCode::Down => match check(step, answers[step].trim()) {
+                Ok(()) => {
+                    answers[step] = answers[step].trim().to_string();
+                    step += 1;
+                    error = None;
+                }
+                Err(problem) => error = Some(problem),
+            },
+            _ if step == STEPS.len() => {}
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if step == MODE => {
+                answers[MODE] = MODES.iter().find(|mode| **mode != answers[MODE]).unwrap_or(&MODES[0]).to_string();
+            }
+            _ if step == MODE => {}
+            KeyCode::Backspace => {
+                answers[step].pop();
+            }
+            KeyCode::Char(c) => answers[step].push(c),
+            _ => {}
+        }
+    }
+}
+
+fn check(step: usize, answer: &str) -> std::result::Result<(), String> {
+    match step {
+        ADDRESS if !config::is_address(answer) => Err("expected 0x followed by 40 hex digits".to_string()),
+        VAULT if !answer.is_empty() && !config::is_address(answer) => Err("expected 0x followed by 40 hex digits, or nothing".to_string()),
+        MQTT if !answer.is_empty() && !answer.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()) => {
+            Err("expected host:port, e.g. localhost:1883".to_string())
+        }
+        EMAIL => match recipients(answer).find(|address| !address.contains('@')) {
+            Some(address) => Err(format!("{} is not an email address", address)),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+fn recipients(answer: &str) -> impl Iterator<Item = &str> {
+    answer.split(',').map(str::trim).filter(|address| !address.is_empty())
+}
+
+fn build(answers: &[String]) -> Config {
+    let mut config = Config {
+        user_address: answers[ADDRESS].clone(),
+        vault_address: Some(answers[VAULT].clone()).filter(|vault| !vault.is_empty()),
+        operating_mode: if answers[MODE] == "Demo" { OperatingMode::Demo } else { OperatingMode::Live },
+        ..Config::default()
+    };
+    if !answers[MQTT].is_empty() {
+        config.mqtt.enabled = true;
+        config.mqtt.broker = answers[MQTT].clone();
+    }
+    let email_to: Vec<String> = recipients(&answers[EMAIL]).map(str::to_string).collect();
+    if !email_to.is_empty() {
+        config.daily_report.enabled = true;
+        config.daily_report.email_to = email_to;
+    }
+    config
+}
+
+fn draw(f: &mut ratatui::Frame, path: &str, answers: &[String], step: usize, error: Option<&str>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(STEPS.len() as u16 + 2), Constraint::Min(0), Constraint::Length(1)])
+        .split(f.size());
+
+    let title = if step == STEPS.len() {
+        "HLP Toshogu setup - review".to_string()
+    } else {
+        format!("HLP Toshogu setup - step {} of {}", step + 1, STEPS.len())
+    };
+    f.render_widget(
+        Paragraph::new(title)
+            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL)),
+        chunks[0],
+    );
+
+    let fields: Vec<Line> = STEPS
+        .iter()
+        .zip(answers)
+        .enumerate()
+        .map(|(i, (s, answer))| {
+            let current = i == step;
+            let marker = if current { "> " } else { "  " };
+            let value = match (i, answer.is_empty()) {
+                (MODE, _) => format!("< {} >", answer),
+                (_, true) if !current => "(none)".to_string(),
+                _ => answer.clone(),
+            };
+            let style = if current { Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD) } else { Style::default() };
+            let mut spans = vec![Span::styled(format!("{}{:<20}", marker, s.label), style), Span::styled(value, style)];
+            if current && i != MODE {
+                spans.push(Span::styled("_", Style::default().fg(Color::Yellow)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(fields).block(Block::default().borders(Borders::ALL)), chunks[1]);
+
+    let mut help = vec![Line::from(match STEPS.get(step) {
+        Some(s) => s.help.to_string(),
+        None => format!("Enter writes {}; everything else keeps its default and can be edited there later.", path),
+    })];
+    if let Some(error) = error {
+        help.push(Line::from(Span::styled(error.to_string(), Style::default().fg(Color::Red))));
+    }
+    f.render_widget(Paragraph::new(help).wrap(Wrap { trim: true }).block(Block::default().borders(Borders::ALL)), chunks[2]);
+
+    let keys = if step == STEPS.len() { "Enter save | Esc back | Ctrl-C cancel" } else { "Enter next | Esc back | Ctrl-C cancel" };
+    f.render_widget(Paragraph::new(keys).style(Style::default().fg(Color::DarkGray)), chunks[3]);
+}
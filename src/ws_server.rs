@@ -0,0 +1,99 @@
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+use crate::model::{Alert, GlobalMetrics};
+use crate::shared::SharedMetrics;
+use crate::versioned::Versioned;
+
+/// How often the publisher looks for a new snapshot.
+const POLL_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_millis(200);
+
+/// One message per update cycle.
+#[derive(Serialize)]
+struct Update<'a> {
+    metrics: &'a GlobalMetrics,
+    alerts: &'a [Alert],
+}
+
+/// Pushes `{"metrics": ..., "alerts": [...]}` as a text frame to every connected
+/// WebSocket client whenever the collection loop publishes a new snapshot, so a
+/// browser page or stream overlay can mirror the dashboard. Each update is
+/// serialized once and shared; a slow client skips to the latest one instead of
+/// queueing. Anything clients send is ignored.
+pub async fn serve(addr: SocketAddr, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("🔌 WebSocket updates on ws://{}", addr);
+
+    let (tx, rx) = watch::channel(None);
+    tokio::spawn(publish(tx, metrics, alerts));
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        tokio::spawn(client(stream, peer, rx.clone()));
+    }
+}
+
+async fn publish(tx: watch::Sender<Option<Arc<str>>>, metrics: Arc<SharedMetrics>, alerts: Arc<Versioned<Vec<Alert>>>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut last_sent = None;
+
+    loop {
+        interval.tick().await;
+        let last_update = metrics.last_update().await;
+        if last_update.is_none() || last_update == last_sent {
+            continue;
+        }
+        last_sent = last_update;
+        let metrics = metrics.snapshot().await;
+
+        let alerts = alerts.read().await;
+        match serde_json::to_string(&Update { metrics: &metrics, alerts: &alerts }) {
+            Ok(text) => {
+                tx.send_replace(Some(text.into()));
+            }
+            Err(e) => warn!("⚠️ Could not serialize WebSocket update: {}", e),
+        }
+    }
+}
+
+async fn client(stream: TcpStream, peer: SocketAddr, mut rx: watch::Receiver<Option<Arc<str>>>) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            debug!("🔌 WebSocket handshake with {} failed: {}", peer, e);
+            return;
+        }
+    };
+    debug!("🔌 WebSocket client {} connected", peer);
+    let (mut sink, mut incoming) = ws.split();
+
+    // Start with the latest snapshot rather than waiting for the next cycle.
+    rx.mark_changed();
+    loop {
+        tokio::select! {
+            changed = rx.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let Some(text) = rx.borrow_and_update().clone() else {
+                    continue;
+                };
+                if sink.send(Message::Text(text.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("🔌 WebSocket client {} disconnected", peer);
+}
@@ -0,0 +1,157 @@
+//! Golden-file regression tests for the metric pipeline.
+//!
+//! Each directory of `tests/fixtures` holds one collection cycle, `cycle.json`,
+//! as the provider returned it, and the WebSocket stream before it,
+//! `stream.jsonl`, one `{"trade": …}`, `{"book": …}` or `{"order": …}` per line.
+//! The stream is fed to a streaming engine, the cycle's metrics are computed
+//! as the collection loop computes them, and the result must match
+//! `tests/golden/<fixture>.json` within `RELATIVE_TOLERANCE`.
+//!
+//! After an intended change to `metrics/`, `UPDATE_GOLDEN=1 cargo test --test
+//! golden` rewrites the golden files; their diff shows every number that moved.
+
+use hlp_toshogu::metrics::cycle::{compute_metrics, CycleData};
+use hlp_toshogu::metrics::streaming::StreamingMetricsEngine;
+use hlp_toshogu::model::{Fill, L2Snapshot, OrderEvent};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Of the larger magnitude; sums over hash maps may add up in any order.
+const RELATIVE_TOLERANCE: f64 = 1e-9;
+/// Fields set from the clock, left out of the comparison.
+const CLOCK_FIELDS: [&str; 1] = ["last_update"];
+/// Mismatches listed before the rest are counted.
+const REPORTED: usize = 20;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StreamEvent {
+    Trade(Fill),
+    Book(L2Snapshot),
+    Order(OrderEvent),
+}
+
+fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.json", name))
+}
+
+/// The metrics of the fixture's cycle, as JSON without the clock fields.
+async fn run_fixture(name: &str) -> Value {
+    let dir = fixture_dir(name);
+    let read = |file: &str| std::fs::read_to_string(dir.join(file)).unwrap_or_else(|e| panic!("cannot read {}/{}: {}", name, file, e));
+
+    let mut engine = StreamingMetricsEngine::default();
+    for (i, line) in read("stream.jsonl").lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        match serde_json::from_str(line).unwrap_or_else(|e| panic!("{}/stream.jsonl line {}: {}", name, i + 1, e)) {
+            StreamEvent::Trade(fill) => engine.process_trade(fill),
+            StreamEvent::Book(book) => engine.process_l2_update(book),
+            StreamEvent::Order(order) => engine.process_order(&order),
+        }
+    }
+    let data: CycleData = serde_json::from_str(&read("cycle.json")).unwrap_or_else(|e| panic!("{}/cycle.json: {}", name, e));
+
+    let engine = Arc::new(RwLock::new(engine));
+    let mut metrics = compute_metrics(data, &Some(engine.clone())).await;
+    metrics.system_status.shadow = Some(engine.read().await.shadow().clone());
+    let mut value = serde_json::to_value(&metrics).expect("metrics serialize");
+    if let Value::Object(fields) = &mut value {
+        for field in CLOCK_FIELDS {
+            fields.remove(field);
+        }
+    }
+    value
+}
+
+fn close(expected: f64, actual: f64) -> bool {
+    expected == actual || (expected - actual).abs() <= RELATIVE_TOLERANCE * expected.abs().max(actual.abs())
+}
+
+/// Every difference between `expected` and `actual` beyond the tolerance, by
+/// JSON path.
+fn differences(path: &str, expected: &Value, actual: &Value, found: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Number(e), Value::Number(a)) => {
+            let (e, a) = (e.as_f64().unwrap_or(f64::NAN), a.as_f64().unwrap_or(f64::NAN));
+            if !close(e, a) {
+                found.push(format!("{}: expected {}, got {}", path, e, a));
+            }
+        }
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, expected) in e {
+                let at = format!("{}.{}", path, key);
+                match a.get(key) {
+                    Some(actual) => differences(&at, expected, actual, found),
+                    None => found.push(format!("{}: missing", at)),
+                }
+            }
+            for key in a.keys().filter(|key| !e.contains_key(*key)) {
+                found.push(format!("{}.{}: not in the golden file", path, key));
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (i, (expected, actual)) in e.iter().zip(a).enumerate() {
+                differences(&format!("{}[{}]", path, i), expected, actual, found);
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => found.push(format!("{}: expected {} items, got {}", path, e.len(), a.len())),
+        (e, a) if e != a => found.push(format!("{}: expected {}, got {}", path, e, a)),
+        _ => {}
+    }
+}
+
+async fn check(name: &str) {
+    let actual = run_fixture(name).await;
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().expect("golden dir")).expect("create tests/golden");
+        std::fs::write(&path, serde_json::to_string_pretty(&actual).expect("metrics serialize") + "\n").expect("write golden file");
+        return;
+    }
+
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}; UPDATE_GOLDEN=1 writes it", path.display(), e));
+    let expected: Value = serde_json::from_str(&golden).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let mut found = Vec::new();
+    differences("metrics", &expected, &actual, &mut found);
+    if !found.is_empty() {
+        let mut report = found.iter().take(REPORTED).cloned().collect::<Vec<_>>().join("\n  ");
+        if found.len() > REPORTED {
+            report += &format!("\n  … and {} more", found.len() - REPORTED);
+        }
+        panic!(
+            "{} metrics differ from {}:\n  {}\nIf the change is intended, rerun with UPDATE_GOLDEN=1 and review the diff",
+            name,
+            path.display(),
+            report
+        );
+    }
+}
+
+#[tokio::test]
+async fn majors() {
+    check("majors").await;
+}
+
+#[tokio::test]
+async fn squeeze() {
+    check("squeeze").await;
+}
+
+/// The harness would pass anything if the pipeline weren't a function of the
+/// fixtures; two runs must agree exactly, not just within the tolerance.
+#[tokio::test]
+async fn pipeline_is_deterministic() {
+    for name in ["majors", "squeeze"] {
+        let (first, second) = (run_fixture(name).await, run_fixture(name).await);
+        let mut found = Vec::new();
+        differences("metrics", &first, &second, &mut found);
+        assert!(found.is_empty(), "{} differs between runs:\n  {}", name, found.join("\n  "));
+    }
+}
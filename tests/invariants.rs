@@ -0,0 +1,380 @@
+//! Invariants of the metric calculators in `metrics/mod.rs` and
+//! `metrics/risk.rs`, checked over generated fills, books, vaults, positions
+//! and spot balances: VPIN and phantom liquidity stay in [0, 1], depth is
+//! non-negative and grows with the distance, spreads agree with the book they
+//! came from, and the concentrations of positions and of turnover never add up
+//! to more than the whole. The history's bucketed drawdown keeps the deepest
+//! fall of the samples it covers.
+//!
+//! The generators lean towards the inputs these functions tend to get wrong:
+//! zero sizes, one-sided and crossed books, sides other than "B", prices
+//! across fourteen orders of magnitude, an empty vault. Each case draws from its
+//! own seed. Failures are not shrunk: the report gives the property, the case
+//! number, the seed and the values that broke it, and the replay line it prints
+//! (`INVARIANT_SEED=<seed> cargo test --test invariants <property> -- --exact`)
+//! runs that one case alone.
+
+use hlp_toshogu::history::{HistoryMetric, MetricHistory};
+use hlp_toshogu::metrics::{calculate_depth_at_bps, calculate_liquidity_metrics, calculate_order_book_imbalance, risk};
+use hlp_toshogu::model::{AssetInfo, Fill, L2Snapshot, LiquidityMetrics, Meta, OrderBookLevel, Position, SpotBalance, VaultSummary};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Cases per property when no seed is given.
+const CASES: u64 = 400;
+/// Seed of the first case; each property runs `CASES` seeds from it.
+const FIRST_SEED: u64 = 0x494e_5641;
+/// Slack for sums of `Decimal` shares converted to `f64`.
+const EPSILON: f64 = 1e-9;
+
+/// Majors, alts and a builder DEX asset; `meta` lists some of them.
+const COINS: [&str; 10] = ["BTC", "ETH", "SOL", "AVAX", "DOGE", "LINK", "UNI", "kPEPE", "HYPE", "xyz:TSLA"];
+const SIDES: [&str; 4] = ["B", "A", "b", ""];
+
+/// Runs `property` on each case's generator, panicking with the seed of the
+/// first case it fails. There is no shrinking: the seed reproduces the case
+/// exactly, and the failure names the values that broke the property.
+fn check(name: &str, property: impl Fn(&mut StdRng) -> Result<(), String>) {
+    let seeds = match std::env::var("INVARIANT_SEED") {
+        Ok(seed) => {
+            let seed = seed.parse().unwrap_or_else(|_| panic!("INVARIANT_SEED must be a number, not {}", seed));
+            seed..seed + 1
+        }
+        Err(_) => FIRST_SEED..FIRST_SEED + CASES,
+    };
+    let cases = seeds.end - seeds.start;
+    for (case, seed) in seeds.enumerate() {
+        if let Err(failure) = property(&mut StdRng::seed_from_u64(seed)) {
+            panic!(
+                "\n{} failed on case {} of {}\n  seed:    {}\n  failure: {}\n  replay:  INVARIANT_SEED={} cargo test --test invariants {} -- --exact\n",
+                name,
+                case + 1,
+                cases,
+                seed,
+                failure,
+                seed,
+                name,
+            );
+        }
+    }
+}
+
+fn ensure(holds: bool, failure: impl FnOnce() -> String) -> Result<(), String> {
+    if holds {
+        Ok(())
+    } else {
+        Err(failure())
+    }
+}
+
+fn in_unit_interval(what: &str, value: f64) -> Result<(), String> {
+    ensure((0.0..=1.0).contains(&value), || format!("{} is {}, outside [0, 1]", what, value))
+}
+
+/// A positive amount between 10^`min_exp` and 10^`max_exp`, log-uniform, to
+/// eight decimals as Hyperliquid quotes them; zero one time in `zero_one_in`.
+fn amount(rng: &mut StdRng, min_exp: f64, max_exp: f64, zero_one_in: u32) -> Decimal {
+    if zero_one_in > 0 && rng.gen_ratio(1, zero_one_in) {
+        return Decimal::ZERO;
+    }
+    let magnitude = 10f64.powf(rng.gen_range(min_exp..max_exp));
+    Decimal::from_f64(magnitude).unwrap_or(Decimal::ONE).round_dp(8).max(Decimal::new(1, 8))
+}
+
+fn coin(rng: &mut StdRng) -> String {
+    COINS[rng.gen_range(0..COINS.len())].to_string()
+}
+
+/// A universe of most of `COINS`, at leverages either side of the cut-offs
+/// the risk metrics filter majors by.
+fn meta(rng: &mut StdRng) -> Meta {
+    let mut universe = Vec::new();
+    for name in COINS.iter().filter(|_| rng.gen_bool(0.8)).collect::<Vec<_>>() {
+        universe.push(AssetInfo {
+            name: name.to_string(),
+            sz_decimals: rng.gen_range(0..6),
+            max_leverage: [1, 3, 5, 10, 20, 50][rng.gen_range(0..6)],
+            only_isolated: rng.gen_bool(0.1),
+            is_delisted: false,
+        });
+    }
+    Meta { universe, contexts: HashMap::new() }
+}
+
+fn fill(rng: &mut StdRng, coin: String, time: u64) -> Fill {
+    Fill {
+        coin,
+        px: amount(rng, -6.0, 6.0, 0),
+        sz: amount(rng, -4.0, 6.0, 20),
+        side: SIDES[rng.gen_range(0..SIDES.len())].to_string(),
+        time,
+        start_position: Decimal::ZERO,
+        dir: String::new(),
+        closed_pnl: Decimal::ZERO,
+        hash: String::new(),
+        oid: time,
+        crossed: rng.gen(),
+        fee: Decimal::ZERO,
+        builder_fee: Decimal::ZERO,
+        expected_fee: None,
+        liquidation: None,
+        users: Vec::new(),
+    }
+}
+
+/// Up to a few hundred fills, bunched into a few coins as a vault's are.
+fn fills(rng: &mut StdRng) -> Vec<Fill> {
+    let coins: Vec<String> = (0..rng.gen_range(1..4)).map(|_| coin(rng)).collect();
+    let count = if rng.gen_bool(0.1) { 0 } else { rng.gen_range(1..300) };
+    (0..count).map(|i| {
+        let coin = coins[rng.gen_range(0..coins.len())].clone();
+        fill(rng, coin, 1_700_000_000_000 + i)
+    }).collect()
+}
+
+/// Levels outward from `from`, each `tick` further along `direction`.
+fn levels(rng: &mut StdRng, from: Decimal, tick: Decimal, direction: Decimal) -> Vec<OrderBookLevel> {
+    let count = if rng.gen_bool(0.15) { 0 } else { rng.gen_range(1..20) };
+    (0..count)
+        .map(|i| OrderBookLevel {
+            px: (from + direction * tick * Decimal::from(i)).max(Decimal::new(1, 8)),
+            sz: amount(rng, -3.0, 5.0, 10),
+            n: rng.gen_range(0..30),
+        })
+        .collect()
+}
+
+/// A book around a price of any magnitude: usually a normal spread, sometimes
+/// locked or crossed, sometimes with a side missing.
+fn book(rng: &mut StdRng, coin: String) -> L2Snapshot {
+    let mid = amount(rng, -6.0, 6.0, 0);
+    let tick = (mid * Decimal::from_f64(10f64.powf(rng.gen_range(-5.0..-2.0))).unwrap_or(Decimal::ZERO)).round_dp(8).max(Decimal::new(1, 8));
+    let half_spread = match rng.gen_range(0..10) {
+        0 => Decimal::ZERO,
+        1 => -tick,
+        _ => tick * Decimal::from(rng.gen_range(1..20)),
+    };
+    let (best_bid, best_ask) = ((mid - half_spread).max(Decimal::new(1, 8)), mid + half_spread);
+    L2Snapshot {
+        coin,
+        time: 1_700_000_000_000,
+        bids: levels(rng, best_bid, tick, -Decimal::ONE),
+        asks: levels(rng, best_ask, tick, Decimal::ONE),
+    }
+}
+
+fn books(rng: &mut StdRng) -> HashMap<String, L2Snapshot> {
+    let listed: Vec<&str> = COINS.iter().copied().filter(|_| rng.gen_bool(0.6)).collect();
+    listed.into_iter().map(|coin| (coin.to_string(), book(rng, coin.to_string()))).collect()
+}
+
+/// The figures the phantom liquidity index weighs, in their own ranges and a
+/// little beyond, as a streaming engine that counts spoofing events reports.
+fn phantom_inputs(rng: &mut StdRng) -> LiquidityMetrics {
+    let ratio = |rng: &mut StdRng| if rng.gen_bool(0.1) { [0.0, 1.0][rng.gen_range(0..2)] } else { rng.gen_range(0.0..=1.0) };
+    let distances = ["1bps", "5bps", "10bps", "25bps", "50bps"];
+    LiquidityMetrics {
+        fleeting_order_ratio: ratio(rng),
+        layering_detection_score: ratio(rng),
+        spoofing_detection_index: if rng.gen_bool(0.5) { ratio(rng) } else { rng.gen_range(0..200) as f64 },
+        liquidity_realization_rate: ratio(rng),
+        fill_probability_by_distance: distances
+            .iter()
+            .filter(|_| rng.gen_bool(0.7))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|distance| (distance.to_string(), ratio(rng)))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn vault(rng: &mut StdRng) -> VaultSummary {
+    let tvl = amount(rng, 0.0, 9.0, 10);
+    let equity = match rng.gen_range(0..4) {
+        0 => Decimal::ZERO,
+        1 => -amount(rng, 0.0, 9.0, 0),
+        _ => tvl * Decimal::from_f64(rng.gen_range(0.0..2.0)).unwrap_or(Decimal::ONE),
+    };
+    VaultSummary {
+        vault_address: "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303".to_string(),
+        name: Some("Invariants".to_string()),
+        tvl,
+        equity,
+        apr: 0.0,
+        all_time_pnl: Decimal::ZERO,
+        max_drawdown: rng.gen_range(-0.5..2.0),
+        share_price: rng.gen_range(0.0..3.0),
+        num_depositors: 0,
+        portfolio_value: equity,
+        deployed_liquidity: Decimal::ZERO,
+        idle_liquidity: Decimal::ZERO,
+        flows: Vec::new(),
+        vaults: Vec::new(),
+        components: rng.gen_bool(0.5),
+    }
+}
+
+/// A position in a few of `COINS`, long or short, some of them worth nothing.
+fn positions(rng: &mut StdRng) -> Vec<Position> {
+    COINS
+        .iter()
+        .filter(|_| rng.gen_bool(0.3))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|coin| Position {
+            symbol: coin.to_string(),
+            size: if rng.gen_bool(0.5) { amount(rng, -4.0, 6.0, 20) } else { -amount(rng, -4.0, 6.0, 20) },
+            entry_px: None,
+            position_value: amount(rng, -2.0, 9.0, 10),
+            unrealized_pnl: Decimal::ZERO,
+            margin_used: Decimal::ZERO,
+            isolated: rng.gen_bool(0.2),
+            leverage: None,
+            liquidation_px: None,
+        })
+        .collect()
+}
+
+/// USDC and a few tokens, some without a USDC market and so worth nothing.
+fn spot_balances(rng: &mut StdRng) -> Vec<SpotBalance> {
+    ["USDC", "HYPE", "PURR", "BTC"]
+        .iter()
+        .filter(|_| rng.gen_bool(0.5))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|coin| {
+            let total = amount(rng, -2.0, 7.0, 10);
+            SpotBalance { coin: coin.to_string(), total, hold: Decimal::ZERO, value: amount(rng, -2.0, 7.0, 5) }
+        })
+        .collect()
+}
+
+#[test]
+fn vpin_is_in_unit_interval() {
+    check("vpin_is_in_unit_interval", |rng| {
+        let (fills, meta) = (fills(rng), meta(rng));
+        in_unit_interval("VPIN", risk::calculate_vpin(&fills, &meta))?;
+        for (coin, vpin) in risk::calculate_vpin_by_asset(&fills) {
+            in_unit_interval(&format!("{} VPIN", coin), vpin)?;
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn phantom_liquidity_is_in_unit_interval() {
+    check("phantom_liquidity_is_in_unit_interval", |rng| {
+        let inputs = phantom_inputs(rng);
+        in_unit_interval(&format!("PLI of {:?}", inputs), risk::calculate_phantom_liquidity_index(&inputs))?;
+        let computed = calculate_liquidity_metrics(&books(rng), &fills(rng), &meta(rng));
+        in_unit_interval("PLI of the computed liquidity metrics", risk::calculate_phantom_liquidity_index(&computed))
+    });
+}
+
+#[test]
+fn depth_is_non_negative_and_grows_with_distance() {
+    check("depth_is_non_negative_and_grows_with_distance", |rng| {
+        let coin = coin(rng);
+        let book = book(rng, coin);
+        let mut previous = Decimal::ZERO;
+        for bps in [0.0, 1.0, 10.0, 50.0, 250.0, 20_000.0] {
+            let depth = calculate_depth_at_bps(&book, bps);
+            ensure(depth >= previous, || format!("depth at {}bps is {}, below {} nearer the mid in {:?}", bps, depth, previous, book))?;
+            previous = depth;
+        }
+        let total = book.bids.iter().chain(&book.asks).map(|level| level.sz).sum::<Decimal>();
+        ensure(previous <= total, || format!("depth {} exceeds the {} resting in {:?}", previous, total, book))
+    });
+}
+
+#[test]
+fn book_metrics_agree_with_the_book() {
+    check("book_metrics_agree_with_the_book", |rng| {
+        let (books, meta) = (books(rng), meta(rng));
+        let metrics = calculate_liquidity_metrics(&books, &[], &meta);
+        for (coin, spread_bps) in &metrics.bid_ask_spread_bps {
+            let book = &books[coin];
+            let (bid, ask) = (book.bids[0].px.to_f64().unwrap_or(0.0), book.asks[0].px.to_f64().unwrap_or(0.0));
+            let implied = spread_bps / 10_000.0 * (bid + ask) / 2.0;
+            ensure((implied - (ask - bid)).abs() <= 1e-6 * ask.max(bid), || {
+                format!("{} spread of {}bps implies {}, the book's is {} - {}", coin, spread_bps, implied, ask, bid)
+            })?;
+            ensure(bid > ask || (0.0..20_000.0).contains(spread_bps), || format!("{} uncrossed spread is {}bps", coin, spread_bps))?;
+            ensure(metrics.depth_at_50bps[coin] >= Decimal::ZERO, || format!("{} depth is {}", coin, metrics.depth_at_50bps[coin]))?;
+            let imbalance = metrics.order_book_imbalance[coin];
+            ensure((-1.0..=1.0).contains(&imbalance), || format!("{} imbalance is {}", coin, imbalance))?;
+        }
+        for (coin, book) in &books {
+            let quoted = !book.bids.is_empty() && !book.asks.is_empty();
+            let listed = meta.universe.iter().any(|asset| asset.name == *coin && !asset.only_isolated && asset.max_leverage > 1);
+            let reported = metrics.bid_ask_spread_bps.contains_key(coin);
+            ensure(reported == (quoted && listed), || {
+                format!("{} spread reported: {}, with both sides quoted: {}, listed: {}", coin, reported, quoted, listed)
+            })?;
+            let imbalance = calculate_order_book_imbalance(book);
+            ensure((-1.0..=1.0).contains(&imbalance), || format!("{} imbalance is {} for {:?}", coin, imbalance, book))?;
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn concentrations_are_shares_of_a_whole() {
+    check("concentrations_are_shares_of_a_whole", |rng| {
+        let (positions, balances) = (positions(rng), spot_balances(rng));
+        let concentration = risk::calculate_position_concentration(&positions, &balances);
+        shares_of_a_whole("concentration", &concentration)?;
+        let held: HashSet<&str> = positions.iter().map(|p| p.symbol.as_str()).chain(balances.iter().filter(|b| !b.is_quote()).map(|b| b.coin.as_str())).collect();
+        ensure(concentration.keys().all(|coin| held.contains(coin.as_str())), || format!("concentration in an asset not held: {:?}", concentration))?;
+
+        let (fills, meta) = (fills(rng), meta(rng));
+        let turnover = risk::calculate_turnover_concentration(&fills, &meta);
+        shares_of_a_whole("turnover", &turnover)?;
+        ensure(turnover.keys().all(|coin| meta.universe.iter().any(|asset| asset.name == *coin)), || format!("turnover in an unlisted asset: {:?}", turnover))
+    });
+}
+
+fn shares_of_a_whole(what: &str, shares: &HashMap<String, f64>) -> Result<(), String> {
+    for (coin, share) in shares {
+        in_unit_interval(&format!("{} {}", coin, what), *share)?;
+    }
+    let total: f64 = shares.values().sum();
+    ensure(total <= 1.0 + EPSILON, || format!("{} shares sum to {}: {:?}", what, total, shares))
+}
+
+#[test]
+fn liquidation_and_cascade_risk_are_in_unit_interval() {
+    check("liquidation_and_cascade_risk_are_in_unit_interval", |rng| {
+        let vault = vault(rng);
+        in_unit_interval(&format!("liquidation risk of TVL {} and equity {}", vault.tvl, vault.equity), risk::calculate_liquidation_risk(&vault))?;
+        in_unit_interval("cascade risk", risk::calculate_cascade_risk(&fills(rng), &meta(rng)))
+    });
+}
+
+#[test]
+fn bucketed_drawdown_keeps_the_deepest_fall() {
+    check("bucketed_drawdown_keeps_the_deepest_fall", |rng| {
+        let samples = rng.gen_range(1..2_000);
+        let mut equity = amount(rng, 3.0, 9.0, 0).to_f64().unwrap_or(1.0);
+        let walk: Vec<(i64, f64)> = (0..samples)
+            .map(|i| {
+                equity = (equity * (1.0 + rng.gen_range(-0.05..0.05))).max(0.0);
+                (i as i64 * 1_000, equity)
+            })
+            .collect();
+        let end_ms = walk.last().map_or(0, |(ts, _)| *ts);
+        let mut history = MetricHistory::new();
+        history.restore(HistoryMetric::Equity, walk);
+
+        let deepest = |points: Vec<(i64, f64)>| points.iter().map(|(_, dd)| *dd).fold(0.0, f64::min);
+        let raw = history.drawdown(HistoryMetric::Equity, 0, end_ms, 0);
+        let max_points = rng.gen_range(1..200);
+        let bucketed = history.drawdown(HistoryMetric::Equity, 0, end_ms, max_points);
+        ensure(bucketed.len() <= max_points + 1, || format!("{} points for at most {} buckets", bucketed.len(), max_points))?;
+        ensure(bucketed.last() == raw.last(), || format!("ends at {:?}, the latest sample is {:?}", bucketed.last(), raw.last()))?;
+        ensure(deepest(bucketed.clone()) == deepest(raw.clone()), || format!("deepest of {} buckets is {}, of the samples {}", max_points, deepest(bucketed), deepest(raw)))
+    });
+}
@@ -0,0 +1,45 @@
+//! The `[ssh]` users' keys: `authorized_keys` lines of the supported types parse
+//! to their wire form, and the rest, along with a server left without users, is
+//! reported by the config check before the server starts.
+
+use hlp_toshogu::config::{config_problems, Config};
+use hlp_toshogu::ssh::parse_public_key;
+
+const ED25519: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIErK+5VxZMRXSckQmRnx15dTln7C4iOd5/QbebKLYzNm alice@laptop";
+const RSA_2048: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQDATWkVp+57gXon2kPv0EuFdo1zUpe5uTATpsHmllBdk4jD+V5ZimtjY56esmam6X199TgCKObER3XVtDHkwprni8psiZfFyoCpmwZNi7wxox3VYxJQtrssrKd8QJVnJB/55XD5wYTxrLAU5G+rXQlprhALicb2w2gZS7/49AL+wDeiYn7+nqNEchp8UHe+xacdjdVRulSbuSZKDgLgEvRiWSffaXBdk0RlTM/mdOkppxnReX/dKNafV5Se/8lxjzS8f8ihMcStRJXAx9RVUtiZYF082WSVSjtZq3EmEflQaFRDB1DLkl3boSQATGW2G0yxxVMs9VKB3lm+vq5u7A1d bob@desk";
+const RSA_1024: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAAgQDNot6fBywHq7iByGDxbjQTEIxb8SYr7r1xtN0PQplr3WZAMzaxHaeSEq4YghJiMeX+iGc80Zdrvb69xr5o8lW5yTGBRVlTSwZjuQgBAZyHOCB+kqgLSwW9HqrnuVDkkG3K0lJHVZIwUZOKi/6c+dudbxEbxxDvAGqI7yPnYCGQkQ== old@host";
+
+#[test]
+fn supported_keys_parse() {
+    let ed25519 = parse_public_key(ED25519).unwrap();
+    assert_eq!(ed25519.len(), 4 + 11 + 4 + 32);
+    assert_eq!(&ed25519[4..15], b"ssh-ed25519");
+    assert!(parse_public_key(RSA_2048).is_ok());
+    // The comment is optional.
+    assert_eq!(parse_public_key(&ED25519[..ED25519.rfind(' ').unwrap()]).unwrap(), ed25519);
+}
+
+#[test]
+fn unsupported_keys_are_rejected() {
+    assert!(parse_public_key(RSA_1024).is_err());
+    assert!(parse_public_key("ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAI carol@phone").is_err());
+    assert!(parse_public_key("ssh-ed25519 not-base64!").is_err());
+    assert!(parse_public_key("ssh-ed25519").is_err());
+    // An RSA key's data under the Ed25519 label.
+    let relabeled = RSA_2048.replacen("ssh-rsa", "ssh-ed25519", 1);
+    assert!(parse_public_key(&relabeled).is_err());
+}
+
+#[test]
+fn config_check_covers_ssh_users() {
+    let mut config = Config::default();
+    config.ssh.enabled = true;
+    assert!(config_problems(&config).iter().any(|problem| problem.starts_with("ssh.users")));
+
+    config.ssh.users.insert("alice".to_string(), vec![ED25519.to_string()]);
+    assert!(!config_problems(&config).iter().any(|problem| problem.starts_with("ssh.")));
+
+    config.ssh.users.insert("old".to_string(), vec![RSA_1024.to_string()]);
+    let problems = config_problems(&config);
+    assert!(problems.iter().any(|problem| problem.starts_with("ssh.users.old:")), "{:?}", problems);
+}
@@ -0,0 +1,64 @@
+//! The dashboard's state file: what `UIState::save` writes, `UIState::restore`
+//! reads back into a fresh dashboard, and a file from an older version, which is
+//! missing the newer fields, still restores.
+
+use hlp_toshogu::ui::ui::{UIState, CHARTS_TAB};
+use log::LevelFilter;
+use std::path::PathBuf;
+
+fn state_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("hlp-toshogu-{}-{}.json", name, std::process::id()))
+}
+
+#[test]
+fn saved_state_restores() {
+    let mut state = UIState::new();
+    state.select_tab(CHARTS_TAB);
+    state.chart.metric_index = 2;
+    state.chart.range_index = 1;
+    state.chart.zoom = 3;
+    state.chart.asset = Some("ETH".to_string());
+    state.ladder.asset = Some("SOL".to_string());
+    state.log_filters.levels.insert("hlp_toshogu::api".to_string(), LevelFilter::Warn);
+    state.log_filters.levels.insert("hlp_toshogu::alert".to_string(), LevelFilter::Off);
+    state.log_filters.hide_off = true;
+
+    let path = state_file("saved");
+    state.save(path.to_str().unwrap()).unwrap();
+    let mut restored = UIState::new();
+    restored.restore(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(restored.selected_tab, CHARTS_TAB);
+    assert_eq!(restored.chart.metric_index, 2);
+    assert_eq!(restored.chart.range_index, 1);
+    assert_eq!(restored.chart.zoom, 3);
+    assert_eq!(restored.chart.asset.as_deref(), Some("ETH"));
+    assert_eq!(restored.ladder.asset.as_deref(), Some("SOL"));
+    assert_eq!(restored.log_filters, state.log_filters);
+    assert_eq!(restored.log_filters.level("hlp_toshogu::api"), LevelFilter::Warn);
+    assert_eq!(restored.log_filters.level("hlp_toshogu::metrics"), LevelFilter::Info);
+}
+
+#[test]
+fn older_state_file_restores() {
+    let path = state_file("older");
+    std::fs::write(&path, r#"{"selected_tab": 2, "chart_metric": 1, "chart_range": 0, "chart_zoom": 1}"#).unwrap();
+    let mut restored = UIState::new();
+    restored.restore(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(restored.selected_tab, 2);
+    assert_eq!(restored.chart.metric_index, 1);
+    assert_eq!(restored.chart.asset, None);
+    assert_eq!(restored.ladder.asset, None);
+    assert_eq!(restored.log_filters, UIState::new().log_filters);
+}
+
+#[test]
+fn missing_state_file_is_not_an_error() {
+    let path = state_file("missing");
+    let mut restored = UIState::new();
+    restored.restore(path.to_str().unwrap()).unwrap();
+    assert_eq!(restored.selected_tab, 0);
+}